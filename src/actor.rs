@@ -1,9 +1,10 @@
 use crate::{
     bot::Bot,
     player::Player,
-    character::Character,
+    character::{Character, Team},
     level::UpdateContext,
     message::Message,
+    REFERENCE_FPS,
 };
 use rg3d::{
     core::{
@@ -23,7 +24,13 @@ use rg3d::{
         math::vec3::Vec3,
     },
 };
-use rg3d::scene::Scene;
+use rg3d::physics::Physics;
+use rg3d::scene::{
+    Scene,
+    graph::Graph,
+    node::Node,
+    light::LightKind,
+};
 use std::ops::{Deref, DerefMut};
 
 #[allow(clippy::large_enum_variant)]
@@ -120,6 +127,40 @@ pub struct TargetDescriptor {
     pub ptr: *const Actor,
     pub health: f32,
     pub position: Vec3,
+    /// Velocity of target in meters per second.
+    pub velocity: Vec3,
+    pub is_bot: bool,
+    /// How well target is lit, in [0; 1] range.
+    pub illumination: f32,
+    pub team: Team,
+}
+
+/// Collects positions and radii of point and spot lights of the scene.
+fn collect_lights(graph: &Graph) -> Vec<(Vec3, f32)> {
+    graph.linear_iter()
+        .filter_map(|node| match node {
+            Node::Light(light) if light.visibility() => match light.kind() {
+                LightKind::Point(point) => Some((light.global_position(), point.radius())),
+                LightKind::Spot(spot) => Some((light.global_position(), spot.distance())),
+                // Directional light lits everything.
+                LightKind::Directional => Some((light.global_position(), std::f32::MAX)),
+            },
+            _ => None
+        })
+        .collect()
+}
+
+/// Calculates rough illumination at given point, shadows are not taken into account. If
+/// there are no lights at all, everything is considered lit, probably level uses only
+/// baked lighting.
+fn illumination(lights: &[(Vec3, f32)], point: Vec3) -> f32 {
+    if lights.is_empty() {
+        return 1.0;
+    }
+    lights.iter()
+        .map(|(position, radius)| (1.0 - position.distance(&point) / radius).max(0.0))
+        .sum::<f32>()
+        .min(1.0)
 }
 
 #[derive(Default)]
@@ -166,25 +207,55 @@ impl ActorContainer {
         self.pool.alive_count()
     }
 
+    /// Returns every alive actor whose position is inside of given sphere.
+    pub fn overlap_sphere(&self, physics: &Physics, center: Vec3, radius: f32) -> Vec<Handle<Actor>> {
+        self.pool
+            .pair_iter()
+            .filter(|(_, actor)| !actor.is_dead() && actor.position(physics).sqr_distance(&center) < radius * radius)
+            .map(|(handle, _)| handle)
+            .collect()
+    }
+
     pub fn update(&mut self, context: &mut UpdateContext) {
         self.target_descriptors.clear();
+        let lights = collect_lights(&context.scene.graph);
         for (handle, actor) in self.pool.pair_iter() {
+            let position = actor.position(&context.scene.physics);
+            // Body velocity is displacement per physics step.
+            let velocity = context.scene.physics
+                .borrow_body(actor.body)
+                .get_velocity()
+                .scale(1.0 / context.time.delta);
             self.target_descriptors.push(TargetDescriptor {
                 handle,
                 ptr: actor,
                 health: actor.health,
-                position: actor.position(&context.scene.physics),
+                position,
+                velocity,
+                illumination: illumination(&lights, position),
+                team: actor.team(),
+                is_bot: match actor {
+                    Actor::Bot(_) => true,
+                    Actor::Player(_) => false,
+                },
             });
         }
 
         for (handle, actor) in self.pool.pair_iter_mut() {
             let is_dead = actor.is_dead();
+            actor.update_spawn_protection(context.time.delta);
 
             match actor {
                 Actor::Bot(bot) => bot.update(handle, context, &self.target_descriptors),
-                Actor::Player(player) => player.update(context)
+                Actor::Player(player) => player.update(handle, context)
             }
-            if !is_dead {
+            // Player with manual pickup uses items instead, see `Level::find_usable`. Nothing
+            // is picked up while actors are held.
+            let manual_pickup = match actor {
+                Actor::Player(player) => player.is_manual_pickup(),
+                Actor::Bot(_) => false,
+            };
+            if !is_dead && !manual_pickup && !context.frozen {
                 for (item_handle, item) in context.items.pair_iter() {
                     let body = context.scene.physics.borrow_body(actor.get_body());
                     let distance = (context.scene.graph[item.get_pivot()].global_position() - body.get_position()).len();
@@ -211,7 +282,9 @@ impl ActorContainer {
                     }
                 }
                 if push {
-                    body.set_velocity(jump_pad.get_force());
+                    // Force is displacement per step at reference rate, so it is rescaled
+                    // to give the same jump with any tick rate.
+                    body.set_velocity(jump_pad.get_force().scale(context.time.delta * REFERENCE_FPS));
                 }
             }
 
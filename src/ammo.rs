@@ -0,0 +1,143 @@
+//! Ammo that is shared between weapons. Every weapon uses ammo of some type, rounds that are
+//! not in magazines are kept in ammo store of owner by type. Weapons of the same type draw
+//! from one reserve, and ammo pickups fill the reserve instead of a particular weapon.
+
+use rg3d::core::visitor::{Visit, Visitor, VisitResult};
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum AmmoType {
+    Rifle556,
+    Rifle762,
+    Plasma,
+    Rocket,
+}
+
+pub const AMMO_TYPES: [AmmoType; 4] = [AmmoType::Rifle556, AmmoType::Rifle762, AmmoType::Plasma, AmmoType::Rocket];
+
+impl AmmoType {
+    pub fn id(self) -> u32 {
+        match self {
+            AmmoType::Rifle556 => 0,
+            AmmoType::Rifle762 => 1,
+            AmmoType::Plasma => 2,
+            AmmoType::Rocket => 3,
+        }
+    }
+
+    /// Name of ammo type in definitions file.
+    pub fn name(self) -> &'static str {
+        match self {
+            AmmoType::Rifle556 => "556",
+            AmmoType::Rifle762 => "762",
+            AmmoType::Plasma => "plasma",
+            AmmoType::Rocket => "rocket",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self, String> {
+        AMMO_TYPES.iter()
+            .cloned()
+            .find(|ammo_type| ammo_type.name() == name)
+            .ok_or_else(|| format!("unknown ammo type {}, expected 556, 762, plasma or rocket", name))
+    }
+}
+
+/// Reserve of rounds of every ammo type, owned by a character and shared by its weapons.
+#[derive(Default, Clone, Debug)]
+pub struct AmmoStore {
+    reserve: [u32; 4],
+}
+
+impl AmmoStore {
+    pub fn get(&self, ammo_type: AmmoType) -> u32 {
+        self.reserve[ammo_type.id() as usize]
+    }
+
+    pub fn add(&mut self, ammo_type: AmmoType, amount: u32) {
+        let reserve = &mut self.reserve[ammo_type.id() as usize];
+        *reserve = reserve.saturating_add(amount);
+    }
+
+    /// Takes up to given amount of rounds of given type, returns amount that was taken.
+    pub fn take(&mut self, ammo_type: AmmoType, amount: u32) -> u32 {
+        let reserve = &mut self.reserve[ammo_type.id() as usize];
+        let taken = amount.min(*reserve);
+        *reserve -= taken;
+        taken
+    }
+
+    /// Adds rounds of ammo pickup if owner has a weapon of the same ammo type, otherwise
+    /// pickup is left on the ground. Returns true if pickup was taken.
+    pub fn pick_up(&mut self, ammo_type: AmmoType, amount: u32, weapon_ammo_types: &[AmmoType]) -> bool {
+        if weapon_ammo_types.contains(&ammo_type) {
+            self.add(ammo_type, amount);
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Visit for AmmoStore {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        for ammo_type in AMMO_TYPES.iter() {
+            self.reserve[ammo_type.id() as usize].visit(&format!("Reserve{}", ammo_type.id()), visitor)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapon::Weapon;
+    use std::{
+        rc::Rc,
+        cell::RefCell,
+    };
+
+    fn weapon_with_store(ammo_store: &Rc<RefCell<AmmoStore>>) -> Weapon {
+        let mut weapon = Weapon::default();
+        weapon.set_ammo_store(ammo_store.clone());
+        weapon
+    }
+
+    #[test]
+    fn weapons_of_one_ammo_type_share_reserve() {
+        let ammo_store = Rc::new(RefCell::new(AmmoStore::default()));
+        let mut first = weapon_with_store(&ammo_store);
+        let second = weapon_with_store(&ammo_store);
+        assert_eq!(first.ammo_type(), second.ammo_type());
+
+        // Rounds that don't fit into magazine of first weapon go to shared reserve.
+        first.load(first.definition.magazine_size + 40);
+        assert_eq!(first.reserve(), 40);
+        assert_eq!(second.reserve(), 40);
+
+        // Rounds taken for one weapon are gone for the other one.
+        assert_eq!(ammo_store.borrow_mut().take(second.ammo_type(), 30), 30);
+        assert_eq!(first.reserve(), 10);
+        assert_eq!(ammo_store.borrow_mut().take(first.ammo_type(), 30), 10);
+        assert_eq!(second.reserve(), 0);
+    }
+
+    #[test]
+    fn ammo_pickup_fills_shared_pool() {
+        let ammo_store = Rc::new(RefCell::new(AmmoStore::default()));
+        let first = weapon_with_store(&ammo_store);
+        let second = weapon_with_store(&ammo_store);
+        let weapon_ammo_types = [first.ammo_type(), second.ammo_type()];
+
+        assert!(ammo_store.borrow_mut().pick_up(first.ammo_type(), 60, &weapon_ammo_types));
+        assert_eq!(first.reserve(), 60);
+        assert_eq!(second.reserve(), 60);
+
+        // Pickup of ammo that no weapon of owner uses is left on the ground.
+        let other = AMMO_TYPES.iter().cloned().find(|ammo_type| *ammo_type != first.ammo_type()).unwrap();
+        assert!(!ammo_store.borrow_mut().pick_up(other, 60, &weapon_ammo_types));
+        assert_eq!(ammo_store.borrow().get(other), 0);
+    }
+}
@@ -0,0 +1,127 @@
+//! Benchmark mode is started with `--benchmark` command line argument. It plays a fixed
+//! deathmatch with aimbot bots for a fixed amount of frames, simulating exactly one tick per
+//! frame without frame rate limit, then prints frame times and exits. Results are written as
+//! JSON as well, so runs can be compared by scripts. Amount of bots is changed with
+//! `--benchmark-bots=N`, runs with different amounts show how frame time scales with bots.
+
+use crate::frame_profile::{FrameProfile, STAGE_COUNT, STAGE_NAMES};
+use std::{
+    fmt::Write,
+    fs,
+    time::Instant,
+};
+
+pub const BENCHMARK_ARG: &str = "--benchmark";
+/// Prefix of argument that sets amount of bots, for example `--benchmark-bots=12`.
+pub const BENCHMARK_BOTS_ARG: &str = "--benchmark-bots=";
+/// Bots of the scenario, besides the player who just stands and gets shot.
+pub const BENCHMARK_BOTS: u32 = 8;
+/// Shoot interval of aimbot bots in seconds, see `AimbotBehavior`.
+pub const BENCHMARK_AIMBOT_INTERVAL: f32 = 0.5;
+/// Frames that are not measured, they're slowed down by loading of assets on first use.
+const WARMUP_FRAMES: usize = 120;
+const MEASURED_FRAMES: usize = 1800;
+const RESULTS_PATH: &str = "benchmark.json";
+
+pub struct Benchmark {
+    bots: u32,
+    frames_left: usize,
+    last_frame: Option<Instant>,
+    /// Whole frame times in seconds.
+    frame_times: Vec<f64>,
+    /// Sum of time of every stage over measured frames in seconds.
+    stage_totals: [f64; STAGE_COUNT],
+    /// Amount of bots that rebuilt their paths on every measured frame.
+    path_rebuilds: Vec<u32>,
+}
+
+/// Returns value below which given part of sorted samples are.
+fn percentile(sorted: &[f64], part: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let index = ((sorted.len() - 1) as f64 * part).round() as usize;
+    sorted[index]
+}
+
+impl Benchmark {
+    pub fn new(bots: u32) -> Self {
+        Self {
+            bots,
+            frames_left: WARMUP_FRAMES + MEASURED_FRAMES,
+            last_frame: None,
+            frame_times: Vec::with_capacity(MEASURED_FRAMES),
+            stage_totals: [0.0; STAGE_COUNT],
+            path_rebuilds: Vec::with_capacity(MEASURED_FRAMES),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frames_left == 0
+    }
+
+    /// Records frame that was just finished, must be called once per frame before profile
+    /// finishes the frame.
+    pub fn record_frame(&mut self, profile: &FrameProfile, path_rebuilds: u32) {
+        let now = Instant::now();
+        if self.frames_left <= MEASURED_FRAMES {
+            self.path_rebuilds.push(path_rebuilds);
+            if let Some(last_frame) = self.last_frame {
+                self.frame_times.push((now - last_frame).as_secs_f64());
+            }
+            for (total, time) in self.stage_totals.iter_mut().zip(profile.current().iter()) {
+                *total += time;
+            }
+        }
+        self.last_frame = Some(now);
+        self.frames_left = self.frames_left.saturating_sub(1);
+    }
+
+    /// Prints results and writes them to JSON file next to the game.
+    pub fn report(&self, map: &str, tick_rate: u32) {
+        let mut sorted = self.frame_times.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let average = sorted.iter().sum::<f64>() / sorted.len().max(1) as f64;
+        let stats = [
+            ("avg", average),
+            ("p50", percentile(&sorted, 0.5)),
+            ("p95", percentile(&sorted, 0.95)),
+            ("p99", percentile(&sorted, 0.99)),
+            ("max", sorted.last().cloned().unwrap_or(0.0)),
+        ];
+        let frames = self.frame_times.len().max(1) as f64;
+
+        let path_rebuilds_avg = self.path_rebuilds.iter().sum::<u32>() as f64 / self.path_rebuilds.len().max(1) as f64;
+        let path_rebuilds_max = self.path_rebuilds.iter().max().cloned().unwrap_or(0);
+
+        let mut text = format!("Benchmark: {} bots on {}, {} frames at {} Hz\nFrame time:",
+                               self.bots, map, self.frame_times.len(), tick_rate);
+        for (name, time) in stats.iter() {
+            write!(text, "\n  {}: {:.2} ms", name, time * 1000.0).unwrap();
+        }
+        text.push_str("\nStages (avg):");
+        for (name, total) in STAGE_NAMES.iter().zip(self.stage_totals.iter()) {
+            write!(text, "\n  {}: {:.2} ms", name, total / frames * 1000.0).unwrap();
+        }
+        write!(text, "\nPath rebuilds per frame: avg {:.2}, max {}", path_rebuilds_avg, path_rebuilds_max).unwrap();
+        println!("{}", text);
+
+        let frame_time = stats
+            .iter()
+            .map(|(name, time)| format!("\"{}_ms\":{:.4}", name, time * 1000.0))
+            .collect::<Vec<_>>()
+            .join(",");
+        let stages = STAGE_NAMES
+            .iter()
+            .zip(self.stage_totals.iter())
+            .map(|(name, total)| format!("\"{}\":{:.4}", name, total / frames * 1000.0))
+            .collect::<Vec<_>>()
+            .join(",");
+        let json = format!("{{\"map\":\"{}\",\"bots\":{},\"frames\":{},\"tick_rate\":{},\"frame_time\":{{{}}},\"stages_avg_ms\":{{{}}},\"path_rebuilds\":{{\"avg\":{:.4},\"max\":{}}}}}\n",
+                           map, self.bots, self.frame_times.len(), tick_rate, frame_time, stages, path_rebuilds_avg, path_rebuilds_max);
+        match fs::write(RESULTS_PATH, json) {
+            Ok(_) => println!("Benchmark results written to {}", RESULTS_PATH),
+            Err(e) => println!("Unable to write benchmark results to {}. Reason: {}", RESULTS_PATH, e),
+        }
+    }
+}
@@ -3,8 +3,11 @@ use std::{
     sync::mpsc::Sender,
 };
 use crate::{
-    character::Character,
+    character::{Character, Team},
     level::UpdateContext,
+    smoke::SmokeContainer,
+    hazard,
+    debug_draw::{self, DebugDrawFlags},
     message::Message,
     actor::{
         Actor,
@@ -12,7 +15,16 @@ use crate::{
     },
     GameTime,
     item::ItemContainer,
-    weapon::WeaponContainer,
+    control_point::ControlPoint,
+    weapon::{WeaponContainer, WeaponDefinition, WeaponKind},
+    projectile::{Projectile, ProjectileKind, ProjectileDefinition},
+    bot_behavior::{BotBehavior, StandardBehavior},
+    squad::Squads,
+    damage::{DamageKind, DamageMultipliers},
+    DEFAULT_BOT_SIGHT_RANGE,
+    SoundPriority,
+    definitions,
+    settings::parse_f32,
 };
 use rg3d::{
     core::{
@@ -29,6 +41,7 @@ use rg3d::{
             mat4::Mat4,
             quat::Quat,
             frustum::Frustum,
+            aabb::AxisAlignedBoundingBox,
         },
         color::Color,
     },
@@ -55,7 +68,7 @@ use rg3d::{
         graph::Graph,
     },
     engine::resource_manager::ResourceManager,
-    renderer::debug_renderer::{self, DebugRenderer},
+    renderer::debug_renderer::DebugRenderer,
     animation::AnimationSignal,
     utils::navmesh::Navmesh,
 };
@@ -71,6 +84,9 @@ pub enum BotKind {
     // Humans
 }
 
+/// Every kind of bot.
+pub const BOT_KINDS: [BotKind; 3] = [BotKind::Mutant, BotKind::Parasite, BotKind::Maw];
+
 impl BotKind {
     pub fn from_id(id: i32) -> Result<Self, String> {
         match id {
@@ -90,15 +106,536 @@ impl BotKind {
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BotDifficulty {
+    Easy,
+    Normal,
+    Hard,
+}
+
+impl Default for BotDifficulty {
+    fn default() -> Self {
+        BotDifficulty::Normal
+    }
+}
+
+impl BotDifficulty {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(BotDifficulty::Easy),
+            1 => Ok(BotDifficulty::Normal),
+            2 => Ok(BotDifficulty::Hard),
+            _ => Err(format!("Invalid bot difficulty {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            BotDifficulty::Easy => 0,
+            BotDifficulty::Normal => 1,
+            BotDifficulty::Hard => 2,
+        }
+    }
+
+    /// Minimal time in seconds between two grenade throws.
+    fn grenade_cooldown(self) -> f64 {
+        match self {
+            BotDifficulty::Easy => 15.0,
+            BotDifficulty::Normal => 9.0,
+            BotDifficulty::Hard => 5.0,
+        }
+    }
+
+    /// Multiplier for sight range of bots.
+    fn sight_range_multiplier(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.7,
+            BotDifficulty::Normal => 1.0,
+            BotDifficulty::Hard => 1.3,
+        }
+    }
+
+    /// Full angle in degrees of cone in front of bot in which it can see targets, targets
+    /// behind bot are noticed only when they're close or heard.
+    fn view_cone_angle(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 90.0,
+            BotDifficulty::Normal => 120.0,
+            BotDifficulty::Hard => 160.0,
+        }
+    }
+
+    /// Highest speed at which bot turns to face its target or noise, in degrees per second.
+    /// Strafing target that is faster than that escapes aim of the bot.
+    pub fn turn_speed(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 180.0,
+            BotDifficulty::Normal => 260.0,
+            BotDifficulty::Hard => 380.0,
+        }
+    }
+
+    /// Time in seconds in which bot closes most of the angle to its aim target, bot turns fast
+    /// when the angle is large and slows down near the target instead of snapping to it.
+    pub fn aim_smoothing(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.3,
+            BotDifficulty::Normal => 0.18,
+            BotDifficulty::Hard => 0.1,
+        }
+    }
+
+    /// Largest angle in degrees between facing of bot and its target at which bot shoots.
+    fn aim_tolerance(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 8.0,
+            BotDifficulty::Normal => 5.0,
+            BotDifficulty::Hard => 3.0,
+        }
+    }
+
+    /// Time in seconds that bot needs to notice explosive near it.
+    fn danger_reaction_time(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.8,
+            BotDifficulty::Normal => 0.45,
+            BotDifficulty::Hard => 0.2,
+        }
+    }
+
+    /// Probability that bot will notice explosive near it at all.
+    fn danger_notice_chance(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.5,
+            BotDifficulty::Normal => 0.8,
+            BotDifficulty::Hard => 1.0,
+        }
+    }
+
+    /// How much of target movement bot takes into account when it shoots at moving target,
+    /// easy bots shoot right at target and hard bots lead it almost perfectly.
+    fn lead_factor(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.0,
+            BotDifficulty::Normal => 0.6,
+            BotDifficulty::Hard => 0.9,
+        }
+    }
+
+    /// How much bot cooperates with its squad, in [0; 1] range. Distance at which bot hears
+    /// callouts of teammates is scaled by it, bots with low coordination never hold
+    /// position to cover advancing teammate.
+    fn squad_coordination(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.25,
+            BotDifficulty::Normal => 0.6,
+            BotDifficulty::Hard => 1.0,
+        }
+    }
+
+    /// How often bot reconsiders which weapon suits distance to its target, in seconds.
+    fn weapon_think_interval(self) -> f64 {
+        match self {
+            BotDifficulty::Easy => 1.5,
+            BotDifficulty::Normal => 0.7,
+            BotDifficulty::Hard => 0.25,
+        }
+    }
+
+    /// Largest random error that is added to score of every weapon when bot chooses one,
+    /// weak bots often keep weapon that does not suit distance.
+    fn weapon_choice_noise(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.6,
+            BotDifficulty::Normal => 0.25,
+            BotDifficulty::Hard => 0.0,
+        }
+    }
+
+    /// Multiplier for distance at which bot hears footsteps.
+    fn hearing_multiplier(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 0.6,
+            BotDifficulty::Normal => 1.0,
+            BotDifficulty::Hard => 1.4,
+        }
+    }
+
+    /// Maximum distance between aim point of grenade and actual target.
+    fn grenade_spread(self) -> f32 {
+        match self {
+            BotDifficulty::Easy => 2.5,
+            BotDifficulty::Normal => 1.2,
+            BotDifficulty::Hard => 0.4,
+        }
+    }
+
+    /// Whether bot picks up live grenades that land next to it and throws them back.
+    pub fn throws_back_grenades(self) -> bool {
+        self == BotDifficulty::Hard
+    }
+
+    /// Damage multipliers that are suggested in match menu when difficulty is selected.
+    pub fn damage_multipliers(self) -> DamageMultipliers {
+        match self {
+            BotDifficulty::Easy => DamageMultipliers {
+                player_dealt: 1.25,
+                player_taken: 0.75,
+                bot_dealt: 1.0,
+            },
+            BotDifficulty::Normal => Default::default(),
+            BotDifficulty::Hard => DamageMultipliers {
+                player_dealt: 1.0,
+                player_taken: 1.25,
+                bot_dealt: 1.0,
+            },
+        }
+    }
+}
+
+impl Visit for BotDifficulty {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Set of weapons given to bots when they spawn.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum BotWeaponSet {
+    /// Every bot gets AK-47.
+    Standard,
+    /// Every bot gets M4 and AK-47.
+    Rifles,
+    /// Every bot gets plasma rifle.
+    Plasma,
+    /// Every bot gets rocket launcher.
+    Rockets,
+    /// Each bot gets one random weapon.
+    Random,
+}
+
+impl Default for BotWeaponSet {
+    fn default() -> Self {
+        BotWeaponSet::Standard
+    }
+}
+
+impl BotWeaponSet {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(BotWeaponSet::Standard),
+            1 => Ok(BotWeaponSet::Rifles),
+            2 => Ok(BotWeaponSet::Plasma),
+            3 => Ok(BotWeaponSet::Rockets),
+            4 => Ok(BotWeaponSet::Random),
+            _ => Err(format!("Invalid bot weapon set {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            BotWeaponSet::Standard => 0,
+            BotWeaponSet::Rifles => 1,
+            BotWeaponSet::Plasma => 2,
+            BotWeaponSet::Rockets => 3,
+            BotWeaponSet::Random => 4,
+        }
+    }
+
+    /// Returns weapons for a new bot, random set is rolled on each call.
+    pub fn pick_weapons(self) -> Vec<WeaponKind> {
+        match self {
+            BotWeaponSet::Standard => vec![WeaponKind::Ak47],
+            BotWeaponSet::Rifles => vec![WeaponKind::M4, WeaponKind::Ak47],
+            BotWeaponSet::Plasma => vec![WeaponKind::PlasmaRifle],
+            BotWeaponSet::Rockets => vec![WeaponKind::RocketLauncher],
+            BotWeaponSet::Random => {
+                let kinds = [WeaponKind::M4, WeaponKind::Ak47, WeaponKind::PlasmaRifle, WeaponKind::RocketLauncher];
+                vec![kinds[rand::thread_rng().gen_range(0, kinds.len())]]
+            }
+        }
+    }
+}
+
+impl Visit for BotWeaponSet {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Bots won't throw grenades closer than this to not blow up themselves.
+const GRENADE_MIN_DISTANCE: f32 = 4.0;
+const GRENADE_MAX_DISTANCE: f32 = 18.0;
+/// Targets within this radius from each other are considered clustered.
+const GRENADE_CLUSTER_RADIUS: f32 = 3.0;
+/// Time in seconds during which bot remembers target that went out of sight.
+const TARGET_MEMORY_TIME: f64 = 3.0;
+/// Bot that is losing a fight covers itself with smoke when its health drops below this.
+const SMOKE_COVER_HEALTH: f32 = 30.0;
+/// Chance that bot throws flashbang instead of frag grenade at target behind cover.
+const FLASHBANG_CHANCE: f64 = 0.4;
+/// Horizontal speed of thrown grenade in meters per second.
+pub const GRENADE_THROW_SPEED: f32 = 12.0;
+const GRAVITY: f32 = 9.81;
+/// Part of sight range that is left for targets in complete darkness.
+const DARK_SIGHT_FACTOR: f32 = 0.4;
+/// Targets closer than this are noticed even if they're outside of view cone.
+const AWARENESS_RADIUS: f32 = 2.0;
+/// Max distance at which bots hear shots and turn to them.
+pub const HEARING_RANGE: f32 = 25.0;
+/// Max distance at which bots hear footsteps of normal loudness, before difficulty is applied.
+const FOOTSTEP_HEARING_RANGE: f32 = 10.0;
+/// Loudness of footsteps of crouching, walking and running actor. Crouching is so quiet that
+/// it is heard only right behind bot.
+pub const FOOTSTEP_LOUDNESS: (f32, f32, f32) = (0.1, 1.0, 1.8);
+/// Minimum time in seconds between two voice lines of same bot.
+const VOICE_COOLDOWN: f32 = 5.0;
+/// How many times per second spawn protected bot toggles its visibility, twice per blink.
+const SPAWN_PROTECTION_FLICKER_RATE: f32 = 12.0;
+/// Vertical speed of jump, in meters per second.
+const JUMP_SPEED: f32 = 4.8;
+/// Bots run from explosives if they are closer than blast radius multiplied by this value.
+const DANGER_RADIUS_MARGIN: f32 = 1.25;
+const FLEE_SPEED_MULTIPLIER: f32 = 1.6;
+/// Amount of sound files for each voice event in a voice pack.
+const VOICE_VARIANTS: usize = 3;
+/// Folder of voice packs, each pack is a folder with `<event>_<variant>.ogg` files.
+const VOICES_PATH: &str = "data/sounds/voices";
+/// Time in seconds during which bot must move at least minimal distance to not be stuck.
+const STUCK_CHECK_WINDOW: f32 = 1.0;
+/// Horizontal distance in meters that bot must pass during check window.
+const STUCK_MIN_DISTANCE: f32 = 0.5;
+/// Time in seconds during which stuck bot backs up from an obstacle.
+const UNSTICK_DURATION: f32 = 0.6;
+/// Minimal time in seconds between two weapon switches of a bot, so bot that fights at the
+/// edge of ranges of two weapons does not swap them back and forth.
+const WEAPON_SWITCH_INTERVAL: f64 = 2.0;
+/// Bonus to score of current weapon, other weapon must be this much better to be worth a switch.
+const WEAPON_SWITCH_MARGIN: f32 = 0.2;
+/// Bots keep explosive weapons for targets that are farther than blast radius multiplied by this.
+const BLAST_SAFETY_FACTOR: f32 = 1.5;
+/// Minimal time in seconds between two path rebuilds of a bot.
+const PATH_REBUILD_INTERVAL: f64 = 1.0;
+/// Path is rebuilt only when point of interest moves further than this from the point that
+/// path leads to, otherwise bot keeps following its path.
+const PATH_TARGET_TOLERANCE: f32 = 1.5;
+/// Max distance between bot and teammate at which bot hears callouts of the teammate,
+/// before squad coordination is applied.
+const CALLOUT_RADIUS: f32 = 30.0;
+/// Time in seconds between two times bot looks at squad blackboard to pick where to go.
+/// It is less than interval of picking items, so squad orders are not overridden by items.
+const SQUAD_THINK_INTERVAL: f64 = 1.0;
+/// Distance in meters between lanes of bots that go after the same enemy.
+const LANE_SPACING: f32 = 4.0;
+/// Bots with lower squad coordination never hold position to cover teammates.
+const COVER_MIN_COORDINATION: f32 = 0.5;
+
+/// Watches progress of bot while it tries to move. Bot that barely moved during whole check
+/// window is wedged against geometry, it backs up and turns a bit then.
+#[derive(Default)]
+struct StuckDetector {
+    /// Position of bot at the beginning of current check window.
+    window_start: Vec3,
+    window_time: f32,
+    /// Time left until unstick maneuver ends, zero if bot is not unsticking.
+    unstick_time_left: f32,
+    unstick_direction: Vec3,
+}
+
+impl StuckDetector {
+    fn reset(&mut self, position: Vec3) {
+        self.window_start = position;
+        self.window_time = 0.0;
+    }
+
+    /// Direction in which bot must move to get unstuck, None if there is no unstick maneuver.
+    fn unstick_direction(&self) -> Option<Vec3> {
+        if self.unstick_time_left > 0.0 {
+            Some(self.unstick_direction)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if bot got stuck and unstick maneuver has started.
+    fn update(&mut self, position: Vec3, move_dir: Vec3, trying_to_move: bool, dt: f32) -> bool {
+        if self.unstick_time_left > 0.0 {
+            self.unstick_time_left -= dt;
+            self.reset(position);
+            return false;
+        }
+
+        if !trying_to_move {
+            self.reset(position);
+            return false;
+        }
+
+        self.window_time += dt;
+        if self.window_time < STUCK_CHECK_WINDOW {
+            return false;
+        }
+
+        let mut moved = position - self.window_start;
+        moved.y = 0.0;
+        self.reset(position);
+        if moved.len() >= STUCK_MIN_DISTANCE {
+            return false;
+        }
+
+        // Back up from obstacle at random angle, so bot won't walk into the same spot again.
+        let back = Vec3::new(-move_dir.x, 0.0, -move_dir.z);
+        let side = Vec3::new(back.z, 0.0, -back.x);
+        let turn = rand::thread_rng().gen_range(-1.0, 1.0);
+        self.unstick_direction = (back + side.scale(turn)).normalized()
+            .or_else(|| back.normalized())
+            .unwrap_or_else(|| Vec3::new(1.0, 0.0, 0.0));
+        self.unstick_time_left = UNSTICK_DURATION;
+        true
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum VoiceEvent {
+    Spawn,
+    Kill,
+    Death,
+}
+
+impl VoiceEvent {
+    fn name(self) -> &'static str {
+        match self {
+            VoiceEvent::Spawn => "spawn",
+            VoiceEvent::Kill => "kill",
+            VoiceEvent::Death => "death",
+        }
+    }
+
+    /// Chance of voice line for the event, lines on every event would be annoying.
+    fn chance(self) -> f32 {
+        match self {
+            VoiceEvent::Spawn => 0.4,
+            VoiceEvent::Kill => 0.6,
+            VoiceEvent::Death => 1.0,
+        }
+    }
+}
+
+/// Computes velocity (in meters per physics step) that will bring grenade from one point to
+/// another by ballistic arc in given time.
+pub fn throw_velocity(from: Vec3, to: Vec3, flight_time: f32, dt: f32) -> Vec3 {
+    let d = to - from;
+    Vec3::new(
+        d.x / flight_time,
+        d.y / flight_time + 0.5 * GRAVITY * flight_time,
+        d.z / flight_time,
+    ).scale(dt)
+}
+
+/// How well weapon suits a fight at given distance, larger is better. Weapon scores one at its
+/// preferred range and less farther from it, targets out of its range score below zero and
+/// targets so close that explosion would hurt the bot itself score lowest.
+fn weapon_score(definition: &WeaponDefinition, distance: f32) -> f32 {
+    let blast_radius = Projectile::get_definition(definition.projectile).explosion_radius();
+    if distance < blast_radius * BLAST_SAFETY_FACTOR {
+        -2.0
+    } else if distance > definition.bot_range {
+        -1.0
+    } else {
+        1.0 - (distance - definition.bot_preferred_range).abs() / definition.bot_range
+    }
+}
+
+/// Returns index of weapon that suits a fight at given distance best, candidates are
+/// definitions of weapons with flag whether weapon has ammo. Weapons without ammo are skipped
+/// and `current` weapon gets `WEAPON_SWITCH_MARGIN`, so bot doesn't switch back and forth
+/// between weapons that score alike. `noise` is added to score of every weapon, easier bots
+/// pick worse weapons with it. Without distance any weapon with ammo will do.
+fn best_weapon(candidates: &[(&WeaponDefinition, bool)], current: Option<usize>, distance: Option<f32>, mut noise: impl FnMut() -> f32) -> Option<usize> {
+    let mut best: Option<(usize, f32)> = None;
+    for (i, &(definition, has_ammo)) in candidates.iter().enumerate() {
+        if !has_ammo {
+            continue;
+        }
+        let mut score = distance.map_or(0.0, |distance| weapon_score(definition, distance)) + noise();
+        if current == Some(i) {
+            score += WEAPON_SWITCH_MARGIN;
+        }
+        if best.map_or(true, |(_, best_score)| score > best_score) {
+            best = Some((i, score));
+        }
+    }
+    best.map(|(i, _)| i)
+}
+
+/// Shortest signed angle that turns `from` to `to`, in [-pi; pi] range.
+fn angle_difference(from: f32, to: f32) -> f32 {
+    let d = to - from;
+    d.sin().atan2(d.cos())
+}
+
+/// Turns angle towards its target with exponential easing, so turn slows down near the
+/// target, and clamps step by speed of the angle. `smoothing` is a time constant in seconds.
+fn turn_smoothly(angle: &mut SmoothAngle, target: f32, smoothing: f32, dt: f32) {
+    angle.target = target;
+    let difference = angle_difference(angle.angle, target);
+    let eased = difference * (1.0 - (-dt / smoothing.max(std::f32::EPSILON)).exp());
+    let max_step = angle.speed * dt;
+    let step = eased.max(-max_step).min(max_step);
+    angle.angle = angle_difference(0.0, angle.angle + step);
+}
+
+/// Returns point at which bot should shoot to hit target that moves with constant velocity
+/// by projectile of given definition. `lead` in [0; 1] range tells how much of target
+/// movement is taken into account, zero means shooting right at the target. Drop of the
+/// projectile is always compensated by aiming higher.
+fn lead_target(from: Vec3, target: Vec3, target_velocity: Vec3, projectile: &ProjectileDefinition, lead: f32) -> Vec3 {
+    let mut aim_point = target;
+    let mut drop = 0.0;
+    // Distance changes while projectile flies, second iteration takes it into account
+    // and that is precise enough for speeds of actors.
+    for _ in 0..2 {
+        match projectile.flight_time(from.distance(&aim_point)) {
+            Some(flight_time) => {
+                aim_point = target + target_velocity.scale(flight_time * lead);
+                drop = projectile.drop(flight_time);
+            }
+            // Target is out of reach, there is nothing to compensate.
+            None => return target,
+        }
+    }
+    aim_point.y += drop;
+    aim_point
+}
+
 pub struct Target {
-    position: Vec3,
-    handle: Handle<Actor>,
+    pub position: Vec3,
+    /// Velocity of target in meters per second, it is not saved because target is selected
+    /// again on each update.
+    pub velocity: Vec3,
+    pub handle: Handle<Actor>,
 }
 
 impl Default for Target {
     fn default() -> Self {
         Self {
             position: Default::default(),
+            velocity: Default::default(),
             handle: Default::default(),
         }
     }
@@ -133,10 +670,47 @@ pub struct Bot {
     last_poi_update_time: f64,
     point_of_interest: Vec3,
     last_path_rebuild_time: f64,
+    /// Point of interest at the moment when path was built.
+    path_target: Vec3,
+    /// Path must be rebuilt even if point of interest did not move, set when bot was stuck.
+    path_outdated: bool,
+    /// Level allowed bot to rebuild its path on this frame, see [`Bot::wants_path_rebuild`].
+    path_rebuild_allowed: bool,
     last_move_dir: Vec3,
     spine: Handle<Node>,
     yaw: SmoothAngle,
     pitch: SmoothAngle,
+    color: Color,
+    difficulty: BotDifficulty,
+    /// If false, bot ignores other bots and attacks only player.
+    fights_other_bots: bool,
+    /// Last target that bot saw, it is used to throw grenades at targets behind cover.
+    last_seen_target: Handle<Actor>,
+    last_target_seen_time: f64,
+    last_grenade_time: f64,
+    last_weapon_think_time: f64,
+    last_weapon_switch_time: f64,
+    /// Time left until bot can say next voice line.
+    voice_cooldown: f32,
+    /// Max distance at which bot can see targets in lit areas, before difficulty is applied.
+    sight_range: f32,
+    /// Decision logic, it is taken out of bot while its hooks are called so they can
+    /// borrow bot mutably.
+    behavior: Option<Box<dyn BotBehavior>>,
+    /// How long bot stays within blast radius of explosive.
+    danger_time: f32,
+    /// Whether bot noticed explosive it stays near.
+    danger_noticed: bool,
+    /// Direction in which bot runs from explosive, None if there is no danger.
+    flee_direction: Option<Vec3>,
+    /// Use voice lines without harsh language.
+    clean_language: bool,
+    stuck_detector: StuckDetector,
+    /// Bot was torn into gibs, its model is hidden and dying animation is skipped.
+    gibbed: bool,
+    last_squad_think_time: f64,
+    /// Time left until bot recovers from flashbang, blinded bot can't see targets.
+    blind_time: f32,
 }
 
 impl Deref for Bot {
@@ -173,6 +747,9 @@ impl Default for Bot {
             last_poi_update_time: -10.0,
             point_of_interest: Default::default(),
             last_path_rebuild_time: -10.0,
+            path_target: Default::default(),
+            path_outdated: true,
+            path_rebuild_allowed: false,
             last_move_dir: Default::default(),
             spine: Default::default(),
             yaw: SmoothAngle {
@@ -185,10 +762,42 @@ impl Default for Bot {
                 target: 0.0,
                 speed: 260.0f32.to_radians(), // rad/s
             },
+            color: Color::WHITE,
+            difficulty: Default::default(),
+            fights_other_bots: true,
+            last_seen_target: Default::default(),
+            last_target_seen_time: -10.0,
+            last_grenade_time: 0.0,
+            last_weapon_think_time: -10.0,
+            last_weapon_switch_time: -10.0,
+            voice_cooldown: 0.0,
+            sight_range: DEFAULT_BOT_SIGHT_RANGE,
+            behavior: Some(Box::new(StandardBehavior)),
+            danger_time: 0.0,
+            danger_noticed: false,
+            flee_direction: None,
+            clean_language: false,
+            stuck_detector: Default::default(),
+            gibbed: false,
+            last_squad_think_time: -10.0,
+            blind_time: 0.0,
         }
     }
 }
 
+/// Names that are given to bots spawned without explicit name.
+pub const BOT_NAMES: [&str; 12] = [
+    "Ripper", "Surge", "Vortex", "Grim", "Hex", "Bolt",
+    "Razor", "Cinder", "Mangle", "Shade", "Torque", "Wraith",
+];
+
+/// Names that are used instead of `BOT_NAMES` when language filter is enabled.
+pub const CLEAN_BOT_NAMES: [&str; 12] = [
+    "Sprocket", "Comet", "Pixel", "Nimbus", "Echo", "Bolt",
+    "Zigzag", "Pebble", "Orbit", "Widget", "Blip", "Gizmo",
+];
+
+#[derive(Clone)]
 pub struct BotDefinition {
     pub scale: f32,
     pub health: f32,
@@ -210,6 +819,8 @@ pub struct BotDefinition {
     pub right_leg_name: &'static str,
     pub spine: &'static str,
     pub v_aim_angle_hack: f32,
+    /// Name of directory in data/sounds/voices with voice lines of the bot.
+    pub voice_pack: &'static str,
 }
 
 fn load_animation<P: AsRef<Path>>(
@@ -551,8 +1162,26 @@ impl Visit for CombatMachine {
     }
 }
 
+impl BotDefinition {
+    /// Changes value of a field by its name in definitions file.
+    pub fn set_value(&mut self, field: &str, value: &str) -> Result<(), String> {
+        match field {
+            "health" => self.health = parse_f32(value, 1.0, 10000.0)?,
+            "walk_speed" => self.walk_speed = parse_f32(value, 0.0, 20.0)?,
+            "scale" => self.scale = parse_f32(value, 0.001, 1.0)?,
+            _ => return Err(format!("unknown bot field {}", field)),
+        }
+        Ok(())
+    }
+}
+
 impl Bot {
+    /// Returns definition of bot, definitions file may override built-in one.
     pub fn get_definition(kind: BotKind) -> &'static BotDefinition {
+        definitions::bot(kind).unwrap_or_else(|| Self::builtin_definition(kind))
+    }
+
+    pub fn builtin_definition(kind: BotKind) -> &'static BotDefinition {
         match kind {
             BotKind::Mutant => {
                 static DEFINITION: BotDefinition = BotDefinition {
@@ -576,6 +1205,7 @@ impl Bot {
                     weapon_scale: 2.6,
                     health: 100.0,
                     v_aim_angle_hack: -2.0,
+                    voice_pack: "mutant",
                 };
                 &DEFINITION
             }
@@ -601,6 +1231,7 @@ impl Bot {
                     weapon_scale: 2.5,
                     health: 100.0,
                     v_aim_angle_hack: 12.0,
+                    voice_pack: "parasite",
                 };
                 &DEFINITION
             }
@@ -626,6 +1257,7 @@ impl Bot {
                     weapon_scale: 2.5,
                     health: 100.0,
                     v_aim_angle_hack: 16.0,
+                    voice_pack: "maw",
                 };
                 &DEFINITION
             }
@@ -705,20 +1337,154 @@ impl Bot {
     }
 
     pub fn can_be_removed(&self) -> bool {
-        self.dying_machine.machine.active_state() == self.dying_machine.dead_state
+        self.gibbed || self.dying_machine.machine.active_state() == self.dying_machine.dead_state
+    }
+
+    /// Hides model of dead bot, so gibs can be shown instead of dying animation. Bot is
+    /// removed on next update.
+    pub fn gib(&mut self, scene: &mut Scene) {
+        self.gibbed = true;
+        scene.graph[self.model].set_visibility(false);
+    }
+
+    /// Detaches model of dead bot from its pivot, so the model stays on level as a corpse
+    /// when bot is removed. Returns Handle::NONE if bot was torn apart and left no body.
+    pub fn detach_corpse(&mut self, scene: &mut Scene) -> Handle<Node> {
+        if self.gibbed || self.model.is_none() {
+            return Handle::NONE;
+        }
+        // Pivot is a child of root, so its rotation is global rotation of model.
+        let position = scene.graph[self.model].global_position();
+        let rotation = scene.graph[self.character.pivot].local_transform().rotation();
+        scene.graph.unlink_nodes(self.model);
+        let transform = scene.graph[self.model].local_transform_mut();
+        transform.set_position(position);
+        transform.set_rotation(rotation);
+        std::mem::replace(&mut self.model, Handle::NONE)
+    }
+
+    pub fn color(&self) -> Color {
+        self.color
+    }
+
+    /// Tints every mesh of bot's model with given color.
+    pub fn set_color(&mut self, graph: &mut Graph, color: Color) {
+        self.color = color;
+
+        let mut stack = vec![self.model];
+        while let Some(handle) = stack.pop() {
+            if let Node::Mesh(mesh) = &mut graph[handle] {
+                for surface in mesh.surfaces_mut() {
+                    surface.set_color(color);
+                }
+            }
+            stack.extend_from_slice(graph[handle].children());
+        }
+    }
+
+    pub fn set_difficulty(&mut self, difficulty: BotDifficulty) {
+        self.difficulty = difficulty;
+        self.yaw.speed = difficulty.turn_speed().to_radians();
+        self.pitch.speed = difficulty.turn_speed().to_radians();
+    }
+
+    pub fn difficulty(&self) -> BotDifficulty {
+        self.difficulty
+    }
+
+    pub fn set_fights_other_bots(&mut self, fights_other_bots: bool) {
+        self.fights_other_bots = fights_other_bots;
+    }
+
+    pub fn fights_other_bots(&self) -> bool {
+        self.fights_other_bots
+    }
+
+    pub fn set_sight_range(&mut self, sight_range: f32) {
+        self.sight_range = sight_range;
+    }
+
+    pub fn set_clean_language(&mut self, clean_language: bool) {
+        self.clean_language = clean_language;
+    }
+
+    /// Maybe plays random voice line from bot's voice pack at given position. Death lines are
+    /// always played, other lines are skipped if bot said something recently.
+    pub fn play_voice(&mut self, event: VoiceEvent, position: Vec3) {
+        if event != VoiceEvent::Death && self.voice_cooldown > 0.0 {
+            return;
+        }
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f32>() > event.chance() {
+            return;
+        }
+        // Clean variant of a voice pack has same set of lines, just without harsh words.
+        let pack = if self.clean_language {
+            format!("{}_clean", self.definition.voice_pack)
+        } else {
+            self.definition.voice_pack.to_owned()
+        };
+        let pack = Path::new(VOICES_PATH).join(pack);
+        // Voice packs are optional assets, bot stays silent if its pack is not installed.
+        if !pack.is_dir() {
+            return;
+        }
+        self.voice_cooldown = VOICE_COOLDOWN;
+        if let Some(sender) = self.character.sender.as_ref() {
+            sender.send(Message::PlaySound {
+                path: pack.join(format!("{}_{}.ogg", event.name(), rng.gen_range(1, VOICE_VARIANTS + 1))),
+                position,
+                gain: 1.0,
+                rolloff_factor: 2.0,
+                radius: 5.0,
+                priority: SoundPriority::Normal,
+            }).unwrap();
+        }
     }
 
     pub fn can_shoot(&self) -> bool {
         self.combat_machine.machine.active_state() == self.combat_machine.aim_state
     }
 
-    fn select_target(&mut self, self_handle: Handle<Actor>, scene: &Scene, targets: &[TargetDescriptor]) {
-        self.target = None;
+    /// Returns true if point is within view cone of bot or close enough to be noticed
+    /// without looking at it. Obstacles are not checked here.
+    fn is_in_view(&self, position: Vec3, look: Vec3, point: Vec3) -> bool {
+        let to_point = point - position;
+        if to_point.sqr_len() < AWARENESS_RADIUS * AWARENESS_RADIUS {
+            return true;
+        }
+        match to_point.normalized() {
+            Some(direction) => direction.dot(&look) >= (self.difficulty.view_cone_angle() * 0.5).to_radians().cos(),
+            None => true,
+        }
+    }
+
+    /// Returns closest target that bot can see, taking into account its sight range, view
+    /// cone, lighting and obstacles.
+    pub fn closest_visible_target(&self, self_handle: Handle<Actor>, scene: &Scene, smoke: &SmokeContainer, targets: &[TargetDescriptor]) -> Option<Target> {
+        let mut target = None;
         let position = self.character.position(&scene.physics);
+        let look = scene.graph[self.model].look_vector().normalized().unwrap_or(Vec3::LOOK);
         let mut closest_distance = std::f32::MAX;
         let mut raycast_results = Vec::new();
+        let sight_range = self.effective_sight_range();
         'target_loop: for desc in targets {
-            if desc.handle != self_handle && self.frustum.is_contains_point(desc.position) {
+            if desc.is_bot && !self.fights_other_bots {
+                continue;
+            }
+            // Teammates are never targets.
+            if self.character.team != Team::None && desc.team == self.character.team {
+                continue;
+            }
+            // Targets in dark areas can be seen only from closer distance.
+            let range = sight_range * (DARK_SIGHT_FACTOR + (1.0 - DARK_SIGHT_FACTOR) * desc.illumination);
+            if position.sqr_distance(&desc.position) > range * range {
+                continue;
+            }
+            if desc.handle != self_handle && self.is_in_view(position, look, desc.position) {
+                if smoke.blocks_sight(position, desc.position) {
+                    continue 'target_loop;
+                }
                 if let Some(ray) = Ray::from_two_points(&position, &desc.position) {
                     let options = RayCastOptions {
                         ignore_bodies: false,
@@ -744,14 +1510,220 @@ impl Bot {
 
                 let sqr_d = position.sqr_distance(&desc.position);
                 if sqr_d < closest_distance {
-                    self.target = Some(Target { position: desc.position, handle: desc.handle });
+                    target = Some(Target { position: desc.position, velocity: desc.velocity, handle: desc.handle });
                     closest_distance = sqr_d;
                 }
             }
         }
+        target
+    }
+
+    /// Looks for explosives that will hit bot and decides whether to run from them. Bot reacts
+    /// only after some time and easier bots can miss explosive completely.
+    fn update_danger(&mut self, position: Vec3, explosives: &[(Vec3, f32)], dt: f32) {
+        let danger = explosives
+            .iter()
+            .filter(|(explosive, radius)| {
+                let radius = radius * DANGER_RADIUS_MARGIN;
+                explosive.sqr_distance(&position) < radius * radius
+            })
+            .min_by(|(a, _), (b, _)| a.sqr_distance(&position)
+                .partial_cmp(&b.sqr_distance(&position))
+                .unwrap_or(std::cmp::Ordering::Equal));
+
+        match danger {
+            Some((explosive, _)) => {
+                if self.danger_time == 0.0 {
+                    self.danger_noticed = rand::thread_rng().gen::<f32>() < self.difficulty.danger_notice_chance();
+                }
+                self.danger_time += dt;
+                if self.danger_noticed && self.danger_time >= self.difficulty.danger_reaction_time() {
+                    let mut away = position - *explosive;
+                    away.y = 0.0;
+                    // Explosive right under feet gives no direction, keep running where bot
+                    // was going then.
+                    self.flee_direction = away.normalized()
+                        .or_else(|| self.last_move_dir.normalized())
+                        .or(Some(Vec3::new(1.0, 0.0, 0.0)));
+                }
+            }
+            None => {
+                self.danger_time = 0.0;
+                self.flee_direction = None;
+            }
+        }
+    }
+
+    pub fn set_behavior(&mut self, behavior: Box<dyn BotBehavior>) {
+        self.behavior = Some(behavior);
+    }
+
+    /// Shoots from current weapon exactly at current target, does nothing if there is no
+    /// target. Used by behaviors that override shooting.
+    pub fn shoot_at_target(&self, scene: &Scene) {
+        if let Some(target) = self.target.as_ref() {
+            if let Some(weapon) = self.character.weapons.get(self.character.current_weapon as usize) {
+                let direction = target.position - self.character.position(&scene.physics);
+                self.character.sender.as_ref().unwrap().send(Message::ShootWeapon {
+                    weapon: *weapon,
+                    initial_velocity: Vec3::ZERO,
+                    direction: Some(direction),
+                }).unwrap();
+            }
+        }
     }
 
-    fn select_point_of_interest(&mut self, items: &ItemContainer, scene: &Scene, time: &GameTime) {
+    /// Blinds bot for given time in seconds, longer blindness that bot already has is kept.
+    pub fn blind(&mut self, duration: f32) {
+        self.blind_time = self.blind_time.max(duration);
+    }
+
+    pub fn is_blind(&self) -> bool {
+        self.blind_time > 0.0
+    }
+
+    /// Returns position of head and look direction of bot.
+    pub fn view(&self, scene: &Scene) -> (Vec3, Vec3) {
+        let head = self.character.position(&scene.physics) + Vec3::new(0.0, 0.8, 0.0);
+        (head, scene.graph[self.model].look_vector())
+    }
+
+    /// Calls given closure with behavior of bot, behavior is temporarily taken out of
+    /// bot so closure can borrow both.
+    fn with_behavior<R>(&mut self, func: impl FnOnce(&mut dyn BotBehavior, &mut Bot) -> R) -> R {
+        let mut behavior = self.behavior
+            .take()
+            .unwrap_or_else(|| Box::new(StandardBehavior));
+        let result = func(behavior.as_mut(), self);
+        self.behavior = Some(behavior);
+        result
+    }
+
+    pub fn on_spawn(&mut self) {
+        self.with_behavior(|behavior, bot| behavior.on_spawn(bot));
+    }
+
+    pub fn on_damaged(&mut self, attacker: Handle<Actor>, attacker_position: Option<Vec3>, amount: f32, time: GameTime) {
+        self.with_behavior(|behavior, bot| behavior.on_damaged(bot, attacker, attacker_position, amount, time));
+    }
+
+    /// Decides whether bot should throw a grenade. Grenades are thrown at targets that just
+    /// went behind cover or at visible target that has others near it, bot that is losing
+    /// a fight throws smoke between itself and target. Returns point where grenade should
+    /// land and kind of grenade.
+    fn select_grenade_target(&mut self, self_handle: Handle<Actor>, position: Vec3, targets: &[TargetDescriptor], time: GameTime) -> Option<(Vec3, ProjectileKind)> {
+        if let Some(target) = self.target.as_ref() {
+            self.last_seen_target = target.handle;
+            self.last_target_seen_time = time.elapsed;
+        }
+
+        if time.elapsed - self.last_grenade_time < self.difficulty.grenade_cooldown() {
+            return None;
+        }
+
+        let mut rng = rand::thread_rng();
+
+        let (target_position, kind) = match self.target.as_ref() {
+            Some(target) if self.character.health < SMOKE_COVER_HEALTH => {
+                // Smoke is harmless, so distance limits do not apply.
+                self.last_grenade_time = time.elapsed;
+                return Some((position + (target.position - position).scale(0.5), ProjectileKind::SmokeGrenade));
+            }
+            Some(target) => {
+                let neighbours = targets.iter()
+                    .filter(|desc| desc.handle != self_handle && desc.health > 0.0 &&
+                        desc.position.distance(&target.position) <= GRENADE_CLUSTER_RADIUS)
+                    .count();
+                // Count includes target itself.
+                if neighbours >= 2 {
+                    (target.position, ProjectileKind::Grenade)
+                } else {
+                    return None;
+                }
+            }
+            None => {
+                if time.elapsed - self.last_target_seen_time > TARGET_MEMORY_TIME {
+                    return None;
+                }
+                // Bot guesses where target hides, it does not need a direct hit anyway.
+                let hiding_position = targets.iter()
+                    .find(|desc| desc.handle == self.last_seen_target && desc.health > 0.0)?
+                    .position;
+                let kind = if rng.gen_bool(FLASHBANG_CHANCE) {
+                    ProjectileKind::Flashbang
+                } else {
+                    ProjectileKind::Grenade
+                };
+                (hiding_position, kind)
+            }
+        };
+
+        let distance = position.distance(&target_position);
+        if distance < GRENADE_MIN_DISTANCE || distance > GRENADE_MAX_DISTANCE {
+            return None;
+        }
+
+        self.last_grenade_time = time.elapsed;
+
+        let spread = self.difficulty.grenade_spread();
+        Some((target_position + Vec3::new(rng.gen_range(-spread, spread), 0.0, rng.gen_range(-spread, spread)), kind))
+    }
+
+    /// Side of bot in squad, None if bot fights on its own. Bots without team that do not
+    /// fight each other are all on one side against the player.
+    fn squad(&self) -> Option<Team> {
+        if self.character.team != Team::None {
+            Some(self.character.team)
+        } else if !self.fights_other_bots {
+            Some(Team::None)
+        } else {
+            None
+        }
+    }
+
+    /// Shares sighting of current target with squad and follows squad plan: bot without
+    /// target goes after enemy called out by a teammate nearby, bot with target advances
+    /// to it or covers a teammate that advances. Returns true if bot should hold position.
+    fn coordinate_with_squad(&mut self, self_handle: Handle<Actor>, position: Vec3, squads: &mut Squads, time: GameTime) -> bool {
+        let team = match self.squad() {
+            Some(team) => team,
+            None => return false,
+        };
+        let coordination = self.difficulty.squad_coordination();
+        let blackboard = squads.blackboard(team);
+        let think = time.elapsed - self.last_squad_think_time >= SQUAD_THINK_INTERVAL;
+        if think {
+            self.last_squad_think_time = time.elapsed;
+        }
+
+        if let Some(target) = self.target.as_ref() {
+            blackboard.report(self_handle, position, target.handle, target.position, time.elapsed);
+            if coordination >= COVER_MIN_COORDINATION && !blackboard.may_advance(self_handle, time.elapsed) {
+                return true;
+            }
+            if think {
+                let target_position = target.position;
+                self.set_point_of_interest(target_position, time);
+            }
+        } else if think {
+            if let Some((target, target_position)) = blackboard.closest_callout(self_handle, position, CALLOUT_RADIUS * coordination) {
+                // Bots that go after the same enemy take lanes on alternating sides of
+                // direct one, so they don't walk in a line into the same fire.
+                let lane = blackboard.lane(self_handle, target, time.elapsed);
+                let side = if lane % 2 == 0 { -1.0 } else { 1.0 };
+                let offset = ((lane + 1) / 2) as f32 * LANE_SPACING * side;
+                let to_target = target_position - position;
+                let destination = match Vec3::new(-to_target.z, 0.0, to_target.x).normalized() {
+                    Some(perpendicular) => target_position + perpendicular.scale(offset),
+                    None => target_position,
+                };
+                self.set_point_of_interest(destination, time);
+            }
+        }
+        false
+    }
+
+    pub fn select_point_of_interest(&mut self, items: &ItemContainer, scene: &Scene, time: &GameTime) {
         if time.elapsed - self.last_poi_update_time >= 1.25 {
             // Select closest non-despawned item as point of interest.
             let self_position = self.position(&scene.physics);
@@ -770,45 +1742,119 @@ impl Bot {
         }
     }
 
-    fn select_weapon(&mut self, weapons: &WeaponContainer) {
-        if self.character.current_weapon().is_some() && weapons[self.character.current_weapon()].ammo() == 0 {
-            for (i, handle) in self.character.weapons().iter().enumerate() {
-                if weapons[*handle].ammo() > 0 {
-                    self.character.set_current_weapon(i);
-                    break;
-                }
+    /// Picks control point to go to. Team that holds at most half of points attacks closest
+    /// point it does not own, otherwise bots defend points that are being captured or stay
+    /// at closest held point.
+    pub fn select_control_point(&mut self, points: &[ControlPoint], scene: &Scene, time: &GameTime) {
+        if time.elapsed - self.last_poi_update_time < 1.25 {
+            return;
+        }
+        let team = self.character.team;
+        let held = points.iter().filter(|point| point.owner() == team).count();
+        let candidates = if held * 2 <= points.len() {
+            points.iter().filter(|point| point.owner() != team).collect::<Vec<_>>()
+        } else {
+            let threatened = points.iter().filter(|point| point.is_threatened(team)).collect::<Vec<_>>();
+            if threatened.is_empty() {
+                points.iter().filter(|point| point.owner() == team).collect()
+            } else {
+                threatened
             }
+        };
+        let self_position = self.position(&scene.physics);
+        let closest = candidates.iter()
+            .map(|point| point.center())
+            .min_by(|a, b| a.sqr_distance(&self_position)
+                .partial_cmp(&b.sqr_distance(&self_position))
+                .unwrap_or(std::cmp::Ordering::Equal));
+        if let Some(center) = closest {
+            self.point_of_interest = center;
         }
+        self.last_poi_update_time = time.elapsed;
     }
 
-    pub fn debug_draw(&self, debug_renderer: &mut DebugRenderer) {
-        for pts in self.path.windows(2) {
-            let a = pts[0];
-            let b = pts[1];
-            debug_renderer.add_line(debug_renderer::Line {
-                begin: a,
-                end: b,
-                color: Color::from_rgba(255, 0, 0, 255),
-            });
+    /// Switches to weapon that suits distance to current target best. Empty weapon is replaced
+    /// right away, otherwise bot reconsiders its choice only as often as its difficulty allows
+    /// and not sooner than `WEAPON_SWITCH_INTERVAL` after previous switch.
+    pub fn select_weapon(&mut self, weapons: &WeaponContainer, scene: &Scene, time: GameTime) {
+        let current = self.character.current_weapon();
+        let current_empty = current.is_some() && weapons[current].ammo() == 0;
+        if !current_empty {
+            if time.elapsed - self.last_weapon_think_time < self.difficulty.weapon_think_interval() {
+                return;
+            }
+            self.last_weapon_think_time = time.elapsed;
+            if self.target.is_none() || time.elapsed - self.last_weapon_switch_time < WEAPON_SWITCH_INTERVAL {
+                return;
+            }
+        }
+
+        // Without target any weapon with ammo will do.
+        let position = self.character.position(&scene.physics);
+        let distance = self.target.as_ref().map(|target| target.position.distance(&position));
+        let noise = self.difficulty.weapon_choice_noise();
+        let mut rng = rand::thread_rng();
+        let candidates = self.character
+            .weapons()
+            .iter()
+            .map(|handle| (weapons[*handle].definition, weapons[*handle].ammo() > 0))
+            .collect::<Vec<_>>();
+        let current_index = self.character.weapons().iter().position(|handle| *handle == current);
+        let best = best_weapon(&candidates, current_index, distance, || noise * (rng.gen::<f32>() * 2.0 - 1.0));
+
+        if let Some(i) = best {
+            if i != self.character.current_weapon as usize {
+                self.character.set_current_weapon(i);
+                self.last_weapon_switch_time = time.elapsed;
+            }
+        }
+    }
+
+    pub fn debug_draw(&self, debug_renderer: &mut DebugRenderer, scene: &Scene, flags: &DebugDrawFlags) {
+        if flags.paths {
+            for pts in self.path.windows(2) {
+                debug_draw::draw_line(debug_renderer, pts[0], pts[1], Color::from_rgba(255, 0, 0, 255));
+            }
+            if let Some(point) = self.path.get(self.current_path_point) {
+                debug_draw::draw_cross(debug_renderer, *point, 0.5, Color::from_rgba(255, 0, 0, 255));
+            }
+        }
+
+        if flags.sight {
+            debug_renderer.draw_frustum(&self.frustum, Color::from_rgba(0, 200, 0, 255));
+        }
+
+        if flags.targets {
+            let (head, _) = self.view(scene);
+            match self.target.as_ref() {
+                Some(target) => debug_draw::draw_line(debug_renderer, head, target.position, Color::from_rgba(255, 0, 255, 255)),
+                None => debug_draw::draw_line(debug_renderer, head, self.point_of_interest, Color::from_rgba(255, 255, 0, 255)),
+            }
         }
+    }
 
-        debug_renderer.draw_frustum(&self.frustum, Color::from_rgba(0, 200, 0, 255));
+    /// Max distance at which bot can see targets in lit areas.
+    pub fn effective_sight_range(&self) -> f32 {
+        self.sight_range * self.difficulty.sight_range_multiplier()
     }
 
+    /// Updates frustum that is shown in debug view, it matches view cone and sight range
+    /// of bot in lit areas.
     fn update_frustum(&mut self, position: Vec3, graph: &Graph) {
         let head_pos = position + Vec3::new(0.0, 0.8, 0.0);
         let up = graph[self.model].up_vector();
         let look_at = head_pos + graph[self.model].look_vector();
         let view_matrix = Mat4::look_at(head_pos, look_at, up).unwrap_or_default();
-        let projection_matrix = Mat4::perspective(60.0f32.to_radians(), 16.0 / 9.0, 0.1, 7.0);
+        let sight_range = self.effective_sight_range();
+        let projection_matrix = Mat4::perspective(self.difficulty.view_cone_angle().to_radians(), 1.0, 0.1, sight_range);
         let view_projection_matrix = projection_matrix * view_matrix;
         self.frustum = Frustum::from(view_projection_matrix).unwrap();
     }
 
     fn aim_vertically(&mut self, look_dir: Vec3, graph: &mut Graph, time: GameTime) {
         let angle = self.pitch.angle();
-        self.pitch.set_target(look_dir.dot(&Vec3::UP).acos() - std::f32::consts::PI / 2.0 + self.definition.v_aim_angle_hack.to_radians())
-            .update(time.delta);
+        let target = look_dir.dot(&Vec3::UP).acos() - std::f32::consts::PI / 2.0 + self.definition.v_aim_angle_hack.to_radians();
+        turn_smoothly(&mut self.pitch, target, self.difficulty.aim_smoothing(), time.delta);
 
         if self.spine.is_some() {
             graph[self.spine]
@@ -819,15 +1865,42 @@ impl Bot {
 
     fn aim_horizontally(&mut self, look_dir: Vec3, graph: &mut Graph, time: GameTime) {
         let angle = self.yaw.angle();
-        self.yaw.set_target(look_dir.x.atan2(look_dir.z))
-            .update(time.delta);
+        turn_smoothly(&mut self.yaw, look_dir.x.atan2(look_dir.z), self.difficulty.aim_smoothing(), time.delta);
 
         graph[self.character.pivot]
             .local_transform_mut()
             .set_rotation(Quat::from_axis_angle(Vec3::UP, angle));
     }
 
-    fn rebuild_path(&mut self, position: Vec3, navmesh: &mut Navmesh, time: GameTime) {
+    /// Whether bot faces its aim target closely enough to shoot, bot that still turns to a
+    /// target holds fire instead of shooting in a direction it does not look at.
+    fn is_on_target(&self) -> bool {
+        let yaw_error = angle_difference(self.yaw.angle, self.yaw.target);
+        let pitch_error = angle_difference(self.pitch.angle, self.pitch.target);
+        yaw_error.hypot(pitch_error) <= self.difficulty.aim_tolerance().to_radians()
+    }
+
+    /// Returns true if path of bot should be rebuilt. Pathfinding is expensive, so level
+    /// lets only some of bots that want it rebuild their paths on each frame.
+    pub fn wants_path_rebuild(&self, elapsed: f64) -> bool {
+        !self.character.is_dead() &&
+            elapsed - self.last_path_rebuild_time >= PATH_REBUILD_INTERVAL &&
+            (self.path_outdated || self.path.is_empty() || self.point_of_interest.distance(&self.path_target) > PATH_TARGET_TOLERANCE)
+    }
+
+    pub fn last_path_rebuild_time(&self) -> f64 {
+        self.last_path_rebuild_time
+    }
+
+    /// Lets bot rebuild its path on next update.
+    pub fn allow_path_rebuild(&mut self) {
+        self.path_rebuild_allowed = true;
+    }
+
+    fn rebuild_path(&mut self, position: Vec3, navmesh: &mut Navmesh, hazards: &[AxisAlignedBoundingBox], time: GameTime) {
+        // Failed attempt waits for next one too, so it does not take budget of other bots on
+        // every frame.
+        self.last_path_rebuild_time = time.elapsed;
         let from = position - Vec3::new(0.0, 1.0, 0.0);
         if let Some(from_index) = navmesh.query_closest(from) {
             if let Some(to_index) = navmesh.query_closest(self.point_of_interest) {
@@ -835,19 +1908,38 @@ impl Bot {
                 // Rebuild path if target path vertex has changed.
                 if navmesh.build_path(from_index, to_index, &mut self.path).is_ok() {
                     self.path.reverse();
-                    self.last_path_rebuild_time = time.elapsed;
+                    self.path_target = self.point_of_interest;
+                    self.path_outdated = false;
+                    // Navmesh knows nothing about hazards, so bot just skips points in them.
+                    self.path.retain(|point| !hazards.iter().any(|hazard| hazard.is_contains_point(*point)));
                 }
             }
         }
     }
 
     pub fn update(&mut self, self_handle: Handle<Actor>, context: &mut UpdateContext, targets: &[TargetDescriptor]) {
+        self.voice_cooldown -= context.time.delta;
+        // Protected bot flickers, so others can see that shooting it is useless for now.
+        if !self.gibbed {
+            let visible = !self.character.is_spawn_protected() ||
+                (self.character.spawn_protection_timer * SPAWN_PROTECTION_FLICKER_RATE) as u32 % 2 == 0;
+            context.scene.graph[self.model].set_visibility(visible);
+        }
         if self.character.is_dead() {
             self.dying_machine.apply(context.scene, context.time, self.character.is_dead());
+        } else if context.frozen {
+            // Bots wait for match start where they spawned, standing idle.
+            let has_ground_contact = self.character.has_ground_contact(&context.scene.physics);
+            self.locomotion_machine.apply(context.scene, context.time, true, false, has_ground_contact);
+            self.combat_machine.apply(context.scene, context.time, false, false, true);
         } else {
-            self.select_target(self_handle, context.scene, targets);
-            self.select_weapon(context.weapons);
-            self.select_point_of_interest(context.items, context.scene, &context.time);
+            self.blind_time = (self.blind_time - context.time.delta).max(0.0);
+            self.target = if self.is_blind() {
+                None
+            } else {
+                self.with_behavior(|behavior, bot| behavior.select_target(bot, self_handle, context.scene, context.smoke, targets))
+            };
+            self.with_behavior(|behavior, bot| behavior.on_update(bot, context));
 
             let has_ground_contact = self.character.has_ground_contact(&context.scene.physics);
             let body = context.scene.physics.borrow_body_mut(self.character.body);
@@ -862,6 +1954,13 @@ impl Bot {
 
             let position = body.get_position();
 
+            self.update_danger(position, context.explosives, context.time.delta);
+            // Bot that got into a hazard anyway runs out of it the same way as from explosive.
+            if self.flee_direction.is_none() {
+                self.flee_direction = hazard::escape_direction(position, context.hazards);
+            }
+            let holding = self.coordinate_with_squad(self_handle, position, context.squads, context.time);
+
             if let Some(path_point) = self.path.get(self.current_path_point) {
                 self.move_target = *path_point;
                 if self.move_target.distance(&position) <= 2.0 && self.current_path_point < self.path.len() - 1 {
@@ -875,7 +1974,17 @@ impl Bot {
                 self.aim_vertically(look_dir, &mut context.scene.graph, context.time);
                 self.aim_horizontally(look_dir, &mut context.scene.graph, context.time);
 
-                if !in_close_combat {
+                if let Some(flee_direction) = self.flee_direction {
+                    // Running from explosive is more important than anything else.
+                    let vel = flee_direction.scale(self.definition.walk_speed * FLEE_SPEED_MULTIPLIER * context.time.delta);
+                    body.set_x_velocity(vel.x);
+                    body.set_z_velocity(vel.z);
+                    self.last_move_dir = flee_direction;
+                } else if let Some(unstick_direction) = self.stuck_detector.unstick_direction() {
+                    let vel = unstick_direction.scale(self.definition.walk_speed * context.time.delta);
+                    body.set_x_velocity(vel.x);
+                    body.set_z_velocity(vel.z);
+                } else if !in_close_combat && !holding {
                     if has_ground_contact {
                         if let Some(move_dir) = (self.move_target - position).normalized() {
                             let vel = move_dir.scale(self.definition.walk_speed * context.time.delta);
@@ -892,9 +2001,22 @@ impl Bot {
                 }
             }
 
+            // Fleeing bot is not checked, backing up towards explosive is worse than being stuck.
+            let trying_to_move = has_ground_contact && !in_close_combat && !holding && self.flee_direction.is_none() &&
+                self.move_target.distance(&position) > 2.0 * STUCK_MIN_DISTANCE;
+            if self.stuck_detector.update(position, self.last_move_dir, trying_to_move, context.time.delta) {
+                // Obstacle is between bot and current path point, so try next one and build
+                // new path right after bot has backed up.
+                if self.current_path_point + 1 < self.path.len() {
+                    self.current_path_point += 1;
+                }
+                self.last_path_rebuild_time = context.time.elapsed - PATH_REBUILD_INTERVAL + f64::from(UNSTICK_DURATION);
+                self.path_outdated = true;
+            }
+
             let need_jump = look_dir.y >= 0.3 && has_ground_contact && in_close_combat;
             if need_jump {
-                body.set_y_velocity(0.08);
+                body.set_y_velocity(JUMP_SPEED * context.time.delta);
             }
             let was_damaged = self.character.health < self.last_health;
             if was_damaged {
@@ -907,6 +2029,12 @@ impl Bot {
             let can_aim = self.restoration_time <= 0.0;
             self.last_health = self.character.health;
 
+            let grenade_target = if !in_close_combat && can_aim && !self.is_blind() {
+                self.select_grenade_target(self_handle, position, targets, context.time)
+            } else {
+                None
+            };
+
             self.locomotion_machine.apply(context.scene, context.time, in_close_combat, need_jump, has_ground_contact);
             self.combat_machine.apply(context.scene, context.time, in_close_combat, was_damaged, can_aim);
 
@@ -915,12 +2043,37 @@ impl Bot {
                 .as_ref()
                 .unwrap();
 
-            if !in_close_combat && can_aim && self.can_shoot() && self.target.is_some() {
+            let overrides_shooting = self.behavior.as_ref().map_or(false, |behavior| behavior.overrides_shooting());
+            if !overrides_shooting && !in_close_combat && can_aim && self.can_shoot() {
+                if let (Some(target), Some(weapon)) = (self.target.as_ref(), self.character.weapons.get(self.character.current_weapon as usize)) {
+                    let definition = context.weapons[*weapon].definition;
+                    // Do not waste ammo on targets that are out of effective range of the weapon
+                    // or while bot is still turning to the target.
+                    if look_dir.len() <= definition.bot_range && self.is_on_target() {
+                        // Slow projectiles need to be aimed ahead of moving target.
+                        let projectile = Projectile::get_definition(definition.projectile);
+                        let aim_point = lead_target(position, target.position, target.velocity, projectile, self.difficulty.lead_factor());
+                        sender.send(Message::ShootWeapon {
+                            weapon: *weapon,
+                            initial_velocity: Vec3::ZERO,
+                            direction: Some(aim_point - position),
+                        }).unwrap();
+                    }
+                }
+            }
+
+            if let Some((grenade_target, grenade_kind)) = grenade_target {
+                // Grenade is owned by current weapon, so frags will be given to the bot.
                 if let Some(weapon) = self.character.weapons.get(self.character.current_weapon as usize) {
-                    sender.send(Message::ShootWeapon {
-                        weapon: *weapon,
-                        initial_velocity: Vec3::ZERO,
-                        direction: Some(look_dir),
+                    let from = position + Vec3::new(0.0, 0.8, 0.0);
+                    let flight_time = (from.distance(&grenade_target) / GRENADE_THROW_SPEED).max(0.5);
+                    sender.send(Message::CreateProjectile {
+                        kind: grenade_kind,
+                        position: from,
+                        direction: look_dir,
+                        initial_velocity: throw_velocity(from, grenade_target, flight_time, context.time.delta),
+                        owner: *weapon,
+                        basis: Default::default(),
                     }).unwrap();
                 }
             }
@@ -933,6 +2086,9 @@ impl Bot {
                             actor: target.handle,
                             who: Default::default(),
                             amount: 20.0,
+                            region: None,
+                            kind: DamageKind::Melee,
+                            position: None,
                         }).unwrap();
                     }
                 }
@@ -954,14 +2110,22 @@ impl Bot {
                             gain: 1.0,
                             rolloff_factor: 2.0,
                             radius: 3.0,
+                            priority: SoundPriority::Low,
+                        }).unwrap();
+                        // Bot that runs from danger is as loud as running player.
+                        sender.send(Message::Footstep {
+                            actor: self_handle,
+                            position,
+                            loudness: if self.flee_direction.is_some() { FOOTSTEP_LOUDNESS.2 } else { FOOTSTEP_LOUDNESS.1 },
                         }).unwrap();
                     }
                 }
             }
 
-            if context.time.elapsed - self.last_path_rebuild_time >= 1.0 {
+            if self.path_rebuild_allowed {
+                self.path_rebuild_allowed = false;
                 if let Some(navmesh) = context.navmesh.as_mut() {
-                    self.rebuild_path(position, navmesh, context.time);
+                    self.rebuild_path(position, navmesh, context.hazards, context.time);
                 }
             }
             self.restoration_time -= context.time.delta;
@@ -983,6 +2147,21 @@ impl Bot {
         }
     }
 
+    /// Makes bot turn to a shot it heard, bot that already has a target ignores noise.
+    pub fn hear_noise(&mut self, position: Vec3, time: GameTime) {
+        if self.target.is_none() {
+            self.set_point_of_interest(position, time);
+        }
+    }
+
+    /// Makes bot turn to a footstep if it is close enough to be heard, `distance` is the
+    /// distance from bot to the footstep.
+    pub fn hear_footstep(&mut self, position: Vec3, distance: f32, loudness: f32, time: GameTime) {
+        if distance <= FOOTSTEP_HEARING_RANGE * loudness * self.difficulty.hearing_multiplier() {
+            self.hear_noise(position, time);
+        }
+    }
+
     pub fn set_point_of_interest(&mut self, poi: Vec3, time: GameTime) {
         self.point_of_interest = poi;
         self.last_poi_update_time = time.elapsed;
@@ -1016,7 +2195,38 @@ impl Visit for Bot {
         self.restoration_time.visit("RestorationTime", visitor)?;
         self.yaw.visit("Yaw", visitor)?;
         self.pitch.visit("Pitch", visitor)?;
+        self.color.visit("Color", visitor)?;
+        self.difficulty.visit("Difficulty", visitor)?;
+        self.fights_other_bots.visit("FightsOtherBots", visitor)?;
+        self.sight_range.visit("SightRange", visitor)?;
+        self.gibbed.visit("Gibbed", visitor)?;
 
         visitor.leave_region()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::weapon::Weapon;
+
+    #[test]
+    fn bot_switches_to_close_range_weapon_when_target_closes_in() {
+        let rifle = Weapon::get_definition(WeaponKind::M4);
+        let plasma = Weapon::get_definition(WeaponKind::PlasmaRifle);
+        assert!(plasma.bot_preferred_range < rifle.bot_preferred_range);
+        let candidates = [(rifle, true), (plasma, true)];
+
+        // Rifle is kept while target is far.
+        assert_eq!(best_weapon(&candidates, Some(0), Some(25.0), || 0.0), Some(0));
+        // Target closed in, close range weapon wins even over margin of current weapon.
+        assert_eq!(best_weapon(&candidates, Some(0), Some(5.0), || 0.0), Some(1));
+    }
+
+    #[test]
+    fn bot_skips_weapons_without_ammo() {
+        let rifle = Weapon::get_definition(WeaponKind::M4);
+        let plasma = Weapon::get_definition(WeaponKind::PlasmaRifle);
+        assert_eq!(best_weapon(&[(rifle, true), (plasma, false)], Some(0), Some(5.0), || 0.0), Some(0));
+        assert_eq!(best_weapon(&[(rifle, false), (plasma, false)], Some(0), Some(5.0), || 0.0), None);
+    }
+}
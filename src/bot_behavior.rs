@@ -0,0 +1,177 @@
+//! Decision logic of bots. Bot itself keeps everything needed to move, animate and shoot,
+//! but what to attack and where to go is decided by its behavior. Custom behaviors are
+//! injected with `Level::set_bot_behavior_factory`, factory makes behavior of every bot when
+//! it is spawned. Without factory `StandardBehavior` is used.
+//!
+//! Behaviors are not saved, bots from loaded game always get standard behavior.
+
+use crate::{
+    actor::{Actor, TargetDescriptor},
+    bot::{Bot, Target},
+    level::UpdateContext,
+    smoke::SmokeContainer,
+    GameTime,
+};
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        pool::Handle,
+    },
+    scene::Scene,
+};
+
+/// Makes behavior for given bot, it is called when bot is spawned.
+pub type BotBehaviorFactory = Box<dyn Fn(&Bot) -> Box<dyn BotBehavior>>;
+
+pub trait BotBehavior {
+    /// Called once when bot was added to level.
+    fn on_spawn(&mut self, _bot: &mut Bot) {}
+
+    /// Called every frame while bot is alive, after target was selected and before bot
+    /// moves and shoots.
+    fn on_update(&mut self, _bot: &mut Bot, _context: &mut UpdateContext) {}
+
+    /// Returns target which bot should attack, None means that bot has nothing to attack.
+    /// Smoke blocks sight, but behavior may ignore it.
+    fn select_target(&mut self, bot: &Bot, self_handle: Handle<Actor>, scene: &Scene, smoke: &SmokeContainer, targets: &[TargetDescriptor]) -> Option<Target>;
+
+    /// Called when bot took damage. Attacker is none and its position is None for damage
+    /// dealt by environment.
+    fn on_damaged(&mut self, _bot: &mut Bot, _attacker: Handle<Actor>, _attacker_position: Option<Vec3>, _amount: f32, _time: GameTime) {}
+
+    /// If true, bot never shoots by itself and behavior has to do it in `on_update`.
+    fn overrides_shooting(&self) -> bool {
+        false
+    }
+}
+
+/// Attacks closest visible target, picks up items or goes for control points when there is
+/// nothing to attack and turns to attacker when damaged.
+pub struct StandardBehavior;
+
+impl BotBehavior for StandardBehavior {
+    fn on_update(&mut self, bot: &mut Bot, context: &mut UpdateContext) {
+        bot.select_weapon(context.weapons, context.scene, context.time);
+        if context.control_points.is_empty() {
+            bot.select_point_of_interest(context.items, context.scene, &context.time);
+        } else {
+            bot.select_control_point(context.control_points, context.scene, &context.time);
+        }
+    }
+
+    fn select_target(&mut self, bot: &Bot, self_handle: Handle<Actor>, scene: &Scene, smoke: &SmokeContainer, targets: &[TargetDescriptor]) -> Option<Target> {
+        bot.closest_visible_target(self_handle, scene, smoke, targets)
+    }
+
+    fn on_damaged(&mut self, bot: &mut Bot, _attacker: Handle<Actor>, attacker_position: Option<Vec3>, _amount: f32, time: GameTime) {
+        if let Some(attacker_position) = attacker_position {
+            bot.set_point_of_interest(attacker_position, time);
+        }
+    }
+}
+
+/// Debug behavior for benchmarking: bot knows where every target is, aims exactly at the
+/// closest one and shoots at fixed interval, no matter if target is visible. Enabled from
+/// console by `set bot_aimbot <interval>`, never used in normal matches.
+pub struct AimbotBehavior {
+    /// Time between shots in seconds, weapon's own shoot interval still applies.
+    interval: f32,
+    time_until_shot: f32,
+}
+
+impl AimbotBehavior {
+    pub fn new(interval: f32) -> Self {
+        Self {
+            interval,
+            time_until_shot: interval,
+        }
+    }
+}
+
+impl BotBehavior for AimbotBehavior {
+    fn on_update(&mut self, bot: &mut Bot, context: &mut UpdateContext) {
+        bot.select_weapon(context.weapons, context.scene, context.time);
+
+        self.time_until_shot -= context.time.delta;
+        if self.time_until_shot <= 0.0 {
+            self.time_until_shot += self.interval;
+            bot.shoot_at_target(context.scene);
+        }
+    }
+
+    fn select_target(&mut self, bot: &Bot, self_handle: Handle<Actor>, scene: &Scene, _smoke: &SmokeContainer, targets: &[TargetDescriptor]) -> Option<Target> {
+        let position = bot.position(&scene.physics);
+        targets.iter()
+            .filter(|desc| desc.handle != self_handle && (!desc.is_bot || bot.fights_other_bots()))
+            .min_by(|a, b| a.position.sqr_distance(&position)
+                .partial_cmp(&b.position.sqr_distance(&position))
+                .unwrap_or(std::cmp::Ordering::Equal))
+            .map(|desc| Target {
+                position: desc.position,
+                velocity: desc.velocity,
+                handle: desc.handle,
+            })
+    }
+
+    fn overrides_shooting(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::character::Team;
+
+    /// Example of custom behavior: attacks enemy with least health, wherever it is.
+    struct WeakestFirstBehavior;
+
+    impl BotBehavior for WeakestFirstBehavior {
+        fn select_target(&mut self, _bot: &Bot, self_handle: Handle<Actor>, _scene: &Scene, _smoke: &SmokeContainer, targets: &[TargetDescriptor]) -> Option<Target> {
+            weakest_target(self_handle, targets)
+        }
+    }
+
+    fn weakest_target(self_handle: Handle<Actor>, targets: &[TargetDescriptor]) -> Option<Target> {
+        targets.iter()
+            .filter(|desc| desc.handle != self_handle)
+            .min_by(|a, b| a.health.partial_cmp(&b.health).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|desc| Target {
+                position: desc.position,
+                velocity: desc.velocity,
+                handle: desc.handle,
+            })
+    }
+
+    fn descriptor(index: u32, health: f32) -> TargetDescriptor {
+        TargetDescriptor {
+            handle: Handle::new(index, 1),
+            ptr: std::ptr::null(),
+            health,
+            position: Vec3::new(index as f32, 0.0, 0.0),
+            velocity: Vec3::ZERO,
+            is_bot: true,
+            illumination: 1.0,
+            team: Team::None,
+        }
+    }
+
+    #[test]
+    fn custom_behavior_picks_own_target() {
+        let targets = [descriptor(1, 20.0), descriptor(2, 80.0), descriptor(3, 50.0)];
+        let target = weakest_target(Handle::new(2, 1), &targets).unwrap();
+        assert_eq!(target.handle, Handle::new(1, 1));
+        assert_eq!(target.position, Vec3::new(1.0, 0.0, 0.0));
+
+        // Bot never targets itself.
+        let target = weakest_target(Handle::new(1, 1), &targets).unwrap();
+        assert_eq!(target.handle, Handle::new(3, 1));
+        assert!(weakest_target(Handle::new(1, 1), &targets[..1]).is_none());
+    }
+
+    #[test]
+    fn custom_behavior_keeps_default_hooks() {
+        let behavior: Box<dyn BotBehavior> = Box::new(WeakestFirstBehavior);
+        assert!(!behavior.overrides_shooting());
+    }
+}
@@ -21,8 +21,14 @@ use rg3d::{
 use crate::{
     weapon::Weapon,
     message::Message,
+    damage::HitRegion,
+    ammo::AmmoStore,
+};
+use std::{
+    sync::mpsc::Sender,
+    rc::Rc,
+    cell::RefCell,
 };
-use std::sync::mpsc::Sender;
 
 pub struct Character {
     pub name: String,
@@ -34,7 +40,11 @@ pub struct Character {
     pub current_weapon: u32,
     pub weapon_pivot: Handle<Node>,
     pub sender: Option<Sender<Message>>,
-    pub team: Team
+    pub team: Team,
+    /// Time left of invulnerability after spawn, in seconds.
+    pub spawn_protection_timer: f32,
+    /// Rounds outside of magazines by ammo type, shared by every weapon of character.
+    pub ammo_store: Rc<RefCell<AmmoStore>>,
 }
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
@@ -50,6 +60,16 @@ impl Default for Team {
     }
 }
 
+impl Team {
+    pub fn name(self) -> &'static str {
+        match self {
+            Team::None => "None",
+            Team::Red => "Red",
+            Team::Blue => "Blue",
+        }
+    }
+}
+
 impl Visit for Team {
     fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
         let mut id = match self {
@@ -82,7 +102,9 @@ impl Default for Character {
             current_weapon: 0,
             weapon_pivot: Handle::NONE,
             sender: None,
-            team: Team::None
+            team: Team::None,
+            spawn_protection_timer: 0.0,
+            ammo_store: Default::default(),
         }
     }
 }
@@ -100,6 +122,8 @@ impl Visit for Character {
         self.current_weapon.visit("CurrentWeapon", visitor)?;
         self.weapon_pivot.visit("WeaponPivot", visitor)?;
         self.team.visit("Team", visitor)?;
+        self.spawn_protection_timer.visit("SpawnProtectionTimer", visitor)?;
+        self.ammo_store.borrow_mut().visit("AmmoStore", visitor)?;
 
         visitor.leave_region()
     }
@@ -144,6 +168,12 @@ impl Character {
         physics.borrow_body(self.get_body()).get_position()
     }
 
+    /// Returns part of body that given point of hit is on, see [`HitRegion::from_height`].
+    pub fn hit_region(&self, physics: &Physics, position: Vec3) -> HitRegion {
+        let body = physics.borrow_body(self.get_body());
+        HitRegion::from_height(position.y - body.get_position().y, body.get_shape().as_capsule().get_height() * 0.5)
+    }
+
     pub fn damage(&mut self, amount: f32) {
         let amount = amount.abs();
         if self.armor > 0.0 {
@@ -164,6 +194,24 @@ impl Character {
         }
     }
 
+    /// Makes character invulnerable for given time, zero disables protection.
+    pub fn set_spawn_protection(&mut self, duration: f32) {
+        self.spawn_protection_timer = duration.max(0.0);
+    }
+
+    pub fn is_spawn_protected(&self) -> bool {
+        self.spawn_protection_timer > 0.0
+    }
+
+    /// Ends protection right away, it is used when character fires a weapon.
+    pub fn end_spawn_protection(&mut self) {
+        self.spawn_protection_timer = 0.0;
+    }
+
+    pub fn update_spawn_protection(&mut self, dt: f32) {
+        self.spawn_protection_timer = (self.spawn_protection_timer - dt).max(0.0);
+    }
+
     pub fn is_dead(&self) -> bool {
         self.health <= 0.0
     }
@@ -172,11 +220,28 @@ impl Character {
         self.weapon_pivot
     }
 
+    pub fn ammo_store(&self) -> Rc<RefCell<AmmoStore>> {
+        self.ammo_store.clone()
+    }
+
     pub fn weapons(&self) -> &[Handle<Weapon>] {
         &self.weapons
     }
 
-    pub fn add_weapon(&mut self, weapon: Handle<Weapon>) {
+    /// Adds weapon to inventory, new weapon becomes current one if `select` is set or if
+    /// there was no weapon at all, otherwise it is hidden until player switches to it.
+    pub fn add_weapon(&mut self, weapon: Handle<Weapon>, select: bool) {
+        if !select && !self.weapons.is_empty() {
+            if let Some(sender) = self.sender.as_ref() {
+                sender.send(Message::ShowWeapon {
+                    weapon,
+                    state: false,
+                }).unwrap();
+            }
+            self.weapons.push(weapon);
+            return;
+        }
+
         if let Some(sender) = self.sender.as_ref() {
             for other_weapon in self.weapons.iter() {
                 sender.send(Message::ShowWeapon {
@@ -246,3 +311,41 @@ impl Character {
         scene.physics.remove_body(self.body);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::damage::HitMultipliers;
+    use rg3d::physics::convex_shape::{ConvexShape, CapsuleShape, Axis};
+
+    /// Physics with single character body, capsule with 1 m high cylinder centered at origin.
+    fn physics_with_character() -> (Physics, Character) {
+        let mut physics = Physics::new();
+        let body = physics.add_body(RigidBody::new(ConvexShape::Capsule(CapsuleShape::new(0.3, 1.0, Axis::Y))));
+        let character = Character {
+            body,
+            ..Default::default()
+        };
+        (physics, character)
+    }
+
+    #[test]
+    fn hit_at_head_height_yields_head_multiplier() {
+        let (physics, character) = physics_with_character();
+        let multipliers = HitMultipliers {
+            head: 2.0,
+            ..Default::default()
+        };
+
+        let region = character.hit_region(&physics, Vec3::new(0.0, 0.7, 0.3));
+        assert_eq!(region, HitRegion::Head);
+        assert_eq!(multipliers.get(region), 2.0);
+    }
+
+    #[test]
+    fn hits_below_head_yield_torso_and_limbs() {
+        let (physics, character) = physics_with_character();
+        assert_eq!(character.hit_region(&physics, Vec3::new(0.3, 0.2, 0.0)), HitRegion::Torso);
+        assert_eq!(character.hit_region(&physics, Vec3::new(0.3, -0.4, 0.0)), HitRegion::Limbs);
+    }
+}
@@ -0,0 +1,205 @@
+//! Quick communication with teammates. Player holds callout wheel button to open a radial
+//! menu, moves mouse toward a callout and releases the button to say it. Callout goes
+//! through the event bus as `Message::TeamCallout`, teammates see it in kill feed and hear
+//! a voice line, bots of the same team react to some of callouts.
+
+use rg3d::{
+    core::{
+        color::Color,
+        math::vec2::Vec2,
+    },
+    gui::{
+        canvas::CanvasBuilder,
+        widget::WidgetBuilder,
+        text::TextBuilder,
+        node::StubNode,
+        brush::Brush,
+        message::WidgetMessage,
+        ttf::Font,
+        HorizontalAlignment,
+        VerticalAlignment,
+    },
+};
+use crate::{
+    UINodeHandle,
+    BuildContext,
+    Gui,
+};
+use std::{
+    sync::{Arc, Mutex},
+    path::Path,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TeamCallout {
+    /// Bots look where caller looks.
+    EnemySpotted,
+    /// Bots come to caller.
+    NeedBackup,
+    /// Bots come to caller.
+    CoverMe,
+    /// Bots come to caller.
+    FollowMe,
+    Affirmative,
+    Negative,
+}
+
+/// Every callout in the order they're placed on the wheel, clockwise starting from the top.
+pub const TEAM_CALLOUTS: [TeamCallout; 6] = [
+    TeamCallout::EnemySpotted,
+    TeamCallout::NeedBackup,
+    TeamCallout::CoverMe,
+    TeamCallout::Affirmative,
+    TeamCallout::Negative,
+    TeamCallout::FollowMe,
+];
+
+impl TeamCallout {
+    pub fn text(self) -> &'static str {
+        match self {
+            TeamCallout::EnemySpotted => "Enemy spotted!",
+            TeamCallout::NeedBackup => "Need backup!",
+            TeamCallout::CoverMe => "Cover me!",
+            TeamCallout::FollowMe => "Follow me!",
+            TeamCallout::Affirmative => "Affirmative",
+            TeamCallout::Negative => "Negative",
+        }
+    }
+
+    /// Voice clips of callouts are optional assets, None is returned if clip of the callout
+    /// is not installed and callout is only shown as text.
+    pub fn sound(self) -> Option<&'static str> {
+        let path = match self {
+            TeamCallout::EnemySpotted => "data/sounds/callouts/enemy_spotted.ogg",
+            TeamCallout::NeedBackup => "data/sounds/callouts/need_backup.ogg",
+            TeamCallout::CoverMe => "data/sounds/callouts/cover_me.ogg",
+            TeamCallout::FollowMe => "data/sounds/callouts/follow_me.ogg",
+            TeamCallout::Affirmative => "data/sounds/callouts/affirmative.ogg",
+            TeamCallout::Negative => "data/sounds/callouts/negative.ogg",
+        };
+        Some(path).filter(|path| Path::new(path).exists())
+    }
+}
+
+/// Distance from center of wheel to centers of callout labels, before HUD scale is applied.
+const WHEEL_RADIUS: f32 = 130.0;
+/// Mouse has to move this far from where wheel was opened to select a callout, smaller
+/// movements keep wheel without selection, so it can be closed without saying anything.
+const POINTER_DEAD_ZONE: f32 = 40.0;
+/// Pointer never goes further than this, so turning mouse to other callout is quick.
+const MAX_POINTER_DISTANCE: f32 = 120.0;
+/// Size of font of labels, before HUD scale is applied.
+const LABEL_FONT_SIZE: f32 = 20.0;
+
+/// Radial menu of callouts, it is a part of HUD.
+pub struct CalloutWheel {
+    root: UINodeHandle,
+    /// Labels of callouts, in the order of `TEAM_CALLOUTS`.
+    labels: Vec<UINodeHandle>,
+    /// Mouse movement since wheel was opened, y points down.
+    pointer: Vec2,
+    selected: Option<usize>,
+    open: bool,
+    color: Color,
+    selected_color: Color,
+}
+
+impl CalloutWheel {
+    /// Creates wheel centered inside the area given by widget builder, wheel is closed
+    /// until `open` is called.
+    pub fn build(ctx: &mut BuildContext, widget_builder: WidgetBuilder<(), StubNode>, font: Arc<Mutex<Font>>, color: Color, selected_color: Color, scale: f32) -> Self {
+        let radius = WHEEL_RADIUS * scale;
+        let font_size = LABEL_FONT_SIZE * scale;
+        // Labels are wide, so there is extra room for them on the sides.
+        let width = (radius + 8.0 * font_size) * 2.0;
+        let height = (radius + font_size) * 2.0;
+
+        let labels = TEAM_CALLOUTS.iter()
+            .enumerate()
+            .map(|(i, callout)| {
+                let angle = i as f32 / TEAM_CALLOUTS.len() as f32 * 2.0 * std::f32::consts::PI;
+                // Text is roughly centered around its point on the wheel.
+                let text_size = Vec2::new(callout.text().len() as f32 * font_size * 0.5, font_size);
+                let position = Vec2::new(
+                    width * 0.5 + radius * angle.sin() - text_size.x * 0.5,
+                    height * 0.5 - radius * angle.cos() - text_size.y * 0.5);
+                TextBuilder::new(WidgetBuilder::new()
+                    .with_desired_position(position)
+                    .with_foreground(Brush::Solid(color)))
+                    .with_font(font.clone())
+                    .with_text(callout.text())
+                    .build(ctx)
+            })
+            .collect::<Vec<_>>();
+
+        let root = CanvasBuilder::new(widget_builder
+            .with_visibility(false)
+            .with_width(width)
+            .with_height(height)
+            .with_horizontal_alignment(HorizontalAlignment::Center)
+            .with_vertical_alignment(VerticalAlignment::Center)
+            .with_children(&labels))
+            .build(ctx);
+
+        Self {
+            root,
+            labels,
+            pointer: Vec2::new(0.0, 0.0),
+            selected: None,
+            open: false,
+            color,
+            selected_color,
+        }
+    }
+
+    pub fn root(&self) -> UINodeHandle {
+        self.root
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    pub fn open(&mut self, ui: &mut Gui) {
+        self.open = true;
+        self.pointer = Vec2::new(0.0, 0.0);
+        self.select(ui, None);
+        ui.send_message(WidgetMessage::visibility(self.root, true));
+    }
+
+    /// Moves pointer of the wheel by mouse movement, callout in direction of pointer
+    /// becomes selected.
+    pub fn move_pointer(&mut self, ui: &mut Gui, delta: (f64, f64)) {
+        self.pointer += Vec2::new(delta.0 as f32, delta.1 as f32);
+        let distance = self.pointer.len();
+        if distance > MAX_POINTER_DISTANCE {
+            self.pointer = self.pointer.scale(MAX_POINTER_DISTANCE / distance);
+        }
+        let selected = if distance > POINTER_DEAD_ZONE {
+            // Angle is measured clockwise from the top, same as labels are placed.
+            let sector = 2.0 * std::f32::consts::PI / TEAM_CALLOUTS.len() as f32;
+            let angle = self.pointer.x.atan2(-self.pointer.y);
+            Some((angle / sector).round().rem_euclid(TEAM_CALLOUTS.len() as f32) as usize)
+        } else {
+            None
+        };
+        self.select(ui, selected);
+    }
+
+    /// Closes wheel and returns selected callout, if any.
+    pub fn close(&mut self, ui: &mut Gui) -> Option<TeamCallout> {
+        self.open = false;
+        ui.send_message(WidgetMessage::visibility(self.root, false));
+        self.selected.map(|index| TEAM_CALLOUTS[index])
+    }
+
+    fn select(&mut self, ui: &mut Gui, selected: Option<usize>) {
+        if self.selected != selected {
+            for (i, &label) in self.labels.iter().enumerate() {
+                let color = if Some(i) == selected { self.selected_color } else { self.color };
+                ui.send_message(WidgetMessage::foreground(label, Brush::Solid(color)));
+            }
+            self.selected = selected;
+        }
+    }
+}
@@ -0,0 +1,297 @@
+//! Console for debug and network commands. It is opened by console key (`~`) anywhere, also
+//! in menu, so commands that start or join a match can be used before there is any match.
+//! Every command is a row of command table, line is split into name of command and its
+//! arguments and the command reports a line of text that is shown in console. Console does
+//! not write notes, notes are typed into note input of HUD and everything typed there stays
+//! a note.
+
+use crate::{
+    gui::{self, create_backdrop},
+    message::Message,
+    net::{DEFAULT_PORT, DEFAULT_MAX_PLAYERS},
+    settings::{Accessibility, SETTINGS_EXPORT_PATH},
+    Game,
+    UINodeHandle,
+    BuildContext,
+    Gui,
+};
+use rg3d::{
+    core::color::Color,
+    event::VirtualKeyCode,
+    gui::{
+        widget::WidgetBuilder,
+        text::TextBuilder,
+        message::{TextMessage, WidgetMessage},
+        brush::Brush,
+        Thickness,
+        HorizontalAlignment,
+        VerticalAlignment,
+    },
+};
+use std::{
+    collections::VecDeque,
+    path::PathBuf,
+};
+
+/// Key that opens and closes console, it is not bindable same as keys that edit text.
+pub const CONSOLE_KEY: VirtualKeyCode = VirtualKeyCode::Grave;
+/// Longest line that can be typed into console.
+const MAX_LINE_LENGTH: usize = 100;
+/// Amount of output lines that are kept above input line.
+const OUTPUT_SIZE: usize = 8;
+
+/// Result of command is shown in console, error is shown the same way but is also printed.
+pub type CommandResult = Result<String, String>;
+
+pub struct Command {
+    pub name: &'static str,
+    /// Arguments in angle brackets are required, in square brackets are optional.
+    pub usage: &'static str,
+    pub help: &'static str,
+    run: fn(&mut Game, &str) -> CommandResult,
+}
+
+/// Every console command, `help` lists them in this order.
+pub static COMMANDS: [Command; 7] = [
+    Command {
+        name: "help",
+        usage: "help",
+        help: "lists commands",
+        run: help,
+    },
+    Command {
+        name: "set",
+        usage: "set <name> <value>",
+        help: "changes debug, network or balance variable",
+        run: set,
+    },
+    Command {
+        name: "host",
+        usage: "host [port]",
+        help: "accepts network players in current match",
+        run: host,
+    },
+    Command {
+        name: "connect",
+        usage: "connect <address[:port]>",
+        help: "joins match of a server",
+        run: connect,
+    },
+    Command {
+        name: "reset_range",
+        usage: "reset_range",
+        help: "resets targets and stats of target range",
+        run: reset_range,
+    },
+    Command {
+        name: "export",
+        usage: "export [path]",
+        help: "writes settings to a file",
+        run: export,
+    },
+    Command {
+        name: "import",
+        usage: "import [path]",
+        help: "reads settings from a file",
+        run: import,
+    },
+];
+
+pub fn find(name: &str) -> Option<&'static Command> {
+    COMMANDS.iter().find(|command| command.name == name)
+}
+
+/// Splits line into name of command and its arguments, None if line is empty.
+pub fn split(line: &str) -> Option<(&str, &str)> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+    let mut parts = line.splitn(2, char::is_whitespace);
+    let name = parts.next()?;
+    Some((name, parts.next().unwrap_or("").trim()))
+}
+
+/// Runs command line typed into console.
+pub fn execute(game: &mut Game, line: &str) -> CommandResult {
+    let (name, args) = split(line).ok_or_else(|| "Command is empty".to_owned())?;
+    match find(name) {
+        Some(command) => (command.run)(game, args),
+        None => Err(format!("Unknown command {}, type help to list commands", name)),
+    }
+}
+
+fn help(_: &mut Game, _: &str) -> CommandResult {
+    Ok(COMMANDS.iter()
+        .map(|command| format!("{} - {}", command.usage, command.help))
+        .collect::<Vec<String>>()
+        .join("\n"))
+}
+
+fn set(game: &mut Game, args: &str) -> CommandResult {
+    game.set_console_variable(args)
+}
+
+fn host(game: &mut Game, args: &str) -> CommandResult {
+    let port = if args.is_empty() {
+        DEFAULT_PORT
+    } else {
+        args.parse::<u16>().map_err(|_| "Invalid port, usage: host [port]".to_owned())?
+    };
+    game.host_match(None, port, DEFAULT_MAX_PLAYERS, None)
+}
+
+fn connect(game: &mut Game, args: &str) -> CommandResult {
+    if args.is_empty() {
+        return Err("Address is missing, usage: connect <address[:port]>".to_owned());
+    }
+    game.connect_to_server(args)
+}
+
+fn reset_range(game: &mut Game, _: &str) -> CommandResult {
+    if !game.level.as_ref().map_or(false, |level| level.options.is_practice()) {
+        return Err("There is no target range to reset!".to_owned());
+    }
+    game.events_sender.send(Message::ResetTargetRange).unwrap();
+    Ok("Target range reset".to_owned())
+}
+
+/// Settings file of export and import, default one if path is omitted.
+fn settings_path(args: &str) -> PathBuf {
+    PathBuf::from(if args.is_empty() { SETTINGS_EXPORT_PATH } else { args })
+}
+
+fn export(game: &mut Game, args: &str) -> CommandResult {
+    game.export_settings(&settings_path(args))
+}
+
+fn import(game: &mut Game, args: &str) -> CommandResult {
+    game.import_settings(&settings_path(args))
+}
+
+/// Input line of console with output of last commands above it.
+pub struct Console {
+    root: UINodeHandle,
+    text: UINodeHandle,
+    /// Line that is typed now, None when console is closed.
+    input: Option<String>,
+    output: VecDeque<String>,
+}
+
+impl Console {
+    pub fn new(ctx: &mut BuildContext, accessibility: &Accessibility) -> Self {
+        let text = TextBuilder::new(WidgetBuilder::new()
+            .with_margin(Thickness::uniform(5.0))
+            .with_foreground(Brush::Solid(Color::WHITE)))
+            .with_font(gui::load_font("data/ui/SquaresBold.ttf", 18.0))
+            .build(ctx);
+        let root = create_backdrop(ctx, accessibility, WidgetBuilder::new()
+            .with_visibility(false)
+            .with_horizontal_alignment(HorizontalAlignment::Left)
+            .with_vertical_alignment(VerticalAlignment::Top), 200, text);
+
+        Self {
+            root,
+            text,
+            input: None,
+            output: Default::default(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub fn open(&mut self, ui: &mut Gui) {
+        self.input = Some(String::new());
+        ui.send_message(WidgetMessage::visibility(self.root, true));
+        self.sync(ui);
+    }
+
+    pub fn close(&mut self, ui: &mut Gui) {
+        self.input = None;
+        ui.send_message(WidgetMessage::visibility(self.root, false));
+    }
+
+    pub fn push_char(&mut self, ui: &mut Gui, c: char) {
+        if let Some(input) = self.input.as_mut() {
+            if !c.is_control() && input.chars().count() < MAX_LINE_LENGTH {
+                input.push(c);
+                self.sync(ui);
+            }
+        }
+    }
+
+    pub fn pop_char(&mut self, ui: &mut Gui) {
+        if let Some(input) = self.input.as_mut() {
+            input.pop();
+            self.sync(ui);
+        }
+    }
+
+    /// Returns typed line and clears input, console stays open so output can be read.
+    pub fn take_line(&mut self, ui: &mut Gui) -> String {
+        let line = self.input.as_mut().map(std::mem::take).unwrap_or_default();
+        self.sync(ui);
+        line
+    }
+
+    /// Adds output of a command, multiline output takes several lines.
+    pub fn print(&mut self, ui: &mut Gui, text: &str) {
+        for line in text.lines() {
+            if self.output.len() >= OUTPUT_SIZE {
+                self.output.pop_front();
+            }
+            self.output.push_back(line.to_owned());
+        }
+        self.sync(ui);
+    }
+
+    fn sync(&self, ui: &mut Gui) {
+        let mut text = self.output
+            .iter()
+            .map(|line| line.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        if let Some(input) = self.input.as_ref() {
+            if !text.is_empty() {
+                text.push('\n');
+            }
+            text.push_str(&format!("> {}_", input));
+        }
+        ui.send_message(TextMessage::text(self.text, text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_is_split_into_name_and_arguments() {
+        assert_eq!(split("  set debug_draw 1 "), Some(("set", "debug_draw 1")));
+        assert_eq!(split("host"), Some(("host", "")));
+        assert_eq!(split("   "), None);
+    }
+
+    #[test]
+    fn commands_are_found_by_exact_name() {
+        assert_eq!(find("connect").map(|command| command.name), Some("connect"));
+        assert!(find("settings").is_none());
+        assert!(find("").is_none());
+    }
+
+    #[test]
+    fn command_table_is_consistent() {
+        for (i, command) in COMMANDS.iter().enumerate() {
+            assert!(command.usage.starts_with(command.name));
+            assert!(COMMANDS[i + 1..].iter().all(|other| other.name != command.name));
+        }
+    }
+
+    #[test]
+    fn settings_path_defaults_to_export_file() {
+        assert_eq!(settings_path(""), PathBuf::from(SETTINGS_EXPORT_PATH));
+        assert_eq!(settings_path("my.bin"), PathBuf::from("my.bin"));
+    }
+}
@@ -0,0 +1,284 @@
+//! Control points of domination. Control point is a mesh named `ControlPoint*`, points are
+//! labeled A, B, C... in the order of names of their meshes. Meshes are hidden and have no
+//! collision, level geometry under them holds actors. Maps without such meshes get points
+//! placed around items, so domination can be played on any map.
+//!
+//! Team that stands in a point alone captures it in capture time, point that has both teams
+//! in it is contested and its capture does not move. Team has to undo progress of other team
+//! first, unfinished capture fades away when capturing team leaves. Every held point gives
+//! its owner a point of team score every [`SCORE_INTERVAL`] seconds.
+
+use rg3d::core::{
+    math::{
+        vec3::Vec3,
+        aabb::AxisAlignedBoundingBox,
+    },
+    visitor::{Visit, Visitor, VisitResult},
+};
+use crate::character::Team;
+
+/// Labels of control points, maps can't have more points than there are labels.
+pub const CONTROL_POINT_LABELS: [&str; 5] = ["A", "B", "C", "D", "E"];
+/// Time in seconds that a team needs to capture a point uncontested.
+pub const DEFAULT_CAPTURE_TIME: f32 = 8.0;
+/// How often held points give score to their owners, in seconds.
+pub const SCORE_INTERVAL: f32 = 5.0;
+/// Vertical margin around point meshes, body of actor that stands on a point is above it.
+const REACH_HEIGHT: f32 = 1.0;
+/// Half of size of points that are placed around items on maps without point meshes.
+const FALLBACK_HALF_EXTENTS: Vec3 = Vec3 { x: 3.0, y: 2.0, z: 3.0 };
+/// Amount of points placed on maps without point meshes.
+const FALLBACK_COUNT: usize = 3;
+/// Part of capture that is lost per second when capturing team leaves the point.
+const CAPTURE_DECAY_RATE: f32 = 0.1;
+
+/// State of a point as it is shown to players.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum ControlPointState {
+    Neutral,
+    /// Team captures the point, progress is in [0; 1] range. Point may still have an owner.
+    Capturing { team: Team, progress: f32 },
+    Owned(Team),
+}
+
+pub struct ControlPoint {
+    /// Index in `CONTROL_POINT_LABELS`.
+    label: u32,
+    volume: AxisAlignedBoundingBox,
+    owner: Team,
+    /// Team whose capture is in progress, None if nobody captures the point.
+    capturing: Team,
+    progress: f32,
+    /// Both teams are in the point.
+    contested: bool,
+}
+
+impl Default for ControlPoint {
+    fn default() -> Self {
+        Self {
+            label: 0,
+            volume: Default::default(),
+            owner: Team::None,
+            capturing: Team::None,
+            progress: 0.0,
+            contested: false,
+        }
+    }
+}
+
+impl ControlPoint {
+    /// Creates point that covers given bounds of its mesh.
+    pub fn new(label: usize, bounds: AxisAlignedBoundingBox) -> Self {
+        Self {
+            label: label as u32,
+            volume: AxisAlignedBoundingBox {
+                min: Vec3::new(bounds.min.x, bounds.min.y - REACH_HEIGHT, bounds.min.z),
+                max: Vec3::new(bounds.max.x, bounds.max.y + REACH_HEIGHT, bounds.max.z),
+            },
+            ..Default::default()
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        CONTROL_POINT_LABELS[self.label as usize % CONTROL_POINT_LABELS.len()]
+    }
+
+    pub fn volume(&self) -> &AxisAlignedBoundingBox {
+        &self.volume
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.volume.min + self.volume.max).scale(0.5)
+    }
+
+    pub fn owner(&self) -> Team {
+        self.owner
+    }
+
+    pub fn is_contested(&self) -> bool {
+        self.contested
+    }
+
+    pub fn state(&self) -> ControlPointState {
+        if self.capturing != Team::None && self.progress > 0.0 {
+            ControlPointState::Capturing { team: self.capturing, progress: self.progress }
+        } else if self.owner != Team::None {
+            ControlPointState::Owned(self.owner)
+        } else {
+            ControlPointState::Neutral
+        }
+    }
+
+    /// Whether other team captures point of given team, so it needs defense.
+    pub fn is_threatened(&self, team: Team) -> bool {
+        self.owner == team && (self.contested || self.capturing != Team::None)
+    }
+
+    /// Moves capture by teams of actors that stand in the point. Returns team that captured
+    /// the point on this update.
+    fn update(&mut self, actors: &[(Team, Vec3)], capture_time: f32, dt: f32) -> Option<Team> {
+        let volume = self.volume;
+        let (red, blue) = actors.iter()
+            .filter(|(_, position)| volume.is_contains_point(*position))
+            .fold((0, 0), |(red, blue), (team, _)| match team {
+                Team::Red => (red + 1, blue),
+                Team::Blue => (red, blue + 1),
+                Team::None => (red, blue),
+            });
+        self.contested = red > 0 && blue > 0;
+        if self.contested {
+            return None;
+        }
+
+        let present = if red > 0 {
+            Team::Red
+        } else if blue > 0 {
+            Team::Blue
+        } else {
+            Team::None
+        };
+        let step = dt / capture_time.max(std::f32::EPSILON);
+        if present != Team::None && present != self.owner {
+            if self.capturing == present || self.progress <= 0.0 {
+                self.capturing = present;
+                self.progress += step;
+                if self.progress >= 1.0 {
+                    self.owner = present;
+                    self.capturing = Team::None;
+                    self.progress = 0.0;
+                    return Some(present);
+                }
+            } else {
+                // Progress of other team is undone first.
+                self.progress -= step;
+            }
+        } else if self.progress > 0.0 {
+            // Owner undoes capture as fast as it was made, empty point forgets it slowly.
+            self.progress -= if present == Team::None { CAPTURE_DECAY_RATE * dt } else { step };
+        }
+        if self.progress <= 0.0 {
+            self.progress = 0.0;
+            self.capturing = Team::None;
+        }
+        None
+    }
+}
+
+impl Visit for ControlPoint {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.label.visit("Label", visitor)?;
+        self.volume.visit("Volume", visitor)?;
+        self.owner.visit("Owner", visitor)?;
+        self.capturing.visit("Capturing", visitor)?;
+        self.progress.visit("Progress", visitor)?;
+        self.contested.visit("Contested", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Something that happened to control points during an update.
+pub enum ControlPointEvent {
+    Captured { label: &'static str, team: Team },
+    /// Team earns given amount of score for points it holds.
+    Scored { team: Team, amount: u32 },
+}
+
+pub struct ControlPointContainer {
+    points: Vec<ControlPoint>,
+    /// Time left until held points give score.
+    score_timer: f32,
+}
+
+impl Default for ControlPointContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ControlPointContainer {
+    pub fn new() -> Self {
+        Self {
+            points: Vec::new(),
+            score_timer: SCORE_INTERVAL,
+        }
+    }
+
+    /// Creates points from bounds of point meshes, extra meshes over amount of labels are
+    /// ignored. If there are no meshes, points are placed around given item positions as far
+    /// from each other as possible.
+    pub fn build(&mut self, meshes: &[AxisAlignedBoundingBox], items: &[Vec3]) {
+        if meshes.len() > CONTROL_POINT_LABELS.len() {
+            println!("Map has {} control points, only first {} are used!", meshes.len(), CONTROL_POINT_LABELS.len());
+        }
+        let mut bounds = meshes.iter()
+            .take(CONTROL_POINT_LABELS.len())
+            .cloned()
+            .collect::<Vec<_>>();
+        if bounds.is_empty() && !items.is_empty() {
+            println!("Map has no control points, they're placed around items.");
+            let mut centers = vec![items[0]];
+            while centers.len() < FALLBACK_COUNT.min(items.len()) {
+                let distance = |item: &Vec3| centers.iter()
+                    .map(|center| center.sqr_distance(item))
+                    .fold(std::f32::MAX, f32::min);
+                let farthest = items.iter()
+                    .max_by(|a, b| distance(a).partial_cmp(&distance(b)).unwrap_or(std::cmp::Ordering::Equal))
+                    .cloned()
+                    .unwrap();
+                centers.push(farthest);
+            }
+            bounds = centers.into_iter()
+                .map(|center| AxisAlignedBoundingBox {
+                    min: center - FALLBACK_HALF_EXTENTS,
+                    max: center + FALLBACK_HALF_EXTENTS,
+                })
+                .collect();
+        }
+        self.points = bounds.into_iter()
+            .enumerate()
+            .map(|(label, bounds)| ControlPoint::new(label, bounds))
+            .collect();
+    }
+
+    pub fn points(&self) -> &[ControlPoint] {
+        &self.points
+    }
+
+    /// Moves captures and gives score for held points. `actors` are teams and positions of
+    /// alive actors.
+    pub fn update(&mut self, actors: &[(Team, Vec3)], capture_time: f32, dt: f32) -> Vec<ControlPointEvent> {
+        let mut events = Vec::new();
+        for point in self.points.iter_mut() {
+            if let Some(team) = point.update(actors, capture_time, dt) {
+                events.push(ControlPointEvent::Captured { label: point.label(), team });
+            }
+        }
+
+        self.score_timer -= dt;
+        if self.score_timer <= 0.0 {
+            self.score_timer += SCORE_INTERVAL;
+            for &team in [Team::Red, Team::Blue].iter() {
+                let amount = self.points.iter().filter(|point| point.owner == team).count() as u32;
+                if amount > 0 {
+                    events.push(ControlPointEvent::Scored { team, amount });
+                }
+            }
+        }
+
+        events
+    }
+}
+
+impl Visit for ControlPointContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.points.visit("Points", visitor)?;
+        self.score_timer.visit("ScoreTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
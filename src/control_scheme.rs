@@ -1,15 +1,52 @@
-use rg3d::event::VirtualKeyCode;
+use rg3d::{
+    core::visitor::{Visit, Visitor, VisitResult},
+    event::{VirtualKeyCode, KeyboardInput},
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+};
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ControlButton {
     Mouse(u8),
     Key(VirtualKeyCode),
+    /// Physical key that has no virtual key code, for example extra keys of non-US
+    /// keyboards. Scan codes are platform-specific, but so is the rest of the settings.
+    ScanCode(u32),
     WheelUp,
     WheelDown,
 }
 
+thread_local! {
+    /// Characters that keys produced when pressed, filled while the game runs. Used to show
+    /// keys as they are labeled on keyboard of the player instead of US names.
+    static KEY_LABELS: RefCell<HashMap<ControlButton, char>> = RefCell::new(HashMap::new());
+}
+
+/// Remembers character that was typed by given key.
+pub fn learn_key_label(button: ControlButton, c: char) {
+    if !c.is_control() && !c.is_whitespace() {
+        KEY_LABELS.with(|labels| {
+            let c = c.to_uppercase().next().unwrap_or(c);
+            labels.borrow_mut().insert(button, c);
+        });
+    }
+}
+
 impl ControlButton {
-    pub fn name(self) -> &'static str {
+    /// Returns button for pressed or released key. Keys without virtual key code are still
+    /// bindable by their scan code.
+    pub fn from_key(input: &KeyboardInput) -> Self {
+        match input.virtual_keycode {
+            Some(code) => ControlButton::Key(code),
+            None => ControlButton::ScanCode(input.scancode),
+        }
+    }
+
+    /// Returns stable name of the button, used when key bindings are exported. For names
+    /// that are shown to player use [`ControlButton::label`].
+    pub fn name(self) -> String {
         match self {
             ControlButton::Mouse(index) => {
                 match index {
@@ -19,20 +56,153 @@ impl ControlButton {
                     4 => "MB4",
                     5 => "MB5",
                     _ => "Unknown"
-                }
+                }.to_owned()
             }
-            ControlButton::Key(code) => rg3d::utils::virtual_key_code_name(code),
-            ControlButton::WheelUp => "Wheel Up",
-            ControlButton::WheelDown => "Wheel Down",
+            ControlButton::Key(code) => rg3d::utils::virtual_key_code_name(code).to_owned(),
+            ControlButton::ScanCode(code) => format!("Scancode {}", code),
+            ControlButton::WheelUp => "Wheel Up".to_owned(),
+            ControlButton::WheelDown => "Wheel Down".to_owned(),
+        }
+    }
+
+    /// Returns name of the button as it is labeled on keyboard layout of the player, if the
+    /// key was pressed at least once with a printable character, otherwise same as name.
+    pub fn label(self) -> String {
+        KEY_LABELS
+            .with(|labels| labels.borrow().get(&self).cloned())
+            .map_or_else(|| self.name(), |c| c.to_string())
+    }
+
+    /// Finds button by its name, see [`ControlButton::name`]. Only keys from
+    /// [`BINDABLE_KEYS`] and scan codes can be found.
+    pub fn from_name(name: &str) -> Option<Self> {
+        if let Some(code) = name.strip_prefix("Scancode ") {
+            return code.parse().ok().map(ControlButton::ScanCode);
+        }
+        match name {
+            "LMB" => Some(ControlButton::Mouse(1)),
+            "RMB" => Some(ControlButton::Mouse(2)),
+            "MMB" => Some(ControlButton::Mouse(3)),
+            "MB4" => Some(ControlButton::Mouse(4)),
+            "MB5" => Some(ControlButton::Mouse(5)),
+            "Wheel Up" => Some(ControlButton::WheelUp),
+            "Wheel Down" => Some(ControlButton::WheelDown),
+            _ => BINDABLE_KEYS
+                .iter()
+                .find(|&&code| rg3d::utils::virtual_key_code_name(code) == name)
+                .map(|&code| ControlButton::Key(code)),
         }
     }
 }
 
+impl Visit for ControlButton {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        // Buttons are stored by their names, same as in exported settings. Button with name
+        // that can't be restored keeps its current value.
+        let mut button_name = self.name();
+        button_name.visit("Name", visitor)?;
+        if visitor.is_reading() {
+            if let Some(button) = Self::from_name(&button_name) {
+                *self = button;
+            }
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Keys that can be restored from their names, used when key bindings are imported from
+/// a text file.
+pub const BINDABLE_KEYS: [VirtualKeyCode; 97] = [
+    VirtualKeyCode::A, VirtualKeyCode::B, VirtualKeyCode::C, VirtualKeyCode::D,
+    VirtualKeyCode::E, VirtualKeyCode::F, VirtualKeyCode::G, VirtualKeyCode::H,
+    VirtualKeyCode::I, VirtualKeyCode::J, VirtualKeyCode::K, VirtualKeyCode::L,
+    VirtualKeyCode::M, VirtualKeyCode::N, VirtualKeyCode::O, VirtualKeyCode::P,
+    VirtualKeyCode::Q, VirtualKeyCode::R, VirtualKeyCode::S, VirtualKeyCode::T,
+    VirtualKeyCode::U, VirtualKeyCode::V, VirtualKeyCode::W, VirtualKeyCode::X,
+    VirtualKeyCode::Y, VirtualKeyCode::Z,
+    VirtualKeyCode::Key0, VirtualKeyCode::Key1, VirtualKeyCode::Key2, VirtualKeyCode::Key3,
+    VirtualKeyCode::Key4, VirtualKeyCode::Key5, VirtualKeyCode::Key6, VirtualKeyCode::Key7,
+    VirtualKeyCode::Key8, VirtualKeyCode::Key9,
+    VirtualKeyCode::F1, VirtualKeyCode::F2, VirtualKeyCode::F3, VirtualKeyCode::F4,
+    VirtualKeyCode::F5, VirtualKeyCode::F6, VirtualKeyCode::F7, VirtualKeyCode::F8,
+    VirtualKeyCode::F9, VirtualKeyCode::F10, VirtualKeyCode::F11, VirtualKeyCode::F12,
+    VirtualKeyCode::Numpad0, VirtualKeyCode::Numpad1, VirtualKeyCode::Numpad2, VirtualKeyCode::Numpad3,
+    VirtualKeyCode::Numpad4, VirtualKeyCode::Numpad5, VirtualKeyCode::Numpad6, VirtualKeyCode::Numpad7,
+    VirtualKeyCode::Numpad8, VirtualKeyCode::Numpad9,
+    VirtualKeyCode::Space, VirtualKeyCode::Tab, VirtualKeyCode::Return, VirtualKeyCode::Back,
+    VirtualKeyCode::Escape, VirtualKeyCode::Capital,
+    VirtualKeyCode::LShift, VirtualKeyCode::RShift, VirtualKeyCode::LControl, VirtualKeyCode::RControl,
+    VirtualKeyCode::LAlt, VirtualKeyCode::RAlt,
+    VirtualKeyCode::Left, VirtualKeyCode::Right, VirtualKeyCode::Up, VirtualKeyCode::Down,
+    VirtualKeyCode::Insert, VirtualKeyCode::Delete, VirtualKeyCode::Home, VirtualKeyCode::End,
+    VirtualKeyCode::PageUp, VirtualKeyCode::PageDown,
+    VirtualKeyCode::Grave, VirtualKeyCode::Minus, VirtualKeyCode::Equals, VirtualKeyCode::LBracket,
+    VirtualKeyCode::RBracket, VirtualKeyCode::Semicolon, VirtualKeyCode::Apostrophe, VirtualKeyCode::Comma,
+    VirtualKeyCode::Period, VirtualKeyCode::Slash, VirtualKeyCode::Backslash,
+    VirtualKeyCode::Add, VirtualKeyCode::Subtract, VirtualKeyCode::Multiply, VirtualKeyCode::Divide,
+    VirtualKeyCode::NumpadEnter, VirtualKeyCode::Decimal,
+];
+
 pub struct ControlButtonDefinition {
     pub description: String,
     pub button: ControlButton,
 }
 
+/// What happens when player picks up weapon that player did not have.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum PickupSwitch {
+    Always,
+    /// Switches only if picked up weapon ranks higher than current one.
+    IfBetter,
+    Never,
+}
+
+impl Default for PickupSwitch {
+    fn default() -> Self {
+        PickupSwitch::Always
+    }
+}
+
+impl PickupSwitch {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(PickupSwitch::Always),
+            1 => Ok(PickupSwitch::IfBetter),
+            2 => Ok(PickupSwitch::Never),
+            _ => Err(format!("Invalid pickup switch mode {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            PickupSwitch::Always => 0,
+            PickupSwitch::IfBetter => 1,
+            PickupSwitch::Never => 2,
+        }
+    }
+}
+
+impl Visit for PickupSwitch {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+pub const DEFAULT_INPUT_BUFFER: f32 = 0.1;
+/// Longest input buffer in seconds that can be set in options.
+pub const MAX_INPUT_BUFFER: f32 = 0.3;
+
 pub struct ControlScheme {
     pub move_forward: ControlButtonDefinition,
     pub move_backward: ControlButtonDefinition,
@@ -41,13 +211,62 @@ pub struct ControlScheme {
     pub jump: ControlButtonDefinition,
     pub crouch: ControlButtonDefinition,
     pub shoot: ControlButtonDefinition,
+    pub alt_fire: ControlButtonDefinition,
+    pub aim: ControlButtonDefinition,
     pub next_weapon: ControlButtonDefinition,
     pub prev_weapon: ControlButtonDefinition,
     pub run: ControlButtonDefinition,
-    pub mouse_sens: f32,
+    pub toggle_camera: ControlButtonDefinition,
+    pub inspect_weapon: ControlButtonDefinition,
+    pub reload: ControlButtonDefinition,
+    /// Uses focused object - door, button, item when manual pickup is enabled and so on.
+    pub use_object: ControlButtonDefinition,
+    pub write_note: ControlButtonDefinition,
+    /// Opens callout wheel while held, see `comms` module.
+    pub callout_wheel: ControlButtonDefinition,
+    /// Shows leader board while held.
+    pub show_scores: ControlButtonDefinition,
+    pub frame_profile: ControlButtonDefinition,
+    /// Toggles debug geometry, see `debug_draw` module.
+    pub debug_draw: ControlButtonDefinition,
+    /// Horizontal mouse sensitivity, applied to yaw.
+    pub mouse_sens_x: f32,
+    /// Vertical mouse sensitivity, applied to pitch.
+    pub mouse_sens_y: f32,
+    /// Whether changing sensitivity of one axis in options changes the other one too.
+    pub link_mouse_axes: bool,
+    /// Part of mouse sensitivity that is left while aiming down sights.
+    pub ads_sensitivity: f32,
     pub mouse_y_inverse: bool,
     pub smooth_mouse: bool,
+    /// Part of remaining rotation that is left for next frames (at 60 FPS) when smooth mouse
+    /// is enabled, larger values make look smoother but slower to respond.
+    pub mouse_smoothing: f32,
+    /// Whether fast mouse movements rotate camera more than slow movements.
+    pub mouse_acceleration: bool,
+    /// Whether camera bobs while walking, weapon bob is not affected.
     pub shake_camera: bool,
+    /// Multiplier for vertical head bob while walking and running, zero disables it.
+    pub head_bob: f32,
+    /// Multiplier for screen shake from explosions, heavy weapons and damage, zero disables it.
+    pub screen_shake: f32,
+    /// Whether empty weapon is switched to next weapon with ammo on trigger pull.
+    pub auto_switch_weapon: bool,
+    /// Whether newly picked up weapon becomes current one.
+    pub pickup_switch: PickupSwitch,
+    /// Whether run button toggles running instead of being held. Toggled running stops
+    /// when player stops moving.
+    pub toggle_sprint: bool,
+    /// Whether empty magazine is reloaded on trigger pull, otherwise weapon just clicks.
+    pub auto_reload: bool,
+    /// Whether weapon with partially empty magazine starts reloading when it is switched to.
+    pub reload_on_switch: bool,
+    /// Time in seconds during which pressed jump or fire is remembered and performed as soon
+    /// as it becomes possible, zero performs actions only if they're possible right away.
+    pub input_buffer: f32,
+    /// Whether items are picked up only by pressing use button, otherwise they're picked up
+    /// by walking over them.
+    pub manual_pickup: bool,
 }
 
 impl Default for ControlScheme {
@@ -81,6 +300,14 @@ impl Default for ControlScheme {
                 description: "Shoot".to_string(),
                 button: ControlButton::Mouse(1),
             },
+            alt_fire: ControlButtonDefinition {
+                description: "Alternate Fire".to_string(),
+                button: ControlButton::Mouse(3),
+            },
+            aim: ControlButtonDefinition {
+                description: "Aim Down Sights".to_string(),
+                button: ControlButton::Mouse(2),
+            },
             next_weapon: ControlButtonDefinition {
                 description: "Next Weapon".to_string(),
                 button: ControlButton::WheelUp,
@@ -93,16 +320,66 @@ impl Default for ControlScheme {
                 description: "Run".to_string(),
                 button: ControlButton::Key(VirtualKeyCode::LShift),
             },
-            mouse_sens: 0.3,
+            toggle_camera: ControlButtonDefinition {
+                description: "Toggle Camera".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::V),
+            },
+            inspect_weapon: ControlButtonDefinition {
+                description: "Inspect Weapon".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::F),
+            },
+            reload: ControlButtonDefinition {
+                description: "Reload".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::R),
+            },
+            use_object: ControlButtonDefinition {
+                description: "Use".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::E),
+            },
+            write_note: ControlButtonDefinition {
+                description: "Write Note".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::Y),
+            },
+            callout_wheel: ControlButtonDefinition {
+                description: "Callout Wheel".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::Z),
+            },
+            show_scores: ControlButtonDefinition {
+                description: "Show Scores".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::Tab),
+            },
+            frame_profile: ControlButtonDefinition {
+                description: "Frame Profile".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::F3),
+            },
+            debug_draw: ControlButtonDefinition {
+                description: "Debug Draw".to_string(),
+                button: ControlButton::Key(VirtualKeyCode::F4),
+            },
+            mouse_sens_x: 0.3,
+            mouse_sens_y: 0.3,
+            link_mouse_axes: true,
+            ads_sensitivity: 0.6,
             mouse_y_inverse: false,
             smooth_mouse: true,
+            mouse_smoothing: 0.8,
+            mouse_acceleration: false,
             shake_camera: true,
+            head_bob: 1.0,
+            screen_shake: 1.0,
+            auto_switch_weapon: true,
+            pickup_switch: Default::default(),
+            toggle_sprint: false,
+            auto_reload: true,
+            reload_on_switch: false,
+            input_buffer: DEFAULT_INPUT_BUFFER,
+            manual_pickup: false,
         }
     }
 }
 
 impl ControlScheme {
-    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 10] {
+    pub fn buttons_mut(&mut self) -> [&mut ControlButtonDefinition; 21] {
         [
             &mut self.move_forward,
             &mut self.move_backward,
@@ -111,13 +388,24 @@ impl ControlScheme {
             &mut self.jump,
             &mut self.crouch,
             &mut self.shoot,
+            &mut self.alt_fire,
+            &mut self.aim,
             &mut self.next_weapon,
             &mut self.prev_weapon,
             &mut self.run,
+            &mut self.toggle_camera,
+            &mut self.inspect_weapon,
+            &mut self.reload,
+            &mut self.use_object,
+            &mut self.write_note,
+            &mut self.callout_wheel,
+            &mut self.show_scores,
+            &mut self.frame_profile,
+            &mut self.debug_draw,
         ]
     }
 
-    pub fn buttons(&self) -> [&ControlButtonDefinition; 10] {
+    pub fn buttons(&self) -> [&ControlButtonDefinition; 21] {
         [
             &self.move_forward,
             &self.move_backward,
@@ -126,9 +414,20 @@ impl ControlScheme {
             &self.jump,
             &self.crouch,
             &self.shoot,
+            &self.alt_fire,
+            &self.aim,
             &self.next_weapon,
             &self.prev_weapon,
             &self.run,
+            &self.toggle_camera,
+            &self.inspect_weapon,
+            &self.reload,
+            &self.use_object,
+            &self.write_note,
+            &self.callout_wheel,
+            &self.show_scores,
+            &self.frame_profile,
+            &self.debug_draw,
         ]
     }
 
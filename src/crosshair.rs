@@ -0,0 +1,210 @@
+//! Crosshair is built from plain rectangles, so its style, color and size can be changed
+//! at runtime without any textures.
+
+use rg3d::{
+    core::{
+        color::Color,
+        math::vec2::Vec2,
+        visitor::{Visit, Visitor, VisitResult},
+    },
+    gui::{
+        border::BorderBuilder,
+        canvas::CanvasBuilder,
+        widget::WidgetBuilder,
+        node::StubNode,
+        brush::Brush,
+        message::WidgetMessage,
+        Thickness,
+        HorizontalAlignment,
+        VerticalAlignment,
+    },
+};
+use crate::{
+    UINodeHandle,
+    BuildContext,
+    Gui,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CrosshairStyle {
+    Cross,
+    Dot,
+    Circle,
+}
+
+impl Default for CrosshairStyle {
+    fn default() -> Self {
+        CrosshairStyle::Cross
+    }
+}
+
+impl CrosshairStyle {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(CrosshairStyle::Cross),
+            1 => Ok(CrosshairStyle::Dot),
+            2 => Ok(CrosshairStyle::Circle),
+            _ => Err(format!("Invalid crosshair style {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            CrosshairStyle::Cross => 0,
+            CrosshairStyle::Dot => 1,
+            CrosshairStyle::Circle => 2,
+        }
+    }
+}
+
+impl Visit for CrosshairStyle {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CrosshairConfig {
+    pub style: CrosshairStyle,
+    pub color: Color,
+    /// Width of lines or dots in pixels.
+    pub thickness: f32,
+    /// Distance from center of screen to inner end of lines, for circle style - to
+    /// inner edge of circle.
+    pub gap: f32,
+    /// Length of lines of cross style, other styles ignore it.
+    pub length: f32,
+    /// Whether crosshair briefly changes its color when player's shot damages someone.
+    pub hit_confirm: bool,
+    pub hit_confirm_color: Color,
+}
+
+impl Default for CrosshairConfig {
+    fn default() -> Self {
+        Self {
+            style: Default::default(),
+            color: Color::WHITE,
+            thickness: 2.0,
+            gap: 4.0,
+            length: 8.0,
+            hit_confirm: true,
+            hit_confirm_color: Color::opaque(255, 0, 0),
+        }
+    }
+}
+
+impl Visit for CrosshairConfig {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.style.visit("Style", visitor)?;
+        self.color.visit("Color", visitor)?;
+        self.thickness.visit("Thickness", visitor)?;
+        self.gap.visit("Gap", visitor)?;
+        self.length.visit("Length", visitor)?;
+        self.hit_confirm.visit("HitConfirm", visitor)?;
+        self.hit_confirm_color.visit("HitConfirmColor", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Crosshair shape of a weapon, it replaces style of user crosshair while weapon is active.
+/// Color and thickness always come from user config.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CrosshairProfile {
+    pub style: CrosshairStyle,
+    /// Multiplier for gap and length of user config, so weapons with wide spread can
+    /// have wide crosshair.
+    pub size: f32,
+}
+
+impl CrosshairProfile {
+    /// Returns user config with style and size of this profile.
+    pub fn apply(&self, config: &CrosshairConfig) -> CrosshairConfig {
+        CrosshairConfig {
+            style: self.style,
+            gap: config.gap * self.size,
+            length: config.length * self.size,
+            ..*config
+        }
+    }
+}
+
+/// Returns rectangles (position and size) that form crosshair in a square with given side.
+fn make_rects(config: &CrosshairConfig, side: f32, scale: f32) -> Vec<(Vec2, Vec2)> {
+    let center = side * 0.5;
+    let thickness = config.thickness * scale;
+    let gap = config.gap * scale;
+    let length = config.length * scale;
+    let half = thickness * 0.5;
+
+    match config.style {
+        CrosshairStyle::Cross => vec![
+            (Vec2::new(center - gap - length, center - half), Vec2::new(length, thickness)),
+            (Vec2::new(center + gap, center - half), Vec2::new(length, thickness)),
+            (Vec2::new(center - half, center - gap - length), Vec2::new(thickness, length)),
+            (Vec2::new(center - half, center + gap), Vec2::new(thickness, length)),
+        ],
+        CrosshairStyle::Dot => vec![
+            (Vec2::new(center - thickness, center - thickness), Vec2::new(thickness * 2.0, thickness * 2.0)),
+        ],
+        CrosshairStyle::Circle => {
+            // Circle is made of small squares, there are enough of them to make circle look solid.
+            let radius = gap + half;
+            let circumference = 2.0 * std::f32::consts::PI * radius;
+            let count = ((circumference / thickness.max(1.0)) as usize).max(8);
+            (0..count)
+                .map(|i| {
+                    let angle = i as f32 / count as f32 * 2.0 * std::f32::consts::PI;
+                    let position = Vec2::new(
+                        center + radius * angle.cos() - half,
+                        center + radius * angle.sin() - half);
+                    (position, Vec2::new(thickness, thickness))
+                })
+                .collect()
+        }
+    }
+}
+
+/// Creates crosshair widget, it is centered inside the area given by widget builder.
+pub fn build(ctx: &mut BuildContext, widget_builder: WidgetBuilder<(), StubNode>, config: &CrosshairConfig, scale: f32) -> UINodeHandle {
+    let side = ((config.gap + config.length) * 2.0 + config.thickness * 2.0) * scale;
+
+    let parts = make_rects(config, side, scale)
+        .into_iter()
+        .map(|(position, size)| {
+            BorderBuilder::new(WidgetBuilder::new()
+                .with_desired_position(position)
+                .with_width(size.x)
+                .with_height(size.y)
+                .with_background(Brush::Solid(config.color)))
+                .with_stroke_thickness(Thickness::uniform(0.0))
+                .build(ctx)
+        })
+        .collect::<Vec<UINodeHandle>>();
+
+    CanvasBuilder::new(widget_builder
+        .with_width(side)
+        .with_height(side)
+        .with_horizontal_alignment(HorizontalAlignment::Center)
+        .with_vertical_alignment(VerticalAlignment::Center)
+        .with_children(&parts))
+        .build(ctx)
+}
+
+/// Paints every part of crosshair with given color, shape stays the same.
+pub fn set_color(ui: &mut Gui, crosshair: UINodeHandle, color: Color) {
+    let parts = ui.node(crosshair).children().to_vec();
+    for part in parts {
+        ui.send_message(WidgetMessage::background(part, Brush::Solid(color)));
+    }
+}
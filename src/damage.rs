@@ -0,0 +1,333 @@
+//! Damage multipliers are global balancing knobs of a match. They're applied to damage from
+//! every source - projectiles, explosions, melee attacks and environment, so difficulty can
+//! be tuned without changing weapon definitions. Rules of damage from own explosions and
+//! multipliers of hit regions live here as well, as does regeneration of player health.
+
+use rg3d::core::visitor::{Visit, Visitor, VisitResult};
+
+/// Names of multipliers that can be changed from console by `set <name> <value>`.
+pub const DAMAGE_MULTIPLIER_NAMES: [&str; 3] = ["dmg_dealt", "dmg_taken", "bot_dmg"];
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct DamageMultipliers {
+    /// Multiplier for damage dealt by player.
+    pub player_dealt: f32,
+    /// Multiplier for damage taken by player from any source.
+    pub player_taken: f32,
+    /// Multiplier for damage dealt by bots, to player and to other bots. Damage that bot
+    /// deals to player is affected by both this and player taken multiplier.
+    pub bot_dealt: f32,
+}
+
+impl Default for DamageMultipliers {
+    fn default() -> Self {
+        Self {
+            player_dealt: 1.0,
+            player_taken: 1.0,
+            bot_dealt: 1.0,
+        }
+    }
+}
+
+impl DamageMultipliers {
+    /// Returns damage after multipliers of attacker and victim were applied.
+    pub fn apply(&self, amount: f32, attacker: DamageSource, victim_is_player: bool) -> f32 {
+        let mut amount = match attacker {
+            DamageSource::Player => amount * self.player_dealt,
+            DamageSource::Bot => amount * self.bot_dealt,
+            DamageSource::Environment => amount,
+        };
+        if victim_is_player {
+            amount *= self.player_taken;
+        }
+        amount
+    }
+
+    /// Sets multiplier by its console name, see [`DAMAGE_MULTIPLIER_NAMES`].
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), String> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!("damage multiplier must be a non-negative number, got {}", value));
+        }
+        match name {
+            "dmg_dealt" => self.player_dealt = value,
+            "dmg_taken" => self.player_taken = value,
+            "bot_dmg" => self.bot_dealt = value,
+            _ => return Err(format!("unknown damage multiplier {}, expected one of {:?}", name, DAMAGE_MULTIPLIER_NAMES)),
+        }
+        Ok(())
+    }
+}
+
+impl Visit for DamageMultipliers {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.player_dealt.visit("PlayerDealt", visitor)?;
+        self.player_taken.visit("PlayerTaken", visitor)?;
+        self.bot_dealt.visit("BotDealt", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Names of health regeneration values that can be changed from console by
+/// `set <name> <value>`.
+pub const HEALTH_REGEN_NAMES: [&str; 3] = ["regen_rate", "regen_delay", "regen_cap"];
+
+/// Health of players regenerates after they took no damage for a while, as an alternative
+/// to healing by pickups only. Zero rate turns regeneration off, which is classic arena play.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct HealthRegen {
+    /// Health restored per second.
+    pub rate: f32,
+    /// Time in seconds without damage after which regeneration starts.
+    pub delay: f32,
+    /// Regeneration does not raise health above this value.
+    pub cap: f32,
+}
+
+impl Default for HealthRegen {
+    fn default() -> Self {
+        Self {
+            rate: 0.0,
+            delay: 5.0,
+            cap: 100.0,
+        }
+    }
+}
+
+impl HealthRegen {
+    pub fn is_enabled(&self) -> bool {
+        self.rate > 0.0
+    }
+
+    /// Sets value by its console name, see [`HEALTH_REGEN_NAMES`].
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), String> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!("health regeneration value must be a non-negative number, got {}", value));
+        }
+        match name {
+            "regen_rate" => self.rate = value,
+            "regen_delay" => self.delay = value,
+            "regen_cap" => self.cap = value,
+            _ => return Err(format!("unknown health regeneration value {}, expected one of {:?}", name, HEALTH_REGEN_NAMES)),
+        }
+        Ok(())
+    }
+}
+
+impl Visit for HealthRegen {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.rate.visit("Rate", visitor)?;
+        self.delay.visit("Delay", visitor)?;
+        self.cap.visit("Cap", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Who dealt damage, decides which multiplier is applied.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DamageSource {
+    Player,
+    Bot,
+    /// Death zones, falling and other damage that has no attacker.
+    Environment,
+}
+
+/// How damage was dealt, victim sees damage of each kind in its own color.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DamageKind {
+    /// Direct hit of a bullet or any other projectile.
+    Bullet,
+    Explosion,
+    Melee,
+    /// Hazards and other damage that is dealt by level itself.
+    Environment,
+}
+
+/// How much damage actor takes from its own explosions.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SelfDamage {
+    Full,
+    Reduced,
+    None,
+}
+
+/// Every self damage mode with its name, in the order of ids.
+pub const SELF_DAMAGE_MODES: [(&str, SelfDamage); 3] = [
+    ("Full Damage", SelfDamage::Full),
+    ("Reduced Damage", SelfDamage::Reduced),
+    ("No Damage", SelfDamage::None),
+];
+
+impl Default for SelfDamage {
+    fn default() -> Self {
+        SelfDamage::None
+    }
+}
+
+impl SelfDamage {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match SELF_DAMAGE_MODES.get(id as usize) {
+            Some((_, mode)) => Ok(*mode),
+            None => Err(format!("Invalid self damage mode {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            SelfDamage::Full => 0,
+            SelfDamage::Reduced => 1,
+            SelfDamage::None => 2,
+        }
+    }
+
+    /// Returns part of damage that actor takes from its own explosion.
+    pub fn scale(self) -> f32 {
+        match self {
+            SelfDamage::Full => 1.0,
+            SelfDamage::Reduced => 0.35,
+            SelfDamage::None => 0.0,
+        }
+    }
+}
+
+impl Visit for SelfDamage {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Maximum multiplier of knockback that actor gets from its own explosions.
+pub const MAX_SELF_KNOCKBACK: f32 = 2.0;
+
+/// Rules of rocket jumps. Damage and push that actor gets from its own explosions are tuned
+/// separately, so rocket jumps can be free, cost some health or be as painful as enemy rocket.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RocketJump {
+    pub self_damage: SelfDamage,
+    /// Multiplier of knockback from own explosions, zero disables rocket jumps.
+    pub self_knockback: f32,
+}
+
+impl Default for RocketJump {
+    fn default() -> Self {
+        Self {
+            self_damage: Default::default(),
+            self_knockback: 1.0,
+        }
+    }
+}
+
+impl RocketJump {
+    /// Returns damage that actor takes when its own explosion deals given amount to it.
+    pub fn scale_self_damage(&self, amount: f32) -> f32 {
+        amount * self.self_damage.scale()
+    }
+}
+
+impl Visit for RocketJump {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.self_damage.visit("SelfDamage", visitor)?;
+        self.self_knockback.visit("SelfKnockback", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Part of body hit by projectile, decides which of [`HitMultipliers`] is applied.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HitRegion {
+    Head,
+    Torso,
+    /// Legs, body of actor is a capsule so arms can't be told apart from torso.
+    Limbs,
+}
+
+impl HitRegion {
+    /// Resolves region by height of hit point over center of body capsule. `half_height` is
+    /// half of height of cylindrical part of capsule, so head is the upper cap of capsule and
+    /// limbs are its lower half.
+    pub fn from_height(offset: f32, half_height: f32) -> Self {
+        if offset > half_height {
+            HitRegion::Head
+        } else if offset < 0.0 {
+            HitRegion::Limbs
+        } else {
+            HitRegion::Torso
+        }
+    }
+}
+
+/// Names of hit multipliers that can be changed from console by `set <name> <value>`.
+pub const HIT_MULTIPLIER_NAMES: [&str; 3] = ["hit_head", "hit_torso", "hit_limbs"];
+
+pub const MAX_HIT_MULTIPLIER: f32 = 4.0;
+
+/// Multipliers of damage of projectiles by part of body they hit. Explosions, melee attacks
+/// and environment hit no region and are never scaled by these.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct HitMultipliers {
+    pub head: f32,
+    pub torso: f32,
+    pub limbs: f32,
+}
+
+impl Default for HitMultipliers {
+    fn default() -> Self {
+        Self {
+            head: 1.5,
+            torso: 1.0,
+            limbs: 0.75,
+        }
+    }
+}
+
+impl HitMultipliers {
+    pub fn get(&self, region: HitRegion) -> f32 {
+        match region {
+            HitRegion::Head => self.head,
+            HitRegion::Torso => self.torso,
+            HitRegion::Limbs => self.limbs,
+        }
+    }
+
+    /// Sets multiplier by its console name, see [`HIT_MULTIPLIER_NAMES`].
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), String> {
+        if !value.is_finite() || value < 0.0 || value > MAX_HIT_MULTIPLIER {
+            return Err(format!("hit multiplier must be a number in range 0..{}, got {}", MAX_HIT_MULTIPLIER, value));
+        }
+        match name {
+            "hit_head" => self.head = value,
+            "hit_torso" => self.torso = value,
+            "hit_limbs" => self.limbs = value,
+            _ => return Err(format!("unknown hit multiplier {}, expected one of {:?}", name, HIT_MULTIPLIER_NAMES)),
+        }
+        Ok(())
+    }
+}
+
+impl Visit for HitMultipliers {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.head.visit("Head", visitor)?;
+        self.torso.visit("Torso", visitor)?;
+        self.limbs.visit("Limbs", visitor)?;
+
+        visitor.leave_region()
+    }
+}
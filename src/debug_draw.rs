@@ -0,0 +1,126 @@
+//! Debug geometry that is drawn over the scene to diagnose AI and physics issues. Drawing is
+//! toggled by debug draw key (F4 by default) or by `set debug_draw <0|1>` console command,
+//! every category can be toggled separately by `set debug_<category> <0|1>` so only what is
+//! needed clutters the view. Flags are not saved, it is a tool for developers.
+
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        color::Color,
+    },
+    renderer::debug_renderer::{self, DebugRenderer},
+};
+
+/// Names of categories that can be toggled from console by `set debug_<name> <0|1>`.
+pub const DEBUG_DRAW_CATEGORIES: [&str; 9] = [
+    "colliders", "velocities", "targets", "sight", "paths", "spawn_points", "navmesh", "death_zones", "shots"
+];
+
+/// Velocity vectors are drawn as a distance that is travelled in this amount of seconds.
+pub const VELOCITY_SCALE: f32 = 0.25;
+/// Amount of segments in circles of collider shapes.
+const CIRCLE_SEGMENTS: usize = 16;
+
+#[derive(Copy, Clone, Debug)]
+pub struct DebugDrawFlags {
+    /// Whether debug geometry is drawn at all, categories are kept while it is off.
+    pub enabled: bool,
+    /// Shapes of rigid bodies of actors and projectiles.
+    pub colliders: bool,
+    pub velocities: bool,
+    /// Lines from bots to their targets or points of interest.
+    pub targets: bool,
+    /// View frustums of bots and line of sight rays to other actors, rays that are blocked
+    /// by walls or smoke are red.
+    pub sight: bool,
+    /// Paths that bots follow.
+    pub paths: bool,
+    pub spawn_points: bool,
+    pub navmesh: bool,
+    pub death_zones: bool,
+    /// Paths of shots and hit points with damage breakdown, see `shot_debug` module.
+    pub shots: bool,
+}
+
+impl Default for DebugDrawFlags {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            colliders: true,
+            velocities: true,
+            targets: true,
+            sight: true,
+            paths: true,
+            spawn_points: true,
+            // Navmesh is dense and hides everything else, so it's shown only on request.
+            navmesh: false,
+            death_zones: true,
+            // Shots print every hit of player to console, so they're shown only on request.
+            shots: false,
+        }
+    }
+}
+
+impl DebugDrawFlags {
+    /// Toggles category by its console name, see [`DEBUG_DRAW_CATEGORIES`].
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        let flag = match name {
+            "colliders" => &mut self.colliders,
+            "velocities" => &mut self.velocities,
+            "targets" => &mut self.targets,
+            "sight" => &mut self.sight,
+            "paths" => &mut self.paths,
+            "spawn_points" => &mut self.spawn_points,
+            "navmesh" => &mut self.navmesh,
+            "death_zones" => &mut self.death_zones,
+            "shots" => &mut self.shots,
+            _ => return Err(format!("unknown debug draw category {}, expected one of {:?}", name, DEBUG_DRAW_CATEGORIES)),
+        };
+        *flag = enabled;
+        Ok(())
+    }
+}
+
+pub fn draw_line(debug_renderer: &mut DebugRenderer, begin: Vec3, end: Vec3, color: Color) {
+    debug_renderer.add_line(debug_renderer::Line { begin, end, color });
+}
+
+/// Draws circle in a plane given by two perpendicular unit axes.
+fn draw_circle(debug_renderer: &mut DebugRenderer, center: Vec3, radius: f32, a: Vec3, b: Vec3, color: Color) {
+    let point = |i: usize| {
+        let angle = i as f32 / CIRCLE_SEGMENTS as f32 * 2.0 * std::f32::consts::PI;
+        center + a.scale(angle.cos() * radius) + b.scale(angle.sin() * radius)
+    };
+    for i in 0..CIRCLE_SEGMENTS {
+        draw_line(debug_renderer, point(i), point(i + 1), color);
+    }
+}
+
+pub fn draw_sphere(debug_renderer: &mut DebugRenderer, center: Vec3, radius: f32, color: Color) {
+    let (x, y, z) = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 1.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+    draw_circle(debug_renderer, center, radius, x, y, color);
+    draw_circle(debug_renderer, center, radius, y, z, color);
+    draw_circle(debug_renderer, center, radius, x, z, color);
+}
+
+/// Draws vertical capsule, height is the distance between centers of its caps.
+pub fn draw_capsule(debug_renderer: &mut DebugRenderer, center: Vec3, radius: f32, height: f32, color: Color) {
+    let (x, z) = (Vec3::new(1.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0));
+    let top = center + Vec3::new(0.0, height * 0.5, 0.0);
+    let bottom = center - Vec3::new(0.0, height * 0.5, 0.0);
+    for cap in [top, bottom].iter() {
+        draw_sphere(debug_renderer, *cap, radius, color);
+    }
+    for side in [x, z, x.scale(-1.0), z.scale(-1.0)].iter() {
+        draw_line(debug_renderer, top + side.scale(radius), bottom + side.scale(radius), color);
+    }
+}
+
+/// Draws three axis-aligned lines crossing at given point, good to mark points that have
+/// no geometry.
+pub fn draw_cross(debug_renderer: &mut DebugRenderer, center: Vec3, size: f32, color: Color) {
+    let half = size * 0.5;
+    for axis in [Vec3::new(half, 0.0, 0.0), Vec3::new(0.0, half, 0.0), Vec3::new(0.0, 0.0, half)].iter() {
+        draw_line(debug_renderer, center - *axis, center + *axis, color);
+    }
+}
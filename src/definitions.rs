@@ -0,0 +1,170 @@
+//! Built-in definitions of weapons, projectiles, bots and items can be overridden by a data
+//! file, so the game can be tuned and modded without recompiling. The file uses the same
+//! `name = value` format as exported settings, name is made of category, kind and field,
+//! for example `weapon.m4.magazine_size = 40`. Only listed values are changed, everything
+//! else comes from built-in definitions.
+//!
+//! Whole file is validated before anything is applied, so malformed file leaves built-in
+//! definitions intact. Overrides are loaded once at startup and live until the game exits.
+
+use crate::{
+    weapon::{Weapon, WeaponKind, WeaponDefinition},
+    projectile::{Projectile, ProjectileKind, ProjectileDefinition},
+    bot::{Bot, BotKind, BotDefinition},
+    item::{Item, ItemKind, ItemDefinition},
+};
+use std::{
+    path::Path,
+    cell::RefCell,
+};
+
+pub const DEFINITIONS_PATH: &str = "data/definitions.txt";
+
+const WEAPONS: [(&str, WeaponKind); 4] = [
+    ("m4", WeaponKind::M4),
+    ("ak47", WeaponKind::Ak47),
+    ("plasma_rifle", WeaponKind::PlasmaRifle),
+    ("rocket_launcher", WeaponKind::RocketLauncher),
+];
+
+const PROJECTILES: [(&str, ProjectileKind); 6] = [
+    ("plasma", ProjectileKind::Plasma),
+    ("bullet", ProjectileKind::Bullet),
+    ("rocket", ProjectileKind::Rocket),
+    ("grenade", ProjectileKind::Grenade),
+    ("smoke_grenade", ProjectileKind::SmokeGrenade),
+    ("flashbang", ProjectileKind::Flashbang),
+];
+
+const BOTS: [(&str, BotKind); 3] = [
+    ("mutant", BotKind::Mutant),
+    ("parasite", BotKind::Parasite),
+    ("maw", BotKind::Maw),
+];
+
+const ITEMS: [(&str, ItemKind); 8] = [
+    ("medkit", ItemKind::Medkit),
+    ("plasma_ammo", ItemKind::Plasma),
+    ("ak47_ammo", ItemKind::Ak47Ammo),
+    ("m4_ammo", ItemKind::M4Ammo),
+    ("plasma_rifle", ItemKind::PlasmaGun),
+    ("ak47", ItemKind::Ak47),
+    ("m4", ItemKind::M4),
+    ("rocket_launcher", ItemKind::RocketLauncher),
+];
+
+/// Definitions that replace built-in ones, kinds without override are not listed.
+#[derive(Default)]
+struct Registry {
+    weapons: Vec<(WeaponKind, &'static WeaponDefinition)>,
+    projectiles: Vec<(ProjectileKind, &'static ProjectileDefinition)>,
+    bots: Vec<(BotKind, &'static BotDefinition)>,
+    items: Vec<(ItemKind, &'static ItemDefinition)>,
+}
+
+thread_local! {
+    // Game runs in a single thread, definitions are requested from it only.
+    static REGISTRY: RefCell<Registry> = RefCell::new(Registry::default());
+}
+
+fn find<K: PartialEq + Copy, D: 'static>(list: &[(K, &'static D)], kind: K) -> Option<&'static D> {
+    list.iter()
+        .find(|(k, _)| *k == kind)
+        .map(|(_, definition)| *definition)
+}
+
+/// Returns overridden definition of weapon, None if built-in one should be used.
+pub fn weapon(kind: WeaponKind) -> Option<&'static WeaponDefinition> {
+    REGISTRY.with(|registry| find(&registry.borrow().weapons, kind))
+}
+
+pub fn projectile(kind: ProjectileKind) -> Option<&'static ProjectileDefinition> {
+    REGISTRY.with(|registry| find(&registry.borrow().projectiles, kind))
+}
+
+pub fn bot(kind: BotKind) -> Option<&'static BotDefinition> {
+    REGISTRY.with(|registry| find(&registry.borrow().bots, kind))
+}
+
+pub fn item(kind: ItemKind) -> Option<&'static ItemDefinition> {
+    REGISTRY.with(|registry| find(&registry.borrow().items, kind))
+}
+
+/// Returns definition that is being modified for given kind, creates a copy of built-in
+/// definition on first access.
+fn entry<'a, K: PartialEq + Copy, D: Clone>(list: &'a mut Vec<(K, D)>, kind: K, builtin: impl FnOnce(K) -> &'static D) -> &'a mut D {
+    match list.iter().position(|(k, _)| *k == kind) {
+        Some(index) => &mut list[index].1,
+        None => {
+            list.push((kind, builtin(kind).clone()));
+            &mut list.last_mut().unwrap().1
+        }
+    }
+}
+
+fn lookup<K: Copy>(table: &[(&str, K)], category: &str, key: &str) -> Result<K, String> {
+    table.iter()
+        .find(|(name, _)| *name == key)
+        .map(|(_, kind)| *kind)
+        .ok_or_else(|| format!("unknown {} {}", category, key))
+}
+
+/// Reads overrides from given file, returns amount of overridden values. Nothing is changed
+/// if file has any error.
+pub fn load(path: &Path) -> Result<usize, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+
+    let mut weapons = Vec::new();
+    let mut projectiles = Vec::new();
+    let mut bots = Vec::new();
+    let mut items = Vec::new();
+    let mut count = 0;
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = line.splitn(2, '=');
+        let (name, value) = match (parts.next(), parts.next()) {
+            (Some(name), Some(value)) => (name.trim(), value.trim()),
+            _ => return Err(format!("line {}: expected `name = value`", index + 1)),
+        };
+        let path = name.split('.').collect::<Vec<_>>();
+        let result = match path.as_slice() {
+            ["weapon", key, field] => lookup(&WEAPONS, "weapon", key).and_then(|kind| {
+                entry(&mut weapons, kind, Weapon::builtin_definition).set_value(field, value)
+            }),
+            ["projectile", key, field] => lookup(&PROJECTILES, "projectile", key).and_then(|kind| {
+                entry(&mut projectiles, kind, Projectile::builtin_definition).set_value(field, value)
+            }),
+            ["bot", key, field] => lookup(&BOTS, "bot", key).and_then(|kind| {
+                entry(&mut bots, kind, Bot::builtin_definition).set_value(field, value)
+            }),
+            ["item", key, field] => lookup(&ITEMS, "item", key).and_then(|kind| {
+                entry(&mut items, kind, Item::builtin_definition).set_value(field, value)
+            }),
+            _ => Err(format!("expected `category.kind.field`, got {}", name)),
+        };
+        result.map_err(|e| format!("line {}: {}", index + 1, e))?;
+        count += 1;
+    }
+
+    // Definitions are referenced as static everywhere, overrides are loaded once so leaking
+    // them is fine.
+    fn leak<K, D>(list: Vec<(K, D)>) -> Vec<(K, &'static D)> {
+        list.into_iter()
+            .map(|(kind, definition)| (kind, &*Box::leak(Box::new(definition))))
+            .collect()
+    }
+    REGISTRY.with(|registry| {
+        *registry.borrow_mut() = Registry {
+            weapons: leak(weapons),
+            projectiles: leak(projectiles),
+            bots: leak(bots),
+            items: leak(items),
+        };
+    });
+
+    Ok(count)
+}
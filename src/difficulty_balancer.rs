@@ -0,0 +1,77 @@
+//! Dynamic difficulty that keeps a match close. Balancer watches difference between score
+//! of player and score of the best bot and slowly makes bots tougher when player dominates
+//! or weaker when player is losing badly. Strength of bots is applied as a multiplier to
+//! damage that bots deal and an inverse multiplier to damage that bots take.
+
+use rg3d::core::visitor::{Visit, Visitor, VisitResult};
+use crate::damage::DamageSource;
+
+const MIN_STRENGTH: f32 = 0.75;
+const MAX_STRENGTH: f32 = 1.25;
+/// Change of strength per balancing step.
+const STRENGTH_STEP: f32 = 0.05;
+/// Time in seconds between balancing steps, so balance changes are gradual.
+const BALANCE_INTERVAL: f32 = 10.0;
+/// Score difference at which balancer starts to react.
+const SCORE_THRESHOLD: i32 = 3;
+
+pub struct DifficultyBalancer {
+    strength: f32,
+    time_until_step: f32,
+}
+
+impl Default for DifficultyBalancer {
+    fn default() -> Self {
+        Self {
+            strength: 1.0,
+            time_until_step: BALANCE_INTERVAL,
+        }
+    }
+}
+
+impl DifficultyBalancer {
+    /// `score_difference` is score of player minus score of the best bot.
+    pub fn update(&mut self, dt: f32, score_difference: i32) {
+        self.time_until_step -= dt;
+        if self.time_until_step > 0.0 {
+            return;
+        }
+        self.time_until_step = BALANCE_INTERVAL;
+
+        if score_difference >= SCORE_THRESHOLD {
+            self.strength += STRENGTH_STEP;
+        } else if score_difference <= -SCORE_THRESHOLD {
+            self.strength -= STRENGTH_STEP;
+        } else if (self.strength - 1.0).abs() >= STRENGTH_STEP {
+            // Close match, bots return to their normal strength.
+            self.strength -= STRENGTH_STEP * (self.strength - 1.0).signum();
+        } else {
+            self.strength = 1.0;
+        }
+        self.strength = self.strength.max(MIN_STRENGTH).min(MAX_STRENGTH);
+    }
+
+    /// Returns damage after strength of bots was applied. Damage that bots deal to each
+    /// other stays the same.
+    pub fn apply(&self, amount: f32, attacker: DamageSource, victim_is_bot: bool) -> f32 {
+        let mut amount = amount;
+        if attacker == DamageSource::Bot {
+            amount *= self.strength;
+        }
+        if victim_is_bot {
+            amount /= self.strength;
+        }
+        amount
+    }
+}
+
+impl Visit for DifficultyBalancer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.strength.visit("Strength", visitor)?;
+        self.time_until_step.visit("TimeUntilStep", visitor)?;
+
+        visitor.leave_region()
+    }
+}
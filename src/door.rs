@@ -0,0 +1,441 @@
+//! Doors are meshes of level that open when an actor comes close or when player uses them,
+//! and close again after a delay. Mesh named `DoorSlide*` slides aside along its longest
+//! horizontal side, mesh named `DoorSwing*` swings around its origin, so origin of swinging
+//! door must be at its hinge. Player opens doors that have `Use` in name only by use button,
+//! bots open every door when they come close. Navmesh must cover doorways, so bots plan
+//! paths through doors and open them on the way.
+
+use rg3d::{
+    core::{
+        pool::{Handle, Pool, PoolPairIterator},
+        math::{
+            vec3::Vec3,
+            quat::Quat,
+            aabb::AxisAlignedBoundingBox,
+        },
+        visitor::{Visit, Visitor, VisitResult},
+    },
+    scene::{
+        Scene,
+        node::Node,
+        graph::Graph,
+    },
+    physics::{
+        Physics,
+        static_geometry::StaticGeometry,
+    },
+    utils,
+};
+use crate::{
+    actor::{Actor, ActorContainer},
+    usable::Usable,
+};
+
+/// Distance from center of door at which actors open it.
+const OPEN_DISTANCE: f32 = 2.5;
+/// How long door stays open after last actor left doorway.
+const CLOSE_DELAY: f32 = 3.0;
+/// Time of full opening or closing, in seconds.
+const MOVE_TIME: f32 = 0.6;
+/// Angle of fully open swinging door, in degrees.
+const SWING_ANGLE: f32 = 90.0;
+/// Approximate radius of actor body, actors closer than this to closed door are in doorway.
+const ACTOR_RADIUS: f32 = 0.5;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DoorKind {
+    Sliding,
+    Swinging,
+}
+
+impl DoorKind {
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(DoorKind::Sliding),
+            1 => Ok(DoorKind::Swinging),
+            _ => Err(format!("Invalid door kind {}", id))
+        }
+    }
+
+    fn id(self) -> u32 {
+        match self {
+            DoorKind::Sliding => 0,
+            DoorKind::Swinging => 1,
+        }
+    }
+}
+
+impl Visit for DoorKind {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum DoorState {
+    Closed,
+    Opening,
+    Open,
+    Closing,
+}
+
+impl DoorState {
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(DoorState::Closed),
+            1 => Ok(DoorState::Opening),
+            2 => Ok(DoorState::Open),
+            3 => Ok(DoorState::Closing),
+            _ => Err(format!("Invalid door state {}", id))
+        }
+    }
+
+    fn id(self) -> u32 {
+        match self {
+            DoorState::Closed => 0,
+            DoorState::Opening => 1,
+            DoorState::Open => 2,
+            DoorState::Closing => 3,
+        }
+    }
+}
+
+impl Visit for DoorState {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+pub struct Door {
+    node: Handle<Node>,
+    kind: DoorKind,
+    state: DoorState,
+    /// 0.0 - closed, 1.0 - fully open.
+    openness: f32,
+    /// Time left until open door starts closing.
+    close_timer: f32,
+    /// Direction of swing, 1.0 or -1.0, chosen so door swings away from actor that opened it.
+    swing_sign: f32,
+    /// Whether player opens door only by use button.
+    use_only: bool,
+    closed_position: Vec3,
+    closed_rotation: Quat,
+    /// World position of origin of closed door, it is hinge of swinging door.
+    hinge: Vec3,
+    /// World bounds of closed door.
+    bounds: AxisAlignedBoundingBox,
+    /// Collision of closed door. It is removed while door is not closed, so actors can pass
+    /// door as soon as it starts opening.
+    shape: Handle<StaticGeometry>,
+}
+
+impl Default for Door {
+    fn default() -> Self {
+        Self {
+            node: Default::default(),
+            kind: DoorKind::Sliding,
+            state: DoorState::Closed,
+            openness: 0.0,
+            close_timer: 0.0,
+            swing_sign: 1.0,
+            use_only: false,
+            closed_position: Default::default(),
+            closed_rotation: Quat::IDENTITY,
+            hinge: Default::default(),
+            bounds: Default::default(),
+            shape: Default::default(),
+        }
+    }
+}
+
+impl Door {
+    pub fn new(scene: &mut Scene, node: Handle<Node>, kind: DoorKind, use_only: bool) -> Self {
+        let mesh = &scene.graph[node];
+        let closed_position = mesh.local_transform().position();
+        let closed_rotation = mesh.local_transform().rotation();
+        let hinge = mesh.global_position();
+        let bounds = mesh.as_mesh().world_bounding_box();
+        let shape = scene.physics.add_static_geometry(utils::mesh_to_static_geometry(mesh.as_mesh()));
+
+        Self {
+            node,
+            kind,
+            use_only,
+            closed_position,
+            closed_rotation,
+            hinge,
+            bounds,
+            shape,
+            ..Default::default()
+        }
+    }
+
+    pub fn center(&self) -> Vec3 {
+        (self.bounds.min + self.bounds.max).scale(0.5)
+    }
+
+    /// Returns unit vector along width of door and the width itself.
+    fn width_axis(&self) -> (Vec3, f32) {
+        let size = self.bounds.max - self.bounds.min;
+        if size.x > size.z {
+            (Vec3::new(1.0, 0.0, 0.0), size.x)
+        } else {
+            (Vec3::new(0.0, 0.0, 1.0), size.z)
+        }
+    }
+
+    /// Returns horizontal vector from hinge to center of door swung by given angle.
+    fn swung_arm(&self, angle: f32) -> Vec3 {
+        let arm = self.center() - self.hinge;
+        let (sin, cos) = angle.sin_cos();
+        Vec3::new(arm.x * cos + arm.z * sin, 0.0, -arm.x * sin + arm.z * cos)
+    }
+
+    /// Opens door, swinging door swings away from given position of actor that opened it.
+    pub fn open(&mut self, activator: Vec3) {
+        match self.state {
+            DoorState::Closed => {
+                // Direction in which far edge of door moves on positive swing.
+                let arm = self.center() - self.hinge;
+                let motion = Vec3::new(arm.z, 0.0, -arm.x);
+                self.swing_sign = if motion.dot(&(self.center() - activator)) >= 0.0 { 1.0 } else { -1.0 };
+                self.state = DoorState::Opening;
+            }
+            DoorState::Closing => self.state = DoorState::Opening,
+            DoorState::Opening | DoorState::Open => (),
+        }
+        self.close_timer = CLOSE_DELAY;
+    }
+
+    pub fn close(&mut self) {
+        if self.is_open() {
+            self.state = DoorState::Closing;
+        }
+    }
+
+    /// Returns true if door is open or is being opened.
+    pub fn is_open(&self) -> bool {
+        self.state == DoorState::Opening || self.state == DoorState::Open
+    }
+
+    /// Opens closed door or closes open one, used by use button.
+    pub fn toggle(&mut self, activator: Vec3) {
+        if self.is_open() {
+            self.close();
+        } else {
+            self.open(activator);
+        }
+    }
+
+    /// Returns true if position is inside closed door expanded by radius of actor.
+    fn is_in_doorway(&self, position: Vec3) -> bool {
+        position.x > self.bounds.min.x - ACTOR_RADIUS && position.x < self.bounds.max.x + ACTOR_RADIUS &&
+            position.y > self.bounds.min.y - ACTOR_RADIUS && position.y < self.bounds.max.y + ACTOR_RADIUS &&
+            position.z > self.bounds.min.z - ACTOR_RADIUS && position.z < self.bounds.max.z + ACTOR_RADIUS
+    }
+
+    /// Returns true if actor stands where swinging door goes, door stops until the way is free.
+    /// Sliding door slides into wall, so it is never blocked.
+    fn is_swing_blocked(&self, positions: &[Vec3]) -> bool {
+        if self.kind == DoorKind::Sliding {
+            return false;
+        }
+        let arm = self.swung_arm(self.swing_sign * SWING_ANGLE.to_radians());
+        let open_center = self.hinge + arm;
+        let reach = arm.len() + ACTOR_RADIUS;
+        positions.iter().any(|position| {
+            let offset = *position - open_center;
+            Vec3::new(offset.x, 0.0, offset.z).len() < reach && !self.is_in_doorway(*position)
+        })
+    }
+
+    fn update(&mut self, graph: &mut Graph, physics: &mut Physics, actors: &[(Vec3, bool)], dt: f32) {
+        let center = self.center();
+        let use_only = self.use_only;
+        let activator = actors
+            .iter()
+            .find(|(position, is_player)| !(use_only && *is_player) && position.distance(&center) < OPEN_DISTANCE)
+            .map(|(position, _)| *position);
+        if let Some(activator) = activator {
+            self.open(activator);
+        }
+
+        let positions = actors.iter().map(|(position, _)| *position).collect::<Vec<_>>();
+        let doorway_occupied = positions.iter().any(|position| self.is_in_doorway(*position));
+        match self.state {
+            DoorState::Closed => {
+                // Collision is added on next update after door closed, when global transform
+                // of mesh is already updated by engine.
+                if self.shape.is_none() {
+                    if doorway_occupied {
+                        self.state = DoorState::Opening;
+                    } else {
+                        self.shape = physics.add_static_geometry(utils::mesh_to_static_geometry(graph[self.node].as_mesh()));
+                    }
+                }
+            }
+            DoorState::Opening => {
+                if !self.is_swing_blocked(&positions) {
+                    self.openness = (self.openness + dt / MOVE_TIME).min(1.0);
+                    if self.openness >= 1.0 {
+                        self.state = DoorState::Open;
+                    }
+                }
+            }
+            DoorState::Open => {
+                // Door never closes on anyone standing in doorway.
+                if doorway_occupied {
+                    self.close_timer = CLOSE_DELAY;
+                } else {
+                    self.close_timer -= dt;
+                    if self.close_timer <= 0.0 {
+                        self.state = DoorState::Closing;
+                    }
+                }
+            }
+            DoorState::Closing => {
+                if doorway_occupied {
+                    self.state = DoorState::Opening;
+                } else {
+                    self.openness = (self.openness - dt / MOVE_TIME).max(0.0);
+                    if self.openness <= 0.0 {
+                        self.state = DoorState::Closed;
+                    }
+                }
+            }
+        }
+
+        if self.state != DoorState::Closed && self.shape.is_some() {
+            physics.remove_static_geometry(self.shape);
+            self.shape = Handle::NONE;
+        }
+
+        let transform = graph[self.node].local_transform_mut();
+        match self.kind {
+            DoorKind::Sliding => {
+                let (axis, width) = self.width_axis();
+                transform.set_position(self.closed_position + axis.scale(width * self.openness));
+            }
+            DoorKind::Swinging => {
+                let angle = self.swing_sign * SWING_ANGLE.to_radians() * self.openness;
+                transform.set_rotation(Quat::from_axis_angle(Vec3::UP, angle) * self.closed_rotation);
+            }
+        }
+    }
+}
+
+impl Usable for Door {
+    fn use_position(&self, _graph: &Graph) -> Vec3 {
+        self.center()
+    }
+
+    fn can_be_used(&self) -> bool {
+        true
+    }
+
+    fn use_action(&self) -> String {
+        if self.is_open() {
+            "close door".to_owned()
+        } else {
+            "open door".to_owned()
+        }
+    }
+}
+
+impl Visit for Door {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.node.visit("Node", visitor)?;
+        self.kind.visit("Kind", visitor)?;
+        self.state.visit("State", visitor)?;
+        self.openness.visit("Openness", visitor)?;
+        self.close_timer.visit("CloseTimer", visitor)?;
+        self.swing_sign.visit("SwingSign", visitor)?;
+        self.use_only.visit("UseOnly", visitor)?;
+        self.closed_position.visit("ClosedPosition", visitor)?;
+        self.closed_rotation.visit("ClosedRotation", visitor)?;
+        self.hinge.visit("Hinge", visitor)?;
+        self.bounds.visit("Bounds", visitor)?;
+        self.shape.visit("Shape", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+pub struct DoorContainer {
+    pool: Pool<Door>
+}
+
+impl Default for DoorContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DoorContainer {
+    pub fn new() -> Self {
+        Self {
+            pool: Pool::new()
+        }
+    }
+
+    pub fn add(&mut self, door: Door) -> Handle<Door> {
+        self.pool.spawn(door)
+    }
+
+    pub fn get(&self, door: Handle<Door>) -> &Door {
+        self.pool.borrow(door)
+    }
+
+    pub fn get_mut(&mut self, door: Handle<Door>) -> &mut Door {
+        self.pool.borrow_mut(door)
+    }
+
+    pub fn pair_iter(&self) -> PoolPairIterator<Door> {
+        self.pool.pair_iter()
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, dt: f32) {
+        let actors = actors
+            .iter()
+            .filter(|actor| !actor.is_dead())
+            .map(|actor| {
+                let is_player = match actor {
+                    Actor::Player(_) => true,
+                    Actor::Bot(_) => false,
+                };
+                (actor.position(&scene.physics), is_player)
+            })
+            .collect::<Vec<_>>();
+        for door in self.pool.iter_mut() {
+            door.update(&mut scene.graph, &mut scene.physics, &actors, dt);
+        }
+    }
+}
+
+impl Visit for DoorContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.pool.visit("Pool", visitor)?;
+
+        visitor.leave_region()
+    }
+}
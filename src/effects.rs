@@ -4,7 +4,8 @@ use rg3d::{
         color::Color,
         numeric_range::NumericRange,
         math::vec3::Vec3,
-        visitor::{VisitResult, Visitor, Visit}
+        visitor::{VisitResult, Visitor, Visit},
+        pool::Handle,
     },
     engine::resource_manager::ResourceManager,
     scene::{
@@ -27,23 +28,137 @@ use rg3d::{
     },
     resource::texture::TextureKind,
 };
-use std::path::Path;
+use std::{
+    path::Path,
+    collections::VecDeque,
+};
 use rand::Rng;
 
 #[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
 pub enum EffectKind {
     BulletImpact,
+    /// Projectile hit an actor, replaced with bullet impact when gore is disabled.
+    Blood,
     ItemAppear,
     Smoke,
-    Steam
+    Steam,
+    /// Electric discharge of electrified floor.
+    Sparks,
+    /// Fume of lava or acid that burns an actor.
+    Burn,
+}
+
+/// Allowed range of maximum amount of effects on level.
+pub const MAX_EFFECTS_RANGE: (u32, u32) = (8, 256);
+
+/// Limits of visual effects, effects pile up in long firefights so weak hardware may need
+/// lower limits.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct EffectLimits {
+    /// Maximum amount of effects on level, oldest effects are removed first.
+    pub max_effects: u32,
+    /// Multiplier of amount of particles in every effect, in (0; 1] range.
+    pub particle_density: f32,
+    /// Multiplier of lifetime of every effect, in (0; 1] range.
+    pub lifetime_scale: f32,
+}
+
+impl Default for EffectLimits {
+    fn default() -> Self {
+        Self {
+            max_effects: 128,
+            particle_density: 1.0,
+            lifetime_scale: 1.0,
+        }
+    }
+}
+
+impl EffectLimits {
+    /// Preset for weak hardware.
+    pub fn low() -> Self {
+        Self {
+            max_effects: 32,
+            particle_density: 0.35,
+            lifetime_scale: 0.5,
+        }
+    }
+
+    pub fn is_low(&self) -> bool {
+        *self == Self::low()
+    }
+
+    fn particles(&self, count: u32) -> u32 {
+        ((count as f32 * self.particle_density) as u32).max(1)
+    }
+
+    fn lifetime(&self, lifetime: f32) -> f32 {
+        lifetime * self.lifetime_scale
+    }
+}
+
+impl Visit for EffectLimits {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.max_effects.visit("MaxEffects", visitor)?;
+        self.particle_density.visit("ParticleDensity", visitor)?;
+        self.lifetime_scale.visit("LifetimeScale", visitor)?;
+
+        visitor.leave_region()
+    }
 }
 
-pub fn create(kind: EffectKind, graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3) {
+pub fn create(kind: EffectKind, graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, limits: &EffectLimits) -> Handle<Node> {
     match kind {
-        EffectKind::BulletImpact => create_bullet_impact(graph, resource_manager, pos),
-        EffectKind::ItemAppear => create_item_appear(graph, resource_manager, pos),
-        EffectKind::Smoke => create_smoke(graph, resource_manager, pos),
-        EffectKind::Steam => create_steam(graph, resource_manager, pos)
+        EffectKind::BulletImpact => create_bullet_impact(graph, resource_manager, pos, limits),
+        EffectKind::Blood => create_blood(graph, resource_manager, pos, limits),
+        EffectKind::ItemAppear => create_item_appear(graph, resource_manager, pos, limits),
+        EffectKind::Smoke => create_smoke(graph, resource_manager, pos, limits),
+        EffectKind::Steam => create_steam(graph, resource_manager, pos),
+        EffectKind::Sparks => create_sparks(graph, resource_manager, pos, limits),
+        EffectKind::Burn => create_burn(graph, resource_manager, pos, limits),
+    }
+}
+
+/// Keeps track of effects on level, so their amount can be limited. Effects remove
+/// themselves from graph when their lifetime ends.
+#[derive(Default)]
+pub struct EffectContainer {
+    /// Effects in order of creation, some of them could be already removed from graph.
+    effects: VecDeque<Handle<Node>>,
+    limits: EffectLimits,
+}
+
+impl EffectContainer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Changes limits, effects that are over new limit are removed right away.
+    pub fn set_limits(&mut self, graph: &mut Graph, limits: EffectLimits) {
+        self.limits = limits;
+        self.remove_excess(graph, limits.max_effects as usize);
+    }
+
+    fn remove_excess(&mut self, graph: &mut Graph, max_effects: usize) {
+        self.effects.retain(|effect| graph.is_valid_handle(*effect));
+        while self.effects.len() > max_effects {
+            if let Some(oldest) = self.effects.pop_front() {
+                graph.remove_node(oldest);
+            }
+        }
+    }
+
+    pub fn create(&mut self, kind: EffectKind, graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3) {
+        self.remove_excess(graph, (self.limits.max_effects as usize).saturating_sub(1));
+        let effect = create(kind, graph, resource_manager, pos, &self.limits);
+        self.effects.push_back(effect);
+    }
+
+    /// Creates particles of smoke grenade cloud. Cloud is not counted by effect limit,
+    /// because it must be seen for as long as it blocks sight of bots.
+    pub fn create_smoke_cloud(&self, graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, radius: f32, duration: f32) {
+        create_smoke_cloud(graph, resource_manager, pos, radius, duration, &self.limits);
     }
 }
 
@@ -107,7 +222,7 @@ pub fn register_custom_emitter_factory() {
     }
 }
 
-fn create_steam(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3) {
+fn create_steam(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3) -> Handle<Node> {
     graph.add_node(Node::ParticleSystem(
         ParticleSystemBuilder::new(BaseBuilder::new()
             .with_local_transform(TransformBuilder::new()
@@ -126,12 +241,12 @@ fn create_steam(graph: &mut Graph, resource_manager: &mut ResourceManager, pos:
                 EmitterBuilder::new(EmitterKind::Custom(Box::new(CylinderEmitter { height: 0.2, radius: 0.2 }))).build()
             ])
             .with_opt_texture(resource_manager.request_texture(Path::new("data/particles/smoke_04.tga"), TextureKind::R8))
-            .build()));
+            .build()))
 }
 
-fn create_bullet_impact(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3) {
+fn create_bullet_impact(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, limits: &EffectLimits) -> Handle<Node> {
     graph.add_node(Node::ParticleSystem(ParticleSystemBuilder::new(BaseBuilder::new()
-        .with_lifetime(1.0)
+        .with_lifetime(limits.lifetime(1.0))
         .with_local_transform(TransformBuilder::new()
             .with_local_position(pos)
             .build()))
@@ -146,8 +261,8 @@ fn create_bullet_impact(graph: &mut Graph, resource_manager: &mut ResourceManage
         })
         .with_emitters(vec![
             EmitterBuilder::new(EmitterKind::Sphere(SphereEmitter::new(0.01)))
-                .with_max_particles(200)
-                .with_spawn_rate(1000)
+                .with_max_particles(limits.particles(200))
+                .with_spawn_rate(limits.particles(1000))
                 .with_size_modifier_range(NumericRange::new(-0.02, -0.025))
                 .with_size_range(NumericRange::new(0.025, 0.05))
                 .with_x_velocity_range(NumericRange::new(-0.03, 0.03))
@@ -157,12 +272,105 @@ fn create_bullet_impact(graph: &mut Graph, resource_manager: &mut ResourceManage
                 .build()
         ])
         .with_opt_texture(resource_manager.request_texture(Path::new("data/particles/circle_05.png"), TextureKind::R8))
-        .build()));
+        .build()))
+}
+
+fn create_sparks(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, limits: &EffectLimits) -> Handle<Node> {
+    graph.add_node(Node::ParticleSystem(ParticleSystemBuilder::new(BaseBuilder::new()
+        .with_lifetime(limits.lifetime(0.6))
+        .with_local_transform(TransformBuilder::new()
+            .with_local_position(pos)
+            .build()))
+        .with_acceleration(Vec3::new(0.0, -10.0, 0.0))
+        .with_color_over_lifetime_gradient({
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(255, 255, 255, 0)));
+            gradient.add_point(GradientPoint::new(0.05, Color::from_rgba(200, 230, 255, 255)));
+            gradient.add_point(GradientPoint::new(0.80, Color::from_rgba(80, 140, 255, 255)));
+            gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(40, 80, 255, 0)));
+            gradient
+        })
+        .with_emitters(vec![
+            EmitterBuilder::new(EmitterKind::Sphere(SphereEmitter::new(0.3)))
+                .with_max_particles(limits.particles(100))
+                .with_spawn_rate(limits.particles(600))
+                .with_size_modifier_range(NumericRange::new(-0.02, -0.025))
+                .with_size_range(NumericRange::new(0.02, 0.04))
+                .with_x_velocity_range(NumericRange::new(-0.06, 0.06))
+                .with_y_velocity_range(NumericRange::new(0.02, 0.08))
+                .with_z_velocity_range(NumericRange::new(-0.06, 0.06))
+                .resurrect_particles(false)
+                .build()
+        ])
+        .with_opt_texture(resource_manager.request_texture(Path::new("data/particles/circle_05.png"), TextureKind::R8))
+        .build()))
+}
+
+fn create_burn(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, limits: &EffectLimits) -> Handle<Node> {
+    graph.add_node(Node::ParticleSystem(ParticleSystemBuilder::new(BaseBuilder::new()
+        .with_lifetime(limits.lifetime(1.2))
+        .with_local_transform(TransformBuilder::new()
+            .with_local_position(pos)
+            .build()))
+        .with_acceleration(Vec3::new(0.0, 0.5, 0.0))
+        .with_color_over_lifetime_gradient({
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(255, 200, 0, 0)));
+            gradient.add_point(GradientPoint::new(0.10, Color::from_rgba(255, 140, 0, 200)));
+            gradient.add_point(GradientPoint::new(0.70, Color::from_rgba(120, 60, 20, 150)));
+            gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(60, 60, 60, 0)));
+            gradient
+        })
+        .with_emitters(vec![
+            EmitterBuilder::new(EmitterKind::Sphere(SphereEmitter::new(0.3)))
+                .with_max_particles(limits.particles(60))
+                .with_spawn_rate(limits.particles(120))
+                .with_size_modifier_range(NumericRange::new(0.005, 0.01))
+                .with_size_range(NumericRange::new(0.05, 0.1))
+                .with_x_velocity_range(NumericRange::new(-0.005, 0.005))
+                .with_y_velocity_range(NumericRange::new(0.01, 0.03))
+                .with_z_velocity_range(NumericRange::new(-0.005, 0.005))
+                .resurrect_particles(false)
+                .build()
+        ])
+        .with_opt_texture(resource_manager.request_texture(Path::new("data/particles/smoke_04.tga"), TextureKind::R8))
+        .build()))
 }
 
-fn create_smoke(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3) {
+fn create_blood(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, limits: &EffectLimits) -> Handle<Node> {
     graph.add_node(Node::ParticleSystem(ParticleSystemBuilder::new(BaseBuilder::new()
-        .with_lifetime(5.0)
+        .with_lifetime(limits.lifetime(1.0))
+        .with_local_transform(TransformBuilder::new()
+            .with_local_position(pos)
+            .build()))
+        .with_acceleration(Vec3::new(0.0, -10.0, 0.0))
+        .with_color_over_lifetime_gradient({
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(160, 0, 0, 0)));
+            gradient.add_point(GradientPoint::new(0.05, Color::from_rgba(140, 0, 0, 255)));
+            gradient.add_point(GradientPoint::new(0.85, Color::from_rgba(100, 0, 0, 230)));
+            gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(80, 0, 0, 0)));
+            gradient
+        })
+        .with_emitters(vec![
+            EmitterBuilder::new(EmitterKind::Sphere(SphereEmitter::new(0.05)))
+                .with_max_particles(limits.particles(60))
+                .with_spawn_rate(limits.particles(600))
+                .with_size_modifier_range(NumericRange::new(-0.01, -0.015))
+                .with_size_range(NumericRange::new(0.04, 0.08))
+                .with_x_velocity_range(NumericRange::new(-0.02, 0.02))
+                .with_y_velocity_range(NumericRange::new(0.01, 0.03))
+                .with_z_velocity_range(NumericRange::new(-0.02, 0.02))
+                .resurrect_particles(false)
+                .build()
+        ])
+        .with_opt_texture(resource_manager.request_texture(Path::new("data/particles/circle_05.png"), TextureKind::R8))
+        .build()))
+}
+
+fn create_smoke(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, limits: &EffectLimits) -> Handle<Node> {
+    graph.add_node(Node::ParticleSystem(ParticleSystemBuilder::new(BaseBuilder::new()
+        .with_lifetime(limits.lifetime(5.0))
         .with_local_transform(TransformBuilder::new()
             .with_local_position(pos)
             .build()))
@@ -177,20 +385,49 @@ fn create_smoke(graph: &mut Graph, resource_manager: &mut ResourceManager, pos:
         })
         .with_emitters(vec![
             EmitterBuilder::new(EmitterKind::Sphere(SphereEmitter::new(0.01)))
-                .with_max_particles(100)
-                .with_spawn_rate(50)
+                .with_max_particles(limits.particles(100))
+                .with_spawn_rate(limits.particles(50))
                 .with_x_velocity_range(NumericRange::new(-0.01, 0.01))
                 .with_y_velocity_range(NumericRange::new(0.02, 0.03))
                 .with_z_velocity_range(NumericRange::new(-0.01, 0.01))
                 .build()
         ])
         .with_opt_texture(resource_manager.request_texture(Path::new("data/particles/smoke_04.tga"), TextureKind::R8))
-        .build()));
+        .build()))
+}
+
+fn create_smoke_cloud(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, radius: f32, duration: f32, limits: &EffectLimits) -> Handle<Node> {
+    graph.add_node(Node::ParticleSystem(ParticleSystemBuilder::new(BaseBuilder::new()
+        .with_lifetime(duration)
+        .with_local_transform(TransformBuilder::new()
+            .with_local_position(pos)
+            .build()))
+        .with_acceleration(Vec3::new(0.0, 0.0, 0.0))
+        .with_color_over_lifetime_gradient({
+            let mut gradient = ColorGradient::new();
+            gradient.add_point(GradientPoint::new(0.00, Color::from_rgba(170, 170, 170, 0)));
+            gradient.add_point(GradientPoint::new(0.10, Color::from_rgba(170, 170, 170, 240)));
+            gradient.add_point(GradientPoint::new(0.80, Color::from_rgba(200, 200, 200, 230)));
+            gradient.add_point(GradientPoint::new(1.00, Color::from_rgba(220, 220, 220, 0)));
+            gradient
+        })
+        .with_emitters(vec![
+            EmitterBuilder::new(EmitterKind::Sphere(SphereEmitter::new(radius * 0.6)))
+                .with_max_particles(limits.particles(150))
+                .with_spawn_rate(limits.particles(60))
+                .with_size_range(NumericRange::new(radius * 0.4, radius * 0.6))
+                .with_x_velocity_range(NumericRange::new(-0.005, 0.005))
+                .with_y_velocity_range(NumericRange::new(0.0, 0.005))
+                .with_z_velocity_range(NumericRange::new(-0.005, 0.005))
+                .build()
+        ])
+        .with_opt_texture(resource_manager.request_texture(Path::new("data/particles/smoke_04.tga"), TextureKind::R8))
+        .build()))
 }
 
-fn create_item_appear(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3) {
+fn create_item_appear(graph: &mut Graph, resource_manager: &mut ResourceManager, pos: Vec3, limits: &EffectLimits) -> Handle<Node> {
     graph.add_node(Node::ParticleSystem(ParticleSystemBuilder::new(BaseBuilder::new()
-        .with_lifetime(1.4)
+        .with_lifetime(limits.lifetime(1.4))
         .with_local_transform(TransformBuilder::new()
             .with_local_position(pos)
             .build()))
@@ -205,8 +442,8 @@ fn create_item_appear(graph: &mut Graph, resource_manager: &mut ResourceManager,
         })
         .with_emitters(vec![
             EmitterBuilder::new(EmitterKind::Sphere(SphereEmitter::new(0.01)))
-                .with_max_particles(100)
-                .with_spawn_rate(200)
+                .with_max_particles(limits.particles(100))
+                .with_spawn_rate(limits.particles(200))
                 .with_size_modifier_range(NumericRange::new(-0.012, -0.015))
                 .with_size_range(NumericRange::new(0.05, 0.10))
                 .with_x_velocity_range(NumericRange::new(-0.02, 0.02))
@@ -216,5 +453,5 @@ fn create_item_appear(graph: &mut Graph, resource_manager: &mut ResourceManager,
                 .build()
         ])
         .with_opt_texture(resource_manager.request_texture(Path::new("data/particles/star_09.png"), TextureKind::R8))
-        .build()));
+        .build()))
 }
@@ -0,0 +1,92 @@
+//! Simple per-stage frame timing. Collects time spent in major stages of a frame and keeps
+//! rolling average for each of them, so it is easy to see which stage is a bottleneck.
+//! Profile is disabled by default and measures nothing until enabled.
+
+use std::{
+    fmt::Write,
+    time::Instant,
+};
+
+#[derive(Copy, Clone)]
+pub enum Stage {
+    Input,
+    Engine,
+    Level,
+    Render,
+}
+
+pub const STAGE_COUNT: usize = 4;
+
+pub const STAGE_NAMES: [&str; STAGE_COUNT] = ["Input", "Engine update", "Level update", "Render"];
+
+/// Weight of new sample in rolling average.
+const SMOOTHING: f64 = 0.05;
+
+pub struct FrameProfile {
+    enabled: bool,
+    /// Time of stages in current frame in seconds.
+    current: [f64; STAGE_COUNT],
+    /// Rolling average time of stages in seconds.
+    average: [f64; STAGE_COUNT],
+}
+
+impl Default for FrameProfile {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            current: [0.0; STAGE_COUNT],
+            average: [0.0; STAGE_COUNT],
+        }
+    }
+}
+
+impl FrameProfile {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.current = [0.0; STAGE_COUNT];
+        self.average = [0.0; STAGE_COUNT];
+    }
+
+    /// Starts measurement of a stage, returns None if profile is disabled.
+    pub fn begin(&self) -> Option<Instant> {
+        if self.enabled {
+            Some(Instant::now())
+        } else {
+            None
+        }
+    }
+
+    /// Finishes measurement started by `begin`. Stage can be measured multiple times per frame,
+    /// all measurements are summed.
+    pub fn end(&mut self, stage: Stage, start: Option<Instant>) {
+        if let Some(start) = start {
+            self.current[stage as usize] += start.elapsed().as_secs_f64();
+        }
+    }
+
+    /// Returns time of every stage in current frame in seconds, in order of [`STAGE_NAMES`].
+    pub fn current(&self) -> &[f64; STAGE_COUNT] {
+        &self.current
+    }
+
+    /// Adds times of current frame to rolling averages, must be called once per frame.
+    pub fn finish_frame(&mut self) {
+        if self.enabled {
+            for (average, current) in self.average.iter_mut().zip(self.current.iter_mut()) {
+                *average += (*current - *average) * SMOOTHING;
+                *current = 0.0;
+            }
+        }
+    }
+
+    pub fn write_breakdown(&self, out: &mut String) {
+        out.push_str("\nFrame breakdown (avg):");
+        for (name, average) in STAGE_NAMES.iter().zip(self.average.iter()) {
+            write!(out, "\n  {}: {:.2} ms", name, average * 1000.0).unwrap();
+        }
+    }
+}
@@ -0,0 +1,152 @@
+//! Gibs are pieces of body that fly apart when bot is killed with huge overkill damage,
+//! they're used instead of dying animation. Every gib is a sprite with physical body, gibs
+//! live for a few seconds and amount of gibs on level is limited.
+
+use rg3d::{
+    engine::resource_manager::ResourceManager,
+    resource::texture::TextureKind,
+    scene::{
+        sprite::SpriteBuilder,
+        Scene,
+        node::Node,
+        base::BaseBuilder,
+    },
+    physics::{
+        convex_shape::{ConvexShape, SphereShape},
+        rigid_body::RigidBody,
+    },
+    core::{
+        visitor::{Visit, VisitResult, Visitor},
+        pool::{Handle, Pool},
+        color::Color,
+        math::vec3::Vec3,
+    },
+};
+use crate::CollisionGroups;
+use rand::Rng;
+
+/// Maximum amount of gibs on level, oldest gibs are removed first when limit is reached.
+const MAX_GIBS: usize = 64;
+/// Amount of gibs in one burst.
+const GIBS_PER_BURST: usize = 10;
+const GIB_LIFETIME: f32 = 6.0;
+
+pub struct Gib {
+    model: Handle<Node>,
+    body: Handle<RigidBody>,
+    lifetime: f32,
+}
+
+impl Default for Gib {
+    fn default() -> Self {
+        Self {
+            model: Default::default(),
+            body: Default::default(),
+            lifetime: 0.0,
+        }
+    }
+}
+
+impl Gib {
+    fn new(scene: &mut Scene, resource_manager: &mut ResourceManager, position: Vec3, velocity: Vec3) -> Self {
+        let size = rand::thread_rng().gen_range(0.08, 0.16);
+        let model = scene.graph.add_node(Node::Sprite(SpriteBuilder::new(BaseBuilder::new())
+            .with_size(size)
+            .with_color(Color::opaque(120, 10, 10))
+            .with_opt_texture(resource_manager.request_texture("data/particles/circle_05.png", TextureKind::R8))
+            .build()));
+
+        let mut body = RigidBody::new(ConvexShape::Sphere(SphereShape::new(size * 0.5)));
+        body.set_position(position);
+        body.set_velocity(velocity);
+        // Gibs bounce off level geometry, but do not block actors and projectiles.
+        body.collision_group = CollisionGroups::Projectile as u64;
+        body.collision_mask = CollisionGroups::Generic as u64;
+        let body = scene.physics.add_body(body);
+        scene.physics_binder.bind(model, body);
+
+        Self {
+            model,
+            body,
+            lifetime: GIB_LIFETIME,
+        }
+    }
+
+    fn clean_up(&mut self, scene: &mut Scene) {
+        scene.physics.remove_body(self.body);
+        scene.graph.remove_node(self.model);
+    }
+}
+
+impl Visit for Gib {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.model.visit("Model", visitor)?;
+        self.body.visit("Body", visitor)?;
+        self.lifetime.visit("Lifetime", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+pub struct GibContainer {
+    pool: Pool<Gib>
+}
+
+impl GibContainer {
+    pub fn new() -> Self {
+        Self {
+            pool: Pool::new()
+        }
+    }
+
+    /// Throws burst of gibs in random directions from given position.
+    pub fn spawn_burst(&mut self, scene: &mut Scene, resource_manager: &mut ResourceManager, position: Vec3) {
+        for _ in 0..GIBS_PER_BURST {
+            if self.pool.alive_count() >= MAX_GIBS {
+                let oldest = self.pool
+                    .pair_iter()
+                    .min_by(|(_, a), (_, b)| a.lifetime
+                        .partial_cmp(&b.lifetime)
+                        .unwrap_or(std::cmp::Ordering::Equal))
+                    .map(|(handle, _)| handle);
+                if let Some(oldest) = oldest {
+                    self.pool[oldest].clean_up(scene);
+                    self.pool.free(oldest);
+                }
+            }
+
+            let mut rng = rand::thread_rng();
+            let velocity = Vec3::new(
+                rng.gen_range(-0.1, 0.1),
+                rng.gen_range(0.05, 0.2),
+                rng.gen_range(-0.1, 0.1),
+            );
+            let offset = Vec3::new(0.0, rng.gen_range(0.2, 1.2), 0.0);
+            let gib = Gib::new(scene, resource_manager, position + offset, velocity);
+            self.pool.spawn(gib);
+        }
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, dt: f32) {
+        for gib in self.pool.iter_mut() {
+            gib.lifetime -= dt;
+            if gib.lifetime <= 0.0 {
+                gib.clean_up(scene);
+            }
+        }
+
+        self.pool.retain(|gib| gib.lifetime > 0.0);
+    }
+}
+
+impl Visit for GibContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.pool.visit("Pool", visitor)?;
+
+        visitor.leave_region()
+    }
+}
@@ -2,13 +2,23 @@
 //! However most of the styles are used from dark theme of rg3d-ui library so there
 //! is not much.
 
-use crate::{UINodeHandle, BuildContext};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+use crate::{
+    UINodeHandle,
+    BuildContext,
+    settings::Accessibility,
+};
 use rg3d::{
     engine::resource_manager::ResourceManager,
     utils,
     core::color::Color,
-    resource::texture::TextureKind,
+    resource::texture::{Texture, TextureKind},
     gui::{
+        node::StubNode,
+        ttf::Font,
         brush::Brush,
         scroll_bar::ScrollBarBuilder,
         check_box::CheckBoxBuilder,
@@ -19,9 +29,65 @@ use rg3d::{
         Thickness,
         Orientation,
         scroll_viewer::ScrollViewerBuilder,
+        border::BorderBuilder,
     },
 };
 
+/// Color that is used to fill images which textures are failed to load, it is intentionally
+/// ugly so missing asset can be easily spotted.
+pub const MISSING_TEXTURE_COLOR: Color = Color::opaque(255, 0, 255);
+
+/// Loads font from given file. Font is essential for any text in the game, so instead of
+/// crashing when font file is missing, we fall back to a copy of font embedded in executable.
+pub fn load_font<P: AsRef<Path>>(path: P, height: f32) -> Arc<Mutex<Font>> {
+    let font = Font::from_file(path.as_ref(), height, Font::default_char_set())
+        .or_else(|_| {
+            println!("Unable to load font {:?}! Embedded font will be used instead.", path.as_ref());
+            Font::from_memory(include_bytes!("../data/ui/SquaresBold.ttf").to_vec(), height, Font::default_char_set())
+        })
+        .expect("Embedded font must be valid!");
+    Arc::new(Mutex::new(font))
+}
+
+/// Puts content on a dark backdrop of given opacity, backdrop is made darker when high
+/// contrast is enabled. Zero opacity makes backdrop that is visible in high contrast mode only.
+pub fn create_backdrop(ctx: &mut BuildContext, accessibility: &Accessibility, widget_builder: WidgetBuilder<(), StubNode>, alpha: u8, content: UINodeHandle) -> UINodeHandle {
+    BorderBuilder::new(widget_builder
+        .with_background(Brush::Solid(accessibility.backdrop(alpha)))
+        .with_foreground(Brush::Solid(Color::from_rgba(0, 0, 0, 0)))
+        .with_child(content))
+        .with_stroke_thickness(Thickness::uniform(0.0))
+        .build(ctx)
+}
+
+/// Requests texture for user interface and prints a message if texture is missing.
+pub fn request_ui_texture<P: AsRef<Path>>(resource_manager: &mut ResourceManager, path: P) -> Option<Arc<Mutex<Texture>>> {
+    let texture = resource_manager.request_texture(path.as_ref(), TextureKind::RGBA8);
+    if texture.is_none() {
+        println!("Unable to load texture {:?}! Placeholder will be used instead.", path.as_ref());
+    }
+    texture
+}
+
+/// Creates image with given texture. If texture cannot be loaded, image will be filled with
+/// magenta color instead.
+pub fn create_image<P: AsRef<Path>>(
+    ctx: &mut BuildContext,
+    resource_manager: &mut ResourceManager,
+    widget_builder: WidgetBuilder<(), StubNode>,
+    path: P,
+) -> UINodeHandle {
+    let texture = request_ui_texture(resource_manager, path);
+    let widget_builder = if texture.is_none() {
+        widget_builder.with_background(Brush::Solid(MISSING_TEXTURE_COLOR))
+    } else {
+        widget_builder
+    };
+    ImageBuilder::new(widget_builder)
+        .with_opt_texture(utils::into_any_arc(texture))
+        .build(ctx)
+}
+
 pub struct ScrollBarData {
     pub min: f32,
     pub max: f32,
@@ -50,10 +116,8 @@ pub fn create_scroll_bar(ctx: &mut BuildContext, resource_manager: &mut Resource
         .with_min(data.min)
         .with_step(data.step)
         .with_value(data.value)
-        .with_indicator(ImageBuilder::new(WidgetBuilder::new()
-            .with_background(Brush::Solid(Color::opaque(60, 60, 60))))
-            .with_opt_texture(utils::into_any_arc(resource_manager.request_texture("data/ui/circle.png", TextureKind::RGBA8)))
-            .build(ctx))
+        .with_indicator(create_image(ctx, resource_manager, WidgetBuilder::new()
+            .with_background(Brush::Solid(Color::opaque(60, 60, 60))), "data/ui/circle.png"))
         .build(ctx)
 }
 
@@ -67,9 +131,7 @@ pub fn create_check_box(ctx: &mut BuildContext, resource_manager: &mut ResourceM
         .with_vertical_alignment(VerticalAlignment::Center)
         .with_horizontal_alignment(HorizontalAlignment::Left))
         .checked(Some(checked))
-        .with_check_mark(ImageBuilder::new(WidgetBuilder::new())
-            .with_opt_texture(utils::into_any_arc(resource_manager.request_texture("data/ui/check_mark.png", TextureKind::RGBA8)))
-            .build(ctx))
+        .with_check_mark(create_image(ctx, resource_manager, WidgetBuilder::new(), "data/ui/check_mark.png"))
         .build(ctx)
 }
 
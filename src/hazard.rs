@@ -0,0 +1,472 @@
+//! Hazards are parts of level that hurt actors. Pools of lava or acid burn everyone who
+//! stands in them, electrified floors shock while they're switched on and crushers kill
+//! everyone under them when they slam down. Hazard is a mesh named `HazardLava*`,
+//! `HazardAcid*`, `HazardElectric*` or `HazardCrusher*`. Pools and floors hurt actors that
+//! stand on or in their mesh, crusher mesh must be placed raised, it falls by
+//! [`CRUSHER_TRAVEL`] meters. Hazard meshes have no collision, level geometry under them
+//! holds actors. Damage is dealt through usual damage path without attacker, so deaths in
+//! hazards are environmental kills.
+
+use rg3d::{
+    core::{
+        pool::{Handle, Pool, PoolIterator},
+        math::{
+            vec3::Vec3,
+            aabb::AxisAlignedBoundingBox,
+        },
+        visitor::{Visit, Visitor, VisitResult},
+    },
+    scene::{
+        Scene,
+        node::Node,
+        graph::Graph,
+    },
+};
+use crate::{
+    actor::{Actor, ActorContainer},
+    damage::DamageKind,
+    effects::EffectKind,
+    message::Message,
+    SoundPriority,
+};
+use std::{
+    path::PathBuf,
+    sync::mpsc::Sender,
+};
+
+/// How often actors in pools and on electrified floors take damage, in seconds.
+const DAMAGE_TICK: f32 = 0.5;
+/// Vertical margin around pools and floors, body of actor that stands on a floor is above
+/// its mesh.
+const REACH_HEIGHT: f32 = 1.0;
+const ELECTRIC_ON_TIME: f32 = 3.0;
+const ELECTRIC_OFF_TIME: f32 = 3.0;
+/// Distance in meters that crusher falls.
+pub const CRUSHER_TRAVEL: f32 = 2.5;
+/// Time that raised crusher waits before next fall.
+const CRUSHER_WAIT_TIME: f32 = 4.0;
+const CRUSHER_FALL_TIME: f32 = 0.3;
+/// Time that crusher stays down after slam.
+const CRUSHER_HOLD_TIME: f32 = 0.5;
+const CRUSHER_RISE_TIME: f32 = 2.0;
+/// Damage of crusher slam, high enough to kill anyone.
+const CRUSHER_DAMAGE: f32 = 1000.0;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HazardKind {
+    Lava,
+    Acid,
+    Electric,
+    Crusher,
+}
+
+impl HazardKind {
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(HazardKind::Lava),
+            1 => Ok(HazardKind::Acid),
+            2 => Ok(HazardKind::Electric),
+            3 => Ok(HazardKind::Crusher),
+            _ => Err(format!("Invalid hazard kind {}", id))
+        }
+    }
+
+    fn id(self) -> u32 {
+        match self {
+            HazardKind::Lava => 0,
+            HazardKind::Acid => 1,
+            HazardKind::Electric => 2,
+            HazardKind::Crusher => 3,
+        }
+    }
+
+    /// Returns kind of hazard by name of its mesh, None if node is not a hazard.
+    pub fn from_node_name(name: &str) -> Option<Self> {
+        if name.starts_with("HazardLava") {
+            Some(HazardKind::Lava)
+        } else if name.starts_with("HazardAcid") {
+            Some(HazardKind::Acid)
+        } else if name.starts_with("HazardElectric") {
+            Some(HazardKind::Electric)
+        } else if name.starts_with("HazardCrusher") {
+            Some(HazardKind::Crusher)
+        } else {
+            None
+        }
+    }
+
+    /// Damage per second that actor takes while it is inside active hazard, crusher deals
+    /// its damage at once.
+    fn damage_per_second(self) -> f32 {
+        match self {
+            HazardKind::Lava => 40.0,
+            HazardKind::Acid => 20.0,
+            HazardKind::Electric => 60.0,
+            HazardKind::Crusher => 0.0,
+        }
+    }
+
+    fn hurt_effect(self) -> EffectKind {
+        match self {
+            HazardKind::Lava | HazardKind::Acid => EffectKind::Burn,
+            HazardKind::Electric => EffectKind::Sparks,
+            HazardKind::Crusher => EffectKind::Blood,
+        }
+    }
+
+    fn hurt_sound(self) -> &'static str {
+        match self {
+            HazardKind::Lava => "data/sounds/hazard_lava.ogg",
+            HazardKind::Acid => "data/sounds/hazard_acid.ogg",
+            HazardKind::Electric => "data/sounds/hazard_zap.ogg",
+            HazardKind::Crusher => "data/sounds/bullet_impact_body.ogg",
+        }
+    }
+}
+
+impl Visit for HazardKind {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CrusherPhase {
+    Raised,
+    Falling,
+    Down,
+    Rising,
+}
+
+impl CrusherPhase {
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(CrusherPhase::Raised),
+            1 => Ok(CrusherPhase::Falling),
+            2 => Ok(CrusherPhase::Down),
+            3 => Ok(CrusherPhase::Rising),
+            _ => Err(format!("Invalid crusher phase {}", id))
+        }
+    }
+
+    fn id(self) -> u32 {
+        match self {
+            CrusherPhase::Raised => 0,
+            CrusherPhase::Falling => 1,
+            CrusherPhase::Down => 2,
+            CrusherPhase::Rising => 3,
+        }
+    }
+}
+
+impl Visit for CrusherPhase {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+pub struct Hazard {
+    node: Handle<Node>,
+    kind: HazardKind,
+    /// World volume in which actors are hurt. Volume of crusher is space that its mesh
+    /// passes when falling.
+    volume: AxisAlignedBoundingBox,
+    /// Whether electrified floor is switched on, pools are always on.
+    enabled: bool,
+    /// Time left until floor is switched or until crusher moves again.
+    timer: f32,
+    crusher_phase: CrusherPhase,
+    /// 0.0 - raised crusher, 1.0 - crusher is down.
+    fall: f32,
+    raised_position: Vec3,
+}
+
+impl Default for Hazard {
+    fn default() -> Self {
+        Self {
+            node: Default::default(),
+            kind: HazardKind::Lava,
+            volume: Default::default(),
+            enabled: true,
+            timer: 0.0,
+            crusher_phase: CrusherPhase::Raised,
+            fall: 0.0,
+            raised_position: Default::default(),
+        }
+    }
+}
+
+/// Everything that happened to a hazard during an update.
+#[derive(Default)]
+struct HazardEvents {
+    /// Actors that should take given damage and positions where they were hurt.
+    hurt: Vec<(Handle<Actor>, Vec3, f32)>,
+    /// Floor was just switched on.
+    switched_on: bool,
+    /// Crusher just hit the ground.
+    slammed: bool,
+}
+
+impl Hazard {
+    pub fn new(graph: &Graph, node: Handle<Node>, kind: HazardKind) -> Self {
+        let mesh = &graph[node];
+        let bounds = mesh.as_mesh().world_bounding_box();
+        let volume = if kind == HazardKind::Crusher {
+            AxisAlignedBoundingBox {
+                min: Vec3::new(bounds.min.x, bounds.min.y - CRUSHER_TRAVEL - REACH_HEIGHT, bounds.min.z),
+                max: Vec3::new(bounds.max.x, bounds.min.y, bounds.max.z),
+            }
+        } else {
+            AxisAlignedBoundingBox {
+                min: Vec3::new(bounds.min.x, bounds.min.y - REACH_HEIGHT, bounds.min.z),
+                max: Vec3::new(bounds.max.x, bounds.max.y + REACH_HEIGHT, bounds.max.z),
+            }
+        };
+        let timer = match kind {
+            HazardKind::Electric => ELECTRIC_ON_TIME,
+            HazardKind::Crusher => CRUSHER_WAIT_TIME,
+            HazardKind::Lava | HazardKind::Acid => 0.0,
+        };
+
+        Self {
+            node,
+            kind,
+            volume,
+            timer,
+            raised_position: mesh.local_transform().position(),
+            ..Default::default()
+        }
+    }
+
+    pub fn volume(&self) -> &AxisAlignedBoundingBox {
+        &self.volume
+    }
+
+    fn center(&self) -> Vec3 {
+        (self.volume.min + self.volume.max).scale(0.5)
+    }
+
+    fn update(&mut self, graph: &mut Graph, actors: &[(Handle<Actor>, Vec3)], damage_tick: bool, dt: f32) -> HazardEvents {
+        let mut events = HazardEvents::default();
+        let volume = self.volume;
+        let victims = actors.iter().filter(|(_, position)| volume.is_contains_point(*position));
+
+        match self.kind {
+            HazardKind::Lava | HazardKind::Acid | HazardKind::Electric => {
+                if self.kind == HazardKind::Electric {
+                    self.timer -= dt;
+                    if self.timer <= 0.0 {
+                        self.enabled = !self.enabled;
+                        self.timer = if self.enabled { ELECTRIC_ON_TIME } else { ELECTRIC_OFF_TIME };
+                        events.switched_on = self.enabled;
+                    }
+                }
+                if self.enabled && damage_tick {
+                    let amount = self.kind.damage_per_second() * DAMAGE_TICK;
+                    events.hurt.extend(victims.map(|&(actor, position)| (actor, position, amount)));
+                }
+            }
+            HazardKind::Crusher => {
+                match self.crusher_phase {
+                    CrusherPhase::Raised => {
+                        self.timer -= dt;
+                        if self.timer <= 0.0 {
+                            self.crusher_phase = CrusherPhase::Falling;
+                        }
+                    }
+                    CrusherPhase::Falling => {
+                        self.fall = (self.fall + dt / CRUSHER_FALL_TIME).min(1.0);
+                        if self.fall >= 1.0 {
+                            // Nobody can get out from under falling crusher, so actors are
+                            // killed when it hits the ground.
+                            events.slammed = true;
+                            events.hurt.extend(victims.map(|&(actor, position)| (actor, position, CRUSHER_DAMAGE)));
+                            self.crusher_phase = CrusherPhase::Down;
+                            self.timer = CRUSHER_HOLD_TIME;
+                        }
+                    }
+                    CrusherPhase::Down => {
+                        self.timer -= dt;
+                        if self.timer <= 0.0 {
+                            self.crusher_phase = CrusherPhase::Rising;
+                        }
+                    }
+                    CrusherPhase::Rising => {
+                        self.fall = (self.fall - dt / CRUSHER_RISE_TIME).max(0.0);
+                        if self.fall <= 0.0 {
+                            self.crusher_phase = CrusherPhase::Raised;
+                            self.timer = CRUSHER_WAIT_TIME;
+                        }
+                    }
+                }
+                graph[self.node]
+                    .local_transform_mut()
+                    .set_position(self.raised_position - Vec3::new(0.0, CRUSHER_TRAVEL * self.fall, 0.0));
+            }
+        }
+
+        events
+    }
+}
+
+impl Visit for Hazard {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.node.visit("Node", visitor)?;
+        self.kind.visit("Kind", visitor)?;
+        self.volume.visit("Volume", visitor)?;
+        self.enabled.visit("Enabled", visitor)?;
+        self.timer.visit("Timer", visitor)?;
+        self.crusher_phase.visit("CrusherPhase", visitor)?;
+        self.fall.visit("Fall", visitor)?;
+        self.raised_position.visit("RaisedPosition", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+pub struct HazardContainer {
+    pool: Pool<Hazard>,
+    /// Time left until next damage tick of pools and floors.
+    damage_timer: f32,
+}
+
+impl Default for HazardContainer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HazardContainer {
+    pub fn new() -> Self {
+        Self {
+            pool: Pool::new(),
+            damage_timer: DAMAGE_TICK,
+        }
+    }
+
+    pub fn add(&mut self, hazard: Hazard) -> Handle<Hazard> {
+        self.pool.spawn(hazard)
+    }
+
+    pub fn iter(&self) -> PoolIterator<Hazard> {
+        self.pool.iter()
+    }
+
+    /// Returns volumes of every hazard, bots keep out of them.
+    pub fn volumes(&self) -> Vec<AxisAlignedBoundingBox> {
+        self.pool.iter().map(|hazard| hazard.volume).collect()
+    }
+
+    /// Moves hazards and hurts actors inside of them. Replica of network game passes false
+    /// in `deal_damage`, its hazards only move, because damage is dealt by server.
+    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, sender: &Sender<Message>, deal_damage: bool, dt: f32) {
+        self.damage_timer -= dt;
+        let damage_tick = self.damage_timer <= 0.0;
+        if damage_tick {
+            self.damage_timer += DAMAGE_TICK;
+        }
+
+        let actors = actors
+            .pair_iter()
+            .filter(|(_, actor)| !actor.is_dead())
+            .map(|(handle, actor)| (handle, actor.position(&scene.physics)))
+            .collect::<Vec<_>>();
+        for hazard in self.pool.iter_mut() {
+            let events = hazard.update(&mut scene.graph, &actors, damage_tick, dt);
+
+            if events.switched_on {
+                sender.send(Message::CreateEffect {
+                    kind: EffectKind::Sparks,
+                    position: hazard.center(),
+                }).unwrap();
+                sender.send(Message::PlaySound {
+                    path: PathBuf::from("data/sounds/hazard_electric.ogg"),
+                    position: hazard.center(),
+                    gain: 1.0,
+                    rolloff_factor: 2.0,
+                    radius: 4.0,
+                    priority: SoundPriority::Normal,
+                }).unwrap();
+            }
+            if events.slammed {
+                let position = Vec3::new(hazard.center().x, hazard.volume.min.y, hazard.center().z);
+                sender.send(Message::CreateEffect {
+                    kind: EffectKind::Smoke,
+                    position,
+                }).unwrap();
+                sender.send(Message::PlaySound {
+                    path: PathBuf::from("data/sounds/hazard_crusher.ogg"),
+                    position,
+                    gain: 1.0,
+                    rolloff_factor: 2.0,
+                    radius: 6.0,
+                    priority: SoundPriority::High,
+                }).unwrap();
+            }
+            if !deal_damage {
+                continue;
+            }
+            for (actor, position, amount) in events.hurt {
+                sender.send(Message::DamageActor {
+                    actor,
+                    who: Handle::NONE,
+                    amount,
+                    region: None,
+                    kind: DamageKind::Environment,
+                    position: None,
+                }).unwrap();
+                sender.send(Message::CreateEffect {
+                    kind: hazard.kind.hurt_effect(),
+                    position,
+                }).unwrap();
+                sender.send(Message::PlaySound {
+                    path: PathBuf::from(hazard.kind.hurt_sound()),
+                    position,
+                    gain: 1.0,
+                    rolloff_factor: 3.0,
+                    radius: 2.0,
+                    priority: SoundPriority::Normal,
+                }).unwrap();
+            }
+        }
+    }
+}
+
+impl Visit for HazardContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.pool.visit("Pool", visitor)?;
+        self.damage_timer.visit("DamageTimer", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Returns horizontal direction that leads out of hazard which contains given position,
+/// None if position is not in any hazard.
+pub fn escape_direction(position: Vec3, hazards: &[AxisAlignedBoundingBox]) -> Option<Vec3> {
+    let hazard = hazards.iter().find(|hazard| hazard.is_contains_point(position))?;
+    let center = (hazard.min + hazard.max).scale(0.5);
+    let away = Vec3::new(position.x - center.x, 0.0, position.z - center.z);
+    // Actor right in the center goes anywhere.
+    away.normalized().or(Some(Vec3::new(1.0, 0.0, 0.0)))
+}
@@ -1,40 +1,56 @@
 use std::{
-    path::Path,
-    sync::{Arc, Mutex},
     collections::VecDeque,
+    sync::{Arc, Mutex},
+    rc::Rc,
+    cell::RefCell,
 };
 use rg3d::{
-    core::color::Color,
-    resource::texture::TextureKind,
+    core::{
+        color::Color,
+        math::{vec2::Vec2, vec3::Vec3, mat4::Mat4},
+    },
     event::{
         Event,
         WindowEvent,
     },
-    utils,
     gui::{
         border::BorderBuilder,
-        ttf::Font,
+        canvas::CanvasBuilder,
         HorizontalAlignment,
         grid::{GridBuilder, Column, Row},
         widget::WidgetBuilder,
         text::TextBuilder,
         stack_panel::StackPanelBuilder,
-        image::ImageBuilder,
         VerticalAlignment,
         Thickness,
         brush::Brush,
         Orientation,
         message::WidgetMessage,
         message::TextMessage,
+        ttf::Font,
     },
 };
 use crate::{
     leader_board::{
         LeaderBoard,
         LeaderBoardUI,
+        kill_streak_name,
     },
     GameTime,
+    gui,
+    crosshair::{self, CrosshairConfig, CrosshairProfile},
+    comms::{CalloutWheel, TeamCallout},
+    hud_layout::{HudLayout, HudElement, HudModePreset, ModeWidget, HUD_ELEMENTS, HUD_ANCHORS, MODE_WIDGETS},
+    control_scheme::ControlScheme,
+    palette::{self, ColorBlindMode},
+    hud_theme::{HudTheme, HudColors},
+    character::Team,
+    control_point::{ControlPoint, ControlPointState},
+    settings::{DamageFeedback, Accessibility},
+    damage::DamageKind,
+    weapon::ReloadStyle,
     message::Message,
+    target_range::RangeStats,
     MatchOptions,
     UINodeHandle,
     GameEngine,
@@ -45,239 +61,843 @@ pub struct Hud {
     root: UINodeHandle,
     health: UINodeHandle,
     armor: UINodeHandle,
+    /// Shown values of health and armor, they drain toward actual values instead of snapping.
+    health_value: SmoothValue,
+    armor_value: SmoothValue,
     ammo: UINodeHandle,
+    /// Whether ammo counter is painted with overheat color now.
+    overheat_shown: bool,
     time: UINodeHandle,
+    lives: UINodeHandle,
+    /// State of every control point in domination, empty in other modes.
+    control_points: UINodeHandle,
     message: UINodeHandle,
     message_queue: VecDeque<String>,
     message_timeout: f32,
     leader_board: LeaderBoardUI,
+    /// Scores element with leader scores and match limit.
+    scores: UINodeHandle,
     match_limit: UINodeHandle,
     first_score: UINodeHandle,
     second_score: UINodeHandle,
     died: UINodeHandle,
+    announcement: UINodeHandle,
+    announcement_text: String,
+    announcement_timeout: f32,
+    /// Large number in the middle of the screen while intro countdown goes.
+    countdown: UINodeHandle,
+    countdown_timeout: f32,
+    /// Full-screen canvas for widgets that are attached to objects in the world.
+    world_canvas: UINodeHandle,
+    nameplates: Vec<UINodeHandle>,
+    item_timers: Vec<UINodeHandle>,
+    markers: Vec<Marker>,
+    /// Text widgets of markers, reused between frames same as nameplates.
+    marker_texts: Vec<UINodeHandle>,
+    /// Canvas inside of compass bar, cardinal directions and markers are placed on it.
+    compass: UINodeHandle,
+    /// Labels of cardinal directions, in the order of `COMPASS_POINTS`.
+    compass_points: Vec<UINodeHandle>,
+    /// Text widgets of compass markers, reused between frames same as nameplates.
+    compass_markers: Vec<UINodeHandle>,
+    compass_font: Arc<Mutex<Font>>,
+    color_blind_mode: ColorBlindMode,
+    theme: HudTheme,
+    accessibility: Accessibility,
+    damage_numbers: Vec<DamageNumber>,
+    damage_numbers_enabled: bool,
+    damage_number_font: Arc<Mutex<Font>>,
+    damage_number_large_font: Arc<Mutex<Font>>,
+    visible: bool,
+    screen_size: Vec2,
+    damage_feedback: DamageFeedback,
+    vignette: UINodeHandle,
+    vignette_timeout: f32,
+    hit_flash: UINodeHandle,
+    hit_flash_timeout: f32,
+    /// White screen of player blinded by flashbang.
+    flash_blind: UINodeHandle,
+    flash_blind_timeout: f32,
+    /// Kind of last damage taken, vignette and flash are painted with its color.
+    damage_kind: DamageKind,
+    damage_indicators: Vec<DamageIndicator>,
+    spawn_protection: UINodeHandle,
+    spawn_protected: bool,
+    crosshair: UINodeHandle,
+    crosshair_config: CrosshairConfig,
+    /// Crosshair of active weapon of player, None if player holds no weapon.
+    crosshair_profile: Option<CrosshairProfile>,
+    hit_confirm_timeout: f32,
+    scale: f32,
+    layout: HudLayout,
+    /// Preset of current mode, it is applied again when HUD is rebuilt.
+    mode_preset: HudModePreset,
+    control_hints: UINodeHandle,
+    control_hints_timeout: f32,
+    control_scheme: Rc<RefCell<ControlScheme>>,
+    /// Prompt to press use button, shown while player focuses on usable object.
+    use_prompt: UINodeHandle,
+    use_action: Option<String>,
+    /// Magnification of scope under crosshair, shown only while player aims through scope.
+    zoom: UINodeHandle,
+    zoom_shown: Option<f32>,
+    /// Line with note that player is typing, hidden when player does not type.
+    note_input: UINodeHandle,
+    note_feed: UINodeHandle,
+    /// Stats of target range practice, empty in other modes.
+    range_stats: UINodeHandle,
+    /// Recent notes with time left until they disappear from feed.
+    notes: VecDeque<(String, f32)>,
+    callout_wheel: CalloutWheel,
+}
+
+/// How long an announcement is shown.
+const ANNOUNCEMENT_TIMEOUT: f32 = 2.5;
+/// How long a countdown number is shown, next number normally replaces it earlier.
+const COUNTDOWN_TIMEOUT: f32 = 1.5;
+/// How long "Fight!" is shown after countdown.
+const FIGHT_TIMEOUT: f32 = 1.0;
+
+/// How long a note is shown in note feed.
+const NOTE_TIMEOUT: f32 = 8.0;
+/// Max amount of notes in note feed, oldest notes are removed first.
+const NOTE_FEED_SIZE: usize = 5;
+
+/// How long control hints are shown after player spawns.
+const CONTROL_HINTS_TIMEOUT: f32 = 6.0;
+
+/// Makes text with hints about most important controls, buttons are taken from control
+/// scheme so rebinds are reflected.
+fn control_hints_text(control_scheme: &ControlScheme) -> String {
+    format!("Press {} to shoot\n\
+             Hold {} to run\n\
+             Press {} to jump, {} to crouch\n\
+             Use {} and {} to switch weapons\n\
+             Press {} to toggle camera",
+            control_scheme.shoot.button.label(),
+            control_scheme.run.button.label(),
+            control_scheme.jump.button.label(),
+            control_scheme.crouch.button.label(),
+            control_scheme.next_weapon.button.label(),
+            control_scheme.prev_weapon.button.label(),
+            control_scheme.toggle_camera.button.label())
+}
+
+/// Time in which smooth indicators reach new value, no matter how large the change is.
+const SMOOTH_VALUE_DURATION: f32 = 0.35;
+
+/// Value that moves toward its target over a short time, so indicators show draining
+/// animation instead of jumping to new value.
+#[derive(Copy, Clone, Debug)]
+struct SmoothValue {
+    current: f32,
+    target: f32,
+    /// Units per second, recalculated every time target changes.
+    speed: f32,
+}
+
+impl SmoothValue {
+    fn new(value: f32) -> Self {
+        Self {
+            current: value,
+            target: value,
+            speed: 0.0,
+        }
+    }
+
+    fn set_target(&mut self, target: f32) {
+        if target != self.target {
+            self.target = target;
+            self.speed = (target - self.current).abs() / SMOOTH_VALUE_DURATION;
+        }
+    }
+
+    /// Moves current value toward target, returns true if current value has changed.
+    fn update(&mut self, dt: f32) -> bool {
+        if self.current == self.target {
+            return false;
+        }
+        let step = self.speed * dt;
+        if (self.target - self.current).abs() <= step {
+            self.current = self.target;
+        } else {
+            self.current += step * (self.target - self.current).signum();
+        }
+        true
+    }
+
+    fn value(&self) -> f32 {
+        self.current
+    }
+}
+
+/// Damage above which damage number is drawn with large font.
+const VIGNETTE_DURATION: f32 = 0.8;
+const HIT_FLASH_DURATION: f32 = 0.15;
+/// How long crosshair stays painted with hit confirmation color.
+const HIT_CONFIRM_DURATION: f32 = 0.12;
+/// Amount of segments of heat gauge of weapons with heat.
+const HEAT_GAUGE_SEGMENTS: usize = 10;
+/// Amount of segments of capture gauge of control points.
+const CAPTURE_GAUGE_SEGMENTS: usize = 10;
+const OVERHEAT_COLOR: Color = Color::opaque(255, 60, 30);
+const DAMAGE_INDICATOR_DURATION: f32 = 1.5;
+/// How long screen stays white after flashbang that went off right in front of player.
+const FLASH_BLIND_DURATION: f32 = 5.0;
+/// White screen fades out during this last part of blindness, before it is fully opaque.
+const FLASH_BLIND_FADE_TIME: f32 = 2.0;
+/// Distance from center of the screen to damage direction indicator, before scaling.
+const DAMAGE_INDICATOR_RADIUS: f32 = 120.0;
+/// Size of indicator of weakest and strongest hits, before scaling.
+const DAMAGE_INDICATOR_SIZE: (f32, f32) = (10.0, 26.0);
+/// Most indicators shown at once, hit from a new direction takes the oldest one when
+/// every indicator is in use.
+const MAX_DAMAGE_INDICATORS: usize = 4;
+/// Hit of the same kind that comes within this angle from direction of shown indicator
+/// refreshes it instead of taking a new one, in radians.
+const DAMAGE_INDICATOR_MERGE_ANGLE: f32 = 0.35;
+/// Damage that makes vignette and flash fully opaque, smaller hits are fainter.
+const FULL_FEEDBACK_DAMAGE: f32 = 30.0;
+
+/// Returns color of damage feedback for given kind of damage, colors come from palette of
+/// colorblind mode.
+fn damage_color(mode: ColorBlindMode, kind: DamageKind, alpha: f32) -> Color {
+    let alpha = (alpha.max(0.0).min(1.0) * 255.0) as u8;
+    let color = palette::palette(mode).damage_color(kind);
+    Color::from_rgba(color.r, color.g, color.b, alpha)
+}
+
+/// Arrow around the crosshair that points to where damage came from.
+struct DamageIndicator {
+    widget: UINodeHandle,
+    /// Angle to attacker, clockwise from view direction, in radians.
+    direction: f32,
+    kind: DamageKind,
+    timeout: f32,
+    size: f32,
+}
+
+/// Returns angle between two directions in [0; PI] range.
+fn angle_between(a: f32, b: f32) -> f32 {
+    let difference = (a - b).rem_euclid(2.0 * std::f32::consts::PI);
+    difference.min(2.0 * std::f32::consts::PI - difference)
+}
+
+const LARGE_DAMAGE: f32 = 40.0;
+const DAMAGE_NUMBER_LIFETIME: f32 = 1.0;
+/// Speed of rising of damage number in world units per second.
+const DAMAGE_NUMBER_RISE_SPEED: f32 = 0.8;
+
+/// Number that floats above damaged bot. Text widgets of finished numbers are kept in pool
+/// and reused by next hit.
+struct DamageNumber {
+    text: UINodeHandle,
+    large: bool,
+    color: Color,
+    position: Vec3,
+    lifetime: f32,
+}
+
+/// Text that should be shown above some object in the world.
+pub struct Nameplate {
+    pub text: String,
+    pub color: Color,
+    pub position: Vec3,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MarkerIcon {
+    Enemy,
+    Health,
+    /// Place where player died last time.
+    LastDeath,
+    /// Control point with given label and its owner.
+    ControlPoint(&'static str, Team),
+}
+
+impl MarkerIcon {
+    fn label(self) -> &'static str {
+        match self {
+            MarkerIcon::Enemy => "Enemy",
+            MarkerIcon::Health => "Health",
+            MarkerIcon::LastDeath => "Died here",
+            MarkerIcon::ControlPoint(label, _) => label,
+        }
+    }
+
+    fn color(self, colors: &HudColors, mode: ColorBlindMode) -> Color {
+        match self {
+            MarkerIcon::Enemy => colors.enemy_marker,
+            MarkerIcon::Health => colors.health_marker,
+            MarkerIcon::LastDeath => colors.notes,
+            MarkerIcon::ControlPoint(_, owner) => palette::palette(mode).team_color(owner).unwrap_or(colors.text),
+        }
+    }
+}
+
+/// Point of interest in the world that is shown on the screen even if it is not visible,
+/// off-screen markers are pinned to screen edge and point to their direction.
+struct Marker {
+    position: Vec3,
+    icon: MarkerIcon,
+}
+
+/// Distance from screen edge to off-screen markers in pixels.
+const MARKER_EDGE_MARGIN: f32 = 40.0;
+
+/// Size of compass bar before HUD scale is applied.
+const COMPASS_WIDTH: f32 = 600.0;
+const COMPASS_HEIGHT: f32 = 44.0;
+/// Cardinal directions shown on compass, 45 degrees apart clockwise starting from north.
+const COMPASS_POINTS: [&str; 8] = ["N", "NE", "E", "SE", "S", "SW", "W", "NW"];
+
+/// Bearing of horizontal direction in radians, measured clockwise from north. North is
+/// +Z axis and east is -X axis, so bearing of view direction grows when player turns right.
+pub fn compass_bearing(direction: Vec3) -> f32 {
+    (-direction.x).atan2(direction.z)
+}
+
+/// Wraps angle in radians into [-PI; PI] range.
+fn wrap_angle(angle: f32) -> f32 {
+    use std::f32::consts::PI;
+    (angle + PI).rem_euclid(2.0 * PI) - PI
+}
+
+/// Projects point from world space to screen space. Returns None if point is behind camera.
+pub fn world_to_screen(view_projection: &Mat4, point: Vec3, frame_size: (u32, u32)) -> Option<Vec2> {
+    let m = &view_projection.f;
+    let x = m[0] * point.x + m[4] * point.y + m[8] * point.z + m[12];
+    let y = m[1] * point.x + m[5] * point.y + m[9] * point.z + m[13];
+    let w = m[3] * point.x + m[7] * point.y + m[11] * point.z + m[15];
+    if w <= std::f32::EPSILON {
+        return None;
+    }
+    let (ndc_x, ndc_y) = (x / w, y / w);
+    if ndc_x.abs() > 1.0 || ndc_y.abs() > 1.0 {
+        return None;
+    }
+    Some(Vec2::new(
+        (ndc_x + 1.0) * 0.5 * frame_size.0 as f32,
+        (1.0 - ndc_y) * 0.5 * frame_size.1 as f32,
+    ))
+}
+
+/// Projects point from world space to screen space same as `world_to_screen`, but points that
+/// are outside of screen or behind camera are moved to the closest point at screen edge, which
+/// is `margin` pixels away from actual edge. Returns screen position and arrow that should be
+/// shown for clamped point.
+fn world_to_screen_edge(view_projection: &Mat4, point: Vec3, frame_size: (u32, u32), margin: f32) -> (Vec2, Option<&'static str>) {
+    let m = &view_projection.f;
+    let x = m[0] * point.x + m[4] * point.y + m[8] * point.z + m[12];
+    let y = m[1] * point.x + m[5] * point.y + m[9] * point.z + m[13];
+    let w = m[3] * point.x + m[7] * point.y + m[11] * point.z + m[15];
+    let (mut ndc_x, mut ndc_y) = if w.abs() > std::f32::EPSILON {
+        (x / w, y / w)
+    } else {
+        (x, y)
+    };
+    // Projection of point behind camera is mirrored.
+    if w < 0.0 {
+        ndc_x = -ndc_x;
+        ndc_y = -ndc_y;
+    }
+
+    let limit_x = (1.0 - 2.0 * margin / frame_size.0 as f32).max(0.0);
+    let limit_y = (1.0 - 2.0 * margin / frame_size.1 as f32).max(0.0);
+    let mut arrow = None;
+    if w <= std::f32::EPSILON || ndc_x.abs() > limit_x || ndc_y.abs() > limit_y {
+        let kx = ndc_x.abs() / limit_x.max(std::f32::EPSILON);
+        let ky = ndc_y.abs() / limit_y.max(std::f32::EPSILON);
+        let k = kx.max(ky);
+        if k > std::f32::EPSILON {
+            ndc_x /= k;
+            ndc_y /= k;
+        } else {
+            // Point is exactly behind camera, show it at the bottom.
+            ndc_y = -limit_y;
+        }
+        arrow = Some(if kx >= ky {
+            if ndc_x > 0.0 { ">" } else { "<" }
+        } else if ndc_y > 0.0 {
+            "^"
+        } else {
+            "v"
+        });
+    }
+
+    (Vec2::new(
+        (ndc_x + 1.0) * 0.5 * frame_size.0 as f32,
+        (1.0 - ndc_y) * 0.5 * frame_size.1 as f32,
+    ), arrow)
+}
+
+/// Shows texts at screen positions of their objects. Text widgets are taken from `pool` and
+/// reused between frames, new ones are created only if there is not enough of them.
+fn update_world_texts(ui: &mut Gui, canvas: UINodeHandle, pool: &mut Vec<UINodeHandle>, view_projection: &Mat4, frame_size: (u32, u32), texts: &[Nameplate]) {
+    let mut count = 0;
+    for nameplate in texts {
+        if let Some(screen_position) = world_to_screen(view_projection, nameplate.position, frame_size) {
+            if count == pool.len() {
+                let text = TextBuilder::new(WidgetBuilder::new())
+                    .build(&mut ui.build_ctx());
+                ui.send_message(WidgetMessage::link(text, canvas));
+                pool.push(text);
+            }
+            let text = pool[count];
+            count += 1;
+
+            // Roughly center text above the object.
+            let offset = Vec2::new(nameplate.text.len() as f32 * 4.0, 10.0);
+            ui.send_message(TextMessage::text(text, nameplate.text.clone()));
+            ui.send_message(WidgetMessage::foreground(text, Brush::Solid(nameplate.color)));
+            ui.send_message(WidgetMessage::desired_position(text, screen_position - offset));
+            ui.send_message(WidgetMessage::visibility(text, true));
+        }
+    }
+
+    for &unused in pool[count..].iter() {
+        ui.send_message(WidgetMessage::visibility(unused, false));
+    }
 }
 
 impl Hud {
-    pub fn new(engine: &mut GameEngine) -> Self {
+    pub fn new(engine: &mut GameEngine, scale: f32, layout: HudLayout, theme: HudTheme, accessibility: Accessibility, crosshair_config: CrosshairConfig, control_scheme: Rc<RefCell<ControlScheme>>) -> Self {
         let leader_board = LeaderBoardUI::new(engine);
+        Self::build(engine, scale, layout, theme, accessibility, leader_board, crosshair_config, control_scheme)
+    }
 
+    /// Creates widgets of HUD, every font and fixed size is multiplied by `scale`, fonts and
+    /// text boxes are also multiplied by text scale of `accessibility`. Elements are placed
+    /// and hidden as `layout` says and painted with colors of `theme`.
+    #[allow(clippy::too_many_arguments)]
+    fn build(engine: &mut GameEngine, scale: f32, layout: HudLayout, theme: HudTheme, accessibility: Accessibility, leader_board: LeaderBoardUI, crosshair_config: CrosshairConfig, control_scheme: Rc<RefCell<ControlScheme>>) -> Self {
+        let colors = theme.colors();
+        let text_scale = scale * accessibility.text_scale;
         let frame_size = engine.renderer.get_frame_size();
         let ctx = &mut engine.user_interface.build_ctx();
         let resource_manager = &mut engine.resource_manager.lock().unwrap();
 
-        let font = Font::from_file(
-            Path::new("data/ui/SquaresBold.ttf"),
-            35.0,
-            Font::default_char_set()).unwrap();
-        let font = Arc::new(Mutex::new(font));
+        let font = gui::load_font("data/ui/SquaresBold.ttf", 35.0 * text_scale);
 
+        let crosshair;
         let health;
         let armor;
         let ammo;
         let message;
         let time;
+        let lives;
+        let control_points;
         let first_score;
         let second_score;
         let match_limit;
         let died;
-        let root = GridBuilder::new(WidgetBuilder::new()
-            .with_width(frame_size.0 as f32)
-            .with_height(frame_size.1 as f32)
-            .with_visibility(false)
-            .with_child(ImageBuilder::new(WidgetBuilder::new()
-                .with_horizontal_alignment(HorizontalAlignment::Center)
-                .with_vertical_alignment(VerticalAlignment::Center)
-                .with_width(33.0)
-                .with_height(33.0)
-                .on_row(0)
-                .on_column(1))
-                .with_opt_texture(utils::into_any_arc(resource_manager.request_texture(Path::new("data/ui/crosshair.tga"), TextureKind::RGBA8)))
-                .build(ctx))
+        let announcement;
+        let countdown;
+        let control_hints;
+        let use_prompt;
+        let zoom;
+        let note_input;
+        let note_feed;
+        let range_stats;
+        let compass;
+        let callout_wheel;
+        let mut compass_points = Vec::new();
+        let element_margin = Thickness::uniform(5.0 * scale);
+        let timer = StackPanelBuilder::new(WidgetBuilder::new()
+            .with_visibility(layout.timer.visible)
+            .with_margin(element_margin)
             .with_child({
                 time = TextBuilder::new(WidgetBuilder::new()
+                    .with_foreground(Brush::Solid(colors.text))
                     .with_margin(Thickness::uniform(2.0))
-                    .with_horizontal_alignment(HorizontalAlignment::Center)
-                    .on_column(1)
-                    .on_row(0))
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
                     .with_font(font.clone())
                     .with_text("00:00:00")
                     .build(ctx);
                 time
             })
-            .with_child(GridBuilder::new(WidgetBuilder::new()
-                .on_column(0)
-                .on_row(0)
-                .with_vertical_alignment(VerticalAlignment::Bottom)
-                .with_margin(Thickness {
-                    left: 50.0,
-                    top: 0.0,
-                    right: 0.0,
-                    bottom: 150.0,
-                })
-                .with_child(BorderBuilder::new(WidgetBuilder::new()
-                    .on_column(0)
-                    .with_background(Brush::Solid(Color::opaque(34, 177, 76)))
-                    .with_foreground(Brush::Solid(Color::opaque(52, 216, 101)))
-                    .with_child({
-                        match_limit = TextBuilder::new(WidgetBuilder::new()
-                            .with_horizontal_alignment(HorizontalAlignment::Center)
-                            .with_vertical_alignment(VerticalAlignment::Center)
-                            .with_foreground(Brush::Solid(Color::BLACK)))
-                            .with_text("0")
-                            .build(ctx);
-                        match_limit
-                    }))
-                    .with_stroke_thickness(Thickness::uniform(2.0))
-                    .build(ctx))
-                .with_child(BorderBuilder::new(WidgetBuilder::new()
-                    .on_column(1)
-                    .with_background(Brush::Solid(Color::opaque(249, 166, 2)))
-                    .with_foreground(Brush::Solid(Color::opaque(200, 110, 0)))
-                    .with_child({
-                        first_score = TextBuilder::new(WidgetBuilder::new()
-                            .with_horizontal_alignment(HorizontalAlignment::Center)
-                            .with_vertical_alignment(VerticalAlignment::Center)
-                            .with_foreground(Brush::Solid(Color::BLACK)))
-                            .with_text("0")
-                            .build(ctx);
-                        first_score
-                    }))
-                    .with_stroke_thickness(Thickness::uniform(2.0))
-                    .build(ctx))
-                .with_child(BorderBuilder::new(WidgetBuilder::new()
-                    .on_column(2)
-                    .with_background(Brush::Solid(Color::opaque(127, 127, 127)))
-                    .with_foreground(Brush::Solid(Color::opaque(80, 80, 80)))
-                    .with_child({
-                        second_score = TextBuilder::new(WidgetBuilder::new()
-                            .with_horizontal_alignment(HorizontalAlignment::Center)
-                            .with_vertical_alignment(VerticalAlignment::Center)
-                            .with_foreground(Brush::Solid(Color::BLACK)))
-                            .with_text("0")
-                            .build(ctx);
-                        second_score
-                    }))
-                    .with_stroke_thickness(Thickness::uniform(2.0))
-                    .build(ctx)))
-                .add_column(Column::strict(75.0))
-                .add_column(Column::strict(75.0))
-                .add_column(Column::strict(75.0))
-                .add_row(Row::strict(33.0))
-                .build(ctx))
-            .with_child(StackPanelBuilder::new(WidgetBuilder::new()
-                .with_margin(Thickness::bottom(10.0))
-                .on_column(0)
-                .with_vertical_alignment(VerticalAlignment::Bottom)
-                .with_horizontal_alignment(HorizontalAlignment::Center)
-                .with_child(ImageBuilder::new(WidgetBuilder::new()
-                    .with_width(35.0)
-                    .with_height(35.0))
-                    .with_opt_texture(utils::into_any_arc(resource_manager.request_texture(Path::new("data/ui/health_icon.png"), TextureKind::RGBA8)))
-                    .build(ctx))
-                .with_child(TextBuilder::new(WidgetBuilder::new()
-                    .with_width(170.0)
-                    .with_height(35.0))
-                    .with_text("Health:")
+            .with_child({
+                lives = TextBuilder::new(WidgetBuilder::new()
+                    .with_foreground(Brush::Solid(colors.text))
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
+                    .with_font(font.clone())
+                    .build(ctx);
+                lives
+            })
+            .with_child({
+                control_points = TextBuilder::new(WidgetBuilder::new()
+                    .with_foreground(Brush::Solid(colors.text))
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
                     .with_font(font.clone())
-                    .build(ctx))
+                    .build(ctx);
+                control_points
+            }))
+            .build(ctx);
+        let scores = GridBuilder::new(WidgetBuilder::new()
+            .with_visibility(layout.scores.visible)
+            .with_margin(element_margin)
+            .with_child(BorderBuilder::new(WidgetBuilder::new()
+                .on_column(0)
+                .with_background(Brush::Solid(Color::opaque(34, 177, 76)))
+                .with_foreground(Brush::Solid(Color::opaque(52, 216, 101)))
                 .with_child({
-                    health = TextBuilder::new(WidgetBuilder::new()
-                        .with_foreground(Brush::Solid(Color::opaque(180, 14, 22)))
-                        .with_width(170.0)
-                        .with_height(35.0))
-                        .with_text("100")
-                        .with_font(font.clone())
+                    match_limit = TextBuilder::new(WidgetBuilder::new()
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .with_vertical_alignment(VerticalAlignment::Center)
+                        .with_foreground(Brush::Solid(Color::BLACK)))
+                        .with_text("0")
                         .build(ctx);
-                    health
+                    match_limit
                 }))
-                .with_orientation(Orientation::Horizontal)
+                .with_stroke_thickness(Thickness::uniform(2.0))
                 .build(ctx))
-            .with_child(StackPanelBuilder::new(WidgetBuilder::new()
-                .with_margin(Thickness::bottom(10.0))
+            .with_child(BorderBuilder::new(WidgetBuilder::new()
                 .on_column(1)
-                .with_vertical_alignment(VerticalAlignment::Bottom)
-                .with_horizontal_alignment(HorizontalAlignment::Center)
-                .with_child(ImageBuilder::new(WidgetBuilder::new()
-                    .with_width(35.0)
-                    .with_height(35.0))
-                    .with_opt_texture(utils::into_any_arc(resource_manager.request_texture(Path::new("data/ui/ammo_icon.png"), TextureKind::RGBA8)))
-                    .build(ctx))
-                .with_child(TextBuilder::new(WidgetBuilder::new()
-                    .with_width(170.0)
-                    .with_height(35.0))
-                    .with_font(font.clone())
-                    .with_text("Ammo:")
-                    .build(ctx)
-                )
+                .with_background(Brush::Solid(Color::opaque(249, 166, 2)))
+                .with_foreground(Brush::Solid(Color::opaque(200, 110, 0)))
                 .with_child({
-                    ammo = TextBuilder::new(WidgetBuilder::new()
-                        .with_foreground(Brush::Solid(Color::opaque(79, 79, 255)))
-                        .with_width(170.0)
-                        .with_height(35.0))
-                        .with_font(font.clone())
-                        .with_text("40")
+                    first_score = TextBuilder::new(WidgetBuilder::new()
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .with_vertical_alignment(VerticalAlignment::Center)
+                        .with_foreground(Brush::Solid(Color::BLACK)))
+                        .with_text("0")
                         .build(ctx);
-                    ammo
+                    first_score
                 }))
-                .with_orientation(Orientation::Horizontal)
+                .with_stroke_thickness(Thickness::uniform(2.0))
                 .build(ctx))
-            .with_child(StackPanelBuilder::new(WidgetBuilder::new()
-                .with_margin(Thickness::bottom(10.0))
+            .with_child(BorderBuilder::new(WidgetBuilder::new()
                 .on_column(2)
-                .with_vertical_alignment(VerticalAlignment::Bottom)
-                .with_horizontal_alignment(HorizontalAlignment::Center)
-                .with_child(ImageBuilder::new(WidgetBuilder::new()
-                    .with_width(35.0)
-                    .with_height(35.0))
-                    .with_opt_texture(utils::into_any_arc(resource_manager.request_texture(Path::new("data/ui/shield_icon.png"), TextureKind::RGBA8)))
-                    .build(ctx))
-                .with_child(TextBuilder::new(WidgetBuilder::new()
-                    .with_width(170.0)
-                    .with_height(35.0))
-                    .with_font(font.clone())
-                    .with_text("Armor:")
-                    .build(ctx))
+                .with_background(Brush::Solid(Color::opaque(127, 127, 127)))
+                .with_foreground(Brush::Solid(Color::opaque(80, 80, 80)))
                 .with_child({
-                    armor = TextBuilder::new(WidgetBuilder::new()
-                        .with_foreground(Brush::Solid(Color::opaque(255, 100, 26)))
-                        .with_width(170.0)
-                        .with_height(35.0))
-                        .with_font(font.clone())
-                        .with_text("100")
+                    second_score = TextBuilder::new(WidgetBuilder::new()
+                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                        .with_vertical_alignment(VerticalAlignment::Center)
+                        .with_foreground(Brush::Solid(Color::BLACK)))
+                        .with_text("0")
                         .build(ctx);
-                    armor
+                    second_score
                 }))
-                .with_orientation(Orientation::Horizontal)
+                .with_stroke_thickness(Thickness::uniform(2.0))
+                .build(ctx)))
+            .add_column(Column::strict(75.0 * scale))
+            .add_column(Column::strict(75.0 * scale))
+            .add_column(Column::strict(75.0 * scale))
+            .add_row(Row::strict(33.0 * scale))
+            .build(ctx);
+        let health_panel = StackPanelBuilder::new(WidgetBuilder::new()
+            .with_visibility(layout.health.visible)
+            .with_margin(element_margin)
+            .with_child(gui::create_image(ctx, resource_manager, WidgetBuilder::new()
+                .with_width(35.0 * scale)
+                .with_height(35.0 * scale), "data/ui/health_icon.png"))
+            .with_child(TextBuilder::new(WidgetBuilder::new()
+                .with_foreground(Brush::Solid(colors.text))
+                .with_width(170.0 * text_scale)
+                .with_height(35.0 * text_scale))
+                .with_text("Health:")
+                .with_font(font.clone())
                 .build(ctx))
             .with_child({
-                message = TextBuilder::new(WidgetBuilder::new()
+                health = TextBuilder::new(WidgetBuilder::new()
+                    .with_foreground(Brush::Solid(colors.health))
+                    .with_width(170.0 * text_scale)
+                    .with_height(35.0 * text_scale))
+                    .with_text("100")
+                    .with_font(font.clone())
+                    .build(ctx);
+                health
+            }))
+            .with_orientation(Orientation::Horizontal)
+            .build(ctx);
+        let ammo_panel = StackPanelBuilder::new(WidgetBuilder::new()
+            .with_visibility(layout.ammo.visible)
+            .with_margin(element_margin)
+            .with_child(gui::create_image(ctx, resource_manager, WidgetBuilder::new()
+                .with_width(35.0 * scale)
+                .with_height(35.0 * scale), "data/ui/ammo_icon.png"))
+            .with_child(TextBuilder::new(WidgetBuilder::new()
+                .with_foreground(Brush::Solid(colors.text))
+                .with_width(170.0 * text_scale)
+                .with_height(35.0 * text_scale))
+                .with_font(font.clone())
+                .with_text("Ammo:")
+                .build(ctx)
+            )
+            .with_child({
+                ammo = TextBuilder::new(WidgetBuilder::new()
+                    .with_foreground(Brush::Solid(colors.ammo))
+                    .with_width(170.0 * text_scale)
+                    .with_height(35.0 * text_scale))
+                    .with_font(font.clone())
+                    .with_text("0/0")
+                    .build(ctx);
+                ammo
+            }))
+            .with_orientation(Orientation::Horizontal)
+            .build(ctx);
+        let armor_panel = StackPanelBuilder::new(WidgetBuilder::new()
+            .with_visibility(layout.armor.visible)
+            .with_margin(element_margin)
+            .with_child(gui::create_image(ctx, resource_manager, WidgetBuilder::new()
+                .with_width(35.0 * scale)
+                .with_height(35.0 * scale), "data/ui/shield_icon.png"))
+            .with_child(TextBuilder::new(WidgetBuilder::new()
+                .with_foreground(Brush::Solid(colors.text))
+                .with_width(170.0 * text_scale)
+                .with_height(35.0 * text_scale))
+                .with_font(font.clone())
+                .with_text("Armor:")
+                .build(ctx))
+            .with_child({
+                armor = TextBuilder::new(WidgetBuilder::new()
+                    .with_foreground(Brush::Solid(colors.armor))
+                    .with_width(170.0 * text_scale)
+                    .with_height(35.0 * text_scale))
+                    .with_font(font.clone())
+                    .with_text("100")
+                    .build(ctx);
+                armor
+            }))
+            .with_orientation(Orientation::Horizontal)
+            .build(ctx);
+        message = TextBuilder::new(WidgetBuilder::new()
+            .with_foreground(Brush::Solid(colors.text))
+            .with_visibility(layout.kill_feed.visible)
+            .with_margin(element_margin)
+            .with_height(40.0 * text_scale)
+            .with_width(400.0 * text_scale))
+            .build(ctx);
+        let compass_font = gui::load_font("data/ui/SquaresBold.ttf", 16.0 * text_scale);
+        let compass_bar = BorderBuilder::new(WidgetBuilder::new()
+            .with_visibility(layout.compass.visible)
+            .with_margin(element_margin)
+            .with_width(COMPASS_WIDTH * scale)
+            .with_height(COMPASS_HEIGHT * scale)
+            .with_background(Brush::Solid(accessibility.backdrop(100)))
+            .with_foreground(Brush::Solid(colors.text))
+            .with_child({
+                let mut canvas_builder = WidgetBuilder::new()
+                    // Notch in the middle of the bar shows view direction.
+                    .with_child(BorderBuilder::new(WidgetBuilder::new()
+                        .with_width(2.0 * scale)
+                        .with_height(COMPASS_HEIGHT * scale)
+                        .with_desired_position(Vec2::new((COMPASS_WIDTH * 0.5 - 1.0) * scale, 0.0))
+                        .with_background(Brush::Solid(colors.accent)))
+                        .build(ctx));
+                for name in COMPASS_POINTS.iter() {
+                    let point = TextBuilder::new(WidgetBuilder::new()
+                        .with_foreground(Brush::Solid(colors.text)))
+                        .with_font(compass_font.clone())
+                        .with_text(name)
+                        .build(ctx);
+                    compass_points.push(point);
+                    canvas_builder = canvas_builder.with_child(point);
+                }
+                compass = CanvasBuilder::new(canvas_builder).build(ctx);
+                compass
+            }))
+            .with_stroke_thickness(Thickness::uniform(1.0))
+            .build(ctx);
+
+        // Elements are put into stack panel of their anchor, so elements that share an anchor
+        // do not overlap. Hidden elements stay in panels to keep their handles valid, they
+        // just take no space.
+        let element_widgets = HUD_ELEMENTS.iter().map(|(_, element)| {
+            let widget = match element {
+                HudElement::Timer => timer,
+                HudElement::KillFeed => message,
+                HudElement::Scores => scores,
+                HudElement::Health => health_panel,
+                HudElement::Armor => armor_panel,
+                HudElement::Ammo => ammo_panel,
+                HudElement::Compass => compass_bar,
+            };
+            (layout.element(*element).anchor, widget)
+        }).collect::<Vec<_>>();
+
+        let mut root_builder = WidgetBuilder::new()
+            .with_width(frame_size.0 as f32)
+            .with_height(frame_size.1 as f32)
+            .with_visibility(false);
+        for (_, anchor) in HUD_ANCHORS.iter() {
+            let mut panel_builder = WidgetBuilder::new()
+                .on_row(0)
+                .on_column(anchor.column())
+                .with_horizontal_alignment(anchor.horizontal_alignment())
+                .with_vertical_alignment(anchor.vertical_alignment())
+                .with_margin(Thickness::uniform(10.0 * scale));
+            for &(element_anchor, widget) in element_widgets.iter() {
+                if element_anchor == *anchor {
+                    panel_builder = panel_builder.with_child(gui::create_backdrop(ctx, &accessibility, WidgetBuilder::new(), 0, widget));
+                }
+            }
+            root_builder = root_builder.with_child(StackPanelBuilder::new(panel_builder).build(ctx));
+        }
+
+        let root = GridBuilder::new(root_builder
+            .with_child({
+                crosshair = crosshair::build(ctx, WidgetBuilder::new()
+                    .with_visibility(layout.crosshair)
                     .on_row(0)
-                    .on_column(0)
+                    .on_column(1), &crosshair_config, scale);
+                crosshair
+            })
+            .with_child({
+                died = TextBuilder::new(WidgetBuilder::new()
+                    .with_visibility(false)
+                    .on_row(0)
+                    .on_column(1)
+                    .with_foreground(Brush::Solid(Color::opaque(200, 0, 0)))
                     .with_vertical_alignment(VerticalAlignment::Center)
-                    .with_horizontal_alignment(HorizontalAlignment::Left)
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
+                    .with_font(font.clone())
+                    .with_text("You Died")
+                    .build(ctx);
+                died
+            })
+            .with_child({
+                announcement = TextBuilder::new(WidgetBuilder::new()
+                    .on_row(0)
+                    .on_column(1)
                     .with_margin(Thickness {
-                        left: 45.0,
-                        top: 30.0,
+                        left: 0.0,
+                        top: 120.0 * scale,
                         right: 0.0,
                         bottom: 0.0,
                     })
-                    .with_height(40.0)
-                    .with_width(400.0))
+                    .with_foreground(Brush::Solid(colors.accent))
+                    .with_vertical_alignment(VerticalAlignment::Top)
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
+                    .with_font(font)
+                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
                     .build(ctx);
-                message
+                announcement
             })
             .with_child({
-                died = TextBuilder::new(WidgetBuilder::new()
+                countdown = TextBuilder::new(WidgetBuilder::new()
+                    .on_row(0)
+                    .on_column(1)
+                    .with_foreground(Brush::Solid(colors.accent))
+                    .with_vertical_alignment(VerticalAlignment::Center)
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
+                    .with_font(gui::load_font("data/ui/SquaresBold.ttf", 90.0 * text_scale))
+                    .build(ctx);
+                countdown
+            })
+            .with_child({
+                control_hints = TextBuilder::new(WidgetBuilder::new()
                     .with_visibility(false)
                     .on_row(0)
                     .on_column(1)
-                    .with_foreground(Brush::Solid(Color::opaque(200, 0, 0)))
+                    .with_margin(Thickness {
+                        left: 0.0,
+                        top: 0.0,
+                        right: 0.0,
+                        bottom: 80.0 * scale,
+                    })
+                    .with_foreground(Brush::Solid(colors.info))
+                    .with_vertical_alignment(VerticalAlignment::Bottom)
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
+                    .with_font(gui::load_font("data/ui/SquaresBold.ttf", 18.0 * text_scale))
+                    .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                    .build(ctx);
+                control_hints
+            })
+            .with_child({
+                use_prompt = TextBuilder::new(WidgetBuilder::new()
+                    .with_visibility(false)
+                    .on_row(0)
+                    .on_column(1)
+                    .with_margin(Thickness {
+                        left: 0.0,
+                        top: 80.0 * scale,
+                        right: 0.0,
+                        bottom: 0.0,
+                    })
+                    .with_foreground(Brush::Solid(colors.info))
                     .with_vertical_alignment(VerticalAlignment::Center)
                     .with_horizontal_alignment(HorizontalAlignment::Center))
-                    .with_font(font)
-                    .with_text("You Died")
+                    .with_font(gui::load_font("data/ui/SquaresBold.ttf", 18.0 * text_scale))
                     .build(ctx);
-                died
+                use_prompt
+            })
+            .with_child({
+                zoom = TextBuilder::new(WidgetBuilder::new()
+                    .with_visibility(false)
+                    .on_row(0)
+                    .on_column(1)
+                    .with_margin(Thickness {
+                        left: 0.0,
+                        top: 40.0 * scale,
+                        right: 0.0,
+                        bottom: 0.0,
+                    })
+                    .with_foreground(Brush::Solid(colors.accent))
+                    .with_vertical_alignment(VerticalAlignment::Center)
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
+                    .with_font(gui::load_font("data/ui/SquaresBold.ttf", 18.0 * text_scale))
+                    .build(ctx);
+                zoom
+            })
+            .with_child({
+                note_feed = TextBuilder::new(WidgetBuilder::new()
+                    .on_row(0)
+                    .on_column(0)
+                    .with_margin(Thickness {
+                        left: 45.0 * scale,
+                        top: 0.0,
+                        right: 0.0,
+                        bottom: 160.0 * scale,
+                    })
+                    .with_foreground(Brush::Solid(colors.notes))
+                    .with_vertical_alignment(VerticalAlignment::Bottom)
+                    .with_horizontal_alignment(HorizontalAlignment::Left))
+                    .with_font(gui::load_font("data/ui/SquaresBold.ttf", 18.0 * text_scale))
+                    .build(ctx);
+                note_feed
+            })
+            .with_child({
+                range_stats = TextBuilder::new(WidgetBuilder::new()
+                    .on_row(0)
+                    .on_column(2)
+                    .with_margin(Thickness {
+                        left: 0.0,
+                        top: 120.0 * scale,
+                        right: 45.0 * scale,
+                        bottom: 0.0,
+                    })
+                    .with_foreground(Brush::Solid(colors.info))
+                    .with_vertical_alignment(VerticalAlignment::Top)
+                    .with_horizontal_alignment(HorizontalAlignment::Right))
+                    .with_font(gui::load_font("data/ui/SquaresBold.ttf", 18.0 * text_scale))
+                    .build(ctx);
+                range_stats
+            })
+            .with_child({
+                note_input = TextBuilder::new(WidgetBuilder::new()
+                    .with_foreground(Brush::Solid(colors.text))
+                    .with_visibility(false)
+                    .on_row(0)
+                    .on_column(0)
+                    .with_margin(Thickness {
+                        left: 45.0 * scale,
+                        top: 0.0,
+                        right: 0.0,
+                        bottom: 130.0 * scale,
+                    })
+                    .with_vertical_alignment(VerticalAlignment::Bottom)
+                    .with_horizontal_alignment(HorizontalAlignment::Left))
+                    .with_font(gui::load_font("data/ui/SquaresBold.ttf", 20.0 * text_scale))
+                    .build(ctx);
+                note_input
+            })
+            .with_child({
+                callout_wheel = CalloutWheel::build(ctx, WidgetBuilder::new()
+                    .on_row(0)
+                    .on_column(1), gui::load_font("data/ui/SquaresBold.ttf", 20.0 * text_scale), colors.text, colors.accent, scale);
+                callout_wheel.root()
             }))
             .add_column(Column::stretch())
             .add_column(Column::stretch())
@@ -285,37 +905,511 @@ impl Hud {
             .add_row(Row::stretch())
             .build(ctx);
 
+        let vignette = BorderBuilder::new(WidgetBuilder::new()
+            .with_width(frame_size.0 as f32)
+            .with_height(frame_size.1 as f32)
+            .with_visibility(false)
+            .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 0)))
+            .with_foreground(Brush::Solid(Color::from_rgba(0, 0, 0, 0))))
+            .with_stroke_thickness(Thickness::uniform(60.0 * scale))
+            .build(ctx);
+
+        let hit_flash = BorderBuilder::new(WidgetBuilder::new()
+            .with_width(frame_size.0 as f32)
+            .with_height(frame_size.1 as f32)
+            .with_visibility(false)
+            .with_background(Brush::Solid(Color::from_rgba(0, 0, 0, 0)))
+            .with_foreground(Brush::Solid(Color::from_rgba(0, 0, 0, 0))))
+            .build(ctx);
+
+        let flash_blind = BorderBuilder::new(WidgetBuilder::new()
+            .with_width(frame_size.0 as f32)
+            .with_height(frame_size.1 as f32)
+            .with_visibility(false)
+            .with_background(Brush::Solid(Color::from_rgba(255, 255, 255, 0)))
+            .with_foreground(Brush::Solid(Color::from_rgba(0, 0, 0, 0))))
+            .build(ctx);
+
+        let damage_indicators = (0..MAX_DAMAGE_INDICATORS)
+            .map(|_| DamageIndicator {
+                widget: BorderBuilder::new(WidgetBuilder::new()
+                    .with_visibility(false)
+                    .with_foreground(Brush::Solid(Color::opaque(255, 255, 255))))
+                    .build(ctx),
+                direction: 0.0,
+                kind: DamageKind::Bullet,
+                timeout: 0.0,
+                size: 0.0,
+            })
+            .collect::<Vec<_>>();
+
+        // Thin cold frame around the screen while player is invulnerable after spawn.
+        let spawn_protection = BorderBuilder::new(WidgetBuilder::new()
+            .with_width(frame_size.0 as f32)
+            .with_height(frame_size.1 as f32)
+            .with_visibility(false)
+            .with_background(Brush::Solid(Color::from_rgba(120, 200, 255, 25)))
+            .with_foreground(Brush::Solid(Color::from_rgba(120, 200, 255, 140))))
+            .with_stroke_thickness(Thickness::uniform(12.0 * scale))
+            .build(ctx);
+
+        let world_canvas = CanvasBuilder::new(WidgetBuilder::new()
+            .with_width(frame_size.0 as f32)
+            .with_height(frame_size.1 as f32)
+            .with_visibility(false)
+            .with_child(spawn_protection)
+            .with_child(vignette)
+            .with_child(hit_flash)
+            .with_children(&damage_indicators.iter().map(|indicator| indicator.widget).collect::<Vec<_>>())
+            .with_child(flash_blind))
+            .build(ctx);
+
         Self {
             leader_board,
             root,
+            world_canvas,
+            nameplates: Default::default(),
+            item_timers: Default::default(),
+            markers: Default::default(),
+            marker_texts: Default::default(),
+            compass,
+            compass_points,
+            compass_markers: Default::default(),
+            compass_font,
+            color_blind_mode: Default::default(),
+            theme,
+            accessibility,
+            damage_numbers: Default::default(),
+            damage_numbers_enabled: true,
+            damage_number_font: gui::load_font("data/ui/SquaresBold.ttf", 20.0 * text_scale),
+            damage_number_large_font: gui::load_font("data/ui/SquaresBold.ttf", 30.0 * text_scale),
+            visible: false,
+            screen_size: Vec2::new(frame_size.0 as f32, frame_size.1 as f32),
+            damage_feedback: Default::default(),
+            vignette,
+            vignette_timeout: 0.0,
+            hit_flash,
+            hit_flash_timeout: 0.0,
+            flash_blind,
+            flash_blind_timeout: 0.0,
+            damage_kind: DamageKind::Bullet,
+            damage_indicators,
+            spawn_protection,
+            spawn_protected: false,
+            crosshair,
+            crosshair_config,
+            crosshair_profile: None,
+            hit_confirm_timeout: 0.0,
+            scale,
+            layout,
+            mode_preset: Default::default(),
+            control_hints,
+            control_hints_timeout: 0.0,
+            control_scheme,
+            use_prompt,
+            use_action: None,
+            zoom,
+            zoom_shown: None,
+            note_input,
+            note_feed,
+            range_stats,
+            notes: Default::default(),
+            callout_wheel,
             health,
             armor,
+            health_value: SmoothValue::new(100.0),
+            armor_value: SmoothValue::new(100.0),
             ammo,
+            overheat_shown: false,
             message,
             time,
+            lives,
+            control_points,
+            scores,
             first_score,
             second_score,
             match_limit,
             died,
+            announcement,
+            announcement_text: Default::default(),
+            announcement_timeout: 0.0,
+            countdown,
+            countdown_timeout: 0.0,
             message_timeout: 0.0,
             message_queue: Default::default(),
         }
     }
 
-    pub fn set_health(&mut self, ui: &mut Gui, health: f32) {
-        ui.send_message(TextMessage::text(self.health, format!("{}", health)));
+    /// Rebuilds HUD with new scale. Pending messages and settings are kept, values of
+    /// indicators will be synced on next update.
+    pub fn set_scale(&mut self, engine: &mut GameEngine, scale: f32) {
+        self.rebuild(engine, scale, self.layout);
     }
 
-    pub fn set_armor(&mut self, ui: &mut Gui, armor: f32) {
-        ui.send_message(TextMessage::text(self.armor, format!("{}", armor)));
+    /// Rebuilds HUD with new layout, same as `set_scale` does.
+    pub fn set_layout(&mut self, engine: &mut GameEngine, layout: HudLayout) {
+        self.rebuild(engine, self.scale, layout);
     }
 
-    pub fn set_ammo(&mut self, ui: &mut Gui, ammo: u32) {
-        ui.send_message(TextMessage::text(self.ammo, format!("{}", ammo)));
+    /// Rebuilds HUD with new text scale and contrast, same as `set_scale` does.
+    pub fn set_accessibility(&mut self, engine: &mut GameEngine, accessibility: Accessibility) {
+        if self.accessibility != accessibility {
+            self.accessibility = accessibility;
+            self.rebuild(engine, self.scale, self.layout);
+        }
+    }
+
+    /// Rebuilds HUD with colors of new theme, same as `set_scale` does.
+    pub fn set_theme(&mut self, engine: &mut GameEngine, theme: HudTheme) {
+        if self.theme != theme {
+            self.theme = theme;
+            self.rebuild(engine, self.scale, self.layout);
+        }
+    }
+
+    fn rebuild(&mut self, engine: &mut GameEngine, scale: f32, layout: HudLayout) {
+        let ui = &mut engine.user_interface;
+        ui.send_message(WidgetMessage::remove(self.root));
+        ui.send_message(WidgetMessage::remove(self.world_canvas));
+
+        let mut hud = Self::build(engine, scale, layout, self.theme, self.accessibility, self.leader_board, self.crosshair_config, self.control_scheme.clone());
+        hud.message_queue = std::mem::take(&mut self.message_queue);
+        hud.notes = std::mem::take(&mut self.notes);
+        hud.damage_numbers_enabled = self.damage_numbers_enabled;
+        hud.damage_feedback = self.damage_feedback;
+        hud.set_spawn_protected(&mut engine.user_interface, self.spawn_protected);
+        hud.set_crosshair_profile(&mut engine.user_interface, self.crosshair_profile);
+        hud.set_use_prompt(&mut engine.user_interface, self.use_action.take());
+        hud.markers = std::mem::take(&mut self.markers);
+        hud.color_blind_mode = self.color_blind_mode;
+        hud.health_value = self.health_value;
+        hud.armor_value = self.armor_value;
+        hud.sync_indicators(&mut engine.user_interface);
+        hud.apply_preset(&mut engine.user_interface, self.mode_preset);
+        hud.set_visible(&mut engine.user_interface, self.visible);
+        *self = hud;
+    }
+
+    /// Replaces crosshair with a new one made by given config.
+    pub fn set_crosshair(&mut self, ui: &mut Gui, config: CrosshairConfig) {
+        self.crosshair_config = config;
+        self.rebuild_crosshair(ui);
+    }
+
+    /// Sets crosshair of active weapon, crosshair is rebuilt only when profile changes, so
+    /// this can be called every frame.
+    pub fn set_crosshair_profile(&mut self, ui: &mut Gui, profile: Option<CrosshairProfile>) {
+        if self.crosshair_profile != profile {
+            self.crosshair_profile = profile;
+            self.rebuild_crosshair(ui);
+        }
+    }
+
+    fn rebuild_crosshair(&mut self, ui: &mut Gui) {
+        let config = match self.crosshair_profile {
+            Some(profile) => profile.apply(&self.crosshair_config),
+            None => self.crosshair_config,
+        };
+        ui.send_message(WidgetMessage::remove(self.crosshair));
+        // New crosshair has normal color.
+        self.hit_confirm_timeout = 0.0;
+        self.crosshair = crosshair::build(&mut ui.build_ctx(), WidgetBuilder::new()
+            .with_visibility(self.layout.crosshair)
+            .on_row(0)
+            .on_column(1), &config, self.scale);
+        ui.send_message(WidgetMessage::link(self.crosshair, self.root));
+    }
+
+    /// Shows prompt to use focused object with given action, for example "pick up Medkit".
+    /// None hides prompt. Prompt is updated only when action changes, so this can be called
+    /// every frame.
+    pub fn set_use_prompt(&mut self, ui: &mut Gui, action: Option<String>) {
+        if self.use_action != action {
+            if let Some(action) = action.as_ref() {
+                let text = format!("Press {} to {}", self.control_scheme.borrow().use_object.button.label(), action);
+                ui.send_message(TextMessage::text(self.use_prompt, text));
+            }
+            ui.send_message(WidgetMessage::visibility(self.use_prompt, action.is_some()));
+            self.use_action = action;
+        }
+    }
+
+    /// Shows magnification of scope, None hides it. Text is updated only when magnification
+    /// changes, so this can be called every frame.
+    pub fn set_zoom(&mut self, ui: &mut Gui, zoom: Option<f32>) {
+        if self.zoom_shown != zoom {
+            if let Some(zoom) = zoom {
+                ui.send_message(TextMessage::text(self.zoom, format!("{}x", zoom)));
+            }
+            ui.send_message(WidgetMessage::visibility(self.zoom, zoom.is_some()));
+            self.zoom_shown = zoom;
+        }
+    }
+
+    /// Sets actual health, shown value reaches it smoothly on next updates.
+    pub fn set_health(&mut self, health: f32) {
+        self.health_value.set_target(health);
+    }
+
+    /// Sets actual armor, shown value reaches it smoothly on next updates.
+    pub fn set_armor(&mut self, armor: f32) {
+        self.armor_value.set_target(armor);
+    }
+
+    fn sync_indicators(&self, ui: &mut Gui) {
+        ui.send_message(TextMessage::text(self.health, format!("{}", self.health_value.value().round())));
+        ui.send_message(TextMessage::text(self.armor, format!("{}", self.armor_value.value().round())));
+    }
+
+    /// Shows rounds in magazine and ammo left outside of it, `ammo` is total amount of ammo.
+    /// Ammo of alt fire is shown after it if weapon has alt fire that uses ammo.
+    /// Shows ammo counter, `reload` is style of reload that is in progress. Magazine that is
+    /// being replaced is shown as dashes, magazine that is loaded round by round keeps its
+    /// count growing and is marked with plus.
+    pub fn set_ammo(&mut self, ui: &mut Gui, magazine: u32, ammo: u32, alt_ammo: Option<u32>, reload: Option<ReloadStyle>) {
+        let reserve = ammo.saturating_sub(magazine);
+        let mut text = match reload {
+            None => format!("{}/{}", magazine, reserve),
+            Some(ReloadStyle::Magazine) => format!("--/{}", reserve),
+            Some(ReloadStyle::PerRound) => format!("{}+/{}", magazine, reserve),
+        };
+        if let Some(alt_ammo) = alt_ammo {
+            text += &format!(" | {}", alt_ammo);
+        }
+        ui.send_message(TextMessage::text(self.ammo, text));
+        self.set_overheat_shown(ui, false);
+    }
+
+    /// Shows heat gauge in place of ammo counter for weapons with heat, `heat` is part of
+    /// max heat. Overheated weapon is shown by gauge painted with warning color.
+    pub fn set_heat(&mut self, ui: &mut Gui, heat: f32, overheated: bool) {
+        let filled = ((heat.max(0.0).min(1.0) * HEAT_GAUGE_SEGMENTS as f32).round() as usize).min(HEAT_GAUGE_SEGMENTS);
+        let text = if overheated {
+            "OVERHEAT".to_owned()
+        } else {
+            format!("[{}{}]", "|".repeat(filled), ".".repeat(HEAT_GAUGE_SEGMENTS - filled))
+        };
+        ui.send_message(TextMessage::text(self.ammo, text));
+        self.set_overheat_shown(ui, overheated);
+    }
+
+    fn set_overheat_shown(&mut self, ui: &mut Gui, shown: bool) {
+        if self.overheat_shown != shown {
+            self.overheat_shown = shown;
+            let color = if shown { OVERHEAT_COLOR } else { self.theme.colors().ammo };
+            ui.send_message(WidgetMessage::foreground(self.ammo, Brush::Solid(color)));
+        }
     }
 
     pub fn set_visible(&mut self, ui: &mut Gui, visible: bool) {
+        self.visible = visible;
         ui.send_message(WidgetMessage::visibility(self.root, visible));
+        ui.send_message(WidgetMessage::visibility(self.world_canvas, visible));
+    }
+
+    /// Shows given nameplates at screen positions of their objects.
+    pub fn update_nameplates(&mut self, ui: &mut Gui, view_projection: &Mat4, frame_size: (u32, u32), nameplates: &[Nameplate]) {
+        update_world_texts(ui, self.world_canvas, &mut self.nameplates, view_projection, frame_size, nameplates);
+    }
+
+    /// Shows time left until respawn above picked up items, `timers` contains positions of
+    /// items and time left in seconds.
+    pub fn update_item_timers(&mut self, ui: &mut Gui, view_projection: &Mat4, frame_size: (u32, u32), timers: &[(Vec3, f32)]) {
+        let texts = timers.iter()
+            .map(|(position, time_left)| Nameplate {
+                text: format!("{}", time_left.ceil() as u32),
+                color: self.theme.colors().text,
+                position: *position,
+            })
+            .collect::<Vec<_>>();
+        update_world_texts(ui, self.world_canvas, &mut self.item_timers, view_projection, frame_size, &texts);
+    }
+
+    /// Adds marker at given world position, markers are kept until `clear_markers` is called.
+    pub fn add_marker(&mut self, position: Vec3, icon: MarkerIcon) {
+        self.markers.push(Marker { position, icon });
+    }
+
+    pub fn set_color_blind_mode(&mut self, mode: ColorBlindMode) {
+        self.color_blind_mode = mode;
+    }
+
+    pub fn clear_markers(&mut self) {
+        self.markers.clear();
+    }
+
+    /// Moves marker widgets to screen positions of their markers and updates distance labels.
+    pub fn update_markers(&mut self, ui: &mut Gui, view_projection: &Mat4, frame_size: (u32, u32), observer: Vec3) {
+        let colors = self.theme.colors().with_color_blind_mode(self.color_blind_mode);
+        for (i, marker) in self.markers.iter().enumerate() {
+            if i == self.marker_texts.len() {
+                let text = TextBuilder::new(WidgetBuilder::new())
+                    .build(&mut ui.build_ctx());
+                ui.send_message(WidgetMessage::link(text, self.world_canvas));
+                self.marker_texts.push(text);
+            }
+            let text = self.marker_texts[i];
+
+            let (screen_position, arrow) = world_to_screen_edge(view_projection, marker.position, frame_size, MARKER_EDGE_MARGIN);
+            let distance = observer.distance(&marker.position);
+            let label = match arrow {
+                Some(arrow) => format!("{} {} {:.0}m", arrow, marker.icon.label(), distance),
+                None => format!("{} {:.0}m", marker.icon.label(), distance),
+            };
+            let offset = Vec2::new(label.len() as f32 * 4.0, 10.0);
+            ui.send_message(TextMessage::text(text, label));
+            ui.send_message(WidgetMessage::foreground(text, Brush::Solid(marker.icon.color(&colors, self.color_blind_mode))));
+            ui.send_message(WidgetMessage::desired_position(text, screen_position - offset));
+            ui.send_message(WidgetMessage::visibility(text, true));
+        }
+
+        for &unused in self.marker_texts[self.markers.len().min(self.marker_texts.len())..].iter() {
+            ui.send_message(WidgetMessage::visibility(unused, false));
+        }
+    }
+
+    /// Moves cardinal directions and markers along compass bar. `heading` is bearing of view
+    /// direction and `half_fov` is half of horizontal field of view, both in radians, so the
+    /// bar spans exactly what player sees. Markers outside of view are pinned to ends of the
+    /// bar. Besides markers of the HUD, compass shows given `actors` with their names.
+    pub fn update_compass(&mut self, ui: &mut Gui, observer: Vec3, heading: f32, half_fov: f32, actors: &[Nameplate]) {
+        if !self.layout.compass.visible {
+            return;
+        }
+
+        let width = COMPASS_WIDTH * self.scale;
+        let half_fov = half_fov.max(std::f32::EPSILON);
+        // Roughly half of width of a character of compass font.
+        let half_char = 4.0 * self.scale;
+
+        for (&point, (i, name)) in self.compass_points.iter().zip(COMPASS_POINTS.iter().enumerate()) {
+            let offset = wrap_angle((i as f32 * 45.0).to_radians() - heading) / half_fov;
+            if offset.abs() <= 1.0 {
+                let x = (offset + 1.0) * 0.5 * width - name.len() as f32 * half_char;
+                ui.send_message(WidgetMessage::desired_position(point, Vec2::new(x, 2.0 * self.scale)));
+            }
+            ui.send_message(WidgetMessage::visibility(point, offset.abs() <= 1.0));
+        }
+
+        let colors = self.theme.colors().with_color_blind_mode(self.color_blind_mode);
+        let marks = self.markers
+            .iter()
+            .map(|marker| (marker.position, marker.icon.label(), marker.icon.color(&colors, self.color_blind_mode)))
+            .chain(actors.iter().map(|actor| (actor.position, actor.text.as_str(), actor.color)));
+        let mut count = 0;
+        for (position, label, color) in marks {
+            let direction = position - observer;
+            if direction.x.abs() + direction.z.abs() <= std::f32::EPSILON {
+                continue;
+            }
+            let offset = wrap_angle(compass_bearing(direction) - heading) / half_fov;
+            let label = if offset > 1.0 {
+                format!("{} >", label)
+            } else if offset < -1.0 {
+                format!("< {}", label)
+            } else {
+                label.to_owned()
+            };
+
+            if count == self.compass_markers.len() {
+                let text = TextBuilder::new(WidgetBuilder::new())
+                    .with_font(self.compass_font.clone())
+                    .build(&mut ui.build_ctx());
+                ui.send_message(WidgetMessage::link(text, self.compass));
+                self.compass_markers.push(text);
+            }
+            let text = self.compass_markers[count];
+            count += 1;
+
+            let label_width = label.len() as f32 * 2.0 * half_char;
+            let x = ((offset.max(-1.0).min(1.0) + 1.0) * 0.5 * width - label_width * 0.5)
+                .max(0.0)
+                .min((width - label_width).max(0.0));
+            ui.send_message(TextMessage::text(text, label));
+            ui.send_message(WidgetMessage::foreground(text, Brush::Solid(color)));
+            ui.send_message(WidgetMessage::desired_position(text, Vec2::new(x, 22.0 * self.scale)));
+            ui.send_message(WidgetMessage::visibility(text, true));
+        }
+
+        for &unused in self.compass_markers[count..].iter() {
+            ui.send_message(WidgetMessage::visibility(unused, false));
+        }
+    }
+
+    pub fn set_damage_numbers_enabled(&mut self, ui: &mut Gui, enabled: bool) {
+        self.damage_numbers_enabled = enabled;
+        if !enabled {
+            for damage_number in self.damage_numbers.iter_mut() {
+                damage_number.lifetime = 0.0;
+                ui.send_message(WidgetMessage::visibility(damage_number.text, false));
+            }
+        }
+    }
+
+    /// Shows damage number at given point in the world. Color shifts from yellow to red and
+    /// large font is used as damage grows.
+    pub fn add_damage_number(&mut self, ui: &mut Gui, position: Vec3, amount: f32) {
+        if !self.damage_numbers_enabled {
+            return;
+        }
+
+        let large = amount >= LARGE_DAMAGE;
+        let index = match self.damage_numbers.iter().position(|n| n.lifetime <= 0.0 && n.large == large) {
+            Some(index) => index,
+            None => {
+                let font = if large {
+                    self.damage_number_large_font.clone()
+                } else {
+                    self.damage_number_font.clone()
+                };
+                let text = TextBuilder::new(WidgetBuilder::new()
+                    .with_visibility(false))
+                    .with_font(font)
+                    .build(&mut ui.build_ctx());
+                ui.send_message(WidgetMessage::link(text, self.world_canvas));
+                self.damage_numbers.push(DamageNumber {
+                    text,
+                    large,
+                    color: Color::WHITE,
+                    position: Vec3::ZERO,
+                    lifetime: 0.0,
+                });
+                self.damage_numbers.len() - 1
+            }
+        };
+
+        let k = (amount / 100.0).min(1.0).max(0.0);
+        let damage_number = &mut self.damage_numbers[index];
+        damage_number.color = Color::opaque(255, (255.0 * (1.0 - k)) as u8, 0);
+        damage_number.position = position;
+        damage_number.lifetime = DAMAGE_NUMBER_LIFETIME;
+        ui.send_message(TextMessage::text(damage_number.text, format!("{}", amount.round() as i32)));
+    }
+
+    /// Moves active damage numbers up while fading them out.
+    pub fn update_damage_numbers(&mut self, ui: &mut Gui, view_projection: &Mat4, frame_size: (u32, u32), dt: f32) {
+        for damage_number in self.damage_numbers.iter_mut() {
+            if damage_number.lifetime <= 0.0 {
+                continue;
+            }
+
+            damage_number.lifetime -= dt;
+            damage_number.position.y += DAMAGE_NUMBER_RISE_SPEED * dt;
+
+            let screen_position = world_to_screen(view_projection, damage_number.position, frame_size);
+            match screen_position {
+                Some(screen_position) if damage_number.lifetime > 0.0 => {
+                    let alpha = (255.0 * damage_number.lifetime / DAMAGE_NUMBER_LIFETIME) as u8;
+                    let color = Color::from_rgba(damage_number.color.r, damage_number.color.g, damage_number.color.b, alpha);
+                    ui.send_message(WidgetMessage::foreground(damage_number.text, Brush::Solid(color)));
+                    ui.send_message(WidgetMessage::desired_position(damage_number.text, screen_position));
+                    ui.send_message(WidgetMessage::visibility(damage_number.text, true));
+                }
+                _ => {
+                    ui.send_message(WidgetMessage::visibility(damage_number.text, false));
+                }
+            }
+        }
     }
 
     pub fn set_time(&mut self, ui: &mut Gui, time: f32) {
@@ -326,6 +1420,66 @@ impl Hud {
         ui.send_message(TextMessage::text(self.time, format!("{:02}:{:02}:{:02}", hours, minutes, seconds)));
     }
 
+    /// Shows widgets that mode of given match needs and hides widgets of other modes, see
+    /// [`HudModePreset`]. Shared elements that player has hidden in layout stay hidden.
+    pub fn apply_mode_preset(&mut self, ui: &mut Gui, options: &MatchOptions) {
+        self.apply_preset(ui, HudModePreset::for_mode(options));
+    }
+
+    fn apply_preset(&mut self, ui: &mut Gui, preset: HudModePreset) {
+        self.mode_preset = preset;
+        for &widget in MODE_WIDGETS.iter() {
+            let handle = match widget {
+                ModeWidget::Lives => self.lives,
+                ModeWidget::ControlPoints => self.control_points,
+                ModeWidget::RangeStats => self.range_stats,
+                ModeWidget::Scores => self.scores,
+            };
+            ui.send_message(WidgetMessage::visibility(handle, preset.is_widget_visible(&self.layout, widget)));
+        }
+    }
+
+    /// Shows amount of lives left, nothing is shown if lives are unlimited.
+    pub fn set_lives(&mut self, ui: &mut Gui, lives: Option<u32>) {
+        let text = lives.map_or_else(String::new, |lives| format!("Lives: {}", lives));
+        ui.send_message(TextMessage::text(self.lives, text));
+    }
+
+    /// Shows state of every control point under the timer, line per point. Point that is
+    /// being captured has gauge of capture progress.
+    pub fn set_control_points(&mut self, ui: &mut Gui, points: &[ControlPoint]) {
+        let text = points.iter()
+            .map(|point| {
+                let state = match point.state() {
+                    ControlPointState::Neutral => "-".to_owned(),
+                    ControlPointState::Owned(team) => team.name().to_owned(),
+                    ControlPointState::Capturing { team, progress } => {
+                        let filled = ((progress * CAPTURE_GAUGE_SEGMENTS as f32).round() as usize).min(CAPTURE_GAUGE_SEGMENTS);
+                        format!("{} [{}{}]", team.name(), "|".repeat(filled), ".".repeat(CAPTURE_GAUGE_SEGMENTS - filled))
+                    }
+                };
+                if point.is_contested() {
+                    format!("{}: {} contested", point.label(), state)
+                } else {
+                    format!("{}: {}", point.label(), state)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        ui.send_message(TextMessage::text(self.control_points, text));
+    }
+
+    /// Shows stats of target range practice, nothing is shown in other modes.
+    pub fn set_range_stats(&mut self, ui: &mut Gui, stats: Option<RangeStats>) {
+        let text = stats.map_or_else(String::new, |stats| {
+            let accuracy = stats.accuracy.map_or_else(|| "-".to_owned(), |accuracy| format!("{:.0}%", accuracy * 100.0));
+            let reaction_time = stats.reaction_time.map_or_else(|| "-".to_owned(), |time| format!("{:.0} ms", time * 1000.0));
+            format!("Targets: {}/{}\nShots: {}\nHits: {}\nAccuracy: {}\nReaction: {}",
+                    stats.targets_up, stats.targets, stats.shots_fired, stats.hits, accuracy, reaction_time)
+        });
+        ui.send_message(TextMessage::text(self.range_stats, text));
+    }
+
     pub fn set_is_died(&mut self, ui: &mut Gui, is_died: bool) {
         ui.send_message(WidgetMessage::visibility(self.died, is_died));
     }
@@ -338,19 +1492,177 @@ impl Hud {
     pub fn process_event(&mut self, engine: &mut GameEngine, event: &Event<()>) {
         if let Event::WindowEvent { event, .. } = event {
             if let WindowEvent::Resized(new_size) = event {
-                engine.user_interface.send_message(WidgetMessage::width(self.root, new_size.width as f32));
-                engine.user_interface.send_message(WidgetMessage::height(self.root, new_size.height as f32));
+                self.screen_size = Vec2::new(new_size.width as f32, new_size.height as f32);
+                for &widget in [self.root, self.world_canvas, self.vignette, self.hit_flash, self.flash_blind, self.spawn_protection].iter() {
+                    engine.user_interface.send_message(WidgetMessage::width(widget, new_size.width as f32));
+                    engine.user_interface.send_message(WidgetMessage::height(widget, new_size.height as f32));
+                }
             }
         }
 
-        self.leader_board.process_input_event(engine, event);
+        let show_scores = self.control_scheme.borrow().show_scores.button;
+        self.leader_board.process_input_event(engine, event, show_scores);
     }
 
     pub fn leader_board(&self) -> &LeaderBoardUI {
         &self.leader_board
     }
 
+    /// Selects which kinds of feedback are shown when player takes damage, disabled
+    /// feedback that is shown right now is hidden immediately.
+    pub fn set_damage_feedback(&mut self, ui: &mut Gui, feedback: DamageFeedback) {
+        self.damage_feedback = feedback;
+        if !feedback.vignette {
+            self.vignette_timeout = 0.0;
+            ui.send_message(WidgetMessage::visibility(self.vignette, false));
+        }
+        if !feedback.hit_flash {
+            self.hit_flash_timeout = 0.0;
+            ui.send_message(WidgetMessage::visibility(self.hit_flash, false));
+        }
+        if !feedback.direction_indicator {
+            for indicator in self.damage_indicators.iter_mut() {
+                indicator.timeout = 0.0;
+                ui.send_message(WidgetMessage::visibility(indicator.widget, false));
+            }
+        }
+    }
+
+    /// Shows or hides frame that tells player that they are invulnerable after spawn.
+    pub fn set_spawn_protected(&mut self, ui: &mut Gui, protected: bool) {
+        if self.spawn_protected != protected {
+            self.spawn_protected = protected;
+            ui.send_message(WidgetMessage::visibility(self.spawn_protection, protected));
+        }
+    }
+
+    /// Shows feedback of damage taken by player in color of its kind. `direction` is angle
+    /// to attacker clockwise from view direction in radians, None if damage has no attacker.
+    /// Hits from different directions show separate indicators, bigger hits show bigger
+    /// ones.
+    pub fn on_damage_taken(&mut self, ui: &mut Gui, amount: f32, direction: Option<f32>, kind: DamageKind) {
+        let strength = (amount / FULL_FEEDBACK_DAMAGE).max(0.3).min(1.0);
+        self.damage_kind = kind;
+        if self.damage_feedback.vignette {
+            self.vignette_timeout = self.vignette_timeout.max(VIGNETTE_DURATION * strength);
+            ui.send_message(WidgetMessage::visibility(self.vignette, true));
+        }
+        if self.damage_feedback.hit_flash {
+            self.hit_flash_timeout = HIT_FLASH_DURATION * strength;
+            ui.send_message(WidgetMessage::visibility(self.hit_flash, true));
+        }
+        if self.damage_feedback.direction_indicator {
+            if let Some(direction) = direction {
+                let size = (DAMAGE_INDICATOR_SIZE.0 + (DAMAGE_INDICATOR_SIZE.1 - DAMAGE_INDICATOR_SIZE.0) * strength) * self.scale;
+                // Free indicators have no time left, so they are taken before the oldest one.
+                let merged = self.damage_indicators
+                    .iter()
+                    .position(|indicator| indicator.timeout > 0.0 && indicator.kind == kind
+                        && angle_between(indicator.direction, direction) < DAMAGE_INDICATOR_MERGE_ANGLE);
+                let index = merged.or_else(|| self.damage_indicators
+                    .iter()
+                    .enumerate()
+                    .min_by(|(_, a), (_, b)| a.timeout.partial_cmp(&b.timeout).unwrap_or(std::cmp::Ordering::Equal))
+                    .map(|(i, _)| i));
+                if let Some(indicator) = index.and_then(|index| self.damage_indicators.get_mut(index)) {
+                    // Refreshed indicator keeps the size of the biggest hit.
+                    let size = if merged.is_some() { size.max(indicator.size) } else { size };
+                    indicator.direction = direction;
+                    indicator.kind = kind;
+                    indicator.timeout = DAMAGE_INDICATOR_DURATION;
+                    indicator.size = size;
+                    ui.send_message(WidgetMessage::width(indicator.widget, size));
+                    ui.send_message(WidgetMessage::height(indicator.widget, size));
+                    ui.send_message(WidgetMessage::background(indicator.widget, Brush::Solid(damage_color(self.color_blind_mode, kind, 1.0))));
+                    ui.send_message(WidgetMessage::visibility(indicator.widget, true));
+                }
+            }
+        }
+        self.update_damage_feedback(ui, 0.0);
+    }
+
+    /// Whites out the screen of player blinded by flashbang, `strength` is in [0; 1] range.
+    pub fn on_flashed(&mut self, ui: &mut Gui, strength: f32) {
+        self.flash_blind_timeout = self.flash_blind_timeout.max(FLASH_BLIND_DURATION * strength);
+        ui.send_message(WidgetMessage::visibility(self.flash_blind, true));
+        self.update_damage_feedback(ui, 0.0);
+    }
+
+    /// Briefly paints crosshair with hit confirmation color, called when player's shot
+    /// damaged someone.
+    pub fn on_hit(&mut self, ui: &mut Gui) {
+        if self.crosshair_config.hit_confirm {
+            self.hit_confirm_timeout = HIT_CONFIRM_DURATION;
+            crosshair::set_color(ui, self.crosshair, self.crosshair_config.hit_confirm_color);
+        }
+    }
+
+    fn update_hit_confirm(&mut self, ui: &mut Gui, dt: f32) {
+        if self.hit_confirm_timeout > 0.0 {
+            self.hit_confirm_timeout -= dt;
+            if self.hit_confirm_timeout <= 0.0 {
+                crosshair::set_color(ui, self.crosshair, self.crosshair_config.color);
+            }
+        }
+    }
+
+    fn update_damage_feedback(&mut self, ui: &mut Gui, dt: f32) {
+        if self.vignette_timeout > 0.0 {
+            self.vignette_timeout -= dt;
+            if self.vignette_timeout <= 0.0 {
+                ui.send_message(WidgetMessage::visibility(self.vignette, false));
+            } else {
+                let alpha = 0.6 * self.vignette_timeout / VIGNETTE_DURATION;
+                ui.send_message(WidgetMessage::foreground(self.vignette, Brush::Solid(damage_color(self.color_blind_mode, self.damage_kind, alpha))));
+            }
+        }
+
+        if self.hit_flash_timeout > 0.0 {
+            self.hit_flash_timeout -= dt;
+            if self.hit_flash_timeout <= 0.0 {
+                ui.send_message(WidgetMessage::visibility(self.hit_flash, false));
+            } else {
+                let alpha = 0.35 * self.hit_flash_timeout / HIT_FLASH_DURATION;
+                ui.send_message(WidgetMessage::background(self.hit_flash, Brush::Solid(damage_color(self.color_blind_mode, self.damage_kind, alpha))));
+            }
+        }
+
+        if self.flash_blind_timeout > 0.0 {
+            self.flash_blind_timeout -= dt;
+            if self.flash_blind_timeout <= 0.0 {
+                ui.send_message(WidgetMessage::visibility(self.flash_blind, false));
+            } else {
+                let alpha = (self.flash_blind_timeout / FLASH_BLIND_FADE_TIME).min(1.0);
+                ui.send_message(WidgetMessage::background(self.flash_blind, Brush::Solid(Color::from_rgba(255, 255, 255, (alpha * 255.0) as u8))));
+            }
+        }
+
+        let radius = DAMAGE_INDICATOR_RADIUS * self.scale;
+        for indicator in self.damage_indicators.iter_mut().filter(|indicator| indicator.timeout > 0.0) {
+            indicator.timeout -= dt;
+            if indicator.timeout <= 0.0 {
+                ui.send_message(WidgetMessage::visibility(indicator.widget, false));
+            } else {
+                let half_size = indicator.size * 0.5;
+                let position = Vec2::new(
+                    self.screen_size.x * 0.5 + indicator.direction.sin() * radius - half_size,
+                    self.screen_size.y * 0.5 - indicator.direction.cos() * radius - half_size,
+                );
+                ui.send_message(WidgetMessage::desired_position(indicator.widget, position));
+            }
+        }
+    }
+
     pub fn update(&mut self, ui: &mut Gui, time: &GameTime) {
+        let health_changed = self.health_value.update(time.delta);
+        let armor_changed = self.armor_value.update(time.delta);
+        if health_changed || armor_changed {
+            self.sync_indicators(ui);
+        }
+
+        self.update_damage_feedback(ui, time.delta);
+        self.update_hit_confirm(ui, time.delta);
+
         self.message_timeout -= time.delta;
 
         if self.message_timeout <= 0.0 {
@@ -361,9 +1673,109 @@ impl Hud {
                 ui.send_message(TextMessage::text(self.message, Default::default()));
             }
         }
+
+        if self.announcement_timeout > 0.0 {
+            self.announcement_timeout -= time.delta;
+            if self.announcement_timeout <= 0.0 {
+                ui.send_message(TextMessage::text(self.announcement, Default::default()));
+            }
+        }
+
+        if self.countdown_timeout > 0.0 {
+            self.countdown_timeout -= time.delta;
+            if self.countdown_timeout <= 0.0 {
+                ui.send_message(TextMessage::text(self.countdown, Default::default()));
+            }
+        }
+
+        if self.control_hints_timeout > 0.0 {
+            self.control_hints_timeout -= time.delta;
+            if self.control_hints_timeout <= 0.0 {
+                ui.send_message(WidgetMessage::visibility(self.control_hints, false));
+            }
+        }
+
+        let notes_count = self.notes.len();
+        for (_, time_left) in self.notes.iter_mut() {
+            *time_left -= time.delta;
+        }
+        self.notes.retain(|(_, time_left)| *time_left > 0.0);
+        if self.notes.len() != notes_count {
+            self.sync_note_feed(ui);
+        }
+    }
+
+    /// Shows note that player types right now, None hides input line.
+    pub fn set_note_input(&mut self, ui: &mut Gui, text: Option<&str>) {
+        match text {
+            Some(text) => {
+                ui.send_message(TextMessage::text(self.note_input, format!("Note: {}_", text)));
+                ui.send_message(WidgetMessage::visibility(self.note_input, true));
+            }
+            None => {
+                ui.send_message(WidgetMessage::visibility(self.note_input, false));
+            }
+        }
+    }
+
+    pub fn is_callout_wheel_open(&self) -> bool {
+        self.callout_wheel.is_open()
+    }
+
+    pub fn open_callout_wheel(&mut self, ui: &mut Gui) {
+        self.callout_wheel.open(ui);
+    }
+
+    pub fn move_callout_wheel_pointer(&mut self, ui: &mut Gui, delta: (f64, f64)) {
+        self.callout_wheel.move_pointer(ui, delta);
+    }
+
+    /// Hides callout wheel and returns callout that was selected on it.
+    pub fn close_callout_wheel(&mut self, ui: &mut Gui) -> Option<TeamCallout> {
+        self.callout_wheel.close(ui)
+    }
+
+    pub fn add_note(&mut self, ui: &mut Gui, text: &str) {
+        if self.notes.len() >= NOTE_FEED_SIZE {
+            self.notes.pop_front();
+        }
+        self.notes.push_back((text.to_owned(), NOTE_TIMEOUT));
+        self.sync_note_feed(ui);
+    }
+
+    fn sync_note_feed(&self, ui: &mut Gui) {
+        let text = self.notes
+            .iter()
+            .map(|(text, _)| text.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n");
+        ui.send_message(TextMessage::text(self.note_feed, text));
     }
 
-    fn update_leader_board_overview(&mut self, ui: &mut Gui, leader_board: &LeaderBoard, match_options: &MatchOptions) {
+    /// Shows hints about controls for a few seconds. Text is made from current control
+    /// scheme each time, so it always matches actual bindings.
+    pub fn show_control_hints(&mut self, ui: &mut Gui) {
+        let text = control_hints_text(&self.control_scheme.borrow());
+        ui.send_message(TextMessage::text(self.control_hints, text));
+        ui.send_message(WidgetMessage::visibility(self.control_hints, true));
+        self.control_hints_timeout = CONTROL_HINTS_TIMEOUT;
+    }
+
+    /// Shows large text at the top of the screen for a few seconds. Announcements made at
+    /// the same moment, like callouts and kill streak of one kill, are stacked instead of
+    /// replacing each other.
+    pub fn announce<P: AsRef<str>>(&mut self, ui: &mut Gui, text: P) {
+        if self.announcement_timeout >= ANNOUNCEMENT_TIMEOUT {
+            self.announcement_text.push('\n');
+        } else {
+            self.announcement_text.clear();
+        }
+        self.announcement_text.push_str(text.as_ref());
+        ui.send_message(TextMessage::text(self.announcement, self.announcement_text.clone()));
+        self.announcement_timeout = ANNOUNCEMENT_TIMEOUT;
+    }
+
+    pub fn update_leader_board_overview(&mut self, ui: &mut Gui, leader_board: &LeaderBoard, match_options: &MatchOptions) {
         // TODO: This is probably not correct way of showing leader and second place on HUD
         //  it is better to show player's score and leader/second score of some bot.
         if let Some((leader_name, leader_score)) = leader_board.highest_personal_score(None) {
@@ -378,8 +1790,12 @@ impl Hud {
             MatchOptions::DeathMatch(dm) => dm.frag_limit,
             MatchOptions::TeamDeathMatch(tdm) => tdm.team_frag_limit,
             MatchOptions::CaptureTheFlag(ctf) => ctf.flag_limit,
+            MatchOptions::LastManStanding(lms) => lms.lives,
+            MatchOptions::TargetRange(_) => 0,
+            MatchOptions::Domination(dom) => dom.score_limit,
         };
-        ui.send_message(TextMessage::text(self.match_limit, format!("{}", limit)));
+        let text = if limit > 0 { format!("{}", limit) } else { "-".to_owned() };
+        ui.send_message(TextMessage::text(self.match_limit, text));
     }
 
     pub fn handle_message(&mut self, message: &Message, ui: &mut Gui, leader_board: &LeaderBoard, match_options: &MatchOptions) {
@@ -387,7 +1803,40 @@ impl Hud {
             Message::AddNotification { text } => {
                 self.add_message(text)
             }
-            Message::AddBot { .. } | Message::RemoveActor { .. } | Message::RespawnActor { .. } | Message::SpawnBot { .. } | Message::SpawnPlayer => {
+            Message::AddNote { text } => {
+                self.add_note(ui, text);
+            }
+            Message::KillStreak { name, streak, .. } => {
+                if let Some(streak_name) = kill_streak_name(*streak) {
+                    self.announce(ui, format!("{}: {}!", name, streak_name));
+                }
+            }
+            Message::KillCallouts { name, callouts, .. } => {
+                let callouts = callouts
+                    .iter()
+                    .map(|callout| format!("{}!", callout.text()))
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                self.announce(ui, format!("{}: {}", name, callouts));
+            }
+            &Message::ControlPointCaptured { label, team } => {
+                self.announce(ui, format!("{} team captured point {}!", team.name(), label));
+                self.update_leader_board_overview(ui, leader_board, match_options)
+            }
+            &Message::MatchCountdown { seconds_left } => {
+                let (text, timeout) = if seconds_left > 0 {
+                    (seconds_left.to_string(), COUNTDOWN_TIMEOUT)
+                } else {
+                    ("Fight!".to_owned(), FIGHT_TIMEOUT)
+                };
+                ui.send_message(TextMessage::text(self.countdown, text));
+                self.countdown_timeout = timeout;
+            }
+            Message::StartNewGame { .. } | Message::SpawnPlayer => {
+                self.show_control_hints(ui);
+                self.update_leader_board_overview(ui, leader_board, match_options)
+            }
+            Message::AddBot { .. } | Message::RemoveActor { .. } | Message::RespawnActor { .. } | Message::SpawnBot { .. } => {
                 self.update_leader_board_overview(ui, leader_board, match_options)
             }
             _ => ()
@@ -0,0 +1,320 @@
+//! Layout of HUD - which elements are shown and in which corner of the screen. HUD reads
+//! layout when it builds its widgets, so any change of layout rebuilds HUD. Mode presets
+//! are layered on top of layout when a match starts, see [`HudModePreset`].
+
+use rg3d::{
+    core::visitor::{Visit, Visitor, VisitResult},
+    gui::{HorizontalAlignment, VerticalAlignment},
+};
+use crate::MatchOptions;
+
+/// Place on screen where HUD element is attached. Elements with the same anchor are
+/// stacked on top of each other.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HudAnchor {
+    TopLeft,
+    TopCenter,
+    TopRight,
+    BottomLeft,
+    BottomCenter,
+    BottomRight,
+}
+
+/// Every anchor with its name, in the order of ids.
+pub const HUD_ANCHORS: [(&str, HudAnchor); 6] = [
+    ("Top Left", HudAnchor::TopLeft),
+    ("Top Center", HudAnchor::TopCenter),
+    ("Top Right", HudAnchor::TopRight),
+    ("Bottom Left", HudAnchor::BottomLeft),
+    ("Bottom Center", HudAnchor::BottomCenter),
+    ("Bottom Right", HudAnchor::BottomRight),
+];
+
+impl HudAnchor {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match HUD_ANCHORS.get(id as usize) {
+            Some((_, anchor)) => Ok(*anchor),
+            None => Err(format!("Invalid HUD anchor {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            HudAnchor::TopLeft => 0,
+            HudAnchor::TopCenter => 1,
+            HudAnchor::TopRight => 2,
+            HudAnchor::BottomLeft => 3,
+            HudAnchor::BottomCenter => 4,
+            HudAnchor::BottomRight => 5,
+        }
+    }
+
+    /// Column of HUD root grid, grid has three columns - left, center and right.
+    pub fn column(self) -> usize {
+        match self {
+            HudAnchor::TopLeft | HudAnchor::BottomLeft => 0,
+            HudAnchor::TopCenter | HudAnchor::BottomCenter => 1,
+            HudAnchor::TopRight | HudAnchor::BottomRight => 2,
+        }
+    }
+
+    pub fn horizontal_alignment(self) -> HorizontalAlignment {
+        match self.column() {
+            0 => HorizontalAlignment::Left,
+            1 => HorizontalAlignment::Center,
+            _ => HorizontalAlignment::Right,
+        }
+    }
+
+    pub fn vertical_alignment(self) -> VerticalAlignment {
+        match self {
+            HudAnchor::TopLeft | HudAnchor::TopCenter | HudAnchor::TopRight => VerticalAlignment::Top,
+            _ => VerticalAlignment::Bottom,
+        }
+    }
+}
+
+impl Visit for HudAnchor {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Elements of HUD that can be moved and hidden. Crosshair is always in the center of
+/// the screen so it can only be hidden, see [`HudLayout::crosshair`].
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HudElement {
+    Health,
+    Armor,
+    Ammo,
+    /// Match time and lives left.
+    Timer,
+    /// Match limit with two highest scores.
+    Scores,
+    /// Queue of game messages - kills, pickups and so on.
+    KillFeed,
+    /// Bar with cardinal directions and markers relative to view direction.
+    Compass,
+}
+
+/// Every element with its name. Elements that share an anchor are stacked in this order.
+pub const HUD_ELEMENTS: [(&str, HudElement); 7] = [
+    ("Compass", HudElement::Compass),
+    ("Timer", HudElement::Timer),
+    ("Kill Feed", HudElement::KillFeed),
+    ("Scores", HudElement::Scores),
+    ("Health", HudElement::Health),
+    ("Armor", HudElement::Armor),
+    ("Ammo", HudElement::Ammo),
+];
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct HudElementLayout {
+    pub visible: bool,
+    pub anchor: HudAnchor,
+}
+
+impl HudElementLayout {
+    fn new(anchor: HudAnchor) -> Self {
+        Self {
+            visible: true,
+            anchor,
+        }
+    }
+}
+
+impl Visit for HudElementLayout {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.visible.visit("Visible", visitor)?;
+        self.anchor.visit("Anchor", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct HudLayout {
+    pub health: HudElementLayout,
+    pub armor: HudElementLayout,
+    pub ammo: HudElementLayout,
+    pub timer: HudElementLayout,
+    pub scores: HudElementLayout,
+    pub kill_feed: HudElementLayout,
+    pub compass: HudElementLayout,
+    pub crosshair: bool,
+}
+
+impl Default for HudLayout {
+    fn default() -> Self {
+        Self {
+            health: HudElementLayout::new(HudAnchor::BottomLeft),
+            armor: HudElementLayout::new(HudAnchor::BottomRight),
+            ammo: HudElementLayout::new(HudAnchor::BottomCenter),
+            timer: HudElementLayout::new(HudAnchor::TopCenter),
+            scores: HudElementLayout::new(HudAnchor::BottomLeft),
+            kill_feed: HudElementLayout::new(HudAnchor::TopLeft),
+            // Compass is optional, it is off until player turns it on.
+            compass: HudElementLayout {
+                visible: false,
+                anchor: HudAnchor::TopCenter,
+            },
+            crosshair: true,
+        }
+    }
+}
+
+impl HudLayout {
+    pub fn element(&self, element: HudElement) -> &HudElementLayout {
+        match element {
+            HudElement::Health => &self.health,
+            HudElement::Armor => &self.armor,
+            HudElement::Ammo => &self.ammo,
+            HudElement::Timer => &self.timer,
+            HudElement::Scores => &self.scores,
+            HudElement::KillFeed => &self.kill_feed,
+            HudElement::Compass => &self.compass,
+        }
+    }
+
+    pub fn element_mut(&mut self, element: HudElement) -> &mut HudElementLayout {
+        match element {
+            HudElement::Health => &mut self.health,
+            HudElement::Armor => &mut self.armor,
+            HudElement::Ammo => &mut self.ammo,
+            HudElement::Timer => &mut self.timer,
+            HudElement::Scores => &mut self.scores,
+            HudElement::KillFeed => &mut self.kill_feed,
+            HudElement::Compass => &mut self.compass,
+        }
+    }
+}
+
+/// Widgets of HUD that are shown or hidden by mode presets.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ModeWidget {
+    Lives,
+    ControlPoints,
+    RangeStats,
+    Scores,
+}
+
+pub const MODE_WIDGETS: [ModeWidget; 4] = [ModeWidget::Lives, ModeWidget::ControlPoints, ModeWidget::RangeStats, ModeWidget::Scores];
+
+/// Widgets that only some modes need, HUD applies preset of a mode when a match starts.
+/// Preset can hide shared elements that mode has no use for, but never shows elements that
+/// player has hidden in layout.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct HudModePreset {
+    /// Lives left, shown in last man standing.
+    pub lives: bool,
+    /// State of every control point, shown in domination.
+    pub control_points: bool,
+    /// Stats of practice, shown in target range.
+    pub range_stats: bool,
+    /// Scores of leaders and match limit, target range has nobody to compete with.
+    pub scores: bool,
+}
+
+impl Default for HudModePreset {
+    fn default() -> Self {
+        Self {
+            lives: false,
+            control_points: false,
+            range_stats: false,
+            scores: true,
+        }
+    }
+}
+
+impl HudModePreset {
+    pub fn for_mode(options: &MatchOptions) -> Self {
+        match options {
+            MatchOptions::LastManStanding(_) => Self { lives: true, ..Default::default() },
+            MatchOptions::Domination(_) => Self { control_points: true, ..Default::default() },
+            MatchOptions::TargetRange(_) => Self { range_stats: true, scores: false, ..Default::default() },
+            MatchOptions::DeathMatch(_) | MatchOptions::TeamDeathMatch(_) | MatchOptions::CaptureTheFlag(_) => Default::default(),
+        }
+    }
+
+    /// Whether element of layout is shown while this preset is applied.
+    pub fn is_element_visible(&self, layout: &HudLayout, element: HudElement) -> bool {
+        layout.element(element).visible && match element {
+            HudElement::Scores => self.scores,
+            _ => true,
+        }
+    }
+
+    /// Whether widget is shown while this preset is applied, shared elements that player has
+    /// hidden in layout stay hidden.
+    pub fn is_widget_visible(&self, layout: &HudLayout, widget: ModeWidget) -> bool {
+        match widget {
+            ModeWidget::Lives => self.lives,
+            ModeWidget::ControlPoints => self.control_points,
+            ModeWidget::RangeStats => self.range_stats,
+            ModeWidget::Scores => self.is_element_visible(layout, HudElement::Scores),
+        }
+    }
+}
+
+impl Visit for HudLayout {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.health.visit("Health", visitor)?;
+        self.armor.visit("Armor", visitor)?;
+        self.ammo.visit("Ammo", visitor)?;
+        self.timer.visit("Timer", visitor)?;
+        self.scores.visit("Scores", visitor)?;
+        self.kill_feed.visit("KillFeed", visitor)?;
+        self.crosshair.visit("Crosshair", visitor)?;
+        self.compass.visit("Compass", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{LastManStanding, Domination, TargetRange, DeathMatch};
+
+    fn visible_widgets(options: &MatchOptions, layout: &HudLayout) -> Vec<ModeWidget> {
+        let preset = HudModePreset::for_mode(options);
+        MODE_WIDGETS.iter()
+            .cloned()
+            .filter(|widget| preset.is_widget_visible(layout, *widget))
+            .collect()
+    }
+
+    #[test]
+    fn switching_modes_swaps_mode_widgets() {
+        let layout = HudLayout::default();
+        let last_man_standing = MatchOptions::LastManStanding(LastManStanding::default());
+        let domination = MatchOptions::Domination(Domination::default());
+        let target_range = MatchOptions::TargetRange(TargetRange::default());
+        let death_match = MatchOptions::DeathMatch(DeathMatch::default());
+
+        assert_eq!(visible_widgets(&last_man_standing, &layout), vec![ModeWidget::Lives, ModeWidget::Scores]);
+        assert_eq!(visible_widgets(&domination, &layout), vec![ModeWidget::ControlPoints, ModeWidget::Scores]);
+        assert_eq!(visible_widgets(&target_range, &layout), vec![ModeWidget::RangeStats]);
+        assert_eq!(visible_widgets(&death_match, &layout), vec![ModeWidget::Scores]);
+    }
+
+    #[test]
+    fn preset_keeps_elements_hidden_by_player() {
+        let mut layout = HudLayout::default();
+        layout.element_mut(HudElement::Scores).visible = false;
+        let domination = MatchOptions::Domination(Domination::default());
+        assert_eq!(visible_widgets(&domination, &layout), vec![ModeWidget::ControlPoints]);
+    }
+}
@@ -0,0 +1,215 @@
+//! Color theme of HUD. Every color of HUD text and markers is taken from the colors of
+//! current theme, HUD reads them when it builds its widgets, so any change of theme rebuilds
+//! HUD. Markers keep colors of colorblind palette while a colorblind mode is on, because
+//! they must stay easy to tell apart.
+
+use crate::palette::{self, ColorBlindMode};
+use rg3d::core::{
+    color::Color,
+    visitor::{Visit, Visitor, VisitResult},
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum HudThemePreset {
+    Classic,
+    Amber,
+    Ice,
+    Mono,
+    /// Colors are made from accent color picked by user.
+    Custom,
+}
+
+/// Every preset with its name, in the order of ids.
+pub const HUD_THEME_PRESETS: [(&str, HudThemePreset); 5] = [
+    ("Classic", HudThemePreset::Classic),
+    ("Amber", HudThemePreset::Amber),
+    ("Ice", HudThemePreset::Ice),
+    ("Mono", HudThemePreset::Mono),
+    ("Custom", HudThemePreset::Custom),
+];
+
+impl Default for HudThemePreset {
+    fn default() -> Self {
+        HudThemePreset::Classic
+    }
+}
+
+impl HudThemePreset {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match HUD_THEME_PRESETS.get(id as usize) {
+            Some((_, preset)) => Ok(*preset),
+            None => Err(format!("Invalid HUD theme {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            HudThemePreset::Classic => 0,
+            HudThemePreset::Amber => 1,
+            HudThemePreset::Ice => 2,
+            HudThemePreset::Mono => 3,
+            HudThemePreset::Custom => 4,
+        }
+    }
+}
+
+impl Visit for HudThemePreset {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Colors of HUD elements.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct HudColors {
+    /// Labels and values of indicators that have no color of their own.
+    pub text: Color,
+    /// Announcements and countdown.
+    pub accent: Color,
+    /// Hints, prompts and practice stats.
+    pub info: Color,
+    pub notes: Color,
+    pub health: Color,
+    pub armor: Color,
+    pub ammo: Color,
+    pub enemy_marker: Color,
+    pub health_marker: Color,
+    /// Crosshair color that is suggested when theme is picked, crosshair config keeps the
+    /// color that user has actually chosen.
+    pub crosshair: Color,
+}
+
+static CLASSIC_COLORS: HudColors = HudColors {
+    text: Color::opaque(255, 255, 255),
+    accent: Color::opaque(255, 200, 0),
+    info: Color::opaque(220, 220, 220),
+    notes: Color::opaque(255, 230, 140),
+    health: Color::opaque(180, 14, 22),
+    armor: Color::opaque(255, 100, 26),
+    ammo: Color::opaque(79, 79, 255),
+    enemy_marker: Color::opaque(255, 60, 60),
+    health_marker: Color::opaque(60, 255, 60),
+    crosshair: Color::opaque(255, 255, 255),
+};
+
+static AMBER_COLORS: HudColors = HudColors {
+    text: Color::opaque(255, 214, 150),
+    accent: Color::opaque(255, 170, 40),
+    info: Color::opaque(240, 200, 140),
+    notes: Color::opaque(255, 225, 160),
+    health: Color::opaque(255, 120, 40),
+    armor: Color::opaque(255, 190, 70),
+    ammo: Color::opaque(255, 160, 20),
+    enemy_marker: Color::opaque(255, 80, 30),
+    health_marker: Color::opaque(255, 230, 120),
+    crosshair: Color::opaque(255, 190, 70),
+};
+
+static ICE_COLORS: HudColors = HudColors {
+    text: Color::opaque(210, 240, 255),
+    accent: Color::opaque(120, 210, 255),
+    info: Color::opaque(190, 220, 240),
+    notes: Color::opaque(170, 230, 255),
+    health: Color::opaque(90, 200, 255),
+    armor: Color::opaque(160, 180, 255),
+    ammo: Color::opaque(120, 255, 230),
+    enemy_marker: Color::opaque(255, 90, 120),
+    health_marker: Color::opaque(120, 255, 230),
+    crosshair: Color::opaque(150, 230, 255),
+};
+
+static MONO_COLORS: HudColors = HudColors {
+    text: Color::opaque(235, 235, 235),
+    accent: Color::opaque(255, 255, 255),
+    info: Color::opaque(200, 200, 200),
+    notes: Color::opaque(220, 220, 220),
+    health: Color::opaque(255, 255, 255),
+    armor: Color::opaque(200, 200, 200),
+    ammo: Color::opaque(170, 170, 170),
+    enemy_marker: Color::opaque(255, 255, 255),
+    health_marker: Color::opaque(170, 170, 170),
+    crosshair: Color::opaque(255, 255, 255),
+};
+
+/// Mixes color with white, `k` is the part of white.
+fn lighten(color: Color, k: f32) -> Color {
+    let mix = |c: u8| (c as f32 + (255.0 - c as f32) * k) as u8;
+    Color::opaque(mix(color.r), mix(color.g), mix(color.b))
+}
+
+/// Mixes color with black, `k` is the part of black.
+fn darken(color: Color, k: f32) -> Color {
+    let mix = |c: u8| (c as f32 * (1.0 - k)) as u8;
+    Color::opaque(mix(color.r), mix(color.g), mix(color.b))
+}
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct HudTheme {
+    pub preset: HudThemePreset,
+    /// Accent color of custom theme, it is kept while other preset is picked.
+    pub custom: Color,
+}
+
+impl Default for HudTheme {
+    fn default() -> Self {
+        Self {
+            preset: Default::default(),
+            custom: Color::opaque(0, 200, 120),
+        }
+    }
+}
+
+impl HudTheme {
+    /// Returns colors of the theme. Custom theme has every color made from its accent, only
+    /// markers keep classic colors so enemies and health are still told apart.
+    pub fn colors(&self) -> HudColors {
+        match self.preset {
+            HudThemePreset::Classic => CLASSIC_COLORS,
+            HudThemePreset::Amber => AMBER_COLORS,
+            HudThemePreset::Ice => ICE_COLORS,
+            HudThemePreset::Mono => MONO_COLORS,
+            HudThemePreset::Custom => HudColors {
+                text: lighten(self.custom, 0.6),
+                accent: self.custom,
+                info: lighten(self.custom, 0.45),
+                notes: lighten(self.custom, 0.3),
+                health: self.custom,
+                armor: lighten(self.custom, 0.3),
+                ammo: darken(self.custom, 0.2),
+                crosshair: self.custom,
+                ..CLASSIC_COLORS
+            },
+        }
+    }
+}
+
+impl HudColors {
+    /// Replaces marker colors with colors of colorblind palette while colorblind mode is on.
+    pub fn with_color_blind_mode(mut self, mode: ColorBlindMode) -> Self {
+        if mode != ColorBlindMode::None {
+            let palette = palette::palette(mode);
+            self.enemy_marker = palette.enemy_marker;
+            self.health_marker = palette.health_marker;
+        }
+        self
+    }
+}
+
+impl Visit for HudTheme {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.preset.visit("Preset", visitor)?;
+        self.custom.visit("Custom", visitor)?;
+
+        visitor.leave_region()
+    }
+}
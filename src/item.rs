@@ -8,26 +8,38 @@ use rg3d::{
         },
         visitor::{Visit, Visitor, VisitResult},
         math::vec3::Vec3,
+        color::Color,
     },
     engine::resource_manager::ResourceManager,
+    resource::texture::TextureKind,
     scene::{
         base::BaseBuilder,
         Scene,
         node::Node,
         transform::TransformBuilder,
         graph::Graph,
+        sprite::SpriteBuilder,
     },
 };
-use std::{
-    path::Path,
-    sync::mpsc::Sender,
-};
+use std::sync::mpsc::Sender;
 use crate::{
     GameTime,
+    instantiate_model,
     message::Message,
     effects::EffectKind,
+    usable::Usable,
+    definitions,
+    settings::parse_f32,
 };
 
+/// Intensity of glow around items that player needs, zero disables glow.
+pub const DEFAULT_ITEM_GLOW: f32 = 0.6;
+/// Part of glow intensity that is left for items that player has no use for right now.
+pub const REDUNDANT_ITEM_GLOW: f32 = 0.3;
+const GLOW_TEXTURE: &str = "data/particles/light_01.png";
+/// Size of glow sprite in meters, it must be larger than models of items.
+const GLOW_SIZE: f32 = 0.6;
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum ItemKind {
     Medkit,
@@ -44,6 +56,18 @@ pub enum ItemKind {
     RocketLauncher
 }
 
+/// Every kind of item in order of ids.
+pub const ITEM_KINDS: [ItemKind; 8] = [
+    ItemKind::Medkit,
+    ItemKind::Plasma,
+    ItemKind::Ak47Ammo,
+    ItemKind::M4Ammo,
+    ItemKind::PlasmaGun,
+    ItemKind::Ak47,
+    ItemKind::M4,
+    ItemKind::RocketLauncher,
+];
+
 impl ItemKind {
     fn from_id(id: u32) -> Result<ItemKind, String> {
         match id {
@@ -71,12 +95,37 @@ impl ItemKind {
             ItemKind::RocketLauncher => 7,
         }
     }
+
+    /// Human-readable name of item, used in HUD prompts.
+    pub fn name(self) -> &'static str {
+        match self {
+            ItemKind::Medkit => "Medkit",
+            ItemKind::Plasma => "Plasma",
+            ItemKind::Ak47Ammo => "AK47 Ammo",
+            ItemKind::M4Ammo => "M4 Ammo",
+            ItemKind::PlasmaGun => "Plasma Gun",
+            ItemKind::Ak47 => "AK47",
+            ItemKind::M4 => "M4",
+            ItemKind::RocketLauncher => "Rocket Launcher",
+        }
+    }
+
+    /// Color of glow around item, health is green, ammo is yellow and weapons are white.
+    fn glow_color(self, alpha: u8) -> Color {
+        match self {
+            ItemKind::Medkit => Color::from_rgba(60, 255, 60, alpha),
+            ItemKind::Plasma | ItemKind::Ak47Ammo | ItemKind::M4Ammo => Color::from_rgba(255, 200, 40, alpha),
+            ItemKind::PlasmaGun | ItemKind::Ak47 | ItemKind::M4 | ItemKind::RocketLauncher => Color::from_rgba(220, 230, 255, alpha),
+        }
+    }
 }
 
 pub struct Item {
     kind: ItemKind,
     pivot: Handle<Node>,
     model: Handle<Node>,
+    /// Sprite around model that makes item easier to spot.
+    glow: Handle<Node>,
     offset: Vec3,
     dest_offset: Vec3,
     offset_factor: f32,
@@ -85,6 +134,8 @@ pub struct Item {
     definition: &'static ItemDefinition,
     sender: Option<Sender<Message>>,
     lifetime: Option<f32>,
+    /// Ammo of weapon dropped by dead actor, None gives amount from definition.
+    ammo: Option<u32>,
 }
 
 impl Default for Item {
@@ -93,6 +144,7 @@ impl Default for Item {
             kind: ItemKind::Medkit,
             pivot: Default::default(),
             model: Default::default(),
+            glow: Default::default(),
             offset: Default::default(),
             dest_offset: Default::default(),
             offset_factor: 0.0,
@@ -101,24 +153,47 @@ impl Default for Item {
             definition: Self::get_definition(ItemKind::Medkit),
             sender: None,
             lifetime: None,
+            ammo: None,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct ItemDefinition {
-    model: &'static str,
+    pub model: &'static str,
     scale: f32,
     reactivation_interval: f32,
+    /// Health restored by medkit, ammo given by ammo boxes and weapons.
+    pub amount: f32,
+}
+
+impl ItemDefinition {
+    /// Changes value of a field by its name in definitions file.
+    pub fn set_value(&mut self, field: &str, value: &str) -> Result<(), String> {
+        match field {
+            "scale" => self.scale = parse_f32(value, 0.05, 5.0)?,
+            "reactivation_interval" => self.reactivation_interval = parse_f32(value, 0.0, 600.0)?,
+            "amount" => self.amount = parse_f32(value, 0.0, 10000.0)?,
+            _ => return Err(format!("unknown item field {}", field)),
+        }
+        Ok(())
+    }
 }
 
 impl Item {
+    /// Returns definition of item, definitions file may override built-in one.
     pub fn get_definition(kind: ItemKind) -> &'static ItemDefinition {
+        definitions::item(kind).unwrap_or_else(|| Self::builtin_definition(kind))
+    }
+
+    pub fn builtin_definition(kind: ItemKind) -> &'static ItemDefinition {
         match kind {
             ItemKind::Medkit => {
                 static DEFINITION: ItemDefinition = ItemDefinition {
                     model: "data/models/medkit.fbx",
                     scale: 1.0,
                     reactivation_interval: 20.0,
+                    amount: 20.0,
                 };
                 &DEFINITION
             }
@@ -127,6 +202,7 @@ impl Item {
                     model: "data/models/yellow_box.FBX",
                     scale: 0.25,
                     reactivation_interval: 15.0,
+                    amount: 200.0,
                 };
                 &DEFINITION
             }
@@ -135,6 +211,7 @@ impl Item {
                     model: "data/models/box_medium.FBX",
                     scale: 0.30,
                     reactivation_interval: 14.0,
+                    amount: 200.0,
                 };
                 &DEFINITION
             }
@@ -143,6 +220,7 @@ impl Item {
                     model: "data/models/box_small.FBX",
                     scale: 0.30,
                     reactivation_interval: 13.0,
+                    amount: 200.0,
                 };
                 &DEFINITION
             }
@@ -151,6 +229,7 @@ impl Item {
                     model: "data/models/plasma_rifle.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
+                    amount: 200.0,
                 };
                 &DEFINITION
             }
@@ -159,6 +238,7 @@ impl Item {
                     model: "data/models/ak47.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
+                    amount: 200.0,
                 };
                 &DEFINITION
             }
@@ -167,6 +247,7 @@ impl Item {
                     model: "data/models/m4.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
+                    amount: 200.0,
                 };
                 &DEFINITION
             }
@@ -175,6 +256,7 @@ impl Item {
                     model: "data/models/Rpg7.FBX",
                     scale: 3.0,
                     reactivation_interval: 30.0,
+                    amount: 200.0,
                 };
                 &DEFINITION
             }
@@ -190,11 +272,7 @@ impl Item {
     ) -> Self {
         let definition = Self::get_definition(kind);
 
-        let model = resource_manager.request_model(Path::new(definition.model))
-            .unwrap()
-            .lock()
-            .unwrap()
-            .instantiate_geometry(scene);
+        let model = instantiate_model(resource_manager, definition.model, scene);
 
         let pivot = scene.graph.add_node(Node::Base(BaseBuilder::new()
             .with_local_transform(TransformBuilder::new()
@@ -205,10 +283,23 @@ impl Item {
 
         scene.graph.link_nodes(model, pivot);
 
+        // Glow is a child of model, so it bobs with model and is hidden with it. It is fully
+        // transparent until level sets its intensity.
+        let glow = scene.graph.add_node(Node::Sprite(SpriteBuilder::new(BaseBuilder::new()
+            .with_local_transform(TransformBuilder::new()
+                .with_local_position(Vec3::new(0.0, 0.25 / definition.scale, 0.0))
+                .build()))
+            .with_size(GLOW_SIZE)
+            .with_color(kind.glow_color(0))
+            .with_opt_texture(resource_manager.request_texture(GLOW_TEXTURE, TextureKind::R8))
+            .build()));
+        scene.graph.link_nodes(glow, model);
+
         Self {
             pivot,
             kind,
             model,
+            glow,
             sender: Some(sender),
             ..Default::default()
         }
@@ -236,6 +327,10 @@ impl Item {
             .local_transform_mut()
             .set_position(self.offset);
 
+        if let Some(lifetime) = self.lifetime.as_mut() {
+            *lifetime -= time.delta;
+        }
+
         if !self.active {
             self.reactivation_timer -= time.delta;
             if self.reactivation_timer <= 0.0 {
@@ -253,6 +348,14 @@ impl Item {
         self.kind
     }
 
+    /// Sets opacity of glow around item, zero hides glow.
+    pub fn set_glow(&self, graph: &mut Graph, intensity: f32) {
+        if let Node::Sprite(sprite) = &mut graph[self.glow] {
+            let alpha = (intensity.max(0.0).min(1.0) * 255.0) as u8;
+            sprite.set_color(self.kind.glow_color(alpha));
+        }
+    }
+
     pub fn pick_up(&mut self) {
         self.reactivation_timer = self.definition.reactivation_interval;
         self.active = false;
@@ -262,6 +365,16 @@ impl Item {
         !self.active
     }
 
+    /// Returns time left until picked up item appears again. Temporary items (like dropped
+    /// weapons) are removed when picked up, so they never respawn.
+    pub fn respawn_time_left(&self) -> Option<f32> {
+        if !self.active && self.lifetime.is_none() {
+            Some(self.reactivation_timer.max(0.0))
+        } else {
+            None
+        }
+    }
+
     fn cleanup(&self, graph: &mut Graph) {
         graph.remove_node(self.pivot)
     }
@@ -276,6 +389,30 @@ impl Item {
     pub fn set_lifetime(&mut self, lifetime: Option<f32>) {
         self.lifetime = lifetime;
     }
+
+    pub fn set_ammo(&mut self, ammo: Option<u32>) {
+        self.ammo = ammo;
+    }
+
+    /// Returns ammo that item gives, it is less than usual for weapons dropped by dead actors.
+    pub fn ammo(&self) -> Option<u32> {
+        self.ammo
+    }
+}
+
+impl Usable for Item {
+    fn use_position(&self, graph: &Graph) -> Vec3 {
+        // Pivot is on the floor, line of sight is checked to the middle of model instead.
+        self.position(graph) + Vec3::new(0.0, 0.25, 0.0)
+    }
+
+    fn can_be_used(&self) -> bool {
+        !self.is_picked_up()
+    }
+
+    fn use_action(&self) -> String {
+        format!("pick up {}", self.kind.name())
+    }
 }
 
 impl Visit for Item {
@@ -291,12 +428,14 @@ impl Visit for Item {
         self.definition = Self::get_definition(self.kind);
         self.model.visit("Model", visitor)?;
         self.pivot.visit("Pivot", visitor)?;
+        self.glow.visit("Glow", visitor)?;
         self.offset.visit("Offset", visitor)?;
         self.offset_factor.visit("OffsetFactor", visitor)?;
         self.dest_offset.visit("DestOffset", visitor)?;
         self.reactivation_timer.visit("ReactivationTimer", visitor)?;
         self.active.visit("Active", visitor)?;
         self.lifetime.visit("Lifetime", visitor)?;
+        self.ammo.visit("Ammo", visitor)?;
 
         visitor.leave_region()
     }
@@ -333,6 +472,10 @@ impl ItemContainer {
         self.pool.spawn(item)
     }
 
+    pub fn get(&self, item: Handle<Item>) -> &Item {
+        self.pool.borrow(item)
+    }
+
     pub fn get_mut(&mut self, item: Handle<Item>) -> &mut Item {
         self.pool.borrow_mut(item)
     }
@@ -0,0 +1,174 @@
+//! Test harness for lag compensation. There is no networking yet, but hit registration can
+//! be validated against delayed input already: level keeps short history of actor positions
+//! and shots of player can be delayed artificially. Delayed shot is checked against positions
+//! that actors had when the shot was fired, as a server would do for a lagging client.
+//! Killcam replays the same history, so it is recorded while killcam is on as well.
+
+use std::collections::VecDeque;
+use rg3d::core::{
+    math::vec3::Vec3,
+    pool::Handle,
+};
+use crate::{
+    actor::Actor,
+    weapon::Weapon,
+};
+
+/// How long positions are kept in history, delays longer than this can't be compensated and
+/// killcam can't replay more than this.
+pub const HISTORY_DURATION: f32 = 2.0;
+/// Radius of sphere that approximates body of actor in rewound hit tests.
+const ACTOR_HIT_RADIUS: f32 = 0.6;
+/// Center of body sphere is this high above actor position.
+const ACTOR_HIT_HEIGHT: f32 = 0.8;
+
+struct Snapshot {
+    time: f32,
+    positions: Vec<(Handle<Actor>, Vec3)>,
+}
+
+/// Ring buffer of actor positions over last [`HISTORY_DURATION`] seconds.
+#[derive(Default)]
+pub struct TransformHistory {
+    snapshots: VecDeque<Snapshot>,
+}
+
+impl TransformHistory {
+    /// Adds positions of actors at given time, snapshots older than history duration are
+    /// dropped.
+    pub fn record(&mut self, time: f32, positions: Vec<(Handle<Actor>, Vec3)>) {
+        self.snapshots.push_back(Snapshot { time, positions });
+        while self.snapshots
+            .front()
+            .map_or(false, |snapshot| snapshot.time < time - HISTORY_DURATION) {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.snapshots.clear();
+    }
+
+    /// Returns recorded positions of actor since given time, oldest first.
+    pub fn path_of(&self, actor: Handle<Actor>, since: f32) -> Vec<(f32, Vec3)> {
+        self.snapshots
+            .iter()
+            .filter(|snapshot| snapshot.time >= since)
+            .filter_map(|snapshot| snapshot.positions
+                .iter()
+                .find(|(a, _)| *a == actor)
+                .map(|&(_, position)| (snapshot.time, position)))
+            .collect()
+    }
+
+    /// Returns positions of actors at given time, positions between two snapshots are
+    /// interpolated. Actors that did not exist in both snapshots are taken from the nearest
+    /// one. None if time is out of recorded history.
+    pub fn rewind_to(&self, time: f32) -> Option<Vec<(Handle<Actor>, Vec3)>> {
+        let next = self.snapshots.iter().position(|snapshot| snapshot.time >= time)?;
+        let to = &self.snapshots[next];
+        if next == 0 {
+            // Exact match with oldest snapshot is the only case when there is nothing before.
+            return if to.time == time { Some(to.positions.clone()) } else { None };
+        }
+        let from = &self.snapshots[next - 1];
+        // Snapshots that share their time have nothing to blend, newer one is taken then.
+        let span = to.time - from.time;
+        let t = if span > 0.0 { (time - from.time) / span } else { 1.0 };
+        Some(to.positions
+            .iter()
+            .map(|&(actor, to_position)| {
+                let position = from.positions
+                    .iter()
+                    .find(|(from_actor, _)| *from_actor == actor)
+                    .map_or(to_position, |&(_, from_position)| from_position + (to_position - from_position).scale(t));
+                (actor, position)
+            })
+            .collect())
+    }
+}
+
+/// Returns nearest actor hit by ray from `origin` along `direction` if actors were at given
+/// positions. `ignore` is the shooter, shooter can't hit itself.
+pub fn ray_hit(positions: &[(Handle<Actor>, Vec3)], origin: Vec3, direction: Vec3, ignore: Handle<Actor>) -> Option<Handle<Actor>> {
+    let direction = direction.normalized()?;
+    let mut nearest = None;
+    let mut nearest_distance = std::f32::MAX;
+    for &(actor, position) in positions {
+        if actor == ignore {
+            continue;
+        }
+        let center = position + Vec3::new(0.0, ACTOR_HIT_HEIGHT, 0.0);
+        let to_center = center - origin;
+        let projection = to_center.dot(&direction);
+        if projection < 0.0 {
+            continue;
+        }
+        let closest = origin + direction.scale(projection);
+        if closest.distance(&center) <= ACTOR_HIT_RADIUS && projection < nearest_distance {
+            nearest_distance = projection;
+            nearest = Some(actor);
+        }
+    }
+    nearest
+}
+
+/// Shot of player that waits for artificial input delay to pass.
+pub struct DelayedShot {
+    pub weapon: Handle<Weapon>,
+    pub initial_velocity: Vec3,
+    pub direction: Option<Vec3>,
+    /// Level time at which player pressed the trigger.
+    pub fire_time: f32,
+    /// Shot origin and direction at fire time, used to check hit against rewound positions.
+    pub origin: Vec3,
+    pub aim: Vec3,
+    pub time_left: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewound_hit_registers_against_historical_position() {
+        let shooter = Handle::new(1, 1);
+        let target = Handle::new(2, 1);
+        let mut history = TransformHistory::default();
+        history.record(1.0, vec![(shooter, Vec3::ZERO), (target, Vec3::new(0.0, 0.0, 10.0))]);
+        history.record(2.0, vec![(shooter, Vec3::ZERO), (target, Vec3::new(4.0, 0.0, 10.0))]);
+
+        // Target was at x = 1 a quarter into the history.
+        let positions = history.rewind_to(1.25).unwrap();
+        let origin = Vec3::new(0.0, ACTOR_HIT_HEIGHT, 0.0);
+        let at_old_position = Vec3::new(1.0, 0.0, 10.0);
+        let at_new_position = Vec3::new(4.0, 0.0, 10.0);
+        assert_eq!(ray_hit(&positions, origin, at_old_position, shooter), Some(target));
+        assert_eq!(ray_hit(&positions, origin, at_new_position, shooter), None);
+
+        // Without rewind the same shot misses.
+        let current = history.rewind_to(2.0).unwrap();
+        assert_eq!(ray_hit(&current, origin, at_old_position, shooter), None);
+        assert_eq!(ray_hit(&current, origin, at_new_position, shooter), Some(target));
+    }
+
+    #[test]
+    fn rewind_outside_of_history_fails() {
+        let mut history = TransformHistory::default();
+        history.record(1.0, vec![(Handle::new(1, 1), Vec3::ZERO)]);
+        assert!(history.rewind_to(0.5).is_none());
+        assert!(history.rewind_to(1.5).is_none());
+        assert!(history.rewind_to(1.0).is_some());
+    }
+
+    #[test]
+    fn snapshots_with_same_time_do_not_produce_nan() {
+        let actor = Handle::new(1, 1);
+        let mut history = TransformHistory::default();
+        history.record(0.5, vec![(actor, Vec3::ZERO)]);
+        history.record(1.0, vec![(actor, Vec3::ZERO)]);
+        history.record(1.0, vec![(actor, Vec3::new(2.0, 0.0, 0.0))]);
+        let positions = history.rewind_to(1.0).unwrap();
+        assert!(positions[0].1.x.is_finite());
+    }
+}
@@ -1,14 +1,20 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::Path,
+};
 use crate::{
     MatchOptions,
     UINodeHandle,
     GameEngine,
     Gui,
     character::Team,
+    control_scheme::ControlButton,
     message::Message,
+    weapon::WeaponKind,
+    player::PLAYER_NAME,
 };
 use rg3d::{
-    event::{WindowEvent, ElementState, VirtualKeyCode, Event},
+    event::{WindowEvent, ElementState, MouseButton, Event},
     gui::{
         grid::{GridBuilder, Row, Column},
         widget::WidgetBuilder,
@@ -25,10 +31,27 @@ use rg3d::{
     },
 };
 
-#[derive(Copy, Clone)]
+#[derive(Clone)]
 pub struct PersonalScore {
     pub kills: u32,
     pub deaths: u32,
+    /// Amount of kills made since last death.
+    pub kill_streak: u32,
+    /// Amount of kills made in quick succession, see [`COMBO_WINDOW`].
+    pub combo: u32,
+    /// Match time of last kill.
+    pub last_kill_time: f32,
+    /// Name of actor who killed this one last time, empty if there is no one to take
+    /// revenge on.
+    pub last_killer: String,
+    pub shots_fired: u32,
+    /// Amount of times when shots of the actor damaged someone else, explosion that
+    /// damaged few actors counts as few hits.
+    pub hits: u32,
+    /// Total damage dealt to others, self-damage is not counted.
+    pub damage_dealt: f32,
+    /// Kills made with each weapon, indexed by weapon kind id.
+    pub weapon_kills: Vec<u32>,
 }
 
 impl Default for PersonalScore {
@@ -36,8 +59,102 @@ impl Default for PersonalScore {
         Self {
             kills: 0,
             deaths: 0,
+            kill_streak: 0,
+            combo: 0,
+            last_kill_time: 0.0,
+            last_killer: String::new(),
+            shots_fired: 0,
+            hits: 0,
+            damage_dealt: 0.0,
+            weapon_kills: Default::default(),
+        }
+    }
+}
+
+impl PersonalScore {
+    /// Part of shots that hit someone, None if actor did not shoot at all.
+    pub fn accuracy(&self) -> Option<f32> {
+        if self.shots_fired > 0 {
+            Some((self.hits as f32 / self.shots_fired as f32).min(1.0))
+        } else {
+            None
+        }
+    }
+
+    /// Weapon with most kills, None if actor has no kills made with a weapon.
+    pub fn favorite_weapon(&self) -> Option<WeaponKind> {
+        weapon_with_most_kills(&self.weapon_kills)
+    }
+
+    pub fn weapon_kills(&self, weapon: WeaponKind) -> u32 {
+        self.weapon_kills
+            .get(weapon.id() as usize)
+            .cloned()
+            .unwrap_or(0)
+    }
+}
+
+/// Returns weapon with most kills, kills are indexed by weapon kind id. None if there are
+/// no kills at all.
+pub fn weapon_with_most_kills(weapon_kills: &[u32]) -> Option<WeaponKind> {
+    weapon_kills
+        .iter()
+        .enumerate()
+        .filter(|(_, kills)| **kills > 0)
+        .max_by_key(|(_, kills)| **kills)
+        .and_then(|(id, _)| WeaponKind::new(id as u32).ok())
+}
+
+/// Amount of kills in a row after which actor receives reward.
+pub const KILL_STREAK_REWARD_THRESHOLD: u32 = 5;
+
+/// Returns name of a kill streak that should be announced, or None if streak is not
+/// long enough to be announced.
+pub fn kill_streak_name(streak: u32) -> Option<&'static str> {
+    match streak {
+        0 | 1 => None,
+        2 => Some("Double Kill"),
+        3 | 4 => Some("Multi Kill"),
+        _ => Some("Rampage"),
+    }
+}
+
+/// Max time between kills to continue a combo, in seconds.
+pub const COMBO_WINDOW: f32 = 3.0;
+
+/// Callouts for notable kills, they're announced together with kill streaks.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum KillCallout {
+    /// First kill of the match.
+    FirstBlood,
+    Headshot,
+    /// Killer killed the actor who killed it last time.
+    Revenge,
+    /// Few kills each made within combo window of previous one.
+    Combo(u32),
+}
+
+impl KillCallout {
+    pub fn text(self) -> String {
+        match self {
+            KillCallout::FirstBlood => "First Blood".to_owned(),
+            KillCallout::Headshot => "Headshot".to_owned(),
+            KillCallout::Revenge => "Revenge".to_owned(),
+            KillCallout::Combo(kills) => format!("Combo x{}", kills),
         }
     }
+
+    /// Announcer clips are optional assets, None is returned if clip of the callout is not
+    /// installed and callout is only shown on HUD.
+    pub fn sound(self) -> Option<&'static str> {
+        let path = match self {
+            KillCallout::FirstBlood => "data/sounds/announcer/first_blood.ogg",
+            KillCallout::Headshot => "data/sounds/announcer/headshot.ogg",
+            KillCallout::Revenge => "data/sounds/announcer/revenge.ogg",
+            KillCallout::Combo(_) => "data/sounds/announcer/combo.ogg",
+        };
+        Some(path).filter(|path| Path::new(path).exists())
+    }
 }
 
 impl Visit for PersonalScore {
@@ -46,6 +163,14 @@ impl Visit for PersonalScore {
 
         self.kills.visit("Kills", visitor)?;
         self.deaths.visit("Deaths", visitor)?;
+        self.kill_streak.visit("KillStreak", visitor)?;
+        self.combo.visit("Combo", visitor)?;
+        self.last_kill_time.visit("LastKillTime", visitor)?;
+        self.last_killer.visit("LastKiller", visitor)?;
+        self.shots_fired.visit("ShotsFired", visitor)?;
+        self.hits.visit("Hits", visitor)?;
+        self.damage_dealt.visit("DamageDealt", visitor)?;
+        self.weapon_kills.visit("WeaponKills", visitor)?;
 
         visitor.leave_region()
     }
@@ -63,12 +188,72 @@ impl LeaderBoard {
             .or_insert_with(Default::default)
     }
 
-    pub fn add_frag<P: AsRef<str>>(&mut self, actor_name: P) {
-        self.get_or_add_actor(actor_name).kills += 1;
+    /// Adds frag to an actor and returns its current kill streak.
+    pub fn add_frag<P: AsRef<str>>(&mut self, actor_name: P) -> u32 {
+        let score = self.get_or_add_actor(actor_name);
+        score.kills += 1;
+        score.kill_streak += 1;
+        score.kill_streak
+    }
+
+    /// Returns true if nobody made a kill in the match yet.
+    pub fn is_first_kill(&self) -> bool {
+        self.personal_score.values().all(|score| score.kills == 0)
+    }
+
+    /// Remembers kill for combo and revenge tracking and returns callouts that the kill
+    /// deserves, except first blood and headshot which are not known to leader board. Must
+    /// be called before [`LeaderBoard::add_frag`].
+    pub fn register_kill(&mut self, killer_name: &str, victim_name: &str, time: f32) -> Vec<KillCallout> {
+        let mut callouts = Vec::new();
+
+        let killer = self.get_or_add_actor(killer_name);
+        if killer.kills > 0 && time - killer.last_kill_time <= COMBO_WINDOW {
+            killer.combo += 1;
+            callouts.push(KillCallout::Combo(killer.combo));
+        } else {
+            killer.combo = 1;
+        }
+        killer.last_kill_time = time;
+        if killer.last_killer == victim_name {
+            killer.last_killer.clear();
+            callouts.push(KillCallout::Revenge);
+        }
+
+        if killer_name != victim_name {
+            self.get_or_add_actor(victim_name).last_killer = killer_name.to_owned();
+        }
+
+        callouts
+    }
+
+    pub fn add_shot<P: AsRef<str>>(&mut self, actor_name: P) {
+        self.get_or_add_actor(actor_name).shots_fired += 1;
+    }
+
+    pub fn add_hit<P: AsRef<str>>(&mut self, actor_name: P, damage: f32) {
+        let score = self.get_or_add_actor(actor_name);
+        score.hits += 1;
+        score.damage_dealt += damage;
+    }
+
+    pub fn add_weapon_kill<P: AsRef<str>>(&mut self, actor_name: P, weapon: WeaponKind) {
+        let score = self.get_or_add_actor(actor_name);
+        let index = weapon.id() as usize;
+        if score.weapon_kills.len() <= index {
+            score.weapon_kills.resize(index + 1, 0);
+        }
+        score.weapon_kills[index] += 1;
     }
 
     pub fn add_death<P: AsRef<str>>(&mut self, actor_name: P) {
-        self.get_or_add_actor(actor_name).deaths += 1;
+        let score = self.get_or_add_actor(actor_name);
+        score.deaths += 1;
+        score.kill_streak = 0;
+    }
+
+    pub fn add_team_score(&mut self, team: Team, amount: u32) {
+        *self.team_score.entry(team).or_insert(0) += amount;
     }
 
     pub fn team_score(&self, team: Team) -> u32 {
@@ -107,22 +292,38 @@ impl LeaderBoard {
         &self.personal_score
     }
 
+    /// Returns amount of lives left for an actor, None if lives are unlimited in current match.
+    /// Lives are not stored separately, every death takes one life.
+    pub fn lives_left<P: AsRef<str>>(&self, actor_name: P, options: &MatchOptions) -> Option<u32> {
+        let lives = options.lives()?;
+        let deaths = self.personal_score
+            .get(actor_name.as_ref())
+            .map_or(0, |score| score.deaths);
+        Some(lives.saturating_sub(deaths))
+    }
+
+    pub fn is_eliminated<P: AsRef<str>>(&self, actor_name: P, options: &MatchOptions) -> bool {
+        self.lives_left(actor_name, options) == Some(0)
+    }
+
+    /// Returns names of actors that are still in the game.
+    pub fn survivors<'a>(&'a self, options: &'a MatchOptions) -> impl Iterator<Item=&'a str> + 'a {
+        self.personal_score
+            .keys()
+            .filter(move |name| !self.is_eliminated(name, options))
+            .map(|name| name.as_str())
+    }
+
     pub fn is_match_over(&self, options: &MatchOptions) -> bool {
         match options {
-            MatchOptions::DeathMatch(dm) => {
-                if let Some((_, highest_score)) = self.highest_personal_score(None) {
-                    highest_score >= dm.frag_limit
-                } else {
-                    false
+            MatchOptions::DeathMatch(_) => {
+                match (options.frag_limit(), self.highest_personal_score(None)) {
+                    (Some(frag_limit), Some((_, highest_score))) => highest_score >= frag_limit,
+                    _ => false,
                 }
             }
             MatchOptions::TeamDeathMatch(tdm) => {
-                for team_score in self.team_score.values() {
-                    if *team_score >= tdm.team_frag_limit {
-                        return true;
-                    }
-                }
-                false
+                tdm.team_frag_limit > 0 && self.team_score.values().any(|team_score| *team_score >= tdm.team_frag_limit)
             }
             MatchOptions::CaptureTheFlag(ctf) => {
                 for team_score in self.team_score.values() {
@@ -132,6 +333,13 @@ impl LeaderBoard {
                 }
                 false
             }
+            MatchOptions::LastManStanding(_) => {
+                self.personal_score.len() > 1 && self.survivors(options).count() <= 1
+            }
+            MatchOptions::TargetRange(_) => false,
+            MatchOptions::Domination(dom) => {
+                dom.score_limit > 0 && self.team_score.values().any(|team_score| *team_score >= dom.score_limit)
+            }
         }
     }
 }
@@ -156,6 +364,7 @@ impl Visit for LeaderBoard {
     }
 }
 
+#[derive(Copy, Clone)]
 pub struct LeaderBoardUI {
     root: UINodeHandle
 }
@@ -249,6 +458,9 @@ impl LeaderBoardUI {
                         MatchOptions::DeathMatch(dm) => dm.time_limit_secs,
                         MatchOptions::TeamDeathMatch(tdm) => tdm.time_limit_secs,
                         MatchOptions::CaptureTheFlag(ctf) => ctf.time_limit_secs,
+                        MatchOptions::LastManStanding(lms) => lms.time_limit_secs,
+                        MatchOptions::TargetRange(range) => range.time_limit_secs,
+                        MatchOptions::Domination(dom) => dom.time_limit_secs,
                     };
 
                     let seconds = (time_limit_secs % 60.0) as u32;
@@ -259,16 +471,23 @@ impl LeaderBoardUI {
                         MatchOptions::DeathMatch(_) => format!("Death Match - Time Limit {:02}:{:02}:{:02}", hours, minutes, seconds),
                         MatchOptions::TeamDeathMatch(_) => format!("Team Death Match - Time Limit {:02}:{:02}:{:02}", hours, minutes, seconds),
                         MatchOptions::CaptureTheFlag(_) => format!("Capture The Flag - Time Limit {:02}:{:02}:{:02}", hours, minutes, seconds),
+                        MatchOptions::LastManStanding(_) => format!("Last Man Standing - Time Limit {:02}:{:02}:{:02}", hours, minutes, seconds),
+                        MatchOptions::TargetRange(_) => format!("Target Range - Time Limit {:02}:{:02}:{:02}", hours, minutes, seconds),
+                        MatchOptions::Domination(_) => format!("Domination - Time Limit {:02}:{:02}:{:02}", hours, minutes, seconds),
                     }
                 })
                 .build(ctx))
             .with_child({
                 match match_options {
-                    MatchOptions::DeathMatch(dm) => {
+                    MatchOptions::DeathMatch(_) => {
+                        let limit = match match_options.frag_limit() {
+                            Some(frag_limit) => format!("Playing until {} frags", frag_limit),
+                            None => "No frag limit".to_owned(),
+                        };
                         let text = if let Some((name, kills)) = leader_board.highest_personal_score(None) {
-                            format!("{} leads with {} frags\nPlaying until {} frags", name, kills, dm.frag_limit)
+                            format!("{} leads with {} frags\n{}", name, kills, limit)
                         } else {
-                            format!("Draw\nPlaying until {} frags", dm.frag_limit)
+                            format!("Draw\n{}", limit)
                         };
                         TextBuilder::new(WidgetBuilder::new()
                             .with_margin(Thickness::uniform(5.0))
@@ -301,6 +520,60 @@ impl LeaderBoardUI {
                             .with_text(format!("Red team leads\nRed 0 - 0 Blue\nPlaying until {} flags", ctf.flag_limit))
                             .build(ctx)
                     }
+                    MatchOptions::LastManStanding(lms) => {
+                        let survivors = leader_board.survivors(match_options).collect::<Vec<&str>>();
+                        let text = if leader_board.is_match_over(match_options) {
+                            match survivors.first() {
+                                Some(winner) => format!("{} is the last one standing!", winner),
+                                None => "Nobody survived".to_owned(),
+                            }
+                        } else {
+                            format!("{} participants remain\nEveryone has {} lives", survivors.len(), lms.lives)
+                        };
+                        TextBuilder::new(WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(5.0))
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .on_column(0)
+                            .on_row(1))
+                            .with_text(text)
+                            .build(ctx)
+                    }
+                    MatchOptions::TargetRange(_) => {
+                        let text = match leader_board.values().get(PLAYER_NAME).and_then(|score| score.accuracy()) {
+                            Some(accuracy) => format!("Practice\nAccuracy {:.0}%", accuracy * 100.0),
+                            None => "Practice\nNo shots fired yet".to_owned(),
+                        };
+                        TextBuilder::new(WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(5.0))
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .on_column(0)
+                            .on_row(1))
+                            .with_text(text)
+                            .build(ctx)
+                    }
+                    MatchOptions::Domination(dom) => {
+                        let red_score = leader_board.team_score(Team::Red);
+                        let blue_score = leader_board.team_score(Team::Blue);
+                        let leader = if red_score > blue_score {
+                            "Red team leads"
+                        } else if blue_score > red_score {
+                            "Blue team leads"
+                        } else {
+                            "Draw"
+                        };
+                        let limit = if dom.score_limit > 0 {
+                            format!("Playing until {} points", dom.score_limit)
+                        } else {
+                            "No score limit".to_owned()
+                        };
+                        TextBuilder::new(WidgetBuilder::new()
+                            .with_margin(Thickness::uniform(5.0))
+                            .with_horizontal_alignment(HorizontalAlignment::Center)
+                            .on_column(0)
+                            .on_row(1))
+                            .with_text(format!("{}\nRed {} - {} Blue\n{}", leader, red_score, blue_score, limit))
+                            .build(ctx)
+                    }
                 }
             })
             .with_child(GridBuilder::new(WidgetBuilder::new()
@@ -363,7 +636,8 @@ impl LeaderBoardUI {
         ui.send_message(WidgetMessage::visibility(self.root, visible));
     }
 
-    pub fn process_input_event(&mut self, engine: &mut GameEngine, event: &Event<()>) {
+    /// Shows leader board while given button is held.
+    pub fn process_input_event(&mut self, engine: &mut GameEngine, event: &Event<()>, show_button: ControlButton) {
         if let Event::WindowEvent { event, .. } = event {
             match event {
                 WindowEvent::Resized(new_size) => {
@@ -371,15 +645,24 @@ impl LeaderBoardUI {
                     engine.user_interface.send_message(WidgetMessage::height(self.root, new_size.height as f32));
                 }
                 WindowEvent::KeyboardInput { input, .. } => {
-                    if let Some(vk) = input.virtual_keycode {
-                        if vk == VirtualKeyCode::Tab {
-                            let visible = match input.state {
-                                ElementState::Pressed => true,
-                                ElementState::Released => false,
-                            };
-
-                            self.set_visible(visible, &mut engine.user_interface);
-                        }
+                    if ControlButton::from_key(input) == show_button {
+                        let visible = match input.state {
+                            ElementState::Pressed => true,
+                            ElementState::Released => false,
+                        };
+
+                        self.set_visible(visible, &mut engine.user_interface);
+                    }
+                }
+                WindowEvent::MouseInput { button, state, .. } => {
+                    let index = match button {
+                        MouseButton::Left => 1,
+                        MouseButton::Right => 2,
+                        MouseButton::Middle => 3,
+                        MouseButton::Other(i) => *i,
+                    };
+                    if ControlButton::Mouse(index) == show_button {
+                        self.set_visible(*state == ElementState::Pressed, &mut engine.user_interface);
                     }
                 }
                 _ => {}
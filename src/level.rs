@@ -1,7 +1,7 @@
 use std::{
-    path::Path,
     cell::RefCell,
     rc::Rc,
+    collections::HashMap,
     sync::{
         Mutex,
         Arc,
@@ -11,30 +11,76 @@ use std::{
 use rand::Rng;
 use crate::{
     actor::{ActorContainer, Actor},
+    character::{Character, Team},
+    ammo::AmmoType,
     weapon::{
         Weapon,
         WeaponKind,
         WeaponContainer,
+        WEAPON_KINDS,
+        ShotResult,
+        AltFire,
     },
-    player::Player,
+    player::{Player, PLAYER_NAME, remote_player_name},
+    spectator::{Spectator, KillcamFrame},
     GameTime,
+    instantiate_model,
     bot::{
         Bot,
         BotKind,
+        BOT_KINDS,
+        BOT_NAMES,
+        CLEAN_BOT_NAMES,
+        VoiceEvent,
+        HEARING_RANGE,
+        GRENADE_THROW_SPEED,
+        throw_velocity,
     },
+    bot_behavior::{BotBehavior, BotBehaviorFactory, StandardBehavior, AimbotBehavior},
     projectile::{
         ProjectileContainer,
         ProjectileKind,
         Projectile,
+        PROJECTILE_KINDS,
+        GRENADE_PICKUP_TIME,
     },
+    gib::GibContainer,
+    smoke::{SmokeContainer, SMOKE_RADIUS, SMOKE_DURATION},
+    target_range::{RangeTargetContainer, RangeStats, TargetKind},
+    debug_draw::{self, DebugDrawFlags, VELOCITY_SCALE},
+    shot_debug::{ShotDebug, HitRecord},
     jump_pad::{JumpPadContainer, JumpPad},
-    item::{ItemContainer, Item, ItemKind},
-    control_scheme::ControlScheme,
-    effects,
+    door::{DoorContainer, Door, DoorKind},
+    hazard::{HazardContainer, Hazard, HazardKind},
+    control_point::{ControlPointContainer, ControlPoint, ControlPointEvent},
+    match_phase::MatchPhase,
+    item::{ItemContainer, Item, ItemKind, ITEM_KINDS, REDUNDANT_ITEM_GLOW},
+    control_scheme::{ControlScheme, PickupSwitch},
+    effects::{EffectKind, EffectContainer, EffectLimits},
+    squad::Squads,
+    comms::TeamCallout,
     message::Message,
+    loadout::Loadout,
+    skin,
+    match_log::MatchLogger,
+    settings::ContentFilter,
+    damage::{DamageKind, DamageSource, HealthRegen, HitMultipliers, HitRegion, RocketJump},
+    difficulty_balancer::DifficultyBalancer,
+    team_balance::{self, TeamBalancer, Combatant},
+    lag_compensation::{self, TransformHistory, DelayedShot},
+    palette::{self, ColorBlindMode},
+    usable::{self, Usable, UseTarget},
+    net::{ClientId, NetId, ActorState, PlayerInput},
+    prediction::{self, PredictionHistory, SnapshotBuffer},
     MatchOptions,
     GameEngine,
-    leader_board::LeaderBoard,
+    SoundPriority,
+    leader_board::{
+        LeaderBoard,
+        kill_streak_name,
+        KillCallout,
+        KILL_STREAK_REWARD_THRESHOLD,
+    },
 };
 use rg3d::{
     core::{
@@ -56,44 +102,219 @@ use rg3d::{
     event::Event,
     scene::{
         Scene,
-        base::BaseBuilder,
         node::Node,
-        camera::CameraBuilder,
+        graph::Graph,
     },
     utils::{
         self,
         navmesh::Navmesh,
     },
-    physics::RayCastOptions,
+    physics::{
+        RayCastOptions,
+        HitKind,
+        Physics,
+        rigid_body::RigidBody,
+        convex_shape::ConvexShape,
+    },
     sound::{
         context::Context,
+        buffer::SoundBuffer,
+    },
+    engine::resource_manager::ResourceManager,
+    resource::{
+        model::Model,
+        texture::{Texture, TextureKind},
     },
-    renderer::debug_renderer,
 };
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub const RESPAWN_TIME: f32 = 4.0;
+const MAP_PATH: &str = "data/models/dm6.fbx";
+/// Name of the map that is shown to player.
+pub const MAP_NAME: &str = "DM6";
+/// Allowed amount of combatants that bots fill a match up to.
+pub const BOT_FILL_RANGE: (u32, u32) = (2, 16);
+/// Player and three bots, same as in a match without network players.
+pub const DEFAULT_BOT_FILL: u32 = 4;
+/// Allowed amount of bots that can rebuild their paths on one frame.
+pub const PATH_BUDGET_RANGE: (u32, u32) = (1, 16);
+pub const DEFAULT_PATH_BUDGET: u32 = 4;
+/// Bots that fill a match are picked from these kinds in turn.
+const BOT_FILL_KINDS: [BotKind; 3] = [BotKind::Maw, BotKind::Mutant, BotKind::Parasite];
+/// Music tracks of the map, they're played in order. Menu music is played on maps without
+/// music.
+const MAP_MUSIC: &[&str] = &["data/sounds/Antonio_Bizarro_Berzerker.ogg"];
+/// Bot that receives damage exceeding its health by this amount is torn into gibs.
+const GIB_OVERKILL_DAMAGE: f32 = 40.0;
+/// Distance below lowest point of level geometry at which falling actors are killed.
+const KILL_HEIGHT_MARGIN: f32 = 10.0;
+/// Actor that dies in a death zone within this time after being hit is considered killed
+/// by last attacker, older hits are not credited.
+const ENVIRONMENT_KILL_CREDIT_TIME: f32 = 5.0;
+/// Killcam camera is this high above position of killer and looks this high above position
+/// of player, same as spectator that follows a bot.
+const KILLCAM_EYE_HEIGHT: f32 = 0.4;
+/// Time in seconds after which weapon dropped by dead actor disappears.
+const DROPPED_WEAPON_LIFETIME: f32 = 20.0;
+/// Time in seconds that marker of last death of player is shown after respawn.
+const DEATH_MARKER_TIME: f32 = 20.0;
+/// Marker of last death is removed when player comes this close to it.
+const DEATH_MARKER_REACH_DISTANCE: f32 = 2.0;
+/// Range of maximum amount of corpses that can be set in options.
+pub const MAX_CORPSES_RANGE: (u32, u32) = (0, 32);
+/// Longest lifetime of corpses in seconds that can be set in options.
+pub const MAX_CORPSE_LIFETIME: f32 = 120.0;
+/// Cosine of half of the angle in front of weapon in which bash hits.
+const BASH_COS: f32 = 0.6;
+/// Flashbang does not affect anyone farther than this, in meters.
+const FLASH_RADIUS: f32 = 20.0;
+/// Cosine of angle between view direction and direction to flashbang at which flashbang
+/// starts to blind, it blinds at full strength when it is right in front of eyes.
+const FLASH_VIEW_COS: f32 = 0.0;
+/// Flash is checked a bit above grenade, so grenade on floor is not hidden by floor itself.
+const FLASH_HEIGHT: f32 = 0.3;
+/// Bots blinded at full strength can't see anything for this long, in seconds.
+const FLASH_BOT_BLIND_TIME: f32 = 4.0;
+/// Height above spawn point at which camera of observer starts, in meters.
+const OBSERVER_HEIGHT: f32 = 3.0;
+/// How far "enemy spotted" callout points along the view of caller, in meters.
+const CALLOUT_SPOT_DISTANCE: f32 = 30.0;
+/// Distance in front of eyes at which picked up grenade is held.
+const GRENADE_HOLD_DISTANCE: f32 = 0.6;
+/// Speed in meters per second with which player throws grenade back.
+const GRENADE_THROW_BACK_SPEED: f32 = 14.0;
+/// Bots pick up grenades only this close to them.
+const BOT_GRENADE_REACH: f32 = 2.0;
+/// Fuse time that grenade must have left after pickup for bot to risk picking it up.
+const BOT_GRENADE_FUSE_MARGIN: f32 = 0.3;
 
 pub struct Level {
     map_root: Handle<Node>,
     pub scene: Handle<Scene>,
     player: Handle<Actor>,
     projectiles: ProjectileContainer,
+    gibs: GibContainer,
+    /// Oldest corpses go first.
+    corpses: Vec<Corpse>,
+    smoke: SmokeContainer,
+    /// Targets of target range, empty in other modes.
+    targets: RangeTargetContainer,
+    /// Not saved, effects are short-living and ones that exist at save time will just
+    /// expire without being counted by limits.
+    effects: EffectContainer,
+    /// Not saved, see `squad` module.
+    squads: Squads,
+    /// Not saved, see `shot_debug` module.
+    shot_debug: ShotDebug,
     pub actors: ActorContainer,
     weapons: WeaponContainer,
     jump_pads: JumpPadContainer,
+    doors: DoorContainer,
+    hazards: HazardContainer,
+    /// Control points of domination, empty in other modes.
+    control_points: ControlPointContainer,
     items: ItemContainer,
     spawn_points: Vec<SpawnPoint>,
     sender: Option<Sender<Message>>,
     pub navmesh: Option<Navmesh>,
     pub control_scheme: Option<Rc<RefCell<ControlScheme>>>,
     death_zones: Vec<DeathZone>,
+    /// Actors below this height fell out of level and are killed.
+    kill_height: f32,
+    reverb_zones: Vec<ReverbZone>,
+    /// Index of reverb zone in which player is now.
+    current_reverb_zone: Option<usize>,
     pub options: MatchOptions,
     time: f32,
+    /// Match time does not run and actors are held while intro countdown goes.
+    phase: MatchPhase,
     pub leader_board: LeaderBoard,
     respawn_list: Vec<RespawnEntry>,
-    spectator_camera: Handle<Node>,
-    target_spectator_position: Vec3,
+    spectator: Spectator,
+    /// Level has no player at all, spectator camera watches bots that fight each other.
+    observer: bool,
+    preloaded_assets: PreloadedAssets,
+    loadout: Loadout,
+    /// Adjusts strength of bots during the match, only used when match options enable it.
+    balancer: DifficultyBalancer,
+    /// Keeps teams of combatants in team modes, in other modes everyone has no team.
+    team_balancer: TeamBalancer,
+    /// Not saved, logging stops when a match is loaded from a save file.
+    match_log: Option<MatchLogger>,
+    /// Not saved, it is a user setting that is set by the game after level is created or loaded.
+    color_blind_mode: ColorBlindMode,
+    /// Not saved, same as color blind mode.
+    content_filter: ContentFilter,
+    /// Not saved, same as color blind mode. Intensity of glow around items, zero disables it.
+    item_glow: f32,
+    /// Not saved, same as color blind mode.
+    corpse_limits: CorpseLimits,
+    /// Not saved, same as color blind mode.
+    rocket_jump: RocketJump,
+    /// Not saved, same as color blind mode.
+    hit_multipliers: HitMultipliers,
+    /// Not saved, same as behaviors. Makes behavior of every bot that is spawned, None if
+    /// bots use standard behavior.
+    bot_behavior_factory: Option<BotBehaviorFactory>,
+    /// Not saved, last attacker of every actor with time of hit. Used to credit kills by
+    /// death zones, so it only matters for a few seconds.
+    last_attackers: HashMap<Handle<Actor>, (Handle<Actor>, f32)>,
+    /// Not saved, debug harness for lag compensation. Artificial delay of player shots in
+    /// seconds, None if shots are not delayed.
+    input_delay: Option<f32>,
+    /// Not saved, recorded only while input delay is set or killcam is on.
+    history: TransformHistory,
+    /// Not saved, same as color blind mode. Death of player is replayed from viewpoint of
+    /// killer before respawn.
+    killcam: bool,
+    /// Not saved, same as color blind mode. Maximum amount of bots that rebuild their paths
+    /// on one frame, bots that wait longest go first.
+    path_budget: u32,
+    /// Not saved, amount of bots that rebuilt their paths on last frame.
+    path_rebuilds: u32,
+    /// Not saved, same as color blind mode. Dead actors drop their current weapon with ammo
+    /// that was left in it.
+    weapon_drop: bool,
+    /// Not saved, shots of player that wait for input delay to pass.
+    delayed_shots: Vec<DelayedShot>,
+    /// Not saved, network matches can't be saved. Actors of clients of listen server, handle
+    /// is none while client waits for respawn.
+    remote_players: Vec<(ClientId, Handle<Actor>)>,
+    /// Not saved, same as remote players. Amount of combatants that is kept by removing bots
+    /// when network players join and adding them back when players leave, None if amount of
+    /// bots does not depend on players.
+    bot_fill: Option<u32>,
+    /// Not saved. Level only shows state received from server when it is set.
+    replica: Option<Replica>,
+    /// Not saved. Place where own player died last time with time left to show it, time
+    /// goes only while player is alive.
+    death_marker: Option<(Vec3, f32)>,
+}
+
+/// State of level that is a replica of a level on server.
+#[derive(Default)]
+struct Replica {
+    /// Local actor for every server actor except own player.
+    actors: HashMap<NetId, Handle<Actor>>,
+    /// Server actor of own player, None while it waits for respawn.
+    own_actor: Option<NetId>,
+    snapshots: SnapshotBuffer,
+    predictions: PredictionHistory,
+}
+
+/// Holds strong references to resources loaded before match starts, so resource manager
+/// won't unload them while they are not used by anything on level.
+#[derive(Default)]
+struct PreloadedAssets {
+    models: Vec<Arc<Mutex<Model>>>,
+    textures: Vec<Arc<Mutex<Texture>>>,
+    sound_buffers: Vec<Arc<Mutex<SoundBuffer>>>,
+}
+
+impl PreloadedAssets {
+    fn count(&self) -> usize {
+        self.models.len() + self.textures.len() + self.sound_buffers.len()
+    }
 }
 
 impl Default for Level {
@@ -101,23 +322,61 @@ impl Default for Level {
         Self {
             map_root: Default::default(),
             projectiles: ProjectileContainer::new(),
+            gibs: GibContainer::new(),
+            corpses: Default::default(),
+            smoke: SmokeContainer::new(),
+            targets: RangeTargetContainer::new(),
+            effects: EffectContainer::new(),
+            squads: Squads::new(),
+            shot_debug: Default::default(),
             actors: ActorContainer::new(),
             scene: Handle::NONE,
             player: Handle::NONE,
             weapons: WeaponContainer::new(),
             jump_pads: JumpPadContainer::new(),
+            doors: DoorContainer::new(),
+            hazards: HazardContainer::new(),
+            control_points: ControlPointContainer::new(),
             items: ItemContainer::new(),
             spawn_points: Default::default(),
             sender: None,
             navmesh: Default::default(),
             control_scheme: None,
             death_zones: Default::default(),
+            kill_height: std::f32::MIN,
+            reverb_zones: Default::default(),
+            current_reverb_zone: None,
             options: Default::default(),
             time: 0.0,
+            phase: Default::default(),
             leader_board: Default::default(),
             respawn_list: Default::default(),
-            spectator_camera: Default::default(),
-            target_spectator_position: Default::default(),
+            spectator: Default::default(),
+            observer: false,
+            preloaded_assets: Default::default(),
+            loadout: Default::default(),
+            balancer: Default::default(),
+            team_balancer: Default::default(),
+            match_log: None,
+            color_blind_mode: Default::default(),
+            content_filter: Default::default(),
+            item_glow: 0.0,
+            corpse_limits: Default::default(),
+            rocket_jump: Default::default(),
+            hit_multipliers: Default::default(),
+            bot_behavior_factory: None,
+            last_attackers: Default::default(),
+            input_delay: None,
+            history: Default::default(),
+            killcam: true,
+            path_budget: DEFAULT_PATH_BUDGET,
+            path_rebuilds: 0,
+            weapon_drop: true,
+            delayed_shots: Default::default(),
+            remote_players: Default::default(),
+            bot_fill: None,
+            replica: None,
+            death_marker: None,
         }
     }
 }
@@ -131,16 +390,32 @@ impl Visit for Level {
         self.player.visit("Player", visitor)?;
         self.actors.visit("Actors", visitor)?;
         self.projectiles.visit("Projectiles", visitor)?;
+        self.gibs.visit("Gibs", visitor)?;
         self.weapons.visit("Weapons", visitor)?;
+        if visitor.is_reading() {
+            self.link_ammo_stores();
+        }
         self.jump_pads.visit("JumpPads", visitor)?;
+        self.doors.visit("Doors", visitor)?;
         self.spawn_points.visit("SpawnPoints", visitor)?;
         self.death_zones.visit("DeathZones", visitor)?;
+        self.kill_height.visit("KillHeight", visitor)?;
+        self.reverb_zones.visit("ReverbZones", visitor)?;
         self.options.visit("Options", visitor)?;
         self.time.visit("Time", visitor)?;
+        self.phase.visit("Phase", visitor)?;
         self.leader_board.visit("LeaderBoard", visitor)?;
         self.respawn_list.visit("RespawnList", visitor)?;
-        self.spectator_camera.visit("SpectatorCamera", visitor)?;
-        self.target_spectator_position.visit("TargetSpectatorPosition", visitor)?;
+        self.spectator.visit("Spectator", visitor)?;
+        self.loadout.visit("Loadout", visitor)?;
+        self.balancer.visit("Balancer", visitor)?;
+        self.team_balancer.visit("TeamBalancer", visitor)?;
+        self.smoke.visit("Smoke", visitor)?;
+        self.targets.visit("Targets", visitor)?;
+        self.corpses.visit("Corpses", visitor)?;
+        self.observer.visit("Observer", visitor)?;
+        self.hazards.visit("Hazards", visitor)?;
+        self.control_points.visit("ControlPoints", visitor)?;
 
         visitor.leave_region()
     }
@@ -168,6 +443,186 @@ impl Default for DeathZone {
     }
 }
 
+/// Character of reverberation of gameplay sounds.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ReverbPreset {
+    /// Used when player is not inside any reverb zone.
+    Default,
+    Room,
+    Hall,
+    Tunnel,
+}
+
+impl Default for ReverbPreset {
+    fn default() -> Self {
+        ReverbPreset::Default
+    }
+}
+
+impl ReverbPreset {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(ReverbPreset::Default),
+            1 => Ok(ReverbPreset::Room),
+            2 => Ok(ReverbPreset::Hall),
+            3 => Ok(ReverbPreset::Tunnel),
+            _ => Err(format!("Invalid reverb preset {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            ReverbPreset::Default => 0,
+            ReverbPreset::Room => 1,
+            ReverbPreset::Hall => 2,
+            ReverbPreset::Tunnel => 3,
+        }
+    }
+
+    /// Parses preset from name of zone node, for example `ReverbZone_Hall`.
+    fn from_zone_name(name: &str) -> Option<Self> {
+        let preset = name.trim_start_matches("ReverbZone").trim_start_matches('_');
+        if preset.starts_with("Room") {
+            Some(ReverbPreset::Room)
+        } else if preset.starts_with("Hall") {
+            Some(ReverbPreset::Hall)
+        } else if preset.starts_with("Tunnel") {
+            Some(ReverbPreset::Tunnel)
+        } else {
+            None
+        }
+    }
+
+    /// Decay time of reverb in seconds.
+    pub fn decay_time(self) -> f32 {
+        match self {
+            ReverbPreset::Default => 3.0,
+            ReverbPreset::Room => 1.2,
+            ReverbPreset::Hall => 5.0,
+            ReverbPreset::Tunnel => 7.0,
+        }
+    }
+
+    /// Multiplier for gain of gameplay sounds.
+    pub fn gain(self) -> f32 {
+        match self {
+            ReverbPreset::Default => 1.0,
+            ReverbPreset::Room => 0.9,
+            ReverbPreset::Hall => 1.0,
+            ReverbPreset::Tunnel => 1.2,
+        }
+    }
+}
+
+impl Visit for ReverbPreset {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Area of level in which gameplay sounds have specific reverberation.
+pub struct ReverbZone {
+    bounds: AxisAlignedBoundingBox,
+    preset: ReverbPreset,
+}
+
+impl Visit for ReverbZone {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.bounds.visit("Bounds", visitor)?;
+        self.preset.visit("Preset", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl Default for ReverbZone {
+    fn default() -> Self {
+        Self {
+            bounds: Default::default(),
+            preset: Default::default(),
+        }
+    }
+}
+
+/// Limits of bodies of dead bots, corpses pile up in long matches and hurt performance
+/// and clarity of view.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct CorpseLimits {
+    /// Time in seconds before corpse disappears, zero removes bots right on death.
+    pub lifetime: f32,
+    /// Maximum amount of corpses on level, oldest corpses are removed first.
+    pub max_corpses: u32,
+}
+
+impl Default for CorpseLimits {
+    fn default() -> Self {
+        Self {
+            lifetime: 20.0,
+            max_corpses: 8,
+        }
+    }
+}
+
+impl Visit for CorpseLimits {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.lifetime.visit("Lifetime", visitor)?;
+        self.max_corpses.visit("MaxCorpses", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Model of dead bot that is left on level after bot was removed.
+pub struct Corpse {
+    model: Handle<Node>,
+    time_left: f32,
+}
+
+impl Default for Corpse {
+    fn default() -> Self {
+        Self {
+            model: Default::default(),
+            time_left: 0.0,
+        }
+    }
+}
+
+impl Visit for Corpse {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.model.visit("Model", visitor)?;
+        self.time_left.visit("TimeLeft", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Returns path to looping ambient sound for ambient sound node with given name, for
+/// example `AmbientSound_Wind`.
+fn ambient_sound_path(name: &str) -> Option<&'static str> {
+    let kind = name.trim_start_matches("AmbientSound").trim_start_matches('_');
+    if kind.starts_with("Wind") {
+        Some("data/sounds/ambient_wind.ogg")
+    } else if kind.starts_with("Machinery") {
+        Some("data/sounds/ambient_machinery.ogg")
+    } else {
+        None
+    }
+}
+
 pub struct UpdateContext<'a> {
     pub time: GameTime,
     pub scene: &'a mut Scene,
@@ -176,6 +631,17 @@ pub struct UpdateContext<'a> {
     pub jump_pads: &'a JumpPadContainer,
     pub navmesh: Option<&'a mut Navmesh>,
     pub weapons: &'a WeaponContainer,
+    /// Position and blast radius of every live explosive.
+    pub explosives: &'a [(Vec3, f32)],
+    /// Clouds of smoke grenades, they block sight of bots.
+    pub smoke: &'a SmokeContainer,
+    /// Match has not started yet, actors can look around but can't move or shoot.
+    pub frozen: bool,
+    pub squads: &'a mut Squads,
+    /// Volumes of hazards, bots keep out of them.
+    pub hazards: &'a [AxisAlignedBoundingBox],
+    /// Control points of domination, bots attack and defend them.
+    pub control_points: &'a [ControlPoint],
 }
 
 struct PlayerRespawnEntry {
@@ -231,9 +697,35 @@ impl Visit for BotRespawnEntry {
     }
 }
 
+struct RemoteRespawnEntry {
+    client: ClientId,
+    time_left: f32,
+}
+
+impl Default for RemoteRespawnEntry {
+    fn default() -> Self {
+        Self {
+            client: 0,
+            time_left: 0.0,
+        }
+    }
+}
+
+impl Visit for RemoteRespawnEntry {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.client.visit("Client", visitor)?;
+        self.time_left.visit("TimeLeft", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
 enum RespawnEntry {
     Bot(BotRespawnEntry),
     Player(PlayerRespawnEntry),
+    Remote(RemoteRespawnEntry),
 }
 
 impl Default for RespawnEntry {
@@ -247,6 +739,7 @@ impl RespawnEntry {
         match self {
             RespawnEntry::Bot { .. } => 0,
             RespawnEntry::Player { .. } => 1,
+            RespawnEntry::Remote { .. } => 2,
         }
     }
 
@@ -254,6 +747,7 @@ impl RespawnEntry {
         match id {
             0 => Ok(RespawnEntry::Bot(Default::default())),
             1 => Ok(RespawnEntry::Player(Default::default())),
+            2 => Ok(RespawnEntry::Remote(Default::default())),
             _ => Err(format!("Invalid RespawnEntry type {}", id))
         }
     }
@@ -272,6 +766,7 @@ impl Visit for RespawnEntry {
         match self {
             RespawnEntry::Bot(v) => v.visit("Data", visitor)?,
             RespawnEntry::Player(v) => v.visit("Data", visitor)?,
+            RespawnEntry::Remote(v) => v.visit("Data", visitor)?,
         }
 
         visitor.leave_region()
@@ -279,35 +774,31 @@ impl Visit for RespawnEntry {
 }
 
 impl Level {
+    /// Creates level of a new match. Observer level spawns no player, input goes to
+    /// spectator camera instead.
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         engine: &mut GameEngine,
         control_scheme: Rc<RefCell<ControlScheme>>,
         sender: Sender<Message>,
         options: MatchOptions,
+        loadout: Loadout,
+        match_log: Option<MatchLogger>,
+        content_filter: ContentFilter,
+        observer: bool,
     ) -> Level {
         let mut scene = Scene::new();
 
-        // Spectator camera is used when there is no player on level.
-        // This includes situation when player is dead - all dead actors are removed
-        // from level.
-        let spectator_camera = scene.graph.add_node(Node::Camera(
-            CameraBuilder::new(BaseBuilder::new())
-                .enabled(false)
-                .build())
-        );
+        let spectator = Spectator::new(&mut scene);
 
-        let mut map_root = Handle::NONE;
-        let map_model = engine.resource_manager.lock().unwrap().request_model(Path::new("data/models/dm6.fbx"));
-        if let Some(map_model) = map_model {
-            // Instantiate map
-            map_root = map_model.lock().unwrap().instantiate_geometry(&mut scene);
-            // Create collision geometry
-            let polygon_handle = scene.graph.find_by_name(map_root, "Polygon");
-            if polygon_handle.is_some() {
-                scene.physics.add_static_geometry(utils::mesh_to_static_geometry(scene.graph[polygon_handle].as_mesh()));
-            } else {
-                println!("Unable to find Polygon node to build collision shape for level!");
-            }
+        // Instantiate map, if map is missing we'll get empty level but game won't crash.
+        let map_root = instantiate_model(&mut engine.resource_manager.lock().unwrap(), MAP_PATH, &mut scene);
+        // Create collision geometry
+        let polygon_handle = scene.graph.find_by_name(map_root, "Polygon");
+        if polygon_handle.is_some() {
+            scene.physics.add_static_geometry(utils::mesh_to_static_geometry(scene.graph[polygon_handle].as_mesh()));
+        } else {
+            println!("Unable to find Polygon node to build collision shape for level!");
         }
 
         let mut level = Level {
@@ -316,20 +807,139 @@ impl Level {
             control_scheme: Some(control_scheme),
             map_root,
             options,
-            spectator_camera,
+            phase: MatchPhase::new(options.intro_countdown()),
+            spectator,
+            loadout,
+            match_log,
+            content_filter,
+            observer,
+            team_balancer: TeamBalancer::new(options.balance_teams()),
             ..Default::default()
         };
 
+        if let Some(match_log) = level.match_log.as_mut() {
+            match_log.log_match_start(0.0, options.name());
+        }
+
+        if let Some(seconds_left) = level.phase.seconds_left() {
+            level.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::MatchCountdown { seconds_left })
+                .unwrap();
+        }
+
+        level.preload_assets(&mut engine.resource_manager.lock().unwrap());
         level.build_navmesh(engine);
         level.analyze(engine);
-        level.spawn_player(engine);
-        level.spawn_bot(engine, BotKind::Maw, Some("Maw".to_owned()));
-        level.spawn_bot(engine, BotKind::Mutant, Some("Mutant".to_owned()));
-        level.spawn_bot(engine, BotKind::Parasite, Some("Parasite".to_owned()));
+        if observer {
+            level.begin_observing(engine);
+        } else {
+            level.spawn_player(engine);
+        }
+        // Practice has targets instead of bots, they're placed by `analyze`.
+        if !options.is_practice() {
+            level.spawn_bot(engine, BotKind::Maw, None);
+            level.spawn_bot(engine, BotKind::Mutant, None);
+            level.spawn_bot(engine, BotKind::Parasite, None);
+        }
 
         level
     }
 
+    /// Requests every asset that can be used during a match, so there will be no hitches when
+    /// some weapon, item or effect is used first time. Lists of assets are made from
+    /// definitions of every kind, so new kinds are preloaded without changes here.
+    pub fn preload_assets(&mut self, resource_manager: &mut ResourceManager) {
+        let mut models = Vec::new();
+        let mut textures = vec![
+            ("data/particles/light_01.png", TextureKind::R8),
+            ("data/particles/smoke_04.tga", TextureKind::R8),
+            ("data/particles/circle_05.png", TextureKind::R8),
+            ("data/particles/star_09.png", TextureKind::R8),
+        ];
+        let mut sounds = vec![
+            "data/sounds/item_pickup.ogg",
+            "data/sounds/footsteps/FootStep_shoe_stone_step1.wav",
+            "data/sounds/footsteps/FootStep_shoe_stone_step2.wav",
+            "data/sounds/footsteps/FootStep_shoe_stone_step3.wav",
+            "data/sounds/footsteps/FootStep_shoe_stone_step4.wav",
+        ];
+
+        for &kind in WEAPON_KINDS.iter() {
+            let definition = Weapon::get_definition(kind);
+            models.push(definition.model);
+            if let Some(animations) = definition.view_model_animations.as_ref() {
+                models.extend_from_slice(&[
+                    animations.idle,
+                    animations.fire,
+                    animations.reload,
+                    animations.draw,
+                    animations.holster,
+                ]);
+            }
+            if let Some(texture) = skin::find(definition.skin).and_then(|skin| skin.texture) {
+                textures.push((texture, TextureKind::RGBA8));
+            }
+            sounds.push(definition.shot_sound);
+            sounds.push(definition.dry_fire_sound);
+            if let Some(heat) = definition.heat.as_ref() {
+                sounds.push(heat.overheat_sound);
+            }
+        }
+        for &kind in ITEM_KINDS.iter() {
+            models.push(Item::get_definition(kind).model);
+        }
+        for &kind in BOT_KINDS.iter() {
+            let definition = Bot::get_definition(kind);
+            models.extend_from_slice(&[
+                definition.model,
+                definition.idle_animation,
+                definition.walk_animation,
+                definition.aim_animation,
+                definition.whip_animation,
+                definition.jump_animation,
+                definition.falling_animation,
+                definition.hit_reaction_animation,
+                definition.dying_animation,
+                definition.dead_animation,
+            ]);
+        }
+        for &kind in PROJECTILE_KINDS.iter() {
+            sounds.push(Projectile::get_definition(kind).impact_sound);
+        }
+        models.push("data/models/rocket.FBX");
+
+        let mut failed = Vec::new();
+        let assets = &mut self.preloaded_assets;
+        for path in models {
+            match resource_manager.request_model(path) {
+                Some(model) => assets.models.push(model),
+                None => failed.push(path),
+            }
+        }
+        for (path, kind) in textures {
+            match resource_manager.request_texture(path, kind) {
+                Some(texture) => assets.textures.push(texture),
+                None => failed.push(path),
+            }
+        }
+        for path in sounds {
+            match resource_manager.request_sound_buffer(path, false) {
+                Some(buffer) => assets.sound_buffers.push(buffer),
+                None => failed.push(path),
+            }
+        }
+
+        // Same asset can be listed more than once.
+        failed.sort();
+        failed.dedup();
+        for path in failed.iter() {
+            println!("Unable to preload {}! It will be missing during the match.", path);
+        }
+        println!("{} assets preloaded, {} failed.", assets.count(), failed.len());
+    }
+
     pub fn build_navmesh(&mut self, engine: &mut GameEngine) {
         if self.navmesh.is_none() {
             let scene = &mut engine.scenes[self.scene];
@@ -348,10 +958,20 @@ impl Level {
         let mut items = Vec::new();
         let mut spawn_points = Vec::new();
         let mut death_zones = Vec::new();
+        let mut reverb_zones = Vec::new();
+        let mut ambient_sounds = Vec::new();
+        let mut doors = Vec::new();
+        let mut hazards = Vec::new();
+        let mut targets = Vec::new();
+        let mut control_points = Vec::new();
+        let mut lowest_point = std::f32::MAX;
         let scene = &mut engine.scenes[self.scene];
         for (handle, node) in scene.graph.pair_iter() {
             let position = node.global_position();
             let name = node.name();
+            if let Node::Mesh(mesh) = node {
+                lowest_point = lowest_point.min(mesh.world_bounding_box().min.y);
+            }
             if name.starts_with("JumpPad") {
                 let begin = scene.graph.find_by_name_from_root(format!("{}_Begin", name).as_str());
                 let end = scene.graph.find_by_name_from_root(format!("{}_End", name).as_str());
@@ -378,11 +998,63 @@ impl Level {
                 if let Node::Mesh(_) = node {
                     death_zones.push(handle);
                 }
+            } else if name.starts_with("ReverbZone") {
+                if let Node::Mesh(_) = node {
+                    match ReverbPreset::from_zone_name(name) {
+                        Some(preset) => reverb_zones.push((handle, preset)),
+                        None => println!("Unknown reverb preset of zone {}!", name),
+                    }
+                }
+            } else if name.starts_with("DoorSlide") || name.starts_with("DoorSwing") {
+                if let Node::Mesh(_) = node {
+                    let kind = if name.starts_with("DoorSlide") { DoorKind::Sliding } else { DoorKind::Swinging };
+                    doors.push((handle, kind, name.contains("Use")));
+                }
+            } else if name.starts_with("ControlPoint") {
+                if let Node::Mesh(_) = node {
+                    control_points.push((name.to_owned(), handle));
+                }
+            } else if let Some(kind) = HazardKind::from_node_name(name) {
+                if let Node::Mesh(_) = node {
+                    hazards.push((handle, kind));
+                }
+            } else if let Some(kind) = TargetKind::from_node_name(name) {
+                targets.push((kind, position));
+            } else if name.starts_with("AmbientSound") {
+                match ambient_sound_path(name) {
+                    // Ambient sounds are optional assets, node stays silent if its sound is
+                    // not installed.
+                    Some(path) if Path::new(path).exists() => ambient_sounds.push((path, position)),
+                    Some(path) => println!("Ambient sound {} of {} is not installed, it will be silent.", path, name),
+                    None => println!("Unknown ambient sound {}!", name),
+                }
             }
         }
+        for (handle, kind, use_only) in doors {
+            self.doors.add(Door::new(scene, handle, kind, use_only));
+        }
+        for (handle, kind) in hazards {
+            self.hazards.add(Hazard::new(&scene.graph, handle, kind));
+        }
+        // Points are labeled in the order of their names, so map author decides which is A.
+        control_points.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let control_point_bounds = control_points.iter()
+            .map(|(_, handle)| {
+                let node = &mut scene.graph[*handle];
+                node.set_visibility(false);
+                node.as_mesh().world_bounding_box()
+            })
+            .collect::<Vec<_>>();
+        if self.options.capture_time().is_some() {
+            let item_positions = items.iter().map(|(_, position)| *position).collect::<Vec<_>>();
+            self.control_points.build(&control_point_bounds, &item_positions);
+        }
         for (kind, position) in items {
             self.items.add(Item::new(kind, position, scene, &mut engine.resource_manager.lock().unwrap(), self.sender.as_ref().unwrap().clone()));
         }
+        if lowest_point < std::f32::MAX {
+            self.kill_height = lowest_point - KILL_HEIGHT_MARGIN;
+        }
         for handle in death_zones {
             let node = &mut scene.graph[handle];
             node.set_visibility(false);
@@ -392,6 +1064,31 @@ impl Level {
                         .world_bounding_box()
                 });
         }
+        for (handle, preset) in reverb_zones {
+            let node = &mut scene.graph[handle];
+            node.set_visibility(false);
+            self.reverb_zones
+                .push(ReverbZone {
+                    bounds: node.as_mesh()
+                        .world_bounding_box(),
+                    preset,
+                });
+        }
+        // Ambient sounds are owned by sound manager, it will remove them with other
+        // sounds of level.
+        for (path, position) in ambient_sounds {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::AddAmbientSound {
+                    path: PathBuf::from(path),
+                    position,
+                })
+                .unwrap();
+        }
+        if self.options.is_practice() {
+            self.targets.spawn(scene, &mut engine.resource_manager.lock().unwrap(), &targets, &spawn_points);
+        }
         self.spawn_points = spawn_points
             .into_iter()
             .map(|p| SpawnPoint { position: p })
@@ -400,21 +1097,49 @@ impl Level {
 
     pub fn destroy(&mut self, engine: &mut GameEngine) {
         engine.scenes.remove(self.scene);
+        // Log is complete even if the match did not end.
+        if let Some(match_log) = self.match_log.as_mut() {
+            match_log.flush();
+        }
     }
 
     pub fn get_player(&self) -> Handle<Actor> {
         self.player
     }
 
+    /// Releases every control of player, so it won't keep moving if keys are released while
+    /// input goes elsewhere.
+    pub fn reset_player_input(&mut self) {
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                player.reset_input();
+            }
+        }
+    }
+
     pub fn process_input_event(&mut self, event: &Event<()>) -> bool {
         if self.player.is_some() {
             if let Actor::Player(player) = self.actors.get_mut(self.player) {
                 return player.process_input_event(event);
             }
+        } else if let Some(control_scheme) = self.control_scheme.as_ref() {
+            // Player is dead, spectator camera can be controlled while waiting for respawn.
+            self.spectator.process_input_event(event, control_scheme);
         }
         false
     }
 
+    /// Returns handle of camera that is currently used to render level: camera of player
+    /// or spectator camera if player is dead.
+    pub fn active_camera(&self) -> Handle<Node> {
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get(self.player) {
+                return player.camera();
+            }
+        }
+        self.spectator.camera()
+    }
+
     pub fn actors(&self) -> &ActorContainer {
         &self.actors
     }
@@ -423,24 +1148,153 @@ impl Level {
         &mut self.actors
     }
 
+    /// Sets palette for identification colors and recolors every bot on the level.
+    pub fn set_color_blind_mode(&mut self, engine: &mut GameEngine, mode: ColorBlindMode) {
+        self.color_blind_mode = mode;
+        let palette = palette::palette(mode);
+        let scene = &mut engine.scenes[self.scene];
+        for actor in self.actors.iter_mut() {
+            if let Actor::Bot(bot) = actor {
+                let color = palette.team_color(bot.team).unwrap_or_else(|| palette.bot_color(&bot.name));
+                bot.set_color(&mut scene.graph, color);
+            }
+        }
+    }
+
+    /// Applies skins to models of every weapon on the level, has to be called after loading
+    /// a save.
+    pub fn restore_weapon_skins(&mut self, engine: &mut GameEngine) {
+        let graph = &mut engine.scenes[self.scene].graph;
+        let mut resource_manager = engine.resource_manager.lock().unwrap();
+        for weapon in self.weapons.iter_mut() {
+            weapon.reapply_skin(graph, &mut resource_manager);
+        }
+    }
+
+    /// Turns debug records of shots and hits on or off, see `shot_debug` module.
+    pub fn set_shot_debug(&mut self, enabled: bool) {
+        if self.shot_debug.is_enabled() != enabled {
+            self.shot_debug.set_enabled(enabled);
+        }
+    }
+
+    pub fn shot_debug(&self) -> &ShotDebug {
+        &self.shot_debug
+    }
+
+    /// Changes limits of visual effects, effects over new limit are removed right away.
+    pub fn set_effect_limits(&mut self, engine: &mut GameEngine, limits: EffectLimits) {
+        self.effects.set_limits(&mut engine.scenes[self.scene].graph, limits);
+    }
+
+    /// Changes limits of corpses, corpses over new limits are removed on next update.
+    pub fn set_corpse_limits(&mut self, limits: CorpseLimits) {
+        self.corpse_limits = limits;
+    }
+
+    /// Changes damage and knockback that actors get from their own explosions.
+    pub fn set_rocket_jump(&mut self, rocket_jump: RocketJump) {
+        self.rocket_jump = rocket_jump;
+    }
+
+    /// Changes health regeneration of every player on the level, new players get it too.
+    pub fn set_health_regen(&mut self, health_regen: HealthRegen) {
+        self.options.set_health_regen(health_regen);
+        for actor in self.actors.iter_mut() {
+            if let Actor::Player(player) = actor {
+                player.set_health_regen(health_regen);
+            }
+        }
+    }
+
+    /// Changes multipliers of damage of projectiles by part of body they hit.
+    pub fn set_hit_multipliers(&mut self, hit_multipliers: HitMultipliers) {
+        self.hit_multipliers = hit_multipliers;
+    }
+
+    /// Sets intensity of glow around items, zero disables it. Glow is updated every frame,
+    /// because it depends on what player has.
+    pub fn set_item_glow(&mut self, intensity: f32) {
+        self.item_glow = intensity;
+    }
+
+    /// Returns true if player would benefit from picking up item of given kind right now:
+    /// player is hurt, has weapon for ammo or does not have weapon yet.
+    fn player_needs_item(&self, kind: ItemKind) -> bool {
+        if !self.actors.contains(self.player) {
+            return false;
+        }
+        let player = self.actors.get(self.player);
+        let has_weapon = |weapon_kind: WeaponKind| player
+            .weapons()
+            .iter()
+            .any(|weapon| self.weapons[*weapon].get_kind() == weapon_kind);
+        let uses_ammo = |kind: ItemKind| item_ammo_type(kind).map_or(false, |ammo_type| player
+            .weapons()
+            .iter()
+            .any(|weapon| self.weapons[*weapon].ammo_type() == ammo_type));
+        match kind {
+            ItemKind::Medkit => player.get_health() < 100.0,
+            ItemKind::Plasma | ItemKind::Ak47Ammo | ItemKind::M4Ammo => uses_ammo(kind),
+            ItemKind::PlasmaGun => !has_weapon(WeaponKind::PlasmaRifle),
+            ItemKind::Ak47 => !has_weapon(WeaponKind::Ak47),
+            ItemKind::M4 => !has_weapon(WeaponKind::M4),
+            ItemKind::RocketLauncher => !has_weapon(WeaponKind::RocketLauncher),
+        }
+    }
+
+    /// Items that player needs glow brighter than items that are of no use right now.
+    fn update_item_glow(&self, graph: &mut Graph) {
+        for item in self.items.iter() {
+            let intensity = if self.player_needs_item(item.get_kind()) {
+                self.item_glow
+            } else {
+                self.item_glow * REDUNDANT_ITEM_GLOW
+            };
+            item.set_glow(graph, intensity);
+        }
+    }
+
+    /// Changes content filter, it affects effects and voice lines from now on. Names of bots
+    /// that are already in match are kept, so scores won't be lost.
+    pub fn set_content_filter(&mut self, filter: ContentFilter) {
+        self.content_filter = filter;
+        for actor in self.actors.iter_mut() {
+            if let Actor::Bot(bot) = actor {
+                bot.set_clean_language(filter.clean_language);
+            }
+        }
+    }
+
+    /// Name of the map file without extension.
+    pub fn map_name(&self) -> &'static str {
+        Path::new(MAP_PATH)
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .unwrap_or(MAP_PATH)
+    }
+
+    pub fn music(&self) -> &'static [&'static str] {
+        MAP_MUSIC
+    }
+
+    pub fn items(&self) -> &ItemContainer {
+        &self.items
+    }
+
     pub fn weapons(&self) -> &WeaponContainer {
         &self.weapons
     }
 
     fn pick(&self, engine: &mut GameEngine, from: Vec3, to: Vec3) -> Vec3 {
         let scene = &engine.scenes[self.scene];
-        if let Some(ray) = Ray::from_two_points(&from, &to) {
-            let mut intersections = Vec::new();
-            let options = RayCastOptions { ignore_bodies: true, ..Default::default() };
-            scene.physics.ray_cast(&ray, options, &mut intersections);
-            if let Some(pt) = intersections.first() {
-                pt.position
-            } else {
-                from
-            }
-        } else {
-            from
-        }
+        cast_ray(&scene.physics, from, to - from, true)
+            .map_or(from, |hit| hit.position)
+    }
+
+    /// Returns every alive actor inside of given sphere.
+    pub fn overlap_sphere(&self, engine: &GameEngine, center: Vec3, radius: f32) -> Vec<Handle<Actor>> {
+        self.actors.overlap_sphere(&engine.scenes[self.scene].physics, center, radius)
     }
 
     fn remove_weapon(&mut self, engine: &mut GameEngine, weapon: Handle<Weapon>) {
@@ -454,15 +1308,35 @@ impl Level {
         self.weapons.free(weapon);
     }
 
-    fn give_new_weapon(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, kind: WeaponKind) {
+    /// Links every weapon to ammo store of its owner, links are not saved.
+    fn link_ammo_stores(&mut self) {
+        for actor in self.actors.iter() {
+            for weapon in actor.weapons() {
+                if self.weapons.contains(*weapon) {
+                    self.weapons[*weapon].set_ammo_store(actor.ammo_store());
+                }
+            }
+        }
+    }
+
+    /// Gives new weapon to actor, `select` tells whether the weapon becomes current one.
+    /// Weapon is loaded with given amount of ammo, None gives ammo of weapon definition.
+    fn give_new_weapon(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, kind: WeaponKind, select: bool, ammo: Option<u32>) -> Handle<Weapon> {
         if self.actors.contains(actor) {
             let scene = &mut engine.scenes[self.scene];
-            let mut weapon = Weapon::new(kind, &mut engine.resource_manager.lock().unwrap(), scene, self.sender.as_ref().unwrap().clone());
+            // Skins from loadout are picked by player, bots keep skins from definitions.
+            let skin = match self.actors.get(actor) {
+                Actor::Player(_) => self.loadout.skin(kind),
+                _ => None,
+            };
+            let mut weapon = Weapon::new(kind, skin, &mut engine.resource_manager.lock().unwrap(), scene, self.sender.as_ref().unwrap().clone());
             weapon.set_owner(actor);
-            let weapon_model = weapon.get_model();
             let actor = self.actors.get_mut(actor);
+            weapon.set_ammo_store(actor.ammo_store());
+            weapon.load(ammo.unwrap_or(Weapon::get_definition(kind).ammo));
+            let weapon_model = weapon.get_model();
             let weapon_handle = self.weapons.add(weapon);
-            actor.add_weapon(weapon_handle);
+            actor.add_weapon(weapon_handle, select);
             scene.graph.link_nodes(weapon_model, actor.weapon_pivot());
 
             self.sender
@@ -471,35 +1345,99 @@ impl Level {
                 .send(Message::AddNotification {
                     text: format!("Actor picked up weapon {:?}", kind)
                 }).unwrap();
+
+            weapon_handle
+        } else {
+            Handle::NONE
+        }
+    }
+
+    /// Gives behavior made by given factory to every bot, including bots spawned later. None
+    /// returns bots to standard behavior.
+    pub fn set_bot_behavior_factory(&mut self, factory: Option<BotBehaviorFactory>) {
+        for actor in self.actors.iter_mut() {
+            if let Actor::Bot(bot) = actor {
+                let behavior = match factory.as_ref() {
+                    Some(factory) => factory(bot),
+                    None => Box::new(StandardBehavior),
+                };
+                bot.set_behavior(behavior);
+            }
         }
+        self.bot_behavior_factory = factory;
+    }
+
+    /// Gives aimbot behavior with given shoot interval to every bot, including bots spawned
+    /// later. None returns bots to standard behavior.
+    pub fn set_bot_aimbot(&mut self, interval: Option<f32>) {
+        self.set_bot_behavior_factory(interval.map(|interval| {
+            Box::new(move |_: &Bot| Box::new(AimbotBehavior::new(interval)) as Box<dyn BotBehavior>) as BotBehaviorFactory
+        }));
     }
 
+    /// Spawns new bot, it gets behavior from behavior factory or standard one if there is no
+    /// factory.
     fn add_bot(&mut self, engine: &mut GameEngine, kind: BotKind, position: Vec3, name: Option<String>) -> Handle<Actor> {
         let scene = &mut engine.scenes[self.scene];
-        let bot = Bot::new(kind, &mut engine.resource_manager.lock().unwrap(), scene, position, self.sender.as_ref().unwrap().clone()).unwrap();
-        let name = name.unwrap_or_else(|| format!("Bot {:?} {}", kind, self.actors.count()));
+        let mut bot = match Bot::new(kind, &mut engine.resource_manager.lock().unwrap(), scene, position, self.sender.as_ref().unwrap().clone()) {
+            Ok(bot) => bot,
+            Err(_) => {
+                println!("Unable to create bot {:?}, some of its assets are missing!", kind);
+                return Handle::NONE;
+            }
+        };
+        let name = name.unwrap_or_else(|| self.pick_bot_name());
+        bot.set_team(self.assign_team(&name));
+        let palette = palette::palette(self.color_blind_mode);
+        let color = palette.team_color(bot.team).unwrap_or_else(|| palette.bot_color(&name));
+        bot.set_color(&mut scene.graph, color);
+        bot.name = name.clone();
+        bot.set_difficulty(self.options.bot_difficulty());
+        // There is nobody else to fight when match is observed.
+        bot.set_fights_other_bots(self.options.bots_fight_each_other() || self.observer);
+        bot.set_sight_range(self.options.bot_sight_range());
+        bot.set_clean_language(self.content_filter.clean_language);
+        bot.set_spawn_protection(self.options.spawn_protection());
+        if let Some(factory) = self.bot_behavior_factory.as_ref() {
+            let behavior = factory(&bot);
+            bot.set_behavior(behavior);
+        }
+        bot.on_spawn();
         self.leader_board.get_or_add_actor(&name);
+        bot.play_voice(VoiceEvent::Spawn, position);
         let bot = self.actors.add(Actor::Bot(bot));
-        self.give_new_weapon(engine, bot, WeaponKind::Ak47);
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::ActorSpawned { actor: bot })
+            .unwrap();
+        for kind in self.options.bot_weapons().pick_weapons() {
+            self.give_new_weapon(engine, bot, kind, true, None);
+        }
         bot
     }
 
+    /// Returns first name from the pool that is not used by anyone in current match.
+    fn pick_bot_name(&self) -> String {
+        let names = if self.content_filter.clean_language {
+            &CLEAN_BOT_NAMES
+        } else {
+            &BOT_NAMES
+        };
+        names.iter()
+            .find(|name| !self.leader_board.values().contains_key(**name))
+            .map(|name| (*name).to_owned())
+            .unwrap_or_else(|| format!("Bot {}", self.actors.count()))
+    }
+
     fn remove_actor(&mut self, engine: &mut GameEngine, actor: Handle<Actor>) {
         if self.actors.contains(actor) {
             let scene = &mut engine.scenes[self.scene];
             let character = self.actors.get(actor);
 
-            // Make sure to remove weapons and drop appropriate items (items will be temporary).
-            let drop_position = character.position(&scene.physics);
+            // Weapon was already dropped on death if weapon drop is enabled.
             let weapons = character.weapons().iter().copied().collect::<Vec<Handle<Weapon>>>();
             for weapon in weapons {
-                let item_kind = match self.weapons[weapon].get_kind() {
-                    WeaponKind::M4 => ItemKind::M4,
-                    WeaponKind::Ak47 => ItemKind::Ak47,
-                    WeaponKind::PlasmaRifle => ItemKind::PlasmaGun,
-                    WeaponKind::RocketLauncher => ItemKind::RocketLauncher,
-                };
-                self.spawn_item(engine, item_kind, drop_position, true, Some(20.0));
                 self.remove_weapon(engine, weapon);
             }
 
@@ -512,40 +1450,96 @@ impl Level {
             if self.player == actor {
                 self.player = Handle::NONE;
             }
+            for (_, remote_actor) in self.remote_players.iter_mut() {
+                if *remote_actor == actor {
+                    *remote_actor = Handle::NONE;
+                }
+            }
         }
     }
 
+    /// Turns on spectator camera above first spawn point.
+    fn begin_observing(&mut self, engine: &mut GameEngine) {
+        let position = self.spawn_points
+            .first()
+            .map_or(Vec3::ZERO, |pt| pt.position) + Vec3::new(0.0, OBSERVER_HEIGHT, 0.0);
+        self.spectator.begin_observing(&mut engine.scenes[self.scene], position);
+    }
+
+    pub fn is_observer(&self) -> bool {
+        self.observer
+    }
+
     fn spawn_player(&mut self, engine: &mut GameEngine) -> Handle<Actor> {
+        self.spectator.end(&mut engine.scenes[self.scene]);
+        self.player = self.add_player(engine, None);
+        self.player
+    }
+
+    /// Spawns player of network client, nothing happens if client has left already.
+    fn spawn_remote_player(&mut self, engine: &mut GameEngine, client: ClientId) {
+        if self.remote_players.iter().any(|(remote_client, _)| *remote_client == client) {
+            let actor = self.add_player(engine, Some(client));
+            for (remote_client, remote_actor) in self.remote_players.iter_mut() {
+                if *remote_client == client {
+                    *remote_actor = actor;
+                }
+            }
+        }
+    }
+
+    /// Adds player at suitable spawn point and gives it weapons from loadout. Player is
+    /// controlled over network if client is given.
+    fn add_player(&mut self, engine: &mut GameEngine, client: Option<ClientId>) -> Handle<Actor> {
         let index = self.find_suitable_spawn_point(engine);
         let spawn_position = self.spawn_points
             .get(index)
             .map_or(Vec3::ZERO, |pt| pt.position);
         let scene = &mut engine.scenes[self.scene];
-        if let Node::Camera(spectator_camera) = &mut scene.graph[self.spectator_camera] {
-            spectator_camera.set_enabled(false);
-        }
-        let mut player = Player::new(scene, self.sender.as_ref().unwrap().clone());
+        let mut player = Player::new(&mut engine.resource_manager.lock().unwrap(), scene, self.sender.as_ref().unwrap().clone());
         if let Some(control_scheme) = self.control_scheme.as_ref() {
             player.set_control_scheme(control_scheme.clone());
         }
-        self.player = self.actors.add(Actor::Player(player));
-        self.actors
-            .get_mut(self.player)
-            .set_position(&mut scene.physics, spawn_position);
+        if let Some(client) = client {
+            player.set_remote(scene);
+            player.name = remote_player_name(client);
+            self.leader_board.get_or_add_actor(&player.name);
+        }
+        player.set_team(self.assign_team(&player.name));
+        let handle = self.actors.add(Actor::Player(player));
+        let player = self.actors.get_mut(handle);
+        player.set_position(&mut scene.physics, spawn_position);
+        player.set_spawn_protection(self.options.spawn_protection());
+        if let Actor::Player(player) = player {
+            player.set_health_regen(self.options.health_regen());
+        }
 
-        self.give_new_weapon(engine, self.player, WeaponKind::M4);
-        self.give_new_weapon(engine, self.player, WeaponKind::Ak47);
-        self.give_new_weapon(engine, self.player, WeaponKind::PlasmaRifle);
-        self.give_new_weapon(engine, self.player, WeaponKind::RocketLauncher);
+        // Player must have at least something to shoot with, even if loadout is empty.
+        let weapons = if self.loadout.weapons.is_empty() {
+            vec![WeaponKind::M4]
+        } else {
+            self.loadout.weapons.clone()
+        };
+        for kind in weapons {
+            let ammo = (Weapon::get_definition(kind).ammo as f32 * self.loadout.ammo_multiplier) as u32;
+            self.give_new_weapon(engine, handle, kind, true, Some(ammo));
+        }
 
-        self.player
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::ActorSpawned { actor: handle })
+            .unwrap();
+
+        handle
     }
 
-    fn give_item(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, kind: ItemKind) {
+    /// Gives item to actor, `ammo` overrides amount of ammo that weapons give.
+    fn give_item(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, kind: ItemKind, ammo: Option<u32>) {
         if self.actors.contains(actor) {
             let character = self.actors.get_mut(actor);
             match kind {
-                ItemKind::Medkit => character.heal(20.0),
+                ItemKind::Medkit => character.heal(Item::get_definition(kind).amount),
                 ItemKind::Ak47 | ItemKind::PlasmaGun | ItemKind::M4 | ItemKind::RocketLauncher => {
                     let weapon_kind = match kind {
                         ItemKind::Ak47 => WeaponKind::Ak47,
@@ -555,34 +1549,36 @@ impl Level {
                         _ => unreachable!()
                     };
 
+                    let amount = ammo.unwrap_or(Item::get_definition(kind).amount as u32);
                     let mut found = false;
                     for weapon_handle in character.weapons() {
                         let weapon = &mut self.weapons[*weapon_handle];
-                        // If actor already has weapon of given kind, then just add ammo to it.
+                        // If actor already has weapon of given kind, then it only refills shared
+                        // ammo of its type.
                         if weapon.get_kind() == weapon_kind {
                             found = true;
-                            weapon.add_ammo(200);
+                            weapon.add_ammo(amount);
+                            weapon.refill_alt_ammo();
                             break;
                         }
                     }
                     // Finally if actor does not have such weapon, give new one to him.
                     if !found {
-                        self.give_new_weapon(engine, actor, weapon_kind);
+                        let select = self.switch_to_picked_up_weapon(actor, weapon_kind);
+                        self.give_new_weapon(engine, actor, weapon_kind, select, ammo);
                     }
                 }
                 ItemKind::Plasma | ItemKind::Ak47Ammo | ItemKind::M4Ammo => {
-                    for weapon in character.weapons() {
-                        let weapon = &mut self.weapons[*weapon];
-                        let (weapon_kind, ammo) = match kind {
-                            ItemKind::Plasma => (WeaponKind::PlasmaRifle, 200),
-                            ItemKind::Ak47Ammo => (WeaponKind::Ak47, 200),
-                            ItemKind::M4Ammo => (WeaponKind::M4, 200),
-                            _ => continue,
-                        };
-                        if weapon.get_kind() == weapon_kind {
-                            weapon.add_ammo(ammo);
-                            break;
-                        }
+                    // Ammo goes to shared store, so every weapon of its type can use it. Actor
+                    // without such weapon leaves ammo on the ground, same as before.
+                    if let Some(ammo_type) = item_ammo_type(kind) {
+                        let weapon_ammo_types = character.weapons()
+                            .iter()
+                            .map(|weapon| self.weapons[*weapon].ammo_type())
+                            .collect::<Vec<AmmoType>>();
+                        character.ammo_store
+                            .borrow_mut()
+                            .pick_up(ammo_type, Item::get_definition(kind).amount as u32, &weapon_ammo_types);
                     }
                 }
             }
@@ -604,14 +1600,208 @@ impl Level {
             let position = item.position(&scene.graph);
             item.pick_up();
             let kind = item.get_kind();
+            let ammo = item.ammo();
             self.sender.as_ref().unwrap().send(Message::PlaySound {
                 path: PathBuf::from("data/sounds/item_pickup.ogg"),
                 position,
                 gain: 1.0,
                 rolloff_factor: 3.0,
                 radius: 2.0,
+                priority: SoundPriority::Normal,
             }).unwrap();
-            self.give_item(engine, actor, kind);
+            self.give_item(engine, actor, kind, ammo);
+        }
+    }
+
+    /// Returns usable object that player focuses on - closest object that player stands next
+    /// to or looks at and sees. Items are usable only when manual pickup is enabled.
+    pub fn find_usable(&self, engine: &GameEngine, player: Handle<Actor>) -> Option<UseTarget> {
+        // Grenade that player holds is always focused, so the next use throws it.
+        if let Some(grenade) = self.projectiles.held_by(player) {
+            return Some(UseTarget::Grenade(grenade));
+        }
+        let player = match self.actors.get(player) {
+            Actor::Player(player) => player,
+            Actor::Bot(_) => return None,
+        };
+        if player.is_dead() {
+            return None;
+        }
+        let scene = &engine.scenes[self.scene];
+        let position = player.position(&scene.physics);
+        let eye = player.head_position();
+        let look = player.look_direction();
+
+        let mut candidates: Vec<(UseTarget, &dyn Usable)> = Vec::new();
+        if player.is_manual_pickup() {
+            for (handle, item) in self.items.pair_iter() {
+                candidates.push((UseTarget::Item(handle), item));
+            }
+        }
+        for (handle, door) in self.doors.pair_iter() {
+            candidates.push((UseTarget::Door(handle), door));
+        }
+        for (handle, projectile) in self.projectiles.pair_iter() {
+            candidates.push((UseTarget::Grenade(handle), projectile));
+        }
+
+        candidates.into_iter()
+            .filter(|(_, usable)| usable.can_be_used())
+            .filter_map(|(target, usable)| {
+                let object_position = usable.use_position(&scene.graph);
+                usable::focus_score(position, eye, look, object_position)
+                    .filter(|_| cast_ray(&scene.physics, eye, object_position - eye, true)
+                        .map_or(true, |hit| hit.position.distance(&object_position) < usable::HIT_TOLERANCE))
+                    .map(|score| (target, score))
+            })
+            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(target, _)| target)
+    }
+
+    /// Returns action of usable object for HUD prompt, for example "pick up Medkit".
+    pub fn use_action(&self, target: UseTarget) -> String {
+        match target {
+            UseTarget::Item(item) => self.items.get(item).use_action(),
+            UseTarget::Door(door) => self.doors.get(door).use_action(),
+            UseTarget::Grenade(grenade) => self.projectiles.get(grenade).use_action(),
+        }
+    }
+
+    /// Dispatches use of object by given actor.
+    fn use_object(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, target: UseTarget, dt: f32) {
+        match target {
+            UseTarget::Item(item) => {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::PickUpItem { actor, item })
+                    .unwrap();
+            }
+            UseTarget::Door(door) => {
+                let position = self.actors.get(actor).position(&engine.scenes[self.scene].physics);
+                self.doors.get_mut(door).toggle(position);
+            }
+            UseTarget::Grenade(grenade) => {
+                let projectile = self.projectiles.get_mut(grenade);
+                if projectile.holder().is_none() {
+                    projectile.pick_up(actor);
+                } else if projectile.holder() == actor && projectile.is_in_hand() {
+                    let look = match self.actors.get(actor) {
+                        Actor::Player(player) => player.look_direction(),
+                        Actor::Bot(bot) => bot.view(&engine.scenes[self.scene]).1,
+                    };
+                    let velocity = (look + Vec3::new(0.0, 0.2, 0.0)).scale(GRENADE_THROW_BACK_SPEED * dt);
+                    self.throw_grenade(engine, actor, grenade, velocity);
+                }
+            }
+        }
+    }
+
+    /// Throws grenade that actor holds. Grenade is credited to current weapon of the actor
+    /// from now on, so kills of thrown back grenade go to the one who threw it back.
+    fn throw_grenade(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, grenade: Handle<Projectile>, velocity: Vec3) {
+        let weapon = self.actors.get(actor).current_weapon();
+        let scene = &mut engine.scenes[self.scene];
+        self.projectiles.get_mut(grenade).throw(&mut scene.physics, velocity, weapon);
+    }
+
+    /// Keeps picked up grenades in hands of their holders, grenade of holder that died falls
+    /// where it is.
+    fn update_held_grenades(&mut self, scene: &mut Scene, dt: f32) {
+        let actors = &self.actors;
+        for projectile in self.projectiles.iter_mut() {
+            let holder = projectile.holder();
+            if holder.is_none() {
+                continue;
+            }
+            if !actors.contains(holder) || actors.get(holder).is_dead() {
+                projectile.drop();
+                continue;
+            }
+            let (eye, look) = match actors.get(holder) {
+                Actor::Player(player) => (player.head_position(), player.look_direction()),
+                Actor::Bot(bot) => bot.view(scene),
+            };
+            projectile.hold_at(&mut scene.physics, eye + look.scale(GRENADE_HOLD_DISTANCE), dt);
+        }
+    }
+
+    /// Returns actor who threw given projectile last, Handle::NONE if it is unknown.
+    fn grenade_thrower(&self, projectile: &Projectile) -> Handle<Actor> {
+        if self.weapons.contains(projectile.owner) {
+            self.weapons[projectile.owner].owner()
+        } else {
+            Handle::NONE
+        }
+    }
+
+    /// Bots that are good enough pick up enemy grenades that land next to them if there is
+    /// time left, and throw them back at whoever threw them.
+    fn update_bot_grenade_returns(&mut self, engine: &mut GameEngine, dt: f32) {
+        let mut pickups = Vec::new();
+        let mut throws = Vec::new();
+        {
+            let scene = &engine.scenes[self.scene];
+            for (handle, actor) in self.actors.pair_iter() {
+                let bot = match actor {
+                    Actor::Bot(bot) if !bot.is_dead() && bot.difficulty().throws_back_grenades() => bot,
+                    _ => continue,
+                };
+                let position = bot.position(&scene.physics);
+                if let Some(grenade) = self.projectiles.held_by(handle) {
+                    let projectile = self.projectiles.get(grenade);
+                    if projectile.is_in_hand() {
+                        let from = projectile.get_position(&scene.graph);
+                        let thrower = self.grenade_thrower(projectile);
+                        let to = if thrower != handle && self.actors.contains(thrower) && !self.actors.get(thrower).is_dead() {
+                            self.actors.get(thrower).position(&scene.physics)
+                        } else {
+                            // Nobody to return grenade to, so it is thrown away ahead.
+                            from + bot.view(scene).1.scale(GRENADE_THROW_SPEED)
+                        };
+                        let flight_time = (from.distance(&to) / GRENADE_THROW_SPEED).max(0.5);
+                        throws.push((handle, grenade, throw_velocity(from, to, flight_time, dt)));
+                    }
+                    continue;
+                }
+                let team = bot.team();
+                let grenade = self.projectiles
+                    .pair_iter()
+                    .filter(|(_, projectile)| projectile.can_be_used() &&
+                        projectile.definition().explosion_radius() > 0.0 &&
+                        projectile.fuse_time() > GRENADE_PICKUP_TIME + BOT_GRENADE_FUSE_MARGIN &&
+                        projectile.get_position(&scene.graph).distance(&position) <= BOT_GRENADE_REACH)
+                    .find(|(_, projectile)| {
+                        // Own grenades and grenades of teammates are left alone.
+                        let thrower = self.grenade_thrower(projectile);
+                        thrower != handle && (team == Team::None || !self.actors.contains(thrower) || self.actors.get(thrower).team() != team)
+                    })
+                    .map(|(grenade, _)| grenade);
+                if let Some(grenade) = grenade {
+                    pickups.push((handle, grenade));
+                }
+            }
+        }
+        for (bot, grenade) in pickups {
+            self.projectiles.get_mut(grenade).pick_up(bot);
+        }
+        for (bot, grenade, velocity) in throws {
+            self.throw_grenade(engine, bot, grenade, velocity);
+        }
+    }
+
+    fn update_use(&mut self, engine: &mut GameEngine, dt: f32) {
+        if self.player.is_none() {
+            return;
+        }
+        let use_requested = match self.actors.get_mut(self.player) {
+            Actor::Player(player) => player.take_use_request(),
+            Actor::Bot(_) => false,
+        };
+        if use_requested {
+            if let Some(target) = self.find_usable(engine, self.player) {
+                self.use_object(engine, self.player, target, dt);
+            }
         }
     }
 
@@ -637,7 +1827,7 @@ impl Level {
             self.sender.as_ref().unwrap().clone(),
             basis
         );
-        self.projectiles.add(projectile);
+        self.projectiles.add(scene, projectile);
     }
 
     fn shoot_weapon(&mut self,
@@ -646,24 +1836,733 @@ impl Level {
                     initial_velocity: Vec3,
                     time: GameTime,
                     direction: Option<Vec3>,
-    ) {
+    ) -> bool {
         if self.weapons.contains(weapon_handle) {
             let scene = &mut engine.scenes[self.scene];
             let weapon = &mut self.weapons[weapon_handle];
-            if weapon.try_shoot(scene, time) {
+            // Bots always reload automatically, player can turn it off in controls.
+            let auto_reload = weapon.owner() != self.player || self.control_scheme
+                .as_ref()
+                .map_or(true, |control_scheme| control_scheme.borrow().auto_reload);
+            let result = weapon.try_shoot(scene, time, auto_reload);
+            if result == ShotResult::DryFire && weapon.owner() == self.player && weapon.ammo() == 0 {
+                self.auto_switch_empty_weapon();
+            } else if result == ShotResult::Fired {
                 let kind = weapon.definition.projectile;
-                let position = weapon.get_shot_position(&scene.graph);
-                let direction = direction.unwrap_or_else(|| weapon.get_shot_direction(&scene.graph))
-                    .normalized()
-                    .unwrap_or_else(|| Vec3::LOOK);
-                let basis = weapon.world_basis(&scene.graph);
-                self.create_projectile(engine, kind, position, direction, initial_velocity, weapon_handle, basis);
+                self.fire_projectile(engine, weapon_handle, kind, initial_velocity, time, direction);
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Creates projectile of a shot that weapon has just made, either by primary or by alt
+    /// fire, and does everything else that a shot causes.
+    fn fire_projectile(&mut self,
+                       engine: &mut GameEngine,
+                       weapon_handle: Handle<Weapon>,
+                       kind: ProjectileKind,
+                       initial_velocity: Vec3,
+                       time: GameTime,
+                       direction: Option<Vec3>,
+    ) {
+        let scene = &engine.scenes[self.scene];
+        let weapon = &self.weapons[weapon_handle];
+        // Actor that started shooting is not protected anymore.
+        if self.actors.contains(weapon.owner()) {
+            let actor = self.actors.get_mut(weapon.owner());
+            actor.end_spawn_protection();
+            self.leader_board.add_shot(&actor.name);
+        }
+        if weapon.owner() == self.player && weapon.definition.shot_shake > 0.0 {
+            if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                player.add_shake(weapon.definition.shot_shake);
+            }
+        }
+        if weapon.owner() == self.player {
+            let weapon = &mut self.weapons[weapon_handle];
+            if let (Some(kick), Some(recoil)) = (weapon.next_recoil(), weapon.definition.recoil.as_ref()) {
+                if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                    player.add_recoil(kick, recoil);
+                }
+            }
+        }
+        let weapon = &self.weapons[weapon_handle];
+        let position = weapon.get_shot_position(&scene.graph);
+        let direction = direction.unwrap_or_else(|| weapon.get_shot_direction(&scene.graph))
+            .normalized()
+            .unwrap_or_else(|| Vec3::LOOK);
+        let basis = weapon.world_basis(&scene.graph);
+        let owner = weapon.owner();
+        if self.shot_debug.is_enabled() {
+            self.shot_debug.add_shot(position, direction, owner == self.player);
+        }
+        self.create_projectile(engine, kind, position, direction, initial_velocity, weapon_handle, basis);
+        // Shots are loud, bots that do not see shooter turn to the sound.
+        for listener in self.overlap_sphere(engine, position, HEARING_RANGE) {
+            if listener != owner {
+                if let Actor::Bot(bot) = self.actors.get_mut(listener) {
+                    bot.hear_noise(position, time);
+                }
+            }
+        }
+    }
+
+    fn alt_fire_weapon(&mut self, engine: &mut GameEngine, weapon_handle: Handle<Weapon>, initial_velocity: Vec3, time: GameTime) {
+        if !self.weapons.contains(weapon_handle) {
+            return;
+        }
+        match self.weapons[weapon_handle].try_alt_fire(&engine.scenes[self.scene], time) {
+            Some(AltFire::Launcher { projectile, .. }) => {
+                self.fire_projectile(engine, weapon_handle, projectile, initial_velocity, time, None);
+            }
+            Some(AltFire::Bash { damage, range, .. }) => {
+                self.bash(engine, weapon_handle, damage, range);
+            }
+            // Rounds of burst are fired by level update.
+            Some(AltFire::Burst { .. }) | None => (),
+        }
+    }
+
+    /// Lets enemy bots hear footstep of given actor, teammates do not care about each other.
+    fn hear_footstep(&mut self, engine: &GameEngine, actor: Handle<Actor>, position: Vec3, loudness: f32, time: GameTime) {
+        if !self.actors.contains(actor) {
+            return;
+        }
+        let team = self.actors.get(actor).team();
+        let physics = &engine.scenes[self.scene].physics;
+        for (handle, listener) in self.actors.pair_iter_mut() {
+            if handle != actor && (team == Team::None || listener.team() != team) {
+                if let Actor::Bot(bot) = listener {
+                    let distance = bot.position(physics).distance(&position);
+                    bot.hear_footstep(position, distance, loudness, time);
+                }
+            }
+        }
+    }
+
+    /// Damages closest living actor in front of weapon within `range`.
+    fn bash(&mut self, engine: &GameEngine, weapon_handle: Handle<Weapon>, damage: f32, range: f32) {
+        let scene = &engine.scenes[self.scene];
+        let weapon = &self.weapons[weapon_handle];
+        let owner = weapon.owner();
+        let position = weapon.get_shot_position(&scene.graph);
+        let look = weapon.get_shot_direction(&scene.graph).normalized().unwrap_or(Vec3::LOOK);
+        let victim = self.actors
+            .pair_iter()
+            .filter(|(handle, actor)| *handle != owner && !actor.is_dead())
+            .map(|(handle, actor)| (handle, actor.position(&scene.physics) - position))
+            .filter(|(_, offset)| offset.len() <= range && offset.normalized().map_or(false, |dir| dir.dot(&look) >= BASH_COS))
+            .min_by(|(_, a), (_, b)| a.sqr_len().partial_cmp(&b.sqr_len()).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(handle, _)| handle);
+        if self.actors.contains(owner) {
+            self.actors.get_mut(owner).end_spawn_protection();
+        }
+        if let Some(victim) = victim {
+            self.sender.as_ref().unwrap().send(Message::DamageActor {
+                actor: victim,
+                who: owner,
+                amount: damage,
+                region: None,
+                kind: DamageKind::Melee,
+                position: None,
+            }).unwrap();
+        }
+    }
+
+    /// Adds player for network client that joined listen server.
+    pub fn add_remote_player(&mut self, engine: &mut GameEngine, client: ClientId) {
+        self.remote_players.push((client, Handle::NONE));
+        self.spawn_remote_player(engine, client);
+        self.fill_with_bots(engine);
+        self.balance_teams(engine);
+    }
+
+    /// Removes player of network client that left, its score stays on leader board.
+    pub fn remove_remote_player(&mut self, engine: &mut GameEngine, client: ClientId) {
+        if let Some(index) = self.remote_players.iter().position(|(remote_client, _)| *remote_client == client) {
+            let (_, actor) = self.remote_players.remove(index);
+            self.remove_actor(engine, actor);
+        }
+        self.respawn_list.retain(|entry| match entry {
+            RespawnEntry::Remote(v) => v.client != client,
+            _ => true,
+        });
+        self.team_balancer.remove(&remote_player_name(client));
+        self.fill_with_bots(engine);
+        self.balance_teams(engine);
+    }
+
+    /// Returns team for combatant with given name, combatant keeps its team between lives.
+    /// Everyone has no team outside of team modes.
+    fn assign_team(&mut self, name: &str) -> Team {
+        if self.options.is_team_mode() {
+            let combatants = self.combatants();
+            self.team_balancer.assign(name, &combatants)
+        } else {
+            Team::None
+        }
+    }
+
+    /// Returns everyone who takes part in the match, including ones that wait for respawn.
+    fn combatants(&self) -> Vec<Combatant> {
+        let skill = |name: &str| self.leader_board
+            .values()
+            .get(name)
+            .map_or(0.0, |score| team_balance::skill(score.kills, score.deaths));
+        let mut combatants = self.actors
+            .iter()
+            .map(|actor| Combatant {
+                name: actor.name.clone(),
+                bot: matches!(actor, Actor::Bot(_)),
+                alive: !actor.is_dead(),
+                skill: skill(&actor.name),
+            })
+            .collect::<Vec<_>>();
+        for entry in self.respawn_list.iter() {
+            let (name, bot) = match entry {
+                RespawnEntry::Bot(v) => (v.name.clone(), true),
+                RespawnEntry::Player(_) => (PLAYER_NAME.to_owned(), false),
+                RespawnEntry::Remote(v) => (remote_player_name(v.client), false),
+            };
+            combatants.push(Combatant {
+                skill: skill(&name),
+                name,
+                bot,
+                alive: false,
+            });
+        }
+        combatants
+    }
+
+    /// Moves combatants between teams if teams became uneven, see `team_balance` module.
+    fn balance_teams(&mut self, engine: &mut GameEngine) {
+        if !self.options.is_team_mode() {
+            return;
+        }
+        let combatants = self.combatants();
+        let palette = palette::palette(self.color_blind_mode);
+        for (name, team) in self.team_balancer.balance(&combatants) {
+            // Combatants that wait for respawn will spawn in new team.
+            if let Some(actor) = self.actors.iter_mut().find(|actor| actor.name == name) {
+                actor.set_team(team);
+                if let Actor::Bot(bot) = actor {
+                    if let Some(color) = palette.team_color(team) {
+                        bot.set_color(&mut engine.scenes[self.scene].graph, color);
+                    }
+                }
+            }
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::AddNotification {
+                    text: format!("{} moved to {} team to balance teams", name, team.name())
+                }).unwrap();
+        }
+    }
+
+    /// Turns balancing of teams on or off during the match, new combatants join smaller team
+    /// either way.
+    pub fn set_team_balance(&mut self, engine: &mut GameEngine, enabled: bool) {
+        self.team_balancer.set_enabled(enabled);
+        self.balance_teams(engine);
+    }
+
+    /// Keeps given amount of combatants by adding or removing bots as network players join
+    /// and leave, amount is clamped to [`BOT_FILL_RANGE`]. None leaves bots as they are.
+    pub fn set_bot_fill(&mut self, engine: &mut GameEngine, combatants: Option<u32>) {
+        self.bot_fill = combatants.map(|combatants| combatants.max(BOT_FILL_RANGE.0).min(BOT_FILL_RANGE.1));
+        self.fill_with_bots(engine);
+    }
+
+    /// Returns amount of bots in the match, including bots that wait for respawn.
+    fn bot_count(&self) -> usize {
+        let living = self.actors
+            .iter()
+            .filter(|actor| matches!(actor, Actor::Bot(_)))
+            .count();
+        let respawning = self.respawn_list
+            .iter()
+            .filter(|entry| matches!(entry, RespawnEntry::Bot(_)))
+            .count();
+        living + respawning
+    }
+
+    /// Removes one bot, bots that wait for respawn go first so nobody sees a bot vanish.
+    fn remove_bot(&mut self, engine: &mut GameEngine) {
+        let respawning = self.respawn_list
+            .iter()
+            .position(|entry| matches!(entry, RespawnEntry::Bot(_)));
+        let living = self.actors
+            .pair_iter()
+            .find(|(_, actor)| matches!(actor, Actor::Bot(_)))
+            .map(|(handle, _)| handle);
+        let name = match (respawning, living) {
+            (Some(index), _) => match self.respawn_list.remove(index) {
+                RespawnEntry::Bot(v) => v.name,
+                _ => unreachable!(),
+            },
+            (None, Some(bot)) => {
+                let name = self.actors.get(bot).name.clone();
+                self.remove_actor(engine, bot);
+                name
+            }
+            (None, None) => return,
+        };
+        self.team_balancer.remove(&name);
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::AddNotification {
+                text: format!("Bot {} left to make room for a player", name)
+            }).unwrap();
+    }
+
+    fn fill_with_bots(&mut self, engine: &mut GameEngine) {
+        let combatants = match self.bot_fill {
+            Some(combatants) => combatants as usize,
+            None => return,
+        };
+        // Players take their place even while they wait for respawn.
+        let players = 1 + self.remote_players.len();
+        let target = combatants.saturating_sub(players);
+        let mut bots = self.bot_count();
+        while bots > target {
+            self.remove_bot(engine);
+            bots -= 1;
+        }
+        // New bot would have all lives while everyone else may have lost some.
+        if self.options.lives().is_none() {
+            while bots < target {
+                self.spawn_bot(engine, BOT_FILL_KINDS[bots % BOT_FILL_KINDS.len()], None);
+                bots += 1;
+            }
+        }
+    }
+
+    pub fn set_remote_input(&mut self, client: ClientId, input: &PlayerInput) {
+        if let Some((_, actor)) = self.remote_players.iter().find(|(remote_client, _)| *remote_client == client) {
+            if self.actors.contains(*actor) {
+                if let Actor::Player(player) = self.actors.get_mut(*actor) {
+                    player.apply_input(input);
+                }
+            }
+        }
+    }
+
+    /// Returns actor of network client, None while client waits for respawn.
+    pub fn remote_player_actor(&self, client: ClientId) -> Option<NetId> {
+        self.remote_players
+            .iter()
+            .find(|(remote_client, _)| *remote_client == client)
+            .filter(|(_, actor)| actor.is_some())
+            .map(|(_, actor)| NetId::from(*actor))
+    }
+
+    /// Returns state of every actor, server sends it to clients every tick.
+    pub fn actor_states(&self, engine: &GameEngine) -> Vec<ActorState> {
+        let scene = &engine.scenes[self.scene];
+        self.actors
+            .pair_iter()
+            .map(|(handle, actor)| {
+                let (yaw, pitch) = match actor {
+                    Actor::Player(player) => player.look_angles(),
+                    Actor::Bot(_) => {
+                        let look = scene.graph[actor.pivot].look_vector();
+                        (look.x.atan2(look.z).to_degrees(), 0.0)
+                    }
+                };
+                ActorState {
+                    id: NetId::from(handle),
+                    position: actor.position(&scene.physics),
+                    yaw,
+                    pitch,
+                    health: actor.health,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns state of controls of player, None if player is dead.
+    pub fn player_input(&self) -> Option<PlayerInput> {
+        if self.player.is_some() {
+            if let Actor::Player(player) = self.actors.get(self.player) {
+                return Some(player.input());
+            }
+        }
+        None
+    }
+
+    /// Turns level into replica of a level on server: bots are removed and from now on
+    /// level only shows actors from snapshots, match itself goes on the server.
+    pub fn set_replica(&mut self, engine: &mut GameEngine) {
+        let others = self.actors
+            .pair_iter()
+            .map(|(handle, _)| handle)
+            .filter(|handle| *handle != self.player)
+            .collect::<Vec<_>>();
+        for actor in others {
+            // Removed without dropping weapons, server decides what lies on the floor.
+            let weapons = self.actors.get(actor).weapons().to_vec();
+            for weapon in weapons {
+                self.remove_weapon(engine, weapon);
+            }
+            self.actors
+                .get_mut(actor)
+                .clean_up(&mut engine.scenes[self.scene]);
+            self.actors.free(actor);
+        }
+        self.respawn_list.clear();
+        self.leader_board = Default::default();
+        self.replica = Some(Default::default());
+    }
+
+    /// Takes snapshot received from server. Actors that are not known yet are shown with
+    /// player body, actors that are missing in snapshot are removed. Other actors are moved
+    /// later in `update`, own player is corrected only if its prediction for acknowledged
+    /// input was wrong.
+    pub fn apply_snapshot(&mut self, engine: &mut GameEngine, time: GameTime, own_actor: Option<NetId>, acknowledged_input: Option<u32>, states: Vec<ActorState>) {
+        let mut replica = match self.replica.take() {
+            Some(replica) => replica,
+            None => return,
+        };
+
+        let gone = replica.actors
+            .iter()
+            .filter(|(id, _)| !states.iter().any(|state| state.id == **id) || Some(**id) == own_actor)
+            .map(|(id, actor)| (*id, *actor))
+            .collect::<Vec<_>>();
+        for (id, actor) in gone {
+            replica.actors.remove(&id);
+            if self.actors.contains(actor) {
+                self.actors
+                    .get_mut(actor)
+                    .clean_up(&mut engine.scenes[self.scene]);
+                self.actors.free(actor);
+            }
+        }
+
+        for state in states.iter() {
+            if Some(state.id) == own_actor {
+                let respawned = replica.own_actor != own_actor;
+                if respawned {
+                    // Player was respawned on server. Replica does not respawn anyone by
+                    // itself, so body of dead player is replaced with a new player here.
+                    if self.actors.contains(self.player) && self.actors.get(self.player).is_dead() {
+                        self.remove_actor(engine, self.player);
+                    }
+                    if !self.actors.contains(self.player) {
+                        self.spawn_player(engine);
+                    }
+                }
+                if !self.actors.contains(self.player) {
+                    continue;
+                }
+                let scene = &mut engine.scenes[self.scene];
+                let character = self.actors.get_mut(self.player);
+                if respawned {
+                    // Nothing predicted so far is valid.
+                    replica.predictions.clear();
+                    character.set_position(&mut scene.physics, state.position);
+                } else if let Some(acknowledged) = acknowledged_input {
+                    if let Some(correction) = replica.predictions.reconcile(acknowledged, state.position) {
+                        let position = character.position(&scene.physics);
+                        character.set_position(&mut scene.physics, position + correction);
+                    }
+                }
+                character.health = state.health;
+            } else if !replica.actors.contains_key(&state.id) {
+                let scene = &mut engine.scenes[self.scene];
+                let mut player = Player::new(&mut engine.resource_manager.lock().unwrap(), scene, self.sender.as_ref().unwrap().clone());
+                if let Some(control_scheme) = self.control_scheme.as_ref() {
+                    player.set_control_scheme(control_scheme.clone());
+                }
+                player.set_remote(scene);
+                let mut actor = Actor::Player(player);
+                actor.set_position(&mut scene.physics, state.position);
+                replica.actors.insert(state.id, self.actors.add(actor));
+            }
+        }
+
+        replica.own_actor = own_actor;
+        replica.snapshots.push(time.elapsed, states);
+        self.replica = Some(replica);
+    }
+
+    /// Remembers where own player ended up after input with given sequence was applied,
+    /// it is compared with position from server when server acknowledges the input.
+    pub fn record_prediction(&mut self, engine: &GameEngine, sequence: u32) {
+        if let Some(replica) = self.replica.as_mut() {
+            if replica.own_actor.is_some() && self.actors.contains(self.player) {
+                let scene = &engine.scenes[self.scene];
+                let position = self.actors.get(self.player).position(&scene.physics);
+                replica.predictions.record(sequence, position);
+            }
+        }
+    }
+
+    /// Shows other actors a bit in the past, between two snapshots received from server.
+    fn update_replica(&mut self, engine: &mut GameEngine, time: GameTime) {
+        if let Some(replica) = self.replica.as_ref() {
+            let scene = &mut engine.scenes[self.scene];
+            for state in replica.snapshots.sample(time.elapsed - prediction::INTERPOLATION_DELAY) {
+                if let Some(&actor) = replica.actors.get(&state.id) {
+                    if self.actors.contains(actor) {
+                        let character = self.actors.get_mut(actor);
+                        character.set_position(&mut scene.physics, state.position);
+                        character.health = state.health;
+                        if let Actor::Player(player) = character {
+                            player.set_look(state.yaw, state.pitch);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sets artificial delay of player shots in seconds, None turns delay off. Delay is
+    /// limited by length of position history, so every delayed shot can be rewound.
+    pub fn set_input_delay(&mut self, delay: Option<f32>) {
+        self.input_delay = delay.map(|delay| delay.min(lag_compensation::HISTORY_DURATION));
+        if self.input_delay.is_none() && !self.killcam {
+            self.history.clear();
+        }
+    }
+
+    pub fn set_path_budget(&mut self, budget: u32) {
+        self.path_budget = budget.max(1);
+    }
+
+    /// Returns amount of bots that rebuilt their paths on last frame.
+    pub fn path_rebuilds(&self) -> u32 {
+        self.path_rebuilds
+    }
+
+    /// Lets bots that need new paths rebuild them, but no more than budget allows. Bots
+    /// that did not get their turn wait for next frame, and ones that waited longest go first.
+    fn schedule_path_rebuilds(&mut self, elapsed: f64) {
+        let mut candidates = self.actors
+            .pair_iter()
+            .filter_map(|(handle, actor)| match actor {
+                Actor::Bot(bot) if bot.wants_path_rebuild(elapsed) => Some((handle, bot.last_path_rebuild_time())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        candidates.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        candidates.truncate(self.path_budget as usize);
+        self.path_rebuilds = candidates.len() as u32;
+        for (handle, _) in candidates {
+            if let Actor::Bot(bot) = self.actors.get_mut(handle) {
+                bot.allow_path_rebuild();
+            }
+        }
+    }
+
+    pub fn set_weapon_drop(&mut self, enabled: bool) {
+        self.weapon_drop = enabled;
+    }
+
+    pub fn set_killcam(&mut self, enabled: bool) {
+        self.killcam = enabled;
+        if !enabled && self.input_delay.is_none() {
+            self.history.clear();
+        }
+    }
+
+    fn record_history(&mut self, engine: &GameEngine) {
+        let physics = &engine.scenes[self.scene].physics;
+        let positions = self.actors
+            .pair_iter()
+            .map(|(handle, actor)| (handle, actor.position(physics)))
+            .collect::<Vec<_>>();
+        self.history.record(self.time, positions);
+    }
+
+    /// Returns frames of killcam that replays death of given victim from viewpoint of killer.
+    fn killcam_frames(&self, victim: Handle<Actor>, killer: Handle<Actor>) -> Vec<KillcamFrame> {
+        let eye = Vec3::new(0.0, KILLCAM_EYE_HEIGHT, 0.0);
+        let since = self.time - lag_compensation::HISTORY_DURATION;
+        let victim_path = self.history.path_of(victim, since);
+        self.history
+            .path_of(killer, since)
+            .into_iter()
+            .filter_map(|(time, position)| victim_path
+                .iter()
+                .find(|(victim_time, _)| *victim_time == time)
+                .map(|&(_, victim_position)| (time, position + eye, victim_position + eye)))
+            .collect()
+    }
+
+    fn delay_shot(&mut self, engine: &GameEngine, weapon: Handle<Weapon>, initial_velocity: Vec3, direction: Option<Vec3>) {
+        let graph = &engine.scenes[self.scene].graph;
+        let origin = self.weapons[weapon].get_shot_position(graph);
+        let aim = direction.unwrap_or_else(|| self.weapons[weapon].get_shot_direction(graph));
+        self.delayed_shots.push(DelayedShot {
+            weapon,
+            initial_velocity,
+            direction,
+            fire_time: self.time,
+            origin,
+            aim,
+            time_left: self.input_delay.unwrap_or_default(),
+        });
+    }
+
+    /// Resolves delayed shots which delay has passed. Hit of every fired shot is checked
+    /// against positions at fire time and at resolve time, so it can be seen whether rewinding
+    /// registers a hit that delayed input would miss.
+    fn update_lag_compensation(&mut self, engine: &mut GameEngine, time: GameTime) {
+        let physics = &engine.scenes[self.scene].physics;
+        let positions = self.actors
+            .pair_iter()
+            .map(|(handle, actor)| (handle, actor.position(physics)))
+            .collect::<Vec<_>>();
+
+        for shot in self.delayed_shots.iter_mut() {
+            shot.time_left -= time.delta;
+        }
+        let (ready, waiting): (Vec<DelayedShot>, Vec<DelayedShot>) = std::mem::take(&mut self.delayed_shots)
+            .into_iter()
+            .partition(|shot| shot.time_left <= 0.0);
+        self.delayed_shots = waiting;
+
+        for shot in ready {
+            if !self.shoot_weapon(engine, shot.weapon, shot.initial_velocity, time, shot.direction) {
+                continue;
+            }
+            let shooter = self.weapons[shot.weapon].owner();
+            // Actor could be removed while shot was waiting, rewound positions still have it.
+            let actor_name = |hit: Option<Handle<Actor>>| {
+                hit.filter(|&actor| self.actors.contains(actor))
+                    .map(|actor| self.actors.get(actor).name.clone())
+            };
+            let rewound_hit = actor_name(self.history
+                .rewind_to(shot.fire_time)
+                .and_then(|rewound| lag_compensation::ray_hit(&rewound, shot.origin, shot.aim, shooter)));
+            let current_hit = actor_name(lag_compensation::ray_hit(&positions, shot.origin, shot.aim, shooter));
+            let delay = self.time - shot.fire_time;
+            if let Some(match_log) = self.match_log.as_mut() {
+                match_log.log_delayed_shot(self.time, delay, rewound_hit.as_deref(), current_hit.as_deref());
+            }
+            // Only shots where rewinding changes the outcome are worth attention.
+            if rewound_hit != current_hit {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::AddNotification {
+                        text: format!("Delayed shot ({:.0} ms): rewound hit {}, current hit {}",
+                                      delay * 1000.0,
+                                      rewound_hit.as_deref().unwrap_or("nothing"),
+                                      current_hit.as_deref().unwrap_or("nothing"))
+                    }).unwrap();
+            }
+        }
+    }
+
+    /// Blinds everyone who looks at detonated flashbang. Player gets white screen and muffled
+    /// sound, bots can't see targets for a while. Owner knows when flashbang goes off and
+    /// turns away.
+    fn flash(&mut self, engine: &GameEngine, position: Vec3, owner: Handle<Actor>) {
+        let scene = &engine.scenes[self.scene];
+        let position = position + Vec3::new(0.0, FLASH_HEIGHT, 0.0);
+        for (handle, actor) in self.actors.pair_iter_mut() {
+            if handle == owner {
+                continue;
+            }
+            let (eye, look) = match actor {
+                Actor::Player(player) => (player.head_position(), player.look_direction()),
+                Actor::Bot(bot) => bot.view(scene),
+            };
+            let strength = flash_strength(&scene.physics, &self.smoke, eye, look, position);
+            if strength <= 0.0 {
+                continue;
+            }
+            match actor {
+                Actor::Player(_) => {
+                    if handle == self.player {
+                        self.sender
+                            .as_ref()
+                            .unwrap()
+                            .send(Message::FlashPlayer { strength })
+                            .unwrap();
+                    }
+                }
+                Actor::Bot(bot) => bot.blind(FLASH_BOT_BLIND_TIME * strength),
+            }
+        }
+    }
+
+    /// Tells whether actor switches to weapon of given kind that was just picked up. Only
+    /// local player can choose it in controls, everyone else always switches.
+    fn switch_to_picked_up_weapon(&self, actor: Handle<Actor>, kind: WeaponKind) -> bool {
+        if actor != self.player {
+            return true;
+        }
+        let mode = self.control_scheme
+            .as_ref()
+            .map_or(PickupSwitch::Always, |control_scheme| control_scheme.borrow().pickup_switch);
+        match mode {
+            PickupSwitch::Always => true,
+            PickupSwitch::Never => false,
+            PickupSwitch::IfBetter => {
+                let current = self.actors.get(actor).current_weapon();
+                current.is_none() || kind.rank() > self.weapons[current].get_kind().rank()
+            }
+        }
+    }
+
+    /// Switches player to next weapon that has ammo, if player has enabled it in controls.
+    fn auto_switch_empty_weapon(&mut self) {
+        let enabled = self.control_scheme
+            .as_ref()
+            .map_or(false, |control_scheme| control_scheme.borrow().auto_switch_weapon);
+        if enabled && self.actors.contains(self.player) {
+            let player = self.actors.get_mut(self.player);
+            let count = player.weapons.len();
+            let current = player.current_weapon as usize;
+            let next = (1..count)
+                .map(|offset| (current + offset) % count)
+                .find(|&i| self.weapons[player.weapons[i]].ammo() > 0);
+            if let Some(next) = next {
+                player.set_current_weapon(next);
+            }
+        }
+    }
+
+    fn shake_camera(&mut self, engine: &GameEngine, position: Vec3, intensity: f32, radius: f32) {
+        if self.actors.contains(self.player) {
+            let scene = &engine.scenes[self.scene];
+            if let Actor::Player(player) = self.actors.get_mut(self.player) {
+                let distance = player.position(&scene.physics).distance(&position);
+                if distance < radius {
+                    player.add_shake(intensity * (1.0 - distance / radius));
+                }
             }
         }
     }
 
     fn show_weapon(&mut self, engine: &mut GameEngine, weapon_handle: Handle<Weapon>, state: bool) {
-        self.weapons[weapon_handle].set_visibility(state, &mut engine.scenes[self.scene].graph)
+        self.weapons[weapon_handle].set_visibility(state, &mut engine.scenes[self.scene].graph);
+        if state && self.reload_on_switch(weapon_handle) {
+            self.weapons[weapon_handle].reload();
+        }
+    }
+
+    /// Tells whether weapon that player just switched to should start reloading. Only local
+    /// player can enable it in controls, and reload is not started while trigger is held,
+    /// so it won't delay a shot that player is about to make.
+    fn reload_on_switch(&self, weapon_handle: Handle<Weapon>) -> bool {
+        let owner = self.weapons[weapon_handle].owner();
+        let enabled = self.control_scheme
+            .as_ref()
+            .map_or(false, |control_scheme| control_scheme.borrow().reload_on_switch);
+        if !enabled || owner != self.player || !self.actors.contains(owner) {
+            return false;
+        }
+        match self.actors.get(owner) {
+            Actor::Player(player) => !player.input().shoot,
+            Actor::Bot(_) => false,
+        }
     }
 
     fn find_suitable_spawn_point(&self, engine: &mut GameEngine) -> usize {
@@ -677,72 +2576,416 @@ impl Level {
                 let position = actor.position(&scene.physics);
                 sum_distance += pt.position.distance(&position);
             }
-            if sum_distance > max_distance {
-                max_distance = sum_distance;
-                index = i;
+            if sum_distance > max_distance {
+                max_distance = sum_distance;
+                index = i;
+            }
+        }
+        index
+    }
+
+    fn spawn_bot(&mut self, engine: &mut GameEngine, kind: BotKind, name: Option<String>) -> Handle<Actor> {
+        let index = self.find_suitable_spawn_point(engine);
+        let spawn_position = self.spawn_points
+            .get(index)
+            .map_or(Vec3::ZERO, |pt| pt.position);
+
+        let bot = self.add_bot(engine, kind, spawn_position, name);
+
+        if bot.is_some() {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::AddNotification {
+                    text: format!("Bot {} spawned!", self.actors.get(bot).name)
+                }).unwrap();
+        }
+
+        bot
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn damage_actor(&mut self, engine: &mut GameEngine, actor: Handle<Actor>, who: Handle<Actor>, amount: f32, region: Option<HitRegion>, kind: DamageKind, position: Option<Vec3>, time: GameTime) {
+        if self.actors.contains(actor) && (who.is_none() || who.is_some() && self.actors.contains(who)) {
+            // Every step of damage is recorded while shot debug is on.
+            let mut debug_record = if self.shot_debug.is_enabled() {
+                Some(self.hit_record(engine, actor, who, amount, region, kind, position))
+            } else {
+                None
+            };
+            if self.actors.get(actor).is_spawn_protected() {
+                if let Some(mut record) = debug_record {
+                    record.spawn_protected = true;
+                    self.shot_debug.add_hit(record);
+                }
+                return;
+            }
+            // Damage from own explosion is scaled by rules of rocket jumps, it has no
+            // attacker so suicide does not give a frag.
+            let (amount, who) = if who.is_some() && who == actor {
+                (self.rocket_jump.scale_self_damage(amount), Handle::NONE)
+            } else {
+                (amount, who)
+            };
+            if let Some(record) = debug_record.as_mut() {
+                record.after_self_damage = amount;
+            }
+            if amount <= 0.0 {
+                if let Some(record) = debug_record {
+                    self.shot_debug.add_hit(record);
+                }
+                return;
+            }
+            let region_multiplier = region.map_or(1.0, |region| self.hit_multipliers.get(region));
+            let amount = amount * region_multiplier;
+            let source = if who.is_none() {
+                DamageSource::Environment
+            } else if who == self.player {
+                DamageSource::Player
+            } else {
+                DamageSource::Bot
+            };
+            let amount = self.options.damage_multipliers().apply(amount, source, actor == self.player);
+            let amount = if self.options.auto_balance() {
+                self.balancer.apply(amount, source, actor != self.player)
+            } else {
+                amount
+            };
+            let who_position =
+                if who.is_some() {
+                    let scene = &engine.scenes[self.scene];
+                    Some(self.actors.get(who).position(&scene.physics))
+                } else {
+                    None
+                };
+            // Damage event does not tell which weapon was used, so weapon that attacker is
+            // holding at the moment is reported.
+            let weapon = if who.is_some() {
+                let current_weapon = self.actors.get(who).current_weapon();
+                if self.weapons.contains(current_weapon) {
+                    Some(self.weapons[current_weapon].get_kind())
+                } else {
+                    None
+                }
+            } else {
+                None
+            };
+            if let Some(mut record) = debug_record {
+                record.region_multiplier = region_multiplier;
+                record.applied = amount;
+                record.weapon = weapon;
+                self.shot_debug.add_hit(record);
+            }
+            let victim = actor;
+            if who.is_some() && who != victim {
+                self.last_attackers.insert(victim, (who, self.time));
+            }
+            let actor = self.actors.get_mut(actor);
+            if let Actor::Bot(bot) = actor {
+                bot.on_damaged(who, who_position, amount, time);
+            }
+            if let Actor::Player(player) = actor {
+                // Hits shake camera, almost full health damage gives strongest shake.
+                player.add_shake((amount / 100.0).min(1.0));
+                player.reset_regen();
+            }
+            let overkill = amount - actor.health;
+            // Everything else that depends on damage (notifications, scores, logs, etc.) reacts
+            // on events sent here, so it does not need to be done here.
+            let event = DamageEvent {
+                victim,
+                attacker: who,
+                amount,
+                kind,
+                weapon,
+                headshot: region == Some(HitRegion::Head),
+            };
+            let killed = apply_damage(actor, event, self.sender.as_ref().unwrap());
+
+            // Bots killed by huge damage from a weapon are torn apart instead of playing
+            // dying animation.
+            if killed && self.content_filter.gore && source != DamageSource::Environment && overkill >= GIB_OVERKILL_DAMAGE {
+                if let Actor::Bot(bot) = actor {
+                    let scene = &mut engine.scenes[self.scene];
+                    let position = bot.position(&scene.physics);
+                    bot.gib(scene);
+                    self.gibs.spawn_burst(scene, &mut engine.resource_manager.lock().unwrap(), position);
+                    self.effects.create(EffectKind::Blood, &mut scene.graph, &mut engine.resource_manager.lock().unwrap(), position);
+                }
+            } else if killed && self.corpse_limits.lifetime <= 0.0 {
+                // No corpses are left at all, so bot is removed right away same as torn
+                // apart one.
+                if let Actor::Bot(bot) = actor {
+                    bot.gib(&mut engine.scenes[self.scene]);
+                }
+            }
+        }
+    }
+
+    /// Starts debug record of a hit with damage that attacker dealt, `damage_actor` fills in
+    /// the rest of it.
+    fn hit_record(&self, engine: &GameEngine, actor: Handle<Actor>, who: Handle<Actor>, amount: f32, region: Option<HitRegion>, kind: DamageKind, position: Option<Vec3>) -> HitRecord {
+        let attacker = if who.is_some() {
+            self.actors.get(who).name.clone()
+        } else {
+            "Environment".to_owned()
+        };
+        let mut record = HitRecord::new(attacker, self.actors.get(actor).name.clone(), kind, amount);
+        record.region = region;
+        record.position = position;
+        record.player_involved = actor == self.player || who == self.player;
+        if kind == DamageKind::Explosion {
+            let victim_position = self.actors.get(actor).position(&engine.scenes[self.scene].physics);
+            record.explosion_distance = position.map(|center| victim_position.distance(&center));
+        }
+        record
+    }
+
+    fn on_actor_spawned(&mut self, actor: Handle<Actor>) {
+        if self.actors.contains(actor) {
+            if let Some(match_log) = self.match_log.as_mut() {
+                match_log.log_spawn(self.time, &self.actors.get(actor).name);
+            }
+        }
+    }
+
+    fn on_actor_damaged(&mut self, victim: Handle<Actor>, attacker: Handle<Actor>, amount: f32) {
+        if !self.actors.contains(victim) {
+            return;
+        }
+        let victim_name = self.actors.get(victim).name.clone();
+        let attacker_name = if attacker.is_some() && self.actors.contains(attacker) {
+            Some(self.actors.get(attacker).name.clone())
+        } else {
+            None
+        };
+
+        if let Some(attacker_name) = attacker_name.as_ref() {
+            if attacker != victim {
+                self.leader_board.add_hit(attacker_name, amount);
+            }
+        }
+
+        let text = match attacker_name.as_ref() {
+            Some(attacker_name) => format!("{} dealt {} damage to {}!", attacker_name, amount, victim_name),
+            None => format!("{} took {} damage!", victim_name, amount),
+        };
+        self.sender
+            .as_ref()
+            .unwrap()
+            .send(Message::AddNotification {
+                text,
+            }).unwrap();
+
+        if let Some(match_log) = self.match_log.as_mut() {
+            match_log.log_damage(self.time, &victim_name, attacker_name.as_deref(), amount);
+        }
+    }
+
+    fn on_actor_killed(&mut self, engine: &GameEngine, victim: Handle<Actor>, killer: Handle<Actor>, weapon: Option<WeaponKind>, headshot: bool) {
+        if !self.actors.contains(victim) {
+            return;
+        }
+        let physics = &engine.scenes[self.scene].physics;
+        let victim_name = self.actors.get(victim).name.clone();
+        let victim_position = self.actors.get(victim).position(physics);
+        if let Actor::Bot(bot) = self.actors.get_mut(victim) {
+            bot.play_voice(VoiceEvent::Death, victim_position);
+        }
+
+        let killer_name = if killer.is_some() && self.actors.contains(killer) {
+            let killer_position = self.actors.get(killer).position(physics);
+            if let Actor::Bot(bot) = self.actors.get_mut(killer) {
+                bot.play_voice(VoiceEvent::Kill, killer_position);
+            }
+            Some(self.actors.get(killer).name.clone())
+        } else {
+            None
+        };
+
+        if let Some(match_log) = self.match_log.as_mut() {
+            match_log.log_kill(self.time, &victim_name, killer_name.as_deref(), weapon);
+        }
+
+        if let Some(killer_name) = killer_name {
+            let mut callouts = Vec::new();
+            if self.leader_board.is_first_kill() {
+                callouts.push(KillCallout::FirstBlood);
+            }
+            if headshot {
+                callouts.push(KillCallout::Headshot);
+            }
+            callouts.extend(self.leader_board.register_kill(&killer_name, &victim_name, self.time));
+            if let Some(weapon) = weapon {
+                if killer != victim {
+                    self.leader_board.add_weapon_kill(&killer_name, weapon);
+                }
+            }
+            if !callouts.is_empty() {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::KillCallouts {
+                        actor: killer,
+                        name: killer_name.clone(),
+                        callouts,
+                    }).unwrap();
+            }
+
+            // Team kills and suicides do not count for team.
+            if let MatchOptions::TeamDeathMatch(_) = self.options {
+                let killer_team = self.actors.get(killer).team();
+                if killer != victim && killer_team != Team::None && killer_team != self.actors.get(victim).team() {
+                    self.leader_board.add_team_score(killer_team, 1);
+                }
+            }
+
+            let streak = self.leader_board.add_frag(&killer_name);
+            if kill_streak_name(streak).is_some() {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::KillStreak {
+                        actor: killer,
+                        name: killer_name,
+                        streak,
+                    }).unwrap();
             }
         }
-        index
     }
 
-    fn spawn_bot(&mut self, engine: &mut GameEngine, kind: BotKind, name: Option<String>) -> Handle<Actor> {
-        let index = self.find_suitable_spawn_point(engine);
-        let spawn_position = self.spawn_points
-            .get(index)
-            .map_or(Vec3::ZERO, |pt| pt.position);
+    fn on_kill_streak(&mut self, engine: &GameEngine, actor: Handle<Actor>, streak: u32) {
+        if self.actors.contains(actor) {
+            let actor = self.actors.get(actor);
 
-        let bot = self.add_bot(engine, kind, spawn_position, name);
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::PlaySound {
+                    path: PathBuf::from("data/sounds/item_pickup.ogg"),
+                    position: actor.position(&engine.scenes[self.scene].physics),
+                    gain: 1.0,
+                    rolloff_factor: 1.0,
+                    radius: 10.0,
+                    priority: SoundPriority::High,
+                }).unwrap();
 
-        self.sender
-            .as_ref()
-            .unwrap()
-            .send(Message::AddNotification {
-                text: format!("Bot {} spawned!", self.actors.get(bot).name)
-            }).unwrap();
+            // Long streaks are rewarded with some extra ammo for current weapon.
+            if streak >= KILL_STREAK_REWARD_THRESHOLD {
+                let current_weapon = actor.current_weapon();
+                if current_weapon.is_some() {
+                    self.weapons[current_weapon].add_ammo(20);
+                }
+            }
+        }
+    }
 
-        bot
+    fn on_kill_callouts(&mut self, engine: &GameEngine, actor: Handle<Actor>, callouts: &[KillCallout]) {
+        if self.actors.contains(actor) {
+            let position = self.actors.get(actor).position(&engine.scenes[self.scene].physics);
+            for sound in callouts.iter().filter_map(|callout| callout.sound()) {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::PlaySound {
+                        path: PathBuf::from(sound),
+                        position,
+                        gain: 1.0,
+                        rolloff_factor: 1.0,
+                        radius: 10.0,
+                        priority: SoundPriority::High,
+                    }).unwrap();
+            }
+        }
     }
 
-    fn damage_actor(&mut self, engine: &GameEngine, actor: Handle<Actor>, who: Handle<Actor>, amount: f32, time: GameTime) {
-        if self.actors.contains(actor) && (who.is_none() || who.is_some() && self.actors.contains(who)) {
-            let mut who_name = Default::default();
-            let message =
-                if who.is_some() {
-                    who_name = self.actors.get(who).name.clone();
-                    format!("{} dealt {} damage to {}!", who_name, amount, self.actors.get(actor).name)
-                } else {
-                    format!("{} took {} damage!", self.actors.get(actor).name, amount)
-                };
+    /// Tells callout to team of the actor. Player sees callouts of own team in kill feed,
+    /// bots of the same team react to callouts that point them somewhere.
+    fn on_team_callout(&mut self, engine: &GameEngine, actor: Handle<Actor>, callout: TeamCallout, time: GameTime) {
+        if !self.actors.contains(actor) || self.actors.get(actor).is_dead() {
+            return;
+        }
+        let scene = &engine.scenes[self.scene];
+        let caller = self.actors.get(actor);
+        let team = caller.team();
+        let position = caller.position(&scene.physics);
+        let (eye, look) = match caller {
+            Actor::Player(player) => {
+                let camera = &scene.graph[player.camera()];
+                (camera.global_position(), camera.look_vector())
+            }
+            Actor::Bot(_) => (position, scene.graph[caller.pivot].look_vector()),
+        };
 
-            self.sender
-                .as_ref()
-                .unwrap()
+        let sender = self.sender.as_ref().unwrap();
+        let heard_by_player = actor == self.player
+            || (team != Team::None && self.actors.contains(self.player) && self.actors.get(self.player).team() == team);
+        if heard_by_player {
+            sender
                 .send(Message::AddNotification {
-                    text: message,
+                    text: format!("{}: {}", caller.name, callout.text()),
+                }).unwrap();
+        }
+        if let Some(sound) = callout.sound() {
+            sender
+                .send(Message::PlaySound {
+                    path: PathBuf::from(sound),
+                    position,
+                    gain: 1.0,
+                    rolloff_factor: 1.0,
+                    radius: 10.0,
+                    priority: SoundPriority::Normal,
                 }).unwrap();
+        }
 
-            let who_position =
-                if who.is_some() {
-                    let scene = &engine.scenes[self.scene];
-                    Some(self.actors.get(who).position(&scene.physics))
-                } else {
-                    None
-                };
-            let actor = self.actors.get_mut(actor);
-            if let Actor::Bot(bot) = actor {
-                if let Some(who_position) = who_position {
-                    bot.set_point_of_interest(who_position, time);
+        // Free-for-all actors have nobody to talk to.
+        if team == Team::None {
+            return;
+        }
+        let point = match callout {
+            TeamCallout::EnemySpotted => {
+                let look = look.normalized().unwrap_or(Vec3::LOOK);
+                match cast_ray(&scene.physics, eye, look.scale(CALLOUT_SPOT_DISTANCE), true) {
+                    Some(hit) => hit.position,
+                    None => eye + look.scale(CALLOUT_SPOT_DISTANCE),
                 }
             }
-            let was_dead = actor.is_dead();
-            actor.damage(amount);
-            if !was_dead && actor.is_dead() && who.is_some() {
-                self.leader_board.add_frag(who_name)
+            TeamCallout::NeedBackup | TeamCallout::CoverMe | TeamCallout::FollowMe => position,
+            TeamCallout::Affirmative | TeamCallout::Negative => return,
+        };
+        for (handle, listener) in self.actors.pair_iter_mut() {
+            if handle != actor && listener.team() == team {
+                if let Actor::Bot(bot) = listener {
+                    bot.hear_noise(point, time);
+                }
             }
         }
     }
 
-    fn spawn_item(&mut self, engine: &mut GameEngine, kind: ItemKind, position: Vec3, adjust_height: bool, lifetime: Option<f32>) {
+    /// Drops current weapon of dead actor as temporary item with ammo that was left in it,
+    /// weapon stays with actor until actor is removed.
+    fn drop_weapon(&mut self, engine: &mut GameEngine, actor: Handle<Actor>) {
+        if !self.actors.contains(actor) {
+            return;
+        }
+        let actor = self.actors.get(actor);
+        let weapon = actor.current_weapon();
+        if !self.weapons.contains(weapon) || self.weapons[weapon].ammo() == 0 {
+            return;
+        }
+        let position = actor.position(&engine.scenes[self.scene].physics);
+        let item_kind = match self.weapons[weapon].get_kind() {
+            WeaponKind::M4 => ItemKind::M4,
+            WeaponKind::Ak47 => ItemKind::Ak47,
+            WeaponKind::PlasmaRifle => ItemKind::PlasmaGun,
+            WeaponKind::RocketLauncher => ItemKind::RocketLauncher,
+        };
+        let ammo = self.weapons[weapon].ammo();
+        let item = self.spawn_item(engine, item_kind, position, true, Some(DROPPED_WEAPON_LIFETIME));
+        self.items.get_mut(item).set_ammo(Some(ammo));
+    }
+
+    fn spawn_item(&mut self, engine: &mut GameEngine, kind: ItemKind, position: Vec3, adjust_height: bool, lifetime: Option<f32>) -> Handle<Item> {
         let position = if adjust_height {
             self.pick(engine, position, position - Vec3::new(0.0, 1000.0, 0.0))
         } else {
@@ -752,7 +2995,7 @@ impl Level {
         let resource_manager = &mut engine.resource_manager.lock().unwrap();
         let mut item = Item::new(kind, position, scene, resource_manager, self.sender.as_ref().unwrap().clone());
         item.set_lifetime(lifetime);
-        self.items.add(item);
+        self.items.add(item)
     }
 
     pub fn time(&self) -> f32 {
@@ -789,6 +3032,16 @@ impl Level {
                             .unwrap();
                     }
                 }
+                RespawnEntry::Remote(v) => {
+                    v.time_left -= time.delta;
+                    if v.time_left <= 0.0 {
+                        self.sender
+                            .as_mut()
+                            .unwrap()
+                            .send(Message::SpawnRemotePlayer { client: v.client })
+                            .unwrap();
+                    }
+                }
             }
         }
 
@@ -796,34 +3049,90 @@ impl Level {
             match entry {
                 RespawnEntry::Bot(v) => v.time_left >= 0.0,
                 RespawnEntry::Player(v) => v.time_left >= 0.0,
+                RespawnEntry::Remote(v) => v.time_left >= 0.0,
             }
         });
     }
 
-    fn update_spectator_camera(&mut self, scene: &mut Scene) {
-        if let Node::Camera(spectator_camera) = &mut scene.graph[self.spectator_camera] {
-            let mut position = spectator_camera.global_position();
-            position.follow(&self.target_spectator_position, 0.1);
-            spectator_camera.local_transform_mut().set_position(position);
+    /// Removes corpses that outlived their lifetime and oldest corpses over the limit.
+    fn update_corpses(&mut self, scene: &mut Scene, dt: f32) {
+        let excess = self.corpses.len().saturating_sub(self.corpse_limits.max_corpses as usize);
+        for (i, corpse) in self.corpses.iter_mut().enumerate() {
+            corpse.time_left = corpse.time_left.min(self.corpse_limits.lifetime) - dt;
+            if i < excess {
+                corpse.time_left = 0.0;
+            }
+            if corpse.time_left <= 0.0 {
+                scene.graph.remove_node(corpse.model);
+            }
         }
+        self.corpses.retain(|corpse| corpse.time_left > 0.0);
     }
 
+    /// Kills every living actor that is in a death zone or fell below level. Such kill is
+    /// credited to the last attacker of victim if the hit was recent, otherwise to no one.
     fn update_death_zones(&mut self, scene: &Scene) {
-        for (handle, actor) in self.actors.pair_iter_mut() {
-            for death_zone in self.death_zones.iter() {
-                if death_zone.bounds.is_contains_point(actor.position(&scene.physics)) {
-                    self.sender
-                        .as_ref()
-                        .unwrap()
-                        .send(Message::RespawnActor { actor: handle })
-                        .unwrap();
-                }
+        let mut victims = Vec::new();
+        for (handle, actor) in self.actors.pair_iter() {
+            if actor.is_dead() {
+                continue;
             }
+            let position = actor.position(&scene.physics);
+            if position.y < self.kill_height || self.death_zones.iter().any(|zone| zone.bounds.is_contains_point(position)) {
+                victims.push(handle);
+            }
+        }
+
+        for victim in victims {
+            let killer = match self.last_attackers.get(&victim) {
+                Some(&(attacker, time)) if self.time - time <= ENVIRONMENT_KILL_CREDIT_TIME && self.actors.contains(attacker) => attacker,
+                _ => Handle::NONE,
+            };
+            // Instant death, so armor and damage multipliers are ignored.
+            self.actors.get_mut(victim).health = 0.0;
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::ActorKilled {
+                    victim,
+                    killer,
+                    weapon: None,
+                    headshot: false,
+                })
+                .unwrap();
+        }
+    }
+
+    /// Tracks reverb zone in which player is and changes reverb of gameplay sounds when
+    /// player moves to other zone.
+    fn update_reverb_zones(&mut self, scene: &Scene) {
+        if self.player.is_none() {
+            return;
+        }
+        let position = self.actors.get(self.player).position(&scene.physics);
+        let zone = self.reverb_zones
+            .iter()
+            .position(|zone| zone.bounds.is_contains_point(position));
+        if zone != self.current_reverb_zone {
+            self.current_reverb_zone = zone;
+            let preset = zone.map_or(ReverbPreset::Default, |i| self.reverb_zones[i].preset);
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::SetReverbPreset { preset })
+                .unwrap();
         }
     }
 
-    fn update_game_ending(&self) {
-        if self.leader_board.is_match_over(&self.options) {
+    fn update_game_ending(&mut self) {
+        // Zero time limit means that match is played until score limit.
+        let time_limit = self.options.time_limit_secs();
+        let time_is_over = time_limit > 0.0 && self.time >= time_limit;
+        if time_is_over || self.leader_board.is_match_over(&self.options) {
+            // Logger is taken out so final scores are written only once.
+            if let Some(mut match_log) = self.match_log.take() {
+                match_log.log_final_scores(self.time, &self.leader_board);
+            }
             self.sender
                 .as_ref()
                 .unwrap()
@@ -831,20 +3140,73 @@ impl Level {
         }
     }
 
+    /// Advances intro countdown and tells HUD every time whole number of seconds changes.
+    fn update_phase(&mut self, dt: f32) {
+        let seconds_left = self.phase.seconds_left();
+        let started = self.phase.update(dt);
+        if started || self.phase.seconds_left() != seconds_left {
+            self.sender
+                .as_ref()
+                .unwrap()
+                .send(Message::MatchCountdown {
+                    seconds_left: self.phase.seconds_left().unwrap_or(0)
+                })
+                .unwrap();
+        }
+    }
+
     pub fn update(&mut self, engine: &mut GameEngine, time: GameTime) {
-        self.time += time.delta;
-        self.update_respawn(time);
+        // Replica only shows actors, match itself goes on the server.
+        let is_replica = self.replica.is_some();
+        if !is_replica {
+            self.update_phase(time.delta);
+            if self.phase.is_active() {
+                self.time += time.delta;
+            }
+            self.update_respawn(time);
+            if self.input_delay.is_some() || self.killcam {
+                self.record_history(engine);
+            }
+            if self.input_delay.is_some() {
+                self.update_lag_compensation(engine, time);
+            }
+            if self.options.auto_balance() {
+                self.balancer.update(time.delta, self.score_difference());
+            }
+            self.schedule_path_rebuilds(time.elapsed);
+        }
+        if is_replica {
+            self.update_replica(engine, time);
+        }
         let scene = &mut engine.scenes[self.scene];
-        self.update_spectator_camera(scene);
-        self.update_death_zones(scene);
-        self.weapons.update(scene, &self.actors);
+        self.spectator.update(scene, &self.actors, time.delta);
+        self.update_death_marker(scene, time.delta);
+        if !is_replica {
+            self.update_death_zones(scene);
+        }
+        self.update_reverb_zones(scene);
+        let burst_shots = self.weapons.update(scene, &self.actors, time.delta);
+        self.targets.update(scene, time.delta);
+        self.update_held_grenades(scene, time.delta);
         self.projectiles.update(
             scene,
             &self.actors,
             &self.weapons,
+            &self.targets.bodies(),
+            self.rocket_jump,
             time,
         );
+        self.doors.update(scene, &self.actors, time.delta);
+        self.hazards.update(scene, &self.actors, self.sender.as_ref().unwrap(), !is_replica, time.delta);
+        let hazards = self.hazards.volumes();
         self.items.update(scene, time);
+        self.update_item_glow(&mut scene.graph);
+        self.gibs.update(scene, time.delta);
+        self.update_corpses(scene, time.delta);
+        self.smoke.update(time.delta);
+        self.shot_debug.update(time.delta);
+        let explosives = self.projectiles.explosives(&scene.graph);
+        self.squads.update(time.elapsed);
         self.actors.update(&mut UpdateContext {
             time,
             scene,
@@ -853,54 +3215,174 @@ impl Level {
             jump_pads: &self.jump_pads,
             navmesh: self.navmesh.as_mut(),
             weapons: &self.weapons,
+            explosives: &explosives,
+            smoke: &self.smoke,
+            // Own player of replica moves right away, server confirms its moves later.
+            frozen: !is_replica && !self.phase.is_active(),
+            squads: &mut self.squads,
+            hazards: &hazards,
+            control_points: self.control_points.points(),
         });
-        self.update_game_ending();
+        if !is_replica {
+            for weapon in burst_shots {
+                let owner = self.weapons[weapon].owner();
+                let velocity = if self.actors.contains(owner) {
+                    let body = self.actors.get(owner).get_body();
+                    engine.scenes[self.scene].physics.borrow_body(body).get_velocity()
+                } else {
+                    Vec3::ZERO
+                };
+                let kind = self.weapons[weapon].definition.projectile;
+                self.fire_projectile(engine, weapon, kind, velocity, time, None);
+            }
+            if self.phase.is_active() {
+                self.update_use(engine, time.delta);
+                self.update_bot_grenade_returns(engine, time.delta);
+                self.update_control_points(engine, time.delta);
+            }
+            self.update_game_ending();
+        }
+    }
+
+    /// Moves captures of control points by actors that stand in them and adds score of held
+    /// points to their teams.
+    fn update_control_points(&mut self, engine: &mut GameEngine, dt: f32) {
+        let capture_time = match self.options.capture_time() {
+            Some(capture_time) => capture_time,
+            None => return,
+        };
+        let scene = &engine.scenes[self.scene];
+        let actors = self.actors
+            .iter()
+            .filter(|actor| !actor.is_dead())
+            .map(|actor| (actor.team(), actor.position(&scene.physics)))
+            .collect::<Vec<_>>();
+        for event in self.control_points.update(&actors, capture_time, dt) {
+            match event {
+                ControlPointEvent::Captured { label, team } => {
+                    self.sender
+                        .as_ref()
+                        .unwrap()
+                        .send(Message::ControlPointCaptured { label, team })
+                        .unwrap();
+                }
+                ControlPointEvent::Scored { team, amount } => {
+                    self.leader_board.add_team_score(team, amount);
+                }
+            }
+        }
+    }
+
+    /// Returns control points of domination, there are none in other modes.
+    pub fn control_points(&self) -> &[ControlPoint] {
+        self.control_points.points()
+    }
+
+    /// Counts down marker of last death while player is alive, marker is removed when it
+    /// times out or player reaches it.
+    fn update_death_marker(&mut self, scene: &Scene, dt: f32) {
+        if !self.actors.contains(self.player) {
+            return;
+        }
+        let player_position = self.actors.get(self.player).position(&scene.physics);
+        if let Some((position, time_left)) = self.death_marker.as_mut() {
+            *time_left -= dt;
+            if *time_left <= 0.0 || position.distance(&player_position) < DEATH_MARKER_REACH_DISTANCE {
+                self.death_marker = None;
+            }
+        }
+    }
+
+    /// Returns place where player died last time while it should be marked on HUD.
+    pub fn death_marker(&self) -> Option<Vec3> {
+        self.death_marker.map(|(position, _)| position)
+    }
+
+    /// Returns stats of target range practice, None in other modes.
+    pub fn range_stats(&self) -> Option<RangeStats> {
+        if self.options.is_practice() {
+            Some(self.targets.stats(self.leader_board.values().get(PLAYER_NAME)))
+        } else {
+            None
+        }
+    }
+
+    /// Returns kills of player minus kills of the best bot.
+    fn score_difference(&self) -> i32 {
+        let player_kills = self.leader_board
+            .values()
+            .get(PLAYER_NAME)
+            .map_or(0, |score| score.kills);
+        let best_bot_kills = self.leader_board
+            .highest_personal_score(Some(PLAYER_NAME))
+            .map_or(0, |(_, kills)| kills);
+        player_kills as i32 - best_bot_kills as i32
     }
 
     pub fn respawn_actor(&mut self, engine: &mut GameEngine, actor: Handle<Actor>) {
         if self.actors.contains(actor) {
+            let killer = match self.last_attackers.remove(&actor) {
+                Some((attacker, time)) if self.time - time <= ENVIRONMENT_KILL_CREDIT_TIME && self.actors.contains(attacker) => attacker,
+                _ => Handle::NONE,
+            };
             let name = self.actors.get(actor).name.clone();
 
             self.leader_board.add_death(&name);
+            // Actors that lost all lives are out of the match and won't be respawned.
+            let eliminated = self.leader_board.is_eliminated(&name, &self.options);
+            if eliminated {
+                self.sender
+                    .as_ref()
+                    .unwrap()
+                    .send(Message::AddNotification {
+                        text: format!("{} has been eliminated", name)
+                    }).unwrap();
+            }
 
-            let entry = match self.actors.get(actor) {
-                Actor::Bot(bot) => {
+            let remote_client = self.remote_players
+                .iter()
+                .find(|(_, remote_actor)| *remote_actor == actor)
+                .map(|(client, _)| *client);
+
+            let entry = match (self.actors.get(actor), remote_client) {
+                (Actor::Player(_), Some(client)) => {
+                    RespawnEntry::Remote(RemoteRespawnEntry {
+                        client,
+                        time_left: RESPAWN_TIME,
+                    })
+                }
+                (Actor::Bot(bot), _) => {
                     RespawnEntry::Bot(BotRespawnEntry {
                         name,
                         kind: bot.definition.kind,
                         time_left: RESPAWN_TIME,
                     })
                 }
-                Actor::Player(player) => {
+                (Actor::Player(player), None) => {
                     // Turn on spectator camera and prepare its target position. Spectator
-                    // camera will be used to render world until player is despawned.
+                    // camera will be used to render world until player is respawned.
                     let scene = &mut engine.scenes[self.scene];
                     let position = scene.graph[player.camera()].global_position();
-                    if let Node::Camera(spectator_camera) = &mut scene.graph[self.spectator_camera] {
-                        spectator_camera
-                            .set_enabled(true)
-                            .local_transform_mut()
-                            .set_position(position);
-                    }
+                    let look = scene.graph[player.camera()].look_vector();
                     // Use ray casting to get target position for spectator camera, it is used to
                     // create "dropping head" effect.
-                    let ray = Ray::from_two_points(&position, &(position - Vec3::new(0.0, 1000.0, 0.0))).unwrap();
-                    let options = RayCastOptions {
-                        ignore_bodies: true,
-                        ignore_static_geometries: false,
-                        sort_results: true,
-                    };
-                    let mut result = Vec::new();
-                    if scene.physics.ray_cast(&ray, options, &mut result) {
-                        if let Some(hit) = result.first() {
-                            self.target_spectator_position = hit.position;
-                            // Prevent see-thru-floor
-                            self.target_spectator_position.y += 0.1;
-                        } else {
-                            self.target_spectator_position = position;
-                        }
-                    } else {
-                        self.target_spectator_position = position;
+                    let mut target_position = position;
+                    if let Some(hit) = cast_ray(&scene.physics, position, Vec3::new(0.0, -1000.0, 0.0), true) {
+                        target_position = hit.position;
+                        // Prevent see-thru-floor
+                        target_position.y += 0.1;
+                    }
+                    self.spectator.begin(scene, position, look, target_position);
+                    self.death_marker = Some((player.position(&scene.physics), DEATH_MARKER_TIME));
+                    if self.killcam && killer.is_some() {
+                        let frames = self.killcam_frames(actor, killer);
+                        self.spectator.begin_killcam(frames);
+                        self.sender
+                            .as_ref()
+                            .unwrap()
+                            .send(Message::AddNotification {
+                                text: format!("Killed by {}, fire to skip", self.actors.get(killer).name)
+                            }).unwrap();
                     }
 
                     RespawnEntry::Player(PlayerRespawnEntry {
@@ -909,16 +3391,32 @@ impl Level {
                 }
             };
 
+            // Body of bot stays on level for a while after bot itself is removed.
+            if let Actor::Bot(bot) = self.actors.get_mut(actor) {
+                let model = bot.detach_corpse(&mut engine.scenes[self.scene]);
+                if model.is_some() {
+                    self.corpses.push(Corpse {
+                        model,
+                        time_left: self.corpse_limits.lifetime,
+                    });
+                }
+            }
+
             self.remove_actor(engine, actor);
 
-            self.respawn_list.push(entry);
+            if !eliminated {
+                self.respawn_list.push(entry);
+            }
+
+            // Dead players can be moved to other team now without switching them mid-life.
+            self.balance_teams(engine);
         }
     }
 
     pub fn handle_message(&mut self, engine: &mut GameEngine, message: &Message, time: GameTime) {
         match message {
             &Message::GiveNewWeapon { actor, kind } => {
-                self.give_new_weapon(engine, actor, kind);
+                self.give_new_weapon(engine, actor, kind, true, None);
             }
             Message::AddBot { kind, position, name } => {
                 self.add_bot(engine, *kind, *position, name.clone());
@@ -927,13 +3425,26 @@ impl Level {
                 self.remove_actor(engine, actor)
             }
             &Message::GiveItem { actor, kind } => {
-                self.give_item(engine, actor, kind);
+                self.give_item(engine, actor, kind, None);
             }
+            // Replica leaves pickups and shots to server.
+            &Message::PickUpItem { .. } | &Message::ShootWeapon { .. } | &Message::AltFireWeapon { .. } |
+            &Message::Footstep { .. } if self.replica.is_some() => (),
             &Message::PickUpItem { actor, item } => {
                 self.pickup_item(engine, actor, item);
             }
             &Message::ShootWeapon { weapon, initial_velocity, direction } => {
-                self.shoot_weapon(engine, weapon, initial_velocity, time, direction)
+                if self.input_delay.is_some() && self.weapons.contains(weapon) && self.weapons[weapon].owner() == self.player {
+                    self.delay_shot(engine, weapon, initial_velocity, direction);
+                } else {
+                    self.shoot_weapon(engine, weapon, initial_velocity, time, direction);
+                }
+            }
+            &Message::Footstep { actor, position, loudness } => {
+                self.hear_footstep(engine, actor, position, loudness, time);
+            }
+            &Message::AltFireWeapon { weapon, initial_velocity } => {
+                self.alt_fire_weapon(engine, weapon, initial_velocity, time);
             }
             &Message::CreateProjectile { kind, position, direction, initial_velocity, owner , basis} => {
                 self.create_projectile(engine, kind, position, direction, initial_velocity, owner, basis)
@@ -941,24 +3452,99 @@ impl Level {
             &Message::ShowWeapon { weapon, state } => {
                 self.show_weapon(engine, weapon, state)
             }
+            &Message::ShakeCamera { position, intensity, radius } => {
+                self.shake_camera(engine, position, intensity, radius);
+            }
+            &Message::InspectWeapon { weapon } => {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon].inspect();
+                }
+            }
+            &Message::ReloadWeapon { weapon } => {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon].reload();
+                }
+            }
+            &Message::ReleaseTrigger { weapon } => {
+                if self.weapons.contains(weapon) {
+                    self.weapons[weapon].release_trigger();
+                }
+            }
             Message::SpawnBot { kind, name } => {
                 self.spawn_bot(engine, *kind, Some(name.clone()));
             }
-            &Message::DamageActor { actor, who, amount } => {
-                self.damage_actor(engine, actor, who, amount, time);
+            &Message::DamageActor { actor, who, amount, region, kind, position } => {
+                self.damage_actor(engine, actor, who, amount, region, kind, position, time);
+            }
+            &Message::HitRangeTarget { body, who } => {
+                if who == self.player && self.targets.hit(&mut engine.scenes[self.scene], body) {
+                    // Hits of targets count into accuracy same as hits of actors.
+                    self.leader_board.add_hit(PLAYER_NAME, 0.0);
+                }
+            }
+            Message::ResetTargetRange => {
+                self.targets.reset(&mut engine.scenes[self.scene]);
+            }
+            &Message::ActorSpawned { actor } => {
+                self.on_actor_spawned(actor);
+            }
+            &Message::ActorDamaged { victim, attacker, amount, .. } => {
+                self.on_actor_damaged(victim, attacker, amount);
+            }
+            &Message::ActorKilled { victim, killer, weapon, headshot } => {
+                self.on_actor_killed(engine, victim, killer, weapon, headshot);
+                if self.weapon_drop {
+                    self.drop_weapon(engine, victim);
+                }
             }
             &Message::CreateEffect { kind, position } => {
-                effects::create(kind, &mut engine.scenes[self.scene].graph, &mut engine.resource_manager.lock().unwrap(), position)
+                let kind = if kind == EffectKind::Blood && !self.content_filter.gore {
+                    EffectKind::BulletImpact
+                } else {
+                    kind
+                };
+                self.effects.create(kind, &mut engine.scenes[self.scene].graph, &mut engine.resource_manager.lock().unwrap(), position)
+            }
+            &Message::DetonateTacticalGrenade { kind, position, owner } => {
+                match kind {
+                    ProjectileKind::SmokeGrenade => {
+                        self.smoke.add(position);
+                        self.effects.create_smoke_cloud(&mut engine.scenes[self.scene].graph, &mut engine.resource_manager.lock().unwrap(),
+                                                        position, SMOKE_RADIUS, SMOKE_DURATION);
+                    }
+                    ProjectileKind::Flashbang => self.flash(engine, position, owner),
+                    _ => (),
+                }
             }
             Message::SpawnPlayer => {
                 self.spawn_player(engine);
             }
+            &Message::SpawnRemotePlayer { client } => {
+                self.spawn_remote_player(engine, client);
+            }
             &Message::SpawnItem { kind, position, adjust_height, lifetime } => {
-                self.spawn_item(engine, kind, position, adjust_height, lifetime)
+                self.spawn_item(engine, kind, position, adjust_height, lifetime);
             }
+            // Deaths and respawns are decided by server, replica learns about them from
+            // snapshots.
+            &Message::RespawnActor { .. } if self.replica.is_some() => (),
             &Message::RespawnActor { actor } => {
                 self.respawn_actor(engine, actor)
             }
+            &Message::KillStreak { actor, streak, .. } => {
+                self.on_kill_streak(engine, actor, streak)
+            }
+            Message::KillCallouts { actor, callouts, .. } => {
+                self.on_kill_callouts(engine, *actor, callouts)
+            }
+            &Message::TeamCallout { actor, callout } => {
+                self.on_team_callout(engine, actor, callout, time)
+            }
+            Message::AddNote { text } => {
+                if let Some(match_log) = self.match_log.as_mut() {
+                    match_log.log_note(self.time, PLAYER_NAME, text);
+                }
+            }
             _ => ()
         }
     }
@@ -978,33 +3564,194 @@ impl Level {
         }
     }
 
-    pub fn debug_draw(&self, engine: &mut GameEngine) {
+    /// Draws debug geometry of categories that are enabled in `flags`, `dt` is duration of
+    /// physics step and is used to get velocities of bodies.
+    pub fn debug_draw(&self, engine: &mut GameEngine, flags: &DebugDrawFlags, dt: f32) {
         let debug_renderer = &mut engine.renderer.debug_renderer;
+        let scene = &engine.scenes[self.scene];
 
-        if let Some(navmesh) = self.navmesh.as_ref() {
-            for pt in navmesh.vertices() {
-                for neighbour in pt.neighbours() {
-                    debug_renderer.add_line(debug_renderer::Line {
-                        begin: pt.position(),
-                        end: navmesh.vertices()[*neighbour].position(),
-                        color: Default::default(),
-                    });
+        if flags.navmesh {
+            if let Some(navmesh) = self.navmesh.as_ref() {
+                for pt in navmesh.vertices() {
+                    for neighbour in pt.neighbours() {
+                        debug_draw::draw_line(debug_renderer, pt.position(), navmesh.vertices()[*neighbour].position(), Default::default());
+                    }
                 }
             }
+        }
 
-            for actor in self.actors.iter() {
-                if let Actor::Bot(bot) = actor {
-                    bot.debug_draw(debug_renderer);
+        let mut bodies = Vec::new();
+        for (handle, actor) in self.actors.pair_iter() {
+            if !actor.is_dead() {
+                bodies.push(actor.get_body());
+            }
+            if let Actor::Bot(bot) = actor {
+                bot.debug_draw(debug_renderer, scene, flags);
+
+                if flags.sight && !actor.is_dead() {
+                    let position = actor.position(&scene.physics);
+                    let sight_range = bot.effective_sight_range();
+                    for (other_handle, other) in self.actors.pair_iter() {
+                        let other_position = other.position(&scene.physics);
+                        if other_handle == handle || other.is_dead() || position.sqr_distance(&other_position) > sight_range * sight_range {
+                            continue;
+                        }
+                        // Same checks as bot perception does, except for view cone and lighting.
+                        let color = if self.smoke.blocks_sight(position, other_position) || !line_of_sight(&scene.physics, position, other_position) {
+                            Color::opaque(200, 0, 0)
+                        } else {
+                            Color::opaque(0, 200, 0)
+                        };
+                        debug_draw::draw_line(debug_renderer, position, other_position, color);
+                    }
+                }
+            }
+        }
+        bodies.extend(self.projectiles.iter().map(|projectile| projectile.get_body()).filter(|body| body.is_some()));
+
+        for body in bodies {
+            let body = scene.physics.borrow_body(body);
+            let position = body.get_position();
+            if flags.colliders {
+                let color = Color::opaque(0, 200, 200);
+                match body.get_shape() {
+                    ConvexShape::Sphere(sphere) => debug_draw::draw_sphere(debug_renderer, position, sphere.get_radius(), color),
+                    ConvexShape::Capsule(capsule) => debug_draw::draw_capsule(debug_renderer, position, capsule.get_radius(), capsule.get_height(), color),
+                    _ => debug_draw::draw_cross(debug_renderer, position, 0.5, color),
                 }
             }
+            if flags.velocities {
+                // Body velocity is displacement per physics step.
+                let velocity = body.get_velocity().scale(VELOCITY_SCALE / dt);
+                debug_draw::draw_line(debug_renderer, position, position + velocity, Color::opaque(255, 255, 255));
+            }
+        }
+
+        if flags.spawn_points {
+            for spawn_point in self.spawn_points.iter() {
+                debug_draw::draw_cross(debug_renderer, spawn_point.position, 1.0, Color::opaque(255, 128, 0));
+            }
+        }
+
+        if flags.shots {
+            self.shot_debug.draw(debug_renderer);
         }
 
-        for death_zone in self.death_zones.iter() {
-            debug_renderer.draw_aabb(&death_zone.bounds, Color::opaque(0, 0, 200));
+        if flags.death_zones {
+            for death_zone in self.death_zones.iter() {
+                debug_renderer.draw_aabb(&death_zone.bounds, Color::opaque(0, 0, 200));
+            }
+            for hazard in self.hazards.iter() {
+                debug_renderer.draw_aabb(hazard.volume(), Color::opaque(255, 80, 0));
+            }
         }
     }
 }
 
+/// Closest intersection of a ray with level.
+#[derive(Copy, Clone, Debug)]
+pub struct RayHit {
+    pub position: Vec3,
+    /// Body that was hit, Handle::NONE if ray hit static geometry.
+    pub body: Handle<RigidBody>,
+}
+
+/// Ammo type that ammo item gives, it is the type of weapon the item is made for. None if
+/// item is not ammo.
+fn item_ammo_type(kind: ItemKind) -> Option<AmmoType> {
+    let weapon_kind = match kind {
+        ItemKind::Plasma => WeaponKind::PlasmaRifle,
+        ItemKind::Ak47Ammo => WeaponKind::Ak47,
+        ItemKind::M4Ammo => WeaponKind::M4,
+        _ => return None,
+    };
+    Some(Weapon::get_definition(weapon_kind).ammo_type)
+}
+
+/// Damage that is dealt to an actor after every multiplier was applied.
+struct DamageEvent {
+    victim: Handle<Actor>,
+    /// Handle::NONE if damage came from environment.
+    attacker: Handle<Actor>,
+    amount: f32,
+    kind: DamageKind,
+    weapon: Option<WeaponKind>,
+    headshot: bool,
+}
+
+/// Applies damage to character of victim and sends events about it, nothing is sent for
+/// damage of character that is dead already. Returns true if damage killed the character.
+fn apply_damage(character: &mut Character, event: DamageEvent, sender: &Sender<Message>) -> bool {
+    let was_dead = character.is_dead();
+    character.damage(event.amount);
+    let killed = !was_dead && character.is_dead();
+    if !was_dead {
+        sender.send(Message::ActorDamaged {
+            victim: event.victim,
+            attacker: event.attacker,
+            amount: event.amount,
+            kind: event.kind,
+        }).unwrap();
+    }
+    if killed {
+        sender.send(Message::ActorKilled {
+            victim: event.victim,
+            killer: event.attacker,
+            weapon: event.weapon,
+            headshot: event.headshot,
+        }).unwrap();
+    }
+    killed
+}
+
+/// Returns true if no level geometry is between given points, bodies of actors are ignored.
+pub fn line_of_sight(physics: &Physics, from: Vec3, to: Vec3) -> bool {
+    cast_ray(physics, from, to - from, true).is_none()
+}
+
+/// Returns how strongly flashbang at given position blinds someone who looks from `eye`
+/// along `look`, zero if flashbang is too far, out of view or hidden by walls or smoke.
+fn flash_strength(physics: &Physics, smoke: &SmokeContainer, eye: Vec3, look: Vec3, position: Vec3) -> f32 {
+    let to_flash = position - eye;
+    let distance = to_flash.len();
+    if distance >= FLASH_RADIUS {
+        return 0.0;
+    }
+    let facing = match (to_flash.normalized(), look.normalized()) {
+        (Some(direction), Some(look)) => direction.dot(&look),
+        // Flashbang right in the face.
+        _ => 1.0,
+    };
+    let view = ((facing - FLASH_VIEW_COS) / (1.0 - FLASH_VIEW_COS)).max(0.0);
+    if view <= 0.0 || smoke.blocks_sight(eye, position) || !line_of_sight(physics, eye, position) {
+        return 0.0;
+    }
+    (1.0 - distance / FLASH_RADIUS) * view
+}
+
+/// Casts ray from `from` along `dir` and returns closest hit, bodies can be ignored so only
+/// static geometry is checked.
+fn cast_ray(physics: &Physics, from: Vec3, dir: Vec3, ignore_bodies: bool) -> Option<RayHit> {
+    let ray = Ray::from_two_points(&from, &(from + dir))?;
+    let options = RayCastOptions {
+        ignore_bodies,
+        ignore_static_geometries: false,
+        sort_results: true,
+    };
+    let mut result = Vec::new();
+    if physics.ray_cast(&ray, options, &mut result) {
+        result.first().map(|hit| RayHit {
+            position: hit.position,
+            body: match hit.kind {
+                HitKind::Body(body) => body,
+                HitKind::StaticTriangle { .. } => Handle::NONE,
+            },
+        })
+    } else {
+        None
+    }
+}
+
 pub struct SpawnPoint {
     position: Vec3
 }
@@ -1025,4 +3772,130 @@ impl Visit for SpawnPoint {
 
         visitor.leave_region()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rg3d::physics::{
+        static_geometry::{StaticGeometry, StaticTriangle},
+        convex_shape::SphereShape,
+    };
+    use crate::projectile::sweep_body;
+
+    /// Physics with single wall, square in plane z = 5 from -1 to 1 along x and y.
+    fn physics_with_wall() -> Physics {
+        let a = Vec3::new(-1.0, -1.0, 5.0);
+        let b = Vec3::new(1.0, -1.0, 5.0);
+        let c = Vec3::new(1.0, 1.0, 5.0);
+        let d = Vec3::new(-1.0, 1.0, 5.0);
+        let triangles = vec![
+            StaticTriangle::from_points(&a, &b, &c).unwrap(),
+            StaticTriangle::from_points(&a, &c, &d).unwrap(),
+        ];
+        let mut physics = Physics::new();
+        physics.add_static_geometry(StaticGeometry::new(triangles, false));
+        physics
+    }
+
+    #[test]
+    fn ray_hits_wall() {
+        let physics = physics_with_wall();
+        let hit = cast_ray(&physics, Vec3::ZERO, Vec3::new(0.0, 0.0, 10.0), true).expect("ray must hit the wall");
+        assert!((hit.position.z - 5.0).abs() < 1e-3);
+        assert!(hit.body.is_none());
+        assert!(!line_of_sight(&physics, Vec3::ZERO, Vec3::new(0.0, 0.0, 10.0)));
+    }
+
+    #[test]
+    fn ray_misses_open_space() {
+        let physics = physics_with_wall();
+        // Passes by the wall.
+        assert!(cast_ray(&physics, Vec3::new(3.0, 0.0, 0.0), Vec3::new(0.0, 0.0, 10.0), true).is_none());
+        // Stops before the wall.
+        assert!(cast_ray(&physics, Vec3::ZERO, Vec3::new(0.0, 0.0, 4.0), true).is_none());
+        assert!(line_of_sight(&physics, Vec3::ZERO, Vec3::new(0.0, 0.0, 4.0)));
+    }
+
+    /// Adds small physical projectile that moved from `last_position` to `position` during
+    /// last physics step.
+    fn add_projectile(physics: &mut Physics, last_position: Vec3, position: Vec3) -> Handle<RigidBody> {
+        let mut body = RigidBody::new(ConvexShape::Sphere(SphereShape::new(0.05)));
+        body.set_position(position);
+        body.set_velocity(position - last_position);
+        physics.add_body(body)
+    }
+
+    #[test]
+    fn fast_projectile_collides_with_thin_wall() {
+        let mut physics = physics_with_wall();
+        // Projectile flies 2 m per step, it was in front of the wall on last step and is
+        // behind it now.
+        let last_position = Vec3::new(0.0, 0.0, 4.0);
+        let body = add_projectile(&mut physics, last_position, Vec3::new(0.0, 0.0, 6.0));
+        let position = sweep_body(&mut physics, body, last_position, Vec3::new(0.0, 0.0, 6.0));
+        assert!(position.z < 5.0);
+        let body = physics.borrow_body(body);
+        assert!(body.get_position().distance(&position) < 1e-5);
+        assert!(body.get_velocity().z < 0.0);
+    }
+
+    #[test]
+    fn projectile_in_open_space_is_not_swept() {
+        let mut physics = physics_with_wall();
+        let last_position = Vec3::new(0.0, 0.0, 1.0);
+        let body = add_projectile(&mut physics, last_position, Vec3::new(0.0, 0.0, 3.0));
+        let position = sweep_body(&mut physics, body, last_position, Vec3::new(0.0, 0.0, 3.0));
+        assert_eq!(position.z, 3.0);
+        assert!(physics.borrow_body(body).get_velocity().z > 0.0);
+    }
+
+    fn hit(amount: f32, headshot: bool) -> DamageEvent {
+        DamageEvent {
+            victim: Handle::new(1, 1),
+            attacker: Handle::new(2, 1),
+            amount,
+            kind: DamageKind::Bullet,
+            weapon: Some(WeaponKind::Ak47),
+            headshot,
+        }
+    }
+
+    #[test]
+    fn scripted_kill_emits_events() {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let mut character = Character {
+            armor: 0.0,
+            ..Default::default()
+        };
+
+        assert!(!apply_damage(&mut character, hit(30.0, false), &sender));
+        let events = receiver.try_iter().collect::<Vec<_>>();
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            &Message::ActorDamaged { victim, attacker, amount, .. } => {
+                assert_eq!(victim, Handle::new(1, 1));
+                assert_eq!(attacker, Handle::new(2, 1));
+                assert_eq!(amount, 30.0);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        assert!(apply_damage(&mut character, hit(80.0, true), &sender));
+        let events = receiver.try_iter().collect::<Vec<_>>();
+        assert_eq!(events.len(), 2);
+        assert!(matches!(events[0], Message::ActorDamaged { .. }));
+        match &events[1] {
+            &Message::ActorKilled { victim, killer, weapon, headshot } => {
+                assert_eq!(victim, Handle::new(1, 1));
+                assert_eq!(killer, Handle::new(2, 1));
+                assert_eq!(weapon, Some(WeaponKind::Ak47));
+                assert!(headshot);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        // Corpse takes no more damage events and can't be killed twice.
+        assert!(!apply_damage(&mut character, hit(10.0, false), &sender));
+        assert_eq!(receiver.try_iter().count(), 0);
+    }
+}
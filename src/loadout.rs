@@ -0,0 +1,68 @@
+//! Loadout is a set of weapons and amount of ammo the player gets on every spawn. It is
+//! selected in match menu and remembered between runs.
+
+use crate::weapon::WeaponKind;
+use rg3d::core::visitor::{Visit, Visitor, VisitResult};
+use std::collections::HashMap;
+
+/// Every weapon that can be selected in a loadout, in the order they're shown in the menu.
+pub const LOADOUT_WEAPONS: [(&str, WeaponKind); 4] = [
+    ("M4", WeaponKind::M4),
+    ("AK-47", WeaponKind::Ak47),
+    ("Plasma Rifle", WeaponKind::PlasmaRifle),
+    ("Rocket Launcher", WeaponKind::RocketLauncher),
+];
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Loadout {
+    pub weapons: Vec<WeaponKind>,
+    /// Multiplier for default amount of ammo of each weapon.
+    pub ammo_multiplier: f32,
+    /// Skins picked for weapons, weapons that are not listed use skin from their definition.
+    pub skins: Vec<(WeaponKind, String)>,
+}
+
+impl Default for Loadout {
+    fn default() -> Self {
+        Self {
+            weapons: LOADOUT_WEAPONS.iter().map(|(_, kind)| *kind).collect(),
+            ammo_multiplier: 1.0,
+            skins: Vec::new(),
+        }
+    }
+}
+
+impl Loadout {
+    pub fn skin(&self, kind: WeaponKind) -> Option<&str> {
+        self.skins.iter()
+            .find(|(skin_kind, _)| *skin_kind == kind)
+            .map(|(_, skin)| skin.as_str())
+    }
+}
+
+impl Visit for Loadout {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut weapons = self.weapons.iter().map(|kind| kind.id()).collect::<Vec<u32>>();
+        weapons.visit("Weapons", visitor)?;
+        if visitor.is_reading() {
+            self.weapons = weapons.into_iter()
+                .map(WeaponKind::new)
+                .collect::<Result<_, _>>()?;
+        }
+        self.ammo_multiplier.visit("AmmoMultiplier", visitor)?;
+
+        let mut skins = self.skins.iter()
+            .map(|(kind, skin)| (kind.id(), skin.clone()))
+            .collect::<HashMap<u32, String>>();
+        skins.visit("Skins", visitor)?;
+        if visitor.is_reading() {
+            self.skins = skins.into_iter()
+                .map(|(id, skin)| Ok((WeaponKind::new(id)?, skin)))
+                .collect::<Result<_, String>>()?;
+        }
+
+        visitor.leave_region()
+    }
+}
@@ -3,12 +3,15 @@
 
 extern crate rg3d;
 extern crate rand;
+extern crate image;
 
 mod actor;
 mod level;
 mod player;
 mod weapon;
+mod ammo;
 mod bot;
+mod bot_behavior;
 mod projectile;
 mod menu;
 mod effects;
@@ -22,14 +25,68 @@ mod message;
 mod options_menu;
 mod gui;
 mod leader_board;
+mod spectator;
+mod settings;
+mod frame_profile;
+mod loadout;
+mod crosshair;
+mod match_log;
+mod palette;
+mod damage;
+mod hud_layout;
+mod hud_theme;
+mod gib;
+mod difficulty_balancer;
+mod lag_compensation;
+mod usable;
+mod door;
+mod hazard;
+mod match_phase;
+mod match_summary;
+mod definitions;
+mod squad;
+mod player_stats;
+mod stats_menu;
+mod net;
+mod prediction;
+mod multiplayer_menu;
+mod benchmark;
+mod smoke;
+mod debug_draw;
+mod shot_debug;
+mod target_range;
+mod match_preset;
+mod comms;
+mod team_balance;
+mod view_model;
+mod skin;
+mod control_point;
+mod match_vote;
+mod console;
 
 use crate::{
-    level::Level,
+    level::{Level, ReverbPreset, line_of_sight, MAP_NAME, BOT_FILL_RANGE},
     message::Message,
-    menu::Menu,
-    hud::Hud,
+    menu::{Menu, MenuLayout},
+    hud::{Hud, Nameplate, MarkerIcon, compass_bearing},
+    control_point::DEFAULT_CAPTURE_TIME,
+    hud_layout::HudLayout,
+    hud_theme::HudTheme,
+    item::ItemKind,
     actor::Actor,
-    control_scheme::ControlScheme,
+    character::Team,
+    control_scheme::{ControlScheme, ControlButton},
+    bot::{BotDifficulty, BotWeaponSet},
+    settings::{Settings, WindowGeometry, Accessibility},
+    match_log::MatchLogger,
+    player::{PLAYER_NAME, remote_player_name},
+    player_stats::PlayerStats,
+    frame_profile::{FrameProfile, Stage},
+    debug_draw::DebugDrawFlags,
+    damage::{DamageMultipliers, HealthRegen, HIT_MULTIPLIER_NAMES, HEALTH_REGEN_NAMES},
+    net::{Server, ServerEvent, Client, ClientEvent, NetSimulation, DEFAULT_PORT, NET_SIMULATION_NAMES},
+    benchmark::{Benchmark, BENCHMARK_ARG, BENCHMARK_BOTS_ARG, BENCHMARK_BOTS, BENCHMARK_AIMBOT_INTERVAL},
+    console::{self, Console, CommandResult, CONSOLE_KEY},
 };
 use std::{
     sync::mpsc::{
@@ -39,7 +96,8 @@ use std::{
     },
     rc::Rc,
     fs::File,
-    path::Path,
+    path::{Path, PathBuf},
+    collections::HashSet,
     time::{
         Instant,
         self,
@@ -51,17 +109,21 @@ use std::{
 };
 use rg3d::{
     utils::translate_event,
+    window::Icon,
     core::{
         pool::Handle,
         visitor::{
             Visitor,
             VisitResult,
+            VisitError,
             Visit,
         },
         color::Color,
+        math::vec3::Vec3,
     },
     sound::{
         context::Context,
+        buffer::SoundBuffer,
         effects::{Effect, BaseEffect, EffectInput},
         source::{
             spatial::SpatialSourceBuilder,
@@ -80,7 +142,9 @@ use rg3d::{
     },
     event::{DeviceEvent, WindowEvent, ElementState, VirtualKeyCode, Event},
     event_loop::{EventLoop, ControlFlow},
+    dpi::{PhysicalSize, PhysicalPosition},
     engine::Engine,
+    scene::{Scene, node::Node},
 };
 use std::sync::{Arc, Mutex};
 use rg3d::engine::resource_manager::ResourceManager;
@@ -93,6 +157,84 @@ pub type Gui = UserInterface<(), StubNode>;
 pub type GuiMessage = UiMessage<(), StubNode>;
 pub type BuildContext<'a> = rg3d::gui::BuildContext<'a, (), StubNode>;
 
+/// Opponents in last man standing match are marked on HUD when this or less of them left.
+const MARKED_SURVIVORS_COUNT: usize = 2;
+/// Health below which closest medkit is marked on HUD.
+const LOW_HEALTH: f32 = 30.0;
+/// Nameplates start to fade at this distance from camera and disappear at max distance.
+const NAMEPLATE_FADE_DISTANCE: f32 = 15.0;
+const NAMEPLATE_MAX_DISTANCE: f32 = 40.0;
+pub const DEFAULT_BOT_SIGHT_RANGE: f32 = 50.0;
+pub const DEFAULT_SPAWN_PROTECTION: f32 = 2.0;
+/// Length of countdown before match starts, in seconds.
+pub const DEFAULT_INTRO_COUNTDOWN: f32 = 3.0;
+/// Time and frag limits of a match, zero limit is never reached.
+pub const DEFAULT_TIME_LIMIT_SECS: f32 = 600.0;
+pub const DEFAULT_FRAG_LIMIT: u32 = 30;
+/// Maximum amount of gameplay sounds playing at the same time, ambient sounds and music
+/// are not counted.
+pub const DEFAULT_MAX_SOUNDS: u32 = 32;
+pub const MAX_SOUNDS_RANGE: (u32, u32) = (8, 64);
+/// Music that is played when there is no level.
+const MENU_MUSIC: &[&str] = &["data/sounds/Antonio_Bizarro_Berzerker.ogg"];
+const DEFAULT_MUSIC_VOLUME: f32 = 0.25;
+/// Version of save file format, must be increased every time when saved data of any game
+/// entity changes. Saves with other version are refused to load.
+const SAVE_FORMAT_VERSION: u32 = 33;
+const WINDOW_TITLE: &str = "Rusty Shooter";
+const MAX_NOTE_LENGTH: usize = 100;
+const WINDOW_ICON_PATH: &str = "data/ui/icon.tga";
+/// Sleeps shorter than this are not precise on most systems, thread may wake up much later
+/// than asked, in seconds.
+const SLEEP_GRANULARITY: f64 = 0.002;
+
+/// Decodes uncompressed 32-bit TGA image into RGBA pixels with top-left origin, returns
+/// pixels with width and height. Icon is the only image that is decoded by the game itself,
+/// so other kinds of TGA are not supported.
+fn decode_tga(data: &[u8]) -> Result<(Vec<u8>, u32, u32), String> {
+    if data.len() < 18 {
+        return Err("file is too short".to_owned());
+    }
+    let id_length = data[0] as usize;
+    let image_type = data[2];
+    let width = u16::from_le_bytes([data[12], data[13]]) as usize;
+    let height = u16::from_le_bytes([data[14], data[15]]) as usize;
+    let bits_per_pixel = data[16];
+    let top_left_origin = data[17] & 0x20 != 0;
+    if data[1] != 0 || image_type != 2 || bits_per_pixel != 32 {
+        return Err("only uncompressed 32-bit images are supported".to_owned());
+    }
+    let pixels = data.get(18 + id_length..18 + id_length + width * height * 4)
+        .ok_or_else(|| "file is too short".to_owned())?;
+    let mut rgba = Vec::with_capacity(pixels.len());
+    for row in 0..height {
+        let row = if top_left_origin { row } else { height - 1 - row };
+        for bgra in pixels[row * width * 4..(row + 1) * width * 4].chunks(4) {
+            rgba.extend_from_slice(&[bgra[2], bgra[1], bgra[0], bgra[3]]);
+        }
+    }
+    Ok((rgba, width as u32, height as u32))
+}
+
+/// Loads image for window icon, None is returned if there is no such file or it can't be
+/// decoded - window will have default icon then.
+fn load_window_icon(path: &str) -> Option<Icon> {
+    let (rgba, width, height) = match std::fs::read(path).map_err(|e| e.to_string()).and_then(|data| decode_tga(&data)) {
+        Ok(image) => image,
+        Err(e) => {
+            println!("Unable to load window icon {}, default will be used. Reason: {}", path, e);
+            return None;
+        }
+    };
+    match Icon::from_rgba(rgba, width, height) {
+        Ok(icon) => Some(icon),
+        Err(e) => {
+            println!("Unable to create window icon from {}. Reason: {:?}", path, e);
+            None
+        }
+    }
+}
+
 pub struct Game {
     menu: Menu,
     hud: Hud,
@@ -107,6 +249,30 @@ pub struct Game {
     events_receiver: Receiver<Message>,
     events_sender: Sender<Message>,
     sound_manager: SoundManager,
+    /// Options of last started match, used to restart a match.
+    last_match_options: Option<MatchOptions>,
+    /// Per-stage frame timings, toggled by F3.
+    frame_profile: FrameProfile,
+    /// Categories of debug geometry, toggled by F4 and `set debug_*` commands.
+    debug_draw: DebugDrawFlags,
+    /// Set only when game was started in benchmark mode, game exits when it is finished.
+    benchmark: Option<Benchmark>,
+    settings: Settings,
+    /// Lifetime stats of the player, updated when a match ends.
+    player_stats: PlayerStats,
+    /// Whether game window has input focus, sounds are paused when window loses focus.
+    focused: bool,
+    /// Text of note that player types right now, None if player does not type.
+    note_input: Option<String>,
+    console: Console,
+    /// Listen server of current match, None if match is not hosted.
+    server: Option<Server>,
+    /// Connection to server whose match is shown by replica level, None in local matches.
+    client: Option<Client>,
+    /// Not saved, debug tool. Bad network that is simulated for server and client.
+    net_simulation: NetSimulation,
+    /// Last pressed key, character that comes after it becomes its label.
+    last_key: Option<ControlButton>,
 }
 
 #[derive(Copy, Clone)]
@@ -116,6 +282,31 @@ pub struct GameTime {
     delta: f32,
 }
 
+/// Frame rate with which per-frame blend factors were tuned.
+pub const REFERENCE_FPS: f32 = 60.0;
+
+/// Rate of game simulation in Hz. Frames are drawn once per tick without interpolation and
+/// physics is tuned for this rate, so it is fixed.
+const TICK_RATE: u32 = 60;
+
+/// Converts blend factor that was tuned for one frame at reference frame rate into factor for
+/// frame of given duration, so smoothing converges equally fast with any time step.
+pub fn frame_blend_factor(per_frame: f32, dt: f32) -> f32 {
+    1.0 - (1.0 - per_frame).powf(dt * REFERENCE_FPS)
+}
+
+/// Instantiates model from given file. If model cannot be loaded, a message is printed and an
+/// empty pivot node is created instead so game can continue without the asset.
+pub fn instantiate_model<P: AsRef<Path>>(resource_manager: &mut ResourceManager, path: P, scene: &mut Scene) -> Handle<Node> {
+    match resource_manager.request_model(path.as_ref()) {
+        Some(model) => model.lock().unwrap().instantiate_geometry(scene),
+        None => {
+            println!("Unable to load model {:?}! Empty placeholder will be used instead.", path.as_ref());
+            scene.graph.add_node(Node::Base(Default::default()))
+        }
+    }
+}
+
 // Disable false-positive lint, isize *is* portable.
 #[allow(clippy::enum_clike_unportable_variant)]
 pub enum CollisionGroups {
@@ -129,6 +320,19 @@ pub enum CollisionGroups {
 pub struct DeathMatch {
     pub time_limit_secs: f32,
     pub frag_limit: u32,
+    pub bot_difficulty: BotDifficulty,
+    pub bot_weapons: BotWeaponSet,
+    pub bots_fight_each_other: bool,
+    /// Distance in meters at which bots of normal difficulty can see targets in lit areas.
+    pub bot_sight_range: f32,
+    pub damage_multipliers: DamageMultipliers,
+    /// Time in seconds after spawn during which actor takes no damage, zero disables it.
+    pub spawn_protection: f32,
+    /// Whether strength of bots is adjusted during the match to keep it close.
+    pub auto_balance: bool,
+    pub health_regen: HealthRegen,
+    /// Time in seconds of countdown before match starts, zero skips countdown.
+    pub intro_countdown: f32,
 }
 
 impl Default for DeathMatch {
@@ -136,6 +340,15 @@ impl Default for DeathMatch {
         Self {
             time_limit_secs: Default::default(),
             frag_limit: 0,
+            bot_difficulty: Default::default(),
+            bot_weapons: Default::default(),
+            bots_fight_each_other: true,
+            bot_sight_range: DEFAULT_BOT_SIGHT_RANGE,
+            damage_multipliers: Default::default(),
+            spawn_protection: DEFAULT_SPAWN_PROTECTION,
+            auto_balance: false,
+            health_regen: Default::default(),
+            intro_countdown: DEFAULT_INTRO_COUNTDOWN,
         }
     }
 }
@@ -146,6 +359,15 @@ impl Visit for DeathMatch {
 
         self.time_limit_secs.visit("TimeLimit", visitor)?;
         self.frag_limit.visit("FragLimit", visitor)?;
+        self.bot_difficulty.visit("BotDifficulty", visitor)?;
+        self.bot_weapons.visit("BotWeapons", visitor)?;
+        self.bots_fight_each_other.visit("BotsFightEachOther", visitor)?;
+        self.bot_sight_range.visit("BotSightRange", visitor)?;
+        self.damage_multipliers.visit("DamageMultipliers", visitor)?;
+        self.spawn_protection.visit("SpawnProtection", visitor)?;
+        self.auto_balance.visit("AutoBalance", visitor)?;
+        self.health_regen.visit("HealthRegen", visitor)?;
+        self.intro_countdown.visit("IntroCountdown", visitor)?;
 
         visitor.leave_region()
     }
@@ -155,6 +377,21 @@ impl Visit for DeathMatch {
 pub struct TeamDeathMatch {
     pub time_limit_secs: f32,
     pub team_frag_limit: u32,
+    pub bot_difficulty: BotDifficulty,
+    pub bot_weapons: BotWeaponSet,
+    pub bots_fight_each_other: bool,
+    /// Distance in meters at which bots of normal difficulty can see targets in lit areas.
+    pub bot_sight_range: f32,
+    pub damage_multipliers: DamageMultipliers,
+    /// Time in seconds after spawn during which actor takes no damage, zero disables it.
+    pub spawn_protection: f32,
+    /// Whether strength of bots is adjusted during the match to keep it close.
+    pub auto_balance: bool,
+    /// Whether bots and waiting players are moved between teams to keep teams even.
+    pub balance_teams: bool,
+    pub health_regen: HealthRegen,
+    /// Time in seconds of countdown before match starts, zero skips countdown.
+    pub intro_countdown: f32,
 }
 
 impl Default for TeamDeathMatch {
@@ -162,6 +399,16 @@ impl Default for TeamDeathMatch {
         Self {
             time_limit_secs: Default::default(),
             team_frag_limit: 0,
+            bot_difficulty: Default::default(),
+            bot_weapons: Default::default(),
+            bots_fight_each_other: true,
+            bot_sight_range: DEFAULT_BOT_SIGHT_RANGE,
+            damage_multipliers: Default::default(),
+            spawn_protection: DEFAULT_SPAWN_PROTECTION,
+            auto_balance: false,
+            balance_teams: true,
+            health_regen: Default::default(),
+            intro_countdown: DEFAULT_INTRO_COUNTDOWN,
         }
     }
 }
@@ -172,6 +419,16 @@ impl Visit for TeamDeathMatch {
 
         self.time_limit_secs.visit("TimeLimit", visitor)?;
         self.team_frag_limit.visit("TeamFragLimit", visitor)?;
+        self.bot_difficulty.visit("BotDifficulty", visitor)?;
+        self.bot_weapons.visit("BotWeapons", visitor)?;
+        self.bots_fight_each_other.visit("BotsFightEachOther", visitor)?;
+        self.bot_sight_range.visit("BotSightRange", visitor)?;
+        self.damage_multipliers.visit("DamageMultipliers", visitor)?;
+        self.spawn_protection.visit("SpawnProtection", visitor)?;
+        self.auto_balance.visit("AutoBalance", visitor)?;
+        self.balance_teams.visit("BalanceTeams", visitor)?;
+        self.health_regen.visit("HealthRegen", visitor)?;
+        self.intro_countdown.visit("IntroCountdown", visitor)?;
 
         visitor.leave_region()
     }
@@ -181,6 +438,21 @@ impl Visit for TeamDeathMatch {
 pub struct CaptureTheFlag {
     pub time_limit_secs: f32,
     pub flag_limit: u32,
+    pub bot_difficulty: BotDifficulty,
+    pub bot_weapons: BotWeaponSet,
+    pub bots_fight_each_other: bool,
+    /// Distance in meters at which bots of normal difficulty can see targets in lit areas.
+    pub bot_sight_range: f32,
+    pub damage_multipliers: DamageMultipliers,
+    /// Time in seconds after spawn during which actor takes no damage, zero disables it.
+    pub spawn_protection: f32,
+    /// Whether strength of bots is adjusted during the match to keep it close.
+    pub auto_balance: bool,
+    /// Whether bots and waiting players are moved between teams to keep teams even.
+    pub balance_teams: bool,
+    pub health_regen: HealthRegen,
+    /// Time in seconds of countdown before match starts, zero skips countdown.
+    pub intro_countdown: f32,
 }
 
 impl Default for CaptureTheFlag {
@@ -188,6 +460,16 @@ impl Default for CaptureTheFlag {
         Self {
             time_limit_secs: Default::default(),
             flag_limit: 0,
+            bot_difficulty: Default::default(),
+            bot_weapons: Default::default(),
+            bots_fight_each_other: true,
+            bot_sight_range: DEFAULT_BOT_SIGHT_RANGE,
+            damage_multipliers: Default::default(),
+            spawn_protection: DEFAULT_SPAWN_PROTECTION,
+            auto_balance: false,
+            balance_teams: true,
+            health_regen: Default::default(),
+            intro_countdown: DEFAULT_INTRO_COUNTDOWN,
         }
     }
 }
@@ -198,6 +480,169 @@ impl Visit for CaptureTheFlag {
 
         self.time_limit_secs.visit("TimeLimit", visitor)?;
         self.flag_limit.visit("FlagLimit", visitor)?;
+        self.bot_difficulty.visit("BotDifficulty", visitor)?;
+        self.bot_weapons.visit("BotWeapons", visitor)?;
+        self.bots_fight_each_other.visit("BotsFightEachOther", visitor)?;
+        self.bot_sight_range.visit("BotSightRange", visitor)?;
+        self.damage_multipliers.visit("DamageMultipliers", visitor)?;
+        self.spawn_protection.visit("SpawnProtection", visitor)?;
+        self.auto_balance.visit("AutoBalance", visitor)?;
+        self.balance_teams.visit("BalanceTeams", visitor)?;
+        self.health_regen.visit("HealthRegen", visitor)?;
+        self.intro_countdown.visit("IntroCountdown", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Teams capture and hold control points, every held point adds to team score over time,
+/// see `control_point` module.
+#[derive(Copy, Clone, Debug)]
+pub struct Domination {
+    pub time_limit_secs: f32,
+    /// Team score that wins the match, zero means no limit.
+    pub score_limit: u32,
+    /// Time in seconds that a team needs to capture a point uncontested.
+    pub capture_time: f32,
+    pub bot_difficulty: BotDifficulty,
+    pub bot_weapons: BotWeaponSet,
+    pub bots_fight_each_other: bool,
+    /// Distance in meters at which bots of normal difficulty can see targets in lit areas.
+    pub bot_sight_range: f32,
+    pub damage_multipliers: DamageMultipliers,
+    /// Time in seconds after spawn during which actor takes no damage, zero disables it.
+    pub spawn_protection: f32,
+    /// Whether strength of bots is adjusted during the match to keep it close.
+    pub auto_balance: bool,
+    /// Whether bots and waiting players are moved between teams to keep teams even.
+    pub balance_teams: bool,
+    pub health_regen: HealthRegen,
+    /// Time in seconds of countdown before match starts, zero skips countdown.
+    pub intro_countdown: f32,
+}
+
+impl Default for Domination {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: Default::default(),
+            score_limit: 0,
+            capture_time: DEFAULT_CAPTURE_TIME,
+            bot_difficulty: Default::default(),
+            bot_weapons: Default::default(),
+            bots_fight_each_other: true,
+            bot_sight_range: DEFAULT_BOT_SIGHT_RANGE,
+            damage_multipliers: Default::default(),
+            spawn_protection: DEFAULT_SPAWN_PROTECTION,
+            auto_balance: false,
+            balance_teams: true,
+            health_regen: Default::default(),
+            intro_countdown: DEFAULT_INTRO_COUNTDOWN,
+        }
+    }
+}
+
+impl Visit for Domination {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.time_limit_secs.visit("TimeLimit", visitor)?;
+        self.score_limit.visit("ScoreLimit", visitor)?;
+        self.capture_time.visit("CaptureTime", visitor)?;
+        self.bot_difficulty.visit("BotDifficulty", visitor)?;
+        self.bot_weapons.visit("BotWeapons", visitor)?;
+        self.bots_fight_each_other.visit("BotsFightEachOther", visitor)?;
+        self.bot_sight_range.visit("BotSightRange", visitor)?;
+        self.damage_multipliers.visit("DamageMultipliers", visitor)?;
+        self.spawn_protection.visit("SpawnProtection", visitor)?;
+        self.auto_balance.visit("AutoBalance", visitor)?;
+        self.balance_teams.visit("BalanceTeams", visitor)?;
+        self.health_regen.visit("HealthRegen", visitor)?;
+        self.intro_countdown.visit("IntroCountdown", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Every participant has limited amount of lives, participant that lost all lives is
+/// eliminated and won't respawn. Last participant alive wins.
+#[derive(Copy, Clone, Debug)]
+pub struct LastManStanding {
+    pub time_limit_secs: f32,
+    pub lives: u32,
+    pub bot_difficulty: BotDifficulty,
+    pub bot_weapons: BotWeaponSet,
+    pub bots_fight_each_other: bool,
+    /// Distance in meters at which bots of normal difficulty can see targets in lit areas.
+    pub bot_sight_range: f32,
+    pub damage_multipliers: DamageMultipliers,
+    /// Time in seconds after spawn during which actor takes no damage, zero disables it.
+    pub spawn_protection: f32,
+    /// Whether strength of bots is adjusted during the match to keep it close.
+    pub auto_balance: bool,
+    pub health_regen: HealthRegen,
+    /// Time in seconds of countdown before match starts, zero skips countdown.
+    pub intro_countdown: f32,
+}
+
+impl Default for LastManStanding {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: Default::default(),
+            lives: 3,
+            bot_difficulty: Default::default(),
+            bot_weapons: Default::default(),
+            bots_fight_each_other: true,
+            bot_sight_range: DEFAULT_BOT_SIGHT_RANGE,
+            damage_multipliers: Default::default(),
+            spawn_protection: DEFAULT_SPAWN_PROTECTION,
+            auto_balance: false,
+            health_regen: Default::default(),
+            intro_countdown: DEFAULT_INTRO_COUNTDOWN,
+        }
+    }
+}
+
+impl Visit for LastManStanding {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.time_limit_secs.visit("TimeLimit", visitor)?;
+        self.lives.visit("Lives", visitor)?;
+        self.bot_difficulty.visit("BotDifficulty", visitor)?;
+        self.bot_weapons.visit("BotWeapons", visitor)?;
+        self.bots_fight_each_other.visit("BotsFightEachOther", visitor)?;
+        self.bot_sight_range.visit("BotSightRange", visitor)?;
+        self.damage_multipliers.visit("DamageMultipliers", visitor)?;
+        self.spawn_protection.visit("SpawnProtection", visitor)?;
+        self.auto_balance.visit("AutoBalance", visitor)?;
+        self.health_regen.visit("HealthRegen", visitor)?;
+        self.intro_countdown.visit("IntroCountdown", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Practice mode without bots, player shoots at targets and sees accuracy and reaction
+/// time, see `target_range` module.
+#[derive(Copy, Clone, Debug)]
+pub struct TargetRange {
+    /// Zero means that practice goes on until player leaves.
+    pub time_limit_secs: f32,
+}
+
+impl Default for TargetRange {
+    fn default() -> Self {
+        Self {
+            time_limit_secs: 0.0,
+        }
+    }
+}
+
+impl Visit for TargetRange {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.time_limit_secs.visit("TimeLimit", visitor)?;
 
         visitor.leave_region()
     }
@@ -208,6 +653,9 @@ pub enum MatchOptions {
     DeathMatch(DeathMatch),
     TeamDeathMatch(TeamDeathMatch),
     CaptureTheFlag(CaptureTheFlag),
+    LastManStanding(LastManStanding),
+    TargetRange(TargetRange),
+    Domination(Domination),
 }
 
 impl MatchOptions {
@@ -216,6 +664,9 @@ impl MatchOptions {
             0 => Ok(MatchOptions::DeathMatch(Default::default())),
             1 => Ok(MatchOptions::TeamDeathMatch(Default::default())),
             2 => Ok(MatchOptions::CaptureTheFlag(Default::default())),
+            3 => Ok(MatchOptions::LastManStanding(Default::default())),
+            4 => Ok(MatchOptions::TargetRange(Default::default())),
+            5 => Ok(MatchOptions::Domination(Default::default())),
             _ => Err(format!("Invalid match options {}", id))
         }
     }
@@ -225,6 +676,208 @@ impl MatchOptions {
             MatchOptions::DeathMatch(_) => 0,
             MatchOptions::TeamDeathMatch(_) => 1,
             MatchOptions::CaptureTheFlag(_) => 2,
+            MatchOptions::LastManStanding(_) => 3,
+            MatchOptions::TargetRange(_) => 4,
+            MatchOptions::Domination(_) => 5,
+        }
+    }
+
+    pub fn bot_difficulty(&self) -> BotDifficulty {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.bot_difficulty,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.bot_difficulty,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.bot_difficulty,
+            MatchOptions::LastManStanding(lms) => lms.bot_difficulty,
+            MatchOptions::Domination(dom) => dom.bot_difficulty,
+            MatchOptions::TargetRange(_) => Default::default(),
+        }
+    }
+
+    pub fn bot_weapons(&self) -> BotWeaponSet {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.bot_weapons,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.bot_weapons,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.bot_weapons,
+            MatchOptions::LastManStanding(lms) => lms.bot_weapons,
+            MatchOptions::Domination(dom) => dom.bot_weapons,
+            MatchOptions::TargetRange(_) => Default::default(),
+        }
+    }
+
+    pub fn bots_fight_each_other(&self) -> bool {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.bots_fight_each_other,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.bots_fight_each_other,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.bots_fight_each_other,
+            MatchOptions::LastManStanding(lms) => lms.bots_fight_each_other,
+            MatchOptions::Domination(dom) => dom.bots_fight_each_other,
+            MatchOptions::TargetRange(_) => false,
+        }
+    }
+
+    pub fn bot_sight_range(&self) -> f32 {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.bot_sight_range,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.bot_sight_range,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.bot_sight_range,
+            MatchOptions::LastManStanding(lms) => lms.bot_sight_range,
+            MatchOptions::Domination(dom) => dom.bot_sight_range,
+            MatchOptions::TargetRange(_) => DEFAULT_BOT_SIGHT_RANGE,
+        }
+    }
+
+    pub fn damage_multipliers(&self) -> DamageMultipliers {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.damage_multipliers,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.damage_multipliers,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.damage_multipliers,
+            MatchOptions::LastManStanding(lms) => lms.damage_multipliers,
+            MatchOptions::Domination(dom) => dom.damage_multipliers,
+            MatchOptions::TargetRange(_) => Default::default(),
+        }
+    }
+
+    pub fn spawn_protection(&self) -> f32 {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.spawn_protection,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.spawn_protection,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.spawn_protection,
+            MatchOptions::LastManStanding(lms) => lms.spawn_protection,
+            MatchOptions::Domination(dom) => dom.spawn_protection,
+            MatchOptions::TargetRange(_) => 0.0,
+        }
+    }
+
+    pub fn auto_balance(&self) -> bool {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.auto_balance,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.auto_balance,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.auto_balance,
+            MatchOptions::LastManStanding(lms) => lms.auto_balance,
+            MatchOptions::Domination(dom) => dom.auto_balance,
+            MatchOptions::TargetRange(_) => false,
+        }
+    }
+
+    pub fn health_regen(&self) -> HealthRegen {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.health_regen,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.health_regen,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.health_regen,
+            MatchOptions::LastManStanding(lms) => lms.health_regen,
+            MatchOptions::Domination(dom) => dom.health_regen,
+            MatchOptions::TargetRange(_) => Default::default(),
+        }
+    }
+
+    /// Whether teams are kept even, only team modes have teams.
+    pub fn balance_teams(&self) -> bool {
+        match self {
+            MatchOptions::TeamDeathMatch(tdm) => tdm.balance_teams,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.balance_teams,
+            MatchOptions::Domination(dom) => dom.balance_teams,
+            _ => false,
+        }
+    }
+
+    pub fn time_limit_secs(&self) -> f32 {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.time_limit_secs,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.time_limit_secs,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.time_limit_secs,
+            MatchOptions::LastManStanding(lms) => lms.time_limit_secs,
+            MatchOptions::TargetRange(range) => range.time_limit_secs,
+            MatchOptions::Domination(dom) => dom.time_limit_secs,
+        }
+    }
+
+    /// Amount of kills that wins a match, None if match has no frag limit.
+    pub fn frag_limit(&self) -> Option<u32> {
+        match self {
+            MatchOptions::DeathMatch(dm) if dm.frag_limit > 0 => Some(dm.frag_limit),
+            _ => None,
+        }
+    }
+
+    pub fn intro_countdown(&self) -> f32 {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.intro_countdown,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.intro_countdown,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.intro_countdown,
+            MatchOptions::LastManStanding(lms) => lms.intro_countdown,
+            MatchOptions::TargetRange(_) => 0.0,
+            MatchOptions::Domination(dom) => dom.intro_countdown,
+        }
+    }
+
+    pub fn set_damage_multipliers(&mut self, damage_multipliers: DamageMultipliers) {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.damage_multipliers = damage_multipliers,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.damage_multipliers = damage_multipliers,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.damage_multipliers = damage_multipliers,
+            MatchOptions::LastManStanding(lms) => lms.damage_multipliers = damage_multipliers,
+            MatchOptions::Domination(dom) => dom.damage_multipliers = damage_multipliers,
+            MatchOptions::TargetRange(_) => (),
+        }
+    }
+
+    pub fn set_health_regen(&mut self, health_regen: HealthRegen) {
+        match self {
+            MatchOptions::DeathMatch(dm) => dm.health_regen = health_regen,
+            MatchOptions::TeamDeathMatch(tdm) => tdm.health_regen = health_regen,
+            MatchOptions::CaptureTheFlag(ctf) => ctf.health_regen = health_regen,
+            MatchOptions::LastManStanding(lms) => lms.health_regen = health_regen,
+            MatchOptions::Domination(dom) => dom.health_regen = health_regen,
+            MatchOptions::TargetRange(_) => (),
+        }
+    }
+
+    /// Name of the mode that is shown to player.
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            MatchOptions::DeathMatch(_) => "Deathmatch",
+            MatchOptions::TeamDeathMatch(_) => "Team Deathmatch",
+            MatchOptions::CaptureTheFlag(_) => "Capture The Flag",
+            MatchOptions::LastManStanding(_) => "Last Man Standing",
+            MatchOptions::TargetRange(_) => "Target Range",
+            MatchOptions::Domination(_) => "Domination",
+        }
+    }
+
+    /// Short machine-readable name of the mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            MatchOptions::DeathMatch(_) => "deathmatch",
+            MatchOptions::TeamDeathMatch(_) => "team_deathmatch",
+            MatchOptions::CaptureTheFlag(_) => "capture_the_flag",
+            MatchOptions::LastManStanding(_) => "last_man_standing",
+            MatchOptions::TargetRange(_) => "target_range",
+            MatchOptions::Domination(_) => "domination",
+        }
+    }
+
+    /// Returns amount of lives of each participant, None means that lives are unlimited.
+    pub fn lives(&self) -> Option<u32> {
+        match self {
+            MatchOptions::LastManStanding(lms) => Some(lms.lives),
+            _ => None,
+        }
+    }
+
+    /// Practice has no opponents, so it is not counted in lifetime stats of player.
+    pub fn is_practice(&self) -> bool {
+        matches!(self, MatchOptions::TargetRange(_))
+    }
+
+    pub fn is_team_mode(&self) -> bool {
+        matches!(self, MatchOptions::TeamDeathMatch(_) | MatchOptions::CaptureTheFlag(_) | MatchOptions::Domination(_))
+    }
+
+    /// Time to capture a control point, None if mode has no control points.
+    pub fn capture_time(&self) -> Option<f32> {
+        match self {
+            MatchOptions::Domination(dom) => Some(dom.capture_time),
+            _ => None,
         }
     }
 }
@@ -248,72 +901,361 @@ impl Visit for MatchOptions {
             MatchOptions::DeathMatch(o) => o.visit("Data", visitor)?,
             MatchOptions::TeamDeathMatch(o) => o.visit("Data", visitor)?,
             MatchOptions::CaptureTheFlag(o) => o.visit("Data", visitor)?,
+            MatchOptions::LastManStanding(o) => o.visit("Data", visitor)?,
+            MatchOptions::TargetRange(o) => o.visit("Data", visitor)?,
+            MatchOptions::Domination(o) => o.visit("Data", visitor)?,
         }
 
         visitor.leave_region()
     }
 }
 
+/// Part of gameplay sound volume that player loses when flashbang goes off right in front
+/// of eyes.
+const FLASH_DEAFNESS: f32 = 0.85;
+/// Speed at which hearing recovers after flashbang, in part of volume per second.
+const HEARING_RECOVERY_RATE: f32 = 0.2;
+
+/// Importance of gameplay sound. When too many sounds are playing, the least important one
+/// is stopped to make room for a new sound, from equally important ones the farthest from
+/// listener is stopped.
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum SoundPriority {
+    Low,
+    Normal,
+    High,
+}
+
 pub struct SoundManager {
     context: Arc<Mutex<Context>>,
+    /// Source of current track of the playlist.
     music: Handle<SoundSource>,
+    playlist: Vec<String>,
+    track: usize,
+    /// Not saved, it is a user setting.
+    music_volume: f32,
     reverb: Handle<Effect>,
+    /// Sources that were playing when sound was paused, they will continue playing from the
+    /// same position when sound is resumed.
+    paused_sources: Vec<Handle<SoundSource>>,
+    paused: bool,
+    /// Looping sounds placed on level.
+    ambient_sources: Vec<Handle<SoundSource>>,
+    /// Gain multiplier for gameplay sounds, it is defined by current reverb zone.
+    gameplay_gain: f32,
+    /// Not saved, gameplay sounds that may still be playing with their priority and position.
+    gameplay_sources: Vec<(Handle<SoundSource>, SoundPriority, Vec3)>,
+    /// Not saved, it is a user setting.
+    max_sounds: u32,
+    /// Not saved, part of gameplay sound volume that player hears. It drops when player is
+    /// flashed and recovers over time.
+    hearing: f32,
+    /// Not saved, sounds that failed to load. They are reported once and not requested again,
+    /// some sounds are played many times per second.
+    missing_sounds: HashSet<PathBuf>,
 }
 
 impl SoundManager {
-    pub fn new(context: Arc<Mutex<Context>>, resource_manager: &mut ResourceManager) -> Self {
-        let buffer = resource_manager.request_sound_buffer("data/sounds/Antonio_Bizarro_Berzerker.ogg", true).unwrap();
-        let music = context.lock()
-            .unwrap()
-            .add_source(GenericSourceBuilder::new(buffer)
-                .with_looping(true)
-                .with_status(Status::Playing)
-                .with_gain(0.25)
-                .build_source()
-                .unwrap());
-
+    pub fn new(context: Arc<Mutex<Context>>, resource_manager: &mut ResourceManager, max_sounds: u32) -> Self {
         let mut base_effect = BaseEffect::default();
         base_effect.set_gain(0.7);
         let mut reverb = rg3d::sound::effects::reverb::Reverb::new(base_effect);
-        reverb.set_decay_time(Duration::from_secs_f32(3.0));
+        reverb.set_decay_time(Duration::from_secs_f32(ReverbPreset::Default.decay_time()));
         let reverb = context.lock()
             .unwrap()
             .add_effect(rg3d::sound::effects::Effect::Reverb(reverb));
 
-        Self {
+        let mut sound_manager = Self {
             context,
-            music,
+            music: Handle::NONE,
+            playlist: Default::default(),
+            track: 0,
+            music_volume: DEFAULT_MUSIC_VOLUME,
             reverb,
-        }
+            paused_sources: Default::default(),
+            paused: false,
+            ambient_sources: Default::default(),
+            gameplay_gain: ReverbPreset::Default.gain(),
+            gameplay_sources: Default::default(),
+            max_sounds,
+            hearing: 1.0,
+            missing_sounds: Default::default(),
+        };
+        sound_manager.set_playlist(resource_manager, MENU_MUSIC);
+        sound_manager
     }
 
     pub fn handle_message(&mut self, resource_manager: &mut ResourceManager, message: &Message) {
         let mut context = self.context.lock().unwrap();
 
         match message {
-            Message::PlaySound { path, position, gain, rolloff_factor, radius } => {
-                let shot_buffer = resource_manager.request_sound_buffer(path, false).unwrap();
-                let shot_sound = SpatialSourceBuilder::new(
-                    GenericSourceBuilder::new(shot_buffer)
-                        .with_status(Status::Playing)
-                        .with_play_once(true)
-                        .with_gain(*gain)
-                        .build()
-                        .unwrap())
-                    .with_position(*position)
-                    .with_radius(*radius)
-                    .with_rolloff_factor(*rolloff_factor)
-                    .build_source();
-                let source = context.add_source(shot_sound);
-                context.effect_mut(self.reverb).add_input(EffectInput::direct(source));
+            // New sounds are dropped while sound is paused, otherwise they would be heard
+            // when game is in background.
+            Message::PlaySound { .. } if self.paused => {}
+            Message::PlaySound { path, position, gain, rolloff_factor, radius, priority } => {
+                // Sound is loaded before room is made for it, otherwise a playing sound could
+                // be stopped for a sound that won't play.
+                let shot_buffer = match Self::request_sound_buffer(&mut self.missing_sounds, resource_manager, path) {
+                    Some(shot_buffer) => shot_buffer,
+                    None => return,
+                };
+                if Self::make_room(&mut self.gameplay_sources, self.max_sounds, &mut context, *priority, *position) {
+                    let shot_sound = SpatialSourceBuilder::new(
+                        GenericSourceBuilder::new(shot_buffer)
+                            .with_status(Status::Playing)
+                            .with_play_once(true)
+                            .with_gain(*gain * self.gameplay_gain * self.hearing)
+                            .build()
+                            .unwrap())
+                        .with_position(*position)
+                        .with_radius(*radius)
+                        .with_rolloff_factor(*rolloff_factor)
+                        .build_source();
+                    let source = context.add_source(shot_sound);
+                    context.effect_mut(self.reverb).add_input(EffectInput::direct(source));
+                    self.gameplay_sources.push((source, *priority, *position));
+                }
             }
             Message::SetMusicVolume { volume } => {
-                context.source_mut(self.music)
-                    .set_gain(*volume);
+                self.music_volume = *volume;
+                if self.music.is_some() {
+                    context.source_mut(self.music)
+                        .set_gain(*volume);
+                }
+            }
+            Message::AddAmbientSound { path, position } => {
+                if let Some(buffer) = Self::request_sound_buffer(&mut self.missing_sounds, resource_manager, path) {
+                    let ambient_sound = SpatialSourceBuilder::new(
+                        GenericSourceBuilder::new(buffer)
+                            .with_status(Status::Playing)
+                            .with_looping(true)
+                            .with_gain(0.5)
+                            .build()
+                            .unwrap())
+                        .with_position(*position)
+                        .with_radius(5.0)
+                        .with_rolloff_factor(2.0)
+                        .build_source();
+                    let source = context.add_source(ambient_sound);
+                    self.ambient_sources.push(source);
+                }
+            }
+            &Message::SetReverbPreset { preset } => {
+                Self::apply_reverb_preset(&mut context, self.reverb, preset);
+                self.gameplay_gain = preset.gain();
+            }
+            &Message::SetMaxSounds { count } => {
+                self.max_sounds = count;
+            }
+            &Message::FlashPlayer { strength } => {
+                let hearing = 1.0 - FLASH_DEAFNESS * strength;
+                if hearing < self.hearing {
+                    Self::scale_gameplay_sources(&self.gameplay_sources, &mut context, hearing / self.hearing);
+                    self.hearing = hearing;
+                }
             }
             _ => {}
         }
     }
+
+    /// Requests buffer of a sound, sound that failed to load once is not requested again and
+    /// only its first failure is reported.
+    fn request_sound_buffer(missing_sounds: &mut HashSet<PathBuf>,
+                            resource_manager: &mut ResourceManager,
+                            path: &Path,
+    ) -> Option<Arc<Mutex<SoundBuffer>>> {
+        if missing_sounds.contains(path) {
+            return None;
+        }
+        let buffer = resource_manager.request_sound_buffer(path, false);
+        if buffer.is_none() {
+            println!("Unable to load sound {:?}! It won't be played.", path);
+            missing_sounds.insert(path.to_owned());
+        }
+        buffer
+    }
+
+    /// Multiplies volume of gameplay sounds that are playing now, used when hearing changes.
+    fn scale_gameplay_sources(sources: &[(Handle<SoundSource>, SoundPriority, Vec3)], context: &mut Context, scale: f32) {
+        for &(source, _, _) in sources.iter() {
+            // Play-once sources are removed by context when they're done.
+            if context.sources().is_valid_handle(source) {
+                let generic = context.source_mut(source).generic_mut();
+                let gain = generic.gain();
+                generic.set_gain(gain * scale);
+            }
+        }
+    }
+
+    /// Restores hearing after flashbang.
+    pub fn update_hearing(&mut self, dt: f32) {
+        if self.hearing < 1.0 {
+            let hearing = (self.hearing + HEARING_RECOVERY_RATE * dt).min(1.0);
+            let mut context = self.context.lock().unwrap();
+            Self::scale_gameplay_sources(&self.gameplay_sources, &mut context, hearing / self.hearing);
+            self.hearing = hearing;
+        }
+    }
+
+    /// Stops the least important gameplay sound if there are too many of them. Returns false
+    /// if every playing sound is more important than a new one, new sound is dropped then.
+    fn make_room(sources: &mut Vec<(Handle<SoundSource>, SoundPriority, Vec3)>,
+                 max_sounds: u32,
+                 context: &mut Context,
+                 priority: SoundPriority,
+                 position: Vec3,
+    ) -> bool {
+        // Play-once sources are removed by context when they're done.
+        sources.retain(|(source, _, _)| context.sources().is_valid_handle(*source));
+        if sources.len() < max_sounds as usize {
+            return true;
+        }
+        let listener = context.listener().position();
+        let least_important = sources
+            .iter()
+            .enumerate()
+            .min_by(|(_, (_, a_priority, a_position)), (_, (_, b_priority, b_position))| {
+                a_priority.cmp(b_priority).then_with(|| b_position
+                    .distance(&listener)
+                    .partial_cmp(&a_position.distance(&listener))
+                    .unwrap_or(std::cmp::Ordering::Equal))
+            })
+            .map(|(i, &(source, source_priority, _))| (i, source, source_priority));
+        match least_important {
+            Some((i, source, source_priority)) if source_priority <= priority => {
+                context.remove_source(source);
+                sources.swap_remove(i);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+impl SoundManager {
+    /// Removes every sound source except music. Sounds of a level are not bound to its scene,
+    /// so they must be removed explicitly when level is destroyed.
+    pub fn clear_level_sounds(&mut self) {
+        let music = self.music;
+        let mut context = self.context.lock().unwrap();
+        let sources = context.sources()
+            .pair_iter()
+            .map(|(handle, _)| handle)
+            .filter(|&handle| handle != music)
+            .collect::<Vec<_>>();
+        for source in sources {
+            context.remove_source(source);
+        }
+        self.paused_sources.retain(|&source| source == music);
+        self.ambient_sources.clear();
+        self.gameplay_sources.clear();
+        Self::apply_reverb_preset(&mut context, self.reverb, ReverbPreset::Default);
+        self.gameplay_gain = ReverbPreset::Default.gain();
+        self.hearing = 1.0;
+    }
+
+    /// Removes every sound source including music and forgets playlist, so nothing is cut off
+    /// in the middle of playback when sound device is closed. Used on exit, music can be
+    /// started again by setting new playlist.
+    pub fn shutdown(&mut self) {
+        let mut context = self.context.lock().unwrap();
+        let sources = context.sources()
+            .pair_iter()
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        for source in sources {
+            context.remove_source(source);
+        }
+        self.music = Handle::NONE;
+        self.playlist.clear();
+        self.track = 0;
+        self.paused_sources.clear();
+        self.ambient_sources.clear();
+        self.gameplay_sources.clear();
+    }
+
+    /// Switches music to given tracks, current track keeps playing if playlist is the same.
+    pub fn set_playlist(&mut self, resource_manager: &mut ResourceManager, tracks: &[&str]) {
+        if self.playlist.iter().map(|track| track.as_str()).eq(tracks.iter().cloned()) {
+            return;
+        }
+        self.playlist = tracks.iter().map(|&track| track.to_owned()).collect();
+        self.track = 0;
+        self.start_track(resource_manager);
+    }
+
+    /// Replaces music source with current track of the playlist, tracks that can't be loaded
+    /// are skipped. Single track is looped, otherwise every track is played once.
+    fn start_track(&mut self, resource_manager: &mut ResourceManager) {
+        let mut context = self.context.lock().unwrap();
+        let music = self.music;
+        if context.sources().is_valid_handle(music) {
+            context.remove_source(music);
+        }
+        self.paused_sources.retain(|&source| source != music);
+        self.music = Handle::NONE;
+        for _ in 0..self.playlist.len() {
+            let path = &self.playlist[self.track];
+            if let Some(buffer) = resource_manager.request_sound_buffer(path, true) {
+                // Music starts paused if it was switched while game is in background.
+                let status = if self.paused { Status::Paused } else { Status::Playing };
+                self.music = context.add_source(GenericSourceBuilder::new(buffer)
+                    .with_looping(self.playlist.len() == 1)
+                    .with_play_once(self.playlist.len() > 1)
+                    .with_status(status)
+                    .with_gain(self.music_volume)
+                    .build_source()
+                    .unwrap());
+                if self.paused {
+                    self.paused_sources.push(self.music);
+                }
+                return;
+            }
+            println!("Unable to load music {}! Track will be skipped.", path);
+            self.track = (self.track + 1) % self.playlist.len();
+        }
+    }
+
+    /// Starts next track of the playlist when current one has ended.
+    pub fn update_music(&mut self, resource_manager: &mut ResourceManager) {
+        // Play-once sources are removed by context when they're done.
+        let ended = self.music.is_some() && !self.context
+            .lock()
+            .unwrap()
+            .sources()
+            .is_valid_handle(self.music);
+        if ended {
+            self.track = (self.track + 1) % self.playlist.len();
+            self.start_track(resource_manager);
+        }
+    }
+
+    fn apply_reverb_preset(context: &mut Context, reverb: Handle<Effect>, preset: ReverbPreset) {
+        if let Effect::Reverb(reverb) = context.effect_mut(reverb) {
+            reverb.set_decay_time(Duration::from_secs_f32(preset.decay_time()));
+        }
+    }
+
+    /// Pauses every playing sound (including music) or resumes sounds paused previously.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        let mut context = self.context.lock().unwrap();
+        if paused {
+            for (handle, source) in context.sources_mut().pair_iter_mut() {
+                if source.generic().status() == Status::Playing {
+                    source.generic_mut().pause();
+                    self.paused_sources.push(handle);
+                }
+            }
+        } else {
+            for source in self.paused_sources.drain(..) {
+                // Source could be removed while sound was paused.
+                if context.sources().is_valid_handle(source) {
+                    context.source_mut(source).generic_mut().play();
+                }
+            }
+        }
+    }
 }
 
 impl Visit for SoundManager {
@@ -322,6 +1264,10 @@ impl Visit for SoundManager {
 
         self.reverb.visit("Reverb", visitor)?;
         self.music.visit("Music", visitor)?;
+        self.playlist.visit("Playlist", visitor)?;
+        self.track.visit("Track", visitor)?;
+        self.ambient_sources.visit("AmbientSources", visitor)?;
+        self.gameplay_gain.visit("GameplayGain", visitor)?;
 
         visitor.leave_region()
     }
@@ -331,49 +1277,81 @@ impl Game {
     pub fn run() {
         let events_loop = EventLoop::<()>::new();
 
-        let primary_monitor = events_loop.primary_monitor();
-        let mut monitor_dimensions = primary_monitor.size();
-        monitor_dimensions.height = (monitor_dimensions.height as f32 * 0.7) as u32;
-        monitor_dimensions.width = (monitor_dimensions.width as f32 * 0.7) as u32;
-        let inner_size = monitor_dimensions.to_logical::<f32>(primary_monitor.scale_factor());
+        let mut settings = Settings::load();
+        settings.validate();
+
+        let definitions_path = Path::new(definitions::DEFINITIONS_PATH);
+        if definitions_path.exists() {
+            match definitions::load(definitions_path) {
+                Ok(count) => println!("{} definition values overridden from {}", count, definitions_path.display()),
+                Err(e) => println!("Unable to load definitions, built-in ones will be used. Reason: {}", e),
+            }
+        }
 
         let window_builder = rg3d::window::WindowBuilder::new()
-            .with_title("Rusty Shooter")
-            .with_inner_size(inner_size)
+            .with_title(WINDOW_TITLE)
+            .with_window_icon(load_window_icon(WINDOW_ICON_PATH))
             .with_resizable(true);
 
+        // Window geometry of last run is used only if it still fits on one of monitors.
+        let window_geometry = settings.window_geometry.filter(|geometry| geometry.fits_on_any_monitor(&events_loop));
+        let window_builder = match window_geometry {
+            Some(geometry) => window_builder.with_inner_size(PhysicalSize::new(geometry.width, geometry.height)),
+            None => {
+                let primary_monitor = events_loop.primary_monitor();
+                let mut monitor_dimensions = primary_monitor.size();
+                monitor_dimensions.height = (monitor_dimensions.height as f32 * 0.7) as u32;
+                monitor_dimensions.width = (monitor_dimensions.width as f32 * 0.7) as u32;
+                let inner_size = monitor_dimensions.to_logical::<f32>(primary_monitor.scale_factor());
+                window_builder.with_inner_size(inner_size)
+            }
+        };
+
         let mut engine = GameEngine::new(window_builder, &events_loop).unwrap();
-        let hrtf_sphere = rg3d::sound::hrtf::HrtfSphere::new("data/sounds/IRC_1040_C.bin").unwrap();
-        engine.sound_context
-            .lock()
-            .unwrap()
-            .set_renderer(rg3d::sound::renderer::Renderer::HrtfRenderer(rg3d::sound::hrtf::HrtfRenderer::new(hrtf_sphere)));
+        if let Some(geometry) = window_geometry {
+            engine.get_window().set_outer_position(PhysicalPosition::new(geometry.x, geometry.y));
+        }
+        match rg3d::sound::hrtf::HrtfSphere::new("data/sounds/IRC_1040_C.bin") {
+            Ok(hrtf_sphere) => {
+                engine.sound_context
+                    .lock()
+                    .unwrap()
+                    .set_renderer(rg3d::sound::renderer::Renderer::HrtfRenderer(rg3d::sound::hrtf::HrtfRenderer::new(hrtf_sphere)));
+            }
+            Err(e) => println!("Unable to load HRTF sphere, default renderer will be used. Reason: {:?}", e),
+        }
 
         effects::register_custom_emitter_factory();
 
         engine.renderer.set_ambient_color(Color::opaque(60, 60, 60));
 
+        let mut quality_settings = engine.renderer.get_quality_settings();
+        settings.apply_to_quality_settings(&mut quality_settings);
+        if let Err(e) = engine.renderer.set_quality_settings(&quality_settings) {
+            println!("Failed to apply saved quality settings! Reason: {:?}", e);
+        }
 
-        let control_scheme = Rc::new(RefCell::new(ControlScheme::default()));
-
-        let fixed_fps = 60.0;
-        let fixed_timestep = 1.0 / fixed_fps;
+        let mut control_scheme = ControlScheme::default();
+        settings.apply_to_control_scheme(&mut control_scheme);
+        let control_scheme = Rc::new(RefCell::new(control_scheme));
 
         let time = GameTime {
             clock: Instant::now(),
             elapsed: 0.0,
-            delta: fixed_timestep,
+            delta: 1.0 / TICK_RATE as f32,
         };
 
         let (tx, rx) = mpsc::channel();
 
-        let sound_manager = SoundManager::new(engine.sound_context.clone(), &mut engine.resource_manager.lock().unwrap());
+        let sound_manager = SoundManager::new(engine.sound_context.clone(), &mut engine.resource_manager.lock().unwrap(), settings.max_sounds);
 
         let mut game = Game {
             sound_manager,
-            hud: Hud::new(&mut engine),
+            hud: Hud::new(&mut engine, settings.hud_scale, settings.hud_layout, settings.hud_theme, settings.accessibility, settings.crosshair, control_scheme.clone()),
             running: true,
-            menu: Menu::new(&mut engine, control_scheme.clone(), tx.clone()),
+            menu: Menu::new(&mut engine, control_scheme.clone(), tx.clone(), &settings),
+            // Console is built after menu, so it is drawn over menu.
+            console: Console::new(&mut engine.user_interface.build_ctx(), &settings.accessibility),
             control_scheme,
             debug_text: Handle::NONE,
             engine,
@@ -383,43 +1361,80 @@ impl Game {
             time,
             events_receiver: rx,
             events_sender: tx,
+            last_match_options: None,
+            frame_profile: Default::default(),
+            debug_draw: Default::default(),
+            benchmark: None,
+            settings,
+            player_stats: PlayerStats::load(),
+            focused: true,
+            note_input: None,
+            server: None,
+            client: None,
+            net_simulation: Default::default(),
+            last_key: None,
         };
 
         game.create_debug_ui();
+        game.hud.set_damage_feedback(&mut game.engine.user_interface, game.settings.damage_feedback);
+        game.hud.set_damage_numbers_enabled(&mut game.engine.user_interface, game.settings.damage_numbers);
+
+        if std::env::args().any(|arg| arg == BENCHMARK_ARG) {
+            game.start_benchmark();
+        }
 
         events_loop.run(move |event, _, control_flow| {
             game.process_input_event(&event);
 
             match event {
                 Event::MainEventsCleared => {
-                    let mut dt = game.time.clock.elapsed().as_secs_f64() - game.time.elapsed;
-                    while dt >= fixed_timestep as f64 {
-                        dt -= fixed_timestep as f64;
-                        game.time.elapsed += fixed_timestep as f64;
+                    // Benchmark simulates exactly one tick per frame, so every run does the
+                    // same work no matter how fast it goes.
+                    let mut dt = if game.benchmark.is_some() {
+                        game.time.delta as f64
+                    } else {
+                        game.time.clock.elapsed().as_secs_f64() - game.time.elapsed
+                    };
+                    let mut ticked = false;
+                    while dt >= game.time.delta as f64 {
+                        dt -= game.time.delta as f64;
+                        game.time.elapsed += game.time.delta as f64;
+                        ticked = true;
 
                         game.update(game.time);
 
                         while let Some(ui_event) = game.engine.user_interface.poll_message() {
-                            game.menu.handle_ui_event(&mut game.engine, &ui_event);
+                            game.menu.handle_ui_event(&mut game.engine, &ui_event, game.level.is_some());
                         }
                     }
                     if !game.running {
                         *control_flow = ControlFlow::Exit;
                     }
-                    game.engine.get_window().request_redraw();
+                    if game.is_low_latency() {
+                        // Frame is drawn right after the tick, so it shows input that was
+                        // handled just before the tick.
+                        if ticked && game.render_frame() {
+                            *control_flow = ControlFlow::Exit;
+                        }
+                    } else {
+                        game.engine.get_window().request_redraw();
+                    }
                 }
                 Event::RedrawRequested(_) => {
-                    game.update_statistics(game.time.elapsed);
-
-                    // <<<<< ENABLE THIS TO SHOW DEBUG GEOMETRY >>>>>
-                    if false {
-                        game.debug_render();
+                    if game.render_frame() {
+                        *control_flow = ControlFlow::Exit;
+                    }
+                    // There is nothing new to show between ticks, so frame rate is capped
+                    // to tick rate. Benchmark measures how fast frames can go. Low latency
+                    // mode does not sleep here, it waits for next tick in event loop.
+                    if game.benchmark.is_none() && !game.is_low_latency() {
+                        game.limit_fps(1.0 / game.time.delta as f64);
                     }
-
-                    // Render at max speed
-                    game.engine.render(fixed_timestep).unwrap();
-                    // Make sure to cap update rate to 60 FPS.
-                    game.limit_fps(fixed_fps as f64);
+                }
+                Event::RedrawEventsCleared if game.is_low_latency() && *control_flow != ControlFlow::Exit => {
+                    // Event loop sleeps until next tick is due, but wakes up on any input,
+                    // so input is handled as soon as it comes and never waits a whole frame.
+                    *control_flow = ControlFlow::WaitUntil(game.next_tick_instant());
                 }
                 Event::WindowEvent { event, .. } => {
                     match event {
@@ -431,11 +1446,16 @@ impl Game {
                             game.engine
                                 .renderer
                                 .set_frame_size(new_size.into());
+                            game.store_window_geometry();
+                        }
+                        WindowEvent::Moved(_) => {
+                            game.store_window_geometry();
                         }
                         _ => ()
                     }
                 }
                 Event::LoopDestroyed => {
+                    game.shutdown();
                     rg3d::core::profiler::print();
                 }
                 _ => *control_flow = ControlFlow::Poll,
@@ -443,24 +1463,67 @@ impl Game {
         });
     }
 
-    fn debug_render(&mut self) {
-        self.engine.renderer.debug_renderer.clear_lines();
-
-        if let Some(level) = self.level.as_mut() {
-            level.debug_draw(&mut self.engine);
-        }
+    /// Whether every tick is rendered right away, see `Settings::low_latency`. Benchmark
+    /// always uses regular loop, so its results stay comparable.
+    fn is_low_latency(&self) -> bool {
+        self.settings.low_latency && self.benchmark.is_none()
     }
 
-    pub fn create_debug_ui(&mut self) {
-        self.debug_text = TextBuilder::new(WidgetBuilder::new()
-            .with_width(400.0))
-            .build(&mut self.engine.user_interface.build_ctx());
+    /// Moment when simulation time reaches time of next tick.
+    fn next_tick_instant(&self) -> time::Instant {
+        self.time.clock + Duration::from_secs_f64(self.time.elapsed + self.time.delta as f64)
     }
 
-    pub fn save_game(&mut self) -> VisitResult {
-        let mut visitor = Visitor::new();
+    /// Renders one frame and records it into benchmark if there is one. Returns true if
+    /// benchmark has finished and game should exit.
+    fn render_frame(&mut self) -> bool {
+        self.update_statistics(self.time.elapsed);
 
-        // Visit engine state first.
+        self.debug_render();
+
+        let render_start = self.frame_profile.begin();
+        self.engine.render(self.time.delta).unwrap();
+        self.frame_profile.end(Stage::Render, render_start);
+        if let Some(benchmark) = self.benchmark.as_mut() {
+            benchmark.record_frame(&self.frame_profile, self.level.as_ref().map_or(0, Level::path_rebuilds));
+        }
+        // Benchmark is taken, so results are reported once even if some more frames are
+        // drawn before exit.
+        let mut finished = false;
+        if self.benchmark.as_ref().map_or(false, Benchmark::is_finished) {
+            if let Some(benchmark) = self.benchmark.take() {
+                benchmark.report(MAP_NAME, TICK_RATE);
+            }
+            self.destroy_level();
+            finished = true;
+        }
+        self.frame_profile.finish_frame();
+        finished
+    }
+
+    fn debug_render(&mut self) {
+        self.engine.renderer.debug_renderer.clear_lines();
+
+        if self.debug_draw.enabled {
+            if let Some(level) = self.level.as_ref() {
+                level.debug_draw(&mut self.engine, &self.debug_draw, self.time.delta);
+            }
+        }
+    }
+
+    pub fn create_debug_ui(&mut self) {
+        self.debug_text = TextBuilder::new(WidgetBuilder::new()
+            .with_width(400.0))
+            .build(&mut self.engine.user_interface.build_ctx());
+    }
+
+    pub fn save_game(&mut self) -> VisitResult {
+        let mut visitor = Visitor::new();
+
+        let mut version = SAVE_FORMAT_VERSION;
+        version.visit("SaveFormatVersion", &mut visitor)?;
+
+        // Visit engine state first.
         self.engine.visit("GameEngine", &mut visitor)?;
 
         self.level.visit("Level", &mut visitor)?;
@@ -478,33 +1541,47 @@ impl Game {
     pub fn load_game(&mut self) -> VisitResult {
         println!("Attempting load a save...");
 
-        let mut visitor = Visitor::load_binary(Path::new("save.bin"))?;
+        // Save is checked and level is read into temporary before anything is destroyed, so
+        // current game is intact if save is corrupted. Binary file can be read completely but
+        // still have broken or missing entries (for example if it was truncated).
+        let mut visitor = open_save(Path::new("save.bin"))?;
+        let level = read_level(&mut visitor)?;
 
         // Clean up.
         self.destroy_level();
 
-        // Load engine state first
-        println!("Trying to load engine state...");
-        self.engine.visit("GameEngine", &mut visitor)?;
-
-        println!("GameEngine state successfully loaded!");
-
-        // Then load game state.
-        self.level.visit("Level", &mut visitor)?;
-
-        println!("Game state successfully loaded!");
-
-        self.sound_manager.visit("SoundManager", &mut visitor)?;
-        self.sound_manager.context = self.engine.sound_context.clone();
+        // Engine state can't be loaded aside, so if it fails midway, game is returned to the
+        // menu without any half-loaded state.
+        if let Err(e) = self.load_engine_state(&mut visitor) {
+            self.recover_from_failed_load();
+            return Err(e);
+        }
+        self.level = level;
+        self.update_window_title();
+        self.update_music();
 
         // Hide menu only of we successfully loaded a save.
         self.set_menu_visible(false);
 
         // Set control scheme for player.
         if let Some(level) = &mut self.level {
+            self.last_match_options = Some(level.options);
             level.set_message_sender(self.events_sender.clone());
+            level.preload_assets(&mut self.engine.resource_manager.lock().unwrap());
             level.build_navmesh(&mut self.engine);
             level.control_scheme = Some(self.control_scheme.clone());
+            level.set_color_blind_mode(&mut self.engine, self.settings.color_blind_mode);
+            level.restore_weapon_skins(&mut self.engine);
+            level.set_content_filter(self.settings.content_filter);
+            level.set_effect_limits(&mut self.engine, self.settings.effect_limits);
+            level.set_item_glow(self.settings.item_glow);
+            level.set_corpse_limits(self.settings.corpse_limits);
+            level.set_rocket_jump(self.settings.rocket_jump);
+            level.set_hit_multipliers(self.settings.hit_multipliers);
+            level.set_killcam(self.settings.killcam);
+            level.set_weapon_drop(self.settings.weapon_drop);
+            level.set_path_budget(self.settings.path_budget);
+            self.hud.apply_mode_preset(&mut self.engine.user_interface, &level.options);
             let player = level.get_player();
             if let Actor::Player(player) = level.actors_mut().get_mut(player) {
                 player.set_control_scheme(self.control_scheme.clone());
@@ -516,28 +1593,287 @@ impl Game {
         Ok(())
     }
 
+    fn load_engine_state(&mut self, visitor: &mut Visitor) -> VisitResult {
+        println!("Trying to load engine state...");
+        self.engine.visit("GameEngine", visitor)?;
+
+        println!("GameEngine state successfully loaded!");
+
+        self.sound_manager.visit("SoundManager", visitor)?;
+        self.sound_manager.context = self.engine.sound_context.clone();
+
+        Ok(())
+    }
+
+    /// Removes everything that could be loaded by failed load, so game is in the same state
+    /// as right after start - on the main menu without level.
+    fn recover_from_failed_load(&mut self) {
+        // Level was never swapped in, but engine state could be loaded partially and scenes
+        // are owned only by levels in this game, so they all are orphaned.
+        self.engine.scenes.clear();
+        self.sound_manager.context = self.engine.sound_context.clone();
+        self.sound_manager.clear_level_sounds();
+        self.update_music();
+        self.last_match_options = None;
+        self.set_menu_visible(true);
+        println!("Save is corrupted, game was returned to the menu.");
+    }
+
+    /// Remembers size and position of window, so it will be restored on next run. Geometry of
+    /// fullscreen or minimized window is not stored.
+    fn store_window_geometry(&mut self) {
+        let window = self.engine.get_window();
+        if window.fullscreen().is_some() {
+            return;
+        }
+        let size = window.inner_size();
+        if size.width == 0 || size.height == 0 {
+            return;
+        }
+        if let Ok(position) = window.outer_position() {
+            self.settings.window_geometry = Some(WindowGeometry {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            });
+        }
+    }
+
+    /// Saves settings together with current renderer quality settings and control scheme
+    /// settings so they will be restored on next run.
+    /// Releases everything before exit: network session, current level with its scene and
+    /// match log, every sound source, and saves settings. Safe to call more than once and
+    /// without level. Engine itself is dropped together with the game by event loop.
+    fn shutdown(&mut self) {
+        self.running = false;
+        self.server = None;
+        self.client = None;
+        // Level is not destroyed by destroy_level, it would start menu music again.
+        if let Some(mut level) = self.level.take() {
+            level.destroy(&mut self.engine);
+        }
+        self.sound_manager.shutdown();
+        self.save_settings();
+    }
+
+    fn save_settings(&mut self) {
+        self.settings.store_quality_settings(&self.engine.renderer.get_quality_settings());
+        self.settings.store_control_scheme(&self.control_scheme.borrow());
+        self.settings.save();
+    }
+
+    fn export_settings(&mut self, path: &Path) -> CommandResult {
+        self.settings.store_quality_settings(&self.engine.renderer.get_quality_settings());
+        self.settings.store_control_scheme(&self.control_scheme.borrow());
+        match self.settings.export(&self.control_scheme.borrow(), path) {
+            Ok(_) => Ok(format!("Settings exported to {}", path.display())),
+            Err(e) => Err(format!("Unable to export settings. Reason: {}", e)),
+        }
+    }
+
+    fn import_settings(&mut self, path: &Path) -> CommandResult {
+        let result = self.settings.import(&mut self.control_scheme.borrow_mut(), path);
+        match result {
+            Ok(settings) => {
+                self.apply_settings(settings);
+                Ok(format!("Settings imported from {}", path.display()))
+            }
+            Err(e) => Err(format!("Unable to import settings. Reason: {}", e)),
+        }
+    }
+
+    fn report_settings_file_status(&mut self, text: &str) {
+        println!("{}", text);
+        self.menu.set_settings_file_status(&mut self.engine.user_interface, text);
+        self.hud.add_note(&mut self.engine.user_interface, text);
+    }
+
+    /// Applies every setting at once, control scheme is expected to be already updated.
+    fn apply_settings(&mut self, settings: Settings) {
+        let mut quality_settings = self.engine.renderer.get_quality_settings();
+        settings.apply_to_quality_settings(&mut quality_settings);
+        if let Err(e) = self.engine.renderer.set_quality_settings(&quality_settings) {
+            println!("Failed to apply quality settings! Reason: {:?}", e);
+        }
+        self.events_sender
+            .send(Message::SetMaxSounds {
+                count: settings.max_sounds
+            })
+            .unwrap();
+        let ui = &mut self.engine.user_interface;
+        self.hud.set_crosshair(ui, settings.crosshair);
+        self.hud.set_damage_feedback(ui, settings.damage_feedback);
+        self.hud.set_damage_numbers_enabled(ui, settings.damage_numbers);
+        self.hud.set_color_blind_mode(settings.color_blind_mode);
+        if let Some(level) = self.level.as_mut() {
+            level.set_content_filter(settings.content_filter);
+            level.set_color_blind_mode(&mut self.engine, settings.color_blind_mode);
+            level.set_effect_limits(&mut self.engine, settings.effect_limits);
+            level.set_item_glow(settings.item_glow);
+            level.set_corpse_limits(settings.corpse_limits);
+            level.set_rocket_jump(settings.rocket_jump);
+            level.set_hit_multipliers(settings.hit_multipliers);
+            level.set_killcam(settings.killcam);
+            level.set_weapon_drop(settings.weapon_drop);
+            level.set_path_budget(settings.path_budget);
+        }
+        let (hud_scale, hud_layout, hud_theme, accessibility) = (settings.hud_scale, settings.hud_layout, settings.hud_theme, settings.accessibility);
+        self.settings = settings;
+        self.set_hud_scale(hud_scale);
+        self.set_hud_layout(hud_layout);
+        self.set_hud_theme(hud_theme);
+        self.set_accessibility(accessibility);
+        self.menu.sync_to_settings(&mut self.engine, &self.settings);
+    }
+
+    fn set_hud_scale(&mut self, scale: f32) {
+        self.settings.hud_scale = scale;
+        self.hud.set_scale(&mut self.engine, scale);
+        if let Some(level) = self.level.as_ref() {
+            self.hud.update_leader_board_overview(&mut self.engine.user_interface, &level.leader_board, &level.options);
+        }
+    }
+
+    fn set_hud_layout(&mut self, layout: HudLayout) {
+        self.settings.hud_layout = layout;
+        self.hud.set_layout(&mut self.engine, layout);
+        if let Some(level) = self.level.as_ref() {
+            self.hud.update_leader_board_overview(&mut self.engine.user_interface, &level.leader_board, &level.options);
+        }
+    }
+
+    fn set_hud_theme(&mut self, theme: HudTheme) {
+        self.settings.hud_theme = theme;
+        self.hud.set_theme(&mut self.engine, theme);
+        if let Some(level) = self.level.as_ref() {
+            self.hud.update_leader_board_overview(&mut self.engine.user_interface, &level.leader_board, &level.options);
+        }
+    }
+
+    fn set_accessibility(&mut self, accessibility: Accessibility) {
+        self.settings.accessibility = accessibility;
+        self.hud.set_accessibility(&mut self.engine, accessibility);
+        if let Some(level) = self.level.as_ref() {
+            self.hud.update_leader_board_overview(&mut self.engine.user_interface, &level.leader_board, &level.options);
+        }
+    }
+
     fn destroy_level(&mut self) {
+        // Network session lives as long as its match.
+        self.server = None;
+        self.client = None;
         if let Some(ref mut level) = self.level.take() {
             level.destroy(&mut self.engine);
+            self.sound_manager.clear_level_sounds();
             println!("Current level destroyed!");
         }
+        self.update_window_title();
+        self.update_music();
+    }
+
+    /// Plays music of current level or menu music when there is no level.
+    fn update_music(&mut self) {
+        let tracks = match self.level.as_ref() {
+            Some(level) if !level.music().is_empty() => level.music(),
+            _ => MENU_MUSIC,
+        };
+        self.sound_manager.set_playlist(&mut self.engine.resource_manager.lock().unwrap(), tracks);
     }
 
-    pub fn start_new_game(&mut self, options: MatchOptions) {
+    /// Sets window title according to current match, title contains mode and map name
+    /// when there is a level, for example "Rusty Shooter - Deathmatch - dm6".
+    pub fn update_window_title(&self) {
+        let title = match self.level.as_ref() {
+            Some(level) => format!("{} - {} - {}", WINDOW_TITLE, level.options.display_name(), level.map_name()),
+            None => WINDOW_TITLE.to_owned(),
+        };
+        self.engine.get_window().set_title(&title);
+    }
+
+    /// Starts new match, observer match has no player and is watched by spectator camera.
+    pub fn start_new_game(&mut self, options: MatchOptions, observer: bool) {
         self.destroy_level();
+        self.last_match_options = Some(options);
+        let match_log = if self.settings.match_log_enabled {
+            match MatchLogger::new() {
+                Ok(logger) => Some(logger),
+                Err(e) => {
+                    println!("Unable to create match log, match won't be logged. Reason: {:?}", e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
         self.level = Some(Level::new(
             &mut self.engine,
             self.control_scheme.clone(),
             self.events_sender.clone(),
             options,
+            self.settings.loadout.clone(),
+            match_log,
+            self.settings.content_filter,
+            observer,
         ));
+        if let Some(level) = self.level.as_mut() {
+            level.set_color_blind_mode(&mut self.engine, self.settings.color_blind_mode);
+            level.set_effect_limits(&mut self.engine, self.settings.effect_limits);
+            level.set_item_glow(self.settings.item_glow);
+            level.set_corpse_limits(self.settings.corpse_limits);
+            level.set_rocket_jump(self.settings.rocket_jump);
+            level.set_hit_multipliers(self.settings.hit_multipliers);
+            level.set_killcam(self.settings.killcam);
+            level.set_weapon_drop(self.settings.weapon_drop);
+            level.set_path_budget(self.settings.path_budget);
+            self.hud.apply_mode_preset(&mut self.engine.user_interface, &level.options);
+        }
+        self.update_window_title();
+        self.update_music();
         self.set_menu_visible(false);
     }
 
+    /// Starts fixed scenario of benchmark mode: deathmatch without intro countdown in which
+    /// bots with aimbot behavior fight each other and the idle player.
+    fn start_benchmark(&mut self) {
+        let bots = std::env::args()
+            .find_map(|arg| arg.strip_prefix(BENCHMARK_BOTS_ARG).and_then(|count| count.parse::<u32>().ok()))
+            .unwrap_or(BENCHMARK_BOTS)
+            .max(BOT_FILL_RANGE.0 - 1)
+            .min(BOT_FILL_RANGE.1 - 1);
+        self.start_new_game(MatchOptions::DeathMatch(DeathMatch {
+            intro_countdown: 0.0,
+            ..Default::default()
+        }), false);
+        if let Some(level) = self.level.as_mut() {
+            // Bots spawned by fill get aimbot too.
+            level.set_bot_aimbot(Some(BENCHMARK_AIMBOT_INTERVAL));
+            level.set_bot_fill(&mut self.engine, Some(bots + 1));
+        }
+        self.frame_profile.set_enabled(true);
+        self.benchmark = Some(Benchmark::new(bots));
+    }
+
+    /// Recreates level using options of last match, scores, timers and actors are reset.
+    pub fn restart_match(&mut self) {
+        if let Some(options) = self.last_match_options {
+            let observer = self.level.as_ref().map_or(false, |level| level.is_observer());
+            self.start_new_game(options, observer);
+        } else {
+            println!("There is no match to restart!");
+        }
+    }
+
+    /// Shows or hides menu, pause menu is shown during a match and main menu otherwise.
     pub fn set_menu_visible(&mut self, visible: bool) {
         let ui = &mut self.engine.user_interface;
+        let layout = if self.level.is_some() { MenuLayout::Pause } else { MenuLayout::Main };
+        self.menu.set_layout(ui, layout);
         self.menu.set_visible(ui, visible);
         self.hud.set_visible(ui, !visible);
+        if visible && self.hud.is_callout_wheel_open() {
+            self.hud.close_callout_wheel(ui);
+        }
     }
 
     pub fn is_menu_visible(&self) -> bool {
@@ -549,38 +1885,233 @@ impl Game {
         window.set_cursor_visible(self.is_menu_visible());
         let _ = window.set_cursor_grab(!self.is_menu_visible());
 
+        let engine_start = self.frame_profile.begin();
         self.engine.update(time.delta);
+        self.frame_profile.end(Stage::Engine, engine_start);
+        self.sound_manager.update_music(&mut self.engine.resource_manager.lock().unwrap());
+        self.sound_manager.update_hearing(time.delta);
+
+        // Menu is the pause menu while there is a level.
+        let paused = self.benchmark.is_none() && ((!self.focused && self.settings.pause_when_unfocused) || self.is_menu_visible());
+
+        // Input of clients is applied before simulation and input of this client is taken
+        // before simulation resets one-shot controls.
+        let input_sequence = self.receive_network(time);
 
         if let Some(ref mut level) = self.level {
-            level.update(&mut self.engine, time);
+            level.set_shot_debug(self.debug_draw.enabled && self.debug_draw.shots);
+            if !paused {
+                let level_start = self.frame_profile.begin();
+                level.update(&mut self.engine, time);
+                self.frame_profile.end(Stage::Level, level_start);
+            }
+            let use_action = if level.get_player().is_some() {
+                level.find_usable(&self.engine, level.get_player())
+                    .map(|target| level.use_action(target))
+            } else {
+                None
+            };
             let ui = &mut self.engine.user_interface;
+            self.hud.set_use_prompt(ui, use_action);
             self.hud.set_time(ui, level.time());
+            self.hud.set_lives(ui, level.leader_board.lives_left(PLAYER_NAME, &level.options));
+            self.hud.set_range_stats(ui, level.range_stats());
+            self.hud.set_control_points(ui, level.control_points());
             let player = level.get_player();
             if player.is_some() {
                 // Sync hud with player state.
                 let player = level.actors().get(player);
-                self.hud.set_health(ui, player.get_health());
-                self.hud.set_armor(ui, player.get_armor());
+                self.hud.set_health(player.get_health());
+                self.hud.set_armor(player.get_armor());
+                self.hud.set_spawn_protected(ui, player.is_spawn_protected());
                 let current_weapon = player.current_weapon();
                 if current_weapon.is_some() {
-                    self.hud.set_ammo(ui, level.weapons()[current_weapon].ammo());
+                    let weapon = &level.weapons()[current_weapon];
+                    match weapon.heat() {
+                        Some(heat) => self.hud.set_heat(ui, heat, weapon.is_overheated()),
+                        None => self.hud.set_ammo(ui, weapon.magazine(), weapon.ammo(), weapon.alt_ammo(), weapon.active_reload()),
+                    }
+                    self.hud.set_crosshair_profile(ui, Some(weapon.definition.crosshair));
+                } else {
+                    self.hud.set_crosshair_profile(ui, None);
                 }
+                let zoom = match player {
+                    Actor::Player(player) => player.zoom(),
+                    _ => None,
+                };
+                self.hud.set_zoom(ui, zoom);
                 self.hud.set_is_died(ui, false);
             } else {
+                self.hud.set_spawn_protected(ui, false);
+                self.hud.set_zoom(ui, None);
                 self.hud.set_is_died(ui, true);
             }
+
+            // Show names of living bots above their heads.
+            let scene = &self.engine.scenes[level.scene];
+            if let Node::Camera(camera) = &scene.graph[level.active_camera()] {
+                let camera_position = camera.global_position();
+                let through_walls = self.settings.nameplates_through_walls;
+                let nameplates = level.actors()
+                    .iter()
+                    .filter_map(|actor| match actor {
+                        Actor::Bot(bot) if !bot.is_dead() => {
+                            let position = bot.position(&scene.physics) + Vec3::new(0.0, 1.0, 0.0);
+                            let distance = camera_position.distance(&position);
+                            if distance > NAMEPLATE_MAX_DISTANCE ||
+                                !through_walls && !line_of_sight(&scene.physics, camera_position, position) {
+                                return None;
+                            }
+                            let fade = ((NAMEPLATE_MAX_DISTANCE - distance) /
+                                (NAMEPLATE_MAX_DISTANCE - NAMEPLATE_FADE_DISTANCE)).min(1.0);
+                            let mut color = bot.color();
+                            color.a = (255.0 * fade) as u8;
+                            Some(Nameplate {
+                                text: bot.name.clone(),
+                                color,
+                                position,
+                            })
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+                let view_projection = camera.view_projection_matrix();
+                let frame_size = self.engine.renderer.get_frame_size();
+                self.hud.update_nameplates(ui, &view_projection, frame_size, &nameplates);
+
+                let item_timers = if self.settings.show_item_timers {
+                    level.items()
+                        .iter()
+                        .filter_map(|item| item.respawn_time_left()
+                            .map(|time_left| (item.position(&scene.graph) + Vec3::new(0.0, 0.5, 0.0), time_left)))
+                        .collect::<Vec<_>>()
+                } else {
+                    Vec::new()
+                };
+                self.hud.update_item_timers(ui, &view_projection, frame_size, &item_timers);
+
+                self.hud.clear_markers();
+                let player = level.get_player();
+                if player.is_some() {
+                    let player = level.actors().get(player);
+                    let player_position = player.position(&scene.physics);
+
+                    // Show where remaining opponents are when only a few of them left, so
+                    // last man standing match won't end up in endless hide and seek.
+                    if level.options.lives().is_some() {
+                        let survivors = level.leader_board.survivors(&level.options).collect::<Vec<_>>();
+                        if survivors.len() <= MARKED_SURVIVORS_COUNT + 1 {
+                            for actor in level.actors().iter() {
+                                if let Actor::Bot(bot) = actor {
+                                    if !bot.is_dead() && survivors.contains(&bot.name.as_str()) {
+                                        self.hud.add_marker(bot.position(&scene.physics), MarkerIcon::Enemy);
+                                    }
+                                }
+                            }
+                        }
+                    }
+
+                    // Point to closest health when player is about to die.
+                    if player.get_health() < LOW_HEALTH {
+                        let closest_medkit = level.items()
+                            .iter()
+                            .filter(|item| item.get_kind() == ItemKind::Medkit && !item.is_picked_up())
+                            .map(|item| item.position(&scene.graph))
+                            .min_by(|a, b| a.distance(&player_position)
+                                .partial_cmp(&b.distance(&player_position))
+                                .unwrap_or(std::cmp::Ordering::Equal));
+                        if let Some(position) = closest_medkit {
+                            self.hud.add_marker(position, MarkerIcon::Health);
+                        }
+                    }
+
+                    for point in level.control_points() {
+                        self.hud.add_marker(point.center(), MarkerIcon::ControlPoint(point.label(), point.owner()));
+                    }
+
+                    // Remind where player died, so dropped weapon can be picked up again.
+                    if self.settings.death_marker {
+                        if let Some(position) = level.death_marker() {
+                            self.hud.add_marker(position, MarkerIcon::LastDeath);
+                        }
+                    }
+                }
+                self.hud.update_markers(ui, &view_projection, frame_size, scene.graph[level.active_camera()].global_position());
+
+                // Compass shows teammates wherever they are and enemies only when they are
+                // in sight, so it won't work as a wallhack.
+                if self.settings.hud_layout.compass.visible {
+                    let player = level.get_player();
+                    let compass_actors = if player.is_some() {
+                        let team = level.actors().get(player).team();
+                        let enemy_color = self.settings.hud_theme.colors()
+                            .with_color_blind_mode(self.settings.color_blind_mode)
+                            .enemy_marker;
+                        level.actors()
+                            .iter()
+                            .filter_map(|actor| match actor {
+                                Actor::Bot(bot) if !bot.is_dead() => {
+                                    let position = bot.position(&scene.physics);
+                                    let color = if team != Team::None && bot.team() == team {
+                                        bot.color()
+                                    } else if line_of_sight(&scene.physics, camera_position, position + Vec3::new(0.0, 1.0, 0.0)) {
+                                        enemy_color
+                                    } else {
+                                        return None;
+                                    };
+                                    Some(Nameplate {
+                                        text: bot.name.clone(),
+                                        color,
+                                        position,
+                                    })
+                                }
+                                _ => None,
+                            })
+                            .collect::<Vec<_>>()
+                    } else {
+                        Vec::new()
+                    };
+                    let aspect = frame_size.0 as f32 / frame_size.1.max(1) as f32;
+                    let half_fov = ((camera.fov() * 0.5).tan() * aspect).atan();
+                    self.hud.update_compass(ui, camera_position, compass_bearing(camera.look_vector()), half_fov, &compass_actors);
+                }
+                self.hud.update_damage_numbers(ui, &view_projection, frame_size, time.delta);
+            }
         }
 
         self.handle_messages(time);
 
+        // Snapshot goes out when messages of this tick are handled, so it has their results.
+        self.send_network(input_sequence);
+
+        self.menu.update(&mut self.engine.user_interface, time.delta);
         self.hud.update(&mut self.engine.user_interface, &self.time);
     }
 
     fn handle_messages(&mut self, time: GameTime) {
         while let Ok(message) = self.events_receiver.try_recv() {
             match &message {
-                Message::StartNewGame { options } => {
-                    self.start_new_game(*options);
+                Message::StartNewGame { options, loadout, match_log, observer } => {
+                    self.settings.loadout = loadout.clone();
+                    self.settings.match_log_enabled = *match_log;
+                    self.settings.observer_mode = *observer;
+                    // Bot and damage options do not apply to practice, options of last real
+                    // match are kept for next match.
+                    if !options.is_practice() {
+                        self.settings.bots_fight_each_other = options.bots_fight_each_other();
+                        self.settings.bot_sight_range = options.bot_sight_range();
+                        self.settings.damage_multipliers = options.damage_multipliers();
+                        self.settings.spawn_protection = options.spawn_protection();
+                        self.settings.health_regen = options.health_regen();
+                        self.settings.auto_balance = options.auto_balance();
+                        self.settings.intro_countdown = options.intro_countdown();
+                        self.settings.time_limit_secs = options.time_limit_secs();
+                    }
+                    if let MatchOptions::DeathMatch(dm) = options {
+                        self.settings.frag_limit = dm.frag_limit;
+                    }
+                    // Practice has no bots to watch, so it is always played.
+                    self.start_new_game(*options, *observer && !options.is_practice());
                 }
                 Message::SaveGame => {
                     match self.save_game() {
@@ -593,13 +2124,204 @@ impl Game {
                         println!("Failed to load saved game. Reason: {:?}", e);
                     }
                 }
+                Message::RestartMatch => {
+                    self.hud.leader_board().set_visible(false, &mut self.engine.user_interface);
+                    self.restart_match();
+                }
+                &Message::StartVotedMatch { options } => {
+                    self.start_new_game(options, false);
+                }
+                &Message::SetHudScale { scale } => {
+                    self.set_hud_scale(scale);
+                }
+                &Message::SetHudLayout { layout } => {
+                    self.set_hud_layout(layout);
+                }
+                &Message::SetHudTheme { theme } => {
+                    self.set_hud_theme(theme);
+                }
+                &Message::SetAccessibility { accessibility } => {
+                    self.set_accessibility(accessibility);
+                }
+                &Message::SetItemTimersEnabled { enabled } => {
+                    self.settings.show_item_timers = enabled;
+                }
+                &Message::SetDeathMarkerEnabled { enabled } => {
+                    self.settings.death_marker = enabled;
+                }
+                &Message::SetMapVoteTime { time } => {
+                    self.settings.map_vote_time = time;
+                }
+                &Message::SetCorpseLimits { limits } => {
+                    self.settings.corpse_limits = limits;
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_corpse_limits(limits);
+                    }
+                }
+                &Message::SetRocketJump { rocket_jump } => {
+                    self.settings.rocket_jump = rocket_jump;
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_rocket_jump(rocket_jump);
+                    }
+                }
+                &Message::SetKillcam { enabled } => {
+                    self.settings.killcam = enabled;
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_killcam(enabled);
+                    }
+                }
+                &Message::SetWeaponDrop { enabled } => {
+                    self.settings.weapon_drop = enabled;
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_weapon_drop(enabled);
+                    }
+                }
+                &Message::SetPathBudget { budget } => {
+                    self.settings.path_budget = budget;
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_path_budget(budget);
+                    }
+                }
+                &Message::SetItemGlow { intensity } => {
+                    self.settings.item_glow = intensity;
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_item_glow(intensity);
+                    }
+                }
+                &Message::SetNameplatesThroughWalls { enabled } => {
+                    self.settings.nameplates_through_walls = enabled;
+                }
+                &Message::SetContentFilter { filter } => {
+                    self.settings.content_filter = filter;
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_content_filter(filter);
+                    }
+                }
+                &Message::SetEffectLimits { limits } => {
+                    self.settings.effect_limits = limits;
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_effect_limits(&mut self.engine, limits);
+                    }
+                }
+                Message::ExportSettings { path } => {
+                    let text = match self.export_settings(path) {
+                        Ok(text) | Err(text) => text,
+                    };
+                    self.report_settings_file_status(&text);
+                }
+                Message::ImportSettings { path } => {
+                    let text = match self.import_settings(path) {
+                        Ok(text) | Err(text) => text,
+                    };
+                    self.report_settings_file_status(&text);
+                }
+                &Message::SetDamageFeedback { feedback } => {
+                    self.settings.damage_feedback = feedback;
+                    self.hud.set_damage_feedback(&mut self.engine.user_interface, feedback);
+                }
+                &Message::SetColorBlindMode { mode } => {
+                    self.settings.color_blind_mode = mode;
+                    self.hud.set_color_blind_mode(mode);
+                    if let Some(level) = self.level.as_mut() {
+                        level.set_color_blind_mode(&mut self.engine, mode);
+                    }
+                }
+                &Message::SetPreciseFramePacing { enabled } => {
+                    self.settings.precise_frame_pacing = enabled;
+                }
+                &Message::SetLowLatency { enabled } => {
+                    self.settings.low_latency = enabled;
+                }
+                &Message::SetPauseWhenUnfocused { enabled } => {
+                    self.settings.pause_when_unfocused = enabled;
+                }
+                &Message::SetMaxSounds { count } => {
+                    self.settings.max_sounds = count;
+                }
+                &Message::SetCrosshair { config } => {
+                    self.settings.crosshair = config;
+                    self.hud.set_crosshair(&mut self.engine.user_interface, config);
+                }
+                Message::SetDamageNumbersEnabled { enabled } => {
+                    self.settings.damage_numbers = *enabled;
+                    self.hud.set_damage_numbers_enabled(&mut self.engine.user_interface, *enabled);
+                }
+                &Message::ActorDamaged { victim, attacker, amount, kind } => {
+                    // Show how much damage player dealt to a bot, amount is already scaled by
+                    // damage multipliers here.
+                    if let Some(level) = self.level.as_ref() {
+                        if attacker.is_some() && attacker == level.get_player() && level.actors().contains(victim) {
+                            if victim != attacker {
+                                self.hud.on_hit(&mut self.engine.user_interface);
+                            }
+                            if let Actor::Bot(bot) = level.actors().get(victim) {
+                                let scene = &self.engine.scenes[level.scene];
+                                let position = bot.position(&scene.physics) + Vec3::new(0.0, 1.3, 0.0);
+                                self.hud.add_damage_number(&mut self.engine.user_interface, position, amount);
+                            }
+                        }
+
+                        if victim.is_some() && victim == level.get_player() && level.actors().contains(victim) {
+                            let scene = &self.engine.scenes[level.scene];
+                            let player = level.actors().get(victim);
+                            let direction = if attacker.is_some() && attacker != victim && level.actors().contains(attacker) {
+                                let pivot = &scene.graph[player.pivot];
+                                let to_attacker = level.actors().get(attacker).position(&scene.physics) - player.position(&scene.physics);
+                                let forward = to_attacker.dot(&pivot.look_vector());
+                                // Side vector points to the left, angle is measured clockwise.
+                                let right = -to_attacker.dot(&pivot.side_vector());
+                                Some(right.atan2(forward))
+                            } else {
+                                None
+                            };
+                            self.hud.on_damage_taken(&mut self.engine.user_interface, amount, direction, kind);
+                        }
+                    }
+                }
+                &Message::FlashPlayer { strength } => {
+                    self.hud.on_flashed(&mut self.engine.user_interface, strength);
+                }
                 Message::QuitGame => {
                     self.destroy_level();
                     self.running = false;
                 }
-                Message::EndMatch => {
+                Message::ResumeGame => {
+                    self.set_menu_visible(false);
+                }
+                Message::ReturnToMainMenu => {
                     self.destroy_level();
-                    self.hud.leader_board().set_visible(true, &mut self.engine.user_interface);
+                    self.hud.leader_board().set_visible(false, &mut self.engine.user_interface);
+                    self.set_menu_visible(true);
+                }
+                Message::ShowStats => {
+                    self.menu.show_stats(&mut self.engine.user_interface, &self.player_stats);
+                }
+                &Message::HostMatch { options, port, max_players, bot_fill } => {
+                    match self.host_match(Some(options), port, max_players, bot_fill) {
+                        Ok(text) => self.events_sender.send(Message::AddNotification { text }).unwrap(),
+                        Err(text) => self.menu.show_network_status(&mut self.engine.user_interface, &text),
+                    }
+                }
+                Message::ConnectToServer { address } => {
+                    match self.connect_to_server(address) {
+                        Ok(text) => self.events_sender.send(Message::AddNotification { text }).unwrap(),
+                        Err(text) => self.menu.show_network_status(&mut self.engine.user_interface, &text),
+                    }
+                }
+                Message::EndMatch => {
+                    if let Some(level) = self.level.as_ref() {
+                        if let Some(score) = level.leader_board.values().get(PLAYER_NAME) {
+                            // Shots at targets would skew lifetime accuracy.
+                            if !level.options.is_practice() {
+                                self.player_stats.add_match(score);
+                                self.player_stats.save();
+                            }
+                        }
+                        self.menu.show_match_summary(&mut self.engine.user_interface, &level.leader_board, &level.options, self.settings.map_vote_time);
+                        self.destroy_level();
+                        self.hud.leader_board().set_visible(false, &mut self.engine.user_interface);
+                        self.set_menu_visible(true);
+                    }
                 }
                 _ => ()
             }
@@ -637,17 +2359,43 @@ impl Game {
                self.engine.ui_time
         ).unwrap();
 
+        if self.frame_profile.is_enabled() {
+            self.frame_profile.write_breakdown(&mut self.debug_string);
+        }
+
+        if let Some(level) = self.level.as_ref() {
+            if level.shot_debug().is_enabled() {
+                level.shot_debug().write_last_hits(&mut self.debug_string);
+            }
+        }
+
         self.engine.user_interface.send_message(TextMessage::text(self.debug_text, self.debug_string.clone()));
     }
 
+    /// Waits until frame that started at last call took `1 / value` seconds. Sleeps shorter
+    /// than sleep granularity may oversleep a lot, so thread only yields then. Precise frame
+    /// pacing sleeps until the last bit of frame and yields in a loop for the rest of it.
     pub fn limit_fps(&mut self, value: f64) {
-        let current_time = time::Instant::now();
-        let render_call_duration = current_time.duration_since(self.last_tick_time).as_secs_f64();
-        self.last_tick_time = current_time;
         let desired_frame_time = 1.0 / value;
-        if render_call_duration < desired_frame_time {
-            thread::sleep(Duration::from_secs_f64(desired_frame_time - render_call_duration));
+        loop {
+            let remaining = desired_frame_time - self.last_tick_time.elapsed().as_secs_f64();
+            if remaining <= 0.0 {
+                break;
+            }
+            if !self.settings.precise_frame_pacing {
+                if remaining > SLEEP_GRANULARITY {
+                    thread::sleep(Duration::from_secs_f64(remaining));
+                } else {
+                    thread::yield_now();
+                }
+                break;
+            } else if remaining > SLEEP_GRANULARITY {
+                thread::sleep(Duration::from_secs_f64(remaining - SLEEP_GRANULARITY));
+            } else {
+                thread::yield_now();
+            }
         }
+        self.last_tick_time = time::Instant::now();
     }
 
     fn process_dispatched_event(&mut self, event: &Event<()>) {
@@ -666,16 +2414,450 @@ impl Game {
         }
     }
 
+    /// Handles callout wheel, returns true if event was consumed by the wheel. Wheel is open
+    /// while callout button is held, mouse moves pointer of the wheel instead of camera, but
+    /// player still can move, so callout can be said on the run.
+    fn process_callout_input(&mut self, event: &Event<()>) -> bool {
+        let (button, state) = match event {
+            Event::DeviceEvent { event: DeviceEvent::Key(input), .. } => (ControlButton::from_key(input), input.state),
+            Event::DeviceEvent { event: DeviceEvent::Button { button, state }, .. } => (ControlButton::Mouse(*button as u8), *state),
+            Event::DeviceEvent { event: DeviceEvent::MouseMotion { delta }, .. } => {
+                if self.hud.is_callout_wheel_open() {
+                    self.hud.move_callout_wheel_pointer(&mut self.engine.user_interface, *delta);
+                    return true;
+                }
+                return false;
+            }
+            _ => return false
+        };
+
+        if button != self.control_scheme.borrow().callout_wheel.button {
+            return false;
+        }
+
+        match state {
+            ElementState::Pressed => {
+                let has_player = self.level.as_ref().map_or(false, |level| level.get_player().is_some());
+                if has_player && !self.is_menu_visible() && !self.hud.is_callout_wheel_open() {
+                    self.hud.open_callout_wheel(&mut self.engine.user_interface);
+                }
+            }
+            ElementState::Released => {
+                if self.hud.is_callout_wheel_open() {
+                    if let Some(callout) = self.hud.close_callout_wheel(&mut self.engine.user_interface) {
+                        if let Some(level) = self.level.as_ref() {
+                            self.events_sender
+                                .send(Message::TeamCallout {
+                                    actor: level.get_player(),
+                                    callout,
+                                })
+                                .unwrap();
+                        }
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    /// Handles typing of a note, returns true if event was consumed by note input. While
+    /// note is typed every key goes to it, so player won't move or shoot.
+    fn process_note_input(&mut self, event: &Event<()>) -> bool {
+        let mut text = match self.note_input.take() {
+            Some(text) => text,
+            None => {
+                // Typing starts on release of the key, so character of the key itself
+                // won't get into note.
+                if let Event::DeviceEvent { event: DeviceEvent::Key(input), .. } = event {
+                    if input.state == ElementState::Released {
+                        let note_button = self.control_scheme.borrow().write_note.button;
+                        if ControlButton::from_key(input) == note_button && self.level.is_some() && !self.is_menu_visible() && !self.console.is_open() {
+                            if let Some(level) = self.level.as_mut() {
+                                level.reset_player_input();
+                            }
+                            self.hud.set_note_input(&mut self.engine.user_interface, Some(""));
+                            self.note_input = Some(String::new());
+                        }
+                    }
+                }
+                return false;
+            }
+        };
+
+        let mut finished = false;
+        let consumed = match event {
+            Event::WindowEvent { event: WindowEvent::ReceivedCharacter(c), .. } => {
+                if !c.is_control() && text.chars().count() < MAX_NOTE_LENGTH {
+                    text.push(*c);
+                }
+                true
+            }
+            Event::DeviceEvent { event, .. } => {
+                // Mouse is captured as well, so player won't shoot or look around. Editing
+                // keys are not bindable, same as in any other text field.
+                if let DeviceEvent::Key(input) = event {
+                    if let (ElementState::Pressed, Some(key)) = (input.state, input.virtual_keycode) {
+                        match key {
+                            VirtualKeyCode::Back => {
+                                text.pop();
+                            }
+                            VirtualKeyCode::Return | VirtualKeyCode::NumpadEnter => {
+                                // Everything typed here is a note, commands go to console.
+                                if !text.trim().is_empty() {
+                                    self.events_sender
+                                        .send(Message::AddNote {
+                                            text: text.trim().to_owned()
+                                        })
+                                        .unwrap();
+                                }
+                                finished = true;
+                            }
+                            VirtualKeyCode::Escape => {
+                                finished = true;
+                            }
+                            _ => ()
+                        }
+                    }
+                }
+                true
+            }
+            _ => false
+        };
+
+        if finished {
+            self.hud.set_note_input(&mut self.engine.user_interface, None);
+        } else {
+            if consumed {
+                self.hud.set_note_input(&mut self.engine.user_interface, Some(&text));
+            }
+            self.note_input = Some(text);
+        }
+
+        consumed
+    }
+
+    /// Handles typing into console, returns true if event was consumed by console. Console
+    /// can be opened anywhere, even in menu, and takes every key while it is open.
+    fn process_console_input(&mut self, event: &Event<()>) -> bool {
+        if !self.console.is_open() {
+            // Console opens on release of the key, so character of the key itself won't get
+            // into the line.
+            if let Event::DeviceEvent { event: DeviceEvent::Key(input), .. } = event {
+                if input.state == ElementState::Released && input.virtual_keycode == Some(CONSOLE_KEY) {
+                    if let Some(level) = self.level.as_mut() {
+                        level.reset_player_input();
+                    }
+                    self.console.open(&mut self.engine.user_interface);
+                }
+            }
+            return false;
+        }
+
+        match event {
+            Event::WindowEvent { event: WindowEvent::ReceivedCharacter(c), .. } => {
+                // Character of console key comes before its release closes console.
+                if *c != '`' && *c != '~' {
+                    self.console.push_char(&mut self.engine.user_interface, *c);
+                }
+                true
+            }
+            // Keyboard input must not reach focused widgets of menu.
+            Event::WindowEvent { event: WindowEvent::KeyboardInput { .. }, .. } => true,
+            // Mouse is captured as well, so player won't shoot or look around in a match.
+            Event::DeviceEvent { event, .. } => {
+                if let DeviceEvent::Key(input) = event {
+                    match (input.state, input.virtual_keycode) {
+                        (ElementState::Pressed, Some(VirtualKeyCode::Back)) => {
+                            self.console.pop_char(&mut self.engine.user_interface);
+                        }
+                        (ElementState::Pressed, Some(VirtualKeyCode::Return)) | (ElementState::Pressed, Some(VirtualKeyCode::NumpadEnter)) => {
+                            let line = self.console.take_line(&mut self.engine.user_interface);
+                            if !line.trim().is_empty() {
+                                self.console.print(&mut self.engine.user_interface, &format!("> {}", line.trim()));
+                                let text = match console::execute(self, &line) {
+                                    Ok(text) => text,
+                                    Err(text) => {
+                                        println!("{}", text);
+                                        text
+                                    }
+                                };
+                                self.console.print(&mut self.engine.user_interface, &text);
+                            }
+                        }
+                        (ElementState::Pressed, Some(VirtualKeyCode::Escape)) | (ElementState::Released, Some(CONSOLE_KEY)) => {
+                            self.console.close(&mut self.engine.user_interface);
+                        }
+                        _ => (),
+                    }
+                }
+                true
+            }
+            _ => false
+        }
+    }
+
+    /// Starts accepting network players on given port. New match is started if options are
+    /// given, current match is hosted otherwise. Returns text that describes the result.
+    fn host_match(&mut self, options: Option<MatchOptions>, port: u16, max_players: u32, bot_fill: Option<u32>) -> Result<String, String> {
+        if options.is_none() && self.client.is_some() {
+            return Err("Match of other server can't be hosted!".to_owned());
+        }
+        let mode = match options.or_else(|| self.level.as_ref().map(|level| level.options)) {
+            Some(options) => options.display_name(),
+            None => return Err("There is no match to host!".to_owned()),
+        };
+        let mut server = Server::bind(port, max_players, MAP_NAME, mode)
+            .map_err(|e| format!("Unable to host match on port {}. Reason: {}", port, e))?;
+        server.set_simulation(self.net_simulation);
+        // New match destroys current level together with its network state.
+        if let Some(options) = options {
+            self.start_new_game(options, false);
+        }
+        if let Some(level) = self.level.as_mut() {
+            level.set_bot_fill(&mut self.engine, bot_fill);
+        }
+        let text = format!("Hosting match on port {}", server.port().unwrap_or(port));
+        self.server = Some(server);
+        Ok(text)
+    }
+
+    /// Starts new deathmatch that shows match of the server at given address. Default port
+    /// is used if address has no port. Returns text that describes the result.
+    fn connect_to_server(&mut self, address: &str) -> Result<String, String> {
+        let address = if address.contains(':') {
+            address.to_owned()
+        } else {
+            format!("{}:{}", address, DEFAULT_PORT)
+        };
+        let mut client = Client::connect(address.as_str(), self.time.elapsed)
+            .map_err(|e| format!("Unable to connect to {}. Reason: {}", address, e))?;
+        client.set_simulation(self.net_simulation);
+        self.start_new_game(MatchOptions::DeathMatch(Default::default()), false);
+        if let Some(level) = self.level.as_mut() {
+            level.set_replica(&mut self.engine);
+        }
+        self.client = Some(client);
+        Ok(format!("Connecting to {}...", address))
+    }
+
+    /// Receives packets from clients of listen server or from server of replica level and
+    /// sends input of this client. Returns sequence of sent input.
+    fn receive_network(&mut self, time: GameTime) -> Option<u32> {
+        let level = match self.level.as_mut() {
+            Some(level) => level,
+            None => return None,
+        };
+
+        if let Some(server) = self.server.as_mut() {
+            for event in server.poll(time.elapsed) {
+                match event {
+                    ServerEvent::Connected { client, address } => {
+                        level.add_remote_player(&mut self.engine, client);
+                        self.events_sender
+                            .send(Message::AddNotification {
+                                text: format!("{} joined from {}", remote_player_name(client), address)
+                            })
+                            .unwrap();
+                    }
+                    ServerEvent::Input { client, input } => {
+                        level.set_remote_input(client, &input);
+                    }
+                    ServerEvent::Disconnected { client } => {
+                        level.remove_remote_player(&mut self.engine, client);
+                        self.events_sender
+                            .send(Message::AddNotification {
+                                text: format!("{} left", remote_player_name(client))
+                            })
+                            .unwrap();
+                    }
+                }
+            }
+        }
+
+        let mut input_sequence = None;
+        let mut disconnect_reason = None;
+        if let Some(client) = self.client.as_mut() {
+            if let Some(input) = level.player_input() {
+                input_sequence = client.send_input(input);
+            }
+            for event in client.poll(time.elapsed) {
+                match event {
+                    ClientEvent::Accepted => {
+                        self.events_sender
+                            .send(Message::AddNotification {
+                                text: format!("Connected to {}", client.server_address())
+                            })
+                            .unwrap();
+                    }
+                    ClientEvent::Snapshot { own_actor, acknowledged_input, actors } => {
+                        level.apply_snapshot(&mut self.engine, time, own_actor, acknowledged_input, actors);
+                    }
+                    ClientEvent::Rejected { reason } => {
+                        disconnect_reason = Some(format!("Server rejected connection: {}", reason));
+                    }
+                    ClientEvent::NoResponse => {
+                        disconnect_reason = Some(format!("Server at {} did not respond. Check the address and \
+                                                          that a match is hosted there.", client.server_address()));
+                    }
+                    ClientEvent::Disconnected => {
+                        disconnect_reason = Some("Connection to server lost".to_owned());
+                    }
+                }
+            }
+        }
+        // Replica is useless without server, so player returns to the menu and sees why.
+        if let Some(text) = disconnect_reason {
+            self.destroy_level();
+            self.set_menu_visible(true);
+            self.menu.show_network_status(&mut self.engine.user_interface, &text);
+            return None;
+        }
+        input_sequence
+    }
+
+    /// Sends state of simulated level to clients of listen server, replica remembers where
+    /// player got with sent input instead.
+    fn send_network(&mut self, input_sequence: Option<u32>) {
+        if let Some(level) = self.level.as_mut() {
+            if let Some(server) = self.server.as_mut() {
+                let states = level.actor_states(&self.engine);
+                server.send_snapshot(&states, |client| level.remote_player_actor(client));
+            }
+            if let Some(sequence) = input_sequence {
+                level.record_prediction(&self.engine, sequence);
+            }
+        }
+    }
+
+    /// Handles `set <name> <value>` console command. Damage multipliers are applied to current
+    /// match and remembered for next matches, `debug_*` variables toggle debug geometry and
+    /// `net_*` variables simulate bad network.
+    fn set_console_variable(&mut self, args: &str) -> CommandResult {
+        let mut args = args.split_whitespace();
+        let (name, value) = match (args.next(), args.next().map(|value| value.parse::<f32>())) {
+            (Some(name), Some(Ok(value))) => (name, value),
+            _ => return Err("Invalid command, usage: set <name> <value>".to_owned()),
+        };
+        if let Some(category) = name.strip_prefix("debug_") {
+            let enabled = value > 0.0;
+            if category == "draw" {
+                self.debug_draw.enabled = enabled;
+            } else {
+                self.debug_draw.set(category, enabled).map_err(|e| format!("Unable to set {}. Reason: {}", name, e))?;
+            }
+            return Ok(format!("{} set to {}", name, enabled));
+        }
+        if NET_SIMULATION_NAMES.contains(&name) {
+            // Debug tool for netcode, applied to current connection and to next ones.
+            self.net_simulation.set(name, value).map_err(|e| format!("Unable to set {}. Reason: {}", name, e))?;
+            if let Some(server) = self.server.as_mut() {
+                server.set_simulation(self.net_simulation);
+            }
+            if let Some(client) = self.client.as_mut() {
+                client.set_simulation(self.net_simulation);
+            }
+            return Ok(format!("{} set to {}", name, value));
+        }
+        let level = match self.level.as_mut() {
+            Some(level) => level,
+            None => return Err(format!("There is no match to change {} in!", name)),
+        };
+        let text = if name == "bot_aimbot" {
+            // Debug mode for benchmarking, best measured with frame profile (F3).
+            level.set_bot_aimbot(if value > 0.0 { Some(value) } else { None });
+            format!("{} set to {}", name, value)
+        } else if name == "input_delay" {
+            // Debug harness for lag compensation, value is in milliseconds.
+            level.set_input_delay(if value > 0.0 { Some(value / 1000.0) } else { None });
+            format!("{} set to {}", name, value)
+        } else if name == "balance_teams" {
+            let enabled = value > 0.0;
+            level.set_team_balance(&mut self.engine, enabled);
+            format!("{} set to {}", name, enabled)
+        } else if HIT_MULTIPLIER_NAMES.contains(&name) {
+            let mut hit_multipliers = self.settings.hit_multipliers;
+            hit_multipliers.set(name, value).map_err(|e| format!("Unable to set {}. Reason: {}", name, e))?;
+            level.set_hit_multipliers(hit_multipliers);
+            self.settings.hit_multipliers = hit_multipliers;
+            format!("{} set to {}", name, value)
+        } else if HEALTH_REGEN_NAMES.contains(&name) {
+            let mut health_regen = level.options.health_regen();
+            health_regen.set(name, value).map_err(|e| format!("Unable to set {}. Reason: {}", name, e))?;
+            level.set_health_regen(health_regen);
+            if let Some(options) = self.last_match_options.as_mut() {
+                options.set_health_regen(health_regen);
+            }
+            self.settings.health_regen = health_regen;
+            format!("{} set to {}", name, value)
+        } else {
+            let mut damage_multipliers = level.options.damage_multipliers();
+            damage_multipliers.set(name, value).map_err(|e| format!("Unable to set {}. Reason: {}", name, e))?;
+            level.options.set_damage_multipliers(damage_multipliers);
+            if let Some(options) = self.last_match_options.as_mut() {
+                options.set_damage_multipliers(damage_multipliers);
+            }
+            self.settings.damage_multipliers = damage_multipliers;
+            format!("{} set to {}", name, value)
+        };
+        // Changes of a match are put into note feed too, so they're visible and logged.
+        self.events_sender
+            .send(Message::AddNote {
+                text: text.clone()
+            })
+            .unwrap();
+        Ok(text)
+    }
+
+    /// Learns how keys are labeled on keyboard layout of the player, see
+    /// [`control_scheme::learn_key_label`].
+    fn learn_key_labels(&mut self, event: &Event<()>) {
+        if let Event::WindowEvent { event, .. } = event {
+            match event {
+                WindowEvent::KeyboardInput { input, .. } if input.state == ElementState::Pressed => {
+                    self.last_key = Some(ControlButton::from_key(input));
+                }
+                WindowEvent::ReceivedCharacter(c) => {
+                    if let Some(key) = self.last_key.take() {
+                        control_scheme::learn_key_label(key, *c);
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
     pub fn process_input_event(&mut self, event: &Event<()>) {
+        let input_start = self.frame_profile.begin();
+
+        self.learn_key_labels(event);
+
+        if self.process_note_input(event) || self.process_console_input(event) || self.process_callout_input(event) {
+            self.frame_profile.end(Stage::Input, input_start);
+            return;
+        }
+
         self.process_dispatched_event(event);
 
+        if let Event::WindowEvent { event: WindowEvent::Focused(focused), .. } = event {
+            if *focused != self.focused {
+                self.focused = *focused;
+                self.sound_manager.set_paused(!*focused);
+            }
+        }
+
         if let Event::DeviceEvent { event, .. } = event {
             if let DeviceEvent::Key(input) = event {
                 if let ElementState::Pressed = input.state {
-                    if let Some(key) = input.virtual_keycode {
-                        if key == VirtualKeyCode::Escape {
-                            self.set_menu_visible(!self.is_menu_visible());
-                        }
+                    // Escape is not bindable, so menu can't be made unreachable.
+                    // There is nothing to return to if there is no level.
+                    if input.virtual_keycode == Some(VirtualKeyCode::Escape) && self.level.is_some() {
+                        self.set_menu_visible(!self.is_menu_visible());
+                    } else if ControlButton::from_key(input) == self.control_scheme.borrow().frame_profile.button {
+                        let enabled = !self.frame_profile.is_enabled();
+                        self.frame_profile.set_enabled(enabled);
+                    } else if ControlButton::from_key(input) == self.control_scheme.borrow().debug_draw.button {
+                        self.debug_draw.enabled = !self.debug_draw.enabled;
                     }
                 }
             }
@@ -683,9 +2865,101 @@ impl Game {
 
         self.menu.process_input_event(&mut self.engine, &event);
         self.hud.process_event(&mut self.engine, &event);
+
+        self.frame_profile.end(Stage::Input, input_start);
+    }
+}
+
+/// Reads save file and checks its format version. Saves made before versioning was added
+/// have no version at all.
+fn open_save(path: &Path) -> Result<Visitor, VisitError> {
+    let mut visitor = Visitor::load_binary(path)?;
+    let mut version = 0u32;
+    if version.visit("SaveFormatVersion", &mut visitor).is_err() {
+        version = 0;
     }
+    if version != SAVE_FORMAT_VERSION {
+        return Err(format!("Save file has format version {}, but version {} is required. \
+                            The save was made by other version of the game and can't be loaded.",
+                           version, SAVE_FORMAT_VERSION).into());
+    }
+    Ok(visitor)
+}
+
+/// Reads level from opened save, level is not attached to engine yet.
+fn read_level(visitor: &mut Visitor) -> Result<Option<Level>, VisitError> {
+    let mut level = None;
+    level.visit("Level", visitor)?;
+    println!("Game state successfully loaded!");
+    Ok(level)
 }
 
 fn main() {
     Game::run();
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes save with given format version and default level to temporary file.
+    fn write_save(name: &str, version: u32) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(name);
+        let mut visitor = Visitor::new();
+        let mut version = version;
+        version.visit("SaveFormatVersion", &mut visitor).unwrap();
+        let mut level = Some(Level::default());
+        level.visit("Level", &mut visitor).unwrap();
+        visitor.save_binary(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn truncated_save_is_refused_before_game_is_touched() {
+        let path = write_save("rusty_shooter_truncated_save.bin", SAVE_FORMAT_VERSION);
+        let data = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &data[..data.len() / 2]).unwrap();
+
+        let result = open_save(&path).and_then(|mut visitor| read_level(&mut visitor));
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn save_of_other_version_is_refused() {
+        let path = write_save("rusty_shooter_old_save.bin", SAVE_FORMAT_VERSION - 1);
+        let result = open_save(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn blend_converges_equally_with_any_time_step() {
+        let blend = |dt: f32| {
+            let mut value = 0.0;
+            for _ in 0..(1.0 / dt).round() as usize {
+                value += (1.0 - value) * frame_blend_factor(0.1, dt);
+            }
+            value
+        };
+        assert!((blend(1.0 / 60.0) - blend(1.0 / 144.0)).abs() < 1e-3);
+        assert!((blend(1.0 / 60.0) - blend(1.0 / 30.0)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn window_icon_is_shipped() {
+        let data = std::fs::read(WINDOW_ICON_PATH).unwrap();
+        let (rgba, width, height) = decode_tga(&data).unwrap();
+        assert_eq!((width, height), (32, 32));
+        assert_eq!(rgba.len(), 32 * 32 * 4);
+    }
+
+    #[test]
+    fn tga_rows_are_flipped_to_top_left_origin() {
+        // 1x2 image with bottom-left origin: bottom pixel is blue, top one is red.
+        let mut data = vec![0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0, 32, 8];
+        data.extend_from_slice(&[255, 0, 0, 255, 0, 0, 255, 255]);
+        let (rgba, _, _) = decode_tga(&data).unwrap();
+        assert_eq!(rgba, vec![255, 0, 0, 255, 0, 0, 255, 255]);
+        assert!(decode_tga(&data[..20]).is_err());
+    }
+}
@@ -0,0 +1,145 @@
+//! Match log is a file with one JSON object per line that describes everything important
+//! that happened during a match - spawns, damage, kills and final scores. It is meant to be
+//! read by external tools, so format is kept flat and simple.
+
+use crate::{
+    leader_board::LeaderBoard,
+    weapon::{WeaponKind, WEAPON_KINDS},
+};
+use std::{
+    fs::{self, File},
+    io::{self, BufWriter, Write},
+    path::PathBuf,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+const LOGS_DIR: &str = "logs";
+
+pub struct MatchLogger {
+    writer: BufWriter<File>,
+    path: PathBuf,
+}
+
+/// Escapes string so it can be put inside quotes in JSON.
+fn escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn weapon_name(kind: WeaponKind) -> &'static str {
+    match kind {
+        WeaponKind::M4 => "m4",
+        WeaponKind::Ak47 => "ak47",
+        WeaponKind::PlasmaRifle => "plasma_rifle",
+        WeaponKind::RocketLauncher => "rocket_launcher",
+    }
+}
+
+fn optional_string(s: Option<&str>) -> String {
+    s.map_or_else(|| "null".to_owned(), |s| format!("\"{}\"", escape(s)))
+}
+
+impl MatchLogger {
+    /// Creates new log file in logs directory, file name contains current unix time so
+    /// logs of previous matches are never overwritten.
+    pub fn new() -> io::Result<Self> {
+        fs::create_dir_all(LOGS_DIR)?;
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        let path = PathBuf::from(format!("{}/match_{}.jsonl", LOGS_DIR, timestamp));
+        let file = File::create(&path)?;
+        println!("Match log will be written to {:?}", path);
+        Ok(Self {
+            writer: BufWriter::new(file),
+            path,
+        })
+    }
+
+    /// Writes single event, `time` is match time in seconds, `fields` must be comma-separated
+    /// list of already formatted JSON key-value pairs.
+    fn write_event(&mut self, time: f32, event: &str, fields: &str) {
+        let result = if fields.is_empty() {
+            writeln!(self.writer, "{{\"time\":{:.3},\"event\":\"{}\"}}", time, event)
+        } else {
+            writeln!(self.writer, "{{\"time\":{:.3},\"event\":\"{}\",{}}}", time, event, fields)
+        };
+        if let Err(e) = result {
+            println!("Unable to write match log {:?}. Reason: {:?}", self.path, e);
+        }
+    }
+
+    pub fn log_match_start(&mut self, time: f32, mode: &str) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs());
+        self.write_event(time, "match_start", &format!("\"mode\":\"{}\",\"unix_time\":{}", escape(mode), timestamp));
+    }
+
+    pub fn log_spawn(&mut self, time: f32, name: &str) {
+        self.write_event(time, "spawn", &format!("\"actor\":\"{}\"", escape(name)));
+    }
+
+    /// `attacker` is None when damage was dealt by environment (death zones, falling, etc.)
+    pub fn log_damage(&mut self, time: f32, victim: &str, attacker: Option<&str>, amount: f32) {
+        self.write_event(time, "damage", &format!("\"victim\":\"{}\",\"attacker\":{},\"amount\":{:.2}",
+                                                  escape(victim), optional_string(attacker), amount));
+    }
+
+    pub fn log_kill(&mut self, time: f32, victim: &str, killer: Option<&str>, weapon: Option<WeaponKind>) {
+        let weapon = optional_string(weapon.map(weapon_name));
+        self.write_event(time, "kill", &format!("\"victim\":\"{}\",\"killer\":{},\"weapon\":{}",
+                                                escape(victim), optional_string(killer), weapon));
+    }
+
+    /// Writes note that player typed during match.
+    pub fn log_note(&mut self, time: f32, author: &str, text: &str) {
+        self.write_event(time, "note", &format!("\"author\":\"{}\",\"text\":\"{}\"", escape(author), escape(text)));
+    }
+
+    /// Writes delayed shot of lag compensation harness with actors it hits against rewound
+    /// and current positions, `delay` is in seconds.
+    pub fn log_delayed_shot(&mut self, time: f32, delay: f32, rewound_hit: Option<&str>, current_hit: Option<&str>) {
+        self.write_event(time, "delayed_shot", &format!("\"delay\":{:.3},\"rewound_hit\":{},\"current_hit\":{}",
+                                                        delay, optional_string(rewound_hit), optional_string(current_hit)));
+    }
+
+    /// Writes scores of every participant and flushes log, so it is complete even if game
+    /// is closed right after match end.
+    pub fn log_final_scores(&mut self, time: f32, leader_board: &LeaderBoard) {
+        let mut scores = leader_board.values()
+            .iter()
+            .collect::<Vec<_>>();
+        scores.sort_by(|(_, a), (_, b)| b.kills.cmp(&a.kills).then(a.deaths.cmp(&b.deaths)));
+        let scores = scores.iter()
+            .map(|(name, score)| {
+                let weapon_kills = WEAPON_KINDS.iter()
+                    .map(|&weapon| format!("\"{}\":{}", weapon_name(weapon), score.weapon_kills(weapon)))
+                    .collect::<Vec<String>>()
+                    .join(",");
+                format!("{{\"actor\":\"{}\",\"kills\":{},\"deaths\":{},\"weapon_kills\":{{{}}}}}",
+                        escape(name), score.kills, score.deaths, weapon_kills)
+            })
+            .collect::<Vec<String>>()
+            .join(",");
+        self.write_event(time, "final_scores", &format!("\"scores\":[{}]", scores));
+        self.flush();
+    }
+
+    pub fn flush(&mut self) {
+        if let Err(e) = self.writer.flush() {
+            println!("Unable to flush match log {:?}. Reason: {:?}", self.path, e);
+        }
+    }
+}
@@ -19,6 +19,10 @@ use rg3d::{
         message::{
             UiMessageData,
             ButtonMessage,
+            DropdownListMessage,
+            ScrollBarMessage,
+            CheckBoxMessage,
+            TextMessage,
         },
         button::ButtonBuilder,
         node::UINode,
@@ -28,35 +32,173 @@ use rg3d::{
     },
     engine::resource_manager::ResourceManager,
 };
-use std::sync::mpsc::Sender;
 use crate::{
     message::Message,
-    MatchOptions,
-    DeathMatch,
+    bot::{BotDifficulty, BotWeaponSet},
     UINodeHandle,
     GameEngine,
     Gui,
     GuiMessage,
-    gui::create_scroll_bar,
+    gui::{create_scroll_bar, create_check_box},
+    loadout::{Loadout, LOADOUT_WEAPONS},
+    skin::{self, WEAPON_SKINS, DEFAULT_SKIN},
+    weapon::Weapon,
+    settings::Settings,
+    damage::{DamageMultipliers, HealthRegen},
+    DEFAULT_BOT_SIGHT_RANGE,
+    DEFAULT_SPAWN_PROTECTION,
+    DEFAULT_INTRO_COUNTDOWN,
+    match_preset::{self, MatchPreset, MatchType, MATCH_TYPES},
 };
 use crate::gui::ScrollBarData;
 
 pub struct MatchMenu {
-    sender: Sender<Message>,
     pub window: UINodeHandle,
+    dl_match_type: UINodeHandle,
+    sb_lives: UINodeHandle,
+    cb_bots_fight_each_other: UINodeHandle,
+    cb_match_log: UINodeHandle,
+    cb_observer: UINodeHandle,
+    sb_bot_sight_range: UINodeHandle,
+    sb_player_damage_dealt: UINodeHandle,
+    sb_player_damage_taken: UINodeHandle,
+    sb_bot_damage_dealt: UINodeHandle,
+    sb_spawn_protection: UINodeHandle,
+    cb_auto_balance: UINodeHandle,
+    sb_intro_countdown: UINodeHandle,
+    sb_regen_rate: UINodeHandle,
+    sb_regen_delay: UINodeHandle,
+    /// Cap of health regeneration, it is set only in preset files and from console, so menu
+    /// keeps the one of last match or applied preset.
+    regen_cap: f32,
     sb_frag_limit: UINodeHandle,
     sb_time_limit: UINodeHandle,
+    dl_bot_difficulty: UINodeHandle,
+    dl_bot_weapons: UINodeHandle,
+    cb_loadout_weapons: Vec<UINodeHandle>,
+    /// Skin selectors of loadout weapons, in the order of `LOADOUT_WEAPONS`.
+    dl_loadout_skins: Vec<UINodeHandle>,
+    sb_ammo_multiplier: UINodeHandle,
     start_button: UINodeHandle,
+    presets: Vec<MatchPreset>,
+    /// Index of preset that was applied last, if any.
+    selected_preset: Option<usize>,
+    btn_prev_preset: UINodeHandle,
+    btn_next_preset: UINodeHandle,
+    btn_delete_preset: UINodeHandle,
+    preset_name: UINodeHandle,
+    tb_preset_name: UINodeHandle,
+    btn_save_preset: UINodeHandle,
+    /// Result of last save or delete of preset.
+    preset_status: UINodeHandle,
+    /// Damage multipliers of applied preset, they're used instead of ones suggested by
+    /// difficulty when preset changes selected difficulty.
+    pending_damage_multipliers: Option<DamageMultipliers>,
 }
 
+const BOT_DIFFICULTIES: [(&str, BotDifficulty); 3] = [
+    ("Easy", BotDifficulty::Easy),
+    ("Normal", BotDifficulty::Normal),
+    ("Hard", BotDifficulty::Hard),
+];
+
+const BOT_WEAPON_SETS: [(&str, BotWeaponSet); 5] = [
+    ("AK-47", BotWeaponSet::Standard),
+    ("Rifles", BotWeaponSet::Rifles),
+    ("Plasma Rifles", BotWeaponSet::Plasma),
+    ("Rocket Launchers", BotWeaponSet::Rockets),
+    ("Random", BotWeaponSet::Random),
+];
+
 impl MatchMenu {
-    pub fn new(ui: &mut Gui, resource_manager: &mut ResourceManager, sender: Sender<Message>) -> Self {
+    pub fn new(ui: &mut Gui, resource_manager: &mut ResourceManager, settings: &Settings) -> Self {
+        let loadout = &settings.loadout;
         let common_row = Row::strict(36.0);
 
         let ctx = &mut ui.build_ctx();
+        let dl_match_type;
+        let sb_lives;
+        let cb_bots_fight_each_other;
+        let cb_match_log;
+        let cb_observer;
+        let sb_bot_sight_range;
+        let sb_player_damage_dealt;
+        let sb_player_damage_taken;
+        let sb_bot_damage_dealt;
+        let sb_spawn_protection;
+        let cb_auto_balance;
+        let sb_intro_countdown;
+        let sb_regen_rate;
+        let sb_regen_delay;
         let sb_frag_limit;
         let sb_time_limit;
+        let dl_bot_difficulty;
+        let dl_bot_weapons;
+        let mut cb_loadout_weapons = Vec::new();
+        let mut dl_loadout_skins = Vec::new();
+        let sb_ammo_multiplier;
         let start_button;
+        let btn_prev_preset;
+        let btn_next_preset;
+        let btn_delete_preset;
+        let preset_name;
+        let tb_preset_name;
+        let btn_save_preset;
+        let preset_status;
+        let presets = match_preset::load_presets();
+        // Loadout rows are placed right after common match options.
+        let loadout_row = 18;
+        let mut loadout_widgets = Vec::new();
+        for (i, (name, kind)) in LOADOUT_WEAPONS.iter().enumerate() {
+            loadout_widgets.push(TextBuilder::new(WidgetBuilder::new()
+                .on_row(loadout_row + i)
+                .on_column(0)
+                .with_margin(Thickness::uniform(2.0)))
+                .with_text(name)
+                .with_vertical_text_alignment(VerticalAlignment::Center)
+                .build(ctx));
+            let check_box = create_check_box(ctx, resource_manager, 0, 0, loadout.weapons.contains(kind));
+            cb_loadout_weapons.push(check_box);
+            let skin = skin::index_of(loadout.skin(*kind).unwrap_or_else(|| Weapon::get_definition(*kind).skin));
+            let skin_list = DropdownListBuilder::new(WidgetBuilder::new()
+                .on_column(1)
+                .with_margin(Thickness::uniform(2.0)))
+                .with_items({
+                    let mut items = Vec::new();
+                    for weapon_skin in WEAPON_SKINS.iter() {
+                        let item = DecoratorBuilder::new(
+                            BorderBuilder::new(
+                                WidgetBuilder::new()
+                                    .with_height(30.0)
+                                    .with_child(TextBuilder::new(WidgetBuilder::new()
+                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                        .with_vertical_alignment(VerticalAlignment::Center))
+                                        .with_text(weapon_skin.name)
+                                        .build(ctx))))
+                            .build(ctx);
+                        items.push(item);
+                    }
+                    items
+                })
+                .with_selected(skin)
+                .build(ctx);
+            dl_loadout_skins.push(skin_list);
+            loadout_widgets.push(GridBuilder::new(WidgetBuilder::new()
+                .on_row(loadout_row + i)
+                .on_column(1)
+                .with_child(check_box)
+                .with_child(skin_list))
+                .add_row(Row::stretch())
+                .add_column(Column::strict(32.0))
+                .add_column(Column::stretch())
+                .build(ctx));
+        }
+        let ammo_row = loadout_row + LOADOUT_WEAPONS.len();
+        let observer_row = ammo_row + 1;
+        let preset_row = observer_row + 1;
+        let save_preset_row = preset_row + 1;
+        let preset_status_row = save_preset_row + 1;
+        let start_row = preset_status_row + 1;
         let window = WindowBuilder::new(WidgetBuilder::new()
             .with_width(500.0))
             .with_title(WindowTitle::text("Match Options"))
@@ -67,37 +209,41 @@ impl MatchMenu {
                     .on_column(0))
                     .with_text("Match Type")
                     .build(ctx))
-                .with_child(DropdownListBuilder::new(WidgetBuilder::new()
-                    .on_column(1)
-                    .on_row(0))
-                    .with_items({
-                        let mut items = Vec::new();
-                        for mode in ["Deathmatch", "Team Deathmatch", "Capture The Flag"].iter() {
-                            let item = DecoratorBuilder::new(
-                                BorderBuilder::new(
-                                    WidgetBuilder::new()
-                                        .with_height(30.0)
-                                        .with_child(TextBuilder::new(WidgetBuilder::new()
-                                            .with_horizontal_alignment(HorizontalAlignment::Center)
-                                            .with_vertical_alignment(VerticalAlignment::Center))
-                                            .with_text(mode)
-                                            .build(ctx))))
-                                .build(ctx);
-                            items.push(item);
-                        }
-                        items
-                    })
-                    .build(ctx))
+                .with_child({
+                    dl_match_type = DropdownListBuilder::new(WidgetBuilder::new()
+                        .on_column(1)
+                        .on_row(0))
+                        .with_items({
+                            let mut items = Vec::new();
+                            for (mode, _) in MATCH_TYPES.iter() {
+                                let item = DecoratorBuilder::new(
+                                    BorderBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_height(30.0)
+                                            .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                .with_vertical_alignment(VerticalAlignment::Center))
+                                                .with_text(mode)
+                                                .build(ctx))))
+                                    .build(ctx);
+                                items.push(item);
+                            }
+                            items
+                        })
+                        .with_selected(0)
+                        .build(ctx);
+                    dl_match_type
+                })
                 .with_child(TextBuilder::new(WidgetBuilder::new()
                     .on_row(1)
                     .on_column(0))
-                    .with_text("Time Limit (min)")
+                    .with_text("Time Limit (min, 0 - none)")
                     .build(ctx))
                 .with_child({
                     sb_time_limit = create_scroll_bar(ctx, resource_manager, ScrollBarData {
-                        min: 5.0,
+                        min: 0.0,
                         max: 60.0,
-                        value: 10.0,
+                        value: settings.time_limit_secs / 60.0,
                         step: 1.0,
                         row: 1,
                         column: 1,
@@ -110,13 +256,13 @@ impl MatchMenu {
                 .with_child(TextBuilder::new(WidgetBuilder::new()
                     .on_row(2)
                     .on_column(0))
-                    .with_text("Frag Limit")
+                    .with_text("Frag / Score Limit (0 - none)")
                     .build(ctx))
                 .with_child({
                     sb_frag_limit = create_scroll_bar(ctx, resource_manager, ScrollBarData{
-                        min: 10.0,
+                        min: 0.0,
                         max: 200.0,
-                        value: 30.0,
+                        value: settings.frag_limit as f32,
                         step: 1.0,
                         row: 2,
                         column: 1,
@@ -139,9 +285,419 @@ impl MatchMenu {
                     .with_margin(Thickness::uniform(2.0)))
                     .with_text("Unnamed Player".to_owned())
                     .build(ctx))
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(4)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Bot Difficulty")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
                 .with_child({
-                    start_button = ButtonBuilder::new(WidgetBuilder::new()
+                    dl_bot_difficulty = DropdownListBuilder::new(WidgetBuilder::new()
                         .on_row(4)
+                        .on_column(1)
+                        .with_margin(Thickness::uniform(2.0)))
+                        .with_items({
+                            let mut items = Vec::new();
+                            for (name, _) in BOT_DIFFICULTIES.iter() {
+                                let item = DecoratorBuilder::new(
+                                    BorderBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_height(30.0)
+                                            .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                .with_vertical_alignment(VerticalAlignment::Center))
+                                                .with_text(name)
+                                                .build(ctx))))
+                                    .build(ctx);
+                                items.push(item);
+                            }
+                            items
+                        })
+                        .with_selected(1)
+                        .build(ctx);
+                    dl_bot_difficulty
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(5)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Bot Weapons")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    dl_bot_weapons = DropdownListBuilder::new(WidgetBuilder::new()
+                        .on_row(5)
+                        .on_column(1)
+                        .with_margin(Thickness::uniform(2.0)))
+                        .with_items({
+                            let mut items = Vec::new();
+                            for (name, _) in BOT_WEAPON_SETS.iter() {
+                                let item = DecoratorBuilder::new(
+                                    BorderBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_height(30.0)
+                                            .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                .with_vertical_alignment(VerticalAlignment::Center))
+                                                .with_text(name)
+                                                .build(ctx))))
+                                    .build(ctx);
+                                items.push(item);
+                            }
+                            items
+                        })
+                        .with_selected(0)
+                        .build(ctx);
+                    dl_bot_weapons
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(6)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Lives (Last Man Standing)")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_lives = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 1.0,
+                        max: 10.0,
+                        value: 3.0,
+                        step: 1.0,
+                        row: 6,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_lives
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(7)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Bots Fight Each Other")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    cb_bots_fight_each_other = create_check_box(ctx, resource_manager, 7, 1, settings.bots_fight_each_other);
+                    cb_bots_fight_each_other
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(8)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Write Match Log")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    cb_match_log = create_check_box(ctx, resource_manager, 8, 1, settings.match_log_enabled);
+                    cb_match_log
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(9)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Bot Sight Range")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_bot_sight_range = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 10.0,
+                        max: 150.0,
+                        value: settings.bot_sight_range,
+                        step: 5.0,
+                        row: 9,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_bot_sight_range
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(10)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Player Damage Dealt")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_player_damage_dealt = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 0.25,
+                        max: 3.0,
+                        value: settings.damage_multipliers.player_dealt,
+                        step: 0.05,
+                        row: 10,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_player_damage_dealt
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(11)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Player Damage Taken")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_player_damage_taken = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 0.25,
+                        max: 3.0,
+                        value: settings.damage_multipliers.player_taken,
+                        step: 0.05,
+                        row: 11,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_player_damage_taken
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(12)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Bot Damage Dealt")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_bot_damage_dealt = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 0.25,
+                        max: 3.0,
+                        value: settings.damage_multipliers.bot_dealt,
+                        step: 0.05,
+                        row: 12,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_bot_damage_dealt
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(13)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Spawn Protection (s)")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_spawn_protection = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 0.0,
+                        max: 5.0,
+                        value: settings.spawn_protection,
+                        step: 0.5,
+                        row: 13,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_spawn_protection
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(14)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Auto-Balance Bots")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    cb_auto_balance = create_check_box(ctx, resource_manager, 14, 1, settings.auto_balance);
+                    cb_auto_balance
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(15)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Intro Countdown (s)")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_intro_countdown = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 0.0,
+                        max: 10.0,
+                        value: settings.intro_countdown,
+                        step: 1.0,
+                        row: 15,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_intro_countdown
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(16)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Health Regen (HP/s, 0 - off)")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_regen_rate = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 0.0,
+                        max: 20.0,
+                        value: settings.health_regen.rate,
+                        step: 1.0,
+                        row: 16,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_regen_rate
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(17)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Regen Delay (s)")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_regen_delay = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 0.0,
+                        max: 10.0,
+                        value: settings.health_regen.delay,
+                        step: 0.5,
+                        row: 17,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_regen_delay
+                })
+                .with_children(&loadout_widgets)
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(ammo_row)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Starting Ammo")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    sb_ammo_multiplier = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                        min: 0.5,
+                        max: 2.0,
+                        value: loadout.ammo_multiplier,
+                        step: 0.1,
+                        row: ammo_row,
+                        column: 1,
+                        margin: Thickness::uniform(2.0),
+                        show_value: true,
+                        orientation: Orientation::Horizontal,
+                    });
+                    sb_ammo_multiplier
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(observer_row)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Watch Bots (Observer)")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child({
+                    cb_observer = create_check_box(ctx, resource_manager, observer_row, 1, settings.observer_mode);
+                    cb_observer
+                })
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(preset_row)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Preset")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child(GridBuilder::new(WidgetBuilder::new()
+                    .on_row(preset_row)
+                    .on_column(1)
+                    .with_child({
+                        btn_prev_preset = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_column(0)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text("<")
+                            .build(ctx);
+                        btn_prev_preset
+                    })
+                    .with_child({
+                        preset_name = TextBuilder::new(WidgetBuilder::new()
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text(if presets.is_empty() { "No Presets" } else { "Not Selected" })
+                            .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx);
+                        preset_name
+                    })
+                    .with_child({
+                        btn_next_preset = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_column(2)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text(">")
+                            .build(ctx);
+                        btn_next_preset
+                    })
+                    .with_child({
+                        btn_delete_preset = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_column(3)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text("Delete")
+                            .build(ctx);
+                        btn_delete_preset
+                    }))
+                    .add_column(Column::strict(30.0))
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(30.0))
+                    .add_column(Column::strict(70.0))
+                    .add_row(Row::stretch())
+                    .build(ctx))
+                .with_child(TextBuilder::new(WidgetBuilder::new()
+                    .on_row(save_preset_row)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(2.0)))
+                    .with_text("Save As Preset")
+                    .with_vertical_text_alignment(VerticalAlignment::Center)
+                    .build(ctx))
+                .with_child(GridBuilder::new(WidgetBuilder::new()
+                    .on_row(save_preset_row)
+                    .on_column(1)
+                    .with_child({
+                        tb_preset_name = TextBoxBuilder::new(WidgetBuilder::new()
+                            .on_column(0)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text(String::new())
+                            .build(ctx);
+                        tb_preset_name
+                    })
+                    .with_child({
+                        btn_save_preset = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text("Save")
+                            .build(ctx);
+                        btn_save_preset
+                    }))
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(70.0))
+                    .add_row(Row::stretch())
+                    .build(ctx))
+                .with_child({
+                    preset_status = TextBuilder::new(WidgetBuilder::new()
+                        .on_row(preset_status_row)
+                        .on_column(1)
+                        .with_margin(Thickness::uniform(2.0)))
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .build(ctx);
+                    preset_status
+                })
+                .with_child({
+                    start_button = ButtonBuilder::new(WidgetBuilder::new()
+                        .on_row(start_row)
                         .on_column(1))
                         .with_text("Start")
                         .build(ctx);
@@ -154,48 +710,299 @@ impl MatchMenu {
                 .add_row(common_row)
                 .add_row(common_row)
                 .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
+                .add_row(common_row)
                 .add_row(Row::stretch())
                 .build(ctx))
             .build(ctx);
         Self {
-            sender,
             window,
+            dl_match_type,
+            sb_lives,
+            cb_bots_fight_each_other,
+            cb_match_log,
+            cb_observer,
+            sb_bot_sight_range,
+            sb_player_damage_dealt,
+            sb_player_damage_taken,
+            sb_bot_damage_dealt,
+            sb_spawn_protection,
+            cb_auto_balance,
+            sb_intro_countdown,
+            sb_regen_rate,
+            sb_regen_delay,
+            regen_cap: settings.health_regen.cap,
             sb_frag_limit,
             sb_time_limit,
+            dl_bot_difficulty,
+            dl_bot_weapons,
+            cb_loadout_weapons,
+            dl_loadout_skins,
+            sb_ammo_multiplier,
             start_button,
+            presets,
+            selected_preset: None,
+            btn_prev_preset,
+            btn_next_preset,
+            btn_delete_preset,
+            preset_name,
+            tb_preset_name,
+            btn_save_preset,
+            preset_status,
+            pending_damage_multipliers: None,
         }
     }
 
-    pub fn handle_ui_event(&mut self, engine: &mut GameEngine, message: &GuiMessage) {
+    /// Reads current state of widgets as unnamed preset.
+    fn read_preset(&self, ui: &Gui) -> MatchPreset {
+        let scroll_bar_value = |handle: UINodeHandle, default: f32| {
+            if let UINode::ScrollBar(scroll_bar) = ui.node(handle) {
+                scroll_bar.value()
+            } else {
+                default
+            }
+        };
+        let is_checked = |handle: UINodeHandle| {
+            if let UINode::CheckBox(check_box) = ui.node(handle) {
+                check_box.checked().unwrap_or(false)
+            } else {
+                false
+            }
+        };
+        let selection = |handle: UINodeHandle| {
+            if let UINode::DropdownList(dropdown_list) = ui.node(handle) {
+                dropdown_list.selection()
+            } else {
+                None
+            }
+        };
+
+        let weapons = LOADOUT_WEAPONS.iter()
+            .zip(self.cb_loadout_weapons.iter())
+            .filter(|(_, check_box)| is_checked(**check_box))
+            .map(|((_, kind), _)| *kind)
+            .collect();
+        let skins = LOADOUT_WEAPONS.iter()
+            .zip(self.dl_loadout_skins.iter())
+            .map(|((_, kind), skin_list)| {
+                let skin = selection(*skin_list)
+                    .and_then(|i| WEAPON_SKINS.get(i))
+                    .map_or(DEFAULT_SKIN, |skin| skin.id);
+                (*kind, skin.to_owned())
+            })
+            .collect();
+
+        MatchPreset {
+            name: String::new(),
+            match_type: selection(self.dl_match_type)
+                .and_then(|i| MATCH_TYPES.get(i))
+                .map_or(MatchType::DeathMatch, |(_, match_type)| *match_type),
+            time_limit_minutes: scroll_bar_value(self.sb_time_limit, 0.0),
+            frag_limit: scroll_bar_value(self.sb_frag_limit, 0.0) as u32,
+            bot_difficulty: selection(self.dl_bot_difficulty)
+                .and_then(|i| BOT_DIFFICULTIES.get(i))
+                .map(|(_, difficulty)| *difficulty)
+                .unwrap_or_default(),
+            bot_weapons: selection(self.dl_bot_weapons)
+                .and_then(|i| BOT_WEAPON_SETS.get(i))
+                .map(|(_, set)| *set)
+                .unwrap_or_default(),
+            lives: scroll_bar_value(self.sb_lives, 0.0) as u32,
+            bots_fight_each_other: is_checked(self.cb_bots_fight_each_other),
+            bot_sight_range: scroll_bar_value(self.sb_bot_sight_range, DEFAULT_BOT_SIGHT_RANGE),
+            damage_multipliers: DamageMultipliers {
+                player_dealt: scroll_bar_value(self.sb_player_damage_dealt, 1.0),
+                player_taken: scroll_bar_value(self.sb_player_damage_taken, 1.0),
+                bot_dealt: scroll_bar_value(self.sb_bot_damage_dealt, 1.0),
+            },
+            spawn_protection: scroll_bar_value(self.sb_spawn_protection, DEFAULT_SPAWN_PROTECTION),
+            health_regen: HealthRegen {
+                rate: scroll_bar_value(self.sb_regen_rate, 0.0),
+                delay: scroll_bar_value(self.sb_regen_delay, HealthRegen::default().delay),
+                cap: self.regen_cap,
+            },
+            auto_balance: is_checked(self.cb_auto_balance),
+            intro_countdown: scroll_bar_value(self.sb_intro_countdown, DEFAULT_INTRO_COUNTDOWN),
+            loadout: Loadout {
+                weapons,
+                ammo_multiplier: scroll_bar_value(self.sb_ammo_multiplier, 1.0),
+                skins,
+            },
+            observer: is_checked(self.cb_observer),
+        }
+    }
+
+    /// Fills widgets with values of given preset.
+    fn apply_preset(&mut self, ui: &mut Gui, preset: &MatchPreset) {
+        let match_type = MATCH_TYPES.iter().position(|(_, match_type)| *match_type == preset.match_type);
+        ui.send_message(DropdownListMessage::selection(self.dl_match_type, match_type));
+        let difficulty = BOT_DIFFICULTIES.iter().position(|(_, difficulty)| *difficulty == preset.bot_difficulty);
+        if let UINode::DropdownList(dropdown_list) = ui.node(self.dl_bot_difficulty) {
+            if dropdown_list.selection() != difficulty {
+                self.pending_damage_multipliers = Some(preset.damage_multipliers);
+            }
+        }
+        ui.send_message(DropdownListMessage::selection(self.dl_bot_difficulty, difficulty));
+        let weapons = BOT_WEAPON_SETS.iter().position(|(_, set)| *set == preset.bot_weapons);
+        ui.send_message(DropdownListMessage::selection(self.dl_bot_weapons, weapons));
+
+        ui.send_message(ScrollBarMessage::value(self.sb_time_limit, preset.time_limit_minutes));
+        ui.send_message(ScrollBarMessage::value(self.sb_frag_limit, preset.frag_limit as f32));
+        ui.send_message(ScrollBarMessage::value(self.sb_lives, preset.lives as f32));
+        ui.send_message(ScrollBarMessage::value(self.sb_bot_sight_range, preset.bot_sight_range));
+        ui.send_message(ScrollBarMessage::value(self.sb_player_damage_dealt, preset.damage_multipliers.player_dealt));
+        ui.send_message(ScrollBarMessage::value(self.sb_player_damage_taken, preset.damage_multipliers.player_taken));
+        ui.send_message(ScrollBarMessage::value(self.sb_bot_damage_dealt, preset.damage_multipliers.bot_dealt));
+        ui.send_message(ScrollBarMessage::value(self.sb_spawn_protection, preset.spawn_protection));
+        ui.send_message(ScrollBarMessage::value(self.sb_intro_countdown, preset.intro_countdown));
+        ui.send_message(ScrollBarMessage::value(self.sb_regen_rate, preset.health_regen.rate));
+        ui.send_message(ScrollBarMessage::value(self.sb_regen_delay, preset.health_regen.delay));
+        self.regen_cap = preset.health_regen.cap;
+        ui.send_message(ScrollBarMessage::value(self.sb_ammo_multiplier, preset.loadout.ammo_multiplier));
+
+        ui.send_message(CheckBoxMessage::check(self.cb_bots_fight_each_other, Some(preset.bots_fight_each_other)));
+        ui.send_message(CheckBoxMessage::check(self.cb_auto_balance, Some(preset.auto_balance)));
+        ui.send_message(CheckBoxMessage::check(self.cb_observer, Some(preset.observer)));
+        for ((_, kind), check_box) in LOADOUT_WEAPONS.iter().zip(self.cb_loadout_weapons.iter()) {
+            ui.send_message(CheckBoxMessage::check(*check_box, Some(preset.loadout.weapons.contains(kind))));
+        }
+    }
+
+    fn select_preset(&mut self, ui: &mut Gui, index: Option<usize>) {
+        self.selected_preset = index;
+        let name = match index.and_then(|i| self.presets.get(i)).cloned() {
+            Some(preset) => {
+                self.apply_preset(ui, &preset);
+                preset.name
+            }
+            None if self.presets.is_empty() => "No Presets".to_owned(),
+            None => "Not Selected".to_owned(),
+        };
+        ui.send_message(TextMessage::text(self.preset_name, name));
+    }
+
+    fn set_preset_status(&self, ui: &mut Gui, text: String) {
+        ui.send_message(TextMessage::text(self.preset_status, text));
+    }
+
+    fn save_preset(&mut self, ui: &mut Gui) {
+        let name = if let UINode::TextBox(text_box) = ui.node(self.tb_preset_name) {
+            text_box.text().trim().to_owned()
+        } else {
+            String::new()
+        };
+        let preset = MatchPreset {
+            name,
+            ..self.read_preset(ui)
+        };
+        match preset.save() {
+            Ok(_) => {
+                self.set_preset_status(ui, format!("Preset {} saved.", preset.name));
+                self.presets = match_preset::load_presets();
+                let index = self.presets.iter().position(|p| p.name == preset.name);
+                self.select_preset(ui, index);
+            }
+            Err(e) => self.set_preset_status(ui, format!("Unable to save preset: {}", e)),
+        }
+    }
+
+    fn delete_preset(&mut self, ui: &mut Gui) {
+        let name = match self.selected_preset.and_then(|i| self.presets.get(i)) {
+            Some(preset) => preset.name.clone(),
+            None => {
+                self.set_preset_status(ui, "Select preset to delete.".to_owned());
+                return;
+            }
+        };
+        match MatchPreset::delete(&name) {
+            Ok(_) => self.set_preset_status(ui, format!("Preset {} deleted.", name)),
+            Err(e) => self.set_preset_status(ui, format!("Unable to delete preset: {}", e)),
+        }
+        self.presets = match_preset::load_presets();
+        self.select_preset(ui, None);
+    }
+
+    /// Returns message that should be sent to start a match, menu decides what to do with it
+    /// because it may require confirmation from user.
+    pub fn handle_ui_event(&mut self, engine: &mut GameEngine, message: &GuiMessage) -> Option<Message> {
         let ui = &mut engine.user_interface;
 
+        // Selected difficulty suggests its damage multipliers, they still can be tuned after.
+        if let UiMessageData::DropdownList(DropdownListMessage::SelectionChanged(selection)) = &message.data {
+            if message.destination == self.dl_bot_difficulty {
+                if let Some((_, difficulty)) = selection.and_then(|i| BOT_DIFFICULTIES.get(i)) {
+                    let damage_multipliers = self.pending_damage_multipliers
+                        .take()
+                        .unwrap_or_else(|| difficulty.damage_multipliers());
+                    ui.send_message(ScrollBarMessage::value(self.sb_player_damage_dealt, damage_multipliers.player_dealt));
+                    ui.send_message(ScrollBarMessage::value(self.sb_player_damage_taken, damage_multipliers.player_taken));
+                    ui.send_message(ScrollBarMessage::value(self.sb_bot_damage_dealt, damage_multipliers.bot_dealt));
+                }
+            }
+        }
+
         if let UiMessageData::Button(msg) = &message.data {
             if let ButtonMessage::Click = msg {
-                if message.destination == self.start_button {
-                    let time_limit_minutes =
-                        if let UINode::ScrollBar(scroll_bar) = ui.node(self.sb_time_limit) {
-                            scroll_bar.value()
-                        } else {
-                            0.0
+                if message.destination == self.btn_prev_preset || message.destination == self.btn_next_preset {
+                    let count = self.presets.len();
+                    if count != 0 {
+                        let index = match self.selected_preset {
+                            Some(i) if message.destination == self.btn_prev_preset => (i + count - 1) % count,
+                            Some(i) => (i + 1) % count,
+                            None if message.destination == self.btn_prev_preset => count - 1,
+                            None => 0,
                         };
+                        self.select_preset(ui, Some(index));
+                    }
+                } else if message.destination == self.btn_delete_preset {
+                    self.delete_preset(ui);
+                } else if message.destination == self.btn_save_preset {
+                    self.save_preset(ui);
+                } else if message.destination == self.start_button {
+                    let preset = self.read_preset(ui);
+                    if let Err(e) = preset.check() {
+                        self.set_preset_status(ui, format!("Unable to start match: {}", e));
+                        return None;
+                    }
 
-                    let frag_limit =
-                        if let UINode::ScrollBar(scroll_bar) = ui.node(self.sb_frag_limit) {
-                            scroll_bar.value()
+                    let match_log =
+                        if let UINode::CheckBox(check_box) = ui.node(self.cb_match_log) {
+                            check_box.checked().unwrap_or(false)
                         } else {
-                            0.0
+                            false
                         };
 
-                    let options = MatchOptions::DeathMatch(DeathMatch {
-                        time_limit_secs: time_limit_minutes * 60.0,
-                        frag_limit: frag_limit as u32,
+                    return Some(Message::StartNewGame {
+                        options: preset.options(),
+                        loadout: preset.loadout,
+                        match_log,
+                        observer: preset.observer,
                     });
-
-                    self.sender
-                        .send(Message::StartNewGame { options })
-                        .unwrap();
                 }
             }
         }
+
+        None
     }
-}
\ No newline at end of file
+}
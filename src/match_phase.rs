@@ -0,0 +1,86 @@
+//! Phases of a match that are common for every mode. Match starts with intro countdown
+//! during which actors can look around but can't move or shoot, then active play lasts
+//! until the end of the match. Zero countdown starts match right away.
+
+use rg3d::core::visitor::{Visit, VisitResult, Visitor};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MatchPhase {
+    Countdown {
+        time_left: f32
+    },
+    Active,
+}
+
+impl Default for MatchPhase {
+    fn default() -> Self {
+        MatchPhase::Active
+    }
+}
+
+impl MatchPhase {
+    pub fn new(countdown: f32) -> Self {
+        if countdown > 0.0 {
+            MatchPhase::Countdown { time_left: countdown }
+        } else {
+            MatchPhase::Active
+        }
+    }
+
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(MatchPhase::Countdown { time_left: 0.0 }),
+            1 => Ok(MatchPhase::Active),
+            _ => Err(format!("Invalid match phase {}", id))
+        }
+    }
+
+    fn id(self) -> u32 {
+        match self {
+            MatchPhase::Countdown { .. } => 0,
+            MatchPhase::Active => 1,
+        }
+    }
+
+    pub fn is_active(self) -> bool {
+        self == MatchPhase::Active
+    }
+
+    /// Whole seconds left until match starts as they're shown on HUD, None if match
+    /// is already active.
+    pub fn seconds_left(self) -> Option<u32> {
+        match self {
+            MatchPhase::Countdown { time_left } => Some(time_left.ceil() as u32),
+            MatchPhase::Active => None,
+        }
+    }
+
+    /// Advances countdown, returns true if match became active during this update.
+    pub fn update(&mut self, dt: f32) -> bool {
+        if let MatchPhase::Countdown { time_left } = self {
+            *time_left -= dt;
+            if *time_left <= 0.0 {
+                *self = MatchPhase::Active;
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Visit for MatchPhase {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+        if let MatchPhase::Countdown { time_left } = self {
+            time_left.visit("TimeLeft", visitor)?;
+        }
+
+        visitor.leave_region()
+    }
+}
@@ -0,0 +1,368 @@
+//! Match presets are named bundles of match options that are saved from match menu and
+//! picked later to fill it in. Every preset is a text file in [`PRESETS_DIR`] with the same
+//! `name = value` format as exported settings, name of file is name of preset. Presets are
+//! validated when they're loaded, invalid files are reported and skipped.
+
+use crate::{
+    MatchOptions,
+    DeathMatch,
+    TeamDeathMatch,
+    CaptureTheFlag,
+    LastManStanding,
+    TargetRange,
+    Domination,
+    bot::{BotDifficulty, BotWeaponSet},
+    damage::{DamageMultipliers, HealthRegen},
+    loadout::Loadout,
+    weapon::WeaponKind,
+    settings::{parse_bool, parse_u32, parse_f32},
+    DEFAULT_BOT_SIGHT_RANGE,
+    DEFAULT_SPAWN_PROTECTION,
+    DEFAULT_INTRO_COUNTDOWN,
+};
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+pub const PRESETS_DIR: &str = "data/presets";
+const PRESET_EXTENSION: &str = "txt";
+/// Longest name of preset, in characters.
+pub const MAX_PRESET_NAME_LEN: usize = 32;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MatchType {
+    DeathMatch,
+    TeamDeathMatch,
+    CaptureTheFlag,
+    LastManStanding,
+    TargetRange,
+    Domination,
+}
+
+/// Every match type with its name, in the order of ids.
+pub const MATCH_TYPES: [(&str, MatchType); 6] = [
+    ("Deathmatch", MatchType::DeathMatch),
+    ("Team Deathmatch", MatchType::TeamDeathMatch),
+    ("Capture The Flag", MatchType::CaptureTheFlag),
+    ("Last Man Standing", MatchType::LastManStanding),
+    ("Target Range", MatchType::TargetRange),
+    ("Domination", MatchType::Domination),
+];
+
+impl MatchType {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match MATCH_TYPES.get(id as usize) {
+            Some((_, match_type)) => Ok(*match_type),
+            None => Err(format!("Invalid match type {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            MatchType::DeathMatch => 0,
+            MatchType::TeamDeathMatch => 1,
+            MatchType::CaptureTheFlag => 2,
+            MatchType::LastManStanding => 3,
+            MatchType::TargetRange => 4,
+            MatchType::Domination => 5,
+        }
+    }
+
+    /// Whether match of this type can be played. There are no flags on the map yet, so
+    /// capture the flag is listed but can't be started.
+    pub fn is_supported(self) -> bool {
+        self != MatchType::CaptureTheFlag
+    }
+}
+
+/// Everything that can be chosen in match menu. There is only one map, so map is not part
+/// of preset.
+#[derive(Clone, PartialEq, Debug)]
+pub struct MatchPreset {
+    pub name: String,
+    pub match_type: MatchType,
+    pub time_limit_minutes: f32,
+    pub frag_limit: u32,
+    pub bot_difficulty: BotDifficulty,
+    pub bot_weapons: BotWeaponSet,
+    /// Lives of every combatant in last man standing.
+    pub lives: u32,
+    pub bots_fight_each_other: bool,
+    pub bot_sight_range: f32,
+    pub damage_multipliers: DamageMultipliers,
+    pub spawn_protection: f32,
+    pub health_regen: HealthRegen,
+    pub auto_balance: bool,
+    pub intro_countdown: f32,
+    pub loadout: Loadout,
+    pub observer: bool,
+}
+
+impl Default for MatchPreset {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            match_type: MatchType::DeathMatch,
+            time_limit_minutes: 0.0,
+            frag_limit: 0,
+            bot_difficulty: Default::default(),
+            bot_weapons: Default::default(),
+            lives: 3,
+            bots_fight_each_other: false,
+            bot_sight_range: DEFAULT_BOT_SIGHT_RANGE,
+            damage_multipliers: Default::default(),
+            spawn_protection: DEFAULT_SPAWN_PROTECTION,
+            health_regen: Default::default(),
+            auto_balance: false,
+            intro_countdown: DEFAULT_INTRO_COUNTDOWN,
+            loadout: Default::default(),
+            observer: false,
+        }
+    }
+}
+
+/// Returns error if given name can't be used as name of preset file.
+pub fn check_preset_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        Err("Enter name of preset.".to_owned())
+    } else if name.chars().count() > MAX_PRESET_NAME_LEN {
+        Err(format!("Name of preset must be at most {} characters long.", MAX_PRESET_NAME_LEN))
+    } else if !name.chars().all(|c| c.is_alphanumeric() || c == ' ' || c == '-' || c == '_') {
+        Err("Name of preset can have only letters, digits, spaces, '-' and '_'.".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+fn preset_path(name: &str) -> PathBuf {
+    Path::new(PRESETS_DIR).join(format!("{}.{}", name, PRESET_EXTENSION))
+}
+
+impl MatchPreset {
+    /// Returns error if match can't be started from this preset.
+    pub fn check(&self) -> Result<(), String> {
+        if self.match_type.is_supported() {
+            Ok(())
+        } else {
+            let name = MATCH_TYPES.iter()
+                .find(|(_, match_type)| *match_type == self.match_type)
+                .map_or("This match type", |(name, _)| name);
+            Err(format!("{} is not supported yet.", name))
+        }
+    }
+
+    /// Returns options of a match that is started from this preset.
+    pub fn options(&self) -> MatchOptions {
+        let time_limit_secs = self.time_limit_minutes * 60.0;
+        match self.match_type {
+            MatchType::LastManStanding => MatchOptions::LastManStanding(LastManStanding {
+                time_limit_secs,
+                lives: self.lives,
+                bot_difficulty: self.bot_difficulty,
+                bot_weapons: self.bot_weapons,
+                bots_fight_each_other: self.bots_fight_each_other,
+                bot_sight_range: self.bot_sight_range,
+                damage_multipliers: self.damage_multipliers,
+                spawn_protection: self.spawn_protection,
+                health_regen: self.health_regen,
+                auto_balance: self.auto_balance,
+                intro_countdown: self.intro_countdown,
+            }),
+            // Bot and damage options do not matter in practice without bots.
+            MatchType::TargetRange => MatchOptions::TargetRange(TargetRange {
+                time_limit_secs,
+            }),
+            // Frag limit of menu is score limit of domination.
+            MatchType::Domination => MatchOptions::Domination(Domination {
+                time_limit_secs,
+                score_limit: self.frag_limit,
+                bot_difficulty: self.bot_difficulty,
+                bot_weapons: self.bot_weapons,
+                bots_fight_each_other: self.bots_fight_each_other,
+                bot_sight_range: self.bot_sight_range,
+                damage_multipliers: self.damage_multipliers,
+                spawn_protection: self.spawn_protection,
+                health_regen: self.health_regen,
+                auto_balance: self.auto_balance,
+                intro_countdown: self.intro_countdown,
+                ..Default::default()
+            }),
+            // Frag limit of menu is limit of team frags in team deathmatch.
+            MatchType::TeamDeathMatch => MatchOptions::TeamDeathMatch(TeamDeathMatch {
+                time_limit_secs,
+                team_frag_limit: self.frag_limit,
+                bot_difficulty: self.bot_difficulty,
+                bot_weapons: self.bot_weapons,
+                bots_fight_each_other: self.bots_fight_each_other,
+                bot_sight_range: self.bot_sight_range,
+                damage_multipliers: self.damage_multipliers,
+                spawn_protection: self.spawn_protection,
+                health_regen: self.health_regen,
+                auto_balance: self.auto_balance,
+                intro_countdown: self.intro_countdown,
+                ..Default::default()
+            }),
+            // Never started, presets of unsupported types are refused by `check`.
+            MatchType::CaptureTheFlag => MatchOptions::CaptureTheFlag(CaptureTheFlag {
+                time_limit_secs,
+                flag_limit: self.frag_limit,
+                bot_difficulty: self.bot_difficulty,
+                bot_weapons: self.bot_weapons,
+                bots_fight_each_other: self.bots_fight_each_other,
+                bot_sight_range: self.bot_sight_range,
+                damage_multipliers: self.damage_multipliers,
+                spawn_protection: self.spawn_protection,
+                health_regen: self.health_regen,
+                auto_balance: self.auto_balance,
+                intro_countdown: self.intro_countdown,
+                ..Default::default()
+            }),
+            MatchType::DeathMatch => MatchOptions::DeathMatch(DeathMatch {
+                time_limit_secs,
+                frag_limit: self.frag_limit,
+                bot_difficulty: self.bot_difficulty,
+                bot_weapons: self.bot_weapons,
+                bots_fight_each_other: self.bots_fight_each_other,
+                bot_sight_range: self.bot_sight_range,
+                damage_multipliers: self.damage_multipliers,
+                spawn_protection: self.spawn_protection,
+                health_regen: self.health_regen,
+                auto_balance: self.auto_balance,
+                intro_countdown: self.intro_countdown,
+            }),
+        }
+    }
+
+    /// Writes preset into its file in [`PRESETS_DIR`], file of preset with same name is
+    /// overwritten.
+    pub fn save(&self) -> Result<(), String> {
+        check_preset_name(&self.name)?;
+        self.check()?;
+        std::fs::create_dir_all(PRESETS_DIR).map_err(|e| format!("unable to create {}: {}", PRESETS_DIR, e))?;
+
+        let weapons = self.loadout.weapons
+            .iter()
+            .map(|kind| kind.id().to_string())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let values = [
+            ("match_type", self.match_type.id().to_string()),
+            ("time_limit_minutes", self.time_limit_minutes.to_string()),
+            ("frag_limit", self.frag_limit.to_string()),
+            ("bot_difficulty", self.bot_difficulty.id().to_string()),
+            ("bot_weapons", self.bot_weapons.id().to_string()),
+            ("lives", self.lives.to_string()),
+            ("bots_fight_each_other", self.bots_fight_each_other.to_string()),
+            ("bot_sight_range", self.bot_sight_range.to_string()),
+            ("dmg_dealt", self.damage_multipliers.player_dealt.to_string()),
+            ("dmg_taken", self.damage_multipliers.player_taken.to_string()),
+            ("bot_dmg", self.damage_multipliers.bot_dealt.to_string()),
+            ("spawn_protection", self.spawn_protection.to_string()),
+            ("regen_rate", self.health_regen.rate.to_string()),
+            ("regen_delay", self.health_regen.delay.to_string()),
+            ("regen_cap", self.health_regen.cap.to_string()),
+            ("auto_balance", self.auto_balance.to_string()),
+            ("intro_countdown", self.intro_countdown.to_string()),
+            ("loadout.weapons", weapons),
+            ("loadout.ammo_multiplier", self.loadout.ammo_multiplier.to_string()),
+            ("observer", self.observer.to_string()),
+        ];
+        let mut text = format!("# Rusty Shooter match preset {}\n", self.name);
+        for (name, value) in values.iter() {
+            writeln!(text, "{} = {}", name, value).unwrap();
+        }
+        let path = preset_path(&self.name);
+        std::fs::write(&path, text).map_err(|e| format!("unable to write {}: {}", path.display(), e))
+    }
+
+    /// Reads preset from given file, name of preset is taken from name of file. Values that
+    /// are not listed in file are left default.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let name = path.file_stem()
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| format!("invalid name of preset file {}", path.display()))?;
+        check_preset_name(name)?;
+        let text = std::fs::read_to_string(path).map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+
+        let mut preset = MatchPreset {
+            name: name.to_owned(),
+            ..Default::default()
+        };
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name.trim(), value.trim()),
+                _ => return Err(format!("line {}: expected `name = value`", index + 1)),
+            };
+            preset.set_value(name, value).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        }
+        preset.check()?;
+        Ok(preset)
+    }
+
+    fn set_value(&mut self, name: &str, value: &str) -> Result<(), String> {
+        // Ranges are the same as ranges of widgets of match menu.
+        match name {
+            "match_type" => self.match_type = MatchType::from_id(parse_u32(value)?)?,
+            "time_limit_minutes" => self.time_limit_minutes = parse_f32(value, 0.0, 60.0)?,
+            "frag_limit" => self.frag_limit = parse_u32(value)?.min(200),
+            "bot_difficulty" => self.bot_difficulty = BotDifficulty::from_id(parse_u32(value)?)?,
+            "bot_weapons" => self.bot_weapons = BotWeaponSet::from_id(parse_u32(value)?)?,
+            "lives" => self.lives = parse_u32(value)?.max(1).min(10),
+            "bots_fight_each_other" => self.bots_fight_each_other = parse_bool(value)?,
+            "bot_sight_range" => self.bot_sight_range = parse_f32(value, 10.0, 150.0)?,
+            "dmg_dealt" => self.damage_multipliers.player_dealt = parse_f32(value, 0.25, 3.0)?,
+            "dmg_taken" => self.damage_multipliers.player_taken = parse_f32(value, 0.25, 3.0)?,
+            "bot_dmg" => self.damage_multipliers.bot_dealt = parse_f32(value, 0.25, 3.0)?,
+            "spawn_protection" => self.spawn_protection = parse_f32(value, 0.0, 5.0)?,
+            "regen_rate" => self.health_regen.rate = parse_f32(value, 0.0, 20.0)?,
+            "regen_delay" => self.health_regen.delay = parse_f32(value, 0.0, 10.0)?,
+            "regen_cap" => self.health_regen.cap = parse_f32(value, 1.0, 150.0)?,
+            "auto_balance" => self.auto_balance = parse_bool(value)?,
+            "intro_countdown" => self.intro_countdown = parse_f32(value, 0.0, 10.0)?,
+            "loadout.weapons" => {
+                self.loadout.weapons = value.split_whitespace()
+                    .map(|id| parse_u32(id).and_then(WeaponKind::new))
+                    .collect::<Result<_, _>>()?;
+            }
+            "loadout.ammo_multiplier" => self.loadout.ammo_multiplier = parse_f32(value, 0.5, 2.0)?,
+            "observer" => self.observer = parse_bool(value)?,
+            _ => return Err(format!("unknown value {}", name)),
+        }
+        Ok(())
+    }
+
+    /// Removes file of preset with given name.
+    pub fn delete(name: &str) -> Result<(), String> {
+        check_preset_name(name)?;
+        let path = preset_path(name);
+        std::fs::remove_file(&path).map_err(|e| format!("unable to remove {}: {}", path.display(), e))
+    }
+}
+
+/// Loads every valid preset from [`PRESETS_DIR`] sorted by name, invalid presets are
+/// reported and skipped. There are no presets if directory does not exist.
+pub fn load_presets() -> Vec<MatchPreset> {
+    let entries = match std::fs::read_dir(PRESETS_DIR) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut presets = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map_or(false, |extension| extension == PRESET_EXTENSION))
+        .filter_map(|path| match MatchPreset::load(&path) {
+            Ok(preset) => Some(preset),
+            Err(e) => {
+                println!("Match preset {} is invalid and skipped. Reason: {}", path.display(), e);
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    presets.sort_by(|a, b| a.name.cmp(&b.name));
+    presets
+}
@@ -0,0 +1,290 @@
+//! Match summary is shown when match ends, it lists stats of every participant that were
+//! collected by leader board during the match and allows to play same match again or to vote
+//! for the next one, see [`MatchVote`].
+
+use crate::{
+    message::Message,
+    leader_board::{LeaderBoard, PersonalScore},
+    character::Team,
+    weapon::WEAPON_KINDS,
+    match_vote::{MatchVote, VoteOption},
+    MatchOptions,
+    UINodeHandle,
+    Gui,
+    GuiMessage,
+};
+use rg3d::{
+    gui::{
+        grid::{GridBuilder, Row, Column},
+        window::{WindowBuilder, WindowTitle},
+        widget::WidgetBuilder,
+        text::TextBuilder,
+        button::ButtonBuilder,
+        message::{
+            UiMessageData,
+            ButtonMessage,
+            WindowMessage,
+            WidgetMessage,
+            TextMessage,
+        },
+        brush::Brush,
+        Thickness,
+        HorizontalAlignment,
+        VerticalAlignment,
+    },
+    core::color::Color,
+};
+use std::sync::mpsc::Sender;
+
+/// Kills made with each weapon go between these columns.
+const LEADING_COLUMNS: [&str; 5] = ["Name", "Kills", "Deaths", "Accuracy", "Damage"];
+const TRAILING_COLUMNS: [&str; 1] = ["Favorite Weapon"];
+const WINNER_COLOR: Color = Color::opaque(255, 200, 0);
+
+pub struct MatchSummary {
+    sender: Sender<Message>,
+    pub window: UINodeHandle,
+    result_text: UINodeHandle,
+    table_root: UINodeHandle,
+    btn_rematch: UINodeHandle,
+    btn_return: UINodeHandle,
+    vote: MatchVote,
+}
+
+/// Returns name of actor that is highlighted as winner and text that describes result.
+fn match_result(leader_board: &LeaderBoard, options: &MatchOptions) -> (Option<String>, String) {
+    let leader = leader_board
+        .highest_personal_score(None)
+        .map(|(name, _)| name.to_owned());
+    match options {
+        MatchOptions::DeathMatch(_) => {
+            let text = match leader.as_ref() {
+                Some(name) => format!("{} wins the match!", name),
+                None => "Draw".to_owned(),
+            };
+            (leader, text)
+        }
+        MatchOptions::TeamDeathMatch(_) | MatchOptions::CaptureTheFlag(_) | MatchOptions::Domination(_) => {
+            let red_score = leader_board.team_score(Team::Red);
+            let blue_score = leader_board.team_score(Team::Blue);
+            let text = if red_score > blue_score {
+                format!("Red team wins!\nRed {} - {} Blue", red_score, blue_score)
+            } else if blue_score > red_score {
+                format!("Blue team wins!\nRed {} - {} Blue", red_score, blue_score)
+            } else {
+                format!("Draw\nRed {} - {} Blue", red_score, blue_score)
+            };
+            (leader, text)
+        }
+        MatchOptions::LastManStanding(_) => {
+            let survivors = leader_board.survivors(options).collect::<Vec<&str>>();
+            if survivors.len() == 1 {
+                (Some(survivors[0].to_owned()), format!("{} is the last one standing!", survivors[0]))
+            } else {
+                (None, "Nobody survived".to_owned())
+            }
+        }
+        MatchOptions::TargetRange(_) => (None, "Practice is over".to_owned()),
+    }
+}
+
+fn format_accuracy(score: &PersonalScore) -> String {
+    match score.accuracy() {
+        Some(accuracy) => format!("{:.0}%", accuracy * 100.0),
+        None => "N/A".to_owned(),
+    }
+}
+
+impl MatchSummary {
+    pub fn new(ui: &mut Gui, sender: Sender<Message>) -> Self {
+        let ctx = &mut ui.build_ctx();
+        let result_text;
+        let table_root;
+        let btn_rematch;
+        let btn_return;
+        let vote;
+        let window = WindowBuilder::new(WidgetBuilder::new()
+            .with_width(1000.0)
+            .with_height(560.0))
+            .with_title(WindowTitle::text("Match Summary"))
+            .can_minimize(false)
+            .can_close(false)
+            .open(false)
+            .with_content(GridBuilder::new(WidgetBuilder::new()
+                .with_margin(Thickness::uniform(5.0))
+                .with_child({
+                    result_text = TextBuilder::new(WidgetBuilder::new()
+                        .on_row(0)
+                        .on_column(0)
+                        .with_margin(Thickness::uniform(5.0))
+                        .with_horizontal_alignment(HorizontalAlignment::Center))
+                        .build(ctx);
+                    result_text
+                })
+                .with_child({
+                    table_root = GridBuilder::new(WidgetBuilder::new()
+                        .on_row(1)
+                        .on_column(0))
+                        .add_row(Row::stretch())
+                        .add_column(Column::stretch())
+                        .build(ctx);
+                    table_root
+                })
+                .with_child(GridBuilder::new(WidgetBuilder::new()
+                    .on_row(2)
+                    .on_column(0)
+                    .with_child({
+                        btn_rematch = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_column(0)
+                            .with_margin(Thickness::uniform(4.0)))
+                            .with_text("Rematch")
+                            .build(ctx);
+                        btn_rematch
+                    })
+                    .with_child({
+                        btn_return = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(4.0)))
+                            .with_text("Return to Menu")
+                            .build(ctx);
+                        btn_return
+                    }))
+                    .add_column(Column::stretch())
+                    .add_column(Column::stretch())
+                    .add_row(Row::stretch())
+                    .build(ctx))
+                .with_child({
+                    vote = MatchVote::new(ctx, sender.clone(), 3);
+                    vote.root
+                }))
+                .add_column(Column::stretch())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(40.0))
+                .add_row(Row::strict(110.0))
+                .build(ctx))
+            .build(ctx);
+
+        Self {
+            sender,
+            window,
+            result_text,
+            table_root,
+            btn_rematch,
+            btn_return,
+            vote,
+        }
+    }
+
+    /// Fills summary with final scores of a match and opens it. Vote for the next match
+    /// between given options is started if vote time is not zero.
+    pub fn open(&mut self, ui: &mut Gui, leader_board: &LeaderBoard, options: &MatchOptions, vote_options: Vec<VoteOption>, vote_time: f32) {
+        let (winner, text) = match_result(leader_board, options);
+
+        // Best players go first.
+        let mut scores = leader_board.values().iter().collect::<Vec<_>>();
+        scores.sort_by(|(a_name, a), (b_name, b)| b.kills
+            .cmp(&a.kills)
+            .then(a.deaths.cmp(&b.deaths))
+            .then(a_name.cmp(b_name)));
+
+        let old_table = ui.node(self.table_root).children().first().cloned();
+
+        let ctx = &mut ui.build_ctx();
+
+        let headers = LEADING_COLUMNS
+            .iter()
+            .cloned()
+            .chain(WEAPON_KINDS.iter().map(|weapon| weapon.name()))
+            .chain(TRAILING_COLUMNS.iter().cloned())
+            .collect::<Vec<_>>();
+
+        let mut children = Vec::new();
+        for (column, header) in headers.iter().enumerate() {
+            children.push(TextBuilder::new(WidgetBuilder::new()
+                .with_margin(Thickness::uniform(3.0))
+                .with_foreground(Brush::Solid(Color::opaque(120, 120, 120)))
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center)
+                .on_row(0)
+                .on_column(column))
+                .with_text(header)
+                .build(ctx));
+        }
+
+        for (i, (name, score)) in scores.iter().enumerate() {
+            let row = i + 1;
+            let color = if winner.as_deref() == Some(name.as_str()) {
+                WINNER_COLOR
+            } else {
+                Color::WHITE
+            };
+            let favorite_weapon = score
+                .favorite_weapon()
+                .map_or("N/A", |weapon| weapon.name());
+            let mut cells = vec![
+                (*name).clone(),
+                format!("{}", score.kills),
+                format!("{}", score.deaths),
+                format_accuracy(score),
+                format!("{:.0}", score.damage_dealt),
+            ];
+            cells.extend(WEAPON_KINDS.iter().map(|&weapon| format!("{}", score.weapon_kills(weapon))));
+            cells.push(favorite_weapon.to_owned());
+            for (column, cell) in cells.iter().enumerate() {
+                children.push(TextBuilder::new(WidgetBuilder::new()
+                    .with_margin(Thickness::uniform(3.0))
+                    .with_foreground(Brush::Solid(color))
+                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                    .with_vertical_alignment(VerticalAlignment::Center)
+                    .on_row(row)
+                    .on_column(column))
+                    .with_text(cell)
+                    .build(ctx));
+            }
+        }
+
+        let table = GridBuilder::new(WidgetBuilder::new()
+            .with_children(&children))
+            .add_row(Row::strict(30.0))
+            .add_rows((0..scores.len()).map(|_| Row::strict(30.0)).collect())
+            .add_row(Row::stretch())
+            .add_columns(headers.iter().map(|_| Column::stretch()).collect())
+            .draw_border(true)
+            .build(ctx);
+
+        if let Some(old_table) = old_table {
+            ui.send_message(WidgetMessage::remove(old_table));
+        }
+        ui.send_message(WidgetMessage::link(table, self.table_root));
+        ui.send_message(TextMessage::text(self.result_text, text));
+        ui.send_message(WindowMessage::open(self.window));
+        ui.send_message(WidgetMessage::center(self.window));
+        // Only local player votes, network session ends together with its match.
+        self.vote.start(ui, vote_options, vote_time, 1);
+    }
+
+    pub fn update(&mut self, ui: &mut Gui, dt: f32) {
+        self.vote.update(ui, dt);
+    }
+
+    /// Closes summary and stops vote, if any.
+    pub fn close(&mut self, ui: &mut Gui) {
+        self.vote.cancel();
+        ui.send_message(WindowMessage::close(self.window));
+    }
+
+    pub fn handle_ui_event(&mut self, ui: &mut Gui, message: &GuiMessage) {
+        if let UiMessageData::Button(ButtonMessage::Click) = &message.data {
+            if message.destination == self.btn_rematch {
+                self.close(ui);
+                self.sender
+                    .send(Message::RestartMatch)
+                    .unwrap();
+            } else if message.destination == self.btn_return {
+                self.close(ui);
+            }
+        }
+        self.vote.handle_ui_event(ui, message);
+    }
+}
@@ -0,0 +1,287 @@
+//! Vote for the next match, it is shown in match summary when a match ends. There is only one
+//! map, so options differ by mode and rules: the same match again, saved presets and other
+//! modes with rules of the last match. Option with most votes starts automatically when
+//! countdown runs out or when every voter has voted, ties are broken randomly.
+
+use crate::{
+    message::Message,
+    match_preset::{MatchPreset, MatchType},
+    level::MAP_NAME,
+    MatchOptions,
+    UINodeHandle,
+    Gui,
+    GuiMessage,
+    BuildContext,
+};
+use rg3d::{
+    gui::{
+        grid::{GridBuilder, Row, Column},
+        widget::WidgetBuilder,
+        text::TextBuilder,
+        button::ButtonBuilder,
+        message::{
+            UiMessageData,
+            ButtonMessage,
+            WidgetMessage,
+            TextMessage,
+        },
+        Thickness,
+        HorizontalAlignment,
+    },
+};
+use rand::Rng;
+use std::sync::mpsc::Sender;
+
+/// Time in seconds that players have to vote, zero disables the vote.
+pub const DEFAULT_VOTE_TIME: f32 = 20.0;
+pub const MAX_VOTE_TIME: f32 = 60.0;
+/// Most options that are offered in one vote.
+const OPTION_COUNT: usize = 3;
+/// Time limit of other modes when last match had none, so the next match can't go forever.
+const FALLBACK_TIME_LIMIT_MINUTES: f32 = 10.0;
+/// Modes that are offered besides the one that was just played.
+const OTHER_MODES: [MatchType; 3] = [MatchType::DeathMatch, MatchType::LastManStanding, MatchType::Domination];
+
+pub struct VoteOption {
+    pub title: String,
+    pub options: MatchOptions,
+}
+
+/// Makes options of a vote after match with given options. Same match again always goes
+/// first, then saved presets and other modes fill the rest in random order.
+pub fn vote_options(last: &MatchOptions, presets: &[MatchPreset]) -> Vec<VoteOption> {
+    let mut result = vec![VoteOption {
+        title: format!("{} - {}\n(same rules)", MAP_NAME, last.display_name()),
+        options: *last,
+    }];
+
+    let last_time_limit = last.time_limit_secs() / 60.0;
+    let base = MatchPreset {
+        time_limit_minutes: if last_time_limit > 0.0 { last_time_limit } else { FALLBACK_TIME_LIMIT_MINUTES },
+        frag_limit: last.frag_limit().unwrap_or(0),
+        bot_difficulty: last.bot_difficulty(),
+        bot_weapons: last.bot_weapons(),
+        bots_fight_each_other: last.bots_fight_each_other(),
+        bot_sight_range: last.bot_sight_range(),
+        damage_multipliers: last.damage_multipliers(),
+        spawn_protection: last.spawn_protection(),
+        health_regen: last.health_regen(),
+        auto_balance: last.auto_balance(),
+        intro_countdown: last.intro_countdown(),
+        ..Default::default()
+    };
+    let mut others = presets.iter()
+        .map(|preset| {
+            let options = preset.options();
+            VoteOption {
+                title: format!("{} - {}\n({})", MAP_NAME, options.display_name(), preset.name),
+                options,
+            }
+        })
+        .chain(OTHER_MODES.iter().map(|&match_type| {
+            let options = MatchPreset { match_type, ..base.clone() }.options();
+            VoteOption {
+                title: format!("{} - {}", MAP_NAME, options.display_name()),
+                options,
+            }
+        }))
+        // Other options are for a change, so same mode and practice are left out.
+        .filter(|option| option.options.name() != last.name() && !option.options.is_practice())
+        .collect::<Vec<_>>();
+
+    let mut rng = rand::thread_rng();
+    while result.len() < OPTION_COUNT && !others.is_empty() {
+        let option = others.remove(rng.gen_range(0, others.len()));
+        // Several presets may describe the same mode, one option per mode is enough.
+        if result.iter().all(|other| other.options.name() != option.options.name()) {
+            result.push(option);
+        }
+    }
+    result
+}
+
+/// Returns index of option that won by given votes, ties are broken randomly.
+pub fn winner(votes: &[u32]) -> Option<usize> {
+    let most = *votes.iter().max()?;
+    let tied = votes.iter()
+        .enumerate()
+        .filter(|(_, count)| **count == most)
+        .map(|(index, _)| index)
+        .collect::<Vec<_>>();
+    Some(tied[rand::thread_rng().gen_range(0, tied.len())])
+}
+
+pub struct MatchVote {
+    sender: Sender<Message>,
+    pub root: UINodeHandle,
+    countdown_text: UINodeHandle,
+    option_buttons: Vec<UINodeHandle>,
+    option_texts: Vec<UINodeHandle>,
+    vote_texts: Vec<UINodeHandle>,
+    options: Vec<VoteOption>,
+    votes: Vec<u32>,
+    /// Amount of players that can vote, vote ends early when all of them have voted.
+    voters: u32,
+    /// Option that local player voted for, player can change the vote until it ends.
+    own_vote: Option<usize>,
+    /// Time left until vote ends, None when there is no vote in progress.
+    time_left: Option<f32>,
+}
+
+impl MatchVote {
+    pub fn new(ctx: &mut BuildContext, sender: Sender<Message>, row: usize) -> Self {
+        let countdown_text;
+        let mut option_buttons = Vec::new();
+        let mut option_texts = Vec::new();
+        let mut vote_texts = Vec::new();
+        let mut children = Vec::new();
+        for column in 0..OPTION_COUNT {
+            let text = TextBuilder::new(WidgetBuilder::new())
+                .with_horizontal_text_alignment(HorizontalAlignment::Center)
+                .build(ctx);
+            let button = ButtonBuilder::new(WidgetBuilder::new()
+                .on_row(0)
+                .on_column(column)
+                .with_margin(Thickness::uniform(4.0)))
+                .with_content(text)
+                .build(ctx);
+            let votes = TextBuilder::new(WidgetBuilder::new()
+                .on_row(1)
+                .on_column(column)
+                .with_horizontal_alignment(HorizontalAlignment::Center))
+                .build(ctx);
+            children.push(button);
+            children.push(votes);
+            option_buttons.push(button);
+            option_texts.push(text);
+            vote_texts.push(votes);
+        }
+        let root = GridBuilder::new(WidgetBuilder::new()
+            .on_row(row)
+            .on_column(0)
+            .with_visibility(false)
+            .with_child({
+                countdown_text = TextBuilder::new(WidgetBuilder::new()
+                    .on_row(0)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(5.0))
+                    .with_horizontal_alignment(HorizontalAlignment::Center))
+                    .build(ctx);
+                countdown_text
+            })
+            .with_child(GridBuilder::new(WidgetBuilder::new()
+                .on_row(1)
+                .on_column(0)
+                .with_children(&children))
+                .add_columns((0..OPTION_COUNT).map(|_| Column::stretch()).collect())
+                .add_row(Row::strict(50.0))
+                .add_row(Row::strict(20.0))
+                .build(ctx)))
+            .add_column(Column::stretch())
+            .add_row(Row::strict(30.0))
+            .add_row(Row::stretch())
+            .build(ctx);
+
+        Self {
+            sender,
+            root,
+            countdown_text,
+            option_buttons,
+            option_texts,
+            vote_texts,
+            options: Vec::new(),
+            votes: Vec::new(),
+            voters: 1,
+            own_vote: None,
+            time_left: None,
+        }
+    }
+
+    /// Starts vote between given options that lasts given time, vote is hidden if time is
+    /// zero or there is nothing to vote for.
+    pub fn start(&mut self, ui: &mut Gui, options: Vec<VoteOption>, time: f32, voters: u32) {
+        let visible = time > 0.0 && !options.is_empty();
+        ui.send_message(WidgetMessage::visibility(self.root, visible));
+        if !visible {
+            self.cancel();
+            return;
+        }
+        for (i, (&button, &text)) in self.option_buttons.iter().zip(self.option_texts.iter()).enumerate() {
+            ui.send_message(WidgetMessage::visibility(button, i < options.len()));
+            ui.send_message(WidgetMessage::visibility(self.vote_texts[i], i < options.len()));
+            if let Some(option) = options.get(i) {
+                ui.send_message(TextMessage::text(text, option.title.clone()));
+            }
+        }
+        self.votes = vec![0; options.len()];
+        self.options = options;
+        self.voters = voters.max(1);
+        self.own_vote = None;
+        self.time_left = Some(time);
+        self.sync_texts(ui);
+    }
+
+    /// Stops vote without starting anything.
+    pub fn cancel(&mut self) {
+        self.time_left = None;
+        self.options.clear();
+        self.votes.clear();
+    }
+
+    fn sync_texts(&self, ui: &mut Gui) {
+        if let Some(time_left) = self.time_left {
+            ui.send_message(TextMessage::text(self.countdown_text,
+                                              format!("Vote for next match - {} s", time_left.ceil() as u32)));
+        }
+        for (i, &count) in self.votes.iter().enumerate() {
+            let mark = if self.own_vote == Some(i) { " (yours)" } else { "" };
+            ui.send_message(TextMessage::text(self.vote_texts[i], format!("Votes: {}{}", count, mark)));
+        }
+    }
+
+    /// Counts down the vote and starts winning option when time is out.
+    pub fn update(&mut self, ui: &mut Gui, dt: f32) {
+        if let Some(time_left) = self.time_left {
+            let new_time_left = time_left - dt;
+            if new_time_left <= 0.0 {
+                self.finish();
+            } else {
+                self.time_left = Some(new_time_left);
+                // Text changes only once per second, there is no need to send it every frame.
+                if new_time_left.ceil() != time_left.ceil() {
+                    self.sync_texts(ui);
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(index) = winner(&self.votes) {
+            self.sender
+                .send(Message::StartVotedMatch { options: self.options[index].options })
+                .unwrap();
+        }
+        self.cancel();
+    }
+
+    pub fn handle_ui_event(&mut self, ui: &mut Gui, message: &GuiMessage) {
+        if self.time_left.is_none() {
+            return;
+        }
+        if let UiMessageData::Button(ButtonMessage::Click) = &message.data {
+            if let Some(index) = self.option_buttons.iter().position(|&button| button == message.destination) {
+                if let Some(previous) = self.own_vote.replace(index) {
+                    self.votes[previous] -= 1;
+                }
+                self.votes[index] += 1;
+                if self.votes.iter().sum::<u32>() >= self.voters {
+                    // Everyone has voted, so there is no reason to wait. Alone player simply
+                    // picks next match this way.
+                    self.finish();
+                } else {
+                    self.sync_texts(ui);
+                }
+            }
+        }
+    }
+}
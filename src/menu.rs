@@ -1,22 +1,28 @@
 use std::{
-    path::Path,
     rc::Rc,
-    sync::{
-        Mutex,
-        Arc,
-        mpsc::Sender,
-    },
+    sync::{mpsc::Sender, Arc, Mutex},
     cell::RefCell,
 };
 use crate::{
     message::Message,
+    gui,
     match_menu::MatchMenu,
+    match_summary::MatchSummary,
+    match_vote,
+    match_preset,
+    stats_menu::StatsMenu,
+    multiplayer_menu::MultiplayerMenu,
+    player_stats::PlayerStats,
     options_menu::OptionsMenu,
     UINodeHandle,
     GameEngine,
     Gui,
     GuiMessage,
+    BuildContext,
     control_scheme::ControlScheme,
+    settings::Settings,
+    leader_board::LeaderBoard,
+    MatchOptions,
 };
 use rg3d::{
     event::{
@@ -36,144 +42,286 @@ use rg3d::{
             WindowTitle,
         },
         button::ButtonBuilder,
+        text::TextBuilder,
         message::{
             UiMessageData,
             WindowMessage,
             ButtonMessage,
             WidgetMessage,
+            TextMessage,
         },
         widget::WidgetBuilder,
     },
 };
 
+/// Menu has two layouts: main menu is shown when there is no match, pause menu is shown
+/// when player leaves match with Escape.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum MenuLayout {
+    Main,
+    Pause,
+}
+
 pub struct Menu {
     sender: Sender<Message>,
     root: UINodeHandle,
+    main_window: UINodeHandle,
+    pause_window: UINodeHandle,
     btn_new_game: UINodeHandle,
-    btn_save_game: UINodeHandle,
-    btn_settings: UINodeHandle,
+    btn_multiplayer: UINodeHandle,
     btn_load_game: UINodeHandle,
+    btn_settings: UINodeHandle,
+    btn_stats: UINodeHandle,
     btn_quit_game: UINodeHandle,
+    btn_resume: UINodeHandle,
+    btn_save_game: UINodeHandle,
+    btn_pause_settings: UINodeHandle,
+    btn_restart_match: UINodeHandle,
+    btn_main_menu: UINodeHandle,
+    btn_pause_quit_game: UINodeHandle,
     options_menu: OptionsMenu,
     match_menu: MatchMenu,
+    match_summary: MatchSummary,
+    stats_menu: StatsMenu,
+    multiplayer_menu: MultiplayerMenu,
+    confirmation_window: UINodeHandle,
+    confirmation_text: UINodeHandle,
+    btn_confirm_yes: UINodeHandle,
+    btn_confirm_no: UINodeHandle,
+    /// Action that will be performed when user confirms it in confirmation window.
+    pending_action: Option<Message>,
+}
+
+/// Creates window with a column of buttons with given texts, returns window and buttons
+/// in the same order as texts.
+fn build_button_window(ctx: &mut BuildContext, font: &Arc<Mutex<Font>>, title: &str, texts: &[&str]) -> (UINodeHandle, Vec<UINodeHandle>) {
+    let buttons = texts.iter()
+        .enumerate()
+        .map(|(row, text)| {
+            ButtonBuilder::new(WidgetBuilder::new()
+                .on_column(0)
+                .on_row(row)
+                .with_margin(Thickness::uniform(4.0)))
+                .with_text(text)
+                .with_font(font.clone())
+                .build(ctx)
+        })
+        .collect::<Vec<_>>();
+
+    let mut grid = GridBuilder::new(WidgetBuilder::new()
+        .with_margin(Thickness::uniform(20.0))
+        .with_children(&buttons))
+        .add_column(Column::stretch());
+    for _ in texts {
+        grid = grid.add_row(Row::strict(75.0));
+    }
+
+    let window = WindowBuilder::new(WidgetBuilder::new()
+        .on_row(1)
+        .on_column(1))
+        .can_resize(false)
+        .can_minimize(false)
+        .can_close(false)
+        .with_title(WindowTitle::text(title))
+        .with_content(grid.build(ctx))
+        .build(ctx);
+
+    (window, buttons)
 }
 
 impl Menu {
-    pub fn new(engine: &mut GameEngine, control_scheme: Rc<RefCell<ControlScheme>>, sender: Sender<Message>) -> Self {
+    pub fn new(engine: &mut GameEngine, control_scheme: Rc<RefCell<ControlScheme>>, sender: Sender<Message>, settings: &Settings) -> Self {
         let frame_size = engine.renderer.get_frame_size();
 
-        let font: Font = Font::from_file(
-            Path::new("data/ui/SquaresBold.ttf"),
-            30.0,
-            Font::default_char_set()).unwrap();
-        let font = Arc::new(Mutex::new(font));
+        // Menu isn't rebuilt, so new text scale is used here after restart only.
+        let font = gui::load_font("data/ui/SquaresBold.ttf", 30.0 * settings.accessibility.text_scale);
 
         let ctx = &mut engine.user_interface.build_ctx();
 
-        let btn_new_game;
-        let btn_settings;
-        let btn_save_game;
-        let btn_load_game;
-        let btn_quit_game;
+        let (main_window, main_buttons) = build_button_window(ctx, &font, "Rusty Shooter", &[
+            "New Game",
+            "Multiplayer",
+            "Load Game",
+            "Settings",
+            "Stats",
+            "Quit",
+        ]);
+        let (pause_window, pause_buttons) = build_button_window(ctx, &font, "Paused", &[
+            "Resume",
+            "Save Game",
+            "Settings",
+            "Restart Match",
+            "Return to Main Menu",
+            "Quit",
+        ]);
+
         let root: UINodeHandle = GridBuilder::new(WidgetBuilder::new()
             .with_width(frame_size.0 as f32)
             .with_height(frame_size.1 as f32)
-            .with_child(WindowBuilder::new(WidgetBuilder::new()
-                .on_row(1)
-                .on_column(1))
-                .can_resize(false)
-                .can_minimize(false)
-                .can_close(false)
-                .with_title(WindowTitle::text("Rusty Shooter"))
-                .with_content(GridBuilder::new(WidgetBuilder::new()
-                    .with_margin(Thickness::uniform(20.0))
-                    .with_child({
-                        btn_new_game = ButtonBuilder::new(WidgetBuilder::new()
-                            .on_column(0)
-                            .on_row(0)
-                            .with_margin(Thickness::uniform(4.0)))
-                            .with_text("New Game")
-                            .with_font(font.clone())
-                            .build(ctx);
-                        btn_new_game
-                    })
-                    .with_child({
-                        btn_save_game = ButtonBuilder::new(WidgetBuilder::new()
-                            .on_column(0)
-                            .on_row(1)
-                            .with_margin(Thickness::uniform(4.0)))
-                            .with_text("Save Game")
-                            .with_font(font.clone())
-                            .build(ctx);
-                        btn_save_game
-                    })
-                    .with_child({
-                        btn_load_game = ButtonBuilder::new(WidgetBuilder::new()
-                            .on_column(0)
-                            .on_row(2)
-                            .with_margin(Thickness::uniform(4.0)))
-                            .with_text("Load Game")
-                            .with_font(font.clone())
-                            .build(ctx);
-                        btn_load_game
-                    })
+            .with_child(main_window)
+            .with_child(pause_window))
+            .add_row(Row::stretch())
+            .add_row(Row::strict(650.0))
+            .add_row(Row::stretch())
+            .add_column(Column::stretch())
+            .add_column(Column::strict(400.0))
+            .add_column(Column::stretch())
+            .build(ctx);
+
+        let confirmation_text;
+        let btn_confirm_yes;
+        let btn_confirm_no;
+        let confirmation_window = WindowBuilder::new(WidgetBuilder::new()
+            .with_width(400.0)
+            .with_height(150.0))
+            .can_resize(false)
+            .can_minimize(false)
+            .can_close(false)
+            .open(false)
+            .with_title(WindowTitle::text("Are you sure?"))
+            .with_content(GridBuilder::new(WidgetBuilder::new()
+                .with_margin(Thickness::uniform(5.0))
+                .with_child({
+                    confirmation_text = TextBuilder::new(WidgetBuilder::new()
+                        .on_row(0)
+                        .on_column(0))
+                        .with_wrap(true)
+                        .build(ctx);
+                    confirmation_text
+                })
+                .with_child(GridBuilder::new(WidgetBuilder::new()
+                    .on_row(1)
+                    .on_column(0)
                     .with_child({
-                        btn_settings = ButtonBuilder::new(WidgetBuilder::new()
+                        btn_confirm_yes = ButtonBuilder::new(WidgetBuilder::new()
                             .on_column(0)
-                            .on_row(3)
                             .with_margin(Thickness::uniform(4.0)))
-                            .with_text("Settings")
-                            .with_font(font.clone())
+                            .with_text("Yes")
                             .build(ctx);
-                        btn_settings
+                        btn_confirm_yes
                     })
                     .with_child({
-                        btn_quit_game = ButtonBuilder::new(WidgetBuilder::new()
-                            .on_column(0)
-                            .on_row(4)
+                        btn_confirm_no = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_column(1)
                             .with_margin(Thickness::uniform(4.0)))
-                            .with_text("Quit")
-                            .with_font(font)
+                            .with_text("No")
                             .build(ctx);
-                        btn_quit_game
+                        btn_confirm_no
                     }))
                     .add_column(Column::stretch())
-                    .add_row(Row::strict(75.0))
-                    .add_row(Row::strict(75.0))
-                    .add_row(Row::strict(75.0))
-                    .add_row(Row::strict(75.0))
-                    .add_row(Row::strict(75.0))
-                    .build(ctx))
-                .build(ctx)))
-            .add_row(Row::stretch())
-            .add_row(Row::strict(500.0))
-            .add_row(Row::stretch())
-            .add_column(Column::stretch())
-            .add_column(Column::strict(400.0))
-            .add_column(Column::stretch())
+                    .add_column(Column::stretch())
+                    .add_row(Row::stretch())
+                    .build(ctx)))
+                .add_column(Column::stretch())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(40.0))
+                .build(ctx))
             .build(ctx);
 
-        Self {
+        let mut menu = Self {
             sender: sender.clone(),
             root,
-            btn_new_game,
-            btn_settings,
-            btn_save_game,
-            btn_load_game,
-            btn_quit_game,
-            options_menu: OptionsMenu::new(engine, control_scheme, sender.clone()),
-            match_menu: MatchMenu::new(&mut engine.user_interface, &mut engine.resource_manager.lock().unwrap(), sender),
+            main_window,
+            pause_window,
+            btn_new_game: main_buttons[0],
+            btn_multiplayer: main_buttons[1],
+            btn_load_game: main_buttons[2],
+            btn_settings: main_buttons[3],
+            btn_stats: main_buttons[4],
+            btn_quit_game: main_buttons[5],
+            btn_resume: pause_buttons[0],
+            btn_save_game: pause_buttons[1],
+            btn_pause_settings: pause_buttons[2],
+            btn_restart_match: pause_buttons[3],
+            btn_main_menu: pause_buttons[4],
+            btn_pause_quit_game: pause_buttons[5],
+            options_menu: OptionsMenu::new(engine, control_scheme, sender.clone(), settings),
+            match_menu: MatchMenu::new(&mut engine.user_interface, &mut engine.resource_manager.lock().unwrap(), settings),
+            match_summary: MatchSummary::new(&mut engine.user_interface, sender.clone()),
+            stats_menu: StatsMenu::new(&mut engine.user_interface),
+            multiplayer_menu: MultiplayerMenu::new(&mut engine.user_interface, &mut engine.resource_manager.lock().unwrap()),
+            confirmation_window,
+            confirmation_text,
+            btn_confirm_yes,
+            btn_confirm_no,
+            pending_action: None,
+        };
+        menu.set_layout(&mut engine.user_interface, MenuLayout::Main);
+        menu
+    }
+
+    /// Shows window of given layout and hides the other one.
+    pub fn set_layout(&mut self, ui: &mut Gui, layout: MenuLayout) {
+        ui.send_message(WidgetMessage::visibility(self.main_window, layout == MenuLayout::Main));
+        ui.send_message(WidgetMessage::visibility(self.pause_window, layout == MenuLayout::Pause));
+    }
+
+    /// Sends given message right away if there is no active match, otherwise asks user to
+    /// confirm action first because it will destroy current match.
+    fn send_or_confirm(&mut self, ui: &mut Gui, message: Message, match_active: bool, question: &str) {
+        if match_active {
+            ui.send_message(TextMessage::text(self.confirmation_text, question.to_owned()));
+            ui.send_message(WindowMessage::open_modal(self.confirmation_window));
+            ui.send_message(WidgetMessage::center(self.confirmation_window));
+            self.pending_action = Some(message);
+        } else {
+            self.sender
+                .send(message)
+                .unwrap();
         }
     }
 
+    fn close_confirmation(&mut self, ui: &mut Gui) {
+        ui.send_message(WindowMessage::close(self.confirmation_window));
+        self.pending_action = None;
+    }
+
     pub fn set_visible(&mut self, ui: &mut Gui, visible: bool) {
         ui.send_message(WidgetMessage::visibility(self.root, visible));
         if !visible {
             ui.send_message(WindowMessage::close(self.options_menu.window));
             ui.send_message(WindowMessage::close(self.match_menu.window));
+            self.match_summary.close(ui);
+            ui.send_message(WindowMessage::close(self.stats_menu.window));
+            ui.send_message(WindowMessage::close(self.multiplayer_menu.window));
+            self.close_confirmation(ui);
         }
     }
 
+    /// Opens summary of a match that just ended, menu must be visible to see it. Summary
+    /// has vote for the next match that lasts given time, zero disables the vote.
+    pub fn show_match_summary(&mut self, ui: &mut Gui, leader_board: &LeaderBoard, options: &MatchOptions, vote_time: f32) {
+        let vote_options = match_vote::vote_options(options, &match_preset::load_presets());
+        self.match_summary.open(ui, leader_board, options, vote_options, vote_time);
+    }
+
+    /// Opens stats page filled with given lifetime stats of the player.
+    pub fn show_stats(&mut self, ui: &mut Gui, stats: &PlayerStats) {
+        self.stats_menu.open(ui, stats);
+    }
+
+    /// Opens multiplayer page with given status, menu must be visible to see it.
+    pub fn show_network_status(&mut self, ui: &mut Gui, text: &str) {
+        self.multiplayer_menu.set_status(ui, text);
+        ui.send_message(WindowMessage::open(self.multiplayer_menu.window));
+        ui.send_message(WidgetMessage::center(self.multiplayer_menu.window));
+    }
+
+    pub fn update(&mut self, ui: &mut Gui, dt: f32) {
+        self.multiplayer_menu.update(ui);
+        self.match_summary.update(ui, dt);
+        self.options_menu.update(dt);
+    }
+
+    /// Syncs options with given settings, see [`OptionsMenu::sync_to_settings`].
+    pub fn sync_to_settings(&mut self, engine: &mut GameEngine, settings: &Settings) {
+        self.options_menu.sync_to_settings(engine, settings);
+    }
+
+    pub fn set_settings_file_status(&mut self, ui: &mut Gui, text: &str) {
+        self.options_menu.set_settings_file_status(ui, text);
+    }
+
     pub fn is_visible(&self, ui: &Gui) -> bool {
         ui.node(self.root).visibility()
     }
@@ -189,12 +337,14 @@ impl Menu {
         self.options_menu.process_input_event(engine, event);
     }
 
-    pub fn handle_ui_event(&mut self, engine: &mut GameEngine, message: &GuiMessage) {
+    pub fn handle_ui_event(&mut self, engine: &mut GameEngine, message: &GuiMessage, match_active: bool) {
         if let UiMessageData::Button(msg) = &message.data {
             if let ButtonMessage::Click = msg {
                 if message.destination == self.btn_new_game {
                     engine.user_interface.send_message(WindowMessage::open(self.match_menu.window));
                     engine.user_interface.send_message(WidgetMessage::center(self.match_menu.window));
+                } else if message.destination == self.btn_multiplayer {
+                    self.multiplayer_menu.open(&mut engine.user_interface);
                 } else if message.destination == self.btn_save_game {
                     self.sender
                         .send(Message::SaveGame)
@@ -203,11 +353,33 @@ impl Menu {
                     self.sender
                         .send(Message::LoadGame)
                         .unwrap();
-                } else if message.destination == self.btn_quit_game {
+                } else if message.destination == self.btn_stats {
                     self.sender
-                        .send(Message::QuitGame)
+                        .send(Message::ShowStats)
                         .unwrap();
-                } else if message.destination == self.btn_settings {
+                } else if message.destination == self.btn_restart_match {
+                    self.send_or_confirm(&mut engine.user_interface, Message::RestartMatch, match_active,
+                                         "Current match will be lost. Do you really want to restart it?");
+                } else if message.destination == self.btn_main_menu {
+                    self.send_or_confirm(&mut engine.user_interface, Message::ReturnToMainMenu, match_active,
+                                         "Current match will be lost. Do you really want to leave it?");
+                } else if message.destination == self.btn_resume {
+                    self.sender
+                        .send(Message::ResumeGame)
+                        .unwrap();
+                } else if message.destination == self.btn_quit_game || message.destination == self.btn_pause_quit_game {
+                    self.send_or_confirm(&mut engine.user_interface, Message::QuitGame, match_active,
+                                         "Current match will be lost. Do you really want to quit?");
+                } else if message.destination == self.btn_confirm_yes {
+                    if let Some(action) = self.pending_action.take() {
+                        self.sender
+                            .send(action)
+                            .unwrap();
+                    }
+                    self.close_confirmation(&mut engine.user_interface);
+                } else if message.destination == self.btn_confirm_no {
+                    self.close_confirmation(&mut engine.user_interface);
+                } else if message.destination == self.btn_settings || message.destination == self.btn_pause_settings {
                     engine.user_interface.send_message(WindowMessage::open(self.options_menu.window));
                     engine.user_interface.send_message(WidgetMessage::center(self.options_menu.window));
                 }
@@ -215,6 +387,15 @@ impl Menu {
         }
 
         self.options_menu.handle_ui_event(engine, message);
-        self.match_menu.handle_ui_event(engine, message);
+        self.match_summary.handle_ui_event(&mut engine.user_interface, message);
+        self.stats_menu.handle_ui_event(&mut engine.user_interface, message);
+        if let Some(start_message) = self.match_menu.handle_ui_event(engine, message) {
+            self.send_or_confirm(&mut engine.user_interface, start_message, match_active,
+                                 "Current match will be lost. Do you really want to start a new one?");
+        }
+        if let Some(network_message) = self.multiplayer_menu.handle_ui_event(&mut engine.user_interface, message) {
+            self.send_or_confirm(&mut engine.user_interface, network_message, match_active,
+                                 "Current match will be lost. Do you really want to start a new one?");
+        }
     }
 }
\ No newline at end of file
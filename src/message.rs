@@ -20,14 +20,30 @@ use crate::{
         Item,
     },
     projectile::ProjectileKind,
-    effects::EffectKind,
+    effects::{EffectKind, EffectLimits},
+    loadout::Loadout,
+    level::{ReverbPreset, CorpseLimits},
+    crosshair::CrosshairConfig,
+    leader_board::KillCallout,
+    comms::TeamCallout,
+    hud_layout::HudLayout,
+    hud_theme::HudTheme,
+    damage::{DamageKind, HitRegion, RocketJump},
+    palette::ColorBlindMode,
+    settings::{ContentFilter, DamageFeedback, Accessibility},
+    net::ClientId,
+    character::Team,
     MatchOptions,
+    SoundPriority,
 };
 use std::path::PathBuf;
-use rg3d::core::{
-    pool::Handle,
-    math::vec3::Vec3,
-    math::mat3::Mat3
+use rg3d::{
+    physics::rigid_body::RigidBody,
+    core::{
+        pool::Handle,
+        math::vec3::Vec3,
+        math::mat3::Mat3,
+    },
 };
 
 #[derive(Debug)]
@@ -82,12 +98,57 @@ pub enum Message {
         initial_velocity: Vec3,
         direction: Option<Vec3>
     },
+    /// Uses secondary fire of weapon, see `weapon::AltFire`.
+    AltFireWeapon {
+        weapon: Handle<Weapon>,
+        initial_velocity: Vec3,
+    },
+    /// Shakes camera of player if it is within `radius` from `position`, shake is weaker
+    /// at larger distances.
+    ShakeCamera {
+        position: Vec3,
+        intensity: f32,
+        radius: f32,
+    },
+    /// Smoke grenade or flashbang detonated at given position. Owner is actor who threw
+    /// it, Handle::NONE if grenade has no owner.
+    DetonateTacticalGrenade {
+        kind: ProjectileKind,
+        position: Vec3,
+        owner: Handle<Actor>,
+    },
+    /// Player was blinded by flashbang, strength is in [0; 1] range and depends on distance
+    /// to flashbang and on how directly player looked at it. Screen goes white and sound is
+    /// muffled for a while.
+    FlashPlayer {
+        strength: f32,
+    },
+    /// Plays inspect animation of a weapon, it is interrupted by shooting or switching weapon.
+    InspectWeapon {
+        weapon: Handle<Weapon>
+    },
+    /// Starts reload of a weapon, it is interrupted by switching weapon.
+    ReloadWeapon {
+        weapon: Handle<Weapon>
+    },
+    /// Lets empty weapon click again on next trigger pull.
+    ReleaseTrigger {
+        weapon: Handle<Weapon>
+    },
+    /// Actor made a footstep, enemy bots that are close enough hear it. Loudness depends
+    /// on how actor moves, see `FOOTSTEP_LOUDNESS`.
+    Footstep {
+        actor: Handle<Actor>,
+        position: Vec3,
+        loudness: f32,
+    },
     PlaySound {
         path: PathBuf,
         position: Vec3,
         gain: f32,
         rolloff_factor: f32,
-        radius: f32
+        radius: f32,
+        priority: SoundPriority,
     },
     ShowWeapon {
         weapon: Handle<Weapon>,
@@ -99,12 +160,74 @@ pub enum Message {
         /// or not from any actor.
         who: Handle<Actor>,
         amount: f32,
+        /// Part of body hit by projectile, none for explosions, melee attacks and environment.
+        region: Option<HitRegion>,
+        kind: DamageKind,
+        /// Point of hit of projectile or center of explosion, none for melee attacks and
+        /// environment.
+        position: Option<Vec3>,
+    },
+    /// Projectile hit a target of target range, `body` is the body of the target.
+    HitRangeTarget {
+        body: Handle<RigidBody>,
+        who: Handle<Actor>,
     },
+    /// Raises every target of target range again.
+    ResetTargetRange,
     CreateEffect {
         kind: EffectKind,
         position: Vec3,
     },
     SpawnPlayer,
+    /// Respawns player of a network client after it died.
+    SpawnRemotePlayer {
+        client: ClientId
+    },
+    /// Sent when actor was added to level, either at match start or on respawn.
+    ActorSpawned {
+        actor: Handle<Actor>
+    },
+    /// Sent after damage was applied to alive actor. It does not change anything by itself,
+    /// systems that show, count or log damage react on it.
+    ActorDamaged {
+        victim: Handle<Actor>,
+        /// Handle::NONE if damage came from environment.
+        attacker: Handle<Actor>,
+        amount: f32,
+        kind: DamageKind,
+    },
+    /// Sent when actor died from damage. Weapon is the one that killer was holding at the
+    /// moment of kill.
+    ActorKilled {
+        victim: Handle<Actor>,
+        /// Handle::NONE if actor was killed by environment.
+        killer: Handle<Actor>,
+        weapon: Option<WeaponKind>,
+        /// Whether killing blow was a headshot.
+        headshot: bool,
+    },
+    /// Actor made few kills in a row without dying. Sent only for streaks that have a name.
+    KillStreak {
+        actor: Handle<Actor>,
+        name: String,
+        streak: u32,
+    },
+    /// Kill deserved callouts like first blood or revenge, they're announced on HUD with
+    /// distinct sounds.
+    KillCallouts {
+        actor: Handle<Actor>,
+        name: String,
+        callouts: Vec<KillCallout>,
+    },
+    /// Actor said a callout to its team, see `comms` module.
+    TeamCallout {
+        actor: Handle<Actor>,
+        callout: TeamCallout,
+    },
+    /// Note that player typed during match, it is shown in note feed and written to match log.
+    AddNote {
+        text: String
+    },
     /// HUD listens such events and puts them into queue.
     AddNotification {
         text: String
@@ -117,12 +240,162 @@ pub enum Message {
     SaveGame,
     /// Loads game state from a file. TODO: Add filename field.
     LoadGame,
+    /// Writes settings and key bindings into a text file.
+    ExportSettings {
+        path: PathBuf
+    },
+    /// Reads settings and key bindings from a text file made by export and applies them.
+    ImportSettings {
+        path: PathBuf
+    },
+    SetItemTimersEnabled {
+        enabled: bool
+    },
+    /// Turns on or off marker of place where player died last time.
+    SetDeathMarkerEnabled {
+        enabled: bool
+    },
+    /// Time in seconds of vote for the next match, zero disables the vote.
+    SetMapVoteTime {
+        time: f32
+    },
+    /// Intensity of glow around items, zero disables it.
+    SetItemGlow {
+        intensity: f32
+    },
+    /// Whether names of bots are shown when they're behind level geometry.
+    SetNameplatesThroughWalls {
+        enabled: bool
+    },
+    SetContentFilter {
+        filter: ContentFilter
+    },
+    /// Changes limits of tracked visual effects, effects over limit are removed at once.
+    SetEffectLimits {
+        limits: EffectLimits
+    },
+    /// Changes limits of corpses of bots, corpses over limits are removed on next update.
+    SetCorpseLimits {
+        limits: CorpseLimits
+    },
+    /// Changes damage and knockback that actors get from their own explosions.
+    SetRocketJump {
+        rocket_jump: RocketJump
+    },
+    /// Whether death of player is replayed from viewpoint of killer before respawn.
+    SetKillcam {
+        enabled: bool
+    },
+    /// Whether dead actors drop their current weapon.
+    SetWeaponDrop {
+        enabled: bool
+    },
+    /// Maximum amount of bots that rebuild their paths on one frame.
+    SetPathBudget {
+        budget: u32
+    },
+    SetDamageFeedback {
+        feedback: DamageFeedback
+    },
+    /// Changes palette of colors that are used to tell actors and objectives apart.
+    SetColorBlindMode {
+        mode: ColorBlindMode
+    },
     StartNewGame {
+        options: MatchOptions,
+        loadout: Loadout,
+        /// Whether match events should be written into a log file.
+        match_log: bool,
+        /// Whether match is watched by spectator camera instead of being played.
+        observer: bool,
+    },
+    /// Starts new match with options of last started match.
+    RestartMatch,
+    /// Starts match that won vote for the next match.
+    StartVotedMatch {
         options: MatchOptions
     },
     QuitGame,
+    /// Destroys current level (if any) and shows main menu, game keeps running.
+    ReturnToMainMenu,
+    /// Opens stats page with lifetime stats of the player.
+    ShowStats,
+    /// Starts new match with given options and hosts it on given port, `max_players`
+    /// includes host. Bots are added and removed to keep `bot_fill` combatants if it is set.
+    HostMatch {
+        options: MatchOptions,
+        port: u16,
+        max_players: u32,
+        bot_fill: Option<u32>,
+    },
+    /// Starts replica of a match hosted at given address, port may be omitted.
+    ConnectToServer {
+        address: String
+    },
+    /// Hides pause menu and continues current match.
+    ResumeGame,
     SetMusicVolume {
         volume: f32
     },
+    /// Rebuilds HUD with given scale of fonts and elements.
+    SetHudScale {
+        scale: f32
+    },
+    /// Adds looping positional sound owned by level, it is removed with other sounds of level.
+    AddAmbientSound {
+        path: PathBuf,
+        position: Vec3,
+    },
+    /// Changes reverberation and gain of gameplay sounds.
+    SetReverbPreset {
+        preset: ReverbPreset
+    },
+    /// Whether match should be paused while game window is not focused. Sound is paused
+    /// in any case.
+    SetPauseWhenUnfocused {
+        enabled: bool
+    },
+    /// Whether frame limiter waits out the end of frame precisely instead of sleeping.
+    SetPreciseFramePacing {
+        enabled: bool
+    },
+    /// Whether every tick is rendered right away at its exact time, see `Settings::low_latency`.
+    SetLowLatency {
+        enabled: bool
+    },
+    /// Sets maximum amount of gameplay sounds playing at the same time.
+    SetMaxSounds {
+        count: u32
+    },
+    /// Rebuilds HUD with given visibility and positions of elements.
+    SetHudLayout {
+        layout: HudLayout
+    },
+    /// Rebuilds HUD with colors of given theme.
+    SetHudTheme {
+        theme: HudTheme
+    },
+    /// Rebuilds HUD with given text scale and contrast.
+    SetAccessibility {
+        accessibility: Accessibility
+    },
+    /// Replaces crosshair with the one made by given config.
+    SetCrosshair {
+        config: CrosshairConfig
+    },
+    /// Turns on or off numbers that float above bots damaged by player.
+    SetDamageNumbersEnabled {
+        enabled: bool
+    },
+    /// Whole seconds left until match starts, it is sent every time the number changes.
+    /// Zero means that match has just started.
+    MatchCountdown {
+        seconds_left: u32
+    },
+    /// Team captured control point with given label in domination.
+    ControlPointCaptured {
+        label: &'static str,
+        team: Team,
+    },
     EndMatch
 }
\ No newline at end of file
@@ -0,0 +1,445 @@
+//! Multiplayer page of the main menu. It allows to host a match, to join a match by address
+//! and lists servers that were found in local network.
+
+use crate::{
+    message::Message,
+    net::{LanBrowser, LanServer, DEFAULT_PORT, MIN_PLAYERS, MAX_PLAYERS, DEFAULT_MAX_PLAYERS},
+    level::{MAP_NAME, BOT_FILL_RANGE, DEFAULT_BOT_FILL},
+    gui::{create_scroll_bar, create_check_box, ScrollBarData},
+    MatchOptions,
+    UINodeHandle,
+    Gui,
+    GuiMessage,
+};
+use rg3d::{
+    gui::{
+        grid::{GridBuilder, Row, Column},
+        window::{WindowBuilder, WindowTitle},
+        widget::WidgetBuilder,
+        text::TextBuilder,
+        button::ButtonBuilder,
+        text_box::TextBoxBuilder,
+        dropdown_list::DropdownListBuilder,
+        decorator::DecoratorBuilder,
+        border::BorderBuilder,
+        message::{
+            UiMessageData,
+            ButtonMessage,
+            WindowMessage,
+            WidgetMessage,
+            TextMessage,
+        },
+        node::UINode,
+        Thickness,
+        Orientation,
+        HorizontalAlignment,
+        VerticalAlignment,
+    },
+    engine::resource_manager::ResourceManager,
+};
+
+/// Teams are not implemented yet, so only modes without teams can be hosted.
+const HOSTED_MODES: [&str; 2] = ["Deathmatch", "Last Man Standing"];
+
+fn hosted_mode(index: usize) -> MatchOptions {
+    match index {
+        1 => MatchOptions::LastManStanding(Default::default()),
+        _ => MatchOptions::DeathMatch(Default::default()),
+    }
+}
+
+pub struct MultiplayerMenu {
+    pub window: UINodeHandle,
+    dl_mode: UINodeHandle,
+    sb_max_players: UINodeHandle,
+    cb_bot_fill: UINodeHandle,
+    sb_bot_fill: UINodeHandle,
+    tb_port: UINodeHandle,
+    btn_host: UINodeHandle,
+    tb_address: UINodeHandle,
+    btn_connect: UINodeHandle,
+    btn_refresh: UINodeHandle,
+    server_list: UINodeHandle,
+    status_text: UINodeHandle,
+    btn_close: UINodeHandle,
+    /// Created on first search, None if socket for search can't be opened.
+    browser: Option<LanBrowser>,
+    servers: Vec<LanServer>,
+    /// Button of every listed server, in the same order as servers.
+    server_buttons: Vec<UINodeHandle>,
+}
+
+/// Widget of a caption in the first column.
+fn label(row: usize) -> WidgetBuilder {
+    WidgetBuilder::new()
+        .on_row(row)
+        .on_column(0)
+        .with_margin(Thickness::uniform(2.0))
+        .with_vertical_alignment(VerticalAlignment::Center)
+}
+
+impl MultiplayerMenu {
+    pub fn new(ui: &mut Gui, resource_manager: &mut ResourceManager) -> Self {
+        let common_row = Row::strict(36.0);
+        let ctx = &mut ui.build_ctx();
+
+        let dl_mode;
+        let sb_max_players;
+        let cb_bot_fill;
+        let sb_bot_fill;
+        let tb_port;
+        let btn_host;
+        let tb_address;
+        let btn_connect;
+        let btn_refresh;
+        let server_list;
+        let status_text;
+        let btn_close;
+        let window = WindowBuilder::new(WidgetBuilder::new()
+            .with_width(600.0)
+            .with_height(670.0))
+            .with_title(WindowTitle::text("Multiplayer"))
+            .can_minimize(false)
+            .open(false)
+            .with_content(GridBuilder::new(WidgetBuilder::new()
+                .with_margin(Thickness::uniform(5.0))
+                .with_child(GridBuilder::new(WidgetBuilder::new()
+                    .on_row(0)
+                    .on_column(0)
+                    .with_child(TextBuilder::new(label(0))
+                        .with_text("Mode")
+                        .build(ctx))
+                    .with_child({
+                        dl_mode = DropdownListBuilder::new(WidgetBuilder::new()
+                            .on_row(0)
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_items(HOSTED_MODES
+                                .iter()
+                                .map(|mode| DecoratorBuilder::new(BorderBuilder::new(WidgetBuilder::new()
+                                    .with_height(30.0)
+                                    .with_child(TextBuilder::new(WidgetBuilder::new()
+                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                        .with_vertical_alignment(VerticalAlignment::Center))
+                                        .with_text(mode)
+                                        .build(ctx))))
+                                    .build(ctx))
+                                .collect())
+                            .with_selected(0)
+                            .build(ctx);
+                        dl_mode
+                    })
+                    .with_child(TextBuilder::new(label(1))
+                        .with_text("Map")
+                        .build(ctx))
+                    // There is only one map for now.
+                    .with_child(TextBuilder::new(WidgetBuilder::new()
+                        .on_row(1)
+                        .on_column(1)
+                        .with_margin(Thickness::uniform(2.0))
+                        .with_vertical_alignment(VerticalAlignment::Center))
+                        .with_text(MAP_NAME)
+                        .build(ctx))
+                    .with_child(TextBuilder::new(label(2))
+                        .with_text("Max Players")
+                        .build(ctx))
+                    .with_child({
+                        sb_max_players = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                            min: MIN_PLAYERS as f32,
+                            max: MAX_PLAYERS as f32,
+                            value: DEFAULT_MAX_PLAYERS as f32,
+                            step: 1.0,
+                            row: 2,
+                            column: 1,
+                            margin: Thickness::uniform(2.0),
+                            show_value: true,
+                            orientation: Orientation::Horizontal,
+                        });
+                        sb_max_players
+                    })
+                    .with_child(TextBuilder::new(label(3))
+                        .with_text("Fill With Bots")
+                        .build(ctx))
+                    .with_child({
+                        cb_bot_fill = create_check_box(ctx, resource_manager, 3, 1, false);
+                        cb_bot_fill
+                    })
+                    .with_child(TextBuilder::new(label(4))
+                        .with_text("Combatants")
+                        .build(ctx))
+                    .with_child({
+                        sb_bot_fill = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                            min: BOT_FILL_RANGE.0 as f32,
+                            max: BOT_FILL_RANGE.1 as f32,
+                            value: DEFAULT_BOT_FILL as f32,
+                            step: 1.0,
+                            row: 4,
+                            column: 1,
+                            margin: Thickness::uniform(2.0),
+                            show_value: true,
+                            orientation: Orientation::Horizontal,
+                        });
+                        sb_bot_fill
+                    })
+                    .with_child(TextBuilder::new(label(5))
+                        .with_text("Port")
+                        .build(ctx))
+                    .with_child({
+                        tb_port = TextBoxBuilder::new(WidgetBuilder::new()
+                            .on_row(5)
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text(DEFAULT_PORT.to_string())
+                            .build(ctx);
+                        tb_port
+                    })
+                    .with_child({
+                        btn_host = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_row(6)
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text("Host")
+                            .build(ctx);
+                        btn_host
+                    })
+                    .with_child(TextBuilder::new(label(7))
+                        .with_text("Address (IP:Port)")
+                        .build(ctx))
+                    .with_child({
+                        tb_address = TextBoxBuilder::new(WidgetBuilder::new()
+                            .on_row(7)
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .build(ctx);
+                        tb_address
+                    })
+                    .with_child({
+                        btn_connect = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_row(8)
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text("Connect")
+                            .build(ctx);
+                        btn_connect
+                    })
+                    .with_child(TextBuilder::new(label(9))
+                        .with_text("LAN Servers")
+                        .build(ctx))
+                    .with_child({
+                        btn_refresh = ButtonBuilder::new(WidgetBuilder::new()
+                            .on_row(9)
+                            .on_column(1)
+                            .with_margin(Thickness::uniform(2.0)))
+                            .with_text("Refresh")
+                            .build(ctx);
+                        btn_refresh
+                    }))
+                    .add_column(Column::strict(180.0))
+                    .add_column(Column::stretch())
+                    .add_rows((0..10).map(|_| common_row).collect())
+                    .build(ctx))
+                .with_child({
+                    server_list = GridBuilder::new(WidgetBuilder::new()
+                        .on_row(1)
+                        .on_column(0)
+                        .with_margin(Thickness::uniform(2.0)))
+                        .add_row(Row::stretch())
+                        .add_column(Column::stretch())
+                        .build(ctx);
+                    server_list
+                })
+                .with_child({
+                    status_text = TextBuilder::new(WidgetBuilder::new()
+                        .on_row(2)
+                        .on_column(0)
+                        .with_margin(Thickness::uniform(2.0)))
+                        .with_wrap(true)
+                        .build(ctx);
+                    status_text
+                })
+                .with_child({
+                    btn_close = ButtonBuilder::new(WidgetBuilder::new()
+                        .on_row(3)
+                        .on_column(0)
+                        .with_margin(Thickness::uniform(2.0)))
+                        .with_text("Close")
+                        .build(ctx);
+                    btn_close
+                }))
+                .add_column(Column::stretch())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(50.0))
+                .add_row(Row::strict(40.0))
+                .build(ctx))
+            .build(ctx);
+
+        Self {
+            window,
+            dl_mode,
+            sb_max_players,
+            cb_bot_fill,
+            sb_bot_fill,
+            tb_port,
+            btn_host,
+            tb_address,
+            btn_connect,
+            btn_refresh,
+            server_list,
+            status_text,
+            btn_close,
+            browser: None,
+            servers: Default::default(),
+            server_buttons: Default::default(),
+        }
+    }
+
+    /// Opens page and starts new search of servers in local network.
+    pub fn open(&mut self, ui: &mut Gui) {
+        ui.send_message(WindowMessage::open(self.window));
+        ui.send_message(WidgetMessage::center(self.window));
+        self.refresh(ui);
+    }
+
+    /// Shows result of last network action, like failed connection.
+    pub fn set_status(&mut self, ui: &mut Gui, text: &str) {
+        ui.send_message(TextMessage::text(self.status_text, text.to_owned()));
+    }
+
+    fn refresh(&mut self, ui: &mut Gui) {
+        if self.browser.is_none() {
+            match LanBrowser::new() {
+                Ok(browser) => self.browser = Some(browser),
+                Err(e) => self.set_status(ui, &format!("Unable to search LAN servers. Reason: {}", e)),
+            }
+        }
+        if let Some(browser) = self.browser.as_ref() {
+            browser.refresh();
+        }
+        self.servers.clear();
+        self.rebuild_server_list(ui);
+    }
+
+    /// Collects answers of servers in local network, must be called every frame.
+    pub fn update(&mut self, ui: &mut Gui) {
+        let found = match self.browser.as_ref() {
+            Some(browser) => browser.poll(),
+            None => return,
+        };
+        if !found.is_empty() {
+            for server in found {
+                match self.servers.iter_mut().find(|listed| listed.address == server.address) {
+                    Some(listed) => *listed = server,
+                    None => self.servers.push(server),
+                }
+            }
+            self.rebuild_server_list(ui);
+        }
+    }
+
+    fn rebuild_server_list(&mut self, ui: &mut Gui) {
+        let old_list = ui.node(self.server_list).children().first().cloned();
+
+        let ctx = &mut ui.build_ctx();
+        self.server_buttons = self.servers
+            .iter()
+            .enumerate()
+            .map(|(row, server)| {
+                ButtonBuilder::new(WidgetBuilder::new()
+                    .on_row(row)
+                    .on_column(0)
+                    .with_margin(Thickness::uniform(1.0)))
+                    .with_text(&format!("{} - {} - {}/{} players - {}",
+                                        server.info.mode,
+                                        server.info.map,
+                                        server.info.players,
+                                        server.info.max_players,
+                                        server.address))
+                    .build(ctx)
+            })
+            .collect();
+        let mut children = self.server_buttons.clone();
+        if self.servers.is_empty() {
+            children.push(TextBuilder::new(WidgetBuilder::new()
+                .on_row(0)
+                .on_column(0)
+                .with_margin(Thickness::uniform(2.0)))
+                .with_text("No servers found")
+                .build(ctx));
+        }
+        let list = GridBuilder::new(WidgetBuilder::new()
+            .with_children(&children))
+            .add_rows(children.iter().map(|_| Row::strict(30.0)).collect())
+            .add_row(Row::stretch())
+            .add_column(Column::stretch())
+            .build(ctx);
+
+        if let Some(old_list) = old_list {
+            ui.send_message(WidgetMessage::remove(old_list));
+        }
+        ui.send_message(WidgetMessage::link(list, self.server_list));
+    }
+
+    /// Returns message that should be sent to host or join a match, menu decides what to do
+    /// with it because it may require confirmation from user.
+    pub fn handle_ui_event(&mut self, ui: &mut Gui, message: &GuiMessage) -> Option<Message> {
+        if let UiMessageData::Button(ButtonMessage::Click) = &message.data {
+            if message.destination == self.btn_host {
+                let options = if let UINode::DropdownList(dropdown_list) = ui.node(self.dl_mode) {
+                    hosted_mode(dropdown_list.selection().unwrap_or(0))
+                } else {
+                    hosted_mode(0)
+                };
+                let max_players = if let UINode::ScrollBar(scroll_bar) = ui.node(self.sb_max_players) {
+                    scroll_bar.value() as u32
+                } else {
+                    DEFAULT_MAX_PLAYERS
+                };
+                let fill_enabled = if let UINode::CheckBox(check_box) = ui.node(self.cb_bot_fill) {
+                    check_box.checked().unwrap_or(false)
+                } else {
+                    false
+                };
+                let bot_fill = if let UINode::ScrollBar(scroll_bar) = ui.node(self.sb_bot_fill) {
+                    Some(scroll_bar.value() as u32).filter(|_| fill_enabled)
+                } else {
+                    None
+                };
+                let port = if let UINode::TextBox(text_box) = ui.node(self.tb_port) {
+                    text_box.text().trim().parse::<u16>().ok()
+                } else {
+                    None
+                };
+                match port {
+                    Some(port) => {
+                        self.set_status(ui, "");
+                        return Some(Message::HostMatch { options, port, max_players, bot_fill });
+                    }
+                    None => self.set_status(ui, "Port must be a number from 0 to 65535."),
+                }
+            } else if message.destination == self.btn_connect {
+                let address = if let UINode::TextBox(text_box) = ui.node(self.tb_address) {
+                    text_box.text().trim().to_owned()
+                } else {
+                    String::new()
+                };
+                if address.is_empty() {
+                    self.set_status(ui, "Enter address of a server, for example 192.168.0.2:7777.");
+                } else {
+                    self.set_status(ui, "");
+                    return Some(Message::ConnectToServer { address });
+                }
+            } else if message.destination == self.btn_refresh {
+                self.refresh(ui);
+            } else if message.destination == self.btn_close {
+                ui.send_message(WindowMessage::close(self.window));
+            } else if let Some(index) = self.server_buttons.iter().position(|button| *button == message.destination) {
+                self.set_status(ui, "");
+                return Some(Message::ConnectToServer {
+                    address: self.servers[index].address.to_string()
+                });
+            }
+        }
+        None
+    }
+}
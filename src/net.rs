@@ -0,0 +1,1044 @@
+//! Groundwork for multiplayer. Server simulates level authoritatively, clients send their
+//! input and receive snapshots of actors over UDP. Only a listen server is supported for
+//! now: host plays while serving remote players. Every snapshot tells which input of the
+//! client was applied last, client uses it to check its prediction, see `prediction`
+//! module.
+//!
+//! Servers in local network are found by broadcasting a query to discovery port, every
+//! server that listens on it answers with description of its match.
+//!
+//! Sockets are non-blocking and polled once per tick from the game loop, so networking
+//! does not need its own thread.
+//!
+//! For testing, bad network can be simulated on both ends: datagrams of a match are
+//! delayed and dropped as `NetSimulation` says before they are sent and after they are
+//! received. Discovery is never affected.
+
+use crate::actor::Actor;
+use rg3d::core::{
+    math::vec3::Vec3,
+    pool::Handle,
+};
+use std::{
+    net::{UdpSocket, SocketAddr, ToSocketAddrs, Ipv4Addr},
+    io::{self, ErrorKind},
+    time::{Duration, Instant},
+};
+use rand::Rng;
+
+pub const DEFAULT_PORT: u16 = 7777;
+/// Servers listen for LAN queries on this port, whatever port their match is on. Only one
+/// server per machine can be discovered.
+const DISCOVERY_PORT: u16 = 7776;
+/// Every packet starts with this id, so stray datagrams are ignored.
+const PROTOCOL_ID: u32 = 0x5253_4e50;
+/// Must be changed when layout of any packet changes.
+const PROTOCOL_VERSION: u32 = 4;
+/// Player cap of a server, host counts as a player too.
+pub const MIN_PLAYERS: u32 = 2;
+pub const MAX_PLAYERS: u32 = 8;
+pub const DEFAULT_MAX_PLAYERS: u32 = 2;
+/// Peer that sent nothing for this long is considered gone, in seconds.
+const TIMEOUT: f64 = 5.0;
+/// How often client repeats connection request until server answers, in seconds.
+const CONNECT_RETRY_INTERVAL: f64 = 1.0;
+/// Large enough for a snapshot of every actor in a match.
+const MAX_PACKET_SIZE: usize = 1400;
+
+/// Identifies client on server, ids are never reused during server lifetime.
+pub type ClientId = u32;
+
+/// Handle of actor on server, clients use it to match actors between snapshots.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct NetId {
+    index: u32,
+    generation: u32,
+}
+
+impl From<Handle<Actor>> for NetId {
+    fn from(handle: Handle<Actor>) -> Self {
+        Self {
+            index: handle.index(),
+            generation: handle.generation(),
+        }
+    }
+}
+
+/// State of controls of a player, sent by client every tick.
+#[derive(Copy, Clone, Default, PartialEq, Debug)]
+pub struct PlayerInput {
+    pub move_forward: bool,
+    pub move_backward: bool,
+    pub move_left: bool,
+    pub move_right: bool,
+    pub crouch: bool,
+    pub jump: bool,
+    pub run: bool,
+    pub shoot: bool,
+    pub aim: bool,
+    pub alt_fire: bool,
+    /// Look angles in degrees.
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl PlayerInput {
+    fn buttons(&self) -> [bool; 10] {
+        [
+            self.move_forward,
+            self.move_backward,
+            self.move_left,
+            self.move_right,
+            self.crouch,
+            self.jump,
+            self.run,
+            self.shoot,
+            self.aim,
+            self.alt_fire,
+        ]
+    }
+
+    fn write(&self, writer: &mut Writer) {
+        let flags = self.buttons()
+            .iter()
+            .enumerate()
+            .fold(0u32, |flags, (bit, &pressed)| flags | ((pressed as u32) << bit));
+        writer.u32(flags);
+        writer.f32(self.yaw);
+        writer.f32(self.pitch);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, String> {
+        let flags = reader.u32()?;
+        let pressed = |bit: u32| flags & (1 << bit) != 0;
+        Ok(Self {
+            move_forward: pressed(0),
+            move_backward: pressed(1),
+            move_left: pressed(2),
+            move_right: pressed(3),
+            crouch: pressed(4),
+            jump: pressed(5),
+            run: pressed(6),
+            shoot: pressed(7),
+            aim: pressed(8),
+            alt_fire: pressed(9),
+            yaw: reader.f32()?,
+            pitch: reader.f32()?,
+        })
+    }
+}
+
+/// State of one actor in a snapshot.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct ActorState {
+    pub id: NetId,
+    pub position: Vec3,
+    /// Look angles in degrees.
+    pub yaw: f32,
+    pub pitch: f32,
+    pub health: f32,
+}
+
+impl ActorState {
+    fn write(&self, writer: &mut Writer) {
+        writer.u32(self.id.index);
+        writer.u32(self.id.generation);
+        writer.f32(self.position.x);
+        writer.f32(self.position.y);
+        writer.f32(self.position.z);
+        writer.f32(self.yaw);
+        writer.f32(self.pitch);
+        writer.f32(self.health);
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, String> {
+        Ok(Self {
+            id: NetId {
+                index: reader.u32()?,
+                generation: reader.u32()?,
+            },
+            position: Vec3::new(reader.f32()?, reader.f32()?, reader.f32()?),
+            yaw: reader.f32()?,
+            pitch: reader.f32()?,
+            health: reader.f32()?,
+        })
+    }
+}
+
+/// Description of a match that server tells to LAN queries.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ServerInfo {
+    pub map: String,
+    pub mode: String,
+    /// Host included.
+    pub players: u32,
+    pub max_players: u32,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+enum Packet {
+    Connect,
+    Accept,
+    Reject { reason: String },
+    Input { sequence: u32, input: PlayerInput },
+    /// `own_actor` is actor of receiving client, None while it waits for respawn.
+    /// `acknowledged_input` is sequence of last input of the client that was applied
+    /// before the snapshot was taken.
+    Snapshot { tick: u32, own_actor: Option<NetId>, acknowledged_input: Option<u32>, actors: Vec<ActorState> },
+    Disconnect,
+    /// Broadcasted to discovery port to find servers in local network.
+    Query,
+    /// Answer to query, `port` is the one clients must connect to.
+    Info { port: u16, info: ServerInfo },
+}
+
+impl Packet {
+    fn id(&self) -> u8 {
+        match self {
+            Packet::Connect => 0,
+            Packet::Accept => 1,
+            Packet::Reject { .. } => 2,
+            Packet::Input { .. } => 3,
+            Packet::Snapshot { .. } => 4,
+            Packet::Disconnect => 5,
+            Packet::Query => 6,
+            Packet::Info { .. } => 7,
+        }
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut writer = Writer::default();
+        writer.u32(PROTOCOL_ID);
+        writer.u32(PROTOCOL_VERSION);
+        writer.u8(self.id());
+        match self {
+            Packet::Connect | Packet::Accept | Packet::Disconnect | Packet::Query => (),
+            Packet::Reject { reason } => writer.string(reason),
+            Packet::Input { sequence, input } => {
+                writer.u32(*sequence);
+                input.write(&mut writer);
+            }
+            Packet::Snapshot { tick, own_actor, acknowledged_input, actors } => {
+                writer.u32(*tick);
+                match own_actor {
+                    Some(id) => {
+                        writer.u8(1);
+                        writer.u32(id.index);
+                        writer.u32(id.generation);
+                    }
+                    None => writer.u8(0),
+                }
+                match acknowledged_input {
+                    Some(sequence) => {
+                        writer.u8(1);
+                        writer.u32(*sequence);
+                    }
+                    None => writer.u8(0),
+                }
+                writer.u32(actors.len() as u32);
+                for actor in actors {
+                    actor.write(&mut writer);
+                }
+            }
+            Packet::Info { port, info } => {
+                writer.u32(*port as u32);
+                writer.string(&info.map);
+                writer.string(&info.mode);
+                writer.u32(info.players);
+                writer.u32(info.max_players);
+            }
+        }
+        writer.0
+    }
+
+    fn decode(data: &[u8]) -> Result<Self, String> {
+        let mut reader = Reader { data, position: 0 };
+        if reader.u32()? != PROTOCOL_ID {
+            return Err("not a game packet".to_owned());
+        }
+        let version = reader.u32()?;
+        if version != PROTOCOL_VERSION {
+            return Err(format!("protocol version {} is not supported", version));
+        }
+        match reader.u8()? {
+            0 => Ok(Packet::Connect),
+            1 => Ok(Packet::Accept),
+            2 => Ok(Packet::Reject { reason: reader.string()? }),
+            3 => Ok(Packet::Input {
+                sequence: reader.u32()?,
+                input: PlayerInput::read(&mut reader)?,
+            }),
+            4 => {
+                let tick = reader.u32()?;
+                let own_actor = if reader.u8()? != 0 {
+                    Some(NetId {
+                        index: reader.u32()?,
+                        generation: reader.u32()?,
+                    })
+                } else {
+                    None
+                };
+                let acknowledged_input = if reader.u8()? != 0 {
+                    Some(reader.u32()?)
+                } else {
+                    None
+                };
+                let count = reader.u32()? as usize;
+                let mut actors = Vec::new();
+                for _ in 0..count {
+                    actors.push(ActorState::read(&mut reader)?);
+                }
+                Ok(Packet::Snapshot { tick, own_actor, acknowledged_input, actors })
+            }
+            5 => Ok(Packet::Disconnect),
+            6 => Ok(Packet::Query),
+            7 => Ok(Packet::Info {
+                port: reader.u32()? as u16,
+                info: ServerInfo {
+                    map: reader.string()?,
+                    mode: reader.string()?,
+                    players: reader.u32()?,
+                    max_players: reader.u32()?,
+                },
+            }),
+            id => Err(format!("unknown packet {}", id)),
+        }
+    }
+}
+
+/// Writes values in little endian byte order.
+#[derive(Default)]
+struct Writer(Vec<u8>);
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.0.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.0.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.u32(value.to_bits());
+    }
+
+    fn string(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.0.extend_from_slice(value.as_bytes());
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], String> {
+        let bytes = self.data
+            .get(self.position..self.position + count)
+            .ok_or_else(|| "packet is too short".to_owned())?;
+        self.position += count;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.bytes(4)?);
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    fn f32(&mut self) -> Result<f32, String> {
+        Ok(f32::from_bits(self.u32()?))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.bytes(len)?.to_vec()).map_err(|e| e.to_string())
+    }
+}
+
+/// Reads every datagram that arrived since last call without decoding it.
+fn receive_datagrams(socket: &UdpSocket) -> io::Result<Vec<(SocketAddr, Vec<u8>)>> {
+    let mut datagrams = Vec::new();
+    let mut buffer = [0; MAX_PACKET_SIZE];
+    loop {
+        match socket.recv_from(&mut buffer) {
+            Ok((size, address)) => datagrams.push((address, buffer[..size].to_vec())),
+            Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(datagrams),
+            // Windows reports that previous datagram was not delivered on next receive,
+            // it says nothing about further datagrams.
+            Err(e) if e.kind() == ErrorKind::ConnectionReset => (),
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Decodes received datagrams, malformed ones are skipped.
+fn decode_datagrams(datagrams: Vec<(SocketAddr, Vec<u8>)>) -> Vec<(SocketAddr, Packet)> {
+    datagrams
+        .into_iter()
+        .filter_map(|(address, bytes)| match Packet::decode(&bytes) {
+            Ok(packet) => Some((address, packet)),
+            Err(e) => {
+                println!("Ignoring packet from {}. Reason: {}", address, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Reads every datagram that arrived since last call, malformed ones are skipped.
+fn receive(socket: &UdpSocket) -> io::Result<Vec<(SocketAddr, Packet)>> {
+    receive_datagrams(socket).map(decode_datagrams)
+}
+
+fn send_datagram(socket: &UdpSocket, address: SocketAddr, bytes: &[u8]) {
+    // Datagrams may be lost anyway, so failed send is not an error for the protocol.
+    if let Err(e) = socket.send_to(bytes, address) {
+        println!("Unable to send packet to {}. Reason: {:?}", address, e);
+    }
+}
+
+fn send(socket: &UdpSocket, address: SocketAddr, packet: &Packet) {
+    send_datagram(socket, address, &packet.encode());
+}
+
+/// Names of simulation values that can be set from console, see [`NetSimulation::set`].
+pub const NET_SIMULATION_NAMES: [&str; 3] = ["net_latency", "net_jitter", "net_loss"];
+
+/// Artificial network conditions, debug tool for netcode. Everything is applied in each
+/// direction, so round trip to a peer grows by twice the latency.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct NetSimulation {
+    /// Delay of every datagram, in seconds.
+    pub latency: f32,
+    /// Maximum random addition to latency, in seconds. Datagrams may be reordered by it,
+    /// same as in real networks.
+    pub jitter: f32,
+    /// Chance of a datagram to be lost, in 0..1 range.
+    pub loss: f32,
+}
+
+impl NetSimulation {
+    pub fn is_enabled(&self) -> bool {
+        self.latency > 0.0 || self.jitter > 0.0 || self.loss > 0.0
+    }
+
+    /// Returns time after which datagram must be delivered, None if it is lost.
+    fn delay(&self) -> Option<Duration> {
+        let mut rng = rand::thread_rng();
+        if rng.gen::<f32>() < self.loss {
+            None
+        } else {
+            Some(Duration::from_secs_f32(self.latency + self.jitter * rng.gen::<f32>()))
+        }
+    }
+
+    /// Sets value by its console name, see [`NET_SIMULATION_NAMES`]. Latency and jitter
+    /// are given in milliseconds and loss in percents, same as network tools show them.
+    pub fn set(&mut self, name: &str, value: f32) -> Result<(), String> {
+        if !value.is_finite() || value < 0.0 {
+            return Err(format!("network simulation value must be a non-negative number, got {}", value));
+        }
+        match name {
+            "net_latency" => self.latency = value / 1000.0,
+            "net_jitter" => self.jitter = value / 1000.0,
+            "net_loss" => self.loss = (value / 100.0).min(1.0),
+            _ => return Err(format!("unknown network simulation value {}, expected one of {:?}", name, NET_SIMULATION_NAMES)),
+        }
+        Ok(())
+    }
+}
+
+struct DelayedDatagram {
+    time: Instant,
+    address: SocketAddr,
+    bytes: Vec<u8>,
+}
+
+/// Takes datagrams which time has come out of the queue, in the order of their time.
+fn take_due(queue: &mut Vec<DelayedDatagram>, now: Instant) -> Vec<DelayedDatagram> {
+    let (mut due, waiting): (Vec<_>, Vec<_>) = queue.drain(..).partition(|datagram| datagram.time <= now);
+    *queue = waiting;
+    due.sort_by_key(|datagram| datagram.time);
+    due
+}
+
+/// Socket of a match, passes datagrams through network simulation when it is enabled.
+/// Delayed datagrams are sent and delivered on next send or receive, which happen every
+/// tick, so timing is as precise as tick rate allows.
+struct Link {
+    socket: UdpSocket,
+    simulation: NetSimulation,
+    outgoing: Vec<DelayedDatagram>,
+    incoming: Vec<DelayedDatagram>,
+}
+
+impl Link {
+    fn new(socket: UdpSocket) -> Self {
+        Self {
+            socket,
+            simulation: Default::default(),
+            outgoing: Default::default(),
+            incoming: Default::default(),
+        }
+    }
+
+    /// Datagrams that wait in queues keep their delays when simulation changes.
+    fn set_simulation(&mut self, simulation: NetSimulation) {
+        self.simulation = simulation;
+    }
+
+    fn flush(&mut self) {
+        for datagram in take_due(&mut self.outgoing, Instant::now()) {
+            send_datagram(&self.socket, datagram.address, &datagram.bytes);
+        }
+    }
+
+    fn send(&mut self, address: SocketAddr, packet: &Packet) {
+        if self.simulation.is_enabled() || !self.outgoing.is_empty() {
+            if let Some(delay) = self.simulation.delay() {
+                self.outgoing.push(DelayedDatagram {
+                    time: Instant::now() + delay,
+                    address,
+                    bytes: packet.encode(),
+                });
+            }
+            self.flush();
+        } else {
+            send(&self.socket, address, packet);
+        }
+    }
+
+    fn receive(&mut self) -> io::Result<Vec<(SocketAddr, Packet)>> {
+        self.flush();
+        let datagrams = receive_datagrams(&self.socket)?;
+        if !self.simulation.is_enabled() && self.incoming.is_empty() {
+            return Ok(decode_datagrams(datagrams));
+        }
+        let now = Instant::now();
+        for (address, bytes) in datagrams {
+            if let Some(delay) = self.simulation.delay() {
+                self.incoming.push(DelayedDatagram {
+                    time: now + delay,
+                    address,
+                    bytes,
+                });
+            }
+        }
+        let due = take_due(&mut self.incoming, now)
+            .into_iter()
+            .map(|datagram| (datagram.address, datagram.bytes))
+            .collect();
+        Ok(decode_datagrams(due))
+    }
+}
+
+pub enum ServerEvent {
+    /// New client joined, actor must be spawned for it.
+    Connected { client: ClientId, address: SocketAddr },
+    Input { client: ClientId, input: PlayerInput },
+    /// Client left or timed out, its actor must be removed.
+    Disconnected { client: ClientId },
+}
+
+struct RemoteClient {
+    id: ClientId,
+    address: SocketAddr,
+    last_input_sequence: Option<u32>,
+    last_heard: f64,
+}
+
+pub struct Server {
+    link: Link,
+    /// None if discovery port is taken, server still can be joined by address then.
+    discovery: Option<UdpSocket>,
+    clients: Vec<RemoteClient>,
+    next_client_id: ClientId,
+    tick: u32,
+    max_players: u32,
+    map: String,
+    mode: String,
+}
+
+fn bind_discovery() -> io::Result<UdpSocket> {
+    let socket = UdpSocket::bind(("0.0.0.0", DISCOVERY_PORT))?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+impl Server {
+    /// Starts listening on given port on every network interface. `max_players` includes
+    /// host, `map` and `mode` are shown in LAN server list.
+    pub fn bind(port: u16, max_players: u32, map: &str, mode: &str) -> io::Result<Self> {
+        let socket = UdpSocket::bind(("0.0.0.0", port))?;
+        socket.set_nonblocking(true)?;
+        let discovery = match bind_discovery() {
+            Ok(discovery) => Some(discovery),
+            Err(e) => {
+                println!("Server won't be listed in LAN. Reason: {:?}", e);
+                None
+            }
+        };
+        Ok(Self {
+            link: Link::new(socket),
+            discovery,
+            clients: Default::default(),
+            next_client_id: 0,
+            tick: 0,
+            max_players: max_players.max(MIN_PLAYERS).min(MAX_PLAYERS),
+            map: map.to_owned(),
+            mode: mode.to_owned(),
+        })
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.link.socket.local_addr().ok().map(|address| address.port())
+    }
+
+    pub fn set_simulation(&mut self, simulation: NetSimulation) {
+        self.link.set_simulation(simulation);
+    }
+
+    pub fn info(&self) -> ServerInfo {
+        ServerInfo {
+            map: self.map.clone(),
+            mode: self.mode.clone(),
+            players: self.clients.len() as u32 + 1,
+            max_players: self.max_players,
+        }
+    }
+
+    fn answer_queries(&self) {
+        if let (Some(discovery), Some(port)) = (self.discovery.as_ref(), self.port()) {
+            let queries = receive(discovery).unwrap_or_else(|e| {
+                println!("Unable to receive LAN queries. Reason: {:?}", e);
+                Vec::new()
+            });
+            for (address, packet) in queries {
+                if packet == Packet::Query {
+                    send(discovery, address, &Packet::Info { port, info: self.info() });
+                }
+            }
+        }
+    }
+
+    /// Receives packets from clients, `time` is elapsed game time in seconds.
+    pub fn poll(&mut self, time: f64) -> Vec<ServerEvent> {
+        let mut events = Vec::new();
+
+        self.answer_queries();
+
+        let packets = self.link.receive().unwrap_or_else(|e| {
+            println!("Unable to receive packets. Reason: {:?}", e);
+            Vec::new()
+        });
+        for (address, packet) in packets {
+            let client = self.clients.iter_mut().find(|client| client.address == address);
+            match (packet, client) {
+                (Packet::Connect, Some(client)) => {
+                    // Accept was lost, client asks again.
+                    client.last_heard = time;
+                    self.link.send(address, &Packet::Accept);
+                }
+                (Packet::Connect, None) => {
+                    if (self.clients.len() as u32) + 1 < self.max_players {
+                        let id = self.next_client_id;
+                        self.next_client_id += 1;
+                        self.clients.push(RemoteClient {
+                            id,
+                            address,
+                            last_input_sequence: None,
+                            last_heard: time,
+                        });
+                        self.link.send(address, &Packet::Accept);
+                        events.push(ServerEvent::Connected { client: id, address });
+                    } else {
+                        self.link.send(address, &Packet::Reject { reason: "server is full".to_owned() });
+                    }
+                }
+                (Packet::Input { sequence, input }, Some(client)) => {
+                    client.last_heard = time;
+                    // Datagrams may arrive out of order, older input is just dropped.
+                    if client.last_input_sequence.map_or(true, |last| sequence > last) {
+                        client.last_input_sequence = Some(sequence);
+                        events.push(ServerEvent::Input { client: client.id, input });
+                    }
+                }
+                (Packet::Disconnect, Some(client)) => {
+                    let id = client.id;
+                    self.clients.retain(|client| client.id != id);
+                    events.push(ServerEvent::Disconnected { client: id });
+                }
+                _ => (),
+            }
+        }
+
+        let (alive, timed_out): (Vec<_>, Vec<_>) = self.clients
+            .drain(..)
+            .partition(|client| time - client.last_heard < TIMEOUT);
+        self.clients = alive;
+        for client in timed_out {
+            println!("Client {} timed out.", client.address);
+            events.push(ServerEvent::Disconnected { client: client.id });
+        }
+
+        events
+    }
+
+    /// Sends state of actors to every client, `own_actor` gives actor of each client.
+    pub fn send_snapshot<F>(&mut self, actors: &[ActorState], own_actor: F)
+        where F: Fn(ClientId) -> Option<NetId> {
+        self.tick = self.tick.wrapping_add(1);
+        for client in self.clients.iter() {
+            let packet = Packet::Snapshot {
+                tick: self.tick,
+                own_actor: own_actor(client.id),
+                acknowledged_input: client.last_input_sequence,
+                actors: actors.to_vec(),
+            };
+            self.link.send(client.address, &packet);
+        }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        // Simulation is bypassed, delayed datagrams would never be sent.
+        for client in self.clients.iter() {
+            send(&self.link.socket, client.address, &Packet::Disconnect);
+        }
+    }
+}
+
+pub enum ClientEvent {
+    Accepted,
+    Rejected { reason: String },
+    Snapshot { own_actor: Option<NetId>, acknowledged_input: Option<u32>, actors: Vec<ActorState> },
+    /// Server did not answer any connection request, most likely nothing is hosted at
+    /// the address.
+    NoResponse,
+    /// Server stopped or nothing was heard from it for too long.
+    Disconnected,
+}
+
+pub struct Client {
+    link: Link,
+    server: SocketAddr,
+    accepted: bool,
+    input_sequence: u32,
+    last_tick: Option<u32>,
+    last_heard: f64,
+    last_connect_attempt: f64,
+}
+
+impl Client {
+    /// Starts connecting to server at given address, `time` is elapsed game time in seconds.
+    pub fn connect<A: ToSocketAddrs>(address: A, time: f64) -> io::Result<Self> {
+        let server = address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::new(ErrorKind::InvalidInput, "address does not resolve"))?;
+        let local_address = if server.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+        let socket = UdpSocket::bind(local_address)?;
+        socket.set_nonblocking(true)?;
+        send(&socket, server, &Packet::Connect);
+        Ok(Self {
+            link: Link::new(socket),
+            server,
+            accepted: false,
+            input_sequence: 0,
+            last_tick: None,
+            last_heard: time,
+            last_connect_attempt: time,
+        })
+    }
+
+    pub fn server_address(&self) -> SocketAddr {
+        self.server
+    }
+
+    pub fn set_simulation(&mut self, simulation: NetSimulation) {
+        self.link.set_simulation(simulation);
+    }
+
+    /// Sends input to server and returns its sequence, None if server has not accepted
+    /// client yet.
+    pub fn send_input(&mut self, input: PlayerInput) -> Option<u32> {
+        if self.accepted {
+            self.input_sequence = self.input_sequence.wrapping_add(1);
+            self.link.send(self.server, &Packet::Input {
+                sequence: self.input_sequence,
+                input,
+            });
+            Some(self.input_sequence)
+        } else {
+            None
+        }
+    }
+
+    /// Receives packets from server, `time` is elapsed game time in seconds.
+    pub fn poll(&mut self, time: f64) -> Vec<ClientEvent> {
+        let mut events = Vec::new();
+
+        if !self.accepted && time - self.last_connect_attempt >= CONNECT_RETRY_INTERVAL {
+            self.last_connect_attempt = time;
+            self.link.send(self.server, &Packet::Connect);
+        }
+
+        let packets = self.link.receive().unwrap_or_else(|e| {
+            println!("Unable to receive packets. Reason: {:?}", e);
+            Vec::new()
+        });
+        for (address, packet) in packets {
+            if address != self.server {
+                continue;
+            }
+            self.last_heard = time;
+            match packet {
+                Packet::Accept if !self.accepted => {
+                    self.accepted = true;
+                    events.push(ClientEvent::Accepted);
+                }
+                Packet::Reject { reason } => events.push(ClientEvent::Rejected { reason }),
+                // Only the newest state matters, late snapshots are dropped.
+                Packet::Snapshot { tick, own_actor, acknowledged_input, actors }
+                if self.last_tick.map_or(true, |last| tick.wrapping_sub(last) as i32 > 0) => {
+                    self.last_tick = Some(tick);
+                    events.push(ClientEvent::Snapshot { own_actor, acknowledged_input, actors });
+                }
+                Packet::Disconnect => events.push(ClientEvent::Disconnected),
+                _ => (),
+            }
+        }
+
+        if time - self.last_heard >= TIMEOUT {
+            events.push(if self.accepted {
+                ClientEvent::Disconnected
+            } else {
+                ClientEvent::NoResponse
+            });
+        }
+
+        events
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        send(&self.link.socket, self.server, &Packet::Disconnect);
+    }
+}
+
+/// Server found in local network.
+#[derive(Clone, PartialEq, Debug)]
+pub struct LanServer {
+    /// Address that clients must connect to.
+    pub address: SocketAddr,
+    pub info: ServerInfo,
+}
+
+/// Finds servers in local network, see module docs.
+pub struct LanBrowser {
+    socket: UdpSocket,
+}
+
+impl LanBrowser {
+    pub fn new() -> io::Result<Self> {
+        let socket = UdpSocket::bind("0.0.0.0:0")?;
+        socket.set_broadcast(true)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self { socket })
+    }
+
+    /// Asks every server in local network to describe itself, answers come with `poll`.
+    pub fn refresh(&self) {
+        send(&self.socket, SocketAddr::from((Ipv4Addr::BROADCAST, DISCOVERY_PORT)), &Packet::Query);
+    }
+
+    /// Returns servers that answered since last call.
+    pub fn poll(&self) -> Vec<LanServer> {
+        let answers = receive(&self.socket).unwrap_or_else(|e| {
+            println!("Unable to receive LAN answers. Reason: {:?}", e);
+            Vec::new()
+        });
+        answers
+            .into_iter()
+            .filter_map(|(address, packet)| match packet {
+                Packet::Info { port, info } => Some(LanServer {
+                    address: SocketAddr::new(address.ip(), port),
+                    info,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot() -> Packet {
+        Packet::Snapshot {
+            tick: 42,
+            own_actor: Some(NetId { index: 3, generation: 1 }),
+            acknowledged_input: Some(17),
+            actors: vec![
+                ActorState {
+                    id: NetId { index: 3, generation: 1 },
+                    position: Vec3::new(1.0, 2.5, -3.0),
+                    yaw: 90.0,
+                    pitch: -10.0,
+                    health: 75.0,
+                },
+                ActorState {
+                    id: NetId { index: 5, generation: 2 },
+                    position: Vec3::new(0.0, 0.0, 0.0),
+                    yaw: 0.0,
+                    pitch: 0.0,
+                    health: 100.0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn writer_and_reader_agree() {
+        let mut writer = Writer::default();
+        writer.u8(7);
+        writer.u32(0xdead_beef);
+        writer.f32(-1.5);
+        writer.string("Rusty Shooter");
+        writer.string("");
+
+        let mut reader = Reader { data: &writer.0, position: 0 };
+        assert_eq!(reader.u8(), Ok(7));
+        assert_eq!(reader.u32(), Ok(0xdead_beef));
+        assert_eq!(reader.f32(), Ok(-1.5));
+        assert_eq!(reader.string(), Ok("Rusty Shooter".to_owned()));
+        assert_eq!(reader.string(), Ok(String::new()));
+        assert!(reader.u8().is_err());
+    }
+
+    #[test]
+    fn packets_survive_round_trip() {
+        let packets = vec![
+            Packet::Connect,
+            Packet::Accept,
+            Packet::Reject { reason: "server is full".to_owned() },
+            Packet::Input {
+                sequence: 9,
+                input: PlayerInput {
+                    move_forward: true,
+                    run: true,
+                    alt_fire: true,
+                    yaw: 45.0,
+                    pitch: 5.0,
+                    ..Default::default()
+                },
+            },
+            snapshot(),
+            Packet::Snapshot { tick: 1, own_actor: None, acknowledged_input: None, actors: Vec::new() },
+            Packet::Disconnect,
+        ];
+        for packet in packets {
+            assert_eq!(Packet::decode(&packet.encode()), Ok(packet));
+        }
+    }
+
+    #[test]
+    fn truncated_packet_is_refused() {
+        let data = snapshot().encode();
+        for len in 0..data.len() {
+            assert!(Packet::decode(&data[..len]).is_err(), "packet cut to {} bytes was accepted", len);
+        }
+    }
+
+    #[test]
+    fn other_protocol_version_is_refused() {
+        let mut data = Packet::Connect.encode();
+        data[4..8].copy_from_slice(&(PROTOCOL_VERSION + 1).to_le_bytes());
+        assert!(Packet::decode(&data).is_err());
+    }
+
+    #[test]
+    fn foreign_datagram_is_refused() {
+        assert!(Packet::decode(b"GET / HTTP/1.1\r\n").is_err());
+    }
+
+    #[test]
+    fn discovery_packets_survive_round_trip() {
+        assert_eq!(Packet::decode(&Packet::Query.encode()), Ok(Packet::Query));
+
+        let info = Packet::Info {
+            port: DEFAULT_PORT,
+            info: ServerInfo {
+                map: "DM6".to_owned(),
+                mode: "Deathmatch".to_owned(),
+                players: 3,
+                max_players: MAX_PLAYERS,
+            },
+        };
+        assert_eq!(Packet::decode(&info.encode()), Ok(info));
+    }
+
+    /// Polls until given function returns something, fails after a couple of seconds, so
+    /// lost loopback datagram does not hang the test.
+    fn poll_until<T, F: FnMut() -> Option<T>>(mut func: F) -> T {
+        let deadline = Instant::now() + Duration::from_secs(2);
+        loop {
+            if let Some(result) = func() {
+                return result;
+            }
+            assert!(Instant::now() < deadline, "nothing was received over loopback");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn client_and_server_talk_over_loopback() {
+        let mut server = Server::bind(0, 2, "DM6", "Deathmatch").unwrap();
+        let port = server.port().unwrap();
+        let mut client = Client::connect(("127.0.0.1", port), 0.0).unwrap();
+
+        let client_id = poll_until(|| server.poll(0.0).into_iter().find_map(|event| match event {
+            ServerEvent::Connected { client, .. } => Some(client),
+            _ => None,
+        }));
+        poll_until(|| client.poll(0.0).into_iter().find(|event| matches!(event, ClientEvent::Accepted)));
+
+        let input = PlayerInput {
+            move_forward: true,
+            shoot: true,
+            yaw: 30.0,
+            ..Default::default()
+        };
+        let sequence = client.send_input(input).unwrap();
+        let received = poll_until(|| server.poll(0.0).into_iter().find_map(|event| match event {
+            ServerEvent::Input { client, input } if client == client_id => Some(input),
+            _ => None,
+        }));
+        assert_eq!(received, input);
+
+        let own_actor = NetId { index: 3, generation: 1 };
+        let actors = match snapshot() {
+            Packet::Snapshot { actors, .. } => actors,
+            _ => unreachable!(),
+        };
+        server.send_snapshot(&actors, |client| if client == client_id { Some(own_actor) } else { None });
+        let (own, acknowledged, received) = poll_until(|| client.poll(0.0).into_iter().find_map(|event| match event {
+            ClientEvent::Snapshot { own_actor, acknowledged_input, actors } => Some((own_actor, acknowledged_input, actors)),
+            _ => None,
+        }));
+        assert_eq!(own, Some(own_actor));
+        assert_eq!(acknowledged, Some(sequence));
+        assert_eq!(received, actors);
+    }
+}
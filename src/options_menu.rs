@@ -2,15 +2,30 @@ use std::{
     rc::Rc,
     sync::mpsc::Sender,
     cell::RefCell,
+    path::PathBuf,
 };
 use crate::{
     control_scheme::{
         ControlScheme,
         ControlButton,
+        PickupSwitch,
+        MAX_INPUT_BUFFER,
     },
     message::Message,
+    settings::{Settings, ContentFilter, DamageFeedback, Accessibility, SETTINGS_EXPORT_PATH, TEXT_SCALE_RANGE},
+    crosshair::{CrosshairConfig, CrosshairStyle},
+    hud_layout::{HudLayout, HUD_ELEMENTS, HUD_ANCHORS},
+    hud_theme::{HudTheme, HudThemePreset, HUD_THEME_PRESETS},
+    palette::ColorBlindMode,
+    effects::{EffectLimits, MAX_EFFECTS_RANGE},
+    level::{CorpseLimits, MAX_CORPSES_RANGE, MAX_CORPSE_LIFETIME, PATH_BUDGET_RANGE},
+    match_vote::MAX_VOTE_TIME,
+    damage::{RocketJump, SELF_DAMAGE_MODES, MAX_SELF_KNOCKBACK},
+    DEFAULT_MAX_SOUNDS,
+    MAX_SOUNDS_RANGE,
     UINodeHandle,
     GameEngine,
+    Gui,
     GuiMessage,
     gui::{
         create_check_box,
@@ -29,6 +44,7 @@ use rg3d::{
     window::Fullscreen,
     gui::{
         list_view::ListViewBuilder,
+        dropdown_list::DropdownListBuilder,
         grid::{
             GridBuilder,
             Row,
@@ -41,6 +57,7 @@ use rg3d::{
             WindowTitle,
         },
         text::TextBuilder,
+        stack_panel::StackPanelBuilder,
         border::BorderBuilder,
         decorator::DecoratorBuilder,
         button::ButtonBuilder,
@@ -48,6 +65,7 @@ use rg3d::{
             UiMessageData,
             ScrollBarMessage,
             ListViewMessage,
+            DropdownListMessage,
             CheckBoxMessage,
             ButtonMessage,
         },
@@ -64,6 +82,32 @@ use rg3d::{
 };
 use crate::gui::ScrollBarData;
 
+/// Time in seconds that HUD scale slider must stay still before new scale is applied, HUD is
+/// rebuilt for every scale so it is not done on every step of the slider.
+const HUD_SCALE_DELAY: f32 = 0.3;
+
+const CROSSHAIR_STYLES: [(&str, CrosshairStyle); 3] = [
+    ("Cross", CrosshairStyle::Cross),
+    ("Dot", CrosshairStyle::Dot),
+    ("Circle", CrosshairStyle::Circle),
+];
+
+const COLOR_BLIND_MODES: [(&str, ColorBlindMode); 4] = [
+    ("Off", ColorBlindMode::None),
+    ("Deuteranopia", ColorBlindMode::Deuteranopia),
+    ("Protanopia", ColorBlindMode::Protanopia),
+    ("Tritanopia", ColorBlindMode::Tritanopia),
+];
+
+/// Accent color of custom HUD theme is picked by three scroll bars, one per channel.
+const HUD_THEME_CHANNELS: [&str; 3] = ["Theme Red", "Theme Green", "Theme Blue"];
+
+const PICKUP_SWITCH_MODES: [(&str, PickupSwitch); 3] = [
+    ("Always", PickupSwitch::Always),
+    ("If Better", PickupSwitch::IfBetter),
+    ("Never", PickupSwitch::Never),
+];
+
 pub struct OptionsMenu {
     pub window: UINodeHandle,
     sender: Sender<Message>,
@@ -77,21 +121,101 @@ pub struct OptionsMenu {
     cb_soft_point_shadows: UINodeHandle,
     sb_point_shadow_distance: UINodeHandle,
     sb_spot_shadow_distance: UINodeHandle,
+    cb_damage_numbers: UINodeHandle,
+    sb_hud_scale: UINodeHandle,
+    /// Scale of HUD that is not applied yet and time left until it is applied.
+    pending_hud_scale: Option<(f32, f32)>,
+    dl_color_blind_mode: UINodeHandle,
+    cb_item_timers: UINodeHandle,
+    cb_death_marker: UINodeHandle,
+    cb_nameplates_through_walls: UINodeHandle,
+    cb_gore: UINodeHandle,
+    cb_clean_language: UINodeHandle,
+    content_filter: ContentFilter,
+    cb_low_effects: UINodeHandle,
+    sb_max_effects: UINodeHandle,
+    sb_effect_density: UINodeHandle,
+    sb_effect_lifetime: UINodeHandle,
+    sb_item_glow: UINodeHandle,
+    cb_precise_frame_pacing: UINodeHandle,
+    effect_limits: EffectLimits,
+    sb_corpse_lifetime: UINodeHandle,
+    sb_max_corpses: UINodeHandle,
+    corpse_limits: CorpseLimits,
+    dl_self_damage: UINodeHandle,
+    sb_self_knockback: UINodeHandle,
+    rocket_jump: RocketJump,
+    cb_killcam: UINodeHandle,
+    cb_weapon_drop: UINodeHandle,
+    sb_path_budget: UINodeHandle,
+    sb_map_vote_time: UINodeHandle,
+    cb_low_latency: UINodeHandle,
+    dl_crosshair_style: UINodeHandle,
+    sb_crosshair_red: UINodeHandle,
+    sb_crosshair_green: UINodeHandle,
+    sb_crosshair_blue: UINodeHandle,
+    sb_crosshair_thickness: UINodeHandle,
+    sb_crosshair_gap: UINodeHandle,
+    sb_crosshair_length: UINodeHandle,
+    cb_crosshair_hit_confirm: UINodeHandle,
+    sb_crosshair_hit_red: UINodeHandle,
+    sb_crosshair_hit_green: UINodeHandle,
+    sb_crosshair_hit_blue: UINodeHandle,
+    crosshair: CrosshairConfig,
+    /// Visibility check boxes of HUD elements, in the order of `HUD_ELEMENTS`.
+    cb_hud_elements: Vec<UINodeHandle>,
+    /// Anchor dropdown lists of HUD elements, in the order of `HUD_ELEMENTS`.
+    dl_hud_anchors: Vec<UINodeHandle>,
+    cb_hud_crosshair: UINodeHandle,
+    hud_layout: HudLayout,
+    dl_hud_theme: UINodeHandle,
+    /// Scroll bars of red, green and blue channels of custom theme color.
+    sb_hud_theme_color: [UINodeHandle; 3],
+    hud_theme: HudTheme,
+    cb_damage_direction: UINodeHandle,
+    cb_damage_vignette: UINodeHandle,
+    cb_hit_flash: UINodeHandle,
+    damage_feedback: DamageFeedback,
+    sb_text_scale: UINodeHandle,
+    cb_high_contrast: UINodeHandle,
+    accessibility: Accessibility,
     video_modes: Vec<VideoMode>,
     control_scheme: Rc<RefCell<ControlScheme>>,
     control_scheme_buttons: Vec<UINodeHandle>,
     active_control_button: Option<usize>,
-    sb_mouse_sens: UINodeHandle,
+    /// Whether key was just bound and its label must be updated when it types a character.
+    relabel_pending: bool,
+    sb_mouse_sens_x: UINodeHandle,
+    sb_mouse_sens_y: UINodeHandle,
+    cb_link_mouse_axes: UINodeHandle,
     cb_mouse_y_inverse: UINodeHandle,
     cb_smooth_mouse: UINodeHandle,
+    sb_mouse_smoothing: UINodeHandle,
+    cb_mouse_acceleration: UINodeHandle,
     cb_shake_camera: UINodeHandle,
+    sb_screen_shake: UINodeHandle,
+    cb_auto_switch_weapon: UINodeHandle,
+    sb_ads_sensitivity: UINodeHandle,
+    sb_head_bob: UINodeHandle,
+    cb_toggle_sprint: UINodeHandle,
+    cb_auto_reload: UINodeHandle,
+    cb_reload_on_switch: UINodeHandle,
+    sb_input_buffer: UINodeHandle,
+    cb_manual_pickup: UINodeHandle,
+    dl_pickup_switch: UINodeHandle,
     btn_reset_control_scheme: UINodeHandle,
     cb_use_hrtf: UINodeHandle,
+    cb_pause_when_unfocused: UINodeHandle,
+    sb_max_sounds: UINodeHandle,
     btn_reset_audio_settings: UINodeHandle,
+    btn_export_settings: UINodeHandle,
+    btn_import_settings: UINodeHandle,
+    /// Result of last export or import of settings file.
+    settings_file_status: UINodeHandle,
 }
 
 impl OptionsMenu {
-    pub fn new(engine: &mut GameEngine, control_scheme: Rc<RefCell<ControlScheme>>, sender: Sender<Message>) -> Self {
+    pub fn new(engine: &mut GameEngine, control_scheme: Rc<RefCell<ControlScheme>>, sender: Sender<Message>, game_settings: &Settings) -> Self {
         let video_modes: Vec<VideoMode> = engine.get_window()
             .primary_monitor()
             .video_modes()
@@ -118,13 +242,80 @@ impl OptionsMenu {
         let cb_soft_point_shadows;
         let sb_point_shadow_distance;
         let sb_spot_shadow_distance;
-        let sb_mouse_sens;
+        let cb_damage_numbers;
+        let sb_hud_scale;
+        let dl_color_blind_mode;
+        let cb_item_timers;
+        let cb_death_marker;
+        let cb_nameplates_through_walls;
+        let cb_gore;
+        let cb_clean_language;
+        let cb_low_effects;
+        let sb_max_effects;
+        let sb_effect_density;
+        let sb_effect_lifetime;
+        let sb_item_glow;
+        let cb_precise_frame_pacing;
+        let effect_limits = game_settings.effect_limits;
+        let sb_corpse_lifetime;
+        let sb_max_corpses;
+        let corpse_limits = game_settings.corpse_limits;
+        let dl_self_damage;
+        let sb_self_knockback;
+        let rocket_jump = game_settings.rocket_jump;
+        let cb_killcam;
+        let cb_weapon_drop;
+        let sb_path_budget;
+        let sb_map_vote_time;
+        let cb_low_latency;
+        let dl_crosshair_style;
+        let sb_crosshair_red;
+        let sb_crosshair_green;
+        let sb_crosshair_blue;
+        let sb_crosshair_thickness;
+        let sb_crosshair_gap;
+        let sb_crosshair_length;
+        let cb_crosshair_hit_confirm;
+        let sb_crosshair_hit_red;
+        let sb_crosshair_hit_green;
+        let sb_crosshair_hit_blue;
+        let crosshair = game_settings.crosshair;
+        let mut cb_hud_elements = Vec::new();
+        let mut dl_hud_anchors = Vec::new();
+        let cb_hud_crosshair;
+        let cb_damage_direction;
+        let cb_damage_vignette;
+        let cb_hit_flash;
+        let hud_layout = game_settings.hud_layout;
+        let dl_hud_theme;
+        let mut sb_hud_theme_color = [UINodeHandle::NONE; 3];
+        let hud_theme = game_settings.hud_theme;
+        let sb_text_scale;
+        let cb_high_contrast;
+        let accessibility = game_settings.accessibility;
+        let sb_mouse_sens_x;
+        let sb_mouse_sens_y;
+        let cb_link_mouse_axes;
         let cb_mouse_y_inverse;
         let cb_smooth_mouse;
+        let sb_mouse_smoothing;
+        let cb_mouse_acceleration;
         let cb_shake_camera;
+        let sb_screen_shake;
+        let cb_auto_switch_weapon;
+        let sb_ads_sensitivity;
+        let sb_head_bob;
+        let cb_toggle_sprint;
+        let cb_auto_reload;
+        let cb_reload_on_switch;
+        let sb_input_buffer;
+        let cb_manual_pickup;
+        let dl_pickup_switch;
         let btn_reset_control_scheme;
         let mut control_scheme_buttons = Vec::new();
         let cb_use_hrtf;
+        let cb_pause_when_unfocused;
+        let sb_max_sounds;
         let btn_reset_audio_settings;
         let tab_control = TabControlBuilder::new(WidgetBuilder::new())
             .with_tab(TabDefinition {
@@ -296,222 +487,1398 @@ impl OptionsMenu {
                                 orientation: Orientation::Horizontal,
                             });
                             sb_point_shadow_distance
-                        }))
-                        .add_row(Row::strict(200.0))
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_column(Column::strict(250.0))
-                        .add_column(Column::stretch())
-                        .build(ctx)
-                },
-            })
-            .with_tab(TabDefinition {
-                header: {
-                    TextBuilder::new(WidgetBuilder::new()
-                        .with_width(100.0)
-                        .with_height(30.0))
-                        .with_text("Sound")
-                        .build(ctx)
-                },
-                content: {
-                    GridBuilder::new(WidgetBuilder::new()
+                        })
+
+                        // Damage Numbers
+
                         .with_child(TextBuilder::new(WidgetBuilder::new()
-                            .on_row(0)
+                            .on_row(8)
                             .on_column(0)
                             .with_margin(margin))
-                            .with_text("Sound Volume")
+                            .with_text("Damage Numbers")
                             .with_vertical_text_alignment(VerticalAlignment::Center)
                             .build(ctx))
                         .with_child({
-                            sb_sound_volume = create_scroll_bar(ctx, resource_manager, ScrollBarData {
-                                min: 0.0,
-                                max: 1.0,
-                                value: 1.0,
-                                step: 0.025,
-                                row: 0,
-                                column: 1,
-                                margin,
-                                show_value: true,
-                                orientation: Orientation::Horizontal,
-                            });
-                            sb_sound_volume
+                            cb_damage_numbers = create_check_box(ctx, resource_manager, 8, 1, game_settings.damage_numbers);
+                            cb_damage_numbers
                         })
+
+                        // HUD Scale
+
                         .with_child(TextBuilder::new(WidgetBuilder::new()
-                            .on_row(1)
+                            .on_row(9)
                             .on_column(0)
                             .with_margin(margin))
-                            .with_text("Music Volume")
+                            .with_text("HUD Scale")
                             .with_vertical_text_alignment(VerticalAlignment::Center)
                             .build(ctx))
                         .with_child({
-                            sb_music_volume = create_scroll_bar(ctx, resource_manager, ScrollBarData {
-                                min: 0.0,
-                                max: 1.0,
-                                value: 0.0,
-                                step: 0.025,
-                                row: 1,
+                            sb_hud_scale = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.5,
+                                max: 2.0,
+                                value: game_settings.hud_scale,
+                                step: 0.1,
+                                row: 9,
                                 column: 1,
                                 margin,
                                 show_value: true,
                                 orientation: Orientation::Horizontal,
                             });
-                            sb_music_volume
+                            sb_hud_scale
                         })
+
+                        // Colorblind Mode
+
                         .with_child(TextBuilder::new(WidgetBuilder::new()
-                            .on_row(2)
+                            .on_row(10)
                             .on_column(0)
                             .with_margin(margin))
-                            .with_text("Use HRTF")
+                            .with_text("Colorblind Mode")
                             .with_vertical_text_alignment(VerticalAlignment::Center)
                             .build(ctx))
                         .with_child({
-                            cb_use_hrtf = create_check_box(ctx, resource_manager, 2, 1, true);
-                            cb_use_hrtf
-                        })
-                        .with_child({
-                            btn_reset_audio_settings = ButtonBuilder::new(WidgetBuilder::new()
-                                .on_row(3)
+                            dl_color_blind_mode = DropdownListBuilder::new(WidgetBuilder::new()
+                                .on_row(10)
+                                .on_column(1)
                                 .with_margin(margin))
-                                .with_text("Reset")
+                                .with_items({
+                                    let mut items = Vec::new();
+                                    for (name, _) in COLOR_BLIND_MODES.iter() {
+                                        let item = DecoratorBuilder::new(
+                                            BorderBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_height(30.0)
+                                                    .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                        .with_vertical_alignment(VerticalAlignment::Center))
+                                                        .with_text(name)
+                                                        .build(ctx))))
+                                            .build(ctx);
+                                        items.push(item);
+                                    }
+                                    items
+                                })
+                                .with_selected(game_settings.color_blind_mode.id() as usize)
                                 .build(ctx);
-                            btn_reset_audio_settings
-                        }))
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_column(Column::strict(250.0))
-                        .add_column(Column::stretch())
-                        .build(ctx)
-                },
-            })
-            .with_tab(TabDefinition {
-                header: {
-                    TextBuilder::new(WidgetBuilder::new()
-                        .with_width(100.0)
-                        .with_height(30.0))
-                        .with_text("Controls")
-                        .build(ctx)
-                },
-                content: {
-                    let mut children = Vec::new();
+                            dl_color_blind_mode
+                        })
 
-                    for (row, button) in control_scheme.borrow().buttons().iter().enumerate() {
-                        // Offset by total amount of rows that goes before
-                        let row = row + 4;
+                        // Item Respawn Timers
 
-                        let text = TextBuilder::new(WidgetBuilder::new()
-                            .on_row(row)
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(11)
                             .on_column(0)
                             .with_margin(margin))
-                            .with_text(button.description.as_str())
+                            .with_text("Item Respawn Timers")
                             .with_vertical_text_alignment(VerticalAlignment::Center)
-                            .build(ctx);
-                        children.push(text);
+                            .build(ctx))
+                        .with_child({
+                            cb_item_timers = create_check_box(ctx, resource_manager, 11, 1, game_settings.show_item_timers);
+                            cb_item_timers
+                        })
 
-                        let button = ButtonBuilder::new(WidgetBuilder::new()
-                            .with_margin(margin)
-                            .on_row(row)
-                            .on_column(1))
-                            .with_text(button.button.name())
-                            .build(ctx);
-                        children.push(button);
-                        control_scheme_buttons.push(button);
-                    }
+                        // Content Filter
 
-                    GridBuilder::new(WidgetBuilder::new()
                         .with_child(TextBuilder::new(WidgetBuilder::new()
-                            .on_row(0)
+                            .on_row(12)
                             .on_column(0)
                             .with_margin(margin))
-                            .with_text("Mouse Sensitivity")
+                            .with_text("Show Blood")
                             .with_vertical_text_alignment(VerticalAlignment::Center)
                             .build(ctx))
                         .with_child({
-                            sb_mouse_sens = create_scroll_bar(ctx, resource_manager, ScrollBarData {
-                                min: 0.05,
-                                max: 2.0,
-                                value: control_scheme.borrow().mouse_sens,
+                            cb_gore = create_check_box(ctx, resource_manager, 12, 1, game_settings.content_filter.gore);
+                            cb_gore
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(13)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Language Filter")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_clean_language = create_check_box(ctx, resource_manager, 13, 1, game_settings.content_filter.clean_language);
+                            cb_clean_language
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(14)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Nameplates Through Walls")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_nameplates_through_walls = create_check_box(ctx, resource_manager, 14, 1, game_settings.nameplates_through_walls);
+                            cb_nameplates_through_walls
+                        })
+
+                        // Effect Limits
+
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(15)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Low Effects")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_low_effects = create_check_box(ctx, resource_manager, 15, 1, effect_limits.is_low());
+                            cb_low_effects
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(16)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Max Effects")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_max_effects = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: MAX_EFFECTS_RANGE.0 as f32,
+                                max: MAX_EFFECTS_RANGE.1 as f32,
+                                value: effect_limits.max_effects as f32,
+                                step: 8.0,
+                                row: 16,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_max_effects
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(17)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Effect Density")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_effect_density = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.1,
+                                max: 1.0,
+                                value: effect_limits.particle_density,
                                 step: 0.05,
-                                row: 0,
+                                row: 17,
                                 column: 1,
                                 margin,
                                 show_value: true,
                                 orientation: Orientation::Horizontal,
                             });
-                            sb_mouse_sens
+                            sb_effect_density
                         })
                         .with_child(TextBuilder::new(WidgetBuilder::new()
-                            .on_row(1)
+                            .on_row(18)
                             .on_column(0)
                             .with_margin(margin))
-                            .with_text("Inverse Mouse Y")
+                            .with_text("Effect Lifetime")
                             .with_vertical_text_alignment(VerticalAlignment::Center)
                             .build(ctx))
                         .with_child({
-                            cb_mouse_y_inverse = create_check_box(ctx, resource_manager, 1, 1, control_scheme.borrow().mouse_y_inverse);
-                            cb_mouse_y_inverse
+                            sb_effect_lifetime = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.1,
+                                max: 1.0,
+                                value: effect_limits.lifetime_scale,
+                                step: 0.05,
+                                row: 18,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_effect_lifetime
                         })
                         .with_child(TextBuilder::new(WidgetBuilder::new()
-                            .on_row(2)
+                            .on_row(19)
                             .on_column(0)
                             .with_margin(margin))
-                            .with_text("Smooth Mouse")
+                            .with_text("Item Glow")
                             .with_vertical_text_alignment(VerticalAlignment::Center)
                             .build(ctx))
                         .with_child({
-                            cb_smooth_mouse = create_check_box(ctx, resource_manager, 2, 1, control_scheme.borrow().smooth_mouse);
-                            cb_smooth_mouse
+                            sb_item_glow = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 1.0,
+                                value: game_settings.item_glow,
+                                step: 0.05,
+                                row: 19,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_item_glow
                         })
                         .with_child(TextBuilder::new(WidgetBuilder::new()
-                            .on_row(3)
+                            .on_row(20)
                             .on_column(0)
                             .with_margin(margin))
-                            .with_text("Shake Camera")
+                            .with_text("Precise Frame Pacing")
                             .with_vertical_text_alignment(VerticalAlignment::Center)
                             .build(ctx))
                         .with_child({
-                            cb_shake_camera = create_check_box(ctx, resource_manager, 3, 1, control_scheme.borrow().shake_camera);
-                            cb_shake_camera
+                            cb_precise_frame_pacing = create_check_box(ctx, resource_manager, 20, 1, game_settings.precise_frame_pacing);
+                            cb_precise_frame_pacing
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(21)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Corpse Lifetime")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_corpse_lifetime = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: MAX_CORPSE_LIFETIME,
+                                value: corpse_limits.lifetime,
+                                step: 5.0,
+                                row: 21,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_corpse_lifetime
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(22)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Max Corpses")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_max_corpses = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: MAX_CORPSES_RANGE.0 as f32,
+                                max: MAX_CORPSES_RANGE.1 as f32,
+                                value: corpse_limits.max_corpses as f32,
+                                step: 1.0,
+                                row: 22,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_max_corpses
                         })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(23)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Rocket Jump")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
                         .with_child({
-                            btn_reset_control_scheme = ButtonBuilder::new(WidgetBuilder::new()
-                                .on_row(4 + control_scheme.borrow().buttons().len())
+                            dl_self_damage = DropdownListBuilder::new(WidgetBuilder::new()
+                                .on_row(23)
+                                .on_column(1)
                                 .with_margin(margin))
-                                .with_text("Reset")
+                                .with_items({
+                                    let mut items = Vec::new();
+                                    for (name, _) in SELF_DAMAGE_MODES.iter() {
+                                        let item = DecoratorBuilder::new(
+                                            BorderBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_height(30.0)
+                                                    .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                        .with_vertical_alignment(VerticalAlignment::Center))
+                                                        .with_text(name)
+                                                        .build(ctx))))
+                                            .build(ctx);
+                                        items.push(item);
+                                    }
+                                    items
+                                })
+                                .with_selected(rocket_jump.self_damage.id() as usize)
                                 .build(ctx);
-                            btn_reset_control_scheme
+                            dl_self_damage
                         })
-                        .with_children(&children))
-                        .add_column(Column::strict(250.0))
-                        .add_column(Column::stretch())
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_row(common_row)
-                        .add_rows((0..control_scheme.borrow().buttons().len()).map(|_| common_row).collect())
-                        .add_row(common_row)
-                        .build(ctx)
-                },
-            })
-            .build(ctx);
-
-        let options_window: UINodeHandle = WindowBuilder::new(WidgetBuilder::new()
-            .with_width(500.0))
-            .with_title(WindowTitle::text("Options"))
-            .open(false)
-            .with_content(tab_control)
-            .build(ctx);
-
-        Self {
-            sender,
-            window: options_window,
-            sb_sound_volume,
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(24)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Self Knockback")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_self_knockback = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: MAX_SELF_KNOCKBACK,
+                                value: rocket_jump.self_knockback,
+                                step: 0.1,
+                                row: 24,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_self_knockback
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(25)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Killcam")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_killcam = create_check_box(ctx, resource_manager, 25, 1, game_settings.killcam);
+                            cb_killcam
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(26)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Drop Weapons On Death")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_weapon_drop = create_check_box(ctx, resource_manager, 26, 1, game_settings.weapon_drop);
+                            cb_weapon_drop
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(27)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Bot Paths Per Frame")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_path_budget = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: PATH_BUDGET_RANGE.0 as f32,
+                                max: PATH_BUDGET_RANGE.1 as f32,
+                                value: game_settings.path_budget as f32,
+                                step: 1.0,
+                                row: 27,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_path_budget
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(28)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Low Latency Mode")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_low_latency = create_check_box(ctx, resource_manager, 28, 1, game_settings.low_latency);
+                            cb_low_latency
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(29)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Mark Place Of Death")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_death_marker = create_check_box(ctx, resource_manager, 29, 1, game_settings.death_marker);
+                            cb_death_marker
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(30)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Next Match Vote Time (0 - off)")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_map_vote_time = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: MAX_VOTE_TIME,
+                                value: game_settings.map_vote_time,
+                                step: 5.0,
+                                row: 30,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_map_vote_time
+                        }))
+                        .add_row(Row::strict(200.0))
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_column(Column::strict(250.0))
+                        .add_column(Column::stretch())
+                        .build(ctx)
+                },
+            })
+            .with_tab(TabDefinition {
+                header: {
+                    TextBuilder::new(WidgetBuilder::new()
+                        .with_width(100.0)
+                        .with_height(30.0))
+                        .with_text("Crosshair")
+                        .build(ctx)
+                },
+                content: {
+                    GridBuilder::new(WidgetBuilder::new()
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(0)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Style")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            dl_crosshair_style = DropdownListBuilder::new(WidgetBuilder::new()
+                                .on_row(0)
+                                .on_column(1)
+                                .with_margin(margin))
+                                .with_items({
+                                    let mut items = Vec::new();
+                                    for (name, _) in CROSSHAIR_STYLES.iter() {
+                                        let item = DecoratorBuilder::new(
+                                            BorderBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_height(30.0)
+                                                    .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                        .with_vertical_alignment(VerticalAlignment::Center))
+                                                        .with_text(name)
+                                                        .build(ctx))))
+                                            .build(ctx);
+                                        items.push(item);
+                                    }
+                                    items
+                                })
+                                .with_selected(crosshair.style.id() as usize)
+                                .build(ctx);
+                            dl_crosshair_style
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(1)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Red")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_red = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 255.0,
+                                value: crosshair.color.r as f32,
+                                step: 1.0,
+                                row: 1,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_red
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(2)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Green")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_green = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 255.0,
+                                value: crosshair.color.g as f32,
+                                step: 1.0,
+                                row: 2,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_green
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(3)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Blue")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_blue = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 255.0,
+                                value: crosshair.color.b as f32,
+                                step: 1.0,
+                                row: 3,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_blue
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(4)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Thickness")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_thickness = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 1.0,
+                                max: 8.0,
+                                value: crosshair.thickness,
+                                step: 1.0,
+                                row: 4,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_thickness
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(5)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Gap")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_gap = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 30.0,
+                                value: crosshair.gap,
+                                step: 1.0,
+                                row: 5,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_gap
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(6)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Length")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_length = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 2.0,
+                                max: 30.0,
+                                value: crosshair.length,
+                                step: 1.0,
+                                row: 6,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_length
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(7)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Hit Confirmation")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_crosshair_hit_confirm = create_check_box(ctx, resource_manager, 7, 1, crosshair.hit_confirm);
+                            cb_crosshair_hit_confirm
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(8)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Hit Red")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_hit_red = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 255.0,
+                                value: crosshair.hit_confirm_color.r as f32,
+                                step: 1.0,
+                                row: 8,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_hit_red
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(9)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Hit Green")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_hit_green = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 255.0,
+                                value: crosshair.hit_confirm_color.g as f32,
+                                step: 1.0,
+                                row: 9,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_hit_green
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(10)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Hit Blue")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_crosshair_hit_blue = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 255.0,
+                                value: crosshair.hit_confirm_color.b as f32,
+                                step: 1.0,
+                                row: 10,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_crosshair_hit_blue
+                        }))
+                        .add_rows((0..11).map(|_| common_row).collect())
+                        .add_column(Column::strict(250.0))
+                        .add_column(Column::stretch())
+                        .build(ctx)
+                },
+            })
+            .with_tab(TabDefinition {
+                header: {
+                    TextBuilder::new(WidgetBuilder::new()
+                        .with_width(100.0)
+                        .with_height(30.0))
+                        .with_text("HUD")
+                        .build(ctx)
+                },
+                content: {
+                    let mut grid_builder = WidgetBuilder::new();
+                    for (row, (name, element)) in HUD_ELEMENTS.iter().enumerate() {
+                        let element_layout = hud_layout.element(*element);
+                        let check_box = create_check_box(ctx, resource_manager, row, 1, element_layout.visible);
+                        let anchor = DropdownListBuilder::new(WidgetBuilder::new()
+                            .on_row(row)
+                            .on_column(2)
+                            .with_margin(margin))
+                            .with_items({
+                                let mut items = Vec::new();
+                                for (name, _) in HUD_ANCHORS.iter() {
+                                    let item = DecoratorBuilder::new(
+                                        BorderBuilder::new(
+                                            WidgetBuilder::new()
+                                                .with_height(30.0)
+                                                .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                    .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                    .with_vertical_alignment(VerticalAlignment::Center))
+                                                    .with_text(name)
+                                                    .build(ctx))))
+                                        .build(ctx);
+                                    items.push(item);
+                                }
+                                items
+                            })
+                            .with_selected(element_layout.anchor.id() as usize)
+                            .build(ctx);
+                        cb_hud_elements.push(check_box);
+                        dl_hud_anchors.push(anchor);
+                        grid_builder = grid_builder
+                            .with_child(TextBuilder::new(WidgetBuilder::new()
+                                .on_row(row)
+                                .on_column(0)
+                                .with_margin(margin))
+                                .with_text(name)
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx))
+                            .with_child(check_box)
+                            .with_child(anchor);
+                    }
+                    let crosshair_row = HUD_ELEMENTS.len();
+                    cb_hud_crosshair = create_check_box(ctx, resource_manager, crosshair_row, 1, hud_layout.crosshair);
+                    let feedback = game_settings.damage_feedback;
+                    cb_damage_direction = create_check_box(ctx, resource_manager, crosshair_row + 1, 1, feedback.direction_indicator);
+                    cb_damage_vignette = create_check_box(ctx, resource_manager, crosshair_row + 2, 1, feedback.vignette);
+                    cb_hit_flash = create_check_box(ctx, resource_manager, crosshair_row + 3, 1, feedback.hit_flash);
+                    let theme_row = crosshair_row + 4;
+                    dl_hud_theme = DropdownListBuilder::new(WidgetBuilder::new()
+                        .on_row(theme_row)
+                        .on_column(2)
+                        .with_margin(margin))
+                        .with_items({
+                            let mut items = Vec::new();
+                            for (name, _) in HUD_THEME_PRESETS.iter() {
+                                let item = DecoratorBuilder::new(
+                                    BorderBuilder::new(
+                                        WidgetBuilder::new()
+                                            .with_height(30.0)
+                                            .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                .with_vertical_alignment(VerticalAlignment::Center))
+                                                .with_text(name)
+                                                .build(ctx))))
+                                    .build(ctx);
+                                items.push(item);
+                            }
+                            items
+                        })
+                        .with_selected(hud_theme.preset.id() as usize)
+                        .build(ctx);
+                    let custom = hud_theme.custom;
+                    for (i, (sb, value)) in sb_hud_theme_color.iter_mut().zip([custom.r, custom.g, custom.b].iter()).enumerate() {
+                        *sb = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                            min: 0.0,
+                            max: 255.0,
+                            value: *value as f32,
+                            step: 1.0,
+                            row: theme_row + 1 + i,
+                            column: 2,
+                            margin,
+                            show_value: true,
+                            orientation: Orientation::Horizontal,
+                        });
+                        grid_builder = grid_builder.with_child(*sb);
+                    }
+                    for (offset, name) in ["Crosshair", "Damage Direction", "Damage Vignette", "Hit Flash", "Theme"].iter()
+                        .chain(HUD_THEME_CHANNELS.iter())
+                        .enumerate() {
+                        grid_builder = grid_builder
+                            .with_child(TextBuilder::new(WidgetBuilder::new()
+                                .on_row(crosshair_row + offset)
+                                .on_column(0)
+                                .with_margin(margin))
+                                .with_text(name)
+                                .with_vertical_text_alignment(VerticalAlignment::Center)
+                                .build(ctx));
+                    }
+                    GridBuilder::new(grid_builder
+                        .with_child(cb_hud_crosshair)
+                        .with_child(cb_damage_direction)
+                        .with_child(cb_damage_vignette)
+                        .with_child(cb_hit_flash)
+                        .with_child(dl_hud_theme))
+                        .add_rows((0..=theme_row + 3).map(|_| common_row).collect())
+                        .add_column(Column::strict(150.0))
+                        .add_column(Column::strict(40.0))
+                        .add_column(Column::stretch())
+                        .build(ctx)
+                },
+            })
+            .with_tab(TabDefinition {
+                header: {
+                    TextBuilder::new(WidgetBuilder::new()
+                        .with_width(100.0)
+                        .with_height(30.0))
+                        .with_text("Accessibility")
+                        .build(ctx)
+                },
+                content: {
+                    GridBuilder::new(WidgetBuilder::new()
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(0)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Text Scale")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_text_scale = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: TEXT_SCALE_RANGE.0,
+                                max: TEXT_SCALE_RANGE.1,
+                                value: accessibility.text_scale,
+                                step: 0.05,
+                                row: 0,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_text_scale
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(1)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("High Contrast")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_high_contrast = create_check_box(ctx, resource_manager, 1, 1, accessibility.high_contrast);
+                            cb_high_contrast
+                        }))
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_column(Column::strict(250.0))
+                        .add_column(Column::stretch())
+                        .build(ctx)
+                },
+            })
+            .with_tab(TabDefinition {
+                header: {
+                    TextBuilder::new(WidgetBuilder::new()
+                        .with_width(100.0)
+                        .with_height(30.0))
+                        .with_text("Sound")
+                        .build(ctx)
+                },
+                content: {
+                    GridBuilder::new(WidgetBuilder::new()
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(0)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Sound Volume")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_sound_volume = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 1.0,
+                                value: 1.0,
+                                step: 0.025,
+                                row: 0,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_sound_volume
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(1)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Music Volume")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_music_volume = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 1.0,
+                                value: 0.0,
+                                step: 0.025,
+                                row: 1,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_music_volume
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(2)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Use HRTF")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_use_hrtf = create_check_box(ctx, resource_manager, 2, 1, true);
+                            cb_use_hrtf
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(3)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Pause Game When Unfocused")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_pause_when_unfocused = create_check_box(ctx, resource_manager, 3, 1, game_settings.pause_when_unfocused);
+                            cb_pause_when_unfocused
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(4)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Max Simultaneous Sounds")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_max_sounds = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: MAX_SOUNDS_RANGE.0 as f32,
+                                max: MAX_SOUNDS_RANGE.1 as f32,
+                                value: game_settings.max_sounds as f32,
+                                step: 1.0,
+                                row: 4,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_max_sounds
+                        })
+                        .with_child({
+                            btn_reset_audio_settings = ButtonBuilder::new(WidgetBuilder::new()
+                                .on_row(5)
+                                .with_margin(margin))
+                                .with_text("Reset")
+                                .build(ctx);
+                            btn_reset_audio_settings
+                        }))
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_column(Column::strict(250.0))
+                        .add_column(Column::stretch())
+                        .build(ctx)
+                },
+            })
+            .with_tab(TabDefinition {
+                header: {
+                    TextBuilder::new(WidgetBuilder::new()
+                        .with_width(100.0)
+                        .with_height(30.0))
+                        .with_text("Controls")
+                        .build(ctx)
+                },
+                content: {
+                    let mut children = Vec::new();
+
+                    for (row, button) in control_scheme.borrow().buttons().iter().enumerate() {
+                        // Offset by total amount of rows that goes before
+                        let row = row + 18;
+
+                        let text = TextBuilder::new(WidgetBuilder::new()
+                            .on_row(row)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text(button.description.as_str())
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx);
+                        children.push(text);
+
+                        let button = ButtonBuilder::new(WidgetBuilder::new()
+                            .with_margin(margin)
+                            .on_row(row)
+                            .on_column(1))
+                            .with_text(&button.button.label())
+                            .build(ctx);
+                        children.push(button);
+                        control_scheme_buttons.push(button);
+                    }
+
+                    GridBuilder::new(WidgetBuilder::new()
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(0)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Horizontal Sensitivity")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_mouse_sens_x = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.05,
+                                max: 2.0,
+                                value: control_scheme.borrow().mouse_sens_x,
+                                step: 0.05,
+                                row: 0,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_mouse_sens_x
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(1)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Vertical Sensitivity")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_mouse_sens_y = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.05,
+                                max: 2.0,
+                                value: control_scheme.borrow().mouse_sens_y,
+                                step: 0.05,
+                                row: 1,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_mouse_sens_y
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(2)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Link Sensitivity Axes")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_link_mouse_axes = create_check_box(ctx, resource_manager, 2, 1, control_scheme.borrow().link_mouse_axes);
+                            cb_link_mouse_axes
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(3)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Inverse Mouse Y")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_mouse_y_inverse = create_check_box(ctx, resource_manager, 3, 1, control_scheme.borrow().mouse_y_inverse);
+                            cb_mouse_y_inverse
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(4)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Smooth Mouse")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_smooth_mouse = create_check_box(ctx, resource_manager, 4, 1, control_scheme.borrow().smooth_mouse);
+                            cb_smooth_mouse
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(5)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Shake Camera")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_shake_camera = create_check_box(ctx, resource_manager, 5, 1, control_scheme.borrow().shake_camera);
+                            cb_shake_camera
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(6)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Mouse Smoothing")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_mouse_smoothing = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 0.95,
+                                value: control_scheme.borrow().mouse_smoothing,
+                                step: 0.05,
+                                row: 6,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_mouse_smoothing
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(7)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Mouse Acceleration")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_mouse_acceleration = create_check_box(ctx, resource_manager, 7, 1, control_scheme.borrow().mouse_acceleration);
+                            cb_mouse_acceleration
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(8)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Screen Shake")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_screen_shake = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 2.0,
+                                value: control_scheme.borrow().screen_shake,
+                                step: 0.1,
+                                row: 8,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_screen_shake
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(9)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Auto Switch Empty Weapon")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_auto_switch_weapon = create_check_box(ctx, resource_manager, 9, 1, control_scheme.borrow().auto_switch_weapon);
+                            cb_auto_switch_weapon
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(10)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("ADS Sensitivity")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_ads_sensitivity = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.1,
+                                max: 1.0,
+                                value: control_scheme.borrow().ads_sensitivity,
+                                step: 0.05,
+                                row: 10,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_ads_sensitivity
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(11)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Toggle Sprint")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_toggle_sprint = create_check_box(ctx, resource_manager, 11, 1, control_scheme.borrow().toggle_sprint);
+                            cb_toggle_sprint
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(12)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Auto Reload")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_auto_reload = create_check_box(ctx, resource_manager, 12, 1, control_scheme.borrow().auto_reload);
+                            cb_auto_reload
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(13)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Manual Pickup")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_manual_pickup = create_check_box(ctx, resource_manager, 13, 1, control_scheme.borrow().manual_pickup);
+                            cb_manual_pickup
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(14)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Head Bob")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_head_bob = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: 2.0,
+                                value: control_scheme.borrow().head_bob,
+                                step: 0.1,
+                                row: 14,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_head_bob
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(15)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Switch To Picked Up Weapon")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            dl_pickup_switch = DropdownListBuilder::new(WidgetBuilder::new()
+                                .on_row(15)
+                                .on_column(1)
+                                .with_margin(margin))
+                                .with_items({
+                                    let mut items = Vec::new();
+                                    for (name, _) in PICKUP_SWITCH_MODES.iter() {
+                                        let item = DecoratorBuilder::new(
+                                            BorderBuilder::new(
+                                                WidgetBuilder::new()
+                                                    .with_height(30.0)
+                                                    .with_child(TextBuilder::new(WidgetBuilder::new()
+                                                        .with_horizontal_alignment(HorizontalAlignment::Center)
+                                                        .with_vertical_alignment(VerticalAlignment::Center))
+                                                        .with_text(name)
+                                                        .build(ctx))))
+                                            .build(ctx);
+                                        items.push(item);
+                                    }
+                                    items
+                                })
+                                .with_selected(control_scheme.borrow().pickup_switch.id() as usize)
+                                .build(ctx);
+                            dl_pickup_switch
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(16)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Reload On Switch")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            cb_reload_on_switch = create_check_box(ctx, resource_manager, 16, 1, control_scheme.borrow().reload_on_switch);
+                            cb_reload_on_switch
+                        })
+                        .with_child(TextBuilder::new(WidgetBuilder::new()
+                            .on_row(17)
+                            .on_column(0)
+                            .with_margin(margin))
+                            .with_text("Input Buffer (Seconds)")
+                            .with_vertical_text_alignment(VerticalAlignment::Center)
+                            .build(ctx))
+                        .with_child({
+                            sb_input_buffer = create_scroll_bar(ctx, resource_manager, ScrollBarData {
+                                min: 0.0,
+                                max: MAX_INPUT_BUFFER,
+                                value: control_scheme.borrow().input_buffer,
+                                step: 0.02,
+                                row: 17,
+                                column: 1,
+                                margin,
+                                show_value: true,
+                                orientation: Orientation::Horizontal,
+                            });
+                            sb_input_buffer
+                        })
+                        .with_child({
+                            btn_reset_control_scheme = ButtonBuilder::new(WidgetBuilder::new()
+                                .on_row(18 + control_scheme.borrow().buttons().len())
+                                .with_margin(margin))
+                                .with_text("Reset")
+                                .build(ctx);
+                            btn_reset_control_scheme
+                        })
+                        .with_children(&children))
+                        .add_column(Column::strict(250.0))
+                        .add_column(Column::stretch())
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_row(common_row)
+                        .add_rows((0..control_scheme.borrow().buttons().len()).map(|_| common_row).collect())
+                        .add_row(common_row)
+                        .build(ctx)
+                },
+            })
+            .build(ctx);
+
+        let btn_export_settings;
+        let btn_import_settings;
+        let settings_file_status;
+        let content = GridBuilder::new(WidgetBuilder::new()
+            .with_child(tab_control)
+            .with_child(StackPanelBuilder::new(WidgetBuilder::new()
+                .on_row(1)
+                .with_child({
+                    btn_export_settings = ButtonBuilder::new(WidgetBuilder::new()
+                        .with_width(150.0)
+                        .with_margin(margin))
+                        .with_text("Export Settings")
+                        .build(ctx);
+                    btn_export_settings
+                })
+                .with_child({
+                    btn_import_settings = ButtonBuilder::new(WidgetBuilder::new()
+                        .with_width(150.0)
+                        .with_margin(margin))
+                        .with_text("Import Settings")
+                        .build(ctx);
+                    btn_import_settings
+                })
+                .with_child({
+                    settings_file_status = TextBuilder::new(WidgetBuilder::new()
+                        .with_margin(margin))
+                        .with_vertical_text_alignment(VerticalAlignment::Center)
+                        .build(ctx);
+                    settings_file_status
+                }))
+                .with_orientation(Orientation::Horizontal)
+                .build(ctx)))
+            .add_row(Row::stretch())
+            .add_row(common_row)
+            .add_column(Column::stretch())
+            .build(ctx);
+
+        let options_window: UINodeHandle = WindowBuilder::new(WidgetBuilder::new()
+            .with_width(500.0))
+            .with_title(WindowTitle::text("Options"))
+            .open(false)
+            .with_content(content)
+            .build(ctx);
+
+        Self {
+            sender,
+            window: options_window,
+            sb_sound_volume,
             sb_music_volume,
             lb_video_modes,
             cb_fullscreen,
@@ -521,17 +1888,91 @@ impl OptionsMenu {
             cb_soft_point_shadows,
             sb_point_shadow_distance,
             sb_spot_shadow_distance,
+            cb_damage_numbers,
+            sb_hud_scale,
+            pending_hud_scale: None,
+            dl_color_blind_mode,
+            cb_item_timers,
+            cb_death_marker,
+            cb_nameplates_through_walls,
+            cb_gore,
+            cb_clean_language,
+            content_filter: game_settings.content_filter,
+            cb_low_effects,
+            sb_max_effects,
+            sb_effect_density,
+            sb_effect_lifetime,
+            sb_item_glow,
+            cb_precise_frame_pacing,
+            effect_limits,
+            sb_corpse_lifetime,
+            sb_max_corpses,
+            corpse_limits,
+            dl_self_damage,
+            sb_self_knockback,
+            rocket_jump,
+            cb_killcam,
+            cb_weapon_drop,
+            sb_path_budget,
+            sb_map_vote_time,
+            cb_low_latency,
+            dl_crosshair_style,
+            sb_crosshair_red,
+            sb_crosshair_green,
+            sb_crosshair_blue,
+            sb_crosshair_thickness,
+            sb_crosshair_gap,
+            sb_crosshair_length,
+            cb_crosshair_hit_confirm,
+            sb_crosshair_hit_red,
+            sb_crosshair_hit_green,
+            sb_crosshair_hit_blue,
+            crosshair,
+            cb_hud_elements,
+            dl_hud_anchors,
+            cb_hud_crosshair,
+            hud_layout,
+            dl_hud_theme,
+            sb_hud_theme_color,
+            hud_theme,
+            cb_damage_direction,
+            cb_damage_vignette,
+            cb_hit_flash,
+            damage_feedback: game_settings.damage_feedback,
+            sb_text_scale,
+            cb_high_contrast,
+            accessibility,
             video_modes,
             control_scheme,
             control_scheme_buttons,
             active_control_button: None,
-            sb_mouse_sens,
+            relabel_pending: false,
+            sb_mouse_sens_x,
+            sb_mouse_sens_y,
+            cb_link_mouse_axes,
             cb_mouse_y_inverse,
             cb_smooth_mouse,
+            sb_mouse_smoothing,
+            cb_mouse_acceleration,
             cb_shake_camera,
+            sb_screen_shake,
+            cb_auto_switch_weapon,
+            sb_ads_sensitivity,
+            sb_head_bob,
+            cb_toggle_sprint,
+            cb_auto_reload,
+            cb_reload_on_switch,
+            sb_input_buffer,
+            cb_manual_pickup,
+            dl_pickup_switch,
             btn_reset_control_scheme,
             cb_use_hrtf,
+            cb_pause_when_unfocused,
+            sb_max_sounds,
             btn_reset_audio_settings,
+            btn_export_settings,
+            btn_import_settings,
+            settings_file_status,
         }
     }
 
@@ -548,8 +1989,15 @@ impl OptionsMenu {
         sync_check_box(self.cb_point_shadows, settings.point_shadows_enabled);
         sync_check_box(self.cb_soft_point_shadows, settings.point_soft_shadows);
         sync_check_box(self.cb_mouse_y_inverse, control_scheme.mouse_y_inverse);
+        sync_check_box(self.cb_link_mouse_axes, control_scheme.link_mouse_axes);
         sync_check_box(self.cb_smooth_mouse, control_scheme.smooth_mouse);
+        sync_check_box(self.cb_mouse_acceleration, control_scheme.mouse_acceleration);
         sync_check_box(self.cb_shake_camera, control_scheme.shake_camera);
+        sync_check_box(self.cb_auto_switch_weapon, control_scheme.auto_switch_weapon);
+        sync_check_box(self.cb_toggle_sprint, control_scheme.toggle_sprint);
+        sync_check_box(self.cb_auto_reload, control_scheme.auto_reload);
+        sync_check_box(self.cb_reload_on_switch, control_scheme.reload_on_switch);
+        sync_check_box(self.cb_manual_pickup, control_scheme.manual_pickup);
         let is_hrtf = if let rg3d::sound::renderer::Renderer::HrtfRenderer(_) = engine.sound_context.lock().unwrap().renderer() {
             true
         } else {
@@ -562,16 +2010,125 @@ impl OptionsMenu {
         };
         sync_scroll_bar(self.sb_point_shadow_distance, settings.point_shadows_distance);
         sync_scroll_bar(self.sb_spot_shadow_distance, settings.spot_shadows_distance);
-        sync_scroll_bar(self.sb_mouse_sens, control_scheme.mouse_sens);
+        sync_scroll_bar(self.sb_mouse_sens_x, control_scheme.mouse_sens_x);
+        sync_scroll_bar(self.sb_mouse_sens_y, control_scheme.mouse_sens_y);
+        sync_scroll_bar(self.sb_mouse_smoothing, control_scheme.mouse_smoothing);
+        sync_scroll_bar(self.sb_screen_shake, control_scheme.screen_shake);
+        sync_scroll_bar(self.sb_ads_sensitivity, control_scheme.ads_sensitivity);
+        sync_scroll_bar(self.sb_head_bob, control_scheme.head_bob);
+        sync_scroll_bar(self.sb_input_buffer, control_scheme.input_buffer);
         sync_scroll_bar(self.sb_sound_volume, engine.sound_context.lock().unwrap().master_gain());
+        ui.send_message(DropdownListMessage::selection(self.dl_pickup_switch, Some(control_scheme.pickup_switch.id() as usize)));
+
+        self.sync_button_labels(ui);
+    }
 
+    fn sync_button_labels(&self, ui: &mut Gui) {
         for (btn, def) in self.control_scheme_buttons.iter().zip(self.control_scheme.borrow().buttons().iter()) {
             if let UINode::Button(button) = ui.node(*btn) {
-                ui.send_message(TextMessage::text(button.content(), def.button.name().to_owned()));
+                ui.send_message(TextMessage::text(button.content(), def.button.label()));
+            }
+        }
+    }
+
+    /// Syncs widgets and local copies of settings with given settings, used after settings
+    /// were imported from a file. Control scheme must be already updated.
+    pub fn sync_to_settings(&mut self, engine: &mut GameEngine, settings: &Settings) {
+        self.crosshair = settings.crosshair;
+        self.hud_layout = settings.hud_layout;
+        self.hud_theme = settings.hud_theme;
+        self.content_filter = settings.content_filter;
+        self.effect_limits = settings.effect_limits;
+        self.corpse_limits = settings.corpse_limits;
+        self.rocket_jump = settings.rocket_jump;
+        self.damage_feedback = settings.damage_feedback;
+        self.accessibility = settings.accessibility;
+
+        let ui = &mut engine.user_interface;
+        let sync_check_box = |handle: UINodeHandle, value: bool| {
+            ui.send_message(CheckBoxMessage::check(handle, Some(value)));
+        };
+        sync_check_box(self.cb_item_timers, settings.show_item_timers);
+        sync_check_box(self.cb_death_marker, settings.death_marker);
+        sync_check_box(self.cb_damage_numbers, settings.damage_numbers);
+        sync_check_box(self.cb_nameplates_through_walls, settings.nameplates_through_walls);
+        sync_check_box(self.cb_killcam, settings.killcam);
+        sync_check_box(self.cb_weapon_drop, settings.weapon_drop);
+        sync_check_box(self.cb_pause_when_unfocused, settings.pause_when_unfocused);
+        sync_check_box(self.cb_precise_frame_pacing, settings.precise_frame_pacing);
+        sync_check_box(self.cb_low_latency, settings.low_latency);
+        sync_check_box(self.cb_gore, settings.content_filter.gore);
+        sync_check_box(self.cb_clean_language, settings.content_filter.clean_language);
+        sync_check_box(self.cb_low_effects, settings.effect_limits.is_low());
+        sync_check_box(self.cb_hud_crosshair, settings.hud_layout.crosshair);
+        sync_check_box(self.cb_damage_direction, settings.damage_feedback.direction_indicator);
+        sync_check_box(self.cb_damage_vignette, settings.damage_feedback.vignette);
+        sync_check_box(self.cb_hit_flash, settings.damage_feedback.hit_flash);
+        sync_check_box(self.cb_high_contrast, settings.accessibility.high_contrast);
+        sync_check_box(self.cb_crosshair_hit_confirm, settings.crosshair.hit_confirm);
+        for (&check_box, (_, element)) in self.cb_hud_elements.iter().zip(HUD_ELEMENTS.iter()) {
+            sync_check_box(check_box, settings.hud_layout.element(*element).visible);
+        }
+
+        let sync_scroll_bar = |handle: UINodeHandle, value: f32| {
+            ui.send_message(ScrollBarMessage::value(handle, value));
+        };
+        sync_scroll_bar(self.sb_hud_scale, settings.hud_scale);
+        sync_scroll_bar(self.sb_text_scale, settings.accessibility.text_scale);
+        sync_scroll_bar(self.sb_max_sounds, settings.max_sounds as f32);
+        sync_scroll_bar(self.sb_max_effects, settings.effect_limits.max_effects as f32);
+        sync_scroll_bar(self.sb_effect_density, settings.effect_limits.particle_density);
+        sync_scroll_bar(self.sb_effect_lifetime, settings.effect_limits.lifetime_scale);
+        sync_scroll_bar(self.sb_item_glow, settings.item_glow);
+        sync_scroll_bar(self.sb_corpse_lifetime, settings.corpse_limits.lifetime);
+        sync_scroll_bar(self.sb_max_corpses, settings.corpse_limits.max_corpses as f32);
+        sync_scroll_bar(self.sb_path_budget, settings.path_budget as f32);
+        sync_scroll_bar(self.sb_map_vote_time, settings.map_vote_time);
+        sync_scroll_bar(self.sb_self_knockback, settings.rocket_jump.self_knockback);
+        sync_scroll_bar(self.sb_crosshair_red, settings.crosshair.color.r as f32);
+        sync_scroll_bar(self.sb_crosshair_green, settings.crosshair.color.g as f32);
+        sync_scroll_bar(self.sb_crosshair_blue, settings.crosshair.color.b as f32);
+        sync_scroll_bar(self.sb_crosshair_thickness, settings.crosshair.thickness);
+        sync_scroll_bar(self.sb_crosshair_gap, settings.crosshair.gap);
+        sync_scroll_bar(self.sb_crosshair_length, settings.crosshair.length);
+        sync_scroll_bar(self.sb_crosshair_hit_red, settings.crosshair.hit_confirm_color.r as f32);
+        sync_scroll_bar(self.sb_crosshair_hit_green, settings.crosshair.hit_confirm_color.g as f32);
+        sync_scroll_bar(self.sb_crosshair_hit_blue, settings.crosshair.hit_confirm_color.b as f32);
+        let custom = settings.hud_theme.custom;
+        for (&sb, value) in self.sb_hud_theme_color.iter().zip([custom.r, custom.g, custom.b].iter()) {
+            sync_scroll_bar(sb, *value as f32);
+        }
+
+        let sync_dropdown_list = |handle: UINodeHandle, selection: Option<usize>| {
+            ui.send_message(DropdownListMessage::selection(handle, selection));
+        };
+        sync_dropdown_list(self.dl_crosshair_style, CROSSHAIR_STYLES.iter().position(|(_, style)| *style == settings.crosshair.style));
+        sync_dropdown_list(self.dl_color_blind_mode, COLOR_BLIND_MODES.iter().position(|(_, mode)| *mode == settings.color_blind_mode));
+        sync_dropdown_list(self.dl_hud_theme, Some(settings.hud_theme.preset.id() as usize));
+        sync_dropdown_list(self.dl_self_damage, Some(settings.rocket_jump.self_damage.id() as usize));
+        for (&dropdown_list, (_, element)) in self.dl_hud_anchors.iter().zip(HUD_ELEMENTS.iter()) {
+            sync_dropdown_list(dropdown_list, Some(settings.hud_layout.element(*element).anchor.id() as usize));
+        }
+
+        self.sync_to_model(engine);
+    }
+
+    /// Applies scale of HUD when its slider was not moved for a while.
+    pub fn update(&mut self, dt: f32) {
+        if let Some((scale, time_left)) = self.pending_hud_scale.as_mut() {
+            *time_left -= dt;
+            if *time_left <= 0.0 {
+                self.sender.send(Message::SetHudScale { scale: *scale }).unwrap();
+                self.pending_hud_scale = None;
             }
         }
     }
 
+    /// Shows result of last export or import of settings file.
+    pub fn set_settings_file_status(&mut self, ui: &mut Gui, text: &str) {
+        ui.send_message(TextMessage::text(self.settings_file_status, text.to_owned()));
+    }
+
     pub fn process_input_event(&mut self, engine: &mut GameEngine, event: &Event<()>) {
         if let Event::WindowEvent { event, .. } = event {
             let mut control_button = None;
@@ -589,8 +2146,13 @@ impl OptionsMenu {
                     }
                 }
                 WindowEvent::KeyboardInput { input, .. } => {
-                    if let Some(code) = input.virtual_keycode {
-                        control_button = Some(ControlButton::Key(code));
+                    control_button = Some(ControlButton::from_key(input));
+                }
+                WindowEvent::ReceivedCharacter(_) => {
+                    // Label of just bound key is known only after it typed a character.
+                    if self.relabel_pending {
+                        self.relabel_pending = false;
+                        self.sync_button_labels(&mut engine.user_interface);
                     }
                 }
                 WindowEvent::MouseInput { button, .. } => {
@@ -609,7 +2171,7 @@ impl OptionsMenu {
             if let Some(control_button) = control_button {
                 if let Some(active_control_button) = self.active_control_button {
                     if let UINode::Button(button) = engine.user_interface.node(self.control_scheme_buttons[active_control_button]) {
-                        engine.user_interface.send_message(TextMessage::text(button.content(), control_button.name().to_owned()));
+                        engine.user_interface.send_message(TextMessage::text(button.content(), control_button.label()));
                     }
 
                     self.control_scheme
@@ -618,6 +2180,7 @@ impl OptionsMenu {
                         .button = control_button;
 
                     self.active_control_button = None;
+                    self.relabel_pending = true;
                 }
             }
         }
@@ -627,6 +2190,14 @@ impl OptionsMenu {
     pub fn handle_ui_event(&mut self, engine: &mut GameEngine, message: &GuiMessage) {
         let old_settings = engine.renderer.get_quality_settings();
         let mut settings = old_settings;
+        let old_crosshair = self.crosshair;
+        let old_hud_layout = self.hud_layout;
+        let old_hud_theme = self.hud_theme;
+        let old_damage_feedback = self.damage_feedback;
+        let old_accessibility = self.accessibility;
+        let old_effect_limits = self.effect_limits;
+        let old_corpse_limits = self.corpse_limits;
+        let old_rocket_jump = self.rocket_jump;
 
         match &message.data {
             UiMessageData::ScrollBar(prop) => {
@@ -637,16 +2208,162 @@ impl OptionsMenu {
                         settings.point_shadows_distance = *new_value;
                     } else if message.destination == self.sb_spot_shadow_distance {
                         settings.spot_shadows_distance = *new_value;
-                    } else if message.destination == self.sb_mouse_sens {
+                    } else if message.destination == self.sb_mouse_sens_x {
+                        let mut control_scheme = self.control_scheme.borrow_mut();
+                        control_scheme.mouse_sens_x = *new_value;
+                        // Other slider is moved only if it differs, otherwise sliders would
+                        // keep sending values to each other.
+                        if control_scheme.link_mouse_axes && control_scheme.mouse_sens_y != *new_value {
+                            control_scheme.mouse_sens_y = *new_value;
+                            engine.user_interface.send_message(ScrollBarMessage::value(self.sb_mouse_sens_y, *new_value));
+                        }
+                    } else if message.destination == self.sb_mouse_sens_y {
+                        let mut control_scheme = self.control_scheme.borrow_mut();
+                        control_scheme.mouse_sens_y = *new_value;
+                        if control_scheme.link_mouse_axes && control_scheme.mouse_sens_x != *new_value {
+                            control_scheme.mouse_sens_x = *new_value;
+                            engine.user_interface.send_message(ScrollBarMessage::value(self.sb_mouse_sens_x, *new_value));
+                        }
+                    } else if message.destination == self.sb_screen_shake {
+                        self.control_scheme
+                            .borrow_mut()
+                            .screen_shake = *new_value;
+                    } else if message.destination == self.sb_ads_sensitivity {
+                        self.control_scheme
+                            .borrow_mut()
+                            .ads_sensitivity = *new_value;
+                    } else if message.destination == self.sb_head_bob {
+                        self.control_scheme
+                            .borrow_mut()
+                            .head_bob = *new_value;
+                    } else if message.destination == self.sb_input_buffer {
+                        self.control_scheme
+                            .borrow_mut()
+                            .input_buffer = *new_value;
+                    } else if message.destination == self.sb_mouse_smoothing {
                         self.control_scheme
                             .borrow_mut()
-                            .mouse_sens = *new_value;
+                            .mouse_smoothing = *new_value;
+                    } else if message.destination == self.sb_hud_scale {
+                        self.pending_hud_scale = Some((*new_value, HUD_SCALE_DELAY));
+                    } else if message.destination == self.sb_max_sounds {
+                        self.sender
+                            .send(Message::SetMaxSounds {
+                                count: *new_value as u32
+                            })
+                            .unwrap();
                     } else if message.destination == self.sb_music_volume {
                         self.sender
                             .send(Message::SetMusicVolume {
                                 volume: *new_value
                             })
                             .unwrap();
+                    } else if message.destination == self.sb_max_effects {
+                        self.effect_limits.max_effects = *new_value as u32;
+                    } else if message.destination == self.sb_effect_density {
+                        self.effect_limits.particle_density = *new_value;
+                    } else if message.destination == self.sb_effect_lifetime {
+                        self.effect_limits.lifetime_scale = *new_value;
+                    } else if message.destination == self.sb_corpse_lifetime {
+                        self.corpse_limits.lifetime = *new_value;
+                    } else if message.destination == self.sb_max_corpses {
+                        self.corpse_limits.max_corpses = *new_value as u32;
+                    } else if message.destination == self.sb_self_knockback {
+                        self.rocket_jump.self_knockback = *new_value;
+                    } else if message.destination == self.sb_path_budget {
+                        self.sender
+                            .send(Message::SetPathBudget {
+                                budget: *new_value as u32
+                            })
+                            .unwrap();
+                    } else if message.destination == self.sb_map_vote_time {
+                        self.sender
+                            .send(Message::SetMapVoteTime {
+                                time: *new_value
+                            })
+                            .unwrap();
+                    } else if message.destination == self.sb_item_glow {
+                        self.sender
+                            .send(Message::SetItemGlow {
+                                intensity: *new_value
+                            })
+                            .unwrap();
+                    } else if message.destination == self.sb_text_scale {
+                        self.accessibility.text_scale = *new_value;
+                    } else if message.destination == self.sb_crosshair_red {
+                        self.crosshair.color.r = *new_value as u8;
+                    } else if message.destination == self.sb_crosshair_green {
+                        self.crosshair.color.g = *new_value as u8;
+                    } else if message.destination == self.sb_crosshair_blue {
+                        self.crosshair.color.b = *new_value as u8;
+                    } else if message.destination == self.sb_crosshair_thickness {
+                        self.crosshair.thickness = *new_value;
+                    } else if message.destination == self.sb_crosshair_gap {
+                        self.crosshair.gap = *new_value;
+                    } else if message.destination == self.sb_crosshair_length {
+                        self.crosshair.length = *new_value;
+                    } else if message.destination == self.sb_crosshair_hit_red {
+                        self.crosshair.hit_confirm_color.r = *new_value as u8;
+                    } else if message.destination == self.sb_crosshair_hit_green {
+                        self.crosshair.hit_confirm_color.g = *new_value as u8;
+                    } else if message.destination == self.sb_crosshair_hit_blue {
+                        self.crosshair.hit_confirm_color.b = *new_value as u8;
+                    } else if let Some(channel) = self.sb_hud_theme_color.iter().position(|&sb| sb == message.destination) {
+                        let custom = &mut self.hud_theme.custom;
+                        let value = match channel {
+                            0 => &mut custom.r,
+                            1 => &mut custom.g,
+                            _ => &mut custom.b,
+                        };
+                        // Moving a scroll bar means user picks own color, but scroll bars
+                        // are also moved when settings are synced, so only actual changes count.
+                        if *value != *new_value as u8 {
+                            *value = *new_value as u8;
+                            self.hud_theme.preset = HudThemePreset::Custom;
+                        }
+                    }
+                }
+            }
+            UiMessageData::DropdownList(msg) => {
+                if let DropdownListMessage::SelectionChanged(selection) = msg {
+                    if message.destination == self.dl_crosshair_style {
+                        if let Some((_, style)) = selection.and_then(|i| CROSSHAIR_STYLES.get(i)) {
+                            self.crosshair.style = *style;
+                        }
+                    } else if message.destination == self.dl_color_blind_mode {
+                        if let Some((_, mode)) = selection.and_then(|i| COLOR_BLIND_MODES.get(i)) {
+                            self.sender
+                                .send(Message::SetColorBlindMode {
+                                    mode: *mode
+                                })
+                                .unwrap();
+                        }
+                    } else if message.destination == self.dl_pickup_switch {
+                        if let Some((_, mode)) = selection.and_then(|i| PICKUP_SWITCH_MODES.get(i)) {
+                            self.control_scheme.borrow_mut().pickup_switch = *mode;
+                        }
+                    } else if message.destination == self.dl_hud_theme {
+                        if let Some((_, preset)) = selection.and_then(|i| HUD_THEME_PRESETS.get(i)) {
+                            if self.hud_theme.preset != *preset {
+                                self.hud_theme.preset = *preset;
+                                // Picked theme suggests its crosshair color, it can be
+                                // changed on crosshair tab afterwards.
+                                let color = self.hud_theme.colors().crosshair;
+                                self.crosshair.color = color;
+                                let ui = &mut engine.user_interface;
+                                ui.send_message(ScrollBarMessage::value(self.sb_crosshair_red, color.r as f32));
+                                ui.send_message(ScrollBarMessage::value(self.sb_crosshair_green, color.g as f32));
+                                ui.send_message(ScrollBarMessage::value(self.sb_crosshair_blue, color.b as f32));
+                            }
+                        }
+                    } else if let Some(index) = self.dl_hud_anchors.iter().position(|&dl| dl == message.destination) {
+                        if let Some((_, anchor)) = selection.and_then(|i| HUD_ANCHORS.get(i)) {
+                            self.hud_layout.element_mut(HUD_ELEMENTS[index].1).anchor = *anchor;
+                        }
+                    } else if message.destination == self.dl_self_damage {
+                        if let Some((_, mode)) = selection.and_then(|i| SELF_DAMAGE_MODES.get(i)) {
+                            self.rocket_jump.self_damage = *mode;
+                        }
                     }
                 }
             }
@@ -673,10 +2390,116 @@ impl OptionsMenu {
                         settings.point_soft_shadows = value.unwrap_or(false);
                     } else if message.destination == self.cb_mouse_y_inverse {
                         control_scheme.mouse_y_inverse = value.unwrap_or(false);
+                    } else if message.destination == self.cb_link_mouse_axes {
+                        control_scheme.link_mouse_axes = value.unwrap_or(false);
+                        // Linked axes start from horizontal sensitivity.
+                        if control_scheme.link_mouse_axes && control_scheme.mouse_sens_y != control_scheme.mouse_sens_x {
+                            control_scheme.mouse_sens_y = control_scheme.mouse_sens_x;
+                            engine.user_interface.send_message(ScrollBarMessage::value(self.sb_mouse_sens_y, control_scheme.mouse_sens_x));
+                        }
                     } else if message.destination == self.cb_smooth_mouse {
                         control_scheme.smooth_mouse = value.unwrap_or(false);
+                    } else if message.destination == self.cb_mouse_acceleration {
+                        control_scheme.mouse_acceleration = value.unwrap_or(false);
                     } else if message.destination == self.cb_shake_camera {
                         control_scheme.shake_camera = value.unwrap_or(false);
+                    } else if message.destination == self.cb_auto_switch_weapon {
+                        control_scheme.auto_switch_weapon = value.unwrap_or(false);
+                    } else if message.destination == self.cb_toggle_sprint {
+                        control_scheme.toggle_sprint = value.unwrap_or(false);
+                    } else if message.destination == self.cb_auto_reload {
+                        control_scheme.auto_reload = value.unwrap_or(false);
+                    } else if message.destination == self.cb_reload_on_switch {
+                        control_scheme.reload_on_switch = value.unwrap_or(false);
+                    } else if message.destination == self.cb_manual_pickup {
+                        control_scheme.manual_pickup = value.unwrap_or(false);
+                    } else if message.destination == self.cb_pause_when_unfocused {
+                        self.sender
+                            .send(Message::SetPauseWhenUnfocused {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_precise_frame_pacing {
+                        self.sender
+                            .send(Message::SetPreciseFramePacing {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_low_latency {
+                        self.sender
+                            .send(Message::SetLowLatency {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_item_timers {
+                        self.sender
+                            .send(Message::SetItemTimersEnabled {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_death_marker {
+                        self.sender
+                            .send(Message::SetDeathMarkerEnabled {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_nameplates_through_walls {
+                        self.sender
+                            .send(Message::SetNameplatesThroughWalls {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_killcam {
+                        self.sender
+                            .send(Message::SetKillcam {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_weapon_drop {
+                        self.sender
+                            .send(Message::SetWeaponDrop {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_gore || message.destination == self.cb_clean_language {
+                        if message.destination == self.cb_gore {
+                            self.content_filter.gore = value.unwrap_or(false);
+                        } else {
+                            self.content_filter.clean_language = value.unwrap_or(false);
+                        }
+                        self.sender
+                            .send(Message::SetContentFilter {
+                                filter: self.content_filter
+                            })
+                            .unwrap();
+                    } else if message.destination == self.cb_low_effects {
+                        // Unchecking restores defaults only when preset is in use, check box
+                        // is also unchecked when limits are changed by hand.
+                        if value.unwrap_or(false) {
+                            self.effect_limits = EffectLimits::low();
+                        } else if self.effect_limits.is_low() {
+                            self.effect_limits = EffectLimits::default();
+                        }
+                    } else if let Some(index) = self.cb_hud_elements.iter().position(|&cb| cb == message.destination) {
+                        self.hud_layout.element_mut(HUD_ELEMENTS[index].1).visible = value.unwrap_or(false);
+                    } else if message.destination == self.cb_hud_crosshair {
+                        self.hud_layout.crosshair = value.unwrap_or(false);
+                    } else if message.destination == self.cb_damage_direction {
+                        self.damage_feedback.direction_indicator = value.unwrap_or(false);
+                    } else if message.destination == self.cb_damage_vignette {
+                        self.damage_feedback.vignette = value.unwrap_or(false);
+                    } else if message.destination == self.cb_hit_flash {
+                        self.damage_feedback.hit_flash = value.unwrap_or(false);
+                    } else if message.destination == self.cb_high_contrast {
+                        self.accessibility.high_contrast = value.unwrap_or(false);
+                    } else if message.destination == self.cb_crosshair_hit_confirm {
+                        self.crosshair.hit_confirm = value.unwrap_or(false);
+                    } else if message.destination == self.cb_damage_numbers {
+                        self.sender
+                            .send(Message::SetDamageNumbersEnabled {
+                                enabled: value.unwrap_or(false)
+                            })
+                            .unwrap();
                     }
                 }
             }
@@ -687,7 +2510,20 @@ impl OptionsMenu {
                         self.sync_to_model(engine);
                     } else if message.destination == self.btn_reset_audio_settings {
                         engine.sound_context.lock().unwrap().set_master_gain(1.0);
+                        engine.user_interface.send_message(ScrollBarMessage::value(self.sb_max_sounds, DEFAULT_MAX_SOUNDS as f32));
                         self.sync_to_model(engine);
+                    } else if message.destination == self.btn_export_settings {
+                        self.sender
+                            .send(Message::ExportSettings {
+                                path: PathBuf::from(SETTINGS_EXPORT_PATH)
+                            })
+                            .unwrap();
+                    } else if message.destination == self.btn_import_settings {
+                        self.sender
+                            .send(Message::ImportSettings {
+                                path: PathBuf::from(SETTINGS_EXPORT_PATH)
+                            })
+                            .unwrap();
                     }
 
                     for (i, button) in self.control_scheme_buttons.iter().enumerate() {
@@ -704,6 +2540,77 @@ impl OptionsMenu {
             _ => ()
         }
 
+        if self.crosshair != old_crosshair {
+            self.sender
+                .send(Message::SetCrosshair {
+                    config: self.crosshair
+                })
+                .unwrap();
+        }
+
+        if self.hud_layout != old_hud_layout {
+            self.sender
+                .send(Message::SetHudLayout {
+                    layout: self.hud_layout
+                })
+                .unwrap();
+        }
+
+        if self.hud_theme != old_hud_theme {
+            let ui = &mut engine.user_interface;
+            ui.send_message(DropdownListMessage::selection(self.dl_hud_theme, Some(self.hud_theme.preset.id() as usize)));
+            self.sender
+                .send(Message::SetHudTheme {
+                    theme: self.hud_theme
+                })
+                .unwrap();
+        }
+
+        if self.accessibility != old_accessibility {
+            self.sender
+                .send(Message::SetAccessibility {
+                    accessibility: self.accessibility
+                })
+                .unwrap();
+        }
+
+        if self.damage_feedback != old_damage_feedback {
+            self.sender
+                .send(Message::SetDamageFeedback {
+                    feedback: self.damage_feedback
+                })
+                .unwrap();
+        }
+
+        if self.corpse_limits != old_corpse_limits {
+            self.sender
+                .send(Message::SetCorpseLimits {
+                    limits: self.corpse_limits
+                })
+                .unwrap();
+        }
+
+        if self.rocket_jump != old_rocket_jump {
+            self.sender
+                .send(Message::SetRocketJump {
+                    rocket_jump: self.rocket_jump
+                })
+                .unwrap();
+        }
+
+        if self.effect_limits != old_effect_limits {
+            let ui = &mut engine.user_interface;
+            ui.send_message(CheckBoxMessage::check(self.cb_low_effects, Some(self.effect_limits.is_low())));
+            ui.send_message(ScrollBarMessage::value(self.sb_max_effects, self.effect_limits.max_effects as f32));
+            ui.send_message(ScrollBarMessage::value(self.sb_effect_density, self.effect_limits.particle_density));
+            ui.send_message(ScrollBarMessage::value(self.sb_effect_lifetime, self.effect_limits.lifetime_scale));
+            self.sender
+                .send(Message::SetEffectLimits {
+                    limits: self.effect_limits
+                })
+                .unwrap();
+        }
+
         if settings != old_settings {
             if let Err(err) = engine.renderer.set_quality_settings(&settings) {
                 println!("Failed to set renderer quality settings! Reason: {:?}", err);
@@ -0,0 +1,200 @@
+//! Colors that are used to tell actors and objectives apart. Every such color must be taken
+//! from a palette, so colorblind modes can replace them with colors that are easy to
+//! distinguish for people with color vision deficiencies.
+
+use crate::{
+    character::Team,
+    damage::DamageKind,
+};
+use rg3d::core::{
+    color::Color,
+    visitor::{Visit, Visitor, VisitResult},
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ColorBlindMode {
+    None,
+    Deuteranopia,
+    Protanopia,
+    Tritanopia,
+}
+
+impl Default for ColorBlindMode {
+    fn default() -> Self {
+        ColorBlindMode::None
+    }
+}
+
+impl ColorBlindMode {
+    pub fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(ColorBlindMode::None),
+            1 => Ok(ColorBlindMode::Deuteranopia),
+            2 => Ok(ColorBlindMode::Protanopia),
+            3 => Ok(ColorBlindMode::Tritanopia),
+            _ => Err(format!("Invalid colorblind mode {}", id))
+        }
+    }
+
+    pub fn id(self) -> u32 {
+        match self {
+            ColorBlindMode::None => 0,
+            ColorBlindMode::Deuteranopia => 1,
+            ColorBlindMode::Protanopia => 2,
+            ColorBlindMode::Tritanopia => 3,
+        }
+    }
+}
+
+impl Visit for ColorBlindMode {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+pub struct Palette {
+    pub red_team: Color,
+    pub blue_team: Color,
+    /// Tints for bots in modes without teams. Colors are bright enough to not make
+    /// textures too dark when multiplied.
+    pub bots: [Color; 8],
+    pub enemy_marker: Color,
+    pub health_marker: Color,
+    /// Colors of damage feedback, player must be able to tell kind of damage by color.
+    pub bullet_damage: Color,
+    pub explosion_damage: Color,
+    pub melee_damage: Color,
+    pub environment_damage: Color,
+}
+
+impl Palette {
+    pub fn team_color(&self, team: Team) -> Option<Color> {
+        match team {
+            Team::None => None,
+            Team::Red => Some(self.red_team),
+            Team::Blue => Some(self.blue_team),
+        }
+    }
+
+    pub fn damage_color(&self, kind: DamageKind) -> Color {
+        match kind {
+            DamageKind::Bullet => self.bullet_damage,
+            DamageKind::Explosion => self.explosion_damage,
+            DamageKind::Melee => self.melee_damage,
+            DamageKind::Environment => self.environment_damage,
+        }
+    }
+
+    /// Returns color for bot with given name. Same name always gives same color, so bot keeps
+    /// its color after respawn.
+    pub fn bot_color(&self, name: &str) -> Color {
+        let hash = name.bytes().fold(0usize, |hash, b| hash.wrapping_mul(31).wrapping_add(b as usize));
+        self.bots[hash % self.bots.len()]
+    }
+}
+
+static DEFAULT_PALETTE: Palette = Palette {
+    red_team: Color::opaque(255, 110, 110),
+    blue_team: Color::opaque(110, 140, 255),
+    bots: [
+        Color::opaque(255, 160, 160),
+        Color::opaque(160, 255, 160),
+        Color::opaque(160, 160, 255),
+        Color::opaque(255, 255, 150),
+        Color::opaque(255, 160, 255),
+        Color::opaque(150, 255, 255),
+        Color::opaque(255, 200, 130),
+        Color::opaque(200, 170, 255),
+    ],
+    enemy_marker: Color::opaque(255, 60, 60),
+    health_marker: Color::opaque(60, 255, 60),
+    bullet_damage: Color::opaque(200, 0, 0),
+    explosion_damage: Color::opaque(255, 130, 0),
+    melee_damage: Color::opaque(190, 0, 220),
+    environment_damage: Color::opaque(150, 220, 0),
+};
+
+/// Red and green are hard to tell apart with deuteranopia and protanopia, so colors are
+/// based on blue-orange contrast and brightness instead. Protanopia makes reds look darker,
+/// so its palette avoids dark reds completely.
+static DEUTERANOPIA_PALETTE: Palette = Palette {
+    red_team: Color::opaque(255, 170, 60),
+    blue_team: Color::opaque(90, 160, 255),
+    bots: [
+        Color::opaque(255, 190, 90),
+        Color::opaque(130, 200, 250),
+        Color::opaque(255, 245, 130),
+        Color::opaque(120, 150, 255),
+        Color::opaque(235, 175, 210),
+        Color::opaque(255, 255, 255),
+        Color::opaque(230, 140, 70),
+        Color::opaque(170, 170, 170),
+    ],
+    enemy_marker: Color::opaque(255, 150, 0),
+    health_marker: Color::opaque(70, 150, 255),
+    bullet_damage: Color::opaque(230, 120, 0),
+    explosion_damage: Color::opaque(255, 240, 140),
+    melee_damage: Color::opaque(60, 120, 255),
+    environment_damage: Color::opaque(200, 200, 200),
+};
+
+static PROTANOPIA_PALETTE: Palette = Palette {
+    red_team: Color::opaque(255, 200, 60),
+    blue_team: Color::opaque(90, 160, 255),
+    bots: [
+        Color::opaque(255, 210, 100),
+        Color::opaque(130, 200, 250),
+        Color::opaque(255, 250, 160),
+        Color::opaque(120, 150, 255),
+        Color::opaque(220, 190, 230),
+        Color::opaque(255, 255, 255),
+        Color::opaque(240, 170, 80),
+        Color::opaque(170, 170, 170),
+    ],
+    enemy_marker: Color::opaque(255, 190, 0),
+    health_marker: Color::opaque(70, 150, 255),
+    bullet_damage: Color::opaque(255, 170, 0),
+    explosion_damage: Color::opaque(255, 250, 180),
+    melee_damage: Color::opaque(70, 130, 255),
+    environment_damage: Color::opaque(200, 200, 200),
+};
+
+/// Blue and yellow are hard to tell apart with tritanopia, so colors are based on
+/// red-cyan contrast.
+static TRITANOPIA_PALETTE: Palette = Palette {
+    red_team: Color::opaque(255, 100, 120),
+    blue_team: Color::opaque(80, 220, 220),
+    bots: [
+        Color::opaque(255, 130, 140),
+        Color::opaque(120, 230, 230),
+        Color::opaque(255, 190, 200),
+        Color::opaque(60, 180, 180),
+        Color::opaque(255, 255, 255),
+        Color::opaque(230, 80, 100),
+        Color::opaque(190, 240, 240),
+        Color::opaque(170, 170, 170),
+    ],
+    enemy_marker: Color::opaque(255, 40, 80),
+    health_marker: Color::opaque(40, 230, 230),
+    bullet_damage: Color::opaque(230, 0, 60),
+    explosion_damage: Color::opaque(255, 160, 180),
+    melee_damage: Color::opaque(0, 200, 200),
+    environment_damage: Color::opaque(200, 200, 200),
+};
+
+pub fn palette(mode: ColorBlindMode) -> &'static Palette {
+    match mode {
+        ColorBlindMode::None => &DEFAULT_PALETTE,
+        ColorBlindMode::Deuteranopia => &DEUTERANOPIA_PALETTE,
+        ColorBlindMode::Protanopia => &PROTANOPIA_PALETTE,
+        ColorBlindMode::Tritanopia => &TRITANOPIA_PALETTE,
+    }
+}
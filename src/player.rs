@@ -1,12 +1,20 @@
 use rand::Rng;
 use crate::{
     character::Character,
+    actor::Actor,
+    bot::FOOTSTEP_LOUDNESS,
     level::UpdateContext,
     control_scheme::{
         ControlScheme,
         ControlButton,
     },
     message::Message,
+    net::{PlayerInput, ClientId},
+    weapon::{Weapon, RecoilDefinition},
+    damage::HealthRegen,
+    instantiate_model,
+    frame_blend_factor,
+    SoundPriority,
 };
 use std::{
     rc::Rc,
@@ -21,7 +29,7 @@ use rg3d::{
     core::{
         visitor::{Visit, Visitor, VisitResult},
         pool::Handle,
-        math::{vec3::Vec3, quat::Quat, mat3::Mat3},
+        math::{vec3::Vec3, quat::Quat, mat3::Mat3, ray::Ray},
     },
     event::{
         DeviceEvent,
@@ -36,6 +44,7 @@ use rg3d::{
         base::BaseBuilder,
     },
     sound::context::Context,
+    engine::resource_manager::ResourceManager,
     physics::{
         convex_shape::{
             ConvexShape,
@@ -43,10 +52,46 @@ use rg3d::{
             Axis,
         },
         rigid_body::RigidBody,
+        RayCastOptions,
     },
 };
 use std::ops::{Deref, DerefMut};
 
+/// Press of a button that is remembered for a short time, so action pressed a bit before it
+/// becomes possible (jump right before landing, shot at the end of reload) is not lost.
+#[derive(Default)]
+struct BufferedAction {
+    time_left: f32,
+    pending: bool,
+}
+
+impl BufferedAction {
+    fn press(&mut self, window: f32) {
+        self.time_left = window;
+        self.pending = true;
+    }
+
+    /// Returns true if button was pressed within window, press is consumed then.
+    fn take(&mut self) -> bool {
+        std::mem::replace(&mut self.pending, false)
+    }
+
+    fn is_pending(&self) -> bool {
+        self.pending
+    }
+
+    /// Must be called after action had its chance to be performed in current frame, so
+    /// zero window still gives a press one frame.
+    fn update(&mut self, dt: f32) {
+        if self.pending {
+            self.time_left -= dt;
+            if self.time_left < 0.0 {
+                self.pending = false;
+            }
+        }
+    }
+}
+
 pub struct Controller {
     move_forward: bool,
     move_backward: bool,
@@ -56,6 +101,13 @@ pub struct Controller {
     jump: bool,
     run: bool,
     shoot: bool,
+    aim: bool,
+    alt_fire: bool,
+    /// Use button was pressed and request was not processed by level yet.
+    use_object: bool,
+    jump_buffer: BufferedAction,
+    shoot_buffer: BufferedAction,
+    alt_fire_buffer: BufferedAction,
 }
 
 impl Default for Controller {
@@ -69,10 +121,135 @@ impl Default for Controller {
             jump: false,
             run: false,
             shoot: false,
+            aim: false,
+            alt_fire: false,
+            use_object: false,
+            jump_buffer: Default::default(),
+            shoot_buffer: Default::default(),
+            alt_fire_buffer: Default::default(),
         }
     }
 }
 
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum CameraMode {
+    FirstPerson,
+    /// Camera is placed behind and above player, player's body is visible.
+    ThirdPerson,
+}
+
+impl Default for CameraMode {
+    fn default() -> Self {
+        CameraMode::FirstPerson
+    }
+}
+
+impl CameraMode {
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(CameraMode::FirstPerson),
+            1 => Ok(CameraMode::ThirdPerson),
+            _ => Err(format!("Invalid camera mode {}", id))
+        }
+    }
+
+    fn id(self) -> u32 {
+        match self {
+            CameraMode::FirstPerson => 0,
+            CameraMode::ThirdPerson => 1,
+        }
+    }
+}
+
+impl Visit for CameraMode {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Offset of camera from its first person position in third person mode, in camera's
+/// local coordinates.
+const THIRD_PERSON_CAMERA_OFFSET: Vec3 = Vec3 { x: 0.0, y: 0.35, z: -2.5 };
+
+/// Name of player in leader board.
+pub const PLAYER_NAME: &str = "Player";
+
+/// Name of player of network client in leader board.
+pub fn remote_player_name(client: ClientId) -> String {
+    format!("Guest {}", client + 1)
+}
+
+/// How much sensitivity grows per unit of mouse movement in one event when acceleration is on.
+const MOUSE_ACCELERATION: f32 = 0.02;
+const MAX_MOUSE_ACCELERATION_FACTOR: f32 = 3.0;
+
+/// Maximum offset of camera caused by screen shake, in meters.
+const MAX_SHAKE_OFFSET: f32 = 0.12;
+/// How much of shake trauma disappears per second.
+const SHAKE_DECAY: f32 = 1.5;
+
+/// Field of view of camera without aiming, in degrees.
+const HIP_FOV: f32 = 75.0;
+/// Field of view of camera while aiming down sights, in degrees.
+const ADS_FOV: f32 = 50.0;
+/// Field of view of scope with given magnification relative to hip fire view, in degrees.
+fn scope_fov(magnification: f32) -> f32 {
+    2.0 * ((HIP_FOV.to_radians() * 0.5).tan() / magnification).atan().to_degrees()
+}
+
+/// Weapon model looks as if it is rendered with this field of view regardless of field of
+/// view of camera, in degrees.
+const WEAPON_FOV: f32 = 75.0;
+/// Position of weapon relative to camera.
+const WEAPON_BASE_POSITION: Vec3 = Vec3 { x: -0.065, y: -0.052, z: 0.02 };
+/// Weapon is pulled back when a wall is closer to camera than this distance, in meters.
+const WEAPON_CLIP_DISTANCE: f32 = 0.8;
+const MAX_WEAPON_PULLBACK: f32 = 0.15;
+
+/// Vertical speed given by jump, in meters per second.
+const JUMP_SPEED: f32 = 4.2;
+/// Part of difference between current and wanted horizontal velocity that is closed per frame
+/// at reference frame rate while player is in air. Low control keeps momentum of jumps and
+/// knockback, so rocket jumps carry player further than running does.
+const AIR_CONTROL: f32 = 0.05;
+/// How fast weapon bobs while walking, in radians per second.
+const WEAPON_SHAKE_SPEED: f32 = 13.8;
+/// Distance that is added to walked path per second of walking, it grows faster while
+/// running.
+const PATH_SPEED: f32 = 6.0;
+/// Length of walked path between two footsteps.
+const STEP_LENGTH: f32 = 2.0;
+/// How low camera goes on every footstep, in meters.
+const HEAD_BOB_HEIGHT: f32 = 0.04;
+/// How far camera sways to the side of stepping foot, in meters.
+const HEAD_BOB_SWAY: f32 = 0.02;
+/// Head bob is stronger while running.
+const RUN_HEAD_BOB_FACTOR: f32 = 1.5;
+
+/// Returns sensitivity multiplier for given raw mouse delta, slow movements are left
+/// untouched so precise aiming is not affected.
+fn mouse_acceleration_factor(delta: (f64, f64)) -> f32 {
+    let speed = (delta.0 * delta.0 + delta.1 * delta.1).sqrt() as f32;
+    (1.0 + speed * MOUSE_ACCELERATION).min(MAX_MOUSE_ACCELERATION_FACTOR)
+}
+
+/// Takes back part of recoil that player has already pulled against by mouse movement `delta`.
+fn compensate_recoil(recoil: f32, delta: f32) -> f32 {
+    if recoil * delta > 0.0 {
+        recoil - delta.abs().min(recoil.abs()).copysign(recoil)
+    } else {
+        recoil
+    }
+}
+
 pub struct Player {
     character: Character,
     camera: Handle<Node>,
@@ -85,10 +262,13 @@ pub struct Player {
     run_speed_multiplier: f32,
     stand_body_height: f32,
     crouch_body_height: f32,
+    /// Walk speed in meters per second.
     move_speed: f32,
     camera_offset: Vec3,
     camera_dest_offset: Vec3,
     path_len: f32,
+    /// Side of the foot that makes next step, 1.0 or -1.0, camera sways to that side.
+    step_side: f32,
     feet_position: Vec3,
     head_position: Vec3,
     look_direction: Vec3,
@@ -100,6 +280,37 @@ pub struct Player {
     stand_up_speed: f32,
     listener_basis: Mat3,
     control_scheme: Option<Rc<RefCell<ControlScheme>>>,
+    /// Body of player, it is visible only in third person mode.
+    model: Handle<Node>,
+    camera_mode: CameraMode,
+    /// Current blend between first (0.0) and third (1.0) person camera positions.
+    third_person_factor: f32,
+    /// Strength of screen shake in [0; 1] range, decays over time.
+    shake_trauma: f32,
+    /// Part of recoil kick (up, right) in degrees that is not recovered yet, recovery moves
+    /// aim back by it.
+    recoil: (f32, f32),
+    /// Recovery speed of weapon that kicked last, in degrees per second.
+    recoil_recovery_speed: f32,
+    /// Current blend between hip fire (0.0) and aiming down sights (1.0), affects field of
+    /// view and mouse sensitivity.
+    ads_factor: f32,
+    /// Field of view while aiming in degrees, it follows magnification of scope.
+    ads_fov: f32,
+    /// Magnifications of scope of current weapon, empty if weapon has no scope.
+    zoom_levels: &'static [f32],
+    /// Index of current magnification in `zoom_levels`. Not saved, zoom starts from lowest
+    /// magnification every time another weapon is taken.
+    zoom_index: usize,
+    zoom_weapon: Handle<Weapon>,
+    /// How far weapon is pulled back from a close wall, in meters.
+    weapon_pullback: f32,
+    health_regen: HealthRegen,
+    /// Time left until health starts to regenerate, every hit starts it over.
+    regen_delay_left: f32,
+    /// Not saved, network matches can't be saved. Player is controlled by input of a
+    /// network client, its camera is off and its body is always visible.
+    remote: bool,
 }
 
 impl Deref for Player {
@@ -126,7 +337,7 @@ impl Default for Player {
             stand_body_height: 1.05,
             dest_pitch: 0.0,
             dest_yaw: 0.0,
-            move_speed: 0.058,
+            move_speed: 3.5,
             run_speed_multiplier: 1.75,
             crouch_body_height: 0.15,
             yaw: 0.0,
@@ -134,6 +345,7 @@ impl Default for Player {
             camera_dest_offset: Vec3::ZERO,
             camera_offset: Vec3::ZERO,
             path_len: 0.0,
+            step_side: 1.0,
             feet_position: Vec3::ZERO,
             head_position: Vec3::ZERO,
             look_direction: Vec3::ZERO,
@@ -141,10 +353,25 @@ impl Default for Player {
             weapon_offset: Default::default(),
             weapon_dest_offset: Default::default(),
             weapon_shake_factor: 0.0,
-            crouch_speed: 0.15,
-            stand_up_speed: 0.12,
+            crouch_speed: 9.0,
+            stand_up_speed: 7.2,
             listener_basis: Default::default(),
             control_scheme: None,
+            model: Default::default(),
+            camera_mode: Default::default(),
+            third_person_factor: 0.0,
+            shake_trauma: 0.0,
+            recoil: (0.0, 0.0),
+            recoil_recovery_speed: 0.0,
+            ads_factor: 0.0,
+            ads_fov: ADS_FOV,
+            zoom_levels: &[],
+            zoom_index: 0,
+            zoom_weapon: Handle::NONE,
+            weapon_pullback: 0.0,
+            health_regen: Default::default(),
+            regen_delay_left: 0.0,
+            remote: false,
         }
     }
 }
@@ -166,13 +393,22 @@ impl Visit for Player {
         self.move_speed.visit("MoveSpeed", visitor)?;
         self.camera_offset.visit("CameraOffset", visitor)?;
         self.camera_dest_offset.visit("CameraDestOffset", visitor)?;
+        self.model.visit("Model", visitor)?;
+        self.camera_mode.visit("CameraMode", visitor)?;
+        self.third_person_factor.visit("ThirdPersonFactor", visitor)?;
+        self.shake_trauma.visit("ShakeTrauma", visitor)?;
+        self.recoil.0.visit("RecoilUp", visitor)?;
+        self.recoil.1.visit("RecoilRight", visitor)?;
+        self.recoil_recovery_speed.visit("RecoilRecoverySpeed", visitor)?;
+        self.health_regen.visit("HealthRegen", visitor)?;
+        self.regen_delay_left.visit("RegenDelayLeft", visitor)?;
 
         visitor.leave_region()
     }
 }
 
 impl Player {
-    pub fn new(scene: &mut Scene, sender: Sender<Message>) -> Player {
+    pub fn new(resource_manager: &mut ResourceManager, scene: &mut Scene, sender: Sender<Message>) -> Player {
         let camera_handle = scene.graph.add_node(Node::Camera(
             CameraBuilder::new(BaseBuilder::new()).build())
         );
@@ -197,10 +433,20 @@ impl Player {
         scene.physics_binder.bind(pivot_handle, body_handle);
         scene.graph.link_nodes(camera_pivot_handle, pivot_handle);
 
+        // Player does not have its own model, so borrow one from bots. Model is not animated.
+        let model = instantiate_model(resource_manager, "data/models/mutant.FBX", scene);
+        let model_scale = 0.0085;
+        scene.graph[model]
+            .set_visibility(false)
+            .local_transform_mut()
+            .set_position(Vec3::new(0.0, -height * 0.5, 0.0))
+            .set_scale(Vec3::new(model_scale, model_scale, model_scale));
+        scene.graph.link_nodes(model, pivot_handle);
+
         let mut weapon_base_pivot = Node::Base(Default::default());
         weapon_base_pivot
             .local_transform_mut()
-            .set_position(Vec3::new(-0.065, -0.052, 0.02));
+            .set_position(WEAPON_BASE_POSITION);
         let weapon_base_pivot_handle = scene.graph.add_node(weapon_base_pivot);
         scene.graph.link_nodes(weapon_base_pivot_handle, camera_handle);
 
@@ -214,27 +460,28 @@ impl Player {
                 body: body_handle,
                 weapon_pivot: weapon_pivot_handle,
                 sender: Some(sender),
-                name: "Player".to_owned(),
+                name: PLAYER_NAME.to_owned(),
                 ..Default::default()
             },
             camera: camera_handle,
             camera_pivot: camera_pivot_handle,
+            model,
             ..Default::default()
         }
     }
 
-    fn handle_crouch(&mut self, body: &mut RigidBody) {
+    fn handle_crouch(&mut self, body: &mut RigidBody, dt: f32) {
         let capsule = body.get_shape_mut().as_capsule_mut();
         let current_height = capsule.get_height();
         let new_height = if self.controller.crouch {
-            let new_height = current_height - self.crouch_speed;
+            let new_height = current_height - self.crouch_speed * dt;
             if new_height < self.crouch_body_height {
                 self.crouch_body_height
             } else {
                 new_height
             }
         } else {
-            let new_height = current_height + self.stand_up_speed;
+            let new_height = current_height + self.stand_up_speed * dt;
             if new_height > self.stand_body_height {
                 self.stand_body_height
             } else {
@@ -248,10 +495,113 @@ impl Player {
         self.camera
     }
 
+    pub fn toggle_camera_mode(&mut self) {
+        self.camera_mode = match self.camera_mode {
+            CameraMode::FirstPerson => CameraMode::ThirdPerson,
+            CameraMode::ThirdPerson => CameraMode::FirstPerson,
+        };
+    }
+
     pub fn set_control_scheme(&mut self, control_scheme: Rc<RefCell<ControlScheme>>) {
         self.control_scheme = Some(control_scheme);
     }
 
+    /// Returns how long pressed button is remembered. Remote player has its presses buffered
+    /// on its own side, so nothing is buffered here.
+    fn input_buffer(&self) -> f32 {
+        if self.remote {
+            0.0
+        } else {
+            self.control_scheme
+                .as_ref()
+                .map_or(0.0, |control_scheme| control_scheme.borrow().input_buffer)
+        }
+    }
+
+    /// Returns true if items must be picked up by pick up button instead of walking over them.
+    /// Remote player has no use button, so it always walks over items.
+    pub fn is_manual_pickup(&self) -> bool {
+        !self.remote && self.control_scheme
+            .as_ref()
+            .map_or(false, |control_scheme| control_scheme.borrow().manual_pickup)
+    }
+
+    /// Turns player into one that is controlled over network, see [`Player::apply_input`].
+    pub fn set_remote(&mut self, scene: &mut Scene) {
+        self.remote = true;
+        if let Node::Camera(camera) = &mut scene.graph[self.camera] {
+            camera.set_enabled(false);
+        }
+    }
+
+    /// Returns current state of controls, it is sent to server by network client.
+    pub fn input(&self) -> PlayerInput {
+        PlayerInput {
+            move_forward: self.controller.move_forward,
+            move_backward: self.controller.move_backward,
+            move_left: self.controller.move_left,
+            move_right: self.controller.move_right,
+            crouch: self.controller.crouch,
+            jump: self.controller.jump,
+            run: self.controller.run,
+            // Buffered presses are sent as held buttons, so server gets them even if button
+            // was released before weapon got ready.
+            shoot: self.controller.shoot || self.controller.shoot_buffer.is_pending(),
+            aim: self.controller.aim,
+            alt_fire: self.controller.alt_fire || self.controller.alt_fire_buffer.is_pending(),
+            yaw: self.dest_yaw,
+            pitch: self.dest_pitch,
+        }
+    }
+
+    /// Sets controls to state received from network client.
+    pub fn apply_input(&mut self, input: &PlayerInput) {
+        if self.controller.shoot && !input.shoot {
+            let weapon = self.character.current_weapon();
+            if weapon.is_some() {
+                self.character.sender.as_ref().unwrap().send(Message::ReleaseTrigger {
+                    weapon
+                }).unwrap();
+            }
+        }
+        self.controller.move_forward = input.move_forward;
+        self.controller.move_backward = input.move_backward;
+        self.controller.move_left = input.move_left;
+        self.controller.move_right = input.move_right;
+        self.controller.crouch = input.crouch;
+        // Jump is reset once it is performed, so missed release won't make player bounce.
+        self.controller.jump |= input.jump;
+        self.controller.run = input.run;
+        self.controller.shoot = input.shoot;
+        self.controller.aim = input.aim;
+        self.controller.alt_fire = input.alt_fire;
+        self.set_look(input.yaw, input.pitch);
+    }
+
+    /// Look angles in degrees.
+    pub fn look_angles(&self) -> (f32, f32) {
+        (self.dest_yaw, self.dest_pitch)
+    }
+
+    pub fn set_look(&mut self, yaw: f32, pitch: f32) {
+        self.dest_yaw = yaw;
+        self.dest_pitch = pitch.max(-90.0).min(90.0);
+    }
+
+    /// Returns true if use button was pressed since last call.
+    pub fn take_use_request(&mut self) -> bool {
+        std::mem::replace(&mut self.controller.use_object, false)
+    }
+
+    /// Position of camera, used to find object that player looks at.
+    pub fn head_position(&self) -> Vec3 {
+        self.head_position
+    }
+
+    pub fn look_direction(&self) -> Vec3 {
+        self.look_direction
+    }
+
     fn update_movement(&mut self, context: &mut UpdateContext) {
         let pivot = &context.scene.graph[self.character.pivot];
         let look = pivot.look_vector();
@@ -272,6 +622,14 @@ impl Player {
         if self.controller.move_right {
             velocity -= side;
         }
+        if context.frozen {
+            velocity = Vec3::ZERO;
+        }
+
+        // Toggled running ends when player stops, held running lasts while button is held.
+        if velocity.normalized().is_none() && self.control_scheme.as_ref().unwrap().borrow().toggle_sprint {
+            self.controller.run = false;
+        }
 
         let speed_mult = if self.controller.run {
             self.run_speed_multiplier
@@ -279,26 +637,47 @@ impl Player {
             1.0
         };
 
+        // Body velocity is displacement per physics step, so speeds are scaled by frame time.
+        let dt = context.time.delta;
         let body = context.scene.physics.borrow_body_mut(self.character.body);
         if let Some(normalized_velocity) = velocity.normalized() {
-            body.set_x_velocity(normalized_velocity.x * self.move_speed * speed_mult);
-            body.set_z_velocity(normalized_velocity.z * self.move_speed * speed_mult);
+            let x_velocity = normalized_velocity.x * self.move_speed * speed_mult * dt;
+            let z_velocity = normalized_velocity.z * self.move_speed * speed_mult * dt;
+            if has_ground_contact {
+                body.set_x_velocity(x_velocity);
+                body.set_z_velocity(z_velocity);
+            } else {
+                // Movement in air only steers, so momentum of knockback is not lost.
+                let k = frame_blend_factor(AIR_CONTROL, dt);
+                let current = body.get_velocity();
+                body.set_x_velocity(current.x + (x_velocity - current.x) * k);
+                body.set_z_velocity(current.z + (z_velocity - current.z) * k);
+            }
 
             self.weapon_dest_offset.x = 0.01 * (self.weapon_shake_factor * 0.5).cos();
             self.weapon_dest_offset.y = 0.005 * self.weapon_shake_factor.sin();
-            self.weapon_shake_factor += 0.23;
+            self.weapon_shake_factor += WEAPON_SHAKE_SPEED * dt;
 
             if has_ground_contact {
-                let k = (context.time.elapsed * 15.0) as f32;
-                self.camera_dest_offset.x = 0.05 * (k * 0.5).cos();
-                self.camera_dest_offset.y = 0.1 * k.sin();
-                self.path_len += 0.1;
+                self.path_len += PATH_SPEED * speed_mult * dt;
             }
         } else {
             self.weapon_dest_offset = Vec3::ZERO;
         }
 
-        self.weapon_offset.follow(&self.weapon_dest_offset, 0.1);
+        // Head bob is synced with footsteps: camera is at its lowest point when foot touches
+        // the ground and sways to the side of stepping foot.
+        if velocity.normalized().is_some() && has_ground_contact {
+            let intensity = self.control_scheme.as_ref().unwrap().borrow().head_bob *
+                if self.controller.run { RUN_HEAD_BOB_FACTOR } else { 1.0 };
+            let step_phase = (self.path_len / STEP_LENGTH).min(1.0) * std::f32::consts::PI;
+            self.camera_dest_offset.x = self.step_side * HEAD_BOB_SWAY * intensity * step_phase.sin();
+            self.camera_dest_offset.y = -HEAD_BOB_HEIGHT * intensity * 0.5 * (1.0 + (2.0 * step_phase).cos());
+        } else {
+            self.camera_dest_offset = Vec3::ZERO;
+        }
+
+        self.weapon_offset.follow(&self.weapon_dest_offset, frame_blend_factor(0.1, dt));
 
         context.scene
             .graph[self.character.weapon_pivot]
@@ -306,25 +685,71 @@ impl Player {
             .set_position(self.weapon_offset);
 
         if self.controller.jump {
-            if has_ground_contact {
-                body.set_y_velocity(0.07);
-            }
             self.controller.jump = false;
+            self.controller.jump_buffer.press(self.input_buffer());
+        }
+        if has_ground_contact && !context.frozen && self.controller.jump_buffer.take() {
+            body.set_y_velocity(JUMP_SPEED * dt);
         }
+        self.controller.jump_buffer.update(dt);
 
-        self.handle_crouch(body);
+        self.handle_crouch(body, dt);
 
         self.feet_position = body.get_position();
         self.feet_position.y -= body.get_shape().as_capsule().get_height();
 
         if self.control_scheme.as_ref().unwrap().borrow().shake_camera {
-            self.camera_offset.follow(&self.camera_dest_offset, 0.1);
+            self.camera_offset.follow(&self.camera_dest_offset, frame_blend_factor(0.1, dt));
         } else {
             self.camera_offset = Vec3::ZERO;
         }
 
+        let current_weapon = self.character.current_weapon();
+        if current_weapon != self.zoom_weapon {
+            self.zoom_weapon = current_weapon;
+            self.zoom_index = 0;
+            self.zoom_levels = if current_weapon.is_some() {
+                let definition = context.weapons[current_weapon].definition;
+                &definition.zoom_levels
+            } else {
+                &[]
+            };
+        }
+
+        let ads_target = if self.controller.aim { 1.0 } else { 0.0 };
+        self.ads_factor += (ads_target - self.ads_factor) * frame_blend_factor(0.25, dt);
+        let zoom_fov = self.zoom_levels.get(self.zoom_index).map_or(ADS_FOV, |magnification| scope_fov(*magnification));
+        self.ads_fov += (zoom_fov - self.ads_fov) * frame_blend_factor(0.25, dt);
+        let fov = HIP_FOV + (self.ads_fov - HIP_FOV) * self.ads_factor;
+        if let Node::Camera(camera) = &mut context.scene.graph[self.camera] {
+            camera.set_fov(fov.to_radians());
+        }
+
+        let target_factor = match self.camera_mode {
+            CameraMode::FirstPerson => 0.0,
+            CameraMode::ThirdPerson => 1.0,
+        };
+        self.third_person_factor += (target_factor - self.third_person_factor) * frame_blend_factor(0.15, dt);
+
+        // Show body only when camera is far enough from head, weapon is hidden at the same
+        // time because it is attached to camera.
+        let is_third_person = self.remote || self.third_person_factor > 0.5;
+        context.scene.graph[self.model].set_visibility(is_third_person);
+        context.scene.graph[self.character.weapon_pivot].set_visibility(!is_third_person);
+
+        // Screen shake only moves camera, its rotation is untouched so aim direction stays
+        // the same. Shake is squared to make weak shakes barely noticeable.
+        self.shake_trauma = (self.shake_trauma - SHAKE_DECAY * context.time.delta).max(0.0);
+        let shake_strength = self.shake_trauma * self.shake_trauma * MAX_SHAKE_OFFSET *
+            self.control_scheme.as_ref().unwrap().borrow().screen_shake;
+        let t = context.time.elapsed as f32;
+        let shake_offset = Vec3::new((t * 37.0).sin(), (t * 43.0 + 1.3).sin(), 0.0).scale(shake_strength);
+
+        // Weapons shoot along camera's look vector, so aiming works the same in both modes.
         let camera_node = &mut context.scene.graph[self.camera];
-        camera_node.local_transform_mut().set_position(self.camera_offset);
+        camera_node
+            .local_transform_mut()
+            .set_position(self.camera_offset + shake_offset + THIRD_PERSON_CAMERA_OFFSET.scale(self.third_person_factor));
 
         self.head_position = camera_node.global_position();
         self.look_direction = camera_node.look_vector();
@@ -333,9 +758,55 @@ impl Player {
                                                  camera_node.up_vector(),
                                                  -camera_node.look_vector());
 
-        if self.control_scheme.clone().unwrap().borrow().smooth_mouse {
-            self.yaw += (self.dest_yaw - self.yaw) * 0.2;
-            self.pitch += (self.dest_pitch - self.pitch) * 0.2;
+        // Weapon is pulled back when camera is close to a wall, so it does not poke through.
+        let wall_distance = Ray::from_two_points(&self.head_position, &(self.head_position + self.look_direction.scale(WEAPON_CLIP_DISTANCE)))
+            .and_then(|ray| {
+                let options = RayCastOptions {
+                    ignore_bodies: true,
+                    ignore_static_geometries: false,
+                    sort_results: true,
+                };
+                let mut result = Vec::new();
+                if context.scene.physics.ray_cast(&ray, options, &mut result) {
+                    result.first().map(|hit| hit.position.distance(&self.head_position))
+                } else {
+                    None
+                }
+            });
+        let target_pullback = wall_distance.map_or(0.0, |distance| (WEAPON_CLIP_DISTANCE - distance).min(MAX_WEAPON_PULLBACK));
+        self.weapon_pullback += (target_pullback - self.weapon_pullback) * frame_blend_factor(0.3, dt);
+
+        // Weapon is stretched across view direction by the ratio of fields of view, this gives
+        // the same picture as rendering weapon with its own field of view, so weapon does not
+        // grow when camera zooms in.
+        let k = (fov.to_radians() * 0.5).tan() / (WEAPON_FOV.to_radians() * 0.5).tan();
+        let weapon_base_pivot = context.scene.graph[self.character.weapon_pivot].parent();
+        context.scene
+            .graph[weapon_base_pivot]
+            .local_transform_mut()
+            .set_position(Vec3::new(
+                WEAPON_BASE_POSITION.x * k,
+                WEAPON_BASE_POSITION.y * k - self.weapon_pullback * 0.5,
+                WEAPON_BASE_POSITION.z - self.weapon_pullback))
+            .set_scale(Vec3::new(k, k, 1.0));
+
+        // Recovery moves aim back along the kick, so recoil pattern is retraced in reverse.
+        let (up, right) = self.recoil;
+        let distance = (up * up + right * right).sqrt();
+        if distance > 0.0 {
+            let k = (self.recoil_recovery_speed * context.time.delta).min(distance) / distance;
+            self.dest_pitch = (self.dest_pitch + up * k).min(90.0);
+            self.dest_yaw += right * k;
+            self.recoil = (up * (1.0 - k), right * (1.0 - k));
+        }
+
+        let control_scheme = self.control_scheme.clone().unwrap();
+        let control_scheme = control_scheme.borrow();
+        if control_scheme.smooth_mouse {
+            // Blend factor is scaled by frame time, so smoothing feels the same at any frame rate.
+            let k = frame_blend_factor(1.0 - control_scheme.mouse_smoothing, context.time.delta);
+            self.yaw += (self.dest_yaw - self.yaw) * k;
+            self.pitch += (self.dest_pitch - self.pitch) * k;
         } else {
             self.yaw = self.dest_yaw;
             self.pitch = self.dest_pitch;
@@ -359,11 +830,60 @@ impl Player {
         listener.set_position(self.head_position);
     }
 
+    /// Adds screen shake, `amount` is in [0; 1] range where 1 is the strongest shake.
+    pub fn add_shake(&mut self, amount: f32) {
+        self.shake_trauma = (self.shake_trauma + amount).min(1.0);
+    }
+
+    pub fn set_health_regen(&mut self, health_regen: HealthRegen) {
+        self.health_regen = health_regen;
+    }
+
+    /// Postpones health regeneration, must be called whenever player takes damage.
+    pub fn reset_regen(&mut self) {
+        self.regen_delay_left = self.health_regen.delay;
+    }
+
+    fn update_regen(&mut self, dt: f32) {
+        if !self.health_regen.is_enabled() || self.character.is_dead() {
+            return;
+        }
+        if self.regen_delay_left > 0.0 {
+            self.regen_delay_left -= dt;
+        } else if self.character.health < self.health_regen.cap {
+            // Health above cap from pickups is kept, regeneration just does not add to it.
+            self.character.health = (self.character.health + self.health_regen.rate * dt).min(self.health_regen.cap);
+        }
+    }
+
+    /// Kicks aim up and right by given degrees, `recoil` of weapon tells how much of kick is
+    /// recovered and how fast.
+    pub fn add_recoil(&mut self, kick: (f32, f32), recoil: &RecoilDefinition) {
+        self.dest_pitch = (self.dest_pitch - kick.0).max(-90.0);
+        self.dest_yaw -= kick.1;
+        self.recoil.0 += kick.0 * recoil.recovery;
+        self.recoil.1 += kick.1 * recoil.recovery;
+        self.recoil_recovery_speed = recoil.recovery_speed;
+    }
+
+    /// Magnification of scope while player aims through it, None otherwise.
+    pub fn zoom(&self) -> Option<f32> {
+        if self.controller.aim {
+            self.zoom_levels.get(self.zoom_index).copied()
+        } else {
+            None
+        }
+    }
+
     pub fn can_be_removed(&self) -> bool {
         self.character.is_dead()
     }
 
     #[allow(clippy::cognitive_complexity)]
+    pub fn reset_input(&mut self) {
+        self.controller = Default::default();
+    }
+
     pub fn process_input_event(&mut self, event: &Event<()>) -> bool {
         if let Event::DeviceEvent { event, .. } = event {
             if let Some(control_scheme) = self.control_scheme.clone() {
@@ -374,15 +894,33 @@ impl Player {
 
                 match event {
                     DeviceEvent::MouseMotion { delta } => {
-                        self.dest_yaw -= delta.0 as f32 * control_scheme.mouse_sens;
+                        let k = if control_scheme.mouse_acceleration {
+                            mouse_acceleration_factor(*delta)
+                        } else {
+                            1.0
+                        };
+                        // Sensitivity follows field of view while entering or leaving sights,
+                        // so there is no jump in turn speed. Scope slows it down further by
+                        // the ratio of its field of view to field of view of regular sights.
+                        let zoom = (self.ads_fov.to_radians() * 0.5).tan() / (ADS_FOV.to_radians() * 0.5).tan();
+                        let k = k * (1.0 + (control_scheme.ads_sensitivity * zoom - 1.0) * self.ads_factor);
+
+                        let yaw_delta = delta.0 as f32 * control_scheme.mouse_sens_x * k;
+                        self.dest_yaw -= yaw_delta;
 
                         let sens = if control_scheme.mouse_y_inverse {
-                            -control_scheme.mouse_sens
+                            -control_scheme.mouse_sens_y * k
                         } else {
-                            control_scheme.mouse_sens
+                            control_scheme.mouse_sens_y * k
                         };
 
-                        self.dest_pitch += delta.1 as f32 * sens;
+                        let pitch_delta = delta.1 as f32 * sens;
+                        self.dest_pitch += pitch_delta;
+
+                        // Pulling against recoil does the job of recovery, so aim is not
+                        // moved back twice.
+                        self.recoil.0 = compensate_recoil(self.recoil.0, pitch_delta);
+                        self.recoil.1 = compensate_recoil(self.recoil.1, -yaw_delta);
                         if self.dest_pitch > 90.0 {
                             self.dest_pitch = 90.0;
                         } else if self.dest_pitch < -90.0 {
@@ -396,15 +934,21 @@ impl Player {
                     }
 
                     DeviceEvent::Key(input) => {
-                        if let Some(code) = input.virtual_keycode {
-                            control_button = Some(ControlButton::Key(code));
-                            control_button_state = input.state;
-                        }
+                        control_button = Some(ControlButton::from_key(input));
+                        control_button_state = input.state;
                     }
 
                     DeviceEvent::MouseWheel { delta } => {
                         if let MouseScrollDelta::LineDelta(_, y) = delta {
-                            if *y < 0.0 {
+                            // Wheel changes magnification while player aims through scope,
+                            // player stays in sights and keeps the weapon.
+                            if self.controller.aim && !self.zoom_levels.is_empty() {
+                                if *y > 0.0 {
+                                    self.zoom_index = (self.zoom_index + 1).min(self.zoom_levels.len() - 1);
+                                } else if *y < 0.0 {
+                                    self.zoom_index = self.zoom_index.saturating_sub(1);
+                                }
+                            } else if *y < 0.0 {
                                 self.prev_weapon();
                             } else if *y > 0.0 {
                                 self.next_weapon();
@@ -420,6 +964,12 @@ impl Player {
                         ElementState::Pressed => {
                             if control_button == control_scheme.shoot.button {
                                 self.controller.shoot = true;
+                                self.controller.shoot_buffer.press(control_scheme.input_buffer);
+                            } else if control_button == control_scheme.alt_fire.button {
+                                self.controller.alt_fire = true;
+                                self.controller.alt_fire_buffer.press(control_scheme.input_buffer);
+                            } else if control_button == control_scheme.aim.button {
+                                self.controller.aim = true;
                             } else if control_button == control_scheme.move_forward.button {
                                 self.controller.move_forward = true;
                             } else if control_button == control_scheme.move_backward.button {
@@ -431,14 +981,42 @@ impl Player {
                             } else if control_button == control_scheme.crouch.button {
                                 self.controller.crouch = true;
                             } else if control_button == control_scheme.run.button {
-                                self.controller.run = true;
+                                self.controller.run = !control_scheme.toggle_sprint || !self.controller.run;
                             } else if control_button == control_scheme.jump.button {
                                 self.controller.jump = true;
+                            } else if control_button == control_scheme.toggle_camera.button {
+                                self.toggle_camera_mode();
+                            } else if control_button == control_scheme.reload.button {
+                                let weapon = self.character.current_weapon();
+                                if weapon.is_some() {
+                                    self.character.sender.as_ref().unwrap().send(Message::ReloadWeapon {
+                                        weapon
+                                    }).unwrap();
+                                }
+                            } else if control_button == control_scheme.use_object.button {
+                                self.controller.use_object = true;
+                            } else if control_button == control_scheme.inspect_weapon.button {
+                                let weapon = self.character.current_weapon();
+                                if weapon.is_some() {
+                                    self.character.sender.as_ref().unwrap().send(Message::InspectWeapon {
+                                        weapon
+                                    }).unwrap();
+                                }
                             }
                         }
                         ElementState::Released => {
                             if control_button == control_scheme.shoot.button {
                                 self.controller.shoot = false;
+                                let weapon = self.character.current_weapon();
+                                if weapon.is_some() {
+                                    self.character.sender.as_ref().unwrap().send(Message::ReleaseTrigger {
+                                        weapon
+                                    }).unwrap();
+                                }
+                            } else if control_button == control_scheme.alt_fire.button {
+                                self.controller.alt_fire = false;
+                            } else if control_button == control_scheme.aim.button {
+                                self.controller.aim = false;
                             } else if control_button == control_scheme.move_forward.button {
                                 self.controller.move_forward = false;
                             } else if control_button == control_scheme.move_backward.button {
@@ -449,7 +1027,7 @@ impl Player {
                                 self.controller.move_right = false;
                             } else if control_button == control_scheme.crouch.button {
                                 self.controller.crouch = false;
-                            } else if control_button == control_scheme.run.button {
+                            } else if control_button == control_scheme.run.button && !control_scheme.toggle_sprint {
                                 self.controller.run = false;
                             }
                         }
@@ -461,7 +1039,7 @@ impl Player {
         false
     }
 
-    pub fn update(&mut self, context: &mut UpdateContext) {
+    pub fn update(&mut self, self_handle: Handle<Actor>, context: &mut UpdateContext) {
         self.update_movement(context);
 
         if let Some(current_weapon_handle) = self.character.weapons.get(self.character.current_weapon as usize) {
@@ -470,39 +1048,77 @@ impl Player {
                 .borrow_body(self.character.body)
                 .get_velocity();
 
-            if self.controller.shoot {
-                self.character.sender.as_ref().unwrap().send(Message::ShootWeapon {
-                    weapon: *current_weapon_handle,
-                    initial_velocity: velocity,
-                    direction: None,
-                }).unwrap();
+            // Shots are requested only when weapon is ready, so buffered press is consumed
+            // by the shot it causes and not by an attempt that weapon would ignore.
+            let weapon = &context.weapons[*current_weapon_handle];
+            if !context.frozen && weapon.is_ready_to_shoot(context.time.elapsed) {
+                let buffered = self.controller.shoot_buffer.take();
+                if self.controller.shoot || buffered {
+                    self.character.sender.as_ref().unwrap().send(Message::ShootWeapon {
+                        weapon: *current_weapon_handle,
+                        initial_velocity: velocity,
+                        direction: None,
+                    }).unwrap();
+                }
+            }
+
+            // Held alt fire repeats at the pace of its own interval, same as primary fire.
+            if !context.frozen && weapon.is_alt_fire_ready(context.time.elapsed) {
+                let buffered = self.controller.alt_fire_buffer.take();
+                if self.controller.alt_fire || buffered {
+                    self.character.sender.as_ref().unwrap().send(Message::AltFireWeapon {
+                        weapon: *current_weapon_handle,
+                        initial_velocity: velocity,
+                    }).unwrap();
+                }
             }
         }
+        self.controller.shoot_buffer.update(context.time.delta);
+        self.controller.alt_fire_buffer.update(context.time.delta);
 
-        if self.path_len > 2.0 {
+        self.update_regen(context.time.delta);
+
+        if self.path_len > STEP_LENGTH {
             let footsteps = [
                 "data/sounds/footsteps/FootStep_shoe_stone_step1.wav",
                 "data/sounds/footsteps/FootStep_shoe_stone_step2.wav",
                 "data/sounds/footsteps/FootStep_shoe_stone_step3.wav",
                 "data/sounds/footsteps/FootStep_shoe_stone_step4.wav"
             ];
-            self.character
-                .sender
-                .as_ref()
-                .unwrap()
+            let position = self.character.position(&context.scene.physics);
+            let sender = self.character.sender.as_ref().unwrap();
+            sender
                 .send(Message::PlaySound {
                     path: footsteps[rand::thread_rng().gen_range(0, footsteps.len())].into(),
-                    position: self.character.position(&context.scene.physics),
+                    position,
                     gain: 1.0,
                     rolloff_factor: 2.0,
                     radius: 3.0,
+                    priority: SoundPriority::Low,
+                })
+                .unwrap();
+            let loudness = if self.controller.crouch {
+                FOOTSTEP_LOUDNESS.0
+            } else if self.controller.run {
+                FOOTSTEP_LOUDNESS.2
+            } else {
+                FOOTSTEP_LOUDNESS.1
+            };
+            sender
+                .send(Message::Footstep {
+                    actor: self_handle,
+                    position,
+                    loudness,
                 })
                 .unwrap();
 
             self.path_len = 0.0;
+            self.step_side = -self.step_side;
         }
 
-        self.update_listener(context.sound_context.clone());
+        if !self.remote {
+            self.update_listener(context.sound_context.clone());
+        }
     }
 
     pub fn clean_up(&mut self, scene: &mut Scene) {
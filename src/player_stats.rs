@@ -0,0 +1,97 @@
+//! Lifetime stats of the player that are accumulated over every finished match. Stats are
+//! stored in binary file using the same visitor mechanism as settings.
+
+use crate::{
+    leader_board::{self, PersonalScore},
+    weapon::WeaponKind,
+};
+use rg3d::core::visitor::{Visit, Visitor, VisitResult};
+use std::path::Path;
+
+const STATS_PATH: &str = "stats.bin";
+
+pub struct PlayerStats {
+    pub matches_played: u32,
+    pub kills: u32,
+    pub deaths: u32,
+    /// Kills made with each weapon, indexed by weapon kind id.
+    pub weapon_kills: Vec<u32>,
+}
+
+impl Default for PlayerStats {
+    fn default() -> Self {
+        Self {
+            matches_played: 0,
+            kills: 0,
+            deaths: 0,
+            weapon_kills: Default::default(),
+        }
+    }
+}
+
+impl Visit for PlayerStats {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.matches_played.visit("MatchesPlayed", visitor)?;
+        self.kills.visit("Kills", visitor)?;
+        self.deaths.visit("Deaths", visitor)?;
+        self.weapon_kills.visit("WeaponKills", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl PlayerStats {
+    /// Loads stats from stats file, empty stats are used if there is no such file or it is
+    /// corrupted.
+    pub fn load() -> Self {
+        match Visitor::load_binary(Path::new(STATS_PATH)) {
+            Ok(mut visitor) => {
+                let mut stats = PlayerStats::default();
+                match stats.visit("PlayerStats", &mut visitor) {
+                    Ok(_) => stats,
+                    Err(e) => {
+                        println!("Unable to read player stats, they will be reset. Reason: {:?}", e);
+                        PlayerStats::default()
+                    }
+                }
+            }
+            Err(_) => PlayerStats::default(),
+        }
+    }
+
+    pub fn save(&mut self) {
+        let mut visitor = Visitor::new();
+        let result = self.visit("PlayerStats", &mut visitor)
+            .and_then(|_| visitor.save_binary(Path::new(STATS_PATH)));
+        if let Err(e) = result {
+            println!("Unable to save player stats. Reason: {:?}", e);
+        }
+    }
+
+    /// Adds score of the player in a finished match.
+    pub fn add_match(&mut self, score: &PersonalScore) {
+        self.matches_played += 1;
+        self.kills += score.kills;
+        self.deaths += score.deaths;
+        if self.weapon_kills.len() < score.weapon_kills.len() {
+            self.weapon_kills.resize(score.weapon_kills.len(), 0);
+        }
+        for (total, kills) in self.weapon_kills.iter_mut().zip(score.weapon_kills.iter()) {
+            *total += kills;
+        }
+    }
+
+    pub fn weapon_kills(&self, weapon: WeaponKind) -> u32 {
+        self.weapon_kills
+            .get(weapon.id() as usize)
+            .cloned()
+            .unwrap_or(0)
+    }
+
+    /// Weapon with most kills over all matches, None if there are no kills made with a weapon.
+    pub fn best_weapon(&self) -> Option<WeaponKind> {
+        leader_board::weapon_with_most_kills(&self.weapon_kills)
+    }
+}
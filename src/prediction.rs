@@ -0,0 +1,276 @@
+//! Client side of network match. Local player moves right away on input and its predicted
+//! positions are remembered, when server acknowledges an input its authoritative position
+//! is compared with prediction made for the same input. Other actors are shown a bit in
+//! the past, interpolated between two received snapshots, so they move smoothly even
+//! though snapshots arrive unevenly.
+
+use crate::net::{ActorState, NetId};
+use rg3d::core::math::vec3::Vec3;
+use std::collections::VecDeque;
+
+/// Other actors are shown this much in the past, in seconds. Must cover a few snapshot
+/// intervals, so there is almost always a newer snapshot to interpolate to.
+pub const INTERPOLATION_DELAY: f64 = 0.1;
+/// Snapshots older than this are not needed for interpolation, in seconds.
+const SNAPSHOT_MEMORY: f64 = 1.0;
+/// Amount of predictions kept while server does not acknowledge input, about two seconds
+/// at highest tick rate.
+const MAX_PREDICTIONS: usize = 256;
+/// Prediction that differs from server position less than this is considered correct, in
+/// meters. Small errors come from different frame timing and are not worth a visible jump.
+const PREDICTION_TOLERANCE: f32 = 0.1;
+
+/// Returns true if sequence `a` goes before `b`, sequences wrap around.
+fn is_before(a: u32, b: u32) -> bool {
+    (a.wrapping_sub(b) as i32) < 0
+}
+
+/// Positions of local player predicted for inputs that server has not acknowledged yet.
+#[derive(Default)]
+pub struct PredictionHistory {
+    predictions: VecDeque<(u32, Vec3)>,
+}
+
+impl PredictionHistory {
+    /// Remembers position of player after input with given sequence was applied.
+    pub fn record(&mut self, sequence: u32, position: Vec3) {
+        if self.predictions.len() >= MAX_PREDICTIONS {
+            self.predictions.pop_front();
+        }
+        self.predictions.push_back((sequence, position));
+    }
+
+    pub fn clear(&mut self) {
+        self.predictions.clear();
+    }
+
+    /// Compares prediction for acknowledged input with position from server and returns
+    /// correction that must be added to current position of player, None if prediction was
+    /// right. Physics can't step one body alone, so inputs that are not acknowledged yet are
+    /// not simulated again, instead their predictions are moved by the same correction.
+    pub fn reconcile(&mut self, acknowledged: u32, server_position: Vec3) -> Option<Vec3> {
+        while let Some(&(sequence, _)) = self.predictions.front() {
+            if is_before(sequence, acknowledged) {
+                self.predictions.pop_front();
+            } else {
+                break;
+            }
+        }
+        match self.predictions.front() {
+            Some(&(sequence, predicted)) if sequence == acknowledged => {
+                self.predictions.pop_front();
+                let error = server_position - predicted;
+                if error.len() > PREDICTION_TOLERANCE {
+                    for (_, position) in self.predictions.iter_mut() {
+                        *position += error;
+                    }
+                    Some(error)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Interpolates angle in degrees along the shortest arc.
+fn lerp_angle(a: f32, b: f32, k: f32) -> f32 {
+    let delta = (b - a + 180.0).rem_euclid(360.0) - 180.0;
+    a + delta * k
+}
+
+fn find(states: &[ActorState], id: NetId) -> Option<&ActorState> {
+    states.iter().find(|state| state.id == id)
+}
+
+/// Snapshots received from server with time of arrival.
+#[derive(Default)]
+pub struct SnapshotBuffer {
+    snapshots: VecDeque<(f64, Vec<ActorState>)>,
+}
+
+impl SnapshotBuffer {
+    pub fn push(&mut self, time: f64, states: Vec<ActorState>) {
+        self.snapshots.push_back((time, states));
+        // Oldest snapshot is kept while next one is still needed for interpolation.
+        while self.snapshots.len() > 2 && time - self.snapshots[1].0 > SNAPSHOT_MEMORY {
+            self.snapshots.pop_front();
+        }
+    }
+
+    pub fn latest(&self) -> Option<&[ActorState]> {
+        self.snapshots.back().map(|(_, states)| states.as_slice())
+    }
+
+    /// Returns state of every actor at given time, interpolated between snapshots that
+    /// arrived before and after it. Actors that are missing in the older snapshot are taken
+    /// as is, there is no extrapolation past the latest snapshot.
+    pub fn sample(&self, time: f64) -> Vec<ActorState> {
+        let next = match self.snapshots.iter().position(|(arrival, _)| *arrival > time) {
+            Some(next) if next > 0 => next,
+            // Time is before the oldest snapshot or after the latest one.
+            Some(_) => return self.snapshots.front().map_or_else(Vec::new, |(_, states)| states.clone()),
+            None => return self.snapshots.back().map_or_else(Vec::new, |(_, states)| states.clone()),
+        };
+        let (from_time, from) = &self.snapshots[next - 1];
+        let (to_time, to) = &self.snapshots[next];
+        let k = ((time - from_time) / (to_time - from_time)) as f32;
+        to.iter()
+            .map(|state| match find(from, state.id) {
+                Some(previous) => ActorState {
+                    position: previous.position + (state.position - previous.position).scale(k),
+                    yaw: lerp_angle(previous.yaw, state.yaw, k),
+                    pitch: previous.pitch + (state.pitch - previous.pitch) * k,
+                    ..*state
+                },
+                None => *state,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::net::{Client, ClientEvent, NetSimulation, PlayerInput, Server, ServerEvent};
+    use rg3d::core::pool::Handle;
+    use std::time::{Duration, Instant};
+
+    fn state(index: u32, position: Vec3, yaw: f32) -> ActorState {
+        ActorState {
+            id: NetId::from(Handle::new(index, 1)),
+            position,
+            yaw,
+            pitch: 0.0,
+            health: 100.0,
+        }
+    }
+
+    #[test]
+    fn acknowledged_inputs_are_trimmed() {
+        let mut history = PredictionHistory::default();
+        for sequence in 1..=5 {
+            history.record(sequence, Vec3::new(sequence as f32, 0.0, 0.0));
+        }
+        assert_eq!(history.reconcile(3, Vec3::new(3.0, 0.0, 0.0)), None);
+        let sequences = history.predictions.iter().map(|(sequence, _)| *sequence).collect::<Vec<_>>();
+        assert_eq!(sequences, vec![4, 5]);
+    }
+
+    #[test]
+    fn trimming_handles_wrapped_sequences() {
+        let mut history = PredictionHistory::default();
+        for sequence in [std::u32::MAX - 1, std::u32::MAX, 0, 1].iter() {
+            history.record(*sequence, Vec3::default());
+        }
+        history.reconcile(0, Vec3::default());
+        let sequences = history.predictions.iter().map(|(sequence, _)| *sequence).collect::<Vec<_>>();
+        assert_eq!(sequences, vec![1]);
+    }
+
+    #[test]
+    fn wrong_prediction_is_corrected() {
+        let mut history = PredictionHistory::default();
+        history.record(1, Vec3::new(1.0, 0.0, 0.0));
+        history.record(2, Vec3::new(2.0, 0.0, 0.0));
+        history.record(3, Vec3::new(3.0, 0.0, 0.0));
+
+        // Server put player half a meter aside, later predictions move with it.
+        let correction = history.reconcile(1, Vec3::new(1.0, 0.0, 0.5));
+        assert_eq!(correction, Some(Vec3::new(0.0, 0.0, 0.5)));
+        assert_eq!(history.predictions[0], (2, Vec3::new(2.0, 0.0, 0.5)));
+        assert_eq!(history.predictions[1], (3, Vec3::new(3.0, 0.0, 0.5)));
+    }
+
+    #[test]
+    fn small_error_is_tolerated() {
+        let mut history = PredictionHistory::default();
+        history.record(1, Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(history.reconcile(1, Vec3::new(1.0, 0.0, PREDICTION_TOLERANCE * 0.5)), None);
+    }
+
+    #[test]
+    fn snapshots_are_interpolated() {
+        let mut buffer = SnapshotBuffer::default();
+        buffer.push(1.0, vec![state(1, Vec3::new(0.0, 0.0, 0.0), 350.0)]);
+        buffer.push(2.0, vec![state(1, Vec3::new(10.0, 0.0, 0.0), 10.0), state(2, Vec3::new(5.0, 0.0, 0.0), 0.0)]);
+
+        let sampled = buffer.sample(1.25);
+        assert_eq!(sampled.len(), 2);
+        assert_eq!(sampled[0].position, Vec3::new(2.5, 0.0, 0.0));
+        // Yaw goes through 0 instead of turning back over 180 degrees.
+        assert!((sampled[0].yaw - 355.0).abs() < 1e-3);
+        // Actor that appeared in the newer snapshot is taken as is.
+        assert_eq!(sampled[1].position, Vec3::new(5.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn sampling_out_of_buffer_takes_nearest_snapshot() {
+        let mut buffer = SnapshotBuffer::default();
+        assert!(buffer.sample(1.0).is_empty());
+        buffer.push(1.0, vec![state(1, Vec3::new(0.0, 0.0, 0.0), 0.0)]);
+        buffer.push(2.0, vec![state(1, Vec3::new(10.0, 0.0, 0.0), 0.0)]);
+        assert_eq!(buffer.sample(0.5)[0].position, Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(buffer.sample(3.0)[0].position, Vec3::new(10.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn prediction_holds_under_latency() {
+        let simulation = NetSimulation {
+            latency: 0.03,
+            ..Default::default()
+        };
+        let mut server = Server::bind(0, 2, "DM6", "Deathmatch").unwrap();
+        let mut client = Client::connect(("127.0.0.1", server.port().unwrap()), 0.0).unwrap();
+        server.set_simulation(simulation);
+        client.set_simulation(simulation);
+
+        let start = Instant::now();
+        let mut accepted = false;
+        while !accepted {
+            assert!(start.elapsed() < Duration::from_secs(2), "client was not accepted");
+            let time = start.elapsed().as_secs_f64();
+            server.poll(time);
+            accepted = client.poll(time).iter().any(|event| matches!(event, ClientEvent::Accepted));
+            std::thread::sleep(Duration::from_millis(5));
+        }
+
+        // Client and server move player by the same step for every input, so prediction is
+        // always right and must never be corrected, no matter how late server answers.
+        let step = Vec3::new(0.1, 0.0, 0.0);
+        let mut history = PredictionHistory::default();
+        let mut predicted = Vec3::default();
+        let mut authoritative = state(1, Vec3::default(), 0.0);
+        let mut last_sequence = 0;
+        let mut acknowledgements = 0;
+        let mut max_lag = 0;
+        for _ in 0..100 {
+            let time = start.elapsed().as_secs_f64();
+            if let Some(sequence) = client.send_input(PlayerInput { move_forward: true, ..Default::default() }) {
+                predicted += step;
+                history.record(sequence, predicted);
+                last_sequence = sequence;
+            }
+            for event in server.poll(time) {
+                if let ServerEvent::Input { input, .. } = event {
+                    if input.move_forward {
+                        authoritative.position += step;
+                    }
+                }
+            }
+            server.send_snapshot(&[authoritative], |_| Some(authoritative.id));
+            for event in client.poll(time) {
+                if let ClientEvent::Snapshot { acknowledged_input: Some(acknowledged), actors, .. } = event {
+                    acknowledgements += 1;
+                    max_lag = max_lag.max(last_sequence.wrapping_sub(acknowledged));
+                    assert_eq!(history.reconcile(acknowledged, actors[0].position), None);
+                }
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        assert!(acknowledgements > 0);
+        // Server really answered late, client was several inputs ahead of it.
+        assert!(max_lag > 1);
+    }
+}
@@ -14,16 +14,23 @@ use rg3d::{
         convex_shape::{ConvexShape, SphereShape},
         RayCastOptions, rigid_body::{RigidBody, CollisionFlags},
         HitKind,
+        Physics,
     },
     core::{
         visitor::{Visit, VisitResult, Visitor},
-        pool::{Handle, Pool, PoolIteratorMut},
+        pool::{Handle, Pool, PoolIterator, PoolIteratorMut, PoolPairIterator},
         color::Color,
         math::{vec3::Vec3, ray::Ray, quat::Quat, mat3::Mat3},
     },
 };
 use crate::{
     GameTime,
+    SoundPriority,
+    instantiate_model,
+    frame_blend_factor,
+    REFERENCE_FPS,
+    definitions,
+    settings::parse_f32,
     actor::{
         ActorContainer,
         Actor,
@@ -35,6 +42,8 @@ use crate::{
     },
     message::Message,
     effects::EffectKind,
+    damage::{DamageKind, HitRegion, RocketJump, SelfDamage},
+    usable::Usable,
 };
 use std::{
     sync::mpsc::Sender,
@@ -42,19 +51,63 @@ use std::{
 use rand::Rng;
 use std::path::PathBuf;
 
+/// Maximum amount of projectiles alive at the same time. When limit is reached, projectile
+/// that lived the largest part of its lifetime is removed to free space for a new one.
+const MAX_PROJECTILES: usize = 256;
+
+/// Projectiles that flew farther than this from the point where they were fired are removed,
+/// they won't hit anything anyway.
+const MAX_TRAVEL_DISTANCE: f32 = 250.0;
+
+/// Part of upward direction that is mixed into knockback direction, so actors that stand on
+/// the ground are lifted off it instead of being pressed into the floor.
+const KNOCKBACK_LIFT: f32 = 0.5;
+
+/// Rotation speed of sprite projectiles, in the same units as sprite rotation per second.
+const SPRITE_ROTATION_SPEED: f32 = 90.0;
+
+/// Distance from wall at which physical projectile that tunneled through it is put back,
+/// a bit more than radius of grenade.
+const TUNNELING_PUSH_BACK: f32 = 0.12;
+
+/// Part of speed that physical projectile keeps after bouncing off a wall it tunneled through.
+const TUNNELING_BOUNCE_DAMPING: f32 = 0.5;
+
+/// Time in seconds that actor needs to pick up live grenade before it can throw it back, fuse
+/// keeps burning meanwhile.
+pub const GRENADE_PICKUP_TIME: f32 = 0.4;
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum ProjectileKind {
     Plasma,
     Bullet,
     Rocket,
+    Grenade,
+    /// Makes cloud that blocks sight, see `smoke` module.
+    SmokeGrenade,
+    /// Blinds and deafens everyone who looks at it when it detonates.
+    Flashbang,
 }
 
+/// Every kind of projectile.
+pub const PROJECTILE_KINDS: [ProjectileKind; 6] = [
+    ProjectileKind::Plasma,
+    ProjectileKind::Bullet,
+    ProjectileKind::Rocket,
+    ProjectileKind::Grenade,
+    ProjectileKind::SmokeGrenade,
+    ProjectileKind::Flashbang,
+];
+
 impl ProjectileKind {
     pub fn new(id: u32) -> Result<Self, String> {
         match id {
             0 => Ok(ProjectileKind::Plasma),
             1 => Ok(ProjectileKind::Bullet),
             2 => Ok(ProjectileKind::Rocket),
+            3 => Ok(ProjectileKind::Grenade),
+            4 => Ok(ProjectileKind::SmokeGrenade),
+            5 => Ok(ProjectileKind::Flashbang),
             _ => Err(format!("Invalid projectile kind id {}", id))
         }
     }
@@ -64,8 +117,17 @@ impl ProjectileKind {
             ProjectileKind::Plasma => 0,
             ProjectileKind::Bullet => 1,
             ProjectileKind::Rocket => 2,
+            ProjectileKind::Grenade => 3,
+            ProjectileKind::SmokeGrenade => 4,
+            ProjectileKind::Flashbang => 5,
         }
     }
+
+    /// Returns true for grenades that do no damage, instead they have special effect when
+    /// they detonate.
+    pub fn is_tactical(self) -> bool {
+        matches!(self, ProjectileKind::SmokeGrenade | ProjectileKind::Flashbang)
+    }
 }
 
 pub struct Projectile {
@@ -77,6 +139,9 @@ pub struct Projectile {
     /// interaction with environment handled with ray cast.
     body: Handle<RigidBody>,
     dir: Vec3,
+    /// Velocity of kinematic projectile in meters per second, it starts along `dir` and
+    /// changes with gravity and drag of definition.
+    velocity: Vec3,
     lifetime: f32,
     rotation_angle: f32,
     /// Handle of weapons from which projectile was fired.
@@ -85,8 +150,15 @@ pub struct Projectile {
     /// Position of projectile on the previous frame, it is used to simulate
     /// continuous intersection detection from fast moving projectiles.
     last_position: Vec3,
+    /// Position at which projectile was created.
+    origin: Vec3,
     definition: &'static ProjectileDefinition,
     pub sender: Option<Sender<Message>>,
+    /// Actor that picked up live grenade to throw it back. Grenade follows its hand until
+    /// thrown, grenade that runs out of fuse meanwhile detonates in hand.
+    holder: Handle<Actor>,
+    /// Time left until holder has grenade in hand and can throw it.
+    pickup_time: f32,
 }
 
 impl Default for Projectile {
@@ -95,58 +167,278 @@ impl Default for Projectile {
             kind: ProjectileKind::Plasma,
             model: Default::default(),
             dir: Default::default(),
+            velocity: Default::default(),
             body: Default::default(),
             lifetime: 0.0,
             rotation_angle: 0.0,
             owner: Default::default(),
             initial_velocity: Default::default(),
             last_position: Default::default(),
+            origin: Default::default(),
             definition: Self::get_definition(ProjectileKind::Plasma),
             sender: None,
+            holder: Default::default(),
+            pickup_time: 0.0,
         }
     }
 }
 
+#[derive(Clone)]
 pub struct ProjectileDefinition {
     damage: f32,
-    speed: f32,
+    /// Speed of kinematic projectile in meters per second.
+    pub speed: f32,
     lifetime: f32,
     /// Means that movement of projectile controlled by code, not physics.
     /// However projectile still could have rigid body to detect collisions.
     is_kinematic: bool,
-    impact_sound: &'static str
+    pub impact_sound: &'static str,
+    /// Explosive projectiles are not destroyed on hit, instead they explode when their
+    /// lifetime ends and damage every actor in this radius. Zero means not explosive.
+    explosion_radius: f32,
+    /// Downward acceleration of kinematic projectile in meters per second squared, physical
+    /// projectiles fall with physics gravity instead.
+    gravity: f32,
+    /// Rate at which kinematic projectile loses its speed, per second.
+    drag: f32,
+    /// Speed in meters per second that is given to bodies at the point of impact or explosion,
+    /// it falls off to zero at `knockback_radius`. Owner is pushed too, so it can be used to
+    /// jump higher.
+    knockback: f32,
+    knockback_radius: f32,
+}
+
+impl ProjectileDefinition {
+    /// Changes value of a field by its name in definitions file.
+    pub fn set_value(&mut self, field: &str, value: &str) -> Result<(), String> {
+        match field {
+            "damage" => self.damage = parse_f32(value, 0.0, 1000.0)?,
+            "speed" => self.speed = parse_f32(value, 0.0, 500.0)?,
+            "lifetime" => self.lifetime = parse_f32(value, 0.1, 60.0)?,
+            "explosion_radius" => self.explosion_radius = parse_f32(value, 0.0, 20.0)?,
+            "gravity" => self.gravity = parse_f32(value, 0.0, 50.0)?,
+            "drag" => self.drag = parse_f32(value, 0.0, 10.0)?,
+            "knockback" => self.knockback = parse_f32(value, 0.0, 50.0)?,
+            "knockback_radius" => self.knockback_radius = parse_f32(value, 0.0, 20.0)?,
+            _ => return Err(format!("unknown projectile field {}", field)),
+        }
+        Ok(())
+    }
+
+    /// Returns time in seconds that kinematic projectile needs to fly given distance, None
+    /// if drag stops it before. Gravity is ignored, it changes the path only a little.
+    pub fn flight_time(&self, distance: f32) -> Option<f32> {
+        if self.speed <= 0.0 {
+            None
+        } else if self.drag <= 0.0 {
+            Some(distance / self.speed)
+        } else {
+            // Speed decays exponentially, so covered distance is v / k * (1 - e^(-k * t)).
+            let part = 1.0 - distance * self.drag / self.speed;
+            if part > 0.0 {
+                Some(-part.ln() / self.drag)
+            } else {
+                None
+            }
+        }
+    }
+
+    /// Radius in which explosion damages actors, zero if projectile is not explosive.
+    pub fn explosion_radius(&self) -> f32 {
+        self.explosion_radius
+    }
+
+    /// How much kinematic projectile falls during given flight time, in meters.
+    pub fn drop(&self, flight_time: f32) -> f32 {
+        0.5 * self.gravity * flight_time * flight_time
+    }
+
+    /// Damage of explosion to actor at given distance from its center, it falls off to zero
+    /// at explosion radius.
+    pub fn explosion_damage(&self, distance: f32) -> f32 {
+        self.damage * (1.0 - distance / self.explosion_radius)
+    }
+
+    /// Pushes bodies away from impact or explosion at `center`. Bodies of actor that fired
+    /// the projectile are passed in `own` and are pushed by rules of rocket jumps.
+    pub fn apply_knockback(&self, physics: &mut Physics, own: &[Handle<RigidBody>], others: &[Handle<RigidBody>], center: Vec3, rocket_jump: RocketJump, dt: f32) {
+        if self.knockback > 0.0 && self.knockback_radius > 0.0 {
+            apply_radial_impulse(physics, others, center, self.knockback_radius, self.knockback, dt);
+            // Own explosion pushes with its own strength, this is what makes rocket jumps.
+            apply_radial_impulse(physics, own, center, self.knockback_radius, self.knockback * rocket_jump.self_knockback, dt);
+        }
+    }
+}
+
+/// Returns velocity of kinematic projectile after it flew `dt` seconds with gravity and drag
+/// of given definition.
+fn kinematic_velocity(definition: &ProjectileDefinition, velocity: Vec3, dt: f32) -> Vec3 {
+    let mut velocity = velocity;
+    velocity.y -= definition.gravity * dt;
+    velocity.scale((1.0 - definition.drag * dt).max(0.0))
+}
+
+/// Advances kinematic projectile by `dt` seconds and returns its displacement. Initial velocity
+/// is inherited from body of shooter, so it is a displacement per reference frame and is scaled
+/// to actual time step. It fades out over time, because projectile stabilizes its movement.
+fn kinematic_step(definition: &ProjectileDefinition, velocity: &mut Vec3, initial_velocity: &mut Vec3, dt: f32) -> Vec3 {
+    *velocity = kinematic_velocity(definition, *velocity, dt);
+    let displacement = initial_velocity.scale(dt * REFERENCE_FPS) + velocity.scale(dt);
+    initial_velocity.follow(&Vec3::ZERO, frame_blend_factor(0.15, dt));
+    displacement
+}
+
+/// Physical projectiles can pass through thin walls when they fly fast, because physics checks
+/// collisions only at the end of each step. Movement of body from `last_position` to `position`
+/// is swept against level geometry and body that tunneled is put back in front of the wall and
+/// bounced off. Returns position of body after the sweep.
+pub fn sweep_body(physics: &mut Physics, body: Handle<RigidBody>, last_position: Vec3, position: Vec3) -> Vec3 {
+    if let Some(ray) = Ray::from_two_points(&last_position, &position) {
+        let options = RayCastOptions {
+            ignore_bodies: true,
+            ignore_static_geometries: false,
+            sort_results: true,
+        };
+        let mut result = Vec::new();
+        if physics.ray_cast(&ray, options, &mut result) {
+            if let Some(hit) = result.first() {
+                // Normal of triangle may face either way, body is put back on the side it came from.
+                let normal = if hit.normal.dot(&(position - last_position)) > 0.0 {
+                    hit.normal.scale(-1.0)
+                } else {
+                    hit.normal
+                };
+                let body = physics.borrow_body_mut(body);
+                let velocity = body.get_velocity();
+                let bounced = velocity - normal.scale(2.0 * velocity.dot(&normal));
+                let position = hit.position + normal.scale(TUNNELING_PUSH_BACK);
+                body.set_position(position);
+                body.set_velocity(bounced.scale(TUNNELING_BOUNCE_DAMPING));
+                return position;
+            }
+        }
+    }
+    position
+}
+
+/// Pushes given bodies away from `center`. Bodies at the center get `strength` meters per
+/// second, push falls off linearly to zero at `radius`, bodies farther than that are not
+/// affected. `dt` is time of physics step, velocity of body is displacement per step.
+pub fn apply_radial_impulse(physics: &mut Physics, bodies: &[Handle<RigidBody>], center: Vec3, radius: f32, strength: f32, dt: f32) {
+    for &handle in bodies {
+        let body = physics.borrow_body_mut(handle);
+        let offset = body.get_position() - center;
+        let distance = offset.len();
+        if distance < radius {
+            let direction = (offset.normalized().unwrap_or(Vec3::UP) + Vec3::new(0.0, KNOCKBACK_LIFT, 0.0))
+                .normalized()
+                .unwrap_or(Vec3::UP);
+            let speed = strength * (1.0 - distance / radius);
+            body.set_velocity(body.get_velocity() + direction.scale(speed * dt));
+        }
+    }
 }
 
 impl Projectile {
+    /// Returns definition of projectile, definitions file may override built-in one.
     pub fn get_definition(kind: ProjectileKind) -> &'static ProjectileDefinition {
+        definitions::projectile(kind).unwrap_or_else(|| Self::builtin_definition(kind))
+    }
+
+    pub fn builtin_definition(kind: ProjectileKind) -> &'static ProjectileDefinition {
         match kind {
             ProjectileKind::Plasma => {
                 static DEFINITION: ProjectileDefinition = ProjectileDefinition {
                     damage: 30.0,
-                    speed: 0.15,
+                    speed: 9.0,
                     lifetime: 10.0,
                     is_kinematic: true,
-                    impact_sound: "data/sounds/bullet_impact_concrete.ogg"
+                    impact_sound: "data/sounds/bullet_impact_concrete.ogg",
+                    explosion_radius: 0.0,
+                    gravity: 0.0,
+                    drag: 0.0,
+                    knockback: 0.0,
+                    knockback_radius: 0.0,
                 };
                 &DEFINITION
             }
             ProjectileKind::Bullet => {
                 static DEFINITION: ProjectileDefinition = ProjectileDefinition {
                     damage: 15.0,
-                    speed: 0.75,
+                    speed: 45.0,
                     lifetime: 10.0,
                     is_kinematic: true,
-                    impact_sound: "data/sounds/bullet_impact_concrete.ogg"
+                    impact_sound: "data/sounds/bullet_impact_concrete.ogg",
+                    explosion_radius: 0.0,
+                    // Bullets are fast enough to fly flat at game distances.
+                    gravity: 0.0,
+                    drag: 0.0,
+                    knockback: 0.0,
+                    knockback_radius: 0.0,
                 };
                 &DEFINITION
             }
             ProjectileKind::Rocket => {
                 static DEFINITION: ProjectileDefinition = ProjectileDefinition {
                     damage: 30.0,
-                    speed: 0.5,
+                    speed: 30.0,
                     lifetime: 10.0,
                     is_kinematic: true,
-                    impact_sound: "data/sounds/explosion.ogg"
+                    impact_sound: "data/sounds/explosion.ogg",
+                    explosion_radius: 0.0,
+                    // Rocket motor keeps speed, but the rocket slowly drops.
+                    gravity: 2.0,
+                    drag: 0.0,
+                    knockback: 7.0,
+                    knockback_radius: 2.5,
+                };
+                &DEFINITION
+            }
+            ProjectileKind::Grenade => {
+                static DEFINITION: ProjectileDefinition = ProjectileDefinition {
+                    damage: 80.0,
+                    speed: 0.0,
+                    // Fuse time.
+                    lifetime: 2.0,
+                    is_kinematic: false,
+                    impact_sound: "data/sounds/explosion.ogg",
+                    explosion_radius: 4.0,
+                    gravity: 0.0,
+                    drag: 0.0,
+                    knockback: 9.0,
+                    knockback_radius: 4.0,
+                };
+                &DEFINITION
+            }
+            ProjectileKind::SmokeGrenade => {
+                static DEFINITION: ProjectileDefinition = ProjectileDefinition {
+                    damage: 0.0,
+                    speed: 0.0,
+                    // Fuse time.
+                    lifetime: 1.5,
+                    is_kinematic: false,
+                    impact_sound: "data/sounds/bullet_impact_metal.ogg",
+                    explosion_radius: 0.0,
+                    gravity: 0.0,
+                    drag: 0.0,
+                    knockback: 0.0,
+                    knockback_radius: 0.0,
+                };
+                &DEFINITION
+            }
+            ProjectileKind::Flashbang => {
+                static DEFINITION: ProjectileDefinition = ProjectileDefinition {
+                    damage: 0.0,
+                    speed: 0.0,
+                    // Fuse time.
+                    lifetime: 1.5,
+                    is_kinematic: false,
+                    impact_sound: "data/sounds/explosion.ogg",
+                    explosion_radius: 0.0,
+                    gravity: 0.0,
+                    drag: 0.0,
+                    knockback: 0.0,
+                    knockback_radius: 0.0,
                 };
                 &DEFINITION
             }
@@ -207,8 +499,7 @@ impl Projectile {
                     (model, Handle::NONE)
                 }
                 ProjectileKind::Rocket => {
-                    let resource = resource_manager.request_model("data/models/rocket.FBX").unwrap();
-                    let model = resource.lock().unwrap().instantiate_geometry(scene);
+                    let model = instantiate_model(resource_manager, "data/models/rocket.FBX", scene);
                     scene.graph[model]
                         .local_transform_mut()
                         .set_rotation(Quat::from(basis))
@@ -220,6 +511,28 @@ impl Projectile {
                     scene.graph.link_nodes(light, model);
                     (model, Handle::NONE)
                 }
+                ProjectileKind::Grenade | ProjectileKind::SmokeGrenade | ProjectileKind::Flashbang => {
+                    let size = 0.1;
+                    let color = match kind {
+                        ProjectileKind::SmokeGrenade => Color::opaque(160, 160, 160),
+                        ProjectileKind::Flashbang => Color::opaque(255, 255, 220),
+                        _ => Color::opaque(255, 60, 0),
+                    };
+                    let model = scene.graph.add_node(Node::Sprite(SpriteBuilder::new(BaseBuilder::new())
+                        .with_size(size)
+                        .with_color(color)
+                        .with_opt_texture(resource_manager.request_texture("data/particles/light_01.png", TextureKind::R8))
+                        .build()));
+
+                    // Grenade is a physical object - it falls and bounces off walls and floor.
+                    let mut body = RigidBody::new(ConvexShape::Sphere(SphereShape::new(size)));
+                    body.set_position(position);
+                    body.set_velocity(initial_velocity);
+                    body.collision_group = CollisionGroups::Projectile as u64;
+                    body.collision_mask = CollisionGroups::All as u64 & !(CollisionGroups::Projectile as u64);
+
+                    (model, scene.physics.add_body(body))
+                }
             }
         };
 
@@ -232,9 +545,11 @@ impl Projectile {
             body,
             initial_velocity,
             dir: dir.normalized().unwrap_or(Vec3::UP),
+            velocity: dir.normalized().unwrap_or(Vec3::UP).scale(definition.speed),
             kind,
             model,
             last_position: position,
+            origin: position,
             owner,
             definition,
             sender: Some(sender),
@@ -250,25 +565,108 @@ impl Projectile {
         self.lifetime = 0.0;
     }
 
-    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, weapons: &WeaponContainer, time: GameTime) {
+    /// Whether projectile is a thrown grenade, which can be picked up and thrown back.
+    pub fn is_throwable(&self) -> bool {
+        !self.definition.is_kinematic && self.body.is_some()
+    }
+
+    pub fn definition(&self) -> &'static ProjectileDefinition {
+        self.definition
+    }
+
+    /// Time in seconds left until grenade detonates.
+    pub fn fuse_time(&self) -> f32 {
+        self.lifetime
+    }
+
+    pub fn holder(&self) -> Handle<Actor> {
+        self.holder
+    }
+
+    /// Whether holder has finished picking up grenade and can throw it.
+    pub fn is_in_hand(&self) -> bool {
+        self.holder.is_some() && self.pickup_time <= 0.0
+    }
+
+    /// Starts picking up grenade by given actor. Owner stays the same until the grenade is
+    /// thrown, so grenade that detonates in hand is credited to the one who threw it first.
+    pub fn pick_up(&mut self, actor: Handle<Actor>) {
+        self.holder = actor;
+        self.pickup_time = GRENADE_PICKUP_TIME;
+    }
+
+    /// Keeps held grenade at given point of hand of its holder.
+    pub fn hold_at(&mut self, physics: &mut Physics, position: Vec3, dt: f32) {
+        let body = physics.borrow_body_mut(self.body);
+        body.set_position(position);
+        body.set_velocity(Vec3::ZERO);
+        // Grenade is moved with hand, not thrown, so there is nothing to check for tunneling.
+        self.last_position = position;
+        self.pickup_time = (self.pickup_time - dt).max(0.0);
+    }
+
+    /// Lets grenade fall where it is, used when holder dies or leaves.
+    pub fn drop(&mut self) {
+        self.holder = Handle::NONE;
+        self.pickup_time = 0.0;
+    }
+
+    /// Throws held grenade with given velocity (displacement per physics step). Grenade now
+    /// belongs to given weapon of the new thrower, fuse is not reset.
+    pub fn throw(&mut self, physics: &mut Physics, velocity: Vec3, owner: Handle<Weapon>) {
+        self.drop();
+        physics.borrow_body_mut(self.body).set_velocity(velocity);
+        if owner.is_some() {
+            self.owner = owner;
+        }
+    }
+
+    /// Returns part of lifetime left, 1.0 for new projectile and 0.0 for dead one.
+    fn lifetime_fraction(&self) -> f32 {
+        self.lifetime / self.definition.lifetime
+    }
+
+    /// Bodies of range targets are passed in `targets`, hits of them are reported by
+    /// `Message::HitRangeTarget` instead of damage.
+    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, weapons: &WeaponContainer, targets: &[Handle<RigidBody>], rocket_jump: RocketJump, time: GameTime) {
         // Fetch current position of projectile.
-        let position = if self.body.is_some() {
+        let mut position = if self.body.is_some() {
             scene.physics.borrow_body(self.body).get_position()
         } else {
             scene.graph[self.model].global_position()
         };
 
+        // Projectile that flew away from level silently disappears.
+        if position.distance(&self.origin) > MAX_TRAVEL_DISTANCE {
+            self.kill();
+            return;
+        }
+
         let mut hits: Vec<Hit> = Vec::new();
+        let mut target_hits = Vec::new();
         let mut effect_position = None;
+        let mut effect_kind = EffectKind::BulletImpact;
 
         // Do ray based intersection tests for every kind of projectiles. This will help to handle
-        // fast moving projectiles.
-        if let Some(ray) = Ray::from_two_points(&self.last_position, &position) {
+        // fast moving projectiles. Explosive projectiles ignore hits and deal damage only when
+        // they explode, tactical grenades ignore hits as well.
+        let ray = if self.definition.explosion_radius > 0.0 || self.kind.is_tactical() {
+            None
+        } else {
+            Ray::from_two_points(&self.last_position, &position)
+        };
+        if let Some(ray) = ray {
             let mut result = Vec::new();
             if scene.physics.ray_cast(&ray, RayCastOptions::default(), &mut result) {
                 // List of hits sorted by distance from ray origin.
                 'hit_loop: for hit in result.iter() {
                     if let HitKind::Body(body) = hit.kind {
+                        if targets.contains(&body) {
+                            target_hits.push(body);
+                            self.kill();
+                            effect_position = Some(hit.position);
+                            break 'hit_loop;
+                        }
                         for (actor_handle, actor) in actors.pair_iter() {
                             if actor.get_body() == body && self.owner.is_some() {
                                 let weapon = &weapons[self.owner];
@@ -277,10 +675,15 @@ impl Projectile {
                                     hits.push(Hit {
                                         actor: actor_handle,
                                         who: weapon.owner(),
+                                        amount: self.definition.damage,
+                                        region: Some(actor.hit_region(&scene.physics, hit.position)),
+                                        kind: DamageKind::Bullet,
+                                        position: hit.position,
                                     });
 
                                     self.kill();
                                     effect_position = Some(hit.position);
+                                    effect_kind = EffectKind::Blood;
                                     break 'hit_loop;
                                 }
                             }
@@ -294,15 +697,23 @@ impl Projectile {
             }
         }
 
+        if !self.definition.is_kinematic && self.body.is_some() {
+            position = sweep_body(&mut scene.physics, self.body, self.last_position, position);
+        }
+
         // Movement of kinematic projectiles are controlled explicitly.
         if self.definition.is_kinematic {
-            let total_velocity = self.initial_velocity + self.dir.scale(self.definition.speed);
+            let total_velocity = kinematic_step(self.definition, &mut self.velocity, &mut self.initial_velocity, time.delta);
 
             // Special case for projectiles with rigid body.
             if self.body.is_some() {
                 for contact in scene.physics.borrow_body(self.body).get_contacts() {
                     let mut owner_contact = false;
 
+                    if targets.contains(&contact.body) {
+                        target_hits.push(contact.body);
+                    }
+
                     // Check if we got contact with any actor and damage it then.
                     for (actor_handle, actor) in actors.pair_iter() {
                         if contact.body == actor.get_body() && self.owner.is_some() {
@@ -312,7 +723,12 @@ impl Projectile {
                                 hits.push(Hit {
                                     actor: actor_handle,
                                     who: weapon.owner(),
+                                    amount: self.definition.damage,
+                                    region: Some(actor.hit_region(&scene.physics, contact.position)),
+                                    kind: DamageKind::Bullet,
+                                    position: contact.position,
                                 });
+                                effect_kind = EffectKind::Blood;
                             } else {
                                 // Make sure that projectile won't die on contact with owner.
                                 owner_contact = true;
@@ -338,30 +754,82 @@ impl Projectile {
 
         if let Node::Sprite(sprite) = &mut scene.graph[self.model] {
             sprite.set_rotation(self.rotation_angle);
-            self.rotation_angle += 1.5;
+            self.rotation_angle += SPRITE_ROTATION_SPEED * time.delta;
         }
 
-        // Reduce initial velocity down to zero over time. This is needed because projectile
-        // stabilizes its movement over time.
-        self.initial_velocity.follow(&Vec3::ZERO, 0.15);
-
         self.lifetime -= time.delta;
 
         if self.lifetime <= 0.0 {
             let pos = effect_position.unwrap_or_else(|| self.get_position(&scene.graph));
 
-            self.sender.as_ref().unwrap().send(Message::CreateEffect {
-                kind: EffectKind::BulletImpact,
-                position: pos,
-            }).unwrap();
+            if self.kind.is_tactical() {
+                self.sender.as_ref().unwrap().send(Message::DetonateTacticalGrenade {
+                    kind: self.kind,
+                    position: pos,
+                    owner: if self.owner.is_some() { weapons[self.owner].owner() } else { Handle::NONE },
+                }).unwrap();
+            } else {
+                self.sender.as_ref().unwrap().send(Message::CreateEffect {
+                    kind: effect_kind,
+                    position: pos,
+                }).unwrap();
+            }
 
             self.sender.as_ref().unwrap().send(Message::PlaySound {
                 path: PathBuf::from(self.definition.impact_sound),
                 position: pos,
                 gain: 1.0,
                 rolloff_factor: 4.0,
-                radius: 3.0
+                radius: 3.0,
+                priority: if self.definition.explosion_radius > 0.0 {
+                    SoundPriority::High
+                } else {
+                    SoundPriority::Normal
+                },
             }).unwrap();
+
+            if self.definition.explosion_radius > 0.0 {
+                self.sender.as_ref().unwrap().send(Message::ShakeCamera {
+                    position: pos,
+                    intensity: 0.8,
+                    radius: self.definition.explosion_radius * 4.0,
+                }).unwrap();
+            }
+
+            if self.definition.knockback > 0.0 && self.definition.knockback_radius > 0.0 {
+                let who = if self.owner.is_some() { weapons[self.owner].owner() } else { Handle::NONE };
+                let (own, others): (Vec<_>, Vec<_>) = actors
+                    .pair_iter()
+                    .filter(|(_, actor)| !actor.is_dead())
+                    .partition(|(handle, _)| *handle == who);
+                let own = own.iter().map(|(_, actor)| actor.get_body()).collect::<Vec<_>>();
+                let others = others.iter().map(|(_, actor)| actor.get_body()).collect::<Vec<_>>();
+                self.definition.apply_knockback(&mut scene.physics, &own, &others, pos, rocket_jump, time.delta);
+            }
+
+            if self.definition.explosion_radius > 0.0 && self.owner.is_some() {
+                let who = weapons[self.owner].owner();
+                for actor_handle in actors.overlap_sphere(&scene.physics, pos, self.definition.explosion_radius) {
+                    // Owner is hit as well, level scales damage from own explosions by rules
+                    // of rocket jumps.
+                    if actor_handle != who || rocket_jump.self_damage != SelfDamage::None {
+                        let distance = actors.get(actor_handle).position(&scene.physics).distance(&pos);
+                        hits.push(Hit {
+                            actor: actor_handle,
+                            who,
+                            amount: self.definition.explosion_damage(distance),
+                            region: None,
+                            kind: DamageKind::Explosion,
+                            position: pos,
+                        });
+                    }
+                }
+                for &body in targets.iter() {
+                    if scene.physics.borrow_body(body).get_position().distance(&pos) <= self.definition.explosion_radius {
+                        target_hits.push(body);
+                    }
+                }
+            }
         }
 
         // List of hit actors can contain same actor multiple times in a row because this list could
@@ -372,10 +840,23 @@ impl Projectile {
             self.sender.as_ref().unwrap().send(Message::DamageActor {
                 actor: hit.actor,
                 who: hit.who,
-                amount: self.definition.damage,
+                amount: hit.amount,
+                region: hit.region,
+                kind: hit.kind,
+                position: Some(hit.position),
             }).unwrap();
         }
 
+        target_hits.dedup();
+        if self.owner.is_some() {
+            for body in target_hits {
+                self.sender.as_ref().unwrap().send(Message::HitRangeTarget {
+                    body,
+                    who: weapons[self.owner].owner(),
+                }).unwrap();
+            }
+        }
+
         self.last_position = position;
     }
 
@@ -383,6 +864,11 @@ impl Projectile {
         graph[self.model].global_position()
     }
 
+    /// Returns handle of rigid body of projectile, it is none for ray-based projectiles.
+    pub fn get_body(&self) -> Handle<RigidBody> {
+        self.body
+    }
+
     fn clean_up(&mut self, scene: &mut Scene) {
         if self.body.is_some() {
             scene.physics.remove_body(self.body);
@@ -393,9 +879,31 @@ impl Projectile {
     }
 }
 
+impl Usable for Projectile {
+    fn use_position(&self, graph: &Graph) -> Vec3 {
+        self.get_position(graph)
+    }
+
+    fn can_be_used(&self) -> bool {
+        self.is_throwable() && !self.is_dead() && self.holder.is_none()
+    }
+
+    fn use_action(&self) -> String {
+        if self.is_in_hand() {
+            "throw grenade back".to_owned()
+        } else {
+            "pick up grenade".to_owned()
+        }
+    }
+}
+
 struct Hit {
     actor: Handle<Actor>,
     who: Handle<Actor>,
+    amount: f32,
+    region: Option<HitRegion>,
+    kind: DamageKind,
+    position: Vec3,
 }
 
 impl Visit for Projectile {
@@ -411,11 +919,15 @@ impl Visit for Projectile {
         self.definition = Self::get_definition(self.kind);
         self.lifetime.visit("Lifetime", visitor)?;
         self.dir.visit("Direction", visitor)?;
+        self.velocity.visit("Velocity", visitor)?;
         self.model.visit("Model", visitor)?;
         self.body.visit("Body", visitor)?;
         self.rotation_angle.visit("RotationAngle", visitor)?;
         self.initial_velocity.visit("InitialVelocity", visitor)?;
         self.owner.visit("Owner", visitor)?;
+        self.origin.visit("Origin", visitor)?;
+        self.holder.visit("Holder", visitor)?;
+        self.pickup_time.visit("PickupTime", visitor)?;
 
         visitor.leave_region()
     }
@@ -432,17 +944,71 @@ impl ProjectileContainer {
         }
     }
 
-    pub fn add(&mut self, projectile: Projectile) -> Handle<Projectile> {
+    /// Adds new projectile, if there are too many projectiles, the oldest one is removed
+    /// first. Pool reuses slots of removed projectiles.
+    pub fn add(&mut self, scene: &mut Scene, projectile: Projectile) -> Handle<Projectile> {
+        if self.pool.alive_count() >= MAX_PROJECTILES {
+            let oldest = self.pool
+                .pair_iter()
+                .min_by(|(_, a), (_, b)| a.lifetime_fraction()
+                    .partial_cmp(&b.lifetime_fraction())
+                    .unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(handle, _)| handle);
+            if let Some(oldest) = oldest {
+                self.pool[oldest].clean_up(scene);
+                self.pool.free(oldest);
+            }
+        }
         self.pool.spawn(projectile)
     }
 
+    pub fn iter(&self) -> PoolIterator<Projectile> {
+        self.pool.iter()
+    }
+
+    pub fn pair_iter(&self) -> PoolPairIterator<Projectile> {
+        self.pool.pair_iter()
+    }
+
+    pub fn get(&self, projectile: Handle<Projectile>) -> &Projectile {
+        self.pool.borrow(projectile)
+    }
+
+    pub fn get_mut(&mut self, projectile: Handle<Projectile>) -> &mut Projectile {
+        self.pool.borrow_mut(projectile)
+    }
+
+    pub fn contains(&self, projectile: Handle<Projectile>) -> bool {
+        self.pool.is_valid_handle(projectile)
+    }
+
+    /// Returns grenade that given actor holds, if any.
+    pub fn held_by(&self, actor: Handle<Actor>) -> Option<Handle<Projectile>> {
+        if actor.is_none() {
+            return None;
+        }
+        self.pool
+            .pair_iter()
+            .find(|(_, projectile)| projectile.holder == actor && !projectile.is_dead())
+            .map(|(handle, _)| handle)
+    }
+
     pub fn iter_mut(&mut self) -> PoolIteratorMut<Projectile> {
         self.pool.iter_mut()
     }
 
-    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, weapons: &WeaponContainer, time: GameTime) {
+    /// Returns position and blast radius of every live explosive projectile.
+    pub fn explosives(&self, graph: &Graph) -> Vec<(Vec3, f32)> {
+        self.pool
+            .iter()
+            .filter(|projectile| !projectile.is_dead() && projectile.definition.explosion_radius > 0.0)
+            .map(|projectile| (projectile.get_position(graph), projectile.definition.explosion_radius))
+            .collect()
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, weapons: &WeaponContainer, targets: &[Handle<RigidBody>], rocket_jump: RocketJump, time: GameTime) {
         for projectile in self.pool.iter_mut() {
-            projectile.update(scene, actors, weapons, time);
+            projectile.update(scene, actors, weapons, targets, rocket_jump, time);
             if projectile.is_dead() {
                 projectile.clean_up(scene);
             }
@@ -460,4 +1026,138 @@ impl Visit for ProjectileContainer {
 
         visitor.leave_region()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projectile_with_gravity_follows_parabolic_path() {
+        let definition = ProjectileDefinition {
+            gravity: 9.81,
+            drag: 0.0,
+            ..Projectile::builtin_definition(ProjectileKind::Rocket).clone()
+        };
+        let dt = 1.0 / 60.0;
+        let mut velocity = Vec3::new(10.0, 0.0, 0.0);
+        let mut position = Vec3::ZERO;
+        for step in 1..=120 {
+            velocity = kinematic_velocity(&definition, velocity, dt);
+            position += velocity.scale(dt);
+            if step % 30 == 0 {
+                let t = step as f32 * dt;
+                // Horizontal movement is uniform, drop grows with square of flight time.
+                assert!((position.x - 10.0 * t).abs() < 1e-3);
+                assert!((-position.y - definition.drop(t)).abs() < 0.2 * t);
+            }
+        }
+    }
+
+    fn add_body(physics: &mut Physics, position: Vec3) -> Handle<RigidBody> {
+        let mut body = RigidBody::new(ConvexShape::Sphere(SphereShape::new(0.5)));
+        body.set_position(position);
+        physics.add_body(body)
+    }
+
+    #[test]
+    fn body_near_blast_gains_outward_velocity() {
+        let mut physics = Physics::new();
+        let body = add_body(&mut physics, Vec3::new(2.0, 0.0, 0.0));
+        apply_radial_impulse(&mut physics, &[body], Vec3::ZERO, 4.0, 10.0, 1.0 / 60.0);
+        let velocity = physics.borrow_body(body).get_velocity();
+        assert!(velocity.x > 0.0);
+        assert!(velocity.y > 0.0);
+        assert_eq!(velocity.z, 0.0);
+    }
+
+    #[test]
+    fn blast_falls_off_with_distance() {
+        let mut physics = Physics::new();
+        let near = add_body(&mut physics, Vec3::new(1.0, 0.0, 0.0));
+        let far = add_body(&mut physics, Vec3::new(-3.0, 0.0, 0.0));
+        let outside = add_body(&mut physics, Vec3::new(0.0, 0.0, 5.0));
+        apply_radial_impulse(&mut physics, &[near, far, outside], Vec3::ZERO, 4.0, 10.0, 1.0 / 60.0);
+        let near_speed = physics.borrow_body(near).get_velocity().len();
+        let far_velocity = physics.borrow_body(far).get_velocity();
+        assert!(far_velocity.x < 0.0);
+        assert!(near_speed > far_velocity.len());
+        assert_eq!(physics.borrow_body(outside).get_velocity().len(), 0.0);
+    }
+
+    #[test]
+    fn grenade_at_feet_launches_owner_and_deals_self_damage() {
+        let definition = Projectile::builtin_definition(ProjectileKind::Grenade);
+        let rocket_jump = RocketJump {
+            self_damage: SelfDamage::Reduced,
+            self_knockback: 1.5,
+        };
+        let feet = Vec3::ZERO;
+        let center = Vec3::new(0.2, 0.9, 0.0);
+        let mut physics = Physics::new();
+        let owner = add_body(&mut physics, center);
+        let other = add_body(&mut physics, center);
+        definition.apply_knockback(&mut physics, &[owner], &[other], feet, rocket_jump, 1.0 / 60.0);
+
+        // Owner is launched up, with its own strength of knockback.
+        let own_velocity = physics.borrow_body(owner).get_velocity();
+        let other_velocity = physics.borrow_body(other).get_velocity();
+        assert!(own_velocity.y > 0.0);
+        assert!((own_velocity.len() - other_velocity.len() * 1.5).abs() < 1e-5);
+
+        // Damage from own grenade is scaled by configured mode.
+        let damage = definition.explosion_damage(center.distance(&feet));
+        assert!(damage > 0.0);
+        assert!((rocket_jump.scale_self_damage(damage) - damage * 0.35).abs() < 1e-5);
+        let no_damage = RocketJump {
+            self_damage: SelfDamage::None,
+            ..rocket_jump
+        };
+        assert_eq!(no_damage.scale_self_damage(damage), 0.0);
+    }
+
+    #[test]
+    fn rocket_jumps_can_be_disabled() {
+        let definition = Projectile::builtin_definition(ProjectileKind::Grenade);
+        let rocket_jump = RocketJump {
+            self_damage: SelfDamage::Full,
+            self_knockback: 0.0,
+        };
+        let mut physics = Physics::new();
+        let owner = add_body(&mut physics, Vec3::new(0.0, 0.9, 0.0));
+        definition.apply_knockback(&mut physics, &[owner], &[], Vec3::ZERO, rocket_jump, 1.0 / 60.0);
+        assert_eq!(physics.borrow_body(owner).get_velocity().len(), 0.0);
+    }
+
+    /// Flies kinematic projectile for one second with given time step, the same way
+    /// `Projectile::update` moves it, and returns its final position.
+    fn fly_for_second(definition: &ProjectileDefinition, dt: f32) -> Vec3 {
+        let mut velocity = Vec3::new(20.0, 0.0, 0.0);
+        let mut initial_velocity = Vec3::new(0.1, 0.0, 0.0);
+        let mut position = Vec3::ZERO;
+        for _ in 0..(1.0 / dt).round() as usize {
+            position += kinematic_step(definition, &mut velocity, &mut initial_velocity, dt);
+        }
+        position
+    }
+
+    #[test]
+    fn projectile_flight_does_not_depend_on_time_step() {
+        let definition = ProjectileDefinition {
+            gravity: 9.81,
+            drag: 0.1,
+            ..Projectile::builtin_definition(ProjectileKind::Rocket).clone()
+        };
+        let position_60 = fly_for_second(&definition, 1.0 / 60.0);
+        let position_144 = fly_for_second(&definition, 1.0 / 144.0);
+        let position_30 = fly_for_second(&definition, 1.0 / 30.0);
+        assert!(position_60.distance(&position_144) < 0.1);
+        assert!(position_60.distance(&position_30) < 0.1);
+    }
+
+    #[test]
+    fn projectile_without_gravity_flies_straight() {
+        let definition = Projectile::builtin_definition(ProjectileKind::Bullet);
+        let velocity = kinematic_velocity(definition, Vec3::new(0.0, 0.0, 45.0), 1.0 / 60.0);
+        assert_eq!(velocity.y, 0.0);
+    }
+}
@@ -0,0 +1,987 @@
+//! Game settings that are persisted between runs. Settings are stored in binary file using
+//! the same visitor mechanism as saved games.
+
+use crate::{
+    loadout::Loadout,
+    crosshair::{CrosshairConfig, CrosshairStyle},
+    hud_layout::{HudLayout, HudAnchor, HUD_ELEMENTS},
+    hud_theme::{HudTheme, HudThemePreset},
+    control_scheme::{ControlScheme, ControlButton, PickupSwitch, MAX_INPUT_BUFFER},
+    palette::ColorBlindMode,
+    damage::{DamageMultipliers, HealthRegen, HitMultipliers, RocketJump, SelfDamage, MAX_HIT_MULTIPLIER, MAX_SELF_KNOCKBACK},
+    effects::{EffectLimits, MAX_EFFECTS_RANGE},
+    item::DEFAULT_ITEM_GLOW,
+    level::{CorpseLimits, MAX_CORPSES_RANGE, MAX_CORPSE_LIFETIME, PATH_BUDGET_RANGE, DEFAULT_PATH_BUDGET},
+    match_vote::{DEFAULT_VOTE_TIME, MAX_VOTE_TIME},
+    DEFAULT_BOT_SIGHT_RANGE,
+    DEFAULT_SPAWN_PROTECTION,
+    DEFAULT_INTRO_COUNTDOWN,
+    DEFAULT_TIME_LIMIT_SECS,
+    DEFAULT_FRAG_LIMIT,
+    DEFAULT_MAX_SOUNDS,
+    MAX_SOUNDS_RANGE,
+};
+use std::{
+    path::Path,
+    fmt::Write,
+};
+use rg3d::{
+    core::{
+        visitor::{Visit, Visitor, VisitResult},
+        color::Color,
+    },
+    renderer::QualitySettings,
+    event_loop::EventLoop,
+};
+
+const SETTINGS_PATH: &str = "settings.bin";
+/// Default file for exported settings, unlike settings file it is plain text, so it can
+/// be shared and edited by hand.
+pub const SETTINGS_EXPORT_PATH: &str = "settings.txt";
+
+/// Smallest window that is considered usable, smaller stored windows are not restored.
+const MIN_WINDOW_SIZE: (u32, u32) = (320, 240);
+/// Height of area at the top of a window that must be on screen, so window can be dragged.
+const WINDOW_TITLE_HEIGHT: i32 = 30;
+
+/// Size and position of window in physical pixels.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for WindowGeometry {
+    fn default() -> Self {
+        Self {
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+        }
+    }
+}
+
+impl Visit for WindowGeometry {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.x.visit("X", visitor)?;
+        self.y.visit("Y", visitor)?;
+        self.width.visit("Width", visitor)?;
+        self.height.visit("Height", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl WindowGeometry {
+    /// Checks that window fits on at least one monitor, monitors could be disconnected or
+    /// change resolution since geometry was stored. Middle of window title must be on
+    /// the monitor and window must not be larger than the monitor.
+    pub fn fits_on_any_monitor(&self, events_loop: &EventLoop<()>) -> bool {
+        if self.width < MIN_WINDOW_SIZE.0 || self.height < MIN_WINDOW_SIZE.1 {
+            return false;
+        }
+        let title_x = self.x + self.width as i32 / 2;
+        let title_y = self.y + WINDOW_TITLE_HEIGHT / 2;
+        events_loop.available_monitors().any(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+            title_x >= position.x && title_x < position.x + size.width as i32 &&
+                title_y >= position.y && title_y < position.y + size.height as i32 &&
+                self.width <= size.width && self.height <= size.height
+        })
+    }
+}
+
+/// Buttons bound to actions of control scheme, in the order of [`ControlScheme::buttons`].
+#[derive(Clone, PartialEq)]
+pub struct KeyBindings {
+    buttons: Vec<ControlButton>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            buttons: ControlScheme::default().buttons().iter().map(|d| d.button).collect(),
+        }
+    }
+}
+
+impl Visit for KeyBindings {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        // Every binding is stored by name of its action, actions that are missing in the
+        // file, for example added by newer version, keep their default buttons.
+        let reading = visitor.is_reading();
+        for (definition, button) in ControlScheme::default().buttons().iter().zip(self.buttons.iter_mut()) {
+            let result = button.visit(&setting_name(&definition.description), visitor);
+            if !reading {
+                result?;
+            }
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Content toggles for younger audiences. Disabled content is replaced with tame
+/// alternatives, not removed, so gameplay stays the same.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ContentFilter {
+    /// Whether hits show blood, when disabled sparks are shown instead.
+    pub gore: bool,
+    /// Whether bots use names and voice lines that are free of harsh language.
+    pub clean_language: bool,
+}
+
+impl Default for ContentFilter {
+    fn default() -> Self {
+        Self {
+            gore: true,
+            clean_language: false,
+        }
+    }
+}
+
+impl Visit for ContentFilter {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.gore.visit("Gore", visitor)?;
+        self.clean_language.visit("CleanLanguage", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Independent kinds of feedback shown by HUD when player takes damage.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct DamageFeedback {
+    /// Whether a marker around the crosshair points towards the attacker.
+    pub direction_indicator: bool,
+    /// Whether edges of the screen are tinted red for a moment.
+    pub vignette: bool,
+    /// Whether whole screen briefly flashes red.
+    pub hit_flash: bool,
+}
+
+impl Default for DamageFeedback {
+    fn default() -> Self {
+        Self {
+            direction_indicator: true,
+            vignette: true,
+            hit_flash: true,
+        }
+    }
+}
+
+impl Visit for DamageFeedback {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.direction_indicator.visit("DirectionIndicator", visitor)?;
+        self.vignette.visit("Vignette", visitor)?;
+        self.hit_flash.visit("HitFlash", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Smallest and largest multiplier of text size.
+pub const TEXT_SCALE_RANGE: (f32, f32) = (0.75, 2.0);
+/// Opacity of backdrops behind HUD text in high contrast mode.
+const HIGH_CONTRAST_BACKDROP_ALPHA: u8 = 210;
+
+/// Options that make text easier to read.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct Accessibility {
+    /// Multiplier of size of HUD and menu text, HUD scale is applied on top of it.
+    pub text_scale: f32,
+    /// Puts nearly opaque dark backdrops behind HUD text.
+    pub high_contrast: bool,
+}
+
+impl Default for Accessibility {
+    fn default() -> Self {
+        Self {
+            text_scale: 1.0,
+            high_contrast: false,
+        }
+    }
+}
+
+impl Accessibility {
+    /// Returns color of backdrop that has given opacity in normal mode, high contrast mode
+    /// makes it darker.
+    pub fn backdrop(&self, alpha: u8) -> Color {
+        let alpha = if self.high_contrast {
+            alpha.max(HIGH_CONTRAST_BACKDROP_ALPHA)
+        } else {
+            alpha
+        };
+        Color::from_rgba(0, 0, 0, alpha)
+    }
+}
+
+impl Visit for Accessibility {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.text_scale.visit("TextScale", visitor)?;
+        self.high_contrast.visit("HighContrast", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[derive(Clone)]
+pub struct Settings {
+    pub spot_shadows_enabled: bool,
+    pub spot_soft_shadows: bool,
+    pub spot_shadows_distance: f32,
+    pub point_shadows_enabled: bool,
+    pub point_soft_shadows: bool,
+    pub point_shadows_distance: f32,
+    pub effect_limits: EffectLimits,
+    pub hud_scale: f32,
+    pub hud_layout: HudLayout,
+    pub hud_theme: HudTheme,
+    pub accessibility: Accessibility,
+    /// Loadout of last started match.
+    pub loadout: Loadout,
+    pub crosshair: CrosshairConfig,
+    pub pause_when_unfocused: bool,
+    /// Whether frame limiter yields thread in a loop at the end of frame instead of sleeping
+    /// through it. Frame times are steadier and latency is lower, but CPU is kept busy.
+    pub precise_frame_pacing: bool,
+    /// Whether game waits for exact time of next tick and renders right after it, instead
+    /// of rendering and then sleeping through the rest of frame. Input is fresher, but the
+    /// window wakes up more often.
+    pub low_latency: bool,
+    /// Whether bots fought each other in last started match.
+    pub bots_fight_each_other: bool,
+    /// Bot sight range of last started match.
+    pub bot_sight_range: f32,
+    /// Damage multipliers of last started match.
+    pub damage_multipliers: DamageMultipliers,
+    pub spawn_protection: f32,
+    /// Health regeneration of last started match.
+    pub health_regen: HealthRegen,
+    /// Whether bot auto-balancing was enabled in last started match.
+    pub auto_balance: bool,
+    /// Intro countdown of last started match.
+    pub intro_countdown: f32,
+    /// Time and frag limits of last started match.
+    pub time_limit_secs: f32,
+    pub frag_limit: u32,
+    /// Whether events of started matches are written into log files.
+    pub match_log_enabled: bool,
+    /// Whether last started match was watched instead of played.
+    pub observer_mode: bool,
+    pub mouse_sens_x: f32,
+    pub mouse_sens_y: f32,
+    pub link_mouse_axes: bool,
+    pub smooth_mouse: bool,
+    pub mouse_smoothing: f32,
+    pub mouse_acceleration: bool,
+    pub screen_shake: f32,
+    pub head_bob: f32,
+    pub auto_switch_weapon: bool,
+    pub pickup_switch: PickupSwitch,
+    pub toggle_sprint: bool,
+    pub auto_reload: bool,
+    pub reload_on_switch: bool,
+    pub input_buffer: f32,
+    pub manual_pickup: bool,
+    pub ads_sensitivity: f32,
+    pub mouse_y_inverse: bool,
+    pub shake_camera: bool,
+    pub key_bindings: KeyBindings,
+    pub color_blind_mode: ColorBlindMode,
+    /// Whether time left until respawn is shown above picked up items.
+    pub show_item_timers: bool,
+    /// Whether place of last death of player is marked on HUD for a while after respawn.
+    pub death_marker: bool,
+    /// Whether damage dealt by player pops up as numbers above hit actors.
+    pub damage_numbers: bool,
+    /// Time in seconds of vote for the next match after match ends, zero disables the vote.
+    pub map_vote_time: f32,
+    /// Intensity of glow around items, zero disables it.
+    pub item_glow: f32,
+    pub corpse_limits: CorpseLimits,
+    pub rocket_jump: RocketJump,
+    pub hit_multipliers: HitMultipliers,
+    pub killcam: bool,
+    /// Whether dead actors drop their current weapon with ammo that was left in it.
+    pub weapon_drop: bool,
+    /// Maximum amount of bots that rebuild their paths on one frame, lower values help weak
+    /// hardware in matches with many bots.
+    pub path_budget: u32,
+    /// Whether names of bots are shown when they're behind walls, it is off by default
+    /// because it gives an unfair advantage.
+    pub nameplates_through_walls: bool,
+    /// Maximum amount of gameplay sounds playing at the same time.
+    pub max_sounds: u32,
+    pub content_filter: ContentFilter,
+    pub damage_feedback: DamageFeedback,
+    /// Window geometry on last exit, None if it was never stored.
+    pub window_geometry: Option<WindowGeometry>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let quality = QualitySettings::default();
+        let control_scheme = ControlScheme::default();
+        Self {
+            spot_shadows_enabled: quality.spot_shadows_enabled,
+            spot_soft_shadows: quality.spot_soft_shadows,
+            spot_shadows_distance: quality.spot_shadows_distance,
+            point_shadows_enabled: quality.point_shadows_enabled,
+            point_soft_shadows: quality.point_soft_shadows,
+            point_shadows_distance: quality.point_shadows_distance,
+            effect_limits: Default::default(),
+            hud_scale: 1.0,
+            accessibility: Default::default(),
+            hud_layout: Default::default(),
+            hud_theme: Default::default(),
+            loadout: Default::default(),
+            crosshair: Default::default(),
+            pause_when_unfocused: false,
+            precise_frame_pacing: false,
+            low_latency: false,
+            bots_fight_each_other: true,
+            bot_sight_range: DEFAULT_BOT_SIGHT_RANGE,
+            damage_multipliers: Default::default(),
+            spawn_protection: DEFAULT_SPAWN_PROTECTION,
+            health_regen: Default::default(),
+            auto_balance: false,
+            intro_countdown: DEFAULT_INTRO_COUNTDOWN,
+            time_limit_secs: DEFAULT_TIME_LIMIT_SECS,
+            frag_limit: DEFAULT_FRAG_LIMIT,
+            match_log_enabled: false,
+            observer_mode: false,
+            mouse_sens_x: control_scheme.mouse_sens_x,
+            mouse_sens_y: control_scheme.mouse_sens_y,
+            link_mouse_axes: control_scheme.link_mouse_axes,
+            smooth_mouse: control_scheme.smooth_mouse,
+            mouse_smoothing: control_scheme.mouse_smoothing,
+            mouse_acceleration: control_scheme.mouse_acceleration,
+            screen_shake: control_scheme.screen_shake,
+            head_bob: control_scheme.head_bob,
+            auto_switch_weapon: control_scheme.auto_switch_weapon,
+            pickup_switch: control_scheme.pickup_switch,
+            toggle_sprint: control_scheme.toggle_sprint,
+            auto_reload: control_scheme.auto_reload,
+            reload_on_switch: control_scheme.reload_on_switch,
+            input_buffer: control_scheme.input_buffer,
+            manual_pickup: control_scheme.manual_pickup,
+            ads_sensitivity: control_scheme.ads_sensitivity,
+            mouse_y_inverse: control_scheme.mouse_y_inverse,
+            shake_camera: control_scheme.shake_camera,
+            key_bindings: Default::default(),
+            color_blind_mode: Default::default(),
+            show_item_timers: true,
+            death_marker: true,
+            damage_numbers: true,
+            map_vote_time: DEFAULT_VOTE_TIME,
+            item_glow: DEFAULT_ITEM_GLOW,
+            corpse_limits: Default::default(),
+            rocket_jump: Default::default(),
+            hit_multipliers: Default::default(),
+            killcam: true,
+            weapon_drop: true,
+            path_budget: DEFAULT_PATH_BUDGET,
+            nameplates_through_walls: false,
+            max_sounds: DEFAULT_MAX_SOUNDS,
+            content_filter: Default::default(),
+            damage_feedback: Default::default(),
+            window_geometry: None,
+        }
+    }
+}
+
+impl Visit for Settings {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.for_each_field(|field, name| field.visit(name, visitor))?;
+
+        visitor.leave_region()
+    }
+}
+
+impl Settings {
+    /// Calls given function for every field of settings with its name in settings file, in
+    /// the order of the file. Stops at first error.
+    fn for_each_field<F>(&mut self, mut func: F) -> VisitResult
+        where F: FnMut(&mut dyn Visit, &str) -> VisitResult {
+        func(&mut self.spot_shadows_enabled, "SpotShadowsEnabled")?;
+        func(&mut self.spot_soft_shadows, "SpotSoftShadows")?;
+        func(&mut self.spot_shadows_distance, "SpotShadowsDistance")?;
+        func(&mut self.point_shadows_enabled, "PointShadowsEnabled")?;
+        func(&mut self.point_soft_shadows, "PointSoftShadows")?;
+        func(&mut self.point_shadows_distance, "PointShadowsDistance")?;
+        func(&mut self.effect_limits, "EffectLimits")?;
+        func(&mut self.hud_scale, "HudScale")?;
+        func(&mut self.hud_layout, "HudLayout")?;
+        func(&mut self.loadout, "Loadout")?;
+        func(&mut self.crosshair, "Crosshair")?;
+        func(&mut self.pause_when_unfocused, "PauseWhenUnfocused")?;
+        func(&mut self.bots_fight_each_other, "BotsFightEachOther")?;
+        func(&mut self.bot_sight_range, "BotSightRange")?;
+        func(&mut self.damage_multipliers, "DamageMultipliers")?;
+        func(&mut self.spawn_protection, "SpawnProtection")?;
+        func(&mut self.auto_balance, "AutoBalance")?;
+        func(&mut self.intro_countdown, "IntroCountdown")?;
+        func(&mut self.time_limit_secs, "TimeLimit")?;
+        func(&mut self.frag_limit, "FragLimit")?;
+        func(&mut self.match_log_enabled, "MatchLogEnabled")?;
+        func(&mut self.mouse_sens_x, "MouseSensX")?;
+        func(&mut self.mouse_sens_y, "MouseSensY")?;
+        func(&mut self.link_mouse_axes, "LinkMouseAxes")?;
+        func(&mut self.smooth_mouse, "SmoothMouse")?;
+        func(&mut self.mouse_smoothing, "MouseSmoothing")?;
+        func(&mut self.mouse_acceleration, "MouseAcceleration")?;
+        func(&mut self.screen_shake, "ScreenShake")?;
+        func(&mut self.head_bob, "HeadBob")?;
+        func(&mut self.auto_switch_weapon, "AutoSwitchWeapon")?;
+        func(&mut self.toggle_sprint, "ToggleSprint")?;
+        func(&mut self.auto_reload, "AutoReload")?;
+        func(&mut self.manual_pickup, "ManualPickup")?;
+        func(&mut self.ads_sensitivity, "AdsSensitivity")?;
+        func(&mut self.color_blind_mode, "ColorBlindMode")?;
+        func(&mut self.show_item_timers, "ShowItemTimers")?;
+        func(&mut self.nameplates_through_walls, "NameplatesThroughWalls")?;
+        func(&mut self.max_sounds, "MaxSounds")?;
+        func(&mut self.content_filter, "ContentFilter")?;
+        func(&mut self.damage_feedback, "DamageFeedback")?;
+        func(&mut self.window_geometry, "WindowGeometry")?;
+        func(&mut self.item_glow, "ItemGlow")?;
+        func(&mut self.pickup_switch, "PickupSwitch")?;
+        func(&mut self.precise_frame_pacing, "PreciseFramePacing")?;
+        func(&mut self.reload_on_switch, "ReloadOnSwitch")?;
+        func(&mut self.corpse_limits, "CorpseLimits")?;
+        func(&mut self.hud_theme, "HudTheme")?;
+        func(&mut self.rocket_jump, "RocketJump")?;
+        func(&mut self.observer_mode, "ObserverMode")?;
+        func(&mut self.input_buffer, "InputBuffer")?;
+        func(&mut self.hit_multipliers, "HitMultipliers")?;
+        func(&mut self.killcam, "Killcam")?;
+        func(&mut self.weapon_drop, "WeaponDrop")?;
+        func(&mut self.path_budget, "PathBudget")?;
+        func(&mut self.low_latency, "LowLatency")?;
+        func(&mut self.health_regen, "HealthRegen")?;
+        func(&mut self.accessibility, "Accessibility")?;
+        func(&mut self.death_marker, "DeathMarker")?;
+        func(&mut self.map_vote_time, "MapVoteTime")?;
+        func(&mut self.damage_numbers, "DamageNumbers")?;
+        func(&mut self.mouse_y_inverse, "MouseYInverse")?;
+        func(&mut self.shake_camera, "ShakeCamera")?;
+        func(&mut self.key_bindings, "KeyBindings")?;
+
+        Ok(())
+    }
+
+    /// Loads settings from settings file, defaults are used if there is no such file. File
+    /// written by other version of the game can lack some values or have them in other
+    /// format, such values are left default and the rest is kept.
+    pub fn load() -> Self {
+        let path = Path::new(SETTINGS_PATH);
+        let mut visitor = match Visitor::load_binary(path) {
+            Ok(visitor) => visitor,
+            Err(_) => {
+                println!("There is no settings file, defaults will be used.");
+                return Settings::default();
+            }
+        };
+        let mut settings = Settings::default();
+        match settings.visit("Settings", &mut visitor) {
+            Ok(_) => settings,
+            Err(e) => {
+                println!("Settings file is incomplete, values that can't be read are reset to defaults. Reason: {:?}", e);
+                Settings::load_tolerant(path)
+            }
+        }
+    }
+
+    /// Reads every value of settings on its own, values that can't be read are left default.
+    fn load_tolerant(path: &Path) -> Self {
+        let mut settings = Settings::default();
+        let _ = settings.for_each_field(|field, name| {
+            // Failed read can leave visitor inside of a region of the value, so every value
+            // is read by its own visitor.
+            let result = Visitor::load_binary(path).and_then(|mut visitor| {
+                visitor.enter_region("Settings")?;
+                field.visit(name, &mut visitor)
+            });
+            if let Err(e) = result {
+                println!("Setting {} is reset to default. Reason: {:?}", name, e);
+            }
+            Ok(())
+        });
+        settings
+    }
+
+    pub fn save(&mut self) {
+        let mut visitor = Visitor::new();
+        let result = self.visit("Settings", &mut visitor)
+            .and_then(|_| visitor.save_binary(Path::new(SETTINGS_PATH)));
+        match result {
+            Ok(_) => println!("Settings saved."),
+            Err(e) => println!("Unable to save settings. Reason: {:?}", e),
+        }
+    }
+
+    /// Writes preferences and key bindings into a text file, one `name = value` pair per
+    /// line. Window geometry and options of last started match are not exported, they
+    /// are specific to a machine and to a match.
+    pub fn export(&self, control_scheme: &ControlScheme, path: &Path) -> Result<(), String> {
+        let mut text = String::from("# Rusty Shooter settings\n");
+        for (name, value) in self.export_values(control_scheme) {
+            writeln!(text, "{} = {}", name, value).unwrap();
+        }
+        std::fs::write(path, text).map_err(|e| format!("unable to write {}: {}", path.display(), e))
+    }
+
+    fn export_values(&self, control_scheme: &ControlScheme) -> Vec<(String, String)> {
+        let mut values = vec![
+            ("spot_shadows".to_owned(), self.spot_shadows_enabled.to_string()),
+            ("spot_soft_shadows".to_owned(), self.spot_soft_shadows.to_string()),
+            ("spot_shadows_distance".to_owned(), self.spot_shadows_distance.to_string()),
+            ("point_shadows".to_owned(), self.point_shadows_enabled.to_string()),
+            ("point_soft_shadows".to_owned(), self.point_soft_shadows.to_string()),
+            ("point_shadows_distance".to_owned(), self.point_shadows_distance.to_string()),
+            ("effects.max_effects".to_owned(), self.effect_limits.max_effects.to_string()),
+            ("effects.particle_density".to_owned(), self.effect_limits.particle_density.to_string()),
+            ("effects.lifetime_scale".to_owned(), self.effect_limits.lifetime_scale.to_string()),
+            ("hud_scale".to_owned(), self.hud_scale.to_string()),
+            ("hud.crosshair".to_owned(), self.hud_layout.crosshair.to_string()),
+            ("hud.theme".to_owned(), self.hud_theme.preset.id().to_string()),
+            ("hud.theme_color".to_owned(), format!("{} {} {}", self.hud_theme.custom.r, self.hud_theme.custom.g, self.hud_theme.custom.b)),
+            ("text_scale".to_owned(), self.accessibility.text_scale.to_string()),
+            ("high_contrast".to_owned(), self.accessibility.high_contrast.to_string()),
+        ];
+        for (name, element) in HUD_ELEMENTS.iter() {
+            let layout = self.hud_layout.element(*element);
+            values.push((format!("hud.{}.visible", setting_name(name)), layout.visible.to_string()));
+            values.push((format!("hud.{}.anchor", setting_name(name)), layout.anchor.id().to_string()));
+        }
+        values.extend(vec![
+            ("crosshair.style".to_owned(), self.crosshair.style.id().to_string()),
+            ("crosshair.color".to_owned(), format!("{} {} {}", self.crosshair.color.r, self.crosshair.color.g, self.crosshair.color.b)),
+            ("crosshair.thickness".to_owned(), self.crosshair.thickness.to_string()),
+            ("crosshair.gap".to_owned(), self.crosshair.gap.to_string()),
+            ("crosshair.length".to_owned(), self.crosshair.length.to_string()),
+            ("crosshair.hit_confirm".to_owned(), self.crosshair.hit_confirm.to_string()),
+            ("crosshair.hit_confirm_color".to_owned(), format!("{} {} {}", self.crosshair.hit_confirm_color.r, self.crosshair.hit_confirm_color.g, self.crosshair.hit_confirm_color.b)),
+            ("pause_when_unfocused".to_owned(), self.pause_when_unfocused.to_string()),
+            ("precise_frame_pacing".to_owned(), self.precise_frame_pacing.to_string()),
+            ("low_latency".to_owned(), self.low_latency.to_string()),
+            ("color_blind_mode".to_owned(), self.color_blind_mode.id().to_string()),
+            ("show_item_timers".to_owned(), self.show_item_timers.to_string()),
+            ("death_marker".to_owned(), self.death_marker.to_string()),
+            ("damage_numbers".to_owned(), self.damage_numbers.to_string()),
+            ("map_vote_time".to_owned(), self.map_vote_time.to_string()),
+            ("item_glow".to_owned(), self.item_glow.to_string()),
+            ("corpses.lifetime".to_owned(), self.corpse_limits.lifetime.to_string()),
+            ("corpses.max_corpses".to_owned(), self.corpse_limits.max_corpses.to_string()),
+            ("rocket_jump.self_damage".to_owned(), self.rocket_jump.self_damage.id().to_string()),
+            ("rocket_jump.self_knockback".to_owned(), self.rocket_jump.self_knockback.to_string()),
+            ("hit.head".to_owned(), self.hit_multipliers.head.to_string()),
+            ("hit.torso".to_owned(), self.hit_multipliers.torso.to_string()),
+            ("hit.limbs".to_owned(), self.hit_multipliers.limbs.to_string()),
+            ("killcam".to_owned(), self.killcam.to_string()),
+            ("weapon_drop".to_owned(), self.weapon_drop.to_string()),
+            ("path_budget".to_owned(), self.path_budget.to_string()),
+            ("nameplates_through_walls".to_owned(), self.nameplates_through_walls.to_string()),
+            ("max_sounds".to_owned(), self.max_sounds.to_string()),
+            ("gore".to_owned(), self.content_filter.gore.to_string()),
+            ("clean_language".to_owned(), self.content_filter.clean_language.to_string()),
+            ("damage_direction".to_owned(), self.damage_feedback.direction_indicator.to_string()),
+            ("damage_vignette".to_owned(), self.damage_feedback.vignette.to_string()),
+            ("hit_flash".to_owned(), self.damage_feedback.hit_flash.to_string()),
+            ("mouse_sens_x".to_owned(), control_scheme.mouse_sens_x.to_string()),
+            ("mouse_sens_y".to_owned(), control_scheme.mouse_sens_y.to_string()),
+            ("link_mouse_axes".to_owned(), control_scheme.link_mouse_axes.to_string()),
+            ("mouse_y_inverse".to_owned(), control_scheme.mouse_y_inverse.to_string()),
+            ("smooth_mouse".to_owned(), control_scheme.smooth_mouse.to_string()),
+            ("mouse_smoothing".to_owned(), control_scheme.mouse_smoothing.to_string()),
+            ("mouse_acceleration".to_owned(), control_scheme.mouse_acceleration.to_string()),
+            ("shake_camera".to_owned(), control_scheme.shake_camera.to_string()),
+            ("screen_shake".to_owned(), control_scheme.screen_shake.to_string()),
+            ("head_bob".to_owned(), control_scheme.head_bob.to_string()),
+            ("auto_switch_weapon".to_owned(), control_scheme.auto_switch_weapon.to_string()),
+            ("pickup_switch".to_owned(), control_scheme.pickup_switch.id().to_string()),
+            ("toggle_sprint".to_owned(), control_scheme.toggle_sprint.to_string()),
+            ("auto_reload".to_owned(), control_scheme.auto_reload.to_string()),
+            ("reload_on_switch".to_owned(), control_scheme.reload_on_switch.to_string()),
+            ("input_buffer".to_owned(), control_scheme.input_buffer.to_string()),
+            ("manual_pickup".to_owned(), control_scheme.manual_pickup.to_string()),
+            ("ads_sensitivity".to_owned(), control_scheme.ads_sensitivity.to_string()),
+        ]);
+        for definition in control_scheme.buttons().iter() {
+            values.push((format!("bind.{}", setting_name(&definition.description)), definition.button.name()));
+        }
+        values
+    }
+
+    /// Reads settings from a file made by [`Settings::export`], values that are missing in
+    /// the file are taken from `self`. Every value is validated before anything is changed,
+    /// so malformed file leaves settings and key bindings intact. Imported key bindings and
+    /// mouse settings are written into control scheme.
+    pub fn import(&self, control_scheme: &mut ControlScheme, path: &Path) -> Result<Settings, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("unable to read {}: {}", path.display(), e))?;
+
+        let mut settings = self.clone();
+        settings.store_control_scheme(control_scheme);
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut parts = line.splitn(2, '=');
+            let (name, value) = match (parts.next(), parts.next()) {
+                (Some(name), Some(value)) => (name.trim(), value.trim()),
+                _ => return Err(format!("line {}: expected `name = value`", index + 1)),
+            };
+            settings.import_value(name, value).map_err(|e| format!("line {}: {}", index + 1, e))?;
+        }
+
+        settings.apply_to_control_scheme(control_scheme);
+
+        Ok(settings)
+    }
+
+    /// Checks every value that can be imported against the same bounds as import does,
+    /// settings file is binary and is not checked on load. Values that are out of range,
+    /// for example in a damaged or hand-edited file, are reset to defaults.
+    pub fn validate(&mut self) {
+        let mut control_scheme = ControlScheme::default();
+        self.apply_to_control_scheme(&mut control_scheme);
+        let defaults = Settings::default().export_values(&ControlScheme::default());
+        for (name, value) in self.export_values(&control_scheme) {
+            if let Err(e) = self.clone().import_value(&name, &value) {
+                println!("Setting {} is reset to default. Reason: {}", name, e);
+                if let Some((_, default)) = defaults.iter().find(|(default_name, _)| *default_name == name) {
+                    // Default values are always in range.
+                    self.import_value(&name, default).unwrap();
+                }
+            }
+        }
+    }
+
+    fn import_value(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "spot_shadows" => self.spot_shadows_enabled = parse_bool(value)?,
+            "spot_soft_shadows" => self.spot_soft_shadows = parse_bool(value)?,
+            "spot_shadows_distance" => self.spot_shadows_distance = parse_f32(value, 1.0, 15.0)?,
+            "point_shadows" => self.point_shadows_enabled = parse_bool(value)?,
+            "point_soft_shadows" => self.point_soft_shadows = parse_bool(value)?,
+            "point_shadows_distance" => self.point_shadows_distance = parse_f32(value, 1.0, 15.0)?,
+            "effects.max_effects" => {
+                let count = parse_u32(value)?;
+                let (min, max) = MAX_EFFECTS_RANGE;
+                if count < min || count > max {
+                    return Err(format!("expected number in range {}..{}, got {}", min, max, count));
+                }
+                self.effect_limits.max_effects = count;
+            }
+            "effects.particle_density" => self.effect_limits.particle_density = parse_f32(value, 0.1, 1.0)?,
+            "effects.lifetime_scale" => self.effect_limits.lifetime_scale = parse_f32(value, 0.1, 1.0)?,
+            "hud_scale" => self.hud_scale = parse_f32(value, 0.5, 2.0)?,
+            "hud.crosshair" => self.hud_layout.crosshair = parse_bool(value)?,
+            "hud.theme" => self.hud_theme.preset = HudThemePreset::from_id(parse_u32(value)?)?,
+            "hud.theme_color" => self.hud_theme.custom = parse_color(value)?,
+            "text_scale" => self.accessibility.text_scale = parse_f32(value, TEXT_SCALE_RANGE.0, TEXT_SCALE_RANGE.1)?,
+            "high_contrast" => self.accessibility.high_contrast = parse_bool(value)?,
+            "crosshair.style" => self.crosshair.style = CrosshairStyle::from_id(parse_u32(value)?)?,
+            "crosshair.color" => self.crosshair.color = parse_color(value)?,
+            "crosshair.thickness" => self.crosshair.thickness = parse_f32(value, 1.0, 8.0)?,
+            "crosshair.gap" => self.crosshair.gap = parse_f32(value, 0.0, 30.0)?,
+            "crosshair.length" => self.crosshair.length = parse_f32(value, 2.0, 30.0)?,
+            "crosshair.hit_confirm" => self.crosshair.hit_confirm = parse_bool(value)?,
+            "crosshair.hit_confirm_color" => self.crosshair.hit_confirm_color = parse_color(value)?,
+            "pause_when_unfocused" => self.pause_when_unfocused = parse_bool(value)?,
+            "precise_frame_pacing" => self.precise_frame_pacing = parse_bool(value)?,
+            "low_latency" => self.low_latency = parse_bool(value)?,
+            "color_blind_mode" => self.color_blind_mode = ColorBlindMode::from_id(parse_u32(value)?)?,
+            "show_item_timers" => self.show_item_timers = parse_bool(value)?,
+            "death_marker" => self.death_marker = parse_bool(value)?,
+            "damage_numbers" => self.damage_numbers = parse_bool(value)?,
+            "map_vote_time" => self.map_vote_time = parse_f32(value, 0.0, MAX_VOTE_TIME)?,
+            "item_glow" => self.item_glow = parse_f32(value, 0.0, 1.0)?,
+            "corpses.lifetime" => self.corpse_limits.lifetime = parse_f32(value, 0.0, MAX_CORPSE_LIFETIME)?,
+            "corpses.max_corpses" => {
+                let count = parse_u32(value)?;
+                let (min, max) = MAX_CORPSES_RANGE;
+                if count < min || count > max {
+                    return Err(format!("expected number in range {}..{}, got {}", min, max, count));
+                }
+                self.corpse_limits.max_corpses = count;
+            }
+            "rocket_jump.self_damage" => self.rocket_jump.self_damage = SelfDamage::from_id(parse_u32(value)?)?,
+            "rocket_jump.self_knockback" => self.rocket_jump.self_knockback = parse_f32(value, 0.0, MAX_SELF_KNOCKBACK)?,
+            "hit.head" => self.hit_multipliers.head = parse_f32(value, 0.0, MAX_HIT_MULTIPLIER)?,
+            "hit.torso" => self.hit_multipliers.torso = parse_f32(value, 0.0, MAX_HIT_MULTIPLIER)?,
+            "hit.limbs" => self.hit_multipliers.limbs = parse_f32(value, 0.0, MAX_HIT_MULTIPLIER)?,
+            "killcam" => self.killcam = parse_bool(value)?,
+            "weapon_drop" => self.weapon_drop = parse_bool(value)?,
+            "path_budget" => {
+                let budget = parse_u32(value)?;
+                let (min, max) = PATH_BUDGET_RANGE;
+                if budget < min || budget > max {
+                    return Err(format!("expected number in range {}..{}, got {}", min, max, budget));
+                }
+                self.path_budget = budget;
+            }
+            "nameplates_through_walls" => self.nameplates_through_walls = parse_bool(value)?,
+            "max_sounds" => {
+                let count = parse_u32(value)?;
+                let (min, max) = MAX_SOUNDS_RANGE;
+                if count < min || count > max {
+                    return Err(format!("expected number in range {}..{}, got {}", min, max, count));
+                }
+                self.max_sounds = count;
+            }
+            "gore" => self.content_filter.gore = parse_bool(value)?,
+            "clean_language" => self.content_filter.clean_language = parse_bool(value)?,
+            "damage_direction" => self.damage_feedback.direction_indicator = parse_bool(value)?,
+            "damage_vignette" => self.damage_feedback.vignette = parse_bool(value)?,
+            "hit_flash" => self.damage_feedback.hit_flash = parse_bool(value)?,
+            // Files exported before sensitivity was split into axes have one value for both.
+            "mouse_sens" => {
+                let sens = parse_f32(value, 0.05, 2.0)?;
+                self.mouse_sens_x = sens;
+                self.mouse_sens_y = sens;
+            }
+            "mouse_sens_x" => self.mouse_sens_x = parse_f32(value, 0.05, 2.0)?,
+            "mouse_sens_y" => self.mouse_sens_y = parse_f32(value, 0.05, 2.0)?,
+            "link_mouse_axes" => self.link_mouse_axes = parse_bool(value)?,
+            "smooth_mouse" => self.smooth_mouse = parse_bool(value)?,
+            "mouse_smoothing" => self.mouse_smoothing = parse_f32(value, 0.0, 0.95)?,
+            "mouse_acceleration" => self.mouse_acceleration = parse_bool(value)?,
+            "screen_shake" => self.screen_shake = parse_f32(value, 0.0, 2.0)?,
+            "head_bob" => self.head_bob = parse_f32(value, 0.0, 2.0)?,
+            "auto_switch_weapon" => self.auto_switch_weapon = parse_bool(value)?,
+            "pickup_switch" => self.pickup_switch = PickupSwitch::from_id(parse_u32(value)?)?,
+            "toggle_sprint" => self.toggle_sprint = parse_bool(value)?,
+            "auto_reload" => self.auto_reload = parse_bool(value)?,
+            "reload_on_switch" => self.reload_on_switch = parse_bool(value)?,
+            "input_buffer" => self.input_buffer = parse_f32(value, 0.0, MAX_INPUT_BUFFER)?,
+            "manual_pickup" => self.manual_pickup = parse_bool(value)?,
+            "ads_sensitivity" => self.ads_sensitivity = parse_f32(value, 0.1, 1.0)?,
+            "mouse_y_inverse" => self.mouse_y_inverse = parse_bool(value)?,
+            "shake_camera" => self.shake_camera = parse_bool(value)?,
+            _ if name.starts_with("bind.") => {
+                let action = &name["bind.".len()..];
+                let index = ControlScheme::default()
+                    .buttons()
+                    .iter()
+                    .position(|definition| setting_name(&definition.description) == action)
+                    .ok_or_else(|| format!("unknown action {}", action))?;
+                self.key_bindings.buttons[index] = ControlButton::from_name(value)
+                    .ok_or_else(|| format!("unknown button {}", value))?;
+            }
+            _ => {
+                let element = HUD_ELEMENTS.iter().find_map(|(element_name, element)| {
+                    name.strip_prefix("hud.")
+                        .and_then(|rest| rest.strip_prefix(setting_name(element_name).as_str()))
+                        .map(|property| (*element, property))
+                });
+                match element {
+                    Some((element, ".visible")) => self.hud_layout.element_mut(element).visible = parse_bool(value)?,
+                    Some((element, ".anchor")) => self.hud_layout.element_mut(element).anchor = HudAnchor::from_id(parse_u32(value)?)?,
+                    _ => return Err(format!("unknown setting {}", name)),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remembers graphics settings from renderer's quality settings.
+    pub fn store_quality_settings(&mut self, quality: &QualitySettings) {
+        self.spot_shadows_enabled = quality.spot_shadows_enabled;
+        self.spot_soft_shadows = quality.spot_soft_shadows;
+        self.spot_shadows_distance = quality.spot_shadows_distance;
+        self.point_shadows_enabled = quality.point_shadows_enabled;
+        self.point_soft_shadows = quality.point_soft_shadows;
+        self.point_shadows_distance = quality.point_shadows_distance;
+    }
+
+    /// Writes stored graphics settings into renderer's quality settings, other fields
+    /// of quality settings are left untouched.
+    pub fn apply_to_quality_settings(&self, quality: &mut QualitySettings) {
+        quality.spot_shadows_enabled = self.spot_shadows_enabled;
+        quality.spot_soft_shadows = self.spot_soft_shadows;
+        quality.spot_shadows_distance = self.spot_shadows_distance;
+        quality.point_shadows_enabled = self.point_shadows_enabled;
+        quality.point_soft_shadows = self.point_soft_shadows;
+        quality.point_shadows_distance = self.point_shadows_distance;
+    }
+
+    /// Remembers key bindings, mouse look, screen shake and weapon switch settings from
+    /// control scheme.
+    pub fn store_control_scheme(&mut self, control_scheme: &ControlScheme) {
+        self.key_bindings.buttons = control_scheme.buttons().iter().map(|d| d.button).collect();
+        self.mouse_y_inverse = control_scheme.mouse_y_inverse;
+        self.shake_camera = control_scheme.shake_camera;
+        self.mouse_sens_x = control_scheme.mouse_sens_x;
+        self.mouse_sens_y = control_scheme.mouse_sens_y;
+        self.link_mouse_axes = control_scheme.link_mouse_axes;
+        self.smooth_mouse = control_scheme.smooth_mouse;
+        self.mouse_smoothing = control_scheme.mouse_smoothing;
+        self.mouse_acceleration = control_scheme.mouse_acceleration;
+        self.screen_shake = control_scheme.screen_shake;
+        self.head_bob = control_scheme.head_bob;
+        self.auto_switch_weapon = control_scheme.auto_switch_weapon;
+        self.pickup_switch = control_scheme.pickup_switch;
+        self.toggle_sprint = control_scheme.toggle_sprint;
+        self.auto_reload = control_scheme.auto_reload;
+        self.reload_on_switch = control_scheme.reload_on_switch;
+        self.input_buffer = control_scheme.input_buffer;
+        self.manual_pickup = control_scheme.manual_pickup;
+        self.ads_sensitivity = control_scheme.ads_sensitivity;
+    }
+
+    /// Writes stored key bindings, mouse look, screen shake and weapon switch settings into
+    /// control scheme.
+    pub fn apply_to_control_scheme(&self, control_scheme: &mut ControlScheme) {
+        for (definition, button) in control_scheme.buttons_mut().iter_mut().zip(self.key_bindings.buttons.iter()) {
+            definition.button = *button;
+        }
+        control_scheme.mouse_y_inverse = self.mouse_y_inverse;
+        control_scheme.shake_camera = self.shake_camera;
+        control_scheme.mouse_sens_x = self.mouse_sens_x;
+        control_scheme.mouse_sens_y = self.mouse_sens_y;
+        control_scheme.link_mouse_axes = self.link_mouse_axes;
+        control_scheme.smooth_mouse = self.smooth_mouse;
+        control_scheme.mouse_smoothing = self.mouse_smoothing;
+        control_scheme.mouse_acceleration = self.mouse_acceleration;
+        control_scheme.screen_shake = self.screen_shake;
+        control_scheme.head_bob = self.head_bob;
+        control_scheme.auto_switch_weapon = self.auto_switch_weapon;
+        control_scheme.pickup_switch = self.pickup_switch;
+        control_scheme.toggle_sprint = self.toggle_sprint;
+        control_scheme.auto_reload = self.auto_reload;
+        control_scheme.reload_on_switch = self.reload_on_switch;
+        control_scheme.input_buffer = self.input_buffer;
+        control_scheme.manual_pickup = self.manual_pickup;
+        control_scheme.ads_sensitivity = self.ads_sensitivity;
+    }
+}
+
+/// Name of setting in exported file made of name that is shown to user, for example
+/// "Move Forward" becomes "move_forward".
+fn setting_name(name: &str) -> String {
+    name.to_lowercase().replace(' ', "_")
+}
+
+pub fn parse_bool(value: &str) -> Result<bool, String> {
+    value.parse().map_err(|_| format!("expected true or false, got {}", value))
+}
+
+pub fn parse_u32(value: &str) -> Result<u32, String> {
+    value.parse().map_err(|_| format!("expected non-negative integer, got {}", value))
+}
+
+/// Parses opaque color written as three components separated by spaces, for example "255 0 0".
+fn parse_color(value: &str) -> Result<Color, String> {
+    let components = value
+        .split_whitespace()
+        .map(|c| c.parse::<u8>().map_err(|_| format!("invalid color component {}", c)))
+        .collect::<Result<Vec<_>, _>>()?;
+    match components.as_slice() {
+        [r, g, b] => Ok(Color::opaque(*r, *g, *b)),
+        _ => Err(format!("expected three color components, got {}", value)),
+    }
+}
+
+pub fn parse_f32(value: &str, min: f32, max: f32) -> Result<f32, String> {
+    match value.parse::<f32>() {
+        Ok(v) if v >= min && v <= max => Ok(v),
+        _ => Err(format!("expected number in range {}..{}, got {}", min, max, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rg3d::event::VirtualKeyCode;
+
+    fn custom_control_scheme() -> ControlScheme {
+        let mut control_scheme = ControlScheme::default();
+        control_scheme.jump.button = ControlButton::Key(VirtualKeyCode::J);
+        control_scheme.reload.button = ControlButton::Mouse(4);
+        control_scheme.mouse_y_inverse = true;
+        control_scheme.shake_camera = false;
+        control_scheme
+    }
+
+    fn assert_custom(control_scheme: &ControlScheme) {
+        assert!(control_scheme.jump.button == ControlButton::Key(VirtualKeyCode::J));
+        assert!(control_scheme.reload.button == ControlButton::Mouse(4));
+        assert!(control_scheme.mouse_y_inverse);
+        assert!(!control_scheme.shake_camera);
+    }
+
+    #[test]
+    fn loaded_values_out_of_range_are_reset() {
+        let mut settings = Settings {
+            hud_scale: 10.0,
+            max_sounds: 0,
+            path_budget: std::u32::MAX,
+            mouse_sens_x: std::f32::NAN,
+            item_glow: 0.5,
+            ..Default::default()
+        };
+        settings.validate();
+        let defaults = Settings::default();
+        assert_eq!(settings.hud_scale, defaults.hud_scale);
+        assert_eq!(settings.max_sounds, defaults.max_sounds);
+        assert_eq!(settings.path_budget, defaults.path_budget);
+        assert_eq!(settings.mouse_sens_x, defaults.mouse_sens_x);
+        // Values in range are kept.
+        assert_eq!(settings.item_glow, 0.5);
+    }
+
+    #[test]
+    fn key_bindings_are_persisted() {
+        let path = std::env::temp_dir().join("rusty_shooter_settings_bindings.bin");
+        let mut settings = Settings::default();
+        settings.store_control_scheme(&custom_control_scheme());
+        let mut visitor = Visitor::new();
+        settings.visit("Settings", &mut visitor).unwrap();
+        visitor.save_binary(&path).unwrap();
+
+        let mut loaded = Settings::default();
+        let mut visitor = Visitor::load_binary(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        loaded.visit("Settings", &mut visitor).unwrap();
+
+        let mut control_scheme = ControlScheme::default();
+        loaded.apply_to_control_scheme(&mut control_scheme);
+        assert_custom(&control_scheme);
+    }
+
+    #[test]
+    fn key_bindings_survive_export_and_import() {
+        let path = std::env::temp_dir().join("rusty_shooter_settings_bindings.txt");
+        let exported = custom_control_scheme();
+        let mut settings = Settings::default();
+        settings.store_control_scheme(&exported);
+        settings.export(&exported, &path).unwrap();
+
+        let mut control_scheme = ControlScheme::default();
+        let imported = Settings::default().import(&mut control_scheme, &path);
+        std::fs::remove_file(&path).unwrap();
+        let imported = imported.unwrap();
+        assert_custom(&control_scheme);
+        assert!(imported.key_bindings == settings.key_bindings);
+    }
+}
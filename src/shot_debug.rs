@@ -0,0 +1,199 @@
+//! Debug overlay of shots for weapon tuning and reports of hits that don't register or deal
+//! wrong damage. Every shot leaves a line along its direction and every hit leaves a mark at
+//! its point, damage of each hit is printed with every step that changed it and last hits
+//! are listed in debug text. It is `shots` category of debug draw, so it works while debug
+//! draw is on and `set debug_shots 1` was entered. Records are made only while it works, so
+//! it costs nothing otherwise. Records are not saved.
+
+use rg3d::{
+    core::{
+        math::vec3::Vec3,
+        color::Color,
+    },
+    renderer::debug_renderer::DebugRenderer,
+};
+use crate::{
+    debug_draw,
+    damage::{HitRegion, DamageKind},
+    weapon::WeaponKind,
+};
+use std::fmt::Write;
+
+/// Time in seconds that shots and hits stay on screen.
+const RECORD_LIFETIME: f32 = 4.0;
+/// Shots are drawn as lines of this length, projectiles may fly further.
+const SHOT_LENGTH: f32 = 40.0;
+/// Oldest records are dropped when there are more of them, automatic weapons of many bots
+/// would clutter view otherwise.
+const MAX_RECORDS: usize = 64;
+/// Amount of last hits that are listed in debug text.
+const LISTED_HITS: usize = 5;
+
+struct ShotRecord {
+    origin: Vec3,
+    direction: Vec3,
+    by_player: bool,
+    time_left: f32,
+}
+
+/// Damage of a hit by every step of damage path, each step is applied over previous one.
+pub struct HitRecord {
+    pub attacker: String,
+    pub victim: String,
+    pub weapon: Option<WeaponKind>,
+    pub kind: DamageKind,
+    /// Point of hit, center of explosion for explosions. None if damage has no point.
+    pub position: Option<Vec3>,
+    /// Distance from center of explosion to victim, damage falls off with it.
+    pub explosion_distance: Option<f32>,
+    pub region: Option<HitRegion>,
+    /// Damage that projectile or attack dealt, explosions have falloff applied already.
+    pub base: f32,
+    /// Damage after scale of rocket jump rules, same as base for hits of other actors.
+    pub after_self_damage: f32,
+    pub region_multiplier: f32,
+    /// Damage after multipliers of match rules and auto balance, this is what victim takes
+    /// unless it is protected.
+    pub applied: f32,
+    /// Victim was protected after spawn and took no damage.
+    pub spawn_protected: bool,
+    /// Player is attacker or victim of the hit.
+    pub player_involved: bool,
+    time_left: f32,
+}
+
+impl HitRecord {
+    pub fn new(attacker: String, victim: String, kind: DamageKind, base: f32) -> Self {
+        Self {
+            attacker,
+            victim,
+            weapon: None,
+            kind,
+            position: None,
+            explosion_distance: None,
+            region: None,
+            base,
+            after_self_damage: base,
+            region_multiplier: 1.0,
+            applied: 0.0,
+            spawn_protected: false,
+            player_involved: false,
+            time_left: RECORD_LIFETIME,
+        }
+    }
+
+    /// One line description of the hit with breakdown of its damage.
+    pub fn describe(&self) -> String {
+        let mut text = format!("{} -> {}: {:?}", self.attacker, self.victim, self.kind);
+        if let Some(weapon) = self.weapon {
+            write!(text, " ({:?})", weapon).unwrap();
+        }
+        if let Some(region) = self.region {
+            write!(text, ", {:?}", region).unwrap();
+        }
+        write!(text, ", base {:.1}", self.base).unwrap();
+        if let Some(distance) = self.explosion_distance {
+            write!(text, " at {:.1} m from explosion", distance).unwrap();
+        }
+        if self.after_self_damage != self.base {
+            write!(text, ", self damage {:.1}", self.after_self_damage).unwrap();
+        }
+        if self.region_multiplier != 1.0 {
+            write!(text, ", x{:.2} region", self.region_multiplier).unwrap();
+        }
+        if self.spawn_protected {
+            text.push_str(", spawn protected");
+        } else {
+            write!(text, ", applied {:.1}", self.applied).unwrap();
+        }
+        text
+    }
+}
+
+#[derive(Default)]
+pub struct ShotDebug {
+    enabled: bool,
+    shots: Vec<ShotRecord>,
+    hits: Vec<HitRecord>,
+}
+
+impl ShotDebug {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Turning overlay off forgets every record.
+    pub fn set_enabled(&mut self, enabled: bool) {
+        if !enabled {
+            self.shots.clear();
+            self.hits.clear();
+        }
+        self.enabled = enabled;
+    }
+
+    pub fn add_shot(&mut self, origin: Vec3, direction: Vec3, by_player: bool) {
+        if self.shots.len() >= MAX_RECORDS {
+            self.shots.remove(0);
+        }
+        self.shots.push(ShotRecord {
+            origin,
+            direction,
+            by_player,
+            time_left: RECORD_LIFETIME,
+        });
+    }
+
+    /// Adds hit and prints it if player took part in it, hits between bots are only drawn.
+    pub fn add_hit(&mut self, hit: HitRecord) {
+        if hit.player_involved {
+            println!("Hit: {}", hit.describe());
+        }
+        if self.hits.len() >= MAX_RECORDS {
+            self.hits.remove(0);
+        }
+        self.hits.push(hit);
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for shot in self.shots.iter_mut() {
+            shot.time_left -= dt;
+        }
+        self.shots.retain(|shot| shot.time_left > 0.0);
+        for hit in self.hits.iter_mut() {
+            hit.time_left -= dt;
+        }
+        self.hits.retain(|hit| hit.time_left > 0.0);
+    }
+
+    /// Draws shots of player in yellow and shots of others in gray, hits are crosses colored
+    /// by region: red for head, orange for torso, yellow for limbs and white for no region.
+    pub fn draw(&self, debug_renderer: &mut DebugRenderer) {
+        for shot in self.shots.iter() {
+            let color = if shot.by_player {
+                Color::opaque(255, 255, 0)
+            } else {
+                Color::opaque(150, 150, 150)
+            };
+            debug_draw::draw_line(debug_renderer, shot.origin, shot.origin + shot.direction.scale(SHOT_LENGTH), color);
+        }
+        for hit in self.hits.iter() {
+            if let Some(position) = hit.position {
+                let color = match hit.region {
+                    Some(HitRegion::Head) => Color::opaque(255, 0, 0),
+                    Some(HitRegion::Torso) => Color::opaque(255, 140, 0),
+                    Some(HitRegion::Limbs) => Color::opaque(255, 255, 0),
+                    None => Color::opaque(255, 255, 255),
+                };
+                debug_draw::draw_cross(debug_renderer, position, 0.3, color);
+            }
+        }
+    }
+
+    /// Lists last hits of player for debug text.
+    pub fn write_last_hits(&self, out: &mut String) {
+        out.push_str("\nLast hits:");
+        for hit in self.hits.iter().rev().filter(|hit| hit.player_involved).take(LISTED_HITS) {
+            write!(out, "\n  {}", hit.describe()).unwrap();
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Weapon skins. Skin replaces material of every mesh of weapon model when the weapon is
+//! created, skin is picked per weapon in loadout. Skins are looked up by id, so loadout and
+//! definitions file refer to them by id and unknown ids fall back to default look.
+
+use rg3d::{
+    core::{
+        color::Color,
+        pool::Handle,
+    },
+    scene::{
+        node::Node,
+        graph::Graph,
+    },
+    engine::resource_manager::ResourceManager,
+    resource::texture::TextureKind,
+};
+
+/// Skin that leaves weapon model as it was exported.
+pub const DEFAULT_SKIN: &str = "default";
+
+pub struct WeaponSkin {
+    pub id: &'static str,
+    /// Name that is shown in loadout.
+    pub name: &'static str,
+    /// Diffuse texture that replaces texture of model, None keeps texture of model.
+    pub texture: Option<&'static str>,
+    /// Tint that is multiplied with texture.
+    pub color: Color,
+}
+
+/// Every available skin in the order they're shown in loadout, default one goes first.
+pub const WEAPON_SKINS: [WeaponSkin; 5] = [
+    WeaponSkin {
+        id: DEFAULT_SKIN,
+        name: "Default",
+        texture: None,
+        color: Color::WHITE,
+    },
+    WeaponSkin {
+        id: "desert",
+        name: "Desert",
+        texture: None,
+        color: Color::opaque(230, 200, 150),
+    },
+    WeaponSkin {
+        id: "carbon",
+        name: "Carbon",
+        texture: None,
+        color: Color::opaque(90, 90, 100),
+    },
+    WeaponSkin {
+        id: "gold",
+        name: "Gold",
+        texture: None,
+        color: Color::opaque(255, 200, 80),
+    },
+    WeaponSkin {
+        id: "arctic",
+        name: "Arctic",
+        texture: None,
+        color: Color::opaque(220, 235, 255),
+    },
+];
+
+pub fn find(id: &str) -> Option<&'static WeaponSkin> {
+    WEAPON_SKINS.iter().find(|skin| skin.id == id)
+}
+
+/// Index of skin in `WEAPON_SKINS`, unknown skin gives index of default one.
+pub fn index_of(id: &str) -> usize {
+    WEAPON_SKINS.iter().position(|skin| skin.id == id).unwrap_or(0)
+}
+
+/// Applies skin to every mesh of model and returns id of skin that was actually applied.
+/// Unknown skin or skin with texture that failed to load is replaced with default one.
+pub fn apply(graph: &mut Graph, model: Handle<Node>, id: &str, resource_manager: &mut ResourceManager) -> &'static str {
+    let skin = find(id).unwrap_or_else(|| {
+        println!("Unknown weapon skin {}, default is used instead.", id);
+        &WEAPON_SKINS[0]
+    });
+
+    let texture = match skin.texture {
+        Some(path) => match resource_manager.request_texture(path, TextureKind::RGBA8) {
+            Some(texture) => Some(texture),
+            None => {
+                println!("Unable to load texture {} of weapon skin {}, default is used instead.", path, skin.id);
+                return apply(graph, model, DEFAULT_SKIN, resource_manager);
+            }
+        },
+        None => None,
+    };
+
+    let mut stack = vec![model];
+    while let Some(handle) = stack.pop() {
+        if let Node::Mesh(mesh) = &mut graph[handle] {
+            for surface in mesh.surfaces_mut() {
+                surface.set_color(skin.color);
+                if let Some(texture) = texture.as_ref() {
+                    surface.set_diffuse_texture(texture.clone());
+                }
+            }
+        }
+        stack.extend_from_slice(graph[handle].children());
+    }
+
+    skin.id
+}
@@ -0,0 +1,106 @@
+//! Clouds made by smoke grenades. Cloud is a sphere that blocks sight of bots while it
+//! lasts, particles only show it to the player. Cloud grows right after detonation and
+//! shrinks before it disappears, so sight is blocked only where smoke is thick.
+
+use rg3d::core::{
+    visitor::{Visit, VisitResult, Visitor},
+    math::vec3::Vec3,
+};
+
+/// Radius of fully grown cloud in meters.
+pub const SMOKE_RADIUS: f32 = 3.5;
+/// Time in seconds during which cloud blocks sight.
+pub const SMOKE_DURATION: f32 = 15.0;
+/// Cloud grows to full size over this time after detonation.
+const SMOKE_GROW_TIME: f32 = 1.5;
+/// Cloud shrinks down to nothing over this time before it disappears.
+const SMOKE_FADE_TIME: f32 = 3.0;
+
+pub struct SmokeCloud {
+    position: Vec3,
+    time_left: f32,
+}
+
+impl Default for SmokeCloud {
+    fn default() -> Self {
+        Self {
+            position: Default::default(),
+            time_left: 0.0,
+        }
+    }
+}
+
+impl SmokeCloud {
+    /// Current radius of the cloud, it depends on time since detonation.
+    fn radius(&self) -> f32 {
+        let grow = ((SMOKE_DURATION - self.time_left) / SMOKE_GROW_TIME).min(1.0);
+        let fade = (self.time_left / SMOKE_FADE_TIME).min(1.0);
+        SMOKE_RADIUS * grow.min(fade).max(0.0)
+    }
+
+    /// Returns true if segment between two points passes through the cloud.
+    fn intersects_segment(&self, from: Vec3, to: Vec3) -> bool {
+        let radius = self.radius();
+        let segment = to - from;
+        let sqr_len = segment.sqr_len();
+        let t = if sqr_len > 0.0 {
+            ((self.position - from).dot(&segment) / sqr_len).max(0.0).min(1.0)
+        } else {
+            0.0
+        };
+        (from + segment.scale(t)).sqr_distance(&self.position) < radius * radius
+    }
+}
+
+impl Visit for SmokeCloud {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.position.visit("Position", visitor)?;
+        self.time_left.visit("TimeLeft", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Clouds of smoke on level. Particles of a cloud are made by effects and remove
+/// themselves, container keeps only what is needed to block sight.
+#[derive(Default)]
+pub struct SmokeContainer {
+    clouds: Vec<SmokeCloud>,
+}
+
+impl SmokeContainer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn add(&mut self, position: Vec3) {
+        self.clouds.push(SmokeCloud {
+            position,
+            time_left: SMOKE_DURATION,
+        });
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for cloud in self.clouds.iter_mut() {
+            cloud.time_left -= dt;
+        }
+        self.clouds.retain(|cloud| cloud.time_left > 0.0);
+    }
+
+    /// Returns true if sight line between two points passes through any cloud.
+    pub fn blocks_sight(&self, from: Vec3, to: Vec3) -> bool {
+        self.clouds.iter().any(|cloud| cloud.intersects_segment(from, to))
+    }
+}
+
+impl Visit for SmokeContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.clouds.visit("Clouds", visitor)?;
+
+        visitor.leave_region()
+    }
+}
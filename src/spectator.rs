@@ -0,0 +1,471 @@
+//! Spectator camera is used to render world when there is no player on level. This includes
+//! situation when player is dead and waits for respawn - all dead actors are removed from
+//! level. Spectator starts with "dropping head" effect, after that player can fly freely
+//! around the level or watch living bots: fire button selects next bot, alternative fire
+//! selects previous one and camera toggle button switches between free fly and following.
+//! Observer of a match without player uses the same camera, it just starts in free fly.
+//! Death of player by someone's hand can start with a killcam: camera replays last moments
+//! from where the killer was, aimed at where player was, any fire button skips it.
+
+use std::{
+    rc::Rc,
+    cell::RefCell,
+};
+use crate::{
+    actor::{Actor, ActorContainer},
+    control_scheme::{ControlScheme, ControlButton},
+    frame_blend_factor,
+};
+use rg3d::{
+    core::{
+        pool::Handle,
+        math::{vec3::Vec3, quat::Quat},
+        visitor::{Visit, Visitor, VisitResult},
+    },
+    event::{DeviceEvent, Event, ElementState},
+    scene::{
+        node::Node,
+        Scene,
+        camera::CameraBuilder,
+        base::BaseBuilder,
+    },
+};
+
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum SpectatorMode {
+    /// Camera falls down to the floor at the place where player died.
+    DroppingHead,
+    /// Camera is controlled by player and flies through everything.
+    FreeFly,
+    /// Camera is attached to the head of specified actor.
+    Follow(Handle<Actor>),
+    /// Camera replays viewpoint of killer, dropping head follows when replay ends.
+    Killcam,
+}
+
+impl Default for SpectatorMode {
+    fn default() -> Self {
+        SpectatorMode::DroppingHead
+    }
+}
+
+impl SpectatorMode {
+    fn id(&self) -> u32 {
+        match self {
+            SpectatorMode::DroppingHead => 0,
+            SpectatorMode::FreeFly => 1,
+            SpectatorMode::Follow(_) => 2,
+            SpectatorMode::Killcam => 3,
+        }
+    }
+
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(SpectatorMode::DroppingHead),
+            1 => Ok(SpectatorMode::FreeFly),
+            2 => Ok(SpectatorMode::Follow(Handle::NONE)),
+            3 => Ok(SpectatorMode::Killcam),
+            _ => Err(format!("Invalid spectator mode {}", id))
+        }
+    }
+}
+
+impl Visit for SpectatorMode {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut id = self.id();
+        id.visit("Id", visitor)?;
+        if visitor.is_reading() {
+            *self = Self::from_id(id)?;
+        }
+
+        if let SpectatorMode::Follow(actor) = self {
+            actor.visit("Actor", visitor)?;
+        }
+
+        visitor.leave_region()
+    }
+}
+
+/// Killcam keeps last frame on screen for this long after replay ends, in seconds.
+const KILLCAM_HOLD: f32 = 0.6;
+
+/// Free fly camera speed, in meters per second.
+const FREE_FLY_SPEED: f32 = 6.0;
+
+/// Free fly camera speed when run button is held, in meters per second.
+const FREE_FLY_RUN_SPEED: f32 = 18.0;
+
+/// Frame of killcam: time, position of camera and point it looks at.
+pub type KillcamFrame = (f32, Vec3, Vec3);
+
+#[derive(Default)]
+struct SpectatorController {
+    move_forward: bool,
+    move_backward: bool,
+    move_left: bool,
+    move_right: bool,
+    run: bool,
+    next_target: bool,
+    prev_target: bool,
+    toggle_free_fly: bool,
+}
+
+pub struct Spectator {
+    camera: Handle<Node>,
+    mode: SpectatorMode,
+    target_position: Vec3,
+    yaw: f32,
+    pitch: f32,
+    controller: SpectatorController,
+    /// Position of camera at the moment of death, dropping head starts from it after killcam.
+    death_position: Vec3,
+    /// Not saved, killcam of loaded game ends right away.
+    killcam: Vec<KillcamFrame>,
+    killcam_time: f32,
+}
+
+impl Default for Spectator {
+    fn default() -> Self {
+        Self {
+            camera: Handle::NONE,
+            mode: Default::default(),
+            target_position: Default::default(),
+            yaw: 0.0,
+            pitch: 0.0,
+            controller: Default::default(),
+            death_position: Default::default(),
+            killcam: Default::default(),
+            killcam_time: 0.0,
+        }
+    }
+}
+
+impl Visit for Spectator {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.camera.visit("Camera", visitor)?;
+        self.mode.visit("Mode", visitor)?;
+        self.target_position.visit("TargetPosition", visitor)?;
+        self.yaw.visit("Yaw", visitor)?;
+        self.pitch.visit("Pitch", visitor)?;
+        self.death_position.visit("DeathPosition", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl Spectator {
+    pub fn new(scene: &mut Scene) -> Self {
+        let camera = scene.graph.add_node(Node::Camera(
+            CameraBuilder::new(BaseBuilder::new())
+                .enabled(false)
+                .build()));
+
+        Self {
+            camera,
+            ..Default::default()
+        }
+    }
+
+    pub fn camera(&self) -> Handle<Node> {
+        self.camera
+    }
+
+    pub fn is_active(&self, scene: &Scene) -> bool {
+        if let Node::Camera(camera) = &scene.graph[self.camera] {
+            camera.is_enabled()
+        } else {
+            false
+        }
+    }
+
+    /// Turns on spectator camera at given position, camera will then fall to `target_position`.
+    pub fn begin(&mut self, scene: &mut Scene, position: Vec3, look: Vec3, target_position: Vec3) {
+        if let Node::Camera(camera) = &mut scene.graph[self.camera] {
+            camera
+                .set_enabled(true)
+                .local_transform_mut()
+                .set_position(position);
+        }
+        self.yaw = look.x.atan2(look.z).to_degrees();
+        self.pitch = 0.0;
+        self.target_position = target_position;
+        self.death_position = position;
+        self.mode = SpectatorMode::DroppingHead;
+        self.controller = Default::default();
+        self.killcam.clear();
+    }
+
+    /// Starts killcam that replays given frames, must be called after [`Spectator::begin`].
+    pub fn begin_killcam(&mut self, frames: Vec<KillcamFrame>) {
+        if let Some(&(time, _, _)) = frames.first() {
+            self.killcam_time = time;
+            self.killcam = frames;
+            self.mode = SpectatorMode::Killcam;
+        }
+    }
+
+    fn end_killcam(&mut self, scene: &mut Scene) {
+        self.killcam.clear();
+        self.mode = SpectatorMode::DroppingHead;
+        scene.graph[self.camera]
+            .local_transform_mut()
+            .set_position(self.death_position);
+    }
+
+    /// Moves camera to the point of killcam at current replay time.
+    fn update_killcam(&mut self, scene: &mut Scene, dt: f32) {
+        self.killcam_time += dt;
+        let last_time = match self.killcam.last() {
+            Some(&(time, _, _)) if self.killcam_time <= time + KILLCAM_HOLD => time,
+            _ => {
+                self.end_killcam(scene);
+                return;
+            }
+        };
+
+        let next = self.killcam
+            .iter()
+            .position(|&(time, _, _)| time >= self.killcam_time)
+            .unwrap_or(self.killcam.len() - 1);
+        let (position, target) = if next == 0 || self.killcam_time >= last_time {
+            let (_, position, target) = self.killcam[next];
+            (position, target)
+        } else {
+            let (from_time, from_position, from_target) = self.killcam[next - 1];
+            let (to_time, to_position, to_target) = self.killcam[next];
+            let t = (self.killcam_time - from_time) / (to_time - from_time);
+            (from_position + (to_position - from_position).scale(t), from_target + (to_target - from_target).scale(t))
+        };
+
+        if let Some(look) = (target - position).normalized() {
+            // Remember orientation so free fly will continue from killer's viewpoint.
+            self.yaw = look.x.atan2(look.z).to_degrees();
+            self.pitch = -look.y.asin().to_degrees();
+        }
+        scene.graph[self.camera]
+            .local_transform_mut()
+            .set_position(position)
+            .set_rotation(
+                Quat::from_axis_angle(Vec3::UP, self.yaw.to_radians()) *
+                    Quat::from_axis_angle(Vec3::RIGHT, self.pitch.to_radians()));
+    }
+
+    /// Turns on spectator camera in free fly mode at given position, it is used by observer
+    /// who has no body on level at all.
+    pub fn begin_observing(&mut self, scene: &mut Scene, position: Vec3) {
+        self.begin(scene, position, Vec3::LOOK, position);
+        self.mode = SpectatorMode::FreeFly;
+    }
+
+    /// Turns off spectator camera, control returns to player.
+    pub fn end(&mut self, scene: &mut Scene) {
+        if let Node::Camera(camera) = &mut scene.graph[self.camera] {
+            camera.set_enabled(false);
+        }
+        self.mode = SpectatorMode::DroppingHead;
+        self.controller = Default::default();
+        self.killcam.clear();
+    }
+
+    pub fn process_input_event(&mut self, event: &Event<()>, control_scheme: &Rc<RefCell<ControlScheme>>) {
+        if let Event::DeviceEvent { event, .. } = event {
+            let control_scheme = control_scheme.borrow();
+
+            let mut control_button = None;
+            let mut control_button_state = ElementState::Released;
+
+            match event {
+                DeviceEvent::MouseMotion { delta } => {
+                    if let SpectatorMode::FreeFly = self.mode {
+                        self.yaw -= delta.0 as f32 * control_scheme.mouse_sens_x;
+
+                        let sens = if control_scheme.mouse_y_inverse {
+                            -control_scheme.mouse_sens_y
+                        } else {
+                            control_scheme.mouse_sens_y
+                        };
+
+                        self.pitch = (self.pitch + delta.1 as f32 * sens).max(-90.0).min(90.0);
+                    }
+                }
+                DeviceEvent::Button { button, state } => {
+                    control_button = Some(ControlButton::Mouse(*button as u8));
+                    control_button_state = *state;
+                }
+                DeviceEvent::Key(input) => {
+                    control_button = Some(ControlButton::from_key(input));
+                    control_button_state = input.state;
+                }
+                _ => ()
+            }
+
+            if let Some(control_button) = control_button {
+                let pressed = control_button_state == ElementState::Pressed;
+                if control_button == control_scheme.shoot.button {
+                    if pressed {
+                        self.controller.next_target = true;
+                    }
+                } else if control_button == control_scheme.alt_fire.button {
+                    if pressed {
+                        self.controller.prev_target = true;
+                    }
+                } else if control_button == control_scheme.toggle_camera.button {
+                    if pressed {
+                        self.controller.toggle_free_fly = true;
+                    }
+                } else if control_button == control_scheme.move_forward.button {
+                    self.controller.move_forward = pressed;
+                } else if control_button == control_scheme.move_backward.button {
+                    self.controller.move_backward = pressed;
+                } else if control_button == control_scheme.move_left.button {
+                    self.controller.move_left = pressed;
+                } else if control_button == control_scheme.move_right.button {
+                    self.controller.move_right = pressed;
+                } else if control_button == control_scheme.run.button {
+                    self.controller.run = pressed;
+                }
+            }
+        }
+    }
+
+    fn is_moving(&self) -> bool {
+        self.controller.move_forward || self.controller.move_backward ||
+            self.controller.move_left || self.controller.move_right
+    }
+
+    /// Selects next or previous living bot to watch, after last bot in given direction
+    /// camera switches to free fly mode.
+    fn select_target(&mut self, actors: &ActorContainer, forward: bool) {
+        let current = if let SpectatorMode::Follow(actor) = self.mode {
+            actor
+        } else {
+            Handle::NONE
+        };
+
+        let mut bots = actors.pair_iter()
+            .filter(|(_, actor)| if let Actor::Bot(_) = actor { !actor.is_dead() } else { false })
+            .map(|(handle, _)| handle)
+            .collect::<Vec<_>>();
+        if !forward {
+            bots.reverse();
+        }
+
+        let next = match bots.iter().position(|&bot| bot == current) {
+            Some(index) => bots.get(index + 1),
+            None => bots.first(),
+        };
+
+        self.mode = match next {
+            Some(&bot) => SpectatorMode::Follow(bot),
+            None => SpectatorMode::FreeFly,
+        };
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, dt: f32) {
+        if !self.is_active(scene) {
+            return;
+        }
+
+        if self.mode == SpectatorMode::Killcam {
+            let controller = &mut self.controller;
+            if controller.next_target || controller.prev_target || controller.toggle_free_fly {
+                // Any fire button skips killcam, it does not select a bot to watch.
+                controller.next_target = false;
+                controller.prev_target = false;
+                controller.toggle_free_fly = false;
+                self.end_killcam(scene);
+            } else {
+                self.update_killcam(scene, dt);
+                return;
+            }
+        }
+
+        if self.controller.next_target {
+            self.controller.next_target = false;
+            self.select_target(actors, true);
+        }
+
+        if self.controller.prev_target {
+            self.controller.prev_target = false;
+            self.select_target(actors, false);
+        }
+
+        if self.controller.toggle_free_fly {
+            self.controller.toggle_free_fly = false;
+            if let SpectatorMode::Follow(_) = self.mode {
+                self.mode = SpectatorMode::FreeFly;
+            } else {
+                self.select_target(actors, true);
+            }
+        }
+
+        if let SpectatorMode::Follow(actor) = self.mode {
+            if !actors.contains(actor) || actors.get(actor).is_dead() {
+                // Watched bot died, select someone else.
+                self.select_target(actors, true);
+            }
+        }
+
+        if self.mode == SpectatorMode::DroppingHead && self.is_moving() {
+            self.mode = SpectatorMode::FreeFly;
+        }
+
+        match self.mode {
+            SpectatorMode::DroppingHead => {
+                let camera = &mut scene.graph[self.camera];
+                let mut position = camera.global_position();
+                position.follow(&self.target_position, frame_blend_factor(0.1, dt));
+                camera.local_transform_mut().set_position(position);
+            }
+            SpectatorMode::FreeFly => {
+                let camera = &mut scene.graph[self.camera];
+                let look = camera.look_vector();
+                let side = camera.side_vector();
+
+                let mut velocity = Vec3::ZERO;
+                if self.controller.move_forward {
+                    velocity += look;
+                }
+                if self.controller.move_backward {
+                    velocity -= look;
+                }
+                if self.controller.move_left {
+                    velocity += side;
+                }
+                if self.controller.move_right {
+                    velocity -= side;
+                }
+
+                let speed = if self.controller.run { FREE_FLY_RUN_SPEED } else { FREE_FLY_SPEED };
+
+                let position = camera.global_position();
+                let transform = camera.local_transform_mut();
+                if let Some(normalized_velocity) = velocity.normalized() {
+                    transform.set_position(position + normalized_velocity.scale(speed * dt));
+                }
+                transform.set_rotation(
+                    Quat::from_axis_angle(Vec3::UP, self.yaw.to_radians()) *
+                        Quat::from_axis_angle(Vec3::RIGHT, self.pitch.to_radians()));
+            }
+            SpectatorMode::Follow(actor) => {
+                let actor = actors.get(actor);
+                let look = scene.graph[actor.pivot].look_vector();
+                let mut position = actor.position(&scene.physics);
+                position.y += 0.4;
+                // Remember orientation so free fly will continue from bot's viewpoint.
+                self.yaw = look.x.atan2(look.z).to_degrees();
+                self.pitch = 0.0;
+                scene.graph[self.camera]
+                    .local_transform_mut()
+                    .set_position(position)
+                    .set_rotation(Quat::from_axis_angle(Vec3::UP, self.yaw.to_radians()));
+            }
+            // Killcam is updated before everything else and ends before it gets here.
+            SpectatorMode::Killcam => (),
+        }
+    }
+}
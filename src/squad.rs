@@ -0,0 +1,140 @@
+//! Lightweight coordination of bots that fight on the same side. Every side has a
+//! blackboard, bots write sightings of enemies into it and read sightings of teammates to
+//! go after enemies they can't see themselves. Bots that go after the same enemy spread
+//! over different lanes, and while a squad fights only one bot advances at a time, others
+//! hold position and cover it.
+//!
+//! Blackboards are not saved, bots fill them again right after a game is loaded.
+
+use crate::{
+    actor::Actor,
+    character::Team,
+};
+use rg3d::core::{
+    math::vec3::Vec3,
+    pool::Handle,
+};
+use std::collections::HashMap;
+
+/// Sightings and lanes older than this are forgotten, in seconds.
+const SIGHTING_MEMORY: f64 = 4.0;
+/// How long one bot advances before other squad member takes its turn, in seconds.
+const ADVANCE_TURN: f64 = 3.0;
+
+struct Sighting {
+    target: Handle<Actor>,
+    position: Vec3,
+    spotter: Handle<Actor>,
+    spotter_position: Vec3,
+    time: f64,
+}
+
+/// Bot that goes after called out enemy, lane of bot is its index among bots that go
+/// after the same enemy.
+struct Responder {
+    bot: Handle<Actor>,
+    target: Handle<Actor>,
+    time: f64,
+}
+
+#[derive(Default)]
+pub struct Blackboard {
+    sightings: Vec<Sighting>,
+    responders: Vec<Responder>,
+    /// Bot that advances now and time when its turn started.
+    advancing: Option<(Handle<Actor>, f64)>,
+}
+
+impl Blackboard {
+    /// Writes down that spotter sees target at given position, previous sighting of the
+    /// same target is replaced.
+    pub fn report(&mut self, spotter: Handle<Actor>, spotter_position: Vec3, target: Handle<Actor>, position: Vec3, time: f64) {
+        self.sightings.retain(|sighting| sighting.target != target);
+        self.sightings.push(Sighting {
+            target,
+            position,
+            spotter,
+            spotter_position,
+            time,
+        });
+    }
+
+    /// Returns enemy and its position called out by a teammate who is within given radius
+    /// of bot, closest teammate is preferred.
+    pub fn closest_callout(&self, bot: Handle<Actor>, position: Vec3, radius: f32) -> Option<(Handle<Actor>, Vec3)> {
+        self.sightings
+            .iter()
+            .filter(|sighting| sighting.spotter != bot && sighting.target != bot &&
+                sighting.spotter_position.sqr_distance(&position) <= radius * radius)
+            .min_by(|a, b| a.spotter_position.sqr_distance(&position)
+                .partial_cmp(&b.spotter_position.sqr_distance(&position))
+                .unwrap_or(std::cmp::Ordering::Equal))
+            .map(|sighting| (sighting.target, sighting.position))
+    }
+
+    /// Registers bot as one that goes after given target and returns its lane. Bot keeps
+    /// its lane while it keeps going after the same target.
+    pub fn lane(&mut self, bot: Handle<Actor>, target: Handle<Actor>, time: f64) -> usize {
+        match self.responders.iter().position(|responder| responder.bot == bot) {
+            Some(index) if self.responders[index].target == target => {
+                self.responders[index].time = time;
+            }
+            Some(index) => {
+                self.responders.remove(index);
+                self.responders.push(Responder { bot, target, time });
+            }
+            None => self.responders.push(Responder { bot, target, time }),
+        }
+        self.responders
+            .iter()
+            .filter(|responder| responder.target == target)
+            .position(|responder| responder.bot == bot)
+            .unwrap_or(0)
+    }
+
+    /// Returns true if it is turn of given bot to advance, others should hold position and
+    /// cover it. Turn goes to next bot that asks after current one has advanced for a while.
+    pub fn may_advance(&mut self, bot: Handle<Actor>, time: f64) -> bool {
+        match self.advancing {
+            Some((advancing, started)) if time - started < ADVANCE_TURN => advancing == bot,
+            Some((advancing, _)) if advancing == bot => {
+                // Give others a chance to take the turn.
+                self.advancing = None;
+                false
+            }
+            _ => {
+                self.advancing = Some((bot, time));
+                true
+            }
+        }
+    }
+
+    fn forget(&mut self, time: f64) {
+        self.sightings.retain(|sighting| time - sighting.time < SIGHTING_MEMORY);
+        self.responders.retain(|responder| time - responder.time < SIGHTING_MEMORY);
+    }
+}
+
+/// Blackboards of every side, bots without team that do not fight each other are on the
+/// same side against the player.
+#[derive(Default)]
+pub struct Squads {
+    blackboards: HashMap<Team, Blackboard>,
+}
+
+impl Squads {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    pub fn blackboard(&mut self, team: Team) -> &mut Blackboard {
+        self.blackboards.entry(team).or_default()
+    }
+
+    /// Forgets old sightings, must be called before bots are updated.
+    pub fn update(&mut self, time: f64) {
+        for blackboard in self.blackboards.values_mut() {
+            blackboard.forget(time);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Stats page of the main menu, it shows lifetime stats of the player and kills made with
+//! each weapon, best weapon is highlighted.
+
+use crate::{
+    player_stats::PlayerStats,
+    weapon::WEAPON_KINDS,
+    UINodeHandle,
+    Gui,
+    GuiMessage,
+};
+use rg3d::{
+    gui::{
+        grid::{GridBuilder, Row, Column},
+        window::{WindowBuilder, WindowTitle},
+        widget::WidgetBuilder,
+        text::TextBuilder,
+        button::ButtonBuilder,
+        message::{
+            UiMessageData,
+            ButtonMessage,
+            WindowMessage,
+            WidgetMessage,
+            TextMessage,
+        },
+        brush::Brush,
+        Thickness,
+        HorizontalAlignment,
+        VerticalAlignment,
+    },
+    core::color::Color,
+};
+
+const BEST_WEAPON_COLOR: Color = Color::opaque(255, 200, 0);
+
+pub struct StatsMenu {
+    pub window: UINodeHandle,
+    summary_text: UINodeHandle,
+    weapon_kills: Vec<UINodeHandle>,
+    btn_close: UINodeHandle,
+}
+
+impl StatsMenu {
+    pub fn new(ui: &mut Gui) -> Self {
+        let ctx = &mut ui.build_ctx();
+
+        let mut weapon_children = Vec::new();
+        let mut weapon_kills = Vec::new();
+        for (row, weapon) in WEAPON_KINDS.iter().enumerate() {
+            weapon_children.push(TextBuilder::new(WidgetBuilder::new()
+                .on_row(row)
+                .on_column(0)
+                .with_margin(Thickness::uniform(3.0))
+                .with_vertical_alignment(VerticalAlignment::Center))
+                .with_text(weapon.name())
+                .build(ctx));
+            let kills = TextBuilder::new(WidgetBuilder::new()
+                .on_row(row)
+                .on_column(1)
+                .with_margin(Thickness::uniform(3.0))
+                .with_horizontal_alignment(HorizontalAlignment::Center)
+                .with_vertical_alignment(VerticalAlignment::Center))
+                .build(ctx);
+            weapon_children.push(kills);
+            weapon_kills.push(kills);
+        }
+
+        let summary_text;
+        let btn_close;
+        let window = WindowBuilder::new(WidgetBuilder::new()
+            .with_width(400.0)
+            .with_height(330.0))
+            .with_title(WindowTitle::text("Stats"))
+            .can_minimize(false)
+            .open(false)
+            .with_content(GridBuilder::new(WidgetBuilder::new()
+                .with_margin(Thickness::uniform(5.0))
+                .with_child({
+                    summary_text = TextBuilder::new(WidgetBuilder::new()
+                        .on_row(0)
+                        .on_column(0)
+                        .with_margin(Thickness::uniform(5.0)))
+                        .build(ctx);
+                    summary_text
+                })
+                .with_child(GridBuilder::new(WidgetBuilder::new()
+                    .on_row(1)
+                    .on_column(0)
+                    .with_children(&weapon_children))
+                    .add_rows(WEAPON_KINDS.iter().map(|_| Row::strict(30.0)).collect())
+                    .add_column(Column::stretch())
+                    .add_column(Column::strict(100.0))
+                    .draw_border(true)
+                    .build(ctx))
+                .with_child({
+                    btn_close = ButtonBuilder::new(WidgetBuilder::new()
+                        .on_row(2)
+                        .on_column(0)
+                        .with_margin(Thickness::uniform(4.0)))
+                        .with_text("Close")
+                        .build(ctx);
+                    btn_close
+                }))
+                .add_column(Column::stretch())
+                .add_row(Row::auto())
+                .add_row(Row::stretch())
+                .add_row(Row::strict(40.0))
+                .build(ctx))
+            .build(ctx);
+
+        Self {
+            window,
+            summary_text,
+            weapon_kills,
+            btn_close,
+        }
+    }
+
+    /// Fills page with given stats and opens it.
+    pub fn open(&mut self, ui: &mut Gui, stats: &PlayerStats) {
+        let kd_ratio = if stats.deaths > 0 {
+            format!("{:.2}", stats.kills as f32 / stats.deaths as f32)
+        } else {
+            "N/A".to_owned()
+        };
+        let text = format!("Matches played: {}\nKills: {}\nDeaths: {}\nK/D: {}\nBest weapon: {}",
+                           stats.matches_played,
+                           stats.kills,
+                           stats.deaths,
+                           kd_ratio,
+                           stats.best_weapon().map_or("N/A", |weapon| weapon.name()));
+        ui.send_message(TextMessage::text(self.summary_text, text));
+
+        let best_weapon = stats.best_weapon();
+        for (&text, &weapon) in self.weapon_kills.iter().zip(WEAPON_KINDS.iter()) {
+            let color = if best_weapon == Some(weapon) {
+                BEST_WEAPON_COLOR
+            } else {
+                Color::WHITE
+            };
+            ui.send_message(TextMessage::text(text, format!("{}", stats.weapon_kills(weapon))));
+            ui.send_message(WidgetMessage::foreground(text, Brush::Solid(color)));
+        }
+
+        ui.send_message(WindowMessage::open(self.window));
+        ui.send_message(WidgetMessage::center(self.window));
+    }
+
+    pub fn handle_ui_event(&mut self, ui: &mut Gui, message: &GuiMessage) {
+        if let UiMessageData::Button(ButtonMessage::Click) = &message.data {
+            if message.destination == self.btn_close {
+                ui.send_message(WindowMessage::close(self.window));
+            }
+        }
+    }
+}
@@ -0,0 +1,334 @@
+//! Targets of target range practice. Targets are placed at `RangeTarget_Static`,
+//! `RangeTarget_PopUp` and `RangeTarget_Moving` nodes of the map, maps without such nodes get
+//! targets of every kind in turn at spawn points. Static and moving targets are destroyed by
+//! a hit and stay destroyed until range is reset, pop-up targets rise for a short time, drop
+//! and rise again later. Time between rise of pop-up target and hit is reaction time of player.
+
+use rg3d::{
+    engine::resource_manager::ResourceManager,
+    resource::texture::TextureKind,
+    scene::{
+        sprite::SpriteBuilder,
+        Scene,
+        node::Node,
+        base::BaseBuilder,
+    },
+    physics::{
+        convex_shape::{ConvexShape, SphereShape},
+        rigid_body::RigidBody,
+    },
+    core::{
+        visitor::{Visit, VisitResult, Visitor},
+        pool::Handle,
+        color::Color,
+        math::vec3::Vec3,
+    },
+};
+use crate::{
+    leader_board::PersonalScore,
+    CollisionGroups,
+};
+use rand::Rng;
+
+const TARGET_RADIUS: f32 = 0.3;
+/// Targets at spawn points are lifted to the height of the head.
+const SPAWN_POINT_TARGET_HEIGHT: f32 = 1.2;
+/// How long pop-up target stays up if it is not hit.
+const POP_UP_TIME: f32 = 2.0;
+/// Range of random time in seconds during which pop-up target stays down.
+const POP_DOWN_TIME: (f32, f32) = (1.0, 4.0);
+/// Distance from center of path of moving target to its ends.
+const MOVE_AMPLITUDE: f32 = 2.0;
+/// Angular speed of back and forth movement in radians per second.
+const MOVE_SPEED: f32 = 1.2;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TargetKind {
+    Static,
+    PopUp,
+    Moving,
+}
+
+impl Default for TargetKind {
+    fn default() -> Self {
+        TargetKind::Static
+    }
+}
+
+impl TargetKind {
+    fn from_id(id: u32) -> Result<Self, String> {
+        match id {
+            0 => Ok(TargetKind::Static),
+            1 => Ok(TargetKind::PopUp),
+            2 => Ok(TargetKind::Moving),
+            _ => Err(format!("Unknown target kind {}", id))
+        }
+    }
+
+    fn id(self) -> u32 {
+        match self {
+            TargetKind::Static => 0,
+            TargetKind::PopUp => 1,
+            TargetKind::Moving => 2,
+        }
+    }
+
+    /// Returns kind of target by name of map node, None if node is not a target.
+    pub fn from_node_name(name: &str) -> Option<Self> {
+        if name.starts_with("RangeTarget_Static") {
+            Some(TargetKind::Static)
+        } else if name.starts_with("RangeTarget_PopUp") {
+            Some(TargetKind::PopUp)
+        } else if name.starts_with("RangeTarget_Moving") {
+            Some(TargetKind::Moving)
+        } else {
+            None
+        }
+    }
+
+    fn color(self) -> Color {
+        match self {
+            TargetKind::Static => Color::opaque(220, 40, 40),
+            TargetKind::PopUp => Color::opaque(255, 200, 0),
+            TargetKind::Moving => Color::opaque(40, 160, 255),
+        }
+    }
+}
+
+pub struct RangeTarget {
+    kind: TargetKind,
+    /// Position of the target, moving target moves around it.
+    home: Vec3,
+    model: Handle<Node>,
+    /// Body exists only while target is up, so shots pass where target is down.
+    body: Handle<RigidBody>,
+    /// Time left in current state of pop-up target.
+    timer: f32,
+    /// Time since target went up.
+    up_time: f32,
+    /// Phase of movement of moving target.
+    phase: f32,
+}
+
+impl Default for RangeTarget {
+    fn default() -> Self {
+        Self {
+            kind: Default::default(),
+            home: Default::default(),
+            model: Default::default(),
+            body: Default::default(),
+            timer: 0.0,
+            up_time: 0.0,
+            phase: 0.0,
+        }
+    }
+}
+
+impl RangeTarget {
+    fn new(scene: &mut Scene, resource_manager: &mut ResourceManager, kind: TargetKind, home: Vec3) -> Self {
+        let model = scene.graph.add_node(Node::Sprite(SpriteBuilder::new(BaseBuilder::new())
+            .with_size(TARGET_RADIUS)
+            .with_color(kind.color())
+            .with_opt_texture(resource_manager.request_texture("data/particles/circle_05.png", TextureKind::R8))
+            .build()));
+        let mut target = Self {
+            kind,
+            home,
+            model,
+            ..Default::default()
+        };
+        target.raise(scene);
+        target
+    }
+
+    fn is_up(&self) -> bool {
+        self.body.is_some()
+    }
+
+    fn position(&self) -> Vec3 {
+        match self.kind {
+            TargetKind::Moving => self.home + Vec3::new(self.phase.sin() * MOVE_AMPLITUDE, 0.0, 0.0),
+            TargetKind::Static | TargetKind::PopUp => self.home,
+        }
+    }
+
+    fn raise(&mut self, scene: &mut Scene) {
+        if !self.is_up() {
+            let mut body = RigidBody::new(ConvexShape::Sphere(SphereShape::new(TARGET_RADIUS)));
+            body.set_gravity(Vec3::ZERO);
+            body.set_position(self.position());
+            // Targets are hit by shots, but actors walk through them.
+            body.collision_group = CollisionGroups::Generic as u64;
+            body.collision_mask = CollisionGroups::Projectile as u64;
+            self.body = scene.physics.add_body(body);
+            scene.physics_binder.bind(self.model, self.body);
+            scene.graph[self.model].set_visibility(true);
+        }
+        self.timer = POP_UP_TIME;
+        self.up_time = 0.0;
+    }
+
+    fn drop_down(&mut self, scene: &mut Scene) {
+        if self.is_up() {
+            // Binder forgets removed body by itself, body of next rise is bound anew.
+            scene.physics.remove_body(self.body);
+            self.body = Handle::NONE;
+            scene.graph[self.model].set_visibility(false);
+        }
+        self.timer = rand::thread_rng().gen_range(POP_DOWN_TIME.0, POP_DOWN_TIME.1);
+    }
+
+    fn update(&mut self, scene: &mut Scene, dt: f32) {
+        if self.is_up() {
+            self.up_time += dt;
+            if self.kind == TargetKind::Moving {
+                self.phase += MOVE_SPEED * dt;
+            }
+            // Target stays where it should be even if something pushed it.
+            let body = scene.physics.borrow_body_mut(self.body);
+            body.set_position(self.position());
+            body.set_velocity(Vec3::ZERO);
+        }
+        if self.kind == TargetKind::PopUp {
+            self.timer -= dt;
+            if self.timer <= 0.0 {
+                if self.is_up() {
+                    self.drop_down(scene);
+                } else {
+                    self.raise(scene);
+                }
+            }
+        }
+    }
+}
+
+impl Visit for RangeTarget {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut kind = self.kind.id();
+        kind.visit("Kind", visitor)?;
+        if visitor.is_reading() {
+            self.kind = TargetKind::from_id(kind)?;
+        }
+        self.home.visit("Home", visitor)?;
+        self.model.visit("Model", visitor)?;
+        self.body.visit("Body", visitor)?;
+        self.timer.visit("Timer", visitor)?;
+        self.up_time.visit("UpTime", visitor)?;
+        self.phase.visit("Phase", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+/// Results of practice that are shown on HUD.
+#[derive(Copy, Clone, Debug)]
+pub struct RangeStats {
+    pub shots_fired: u32,
+    pub hits: u32,
+    /// Part of shots that hit, None if no shots were fired yet.
+    pub accuracy: Option<f32>,
+    /// Average reaction time on pop-up targets in seconds.
+    pub reaction_time: Option<f32>,
+    pub targets_up: usize,
+    pub targets: usize,
+}
+
+/// Targets of the range, empty on levels of other modes.
+#[derive(Default)]
+pub struct RangeTargetContainer {
+    targets: Vec<RangeTarget>,
+    /// Sum of reaction times on pop-up targets in seconds.
+    total_reaction_time: f32,
+    reactions: u32,
+}
+
+impl RangeTargetContainer {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Places targets at given points, points that are not named in map get targets of
+    /// every kind in turn.
+    pub fn spawn(&mut self, scene: &mut Scene, resource_manager: &mut ResourceManager, targets: &[(TargetKind, Vec3)], spawn_points: &[Vec3]) {
+        if targets.is_empty() {
+            let kinds = [TargetKind::Static, TargetKind::PopUp, TargetKind::Moving];
+            for (i, point) in spawn_points.iter().enumerate() {
+                let home = *point + Vec3::new(0.0, SPAWN_POINT_TARGET_HEIGHT, 0.0);
+                self.targets.push(RangeTarget::new(scene, resource_manager, kinds[i % kinds.len()], home));
+            }
+        } else {
+            for (kind, home) in targets.iter() {
+                self.targets.push(RangeTarget::new(scene, resource_manager, *kind, *home));
+            }
+        }
+    }
+
+    pub fn update(&mut self, scene: &mut Scene, dt: f32) {
+        for target in self.targets.iter_mut() {
+            target.update(scene, dt);
+        }
+    }
+
+    /// Returns bodies of targets that are up, shots that hit them are reported by
+    /// `Message::HitRangeTarget`.
+    pub fn bodies(&self) -> Vec<Handle<RigidBody>> {
+        self.targets
+            .iter()
+            .filter(|target| target.is_up())
+            .map(|target| target.body)
+            .collect()
+    }
+
+    /// Drops target that owns given body, returns false if body is not a target.
+    pub fn hit(&mut self, scene: &mut Scene, body: Handle<RigidBody>) -> bool {
+        match self.targets.iter_mut().find(|target| target.is_up() && target.body == body) {
+            Some(target) => {
+                if target.kind == TargetKind::PopUp {
+                    self.total_reaction_time += target.up_time;
+                    self.reactions += 1;
+                }
+                target.drop_down(scene);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Raises every target again, reaction times are kept.
+    pub fn reset(&mut self, scene: &mut Scene) {
+        for target in self.targets.iter_mut() {
+            target.raise(scene);
+        }
+    }
+
+    /// Returns stats of practice, shots and hits are counted by leader board so score of
+    /// player is passed in, it is None until player fires first shot.
+    pub fn stats(&self, score: Option<&PersonalScore>) -> RangeStats {
+        RangeStats {
+            shots_fired: score.map_or(0, |score| score.shots_fired),
+            hits: score.map_or(0, |score| score.hits),
+            accuracy: score.and_then(|score| score.accuracy()),
+            reaction_time: if self.reactions > 0 {
+                Some(self.total_reaction_time / self.reactions as f32)
+            } else {
+                None
+            },
+            targets_up: self.targets.iter().filter(|target| target.is_up()).count(),
+            targets: self.targets.len(),
+        }
+    }
+}
+
+impl Visit for RangeTargetContainer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.targets.visit("Targets", visitor)?;
+        self.total_reaction_time.visit("TotalReactionTime", visitor)?;
+        self.reactions.visit("Reactions", visitor)?;
+
+        visitor.leave_region()
+    }
+}
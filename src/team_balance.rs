@@ -0,0 +1,292 @@
+//! Team assignment for team modes. Every combatant joins a team when it first spawns and
+//! keeps it between lives. When teams drift apart in size or strength, balancer moves
+//! combatants to the other team. Bots are moved first, humans are moved only while they
+//! wait for respawn, so nobody is switched in the middle of a life.
+
+use std::collections::HashMap;
+use rg3d::core::visitor::{Visit, Visitor, VisitResult};
+use crate::character::Team;
+
+/// Teams may differ in size by this many combatants before balancer reacts.
+const MAX_SIZE_DIFFERENCE: usize = 1;
+/// Difference of summed skill of teams at which balancer starts to swap bots between teams.
+const MAX_SKILL_DIFFERENCE: f32 = 2.0;
+
+/// Combatant that takes part in a match right now, including ones that wait for respawn.
+pub struct Combatant {
+    pub name: String,
+    pub bot: bool,
+    /// False if combatant is dead or waits for respawn.
+    pub alive: bool,
+    pub skill: f32,
+}
+
+impl Combatant {
+    /// Only bots and humans between lives can change team.
+    fn can_switch(&self) -> bool {
+        self.bot || !self.alive
+    }
+}
+
+/// Rough skill of a combatant, kills per life.
+pub fn skill(kills: u32, deaths: u32) -> f32 {
+    kills as f32 / (deaths + 1) as f32
+}
+
+fn opposite(team: Team) -> Team {
+    match team {
+        Team::Red => Team::Blue,
+        Team::Blue => Team::Red,
+        Team::None => Team::None,
+    }
+}
+
+#[derive(Default)]
+pub struct TeamBalancer {
+    /// When disabled, combatants still join smaller team, but nobody is moved later.
+    enabled: bool,
+    teams: HashMap<String, Team>,
+}
+
+impl TeamBalancer {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            teams: Default::default(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn team(&self, name: &str) -> Team {
+        self.teams.get(name).copied().unwrap_or(Team::None)
+    }
+
+    /// Returns team of combatant with given name. Combatant that has no team yet joins smaller
+    /// team, or weaker one if teams have same size.
+    pub fn assign(&mut self, name: &str, combatants: &[Combatant]) -> Team {
+        if let Some(team) = self.teams.get(name) {
+            return *team;
+        }
+        let (red, blue) = self.sizes(combatants);
+        let team = if red != blue {
+            if red < blue { Team::Red } else { Team::Blue }
+        } else if self.skill_difference(combatants) > 0.0 {
+            Team::Blue
+        } else {
+            Team::Red
+        };
+        self.teams.insert(name.to_owned(), team);
+        team
+    }
+
+    /// Forgets team of combatant that left the match.
+    pub fn remove(&mut self, name: &str) {
+        self.teams.remove(name);
+    }
+
+    /// Moves combatants between teams when teams are uneven. Returns names of combatants that
+    /// were moved with their new teams, nothing is moved when balancer is disabled.
+    pub fn balance(&mut self, combatants: &[Combatant]) -> Vec<(String, Team)> {
+        let mut moves = Vec::new();
+        if !self.enabled {
+            return moves;
+        }
+
+        // Sizes go first, one combatant at a time. Of those who can switch, bots are
+        // preferred and then the one that leaves strength of teams closest.
+        loop {
+            let (red, blue) = self.sizes(combatants);
+            if red.max(blue) - red.min(blue) <= MAX_SIZE_DIFFERENCE {
+                break;
+            }
+            let from = if red > blue { Team::Red } else { Team::Blue };
+            let difference = self.skill_difference(combatants);
+            let sign = if from == Team::Red { 1.0 } else { -1.0 };
+            let candidate = combatants
+                .iter()
+                .filter(|c| self.team(&c.name) == from && c.can_switch())
+                .min_by(|a, b| {
+                    let a_key = (!a.bot, (difference - sign * 2.0 * a.skill).abs());
+                    let b_key = (!b.bot, (difference - sign * 2.0 * b.skill).abs());
+                    a_key.partial_cmp(&b_key).unwrap_or(std::cmp::Ordering::Equal)
+                });
+            match candidate {
+                Some(combatant) => {
+                    let team = opposite(from);
+                    self.teams.insert(combatant.name.clone(), team);
+                    moves.push((combatant.name.clone(), team));
+                }
+                // Everyone on bigger team is human and alive, try again after next death.
+                None => break,
+            }
+        }
+
+        // Then strength, single swap per call keeps teams from being shuffled all the time.
+        let difference = self.skill_difference(combatants);
+        if difference.abs() > MAX_SKILL_DIFFERENCE {
+            let stronger = if difference > 0.0 { Team::Red } else { Team::Blue };
+            let weaker = opposite(stronger);
+            let mut best: Option<(&Combatant, &Combatant, f32)> = None;
+            for a in combatants.iter().filter(|c| self.team(&c.name) == stronger && c.can_switch()) {
+                for b in combatants.iter().filter(|c| self.team(&c.name) == weaker && c.can_switch()) {
+                    let new_difference = (difference.abs() - 2.0 * (a.skill - b.skill)).abs();
+                    if best.map_or(true, |(_, _, best_difference)| new_difference < best_difference) {
+                        best = Some((a, b, new_difference));
+                    }
+                }
+            }
+            if let Some((a, b, new_difference)) = best {
+                if new_difference < difference.abs() {
+                    self.teams.insert(a.name.clone(), weaker);
+                    self.teams.insert(b.name.clone(), stronger);
+                    moves.push((a.name.clone(), weaker));
+                    moves.push((b.name.clone(), stronger));
+                }
+            }
+        }
+
+        moves
+    }
+
+    fn sizes(&self, combatants: &[Combatant]) -> (usize, usize) {
+        combatants.iter().fold((0, 0), |(red, blue), combatant| match self.team(&combatant.name) {
+            Team::Red => (red + 1, blue),
+            Team::Blue => (red, blue + 1),
+            Team::None => (red, blue),
+        })
+    }
+
+    /// Summed skill of red team minus summed skill of blue team.
+    fn skill_difference(&self, combatants: &[Combatant]) -> f32 {
+        combatants.iter().fold(0.0, |difference, combatant| match self.team(&combatant.name) {
+            Team::Red => difference + combatant.skill,
+            Team::Blue => difference - combatant.skill,
+            Team::None => difference,
+        })
+    }
+}
+
+impl Visit for TeamBalancer {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.enabled.visit("Enabled", visitor)?;
+        self.teams.visit("Teams", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn combatant(name: &str, bot: bool, alive: bool, skill: f32) -> Combatant {
+        Combatant {
+            name: name.to_owned(),
+            bot,
+            alive,
+            skill,
+        }
+    }
+
+    /// Assigns everyone in order, as if they joined one by one.
+    fn join_all(balancer: &mut TeamBalancer, combatants: &[Combatant]) {
+        for (i, combatant) in combatants.iter().enumerate() {
+            balancer.assign(&combatant.name, &combatants[..=i]);
+        }
+    }
+
+    #[test]
+    fn newcomers_join_smaller_team() {
+        let combatants = vec![
+            combatant("a", true, true, 0.0),
+            combatant("b", true, true, 0.0),
+            combatant("c", true, true, 0.0),
+            combatant("d", true, true, 0.0),
+        ];
+        let mut balancer = TeamBalancer::new(true);
+        join_all(&mut balancer, &combatants);
+        assert_eq!(balancer.sizes(&combatants), (2, 2));
+    }
+
+    #[test]
+    fn uneven_teams_are_corrected() {
+        let mut combatants = vec![
+            combatant("a", true, true, 0.0),
+            combatant("b", true, true, 0.0),
+            combatant("c", true, true, 0.0),
+            combatant("d", true, true, 0.0),
+            combatant("e", true, true, 0.0),
+        ];
+        let mut balancer = TeamBalancer::new(true);
+        join_all(&mut balancer, &combatants);
+        // Both blue bots leave, red has three and blue has none.
+        combatants.retain(|c| balancer.team(&c.name) == Team::Red);
+        assert_eq!(balancer.sizes(&combatants), (3, 0));
+
+        let moves = balancer.balance(&combatants);
+        assert_eq!(moves.len(), 1);
+        assert_eq!(moves[0].1, Team::Blue);
+        assert_eq!(balancer.sizes(&combatants), (2, 1));
+        assert!(balancer.balance(&combatants).is_empty());
+    }
+
+    #[test]
+    fn alive_humans_are_not_moved() {
+        let mut balancer = TeamBalancer::new(true);
+        balancer.teams.insert("a".to_owned(), Team::Red);
+        balancer.teams.insert("b".to_owned(), Team::Red);
+        balancer.teams.insert("c".to_owned(), Team::Red);
+        let mut combatants = vec![
+            combatant("a", false, true, 0.0),
+            combatant("b", false, true, 0.0),
+            combatant("c", false, true, 0.0),
+        ];
+        assert!(balancer.balance(&combatants).is_empty());
+
+        // Human that waits for respawn can be moved.
+        combatants[1].alive = false;
+        assert_eq!(balancer.balance(&combatants), vec![("b".to_owned(), Team::Blue)]);
+    }
+
+    #[test]
+    fn disabled_balancer_moves_nobody() {
+        let mut balancer = TeamBalancer::new(false);
+        balancer.teams.insert("a".to_owned(), Team::Red);
+        balancer.teams.insert("b".to_owned(), Team::Red);
+        balancer.teams.insert("c".to_owned(), Team::Red);
+        let combatants = vec![
+            combatant("a", true, true, 0.0),
+            combatant("b", true, true, 0.0),
+            combatant("c", true, true, 0.0),
+        ];
+        assert!(balancer.balance(&combatants).is_empty());
+    }
+
+    #[test]
+    fn strong_team_swaps_bots_with_weak_one() {
+        let mut balancer = TeamBalancer::new(true);
+        balancer.teams.insert("strong1".to_owned(), Team::Red);
+        balancer.teams.insert("strong2".to_owned(), Team::Red);
+        balancer.teams.insert("weak1".to_owned(), Team::Blue);
+        balancer.teams.insert("weak2".to_owned(), Team::Blue);
+        let combatants = vec![
+            combatant("strong1", true, true, 3.0),
+            combatant("strong2", true, true, 3.0),
+            combatant("weak1", true, true, 0.0),
+            combatant("weak2", true, true, 0.0),
+        ];
+        let moves = balancer.balance(&combatants);
+        assert_eq!(moves.len(), 2);
+        assert_eq!(balancer.skill_difference(&combatants), 0.0);
+        assert_eq!(balancer.sizes(&combatants), (2, 2));
+    }
+}
@@ -0,0 +1,67 @@
+//! Usable objects are things that player interacts with by pressing use button - items when
+//! manual pickup is enabled, doors, buttons, elevator calls, live grenades and so on. Object
+//! is focused when player stands next to it or looks at it from a short distance, HUD shows
+//! prompt for focused object and level dispatches use of it.
+
+use rg3d::{
+    core::{
+        pool::Handle,
+        math::vec3::Vec3,
+    },
+    scene::graph::Graph,
+};
+use crate::{
+    item::Item,
+    door::Door,
+    projectile::Projectile,
+};
+
+/// Max distance from player to object that player looks at.
+pub const USE_DISTANCE: f32 = 2.5;
+/// Object closer than this is focused even if player does not look at it.
+pub const NEAR_DISTANCE: f32 = 1.25;
+/// Cosine of max angle between view direction and direction to object.
+const FOCUS_ANGLE_COS: f32 = 0.94;
+/// Ray to solid object hits the object itself, hits closer than this to object position do
+/// not block the view.
+pub const HIT_TOLERANCE: f32 = 0.3;
+
+pub trait Usable {
+    /// Point in the world at which object is, focus is checked against it.
+    fn use_position(&self, graph: &Graph) -> Vec3;
+
+    /// Whether object can be used right now, for example picked up item can't be used until
+    /// it respawns.
+    fn can_be_used(&self) -> bool;
+
+    /// Action that is shown in prompt after "Press E to", for example "pick up Medkit".
+    fn use_action(&self) -> String;
+}
+
+/// Object that can be used, level dispatches use by its kind.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum UseTarget {
+    Item(Handle<Item>),
+    Door(Handle<Door>),
+    /// Live grenade, it is picked up by first use and thrown back by second one.
+    Grenade(Handle<Projectile>),
+}
+
+/// Returns how well object at `position` is focused from given player position and view,
+/// lower is better. None if object is not focused at all. Line of sight is not checked here.
+pub fn focus_score(player_position: Vec3, eye: Vec3, look: Vec3, position: Vec3) -> Option<f32> {
+    let distance = (position - player_position).len();
+    if distance < NEAR_DISTANCE {
+        return Some(distance);
+    }
+    if distance > USE_DISTANCE {
+        return None;
+    }
+    let cos = (position - eye).normalized()?.dot(&look);
+    if cos > FOCUS_ANGLE_COS {
+        // Objects that are looked at directly win over closer ones at the side of view.
+        Some(distance * (2.0 - cos))
+    } else {
+        None
+    }
+}
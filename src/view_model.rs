@@ -0,0 +1,399 @@
+//! Animation of first person weapon. Weapon is always in one of view-model states, weapon
+//! switches state on its own events: shot, reload, draw and holster. Every state has a pose
+//! of weapon model, pose of new state blends from whatever pose was shown when state changed,
+//! so interrupted actions do not snap. Weapons whose definition lists skeletal animations get
+//! them played by animation machine in the same states, procedural pose is applied on top.
+
+use std::path::Path;
+use rg3d::{
+    core::{
+        pool::Handle,
+        math::vec3::Vec3,
+        visitor::{Visit, VisitResult, Visitor},
+    },
+    animation::{
+        Animation,
+        machine::{self, Machine, PoseNode, State},
+    },
+    scene::{node::Node, Scene},
+    engine::resource_manager::ResourceManager,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ViewModelState {
+    /// Weapon is raised after switch, it starts lowered out of view.
+    Draw,
+    Idle,
+    /// Weapon kicks up and returns back after a shot.
+    Fire,
+    /// Weapon is lowered while it is being reloaded.
+    Reload,
+    /// Weapon is lowered out of view before it is hidden.
+    Holster,
+}
+
+/// Every state in order of ids.
+const VIEW_MODEL_STATES: [ViewModelState; 5] = [
+    ViewModelState::Draw,
+    ViewModelState::Idle,
+    ViewModelState::Fire,
+    ViewModelState::Reload,
+    ViewModelState::Holster,
+];
+
+impl ViewModelState {
+    fn id(self) -> u32 {
+        match self {
+            ViewModelState::Draw => 0,
+            ViewModelState::Idle => 1,
+            ViewModelState::Fire => 2,
+            ViewModelState::Reload => 3,
+            ViewModelState::Holster => 4,
+        }
+    }
+
+    fn from_id(id: u32) -> Result<Self, String> {
+        VIEW_MODEL_STATES.get(id as usize)
+            .copied()
+            .ok_or_else(|| format!("unknown view model state {}", id))
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ViewModelState::Draw => "Draw",
+            ViewModelState::Idle => "Idle",
+            ViewModelState::Fire => "Fire",
+            ViewModelState::Reload => "Reload",
+            ViewModelState::Holster => "Holster",
+        }
+    }
+
+    /// Name of parameter of animation machine that leads to this state.
+    fn param(self) -> &'static str {
+        match self {
+            ViewModelState::Draw => "ToDraw",
+            ViewModelState::Idle => "ToIdle",
+            ViewModelState::Fire => "ToFire",
+            ViewModelState::Reload => "ToReload",
+            ViewModelState::Holster => "ToHolster",
+        }
+    }
+}
+
+/// Skeletal animations of weapon model, one file per state, same as animations of bots.
+#[derive(Clone)]
+pub struct ViewModelAnimations {
+    pub idle: &'static str,
+    pub fire: &'static str,
+    pub reload: &'static str,
+    pub draw: &'static str,
+    pub holster: &'static str,
+}
+
+impl ViewModelAnimations {
+    fn path(&self, state: ViewModelState) -> &'static str {
+        match state {
+            ViewModelState::Draw => self.draw,
+            ViewModelState::Idle => self.idle,
+            ViewModelState::Fire => self.fire,
+            ViewModelState::Reload => self.reload,
+            ViewModelState::Holster => self.holster,
+        }
+    }
+}
+
+/// Duration of draw in seconds.
+pub const DRAW_DURATION: f32 = 0.35;
+/// Duration of holster in seconds, weapon is hidden when it ends.
+pub const HOLSTER_DURATION: f32 = 0.25;
+/// Shortest duration of fire state, weapons that shoot slower stay in it until next shot
+/// is possible.
+const MIN_FIRE_DURATION: f32 = 0.12;
+/// Time in seconds during which pose of previous state blends into pose of new one.
+const BLEND_TIME: f32 = 0.1;
+/// Offset of weapon that is fully lowered out of view.
+const LOWERED_OFFSET: Vec3 = Vec3 { x: 0.0, y: -0.15, z: 0.0 };
+/// Pitch of weapon that is fully lowered out of view, in degrees.
+const LOWERED_PITCH: f32 = -35.0;
+/// Pitch of weapon right after a shot, in degrees.
+const FIRE_PITCH: f32 = 4.0;
+/// Offset of weapon in the middle of reload.
+const RELOAD_OFFSET: Vec3 = Vec3 { x: 0.0, y: -0.08, z: 0.0 };
+/// Pitch of weapon in the middle of reload, in degrees.
+const RELOAD_PITCH: f32 = -10.0;
+
+/// Procedural pose of weapon model relative to its rest pose.
+#[derive(Copy, Clone, Debug)]
+pub struct ViewModelPose {
+    pub offset: Vec3,
+    /// Rotation around right axis of weapon in degrees, positive turns muzzle up.
+    pub pitch: f32,
+}
+
+impl Default for ViewModelPose {
+    fn default() -> Self {
+        Self {
+            offset: Vec3::ZERO,
+            pitch: 0.0,
+        }
+    }
+}
+
+impl ViewModelPose {
+    fn lowered(weight: f32) -> Self {
+        Self {
+            offset: LOWERED_OFFSET.scale(weight),
+            pitch: LOWERED_PITCH * weight,
+        }
+    }
+
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        Self {
+            offset: self.offset + (other.offset - self.offset).scale(t),
+            pitch: self.pitch + (other.pitch - self.pitch) * t,
+        }
+    }
+}
+
+/// Skeletal animation machine of view-model, it has a state per view-model state and
+/// a transition between every two of them.
+struct ViewModelMachine {
+    machine: Machine,
+    /// Animations in order of `VIEW_MODEL_STATES`.
+    animations: Vec<Handle<Animation>>,
+}
+
+impl Default for ViewModelMachine {
+    fn default() -> Self {
+        Self {
+            machine: Default::default(),
+            animations: Default::default(),
+        }
+    }
+}
+
+impl Visit for ViewModelMachine {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        self.machine.visit("Machine", visitor)?;
+        self.animations.visit("Animations", visitor)?;
+
+        visitor.leave_region()
+    }
+}
+
+impl ViewModelMachine {
+    fn new(resource_manager: &mut ResourceManager, definition: &ViewModelAnimations, model: Handle<Node>, scene: &mut Scene) -> Result<Self, ()> {
+        let mut machine = Machine::new();
+        let mut animations = Vec::new();
+        let mut states = Vec::new();
+        for &state in VIEW_MODEL_STATES.iter() {
+            let animation = load_animation(resource_manager, definition.path(state), model, scene)?;
+            // Only idle loops, other actions play once and hold their last frame.
+            scene.animations
+                .get_mut(animation)
+                .set_loop(state == ViewModelState::Idle);
+            let node = machine.add_node(PoseNode::make_play_animation(animation));
+            states.push(machine.add_state(State::new(state.name(), node)));
+            animations.push(animation);
+        }
+
+        for (i, &from) in VIEW_MODEL_STATES.iter().enumerate() {
+            for (j, &to) in VIEW_MODEL_STATES.iter().enumerate() {
+                if i != j {
+                    let name = format!("{}->{}", from.name(), to.name());
+                    machine.add_transition(machine::Transition::new(&name, states[i], states[j], BLEND_TIME, to.param()));
+                }
+            }
+        }
+
+        machine.set_entry_state(states[ViewModelState::Idle.id() as usize]);
+
+        Ok(Self {
+            machine,
+            animations,
+        })
+    }
+
+    fn enter(&mut self, scene: &mut Scene, state: ViewModelState) {
+        // Actions start from their first frame every time, so repeated shots replay fire.
+        if let Some(&animation) = self.animations.get(state.id() as usize) {
+            scene.animations.get_mut(animation).rewind();
+        }
+    }
+
+    fn apply(&mut self, scene: &mut Scene, state: ViewModelState, dt: f32) {
+        for &other in VIEW_MODEL_STATES.iter() {
+            self.machine.set_parameter(other.param(), machine::Parameter::Rule(other == state));
+        }
+        self.machine
+            .evaluate_pose(&scene.animations, dt)
+            .apply(&mut scene.graph);
+    }
+
+    fn clean_up(&mut self, scene: &mut Scene) {
+        for &animation in self.animations.iter() {
+            scene.animations.remove(animation);
+        }
+    }
+}
+
+fn load_animation<P: AsRef<Path>>(resource_manager: &mut ResourceManager, path: P, model: Handle<Node>, scene: &mut Scene) -> Result<Handle<Animation>, ()> {
+    Ok(*resource_manager.request_model(path)
+        .ok_or(())?
+        .lock()
+        .unwrap()
+        .retarget_animations(model, scene)
+        .get(0)
+        .ok_or(())?)
+}
+
+/// View-model state machine of a weapon.
+pub struct ViewModel {
+    state: ViewModelState,
+    /// Time spent in current state.
+    state_time: f32,
+    /// Duration of current state, zero for states that last until next event.
+    state_duration: f32,
+    /// Pose that was shown when current state started.
+    blend_from: ViewModelPose,
+    pose: ViewModelPose,
+    /// State was entered after last update, its skeletal animation must start over.
+    entered: bool,
+    skeletal: Option<ViewModelMachine>,
+}
+
+impl Default for ViewModel {
+    fn default() -> Self {
+        Self {
+            state: ViewModelState::Idle,
+            state_time: 0.0,
+            state_duration: 0.0,
+            blend_from: Default::default(),
+            pose: Default::default(),
+            entered: false,
+            skeletal: None,
+        }
+    }
+}
+
+impl Visit for ViewModel {
+    fn visit(&mut self, name: &str, visitor: &mut Visitor) -> VisitResult {
+        visitor.enter_region(name)?;
+
+        let mut state_id = self.state.id();
+        state_id.visit("State", visitor)?;
+        if visitor.is_reading() {
+            self.state = ViewModelState::from_id(state_id)?;
+        }
+        self.state_time.visit("StateTime", visitor)?;
+        self.state_duration.visit("StateDuration", visitor)?;
+
+        let mut has_skeletal = self.skeletal.is_some();
+        has_skeletal.visit("HasSkeletal", visitor)?;
+        if has_skeletal {
+            if visitor.is_reading() {
+                self.skeletal = Some(Default::default());
+            }
+            if let Some(skeletal) = self.skeletal.as_mut() {
+                skeletal.visit("Skeletal", visitor)?;
+            }
+        }
+
+        visitor.leave_region()
+    }
+}
+
+impl ViewModel {
+    /// Creates view-model of weapon model. Skeletal animations are optional, if any of them
+    /// can't be loaded weapon is animated by procedural pose only.
+    pub fn new(resource_manager: &mut ResourceManager, animations: Option<&ViewModelAnimations>, model: Handle<Node>, scene: &mut Scene) -> Self {
+        let skeletal = animations.and_then(|animations| {
+            match ViewModelMachine::new(resource_manager, animations, model, scene) {
+                Ok(machine) => Some(machine),
+                Err(_) => {
+                    println!("Unable to load view model animations, procedural animation will be used instead.");
+                    None
+                }
+            }
+        });
+        Self {
+            skeletal,
+            ..Default::default()
+        }
+    }
+
+    pub fn state(&self) -> ViewModelState {
+        self.state
+    }
+
+    /// Switches to given state, duration is used by states that end by themselves. Entering
+    /// fire again restarts it, other states are not restarted by repeated events.
+    pub fn set_state(&mut self, state: ViewModelState, duration: f32) {
+        if self.state == state && state != ViewModelState::Fire {
+            return;
+        }
+        self.blend_from = self.pose;
+        self.state = state;
+        self.state_time = 0.0;
+        self.state_duration = duration;
+        self.entered = true;
+    }
+
+    pub fn fire(&mut self, shoot_interval: f32) {
+        self.set_state(ViewModelState::Fire, shoot_interval.max(MIN_FIRE_DURATION));
+    }
+
+    /// Returns true if current state has its own duration and it is over.
+    pub fn is_finished(&self) -> bool {
+        self.state_duration > 0.0 && self.state_time >= self.state_duration
+    }
+
+    /// Advances current state and returns pose that must be applied to weapon model.
+    /// `reload_progress` is part of reload that is done, it is used by reload state only.
+    pub fn update(&mut self, scene: &mut Scene, dt: f32, reload_progress: f32) -> ViewModelPose {
+        self.state_time += dt;
+        let t = if self.state_duration > 0.0 {
+            (self.state_time / self.state_duration).min(1.0)
+        } else {
+            0.0
+        };
+
+        let target = match self.state {
+            ViewModelState::Idle => ViewModelPose::default(),
+            ViewModelState::Draw => ViewModelPose::lowered(1.0 - t),
+            ViewModelState::Holster => ViewModelPose::lowered(t),
+            ViewModelState::Fire => ViewModelPose {
+                offset: Vec3::ZERO,
+                pitch: FIRE_PITCH * (1.0 - t) * (1.0 - t),
+            },
+            ViewModelState::Reload => {
+                let weight = (reload_progress * std::f32::consts::PI).sin();
+                ViewModelPose {
+                    offset: RELOAD_OFFSET.scale(weight),
+                    pitch: RELOAD_PITCH * weight,
+                }
+            }
+        };
+
+        let blend = (self.state_time / BLEND_TIME).min(1.0);
+        self.pose = self.blend_from.lerp(&target, blend);
+
+        if let Some(skeletal) = self.skeletal.as_mut() {
+            if self.entered {
+                skeletal.enter(scene, self.state);
+            }
+            skeletal.apply(scene, self.state, dt);
+        }
+        self.entered = false;
+
+        self.pose
+    }
+
+    pub fn clean_up(&mut self, scene: &mut Scene) {
+        if let Some(skeletal) = self.skeletal.as_mut() {
+            skeletal.clean_up(scene);
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use std::{
-    path::{
-        Path,
-        PathBuf
-    },
+    path::PathBuf,
     sync::mpsc::Sender,
-    ops::{Index, IndexMut}
+    ops::{Index, IndexMut},
+    borrow::Cow,
+    rc::Rc,
+    cell::RefCell,
 };
 use rg3d::{
     physics::{RayCastOptions, HitKind, Physics},
@@ -32,7 +32,7 @@ use rg3d::{
             VisitResult,
             Visitor,
         },
-        math::{vec3::Vec3, ray::Ray, mat3::Mat3},
+        math::{vec3::Vec3, ray::Ray, mat3::Mat3, quat::Quat},
     },
 };
 use crate::{
@@ -40,9 +40,96 @@ use crate::{
     projectile::ProjectileKind,
     actor::Actor,
     GameTime,
+    SoundPriority,
+    instantiate_model,
     message::Message,
+    crosshair::{CrosshairProfile, CrosshairStyle},
+    definitions,
+    settings::{parse_u32, parse_f32},
+    view_model::{ViewModel, ViewModelAnimations, ViewModelState, DRAW_DURATION, HOLSTER_DURATION},
+    skin::{self, DEFAULT_SKIN},
+    ammo::{AmmoType, AmmoStore},
 };
 
+/// Outcome of an attempt to shoot from a weapon.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ShotResult {
+    Fired,
+    /// Weapon is empty and empty click was played, happens once per trigger pull.
+    DryFire,
+    /// Weapon is not ready to shoot yet, is being reloaded, is overheated or trigger is
+    /// still held after dry fire.
+    Idle,
+}
+
+/// How rounds get into magazine during reload.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum ReloadStyle {
+    /// Whole magazine is replaced at the end of reload, firing can't interrupt it.
+    Magazine,
+    /// Rounds are loaded one at a time, each takes reload time of weapon. Firing stops
+    /// reload and rounds that are loaded already can be fired.
+    PerRound,
+}
+
+/// Secondary fire mode of a weapon, it is bound to its own button.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum AltFire {
+    /// Launches projectiles from separate ammo that is not reloaded, for example underbarrel
+    /// grenade launcher.
+    Launcher {
+        projectile: ProjectileKind,
+        ammo: u32,
+        sound: &'static str,
+    },
+    /// Fires few rounds from magazine in quick succession, rounds are fired by weapon update.
+    Burst {
+        shots: u32,
+        /// Time between rounds in seconds.
+        interval: f32,
+    },
+    /// Hits closest actor in front of owner with the weapon itself, needs no ammo.
+    Bash {
+        damage: f32,
+        range: f32,
+        sound: &'static str,
+    },
+}
+
+#[derive(Clone)]
+pub struct AltFireDefinition {
+    pub mode: AltFire,
+    /// Minimum time between two uses of alt fire, it does not depend on primary fire.
+    pub interval: f64,
+}
+
+/// Heat replaces magazine of energy weapons: every shot heats weapon up, weapon that reaches
+/// maximum heat overheats and can't shoot until it cools down. Such weapons spend no ammo.
+#[derive(Clone)]
+pub struct HeatDefinition {
+    /// Heat added by one shot.
+    pub heat_per_shot: f32,
+    pub max_heat: f32,
+    /// Heat lost per second while weapon is not fired, it cools slower while it is fired.
+    pub cooling_rate: f32,
+    pub overheat_sound: &'static str,
+}
+
+/// Camera recoil of weapon. Every shot of a spray kicks aim by next step of pattern, pattern
+/// is fixed so players can learn it and pull against it. Owner's aim returns by itself at
+/// recovery speed, as much as recovery part says.
+#[derive(Clone)]
+pub struct RecoilDefinition {
+    /// Kick of every shot of spray in degrees, up and right. Spray that is longer than
+    /// pattern repeats its last step.
+    pub pattern: Cow<'static, [(f32, f32)]>,
+    /// Speed at which aim returns, in degrees per second.
+    pub recovery_speed: f32,
+    /// Part of kick that is taken back, 1 returns aim to where it was before spray, 0 leaves
+    /// aim where recoil has put it.
+    pub recovery: f32,
+}
+
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum WeaponKind {
     M4,
@@ -51,6 +138,14 @@ pub enum WeaponKind {
     RocketLauncher,
 }
 
+/// Every kind of weapon in order of ids.
+pub const WEAPON_KINDS: [WeaponKind; 4] = [
+    WeaponKind::M4,
+    WeaponKind::Ak47,
+    WeaponKind::PlasmaRifle,
+    WeaponKind::RocketLauncher,
+];
+
 impl WeaponKind {
     pub fn id(self) -> u32 {
         match self {
@@ -70,6 +165,27 @@ impl WeaponKind {
             _ => Err(format!("unknown weapon kind {}", id))
         }
     }
+
+    /// Human-readable name of weapon, used in match summary.
+    pub fn name(self) -> &'static str {
+        match self {
+            WeaponKind::M4 => "M4",
+            WeaponKind::Ak47 => "AK-47",
+            WeaponKind::PlasmaRifle => "Plasma Rifle",
+            WeaponKind::RocketLauncher => "Rocket Launcher",
+        }
+    }
+
+    /// Place of weapon in ranking that decides whether picked up weapon is better than
+    /// current one, larger is better.
+    pub fn rank(self) -> u32 {
+        match self {
+            WeaponKind::M4 => 0,
+            WeaponKind::Ak47 => 1,
+            WeaponKind::PlasmaRifle => 2,
+            WeaponKind::RocketLauncher => 3,
+        }
+    }
 }
 
 pub struct Weapon {
@@ -82,17 +198,98 @@ pub struct Weapon {
     last_shot_time: f64,
     shot_position: Vec3,
     owner: Handle<Actor>,
-    ammo: u32,
+    /// Ammo store of owner, rounds outside of magazine are shared with other weapons of
+    /// owner that use the same ammo type. Not saved, owner links it again after load.
+    ammo_store: Rc<RefCell<AmmoStore>>,
+    /// Rounds in magazine, weapon has to be reloaded when magazine is empty.
+    magazine: u32,
+    /// Time left until reload ends, zero means weapon is not being reloaded.
+    reload_time_left: f32,
+    /// Rotation of model right after instantiation, animations are applied on top of it.
+    base_rotation: Quat,
+    /// Time since weapon was created, drives idle sway.
+    sway_time: f32,
+    /// Time left until inspect animation ends, zero means weapon is not being inspected.
+    inspect_time_left: f32,
+    /// Whether empty click was already played for current trigger pull, so holding trigger
+    /// of empty weapon clicks only once.
+    dry_fired: bool,
+    /// Ammo of alt fire launcher, always zero for other alt fire modes.
+    alt_ammo: u32,
+    last_alt_fire_time: f64,
+    /// Rounds of alt fire burst that are not fired yet.
+    burst_left: u32,
+    /// Time left until next round of burst.
+    burst_timer: f32,
+    /// Heat of weapon with heat instead of magazine, always zero for other weapons.
+    heat: f32,
+    /// Overheated weapon can't shoot until it cools down.
+    overheated: bool,
+    time_since_shot: f32,
+    /// Shots of current spray, picks step of recoil pattern.
+    spray_shot: u32,
+    view_model: ViewModel,
+    /// Whether weapon is in hands of owner, false while it is holstered or hidden.
+    drawn: bool,
+    /// Id of skin that is applied to model.
+    skin: &'static str,
     pub definition: &'static WeaponDefinition,
     pub sender: Option<Sender<Message>>,
 }
 
+/// Duration of inspect animation in seconds.
+const INSPECT_DURATION: f32 = 2.0;
+/// Amplitude of idle sway in local units of weapon pivot.
+const SWAY_AMPLITUDE: f32 = 0.004;
+/// Speed of idle sway in radians per second.
+const SWAY_SPEED: f32 = 1.6;
+/// Weapon that was not fired for this long cools at full rate, in seconds.
+const HEAT_COOLING_DELAY: f32 = 0.4;
+/// Part of cooling rate at which weapon cools while it is being fired.
+const FIRING_COOLING_FACTOR: f32 = 0.25;
+/// Overheated weapon can shoot again when its heat falls below this part of max heat.
+const OVERHEAT_RECOVERY: f32 = 0.3;
+/// Spray starts over from first step of recoil pattern when weapon was not fired for this
+/// long over its shoot interval, in seconds.
+const SPRAY_RESET_DELAY: f32 = 0.25;
+
+#[derive(Clone)]
 pub struct WeaponDefinition {
     pub model: &'static str,
     pub shot_sound: &'static str,
+    /// Sound of trigger pull when there is no ammo left.
+    pub dry_fire_sound: &'static str,
+    /// Ammo that weapon gives when it is picked up, rounds that don't fit into magazine go
+    /// to the ammo store of owner.
     pub ammo: u32,
+    pub ammo_type: AmmoType,
+    pub magazine_size: u32,
+    /// Duration of reload in seconds, or duration of loading of one round if weapon is
+    /// reloaded round by round.
+    pub reload_time: f32,
+    pub reload_style: ReloadStyle,
     pub projectile: ProjectileKind,
     pub shoot_interval: f64,
+    /// Maximum distance at which bots will shoot from this weapon.
+    pub bot_range: f32,
+    /// Distance to target at which bots prefer this weapon over others.
+    pub bot_preferred_range: f32,
+    /// Screen shake of owner on each shot, in [0; 1] range.
+    pub shot_shake: f32,
+    /// Crosshair that is shown while player holds this weapon.
+    pub crosshair: CrosshairProfile,
+    pub alt_fire: Option<AltFireDefinition>,
+    /// Heat of weapon, magazine and ammo of weapon with heat are not spent.
+    pub heat: Option<HeatDefinition>,
+    pub recoil: Option<RecoilDefinition>,
+    /// Magnifications of scope in ascending order, mouse wheel cycles them while player
+    /// aims. Weapons without scope have no levels and just use regular sights.
+    pub zoom_levels: Cow<'static, [f32]>,
+    /// Skeletal animations of first person model, weapon without them is animated by
+    /// procedural pose only.
+    pub view_model_animations: Option<ViewModelAnimations>,
+    /// Skin that weapon gets when loadout doesn't pick another one.
+    pub skin: &'static str,
 }
 
 impl Default for Weapon {
@@ -107,7 +304,24 @@ impl Default for Weapon {
             last_shot_time: 0.0,
             shot_position: Vec3::ZERO,
             owner: Handle::NONE,
-            ammo: 250,
+            ammo_store: Default::default(),
+            magazine: 0,
+            reload_time_left: 0.0,
+            base_rotation: Quat::IDENTITY,
+            sway_time: 0.0,
+            inspect_time_left: 0.0,
+            dry_fired: false,
+            alt_ammo: 0,
+            last_alt_fire_time: 0.0,
+            burst_left: 0,
+            burst_timer: 0.0,
+            heat: 0.0,
+            overheated: false,
+            time_since_shot: 0.0,
+            spray_shot: 0,
+            view_model: Default::default(),
+            drawn: false,
+            skin: DEFAULT_SKIN,
             definition: Self::get_definition(WeaponKind::M4),
             sender: None,
         }
@@ -131,22 +345,162 @@ impl Visit for Weapon {
         self.dest_offset.visit("DestOffset", visitor)?;
         self.last_shot_time.visit("LastShotTime", visitor)?;
         self.owner.visit("Owner", visitor)?;
-        self.ammo.visit("Ammo", visitor)?;
+        self.magazine.visit("Magazine", visitor)?;
+        self.reload_time_left.visit("ReloadTimeLeft", visitor)?;
+        self.base_rotation.visit("BaseRotation", visitor)?;
+        self.sway_time.visit("SwayTime", visitor)?;
+        self.inspect_time_left.visit("InspectTimeLeft", visitor)?;
+        self.alt_ammo.visit("AltAmmo", visitor)?;
+        self.last_alt_fire_time.visit("LastAltFireTime", visitor)?;
+        self.burst_left.visit("BurstLeft", visitor)?;
+        self.burst_timer.visit("BurstTimer", visitor)?;
+        self.heat.visit("Heat", visitor)?;
+        self.overheated.visit("Overheated", visitor)?;
+        self.time_since_shot.visit("TimeSinceShot", visitor)?;
+        self.spray_shot.visit("SprayShot", visitor)?;
+        self.view_model.visit("ViewModel", visitor)?;
+        self.drawn.visit("Drawn", visitor)?;
+
+        // Skin is saved by id, skin that no longer exists is replaced with default one.
+        let mut skin = self.skin.to_owned();
+        skin.visit("Skin", visitor)?;
+        if visitor.is_reading() {
+            self.skin = skin::find(&skin).map_or(DEFAULT_SKIN, |skin| skin.id);
+        }
 
         visitor.leave_region()
     }
 }
 
+impl WeaponDefinition {
+    /// Changes value of a field by its name in definitions file.
+    pub fn set_value(&mut self, field: &str, value: &str) -> Result<(), String> {
+        match field {
+            "ammo" => self.ammo = parse_u32(value)?,
+            "ammo_type" => self.ammo_type = AmmoType::from_name(value)?,
+            "magazine_size" => self.magazine_size = parse_u32(value)?.max(1),
+            "reload_time" => self.reload_time = parse_f32(value, 0.1, 10.0)?,
+            "reload_style" => self.reload_style = match value {
+                "magazine" => ReloadStyle::Magazine,
+                "per_round" => ReloadStyle::PerRound,
+                _ => return Err(format!("unknown reload style {}, expected magazine or per_round", value)),
+            },
+            "shoot_interval" => self.shoot_interval = f64::from(parse_f32(value, 0.01, 10.0)?),
+            "bot_range" => self.bot_range = parse_f32(value, 1.0, 200.0)?,
+            "bot_preferred_range" => self.bot_preferred_range = parse_f32(value, 1.0, 200.0)?,
+            "shot_shake" => self.shot_shake = parse_f32(value, 0.0, 1.0)?,
+            "alt_fire_interval" => match self.alt_fire.as_mut() {
+                Some(alt_fire) => alt_fire.interval = f64::from(parse_f32(value, 0.05, 10.0)?),
+                None => return Err("weapon has no alt fire".to_owned()),
+            },
+            "heat_per_shot" | "max_heat" | "cooling_rate" => match self.heat.as_mut() {
+                Some(heat) => {
+                    let value = parse_f32(value, 0.1, 1000.0)?;
+                    match field {
+                        "heat_per_shot" => heat.heat_per_shot = value,
+                        "max_heat" => heat.max_heat = value,
+                        _ => heat.cooling_rate = value,
+                    }
+                }
+                None => return Err("weapon has no heat".to_owned()),
+            },
+            "recoil_pattern" => {
+                let pattern = parse_recoil_pattern(value)?;
+                match self.recoil.as_mut() {
+                    Some(recoil) => recoil.pattern = Cow::Owned(pattern),
+                    None => self.recoil = Some(RecoilDefinition {
+                        pattern: Cow::Owned(pattern),
+                        recovery_speed: 10.0,
+                        recovery: 1.0,
+                    }),
+                }
+            }
+            "recoil_recovery_speed" | "recoil_recovery" => match self.recoil.as_mut() {
+                Some(recoil) => match field {
+                    "recoil_recovery_speed" => recoil.recovery_speed = parse_f32(value, 0.0, 100.0)?,
+                    _ => recoil.recovery = parse_f32(value, 0.0, 1.0)?,
+                },
+                None => return Err("weapon has no recoil".to_owned()),
+            },
+            "skin" => match skin::find(value) {
+                Some(skin) => self.skin = skin.id,
+                None => return Err(format!("unknown weapon skin {}", value)),
+            },
+            "zoom_levels" => {
+                let mut levels = value.split_whitespace()
+                    .map(|level| parse_f32(level, 1.0, 20.0))
+                    .collect::<Result<Vec<_>, _>>()?;
+                levels.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+                self.zoom_levels = Cow::Owned(levels);
+            }
+            _ => return Err(format!("unknown weapon field {}", field)),
+        }
+        Ok(())
+    }
+}
+
+/// Parses recoil pattern written as comma separated steps, every step is kick up and kick
+/// right in degrees, for example `0.5 0, 0.6 0.1, 0.4 -0.2`.
+fn parse_recoil_pattern(value: &str) -> Result<Vec<(f32, f32)>, String> {
+    value.split(',')
+        .map(|step| {
+            let mut parts = step.split_whitespace();
+            match (parts.next(), parts.next(), parts.next()) {
+                (Some(up), Some(right), None) => Ok((parse_f32(up, -10.0, 10.0)?, parse_f32(right, -10.0, 10.0)?)),
+                _ => Err(format!("expected recoil step `up right`, got {}", step.trim())),
+            }
+        })
+        .collect()
+}
+
 impl Weapon {
+    /// Returns definition of weapon, definitions file may override built-in one.
     pub fn get_definition(kind: WeaponKind) -> &'static WeaponDefinition {
+        definitions::weapon(kind).unwrap_or_else(|| Self::builtin_definition(kind))
+    }
+
+    pub fn builtin_definition(kind: WeaponKind) -> &'static WeaponDefinition {
         match kind {
             WeaponKind::M4 => {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/m4.FBX",
                     shot_sound: "data/sounds/m4_shot.ogg",
+                    dry_fire_sound: "data/sounds/dry_fire_rifle.wav",
                     ammo: 200,
+                    ammo_type: AmmoType::Rifle556,
+                    magazine_size: 30,
+                    reload_time: 1.8,
+                    reload_style: ReloadStyle::Magazine,
                     projectile: ProjectileKind::Bullet,
                     shoot_interval: 0.15,
+                    bot_range: 30.0,
+                    bot_preferred_range: 22.0,
+                    shot_shake: 0.0,
+                    crosshair: CrosshairProfile {
+                        style: CrosshairStyle::Cross,
+                        size: 1.0,
+                    },
+                    alt_fire: Some(AltFireDefinition {
+                        mode: AltFire::Launcher {
+                            projectile: ProjectileKind::Grenade,
+                            ammo: 3,
+                            sound: "data/sounds/grenade_launcher_fire.ogg",
+                        },
+                        interval: 1.2,
+                    }),
+                    heat: None,
+                    recoil: Some(RecoilDefinition {
+                        pattern: Cow::Borrowed(&[
+                            (0.5, 0.0), (0.6, 0.05), (0.7, 0.1), (0.7, 0.1), (0.6, -0.1),
+                            (0.4, -0.3), (0.3, -0.35), (0.3, 0.3), (0.3, 0.35), (0.3, 0.0),
+                        ]),
+                        recovery_speed: 14.0,
+                        recovery: 1.0,
+                    }),
+                    // Scope of M4 is the only one in the game, it makes M4 the long range rifle.
+                    zoom_levels: Cow::Borrowed(&[2.0, 4.0, 8.0]),
+                    view_model_animations: None,
+                    skin: DEFAULT_SKIN,
                 };
                 &DEFINITION
             }
@@ -154,9 +508,44 @@ impl Weapon {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/ak47.FBX",
                     shot_sound: "data/sounds/ak47.ogg",
+                    dry_fire_sound: "data/sounds/dry_fire_rifle.wav",
                     ammo: 200,
+                    ammo_type: AmmoType::Rifle762,
+                    magazine_size: 30,
+                    reload_time: 2.0,
+                    reload_style: ReloadStyle::Magazine,
                     projectile: ProjectileKind::Bullet,
                     shoot_interval: 0.15,
+                    bot_range: 30.0,
+                    bot_preferred_range: 14.0,
+                    shot_shake: 0.0,
+                    crosshair: CrosshairProfile {
+                        style: CrosshairStyle::Cross,
+                        size: 1.0,
+                    },
+                    alt_fire: Some(AltFireDefinition {
+                        mode: AltFire::Bash {
+                            damage: 35.0,
+                            range: 1.6,
+                            sound: "data/sounds/bullet_impact_body.ogg",
+                        },
+                        interval: 0.8,
+                    }),
+                    heat: None,
+                    // Climbs hard at first and then throws aim sideways, aim returns only
+                    // partially so long sprays have to be pulled down.
+                    recoil: Some(RecoilDefinition {
+                        pattern: Cow::Borrowed(&[
+                            (0.8, 0.0), (0.9, 0.0), (1.0, 0.1), (1.0, 0.15), (0.8, 0.3),
+                            (0.5, 0.6), (0.3, 0.7), (0.2, -0.8), (0.2, -0.8), (0.2, 0.7),
+                            (0.3, 0.0),
+                        ]),
+                        recovery_speed: 10.0,
+                        recovery: 0.7,
+                    }),
+                    zoom_levels: Cow::Borrowed(&[]),
+                    view_model_animations: None,
+                    skin: DEFAULT_SKIN,
                 };
                 &DEFINITION
             }
@@ -164,9 +553,42 @@ impl Weapon {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/plasma_rifle.FBX",
                     shot_sound: "data/sounds/plasma_shot.ogg",
+                    dry_fire_sound: "data/sounds/dry_fire_plasma.wav",
                     ammo: 100,
+                    ammo_type: AmmoType::Plasma,
+                    magazine_size: 25,
+                    reload_time: 2.2,
+                    reload_style: ReloadStyle::Magazine,
                     projectile: ProjectileKind::Plasma,
                     shoot_interval: 0.25,
+                    bot_range: 20.0,
+                    bot_preferred_range: 7.0,
+                    shot_shake: 0.1,
+                    crosshair: CrosshairProfile {
+                        style: CrosshairStyle::Circle,
+                        size: 1.5,
+                    },
+                    alt_fire: Some(AltFireDefinition {
+                        mode: AltFire::Burst {
+                            shots: 3,
+                            interval: 0.07,
+                        },
+                        interval: 0.7,
+                    }),
+                    heat: Some(HeatDefinition {
+                        heat_per_shot: 12.0,
+                        max_heat: 100.0,
+                        cooling_rate: 30.0,
+                        overheat_sound: "data/sounds/plasma_overheat.ogg",
+                    }),
+                    recoil: Some(RecoilDefinition {
+                        pattern: Cow::Borrowed(&[(0.4, 0.0)]),
+                        recovery_speed: 20.0,
+                        recovery: 1.0,
+                    }),
+                    zoom_levels: Cow::Borrowed(&[]),
+                    view_model_animations: None,
+                    skin: DEFAULT_SKIN,
                 };
                 &DEFINITION
             }
@@ -174,23 +596,45 @@ impl Weapon {
                 static DEFINITION: WeaponDefinition = WeaponDefinition {
                     model: "data/models/Rpg7.FBX",
                     shot_sound: "data/sounds/grenade_launcher_fire.ogg",
+                    dry_fire_sound: "data/sounds/dry_fire_launcher.wav",
                     ammo: 100,
+                    ammo_type: AmmoType::Rocket,
+                    magazine_size: 4,
+                    reload_time: 0.7,
+                    reload_style: ReloadStyle::PerRound,
                     projectile: ProjectileKind::Rocket,
                     shoot_interval: 1.5,
+                    bot_range: 40.0,
+                    bot_preferred_range: 18.0,
+                    shot_shake: 0.4,
+                    crosshair: CrosshairProfile {
+                        style: CrosshairStyle::Dot,
+                        size: 1.0,
+                    },
+                    alt_fire: None,
+                    heat: None,
+                    recoil: Some(RecoilDefinition {
+                        pattern: Cow::Borrowed(&[(3.0, 0.0)]),
+                        recovery_speed: 8.0,
+                        recovery: 1.0,
+                    }),
+                    zoom_levels: Cow::Borrowed(&[]),
+                    view_model_animations: None,
+                    skin: DEFAULT_SKIN,
                 };
                 &DEFINITION
             }
         }
     }
 
-    pub fn new(kind: WeaponKind, resource_manager: &mut ResourceManager, scene: &mut Scene, sender: Sender<Message>) -> Weapon {
+    /// Creates empty weapon of given kind, skin overrides default skin of weapon definition.
+    /// Owner links weapon to its ammo store and loads it, see `load`.
+    pub fn new(kind: WeaponKind, skin: Option<&str>, resource_manager: &mut ResourceManager, scene: &mut Scene, sender: Sender<Message>) -> Weapon {
         let definition = Self::get_definition(kind);
 
-        let model = resource_manager.request_model(Path::new(definition.model))
-            .unwrap()
-            .lock()
-            .unwrap()
-            .instantiate_geometry(scene);
+        let model = instantiate_model(resource_manager, definition.model, scene);
+
+        let skin = skin::apply(&mut scene.graph, model, skin.unwrap_or(definition.skin), resource_manager);
 
         let laser_dot = scene.graph.add_node(Node::Light(
             LightBuilder::new(LightKind::Point(PointLight::new(0.5)), BaseBuilder::new())
@@ -201,6 +645,10 @@ impl Weapon {
 
         let shot_point = scene.graph.find_by_name(model, "Weapon:ShotPoint");
 
+        let base_rotation = scene.graph[model].local_transform().rotation();
+
+        let view_model = ViewModel::new(resource_manager, definition.view_model_animations.as_ref(), model, scene);
+
         if shot_point.is_none() {
             println!("Shot point not found!");
         }
@@ -211,14 +659,36 @@ impl Weapon {
             model,
             shot_point,
             definition,
-            ammo: definition.ammo,
+            alt_ammo: Self::alt_fire_ammo(definition),
+            base_rotation,
+            view_model,
+            skin,
             sender: Some(sender),
             ..Default::default()
         }
     }
 
-    pub fn set_visibility(&self, visibility: bool, graph: &mut Graph) {
-        graph[self.model].set_visibility(visibility);
+    /// Shown weapon is drawn. Weapon that is hidden while it is in hands of owner is holstered
+    /// first and `update` hides it when holster ends, other weapons are hidden at once.
+    pub fn set_visibility(&mut self, visibility: bool, graph: &mut Graph) {
+        if visibility {
+            self.drawn = true;
+            self.view_model.set_state(ViewModelState::Draw, DRAW_DURATION);
+            graph[self.model].set_visibility(true);
+        } else {
+            // Weapon is hidden when owner switches to other weapon, inspection, burst and
+            // reload are interrupted then.
+            self.burst_left = 0;
+            self.stop_inspect();
+            self.release_trigger();
+            self.reload_time_left = 0.0;
+            if self.drawn {
+                self.drawn = false;
+                self.view_model.set_state(ViewModelState::Holster, HOLSTER_DURATION);
+            } else {
+                graph[self.model].set_visibility(false);
+            }
+        }
         graph[self.laser_dot].set_visibility(visibility);
     }
 
@@ -226,14 +696,197 @@ impl Weapon {
         self.model
     }
 
-    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer) {
+    pub fn skin(&self) -> &'static str {
+        self.skin
+    }
+
+    /// Applies skin to model once again, materials of meshes are not kept in saved games.
+    pub fn reapply_skin(&mut self, graph: &mut Graph, resource_manager: &mut ResourceManager) {
+        self.skin = skin::apply(graph, self.model, self.skin, resource_manager);
+    }
+
+    /// Returns true if a round of alt fire burst was fired, level creates its projectile.
+    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, dt: f32) -> bool {
         self.offset.follow(&self.dest_offset, 0.2);
 
+        let mut burst_shot = false;
+        if self.burst_left > 0 {
+            self.burst_timer -= dt;
+            if self.burst_timer <= 0.0 {
+                if let AltFire::Burst { interval, .. } = self.definition.alt_fire.as_ref().unwrap().mode {
+                    self.burst_timer = interval;
+                }
+                if self.magazine > 0 && !self.overheated {
+                    self.burst_left -= 1;
+                    self.offset = Vec3::new(0.0, 0.0, -0.05);
+                    self.view_model.fire(self.burst_timer);
+                    self.play_sound(scene, self.definition.shot_sound, 1.0);
+                    self.spend_round(scene);
+                    burst_shot = true;
+                } else {
+                    self.burst_left = 0;
+                }
+            }
+        }
+
+        self.time_since_shot += dt;
+        if self.time_since_shot > self.definition.shoot_interval as f32 + SPRAY_RESET_DELAY {
+            self.spray_shot = 0;
+        }
+
+        if let Some(heat) = self.definition.heat.as_ref() {
+            let cooling_rate = if self.time_since_shot >= HEAT_COOLING_DELAY {
+                heat.cooling_rate
+            } else {
+                heat.cooling_rate * FIRING_COOLING_FACTOR
+            };
+            self.heat = (self.heat - cooling_rate * dt).max(0.0);
+            if self.overheated && self.heat <= heat.max_heat * OVERHEAT_RECOVERY {
+                self.overheated = false;
+            }
+        }
+
         self.update_laser_sight(&mut scene.graph, &scene.physics, actors);
 
+        self.sway_time += dt;
+        let sway = Vec3::new(
+            (self.sway_time * SWAY_SPEED).sin() * SWAY_AMPLITUDE,
+            (self.sway_time * SWAY_SPEED * 2.0).sin() * SWAY_AMPLITUDE * 0.5,
+            0.0);
+
+        // Inspect animation turns weapon to show its side and then returns it back, weight
+        // goes smoothly from zero to one and back to zero.
+        let mut inspect_offset = Vec3::ZERO;
+        let mut rotation = self.base_rotation;
+        if self.is_inspecting() {
+            self.inspect_time_left = (self.inspect_time_left - dt).max(0.0);
+            let t = 1.0 - self.inspect_time_left / INSPECT_DURATION;
+            let weight = (t * std::f32::consts::PI).sin();
+            inspect_offset = Vec3::new(-0.05, 0.03, 0.05).scale(weight);
+            rotation = Quat::from_axis_angle(Vec3::UP, (50.0 * weight).to_radians()) *
+                Quat::from_axis_angle(Vec3::LOOK, (25.0 * weight).to_radians()) *
+                self.base_rotation;
+        }
+
+        let reload_progress = self.update_reload(dt);
+
+        // Overheated weapon is held lowered until it cools down.
+        let overheat_offset = if self.overheated {
+            Vec3::new(0.0, -0.05, 0.0)
+        } else {
+            Vec3::ZERO
+        };
+
+        self.update_view_model_state(&mut scene.graph);
+        let pose = self.view_model.update(scene, dt, reload_progress);
+
         let node = &mut scene.graph[self.model];
-        node.local_transform_mut().set_position(self.offset);
+        let transform = node.local_transform_mut();
+        transform.set_position(self.offset + sway + inspect_offset + pose.offset + overheat_offset);
+        transform.set_rotation(Quat::from_axis_angle(Vec3::RIGHT, pose.pitch.to_radians()) * rotation);
         self.shot_position = node.global_position();
+
+        burst_shot
+    }
+
+    /// Moves view-model to next state when current one is over, states of actions that were
+    /// interrupted are left right away.
+    /// Advances reload and puts rounds into magazine when reload or loading of a round ends.
+    /// Returns progress of reload or of the round that is being loaded, zero if weapon is not
+    /// being reloaded.
+    fn update_reload(&mut self, dt: f32) -> f32 {
+        if self.reload_time_left <= 0.0 {
+            return 0.0;
+        }
+        self.reload_time_left = (self.reload_time_left - dt).max(0.0);
+        let reload_progress = 1.0 - self.reload_time_left / self.definition.reload_time;
+        if self.reload_time_left <= 0.0 {
+            let space = self.definition.magazine_size.saturating_sub(self.magazine);
+            let mut ammo_store = self.ammo_store.borrow_mut();
+            match self.definition.reload_style {
+                ReloadStyle::Magazine => self.magazine += ammo_store.take(self.definition.ammo_type, space),
+                ReloadStyle::PerRound => {
+                    self.magazine += ammo_store.take(self.definition.ammo_type, space.min(1));
+                    if self.magazine < self.definition.magazine_size && ammo_store.get(self.definition.ammo_type) > 0 {
+                        self.reload_time_left = self.definition.reload_time;
+                    }
+                }
+            }
+        }
+        reload_progress
+    }
+
+    fn update_view_model_state(&mut self, graph: &mut Graph) {
+        let finished = self.view_model.is_finished();
+        match self.view_model.state() {
+            ViewModelState::Holster => {
+                if finished {
+                    graph[self.model].set_visibility(false);
+                }
+            }
+            ViewModelState::Draw | ViewModelState::Fire => {
+                if self.is_reloading() {
+                    self.view_model.set_state(ViewModelState::Reload, 0.0);
+                } else if finished {
+                    self.view_model.set_state(ViewModelState::Idle, 0.0);
+                }
+            }
+            ViewModelState::Reload => {
+                if !self.is_reloading() {
+                    self.view_model.set_state(ViewModelState::Idle, 0.0);
+                }
+            }
+            ViewModelState::Idle => {
+                if self.is_reloading() {
+                    self.view_model.set_state(ViewModelState::Reload, 0.0);
+                }
+            }
+        }
+    }
+
+    /// Starts inspect animation, does nothing if weapon is already being inspected or
+    /// reloaded.
+    pub fn inspect(&mut self) {
+        if !self.is_inspecting() && !self.is_reloading() {
+            self.inspect_time_left = INSPECT_DURATION;
+        }
+    }
+
+    pub fn stop_inspect(&mut self) {
+        self.inspect_time_left = 0.0;
+    }
+
+    pub fn is_inspecting(&self) -> bool {
+        self.inspect_time_left > 0.0
+    }
+
+    /// Starts reload, does nothing if weapon is already being reloaded, magazine is full or
+    /// there is no ammo to put in it.
+    pub fn reload(&mut self) {
+        if !self.is_reloading() && self.magazine < self.definition.magazine_size && self.reserve() > 0 {
+            self.stop_inspect();
+            self.burst_left = 0;
+            self.reload_time_left = self.definition.reload_time;
+        }
+    }
+
+    pub fn is_reloading(&self) -> bool {
+        self.reload_time_left > 0.0
+    }
+
+    /// Returns reload style of weapon while it is being reloaded, None otherwise.
+    pub fn active_reload(&self) -> Option<ReloadStyle> {
+        if self.is_reloading() {
+            Some(self.definition.reload_style)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if firing would stop current reload, it is possible when rounds are
+    /// loaded one by one and at least one of them is in magazine already.
+    fn can_interrupt_reload(&self) -> bool {
+        self.definition.reload_style == ReloadStyle::PerRound && self.magazine > 0
     }
 
     pub fn get_shot_position(&self, graph: &Graph) -> Vec3 {
@@ -257,8 +910,31 @@ impl Weapon {
         graph[self.model].global_transform().basis()
     }
 
+    /// Adds rounds to ammo store of owner, they are shared with other weapons of the same
+    /// ammo type.
     pub fn add_ammo(&mut self, amount: u32) {
-        self.ammo += amount;
+        self.ammo_store.borrow_mut().add(self.definition.ammo_type, amount);
+    }
+
+    /// Loads given amount of rounds: magazine is filled first and the rest goes to ammo store.
+    pub fn load(&mut self, amount: u32) {
+        let loaded = self.definition.magazine_size.saturating_sub(self.magazine).min(amount);
+        self.magazine += loaded;
+        self.add_ammo(amount - loaded);
+    }
+
+    pub fn ammo_type(&self) -> AmmoType {
+        self.definition.ammo_type
+    }
+
+    /// Links weapon to ammo store of its owner.
+    pub fn set_ammo_store(&mut self, ammo_store: Rc<RefCell<AmmoStore>>) {
+        self.ammo_store = ammo_store;
+    }
+
+    /// Rounds of ammo type of weapon that are left outside of magazines.
+    pub fn reserve(&self) -> u32 {
+        self.ammo_store.borrow().get(self.definition.ammo_type)
     }
 
     fn update_laser_sight(&self, graph: &mut Graph, physics: &Physics, actors: &ActorContainer) {
@@ -290,8 +966,68 @@ impl Weapon {
             .set_position(laser_dot_position);
     }
 
+    /// Total amount of ammo that weapon can fire, rounds in magazine and shared reserve.
     pub fn ammo(&self) -> u32 {
-        self.ammo
+        self.magazine + self.reserve()
+    }
+
+    fn alt_fire_ammo(definition: &WeaponDefinition) -> u32 {
+        match definition.alt_fire.as_ref().map(|alt_fire| alt_fire.mode) {
+            Some(AltFire::Launcher { ammo, .. }) => ammo,
+            _ => 0,
+        }
+    }
+
+    /// Ammo of alt fire launcher, None if alt fire of weapon needs no separate ammo.
+    pub fn alt_ammo(&self) -> Option<u32> {
+        match self.definition.alt_fire.as_ref().map(|alt_fire| alt_fire.mode) {
+            Some(AltFire::Launcher { .. }) => Some(self.alt_ammo),
+            _ => None,
+        }
+    }
+
+    /// Fills alt fire launcher back to full, it happens when owner picks up same weapon.
+    pub fn refill_alt_ammo(&mut self) {
+        self.alt_ammo = Self::alt_fire_ammo(self.definition);
+    }
+
+    pub fn magazine(&self) -> u32 {
+        self.magazine
+    }
+
+    /// Returns heat as part of max heat, None if weapon has magazine instead of heat.
+    pub fn heat(&self) -> Option<f32> {
+        self.definition.heat.as_ref().map(|heat| self.heat / heat.max_heat)
+    }
+
+    pub fn is_overheated(&self) -> bool {
+        self.overheated
+    }
+
+    /// Returns kick of next shot of spray in degrees (up, right), None if weapon has no
+    /// recoil. Must be called once for every shot.
+    pub fn next_recoil(&mut self) -> Option<(f32, f32)> {
+        let recoil = self.definition.recoil.as_ref()?;
+        let step = (self.spray_shot as usize).min(recoil.pattern.len().checked_sub(1)?);
+        self.spray_shot += 1;
+        Some(recoil.pattern[step])
+    }
+
+    /// Spends round from magazine or heats weapon up if it has heat instead of magazine.
+    fn spend_round(&mut self, scene: &Scene) {
+        self.time_since_shot = 0.0;
+        match self.definition.heat.as_ref() {
+            Some(heat) => {
+                self.heat += heat.heat_per_shot;
+                if self.heat >= heat.max_heat {
+                    self.heat = heat.max_heat;
+                    self.overheated = true;
+                    self.burst_left = 0;
+                    self.play_sound(scene, heat.overheat_sound, 1.0);
+                }
+            }
+            None => self.magazine -= 1,
+        }
     }
 
     pub fn owner(&self) -> Handle<Actor> {
@@ -302,32 +1038,109 @@ impl Weapon {
         self.owner = owner;
     }
 
-    pub fn try_shoot(&mut self, scene: &mut Scene, time: GameTime) -> bool {
-        if self.ammo != 0 && time.elapsed - self.last_shot_time >= self.definition.shoot_interval {
-            self.ammo -= 1;
+    /// Returns true if `try_shoot` would not be ignored at given time.
+    pub fn is_ready_to_shoot(&self, elapsed: f64) -> bool {
+        (!self.is_reloading() || self.can_interrupt_reload()) && !self.overheated && elapsed - self.last_shot_time >= self.definition.shoot_interval
+    }
+
+    /// Returns true if weapon has alt fire and its interval has passed.
+    pub fn is_alt_fire_ready(&self, elapsed: f64) -> bool {
+        self.definition.alt_fire.as_ref().map_or(false, |alt_fire| {
+            !self.is_reloading() && self.burst_left == 0 && elapsed - self.last_alt_fire_time >= alt_fire.interval
+        })
+    }
+
+    /// Shoots if weapon is ready. Empty magazine is reloaded automatically if `auto_reload`
+    /// is set, otherwise weapon just clicks until owner reloads it. Weapon that is reloaded
+    /// round by round stops reloading and fires rounds that are loaded already.
+    pub fn try_shoot(&mut self, scene: &mut Scene, time: GameTime, auto_reload: bool) -> ShotResult {
+        if self.is_reloading() && self.can_interrupt_reload() {
+            self.reload_time_left = 0.0;
+        }
+        if self.is_reloading() || self.overheated {
+            ShotResult::Idle
+        } else if self.magazine == 0 && self.reserve() > 0 && auto_reload {
+            self.reload();
+            ShotResult::Idle
+        } else if self.magazine == 0 {
+            if self.dry_fired {
+                return ShotResult::Idle;
+            }
+            self.dry_fired = true;
+            self.stop_inspect();
+            self.play_sound(scene, self.definition.dry_fire_sound, 0.6);
+            ShotResult::DryFire
+        } else if time.elapsed - self.last_shot_time >= self.definition.shoot_interval {
+            self.stop_inspect();
 
             self.offset = Vec3::new(0.0, 0.0, -0.05);
             self.last_shot_time = time.elapsed;
+            self.view_model.fire(self.definition.shoot_interval as f32);
+
+            self.play_sound(scene, self.definition.shot_sound, 1.0);
+            self.spend_round(scene);
 
-            let position = self.get_shot_position(&scene.graph);
+            ShotResult::Fired
+        } else {
+            ShotResult::Idle
+        }
+    }
 
-            if let Some(sender) = self.sender.as_ref() {
-                sender.send(Message::PlaySound {
-                    path: PathBuf::from(self.definition.shot_sound),
-                    position,
-                    gain: 1.0,
-                    rolloff_factor: 5.0,
-                    radius: 3.0,
-                }).unwrap();
+    /// Uses alt fire if weapon has one and it is ready. Returns mode of alt fire that was
+    /// used, level does the rest: creates projectile of launcher or damages actor hit by
+    /// bash. Rounds of burst are fired later by `update`.
+    pub fn try_alt_fire(&mut self, scene: &Scene, time: GameTime) -> Option<AltFire> {
+        let alt_fire = self.definition.alt_fire.as_ref()?;
+        if self.is_reloading() || self.burst_left > 0 || time.elapsed - self.last_alt_fire_time < alt_fire.interval {
+            return None;
+        }
+        match alt_fire.mode {
+            AltFire::Launcher { sound, .. } => {
+                if self.alt_ammo == 0 {
+                    return None;
+                }
+                self.alt_ammo -= 1;
+                self.offset = Vec3::new(0.0, 0.0, -0.08);
+                self.play_sound(scene, sound, 1.0);
+            }
+            AltFire::Burst { shots, .. } => {
+                if self.magazine == 0 || self.overheated {
+                    return None;
+                }
+                self.burst_left = shots;
+                self.burst_timer = 0.0;
             }
+            AltFire::Bash { sound, .. } => {
+                // Weapon is thrust forward and returns back by itself.
+                self.offset = Vec3::new(0.0, 0.0, 0.15);
+                self.play_sound(scene, sound, 0.8);
+            }
+        }
+        self.stop_inspect();
+        self.last_alt_fire_time = time.elapsed;
+        Some(alt_fire.mode)
+    }
 
-            true
-        } else {
-            false
+    /// Must be called when owner releases trigger, next pull of empty weapon will click again.
+    pub fn release_trigger(&mut self) {
+        self.dry_fired = false;
+    }
+
+    fn play_sound(&self, scene: &Scene, path: &str, gain: f32) {
+        if let Some(sender) = self.sender.as_ref() {
+            sender.send(Message::PlaySound {
+                path: PathBuf::from(path),
+                position: self.get_shot_position(&scene.graph),
+                gain,
+                rolloff_factor: 5.0,
+                radius: 3.0,
+                priority: SoundPriority::High,
+            }).unwrap();
         }
     }
 
     pub fn clean_up(&mut self, scene: &mut Scene) {
+        self.view_model.clean_up(scene);
         scene.graph.remove_node(self.model);
         scene.graph.remove_node(self.laser_dot);
     }
@@ -361,10 +1174,15 @@ impl WeaponContainer {
         self.pool.iter_mut()
     }
 
-    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer) {
-        for weapon in self.pool.iter_mut() {
-            weapon.update(scene, actors)
+    /// Returns weapons that fired a round of alt fire burst.
+    pub fn update(&mut self, scene: &mut Scene, actors: &ActorContainer, dt: f32) -> Vec<Handle<Weapon>> {
+        let mut burst_shots = Vec::new();
+        for (handle, weapon) in self.pool.pair_iter_mut() {
+            if weapon.update(scene, actors, dt) {
+                burst_shots.push(handle);
+            }
         }
+        burst_shots
     }
 }
 
@@ -390,4 +1208,64 @@ impl Visit for WeaponContainer {
 
         visitor.leave_region()
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    fn weapon_with_reserve(kind: WeaponKind, reserve: u32) -> Weapon {
+        let mut weapon = Weapon {
+            kind,
+            definition: Weapon::get_definition(kind),
+            ..Default::default()
+        };
+        weapon.add_ammo(reserve);
+        weapon
+    }
+
+    fn time(elapsed: f64) -> GameTime {
+        GameTime {
+            clock: Instant::now(),
+            elapsed,
+            delta: 1.0 / 60.0,
+        }
+    }
+
+    #[test]
+    fn rounds_loaded_one_by_one_can_be_fired_mid_reload() {
+        let mut weapon = weapon_with_reserve(WeaponKind::RocketLauncher, 10);
+        assert_eq!(weapon.definition.reload_style, ReloadStyle::PerRound);
+        assert!(weapon.definition.magazine_size > 2);
+
+        weapon.reload();
+        let reload_time = weapon.definition.reload_time;
+        weapon.update_reload(reload_time);
+        weapon.update_reload(reload_time);
+        assert_eq!(weapon.magazine(), 2);
+        assert_eq!(weapon.active_reload(), Some(ReloadStyle::PerRound));
+
+        // Firing stops reload and spends one of loaded rounds.
+        let mut scene = Scene::new();
+        assert_eq!(weapon.try_shoot(&mut scene, time(10.0), false), ShotResult::Fired);
+        assert!(!weapon.is_reloading());
+        assert_eq!(weapon.magazine(), 1);
+        assert_eq!(weapon.reserve(), 8);
+    }
+
+    #[test]
+    fn magazine_reload_can_not_be_interrupted() {
+        let mut weapon = weapon_with_reserve(WeaponKind::M4, 100);
+        assert_eq!(weapon.definition.reload_style, ReloadStyle::Magazine);
+
+        weapon.reload();
+        weapon.update_reload(weapon.definition.reload_time * 0.5);
+        let mut scene = Scene::new();
+        assert_eq!(weapon.try_shoot(&mut scene, time(10.0), false), ShotResult::Idle);
+        assert!(weapon.is_reloading());
+        assert_eq!(weapon.magazine(), 0);
+
+        weapon.update_reload(weapon.definition.reload_time);
+        assert_eq!(weapon.magazine(), weapon.definition.magazine_size);
+    }
+}